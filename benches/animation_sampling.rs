@@ -0,0 +1,110 @@
+//! Compares sampling a sequence's animations on the calling thread with sampling them on
+//! rayon's pool, to place PARALLEL_SAMPLING_MIN_ANIMATIONS. Run with
+//! `cargo bench --bench animation_sampling`.
+
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rayon::prelude::*;
+use stunts_engine::animations::{
+    AnimationData, AnimationPlan, AnimationProperty, EasingType, KeyframeValue, ObjectType,
+    Sequence, UIKeyframe,
+};
+
+const ANIMATION_COUNTS: [usize; 5] = [16, 64, 256, 1024, 4096];
+const KEYFRAMES_PER_PROPERTY: u64 = 8;
+const DURATION_MS: u64 = 5000;
+
+fn property(name: &str, value: impl Fn(i32) -> KeyframeValue) -> AnimationProperty {
+    AnimationProperty {
+        name: name.to_string(),
+        property_path: name.to_lowercase(),
+        keyframes: (0..KEYFRAMES_PER_PROPERTY)
+            .map(|i| UIKeyframe {
+                id: format!("{}-{}", name, i),
+                time: Duration::from_millis(i * DURATION_MS / (KEYFRAMES_PER_PROPERTY - 1)),
+                value: value(i as i32 * 10),
+                easing: EasingType::EaseInOut,
+                ..UIKeyframe::default()
+            })
+            .collect(),
+        ..AnimationProperty::default()
+    }
+}
+
+/// A sequence of objects each moving, turning, scaling and fading
+fn sequence(animation_count: usize) -> Sequence {
+    Sequence {
+        id: "bench".to_string(),
+        name: "Bench".to_string(),
+        background_fill: None,
+        duration_ms: DURATION_MS as i32,
+        active_polygons: Vec::new(),
+        polygon_motion_paths: (0..animation_count)
+            .map(|i| AnimationData {
+                id: format!("animation-{}", i),
+                object_type: ObjectType::Polygon,
+                polygon_id: format!("object-{}", i),
+                duration: Duration::from_millis(DURATION_MS),
+                properties: vec![
+                    property("Position", |v| KeyframeValue::Position([v, v * 2])),
+                    property("Rotation", KeyframeValue::Rotation),
+                    property("Scale", |v| KeyframeValue::Scale(100 + v)),
+                    property("Opacity", |v| KeyframeValue::Opacity(100 - v)),
+                ],
+                ..AnimationData::default()
+            })
+            .collect(),
+        active_text_items: Vec::new(),
+        active_image_items: Vec::new(),
+        active_video_items: Vec::new(),
+        tags: Vec::new(),
+        outlined_text_items: Vec::new(),
+        active_groups: Vec::new(),
+        active_particle_emitters: Vec::new(),
+        camera_animation: None,
+        active_callouts: Vec::new(),
+        active_spotlights: Vec::new(),
+        caption_tracks: Vec::new(),
+        constraints: Vec::new(),
+    }
+}
+
+fn sampling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sample_animations");
+
+    for animation_count in ANIMATION_COUNTS {
+        let plan = AnimationPlan::compile(&sequence(animation_count), |_, _| Some(0));
+        // mid-way through, as a playing step samples them
+        let animations: Vec<(usize, Duration)> = (0..animation_count)
+            .map(|i| (i, Duration::from_millis(1234 + i as u64 % 100)))
+            .collect();
+        let sample = |(index, time): &(usize, Duration)| -> usize {
+            plan.animations[*index]
+                .sample(*time)
+                .filter(|sample| sample.is_some())
+                .count()
+        };
+
+        group.bench_with_input(
+            BenchmarkId::new("sequential", animation_count),
+            &animations,
+            |b, animations| b.iter(|| animations.iter().map(sample).collect::<Vec<_>>()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("parallel", animation_count),
+            &animations,
+            |b, animations| b.iter(|| animations.par_iter().map(sample).collect::<Vec<_>>()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("plan", animation_count),
+            &animations,
+            |b, animations| b.iter(|| plan.sample_animations(animations)),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, sampling);
+criterion_main!(benches);
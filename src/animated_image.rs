@@ -0,0 +1,98 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use image::codecs::gif::GifDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, Frame, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+/// Frames shorter than this are treated as this long, like browsers do
+const MIN_FRAME_DELAY_MS: u32 = 20;
+
+/// How an animated GIF or WebP plays
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum AnimatedImageMode {
+    #[default]
+    Animate, // follows the sequence clock, looping
+    StaticFirstFrame, // skips decoding and texture uploads after the first frame
+}
+
+/// The decoded frames of an animated image, each shown until its end time
+pub struct AnimatedFrames {
+    pub frames: Vec<RgbaImage>,
+    pub end_times_ms: Vec<u32>,
+    pub duration_ms: u32,
+}
+
+impl AnimatedFrames {
+    fn from_frames(frames: Vec<Frame>) -> Self {
+        let mut end_times_ms = Vec::with_capacity(frames.len());
+        let mut duration_ms = 0;
+        let mut images = Vec::with_capacity(frames.len());
+
+        for frame in frames {
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            let delay_ms = (numer / denom.max(1)).max(MIN_FRAME_DELAY_MS);
+
+            duration_ms += delay_ms;
+            end_times_ms.push(duration_ms);
+            images.push(frame.into_buffer());
+        }
+
+        AnimatedFrames {
+            frames: images,
+            end_times_ms,
+            duration_ms,
+        }
+    }
+
+    /// Index of the frame showing at a time, looping after the last frame
+    pub fn frame_at(&self, time_ms: u32) -> usize {
+        let time_ms = time_ms % self.duration_ms.max(1);
+
+        self.end_times_ms
+            .iter()
+            .position(|end_ms| time_ms < *end_ms)
+            .unwrap_or(0)
+    }
+}
+
+/// Decodes every frame of a GIF or WebP. None for other formats and single frame images.
+pub fn decode_animation(path: &Path) -> Result<Option<AnimatedFrames>, String> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+
+    let open = || {
+        File::open(path)
+            .map(BufReader::new)
+            .map_err(|e| format!("Couldn't open image {}: {}", path.display(), e))
+    };
+
+    let frames = match extension.as_deref() {
+        Some("gif") => GifDecoder::new(open()?)
+            .and_then(|decoder| decoder.into_frames().collect_frames())
+            .map_err(|e| format!("Couldn't decode gif {}: {}", path.display(), e))?,
+        Some("webp") => {
+            let decoder = WebPDecoder::new(open()?)
+                .map_err(|e| format!("Couldn't decode webp {}: {}", path.display(), e))?;
+            if !decoder.has_animation() {
+                return Ok(None);
+            }
+
+            decoder
+                .into_frames()
+                .collect_frames()
+                .map_err(|e| format!("Couldn't decode webp {}: {}", path.display(), e))?
+        }
+        _ => return Ok(None),
+    };
+
+    if frames.len() < 2 {
+        return Ok(None);
+    }
+
+    Ok(Some(AnimatedFrames::from_frames(frames)))
+}
@@ -1,13 +1,28 @@
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::time::Duration;
 
 use crate::{
-    editor::{ControlPoint, CurveData, PathType},
+    callout::SavedCalloutConfig,
+    captions::SavedCaptionTrack,
+    constraints::PinConstraint,
+    editor::{interpolate_position, ControlPoint, CurveData, PathType},
+    group::SavedGroupConfig,
+    outline::OutlinedText,
+    particles::SavedParticleEmitterConfig,
     polygon::SavedPolygonConfig,
+    spotlight::SavedSpotlightConfig,
+    spring::SpringConfig,
     st_image::SavedStImageConfig,
     st_video::SavedStVideoConfig,
+    tags::ColorTag,
     text_due::SavedTextRendererConfig,
+    warp::MeshWarp,
 };
 
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
@@ -16,6 +31,8 @@ pub enum ObjectType {
     TextItem,
     ImageItem,
     VideoItem,
+    Group,
+    ParticleEmitter,
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
@@ -29,6 +46,537 @@ pub struct Sequence {
     pub active_text_items: Vec<SavedTextRendererConfig>,
     pub active_image_items: Vec<SavedStImageConfig>,
     pub active_video_items: Vec<SavedStVideoConfig>,
+    #[serde(default)]
+    pub tags: Vec<ColorTag>,
+    #[serde(default)]
+    pub outlined_text_items: Vec<OutlinedText>,
+    #[serde(default)]
+    pub active_groups: Vec<SavedGroupConfig>,
+    #[serde(default)]
+    pub active_particle_emitters: Vec<SavedParticleEmitterConfig>,
+    #[serde(default)]
+    pub camera_animation: Option<CameraAnimation>,
+    #[serde(default)]
+    pub active_callouts: Vec<SavedCalloutConfig>,
+    #[serde(default)]
+    pub active_spotlights: Vec<SavedSpotlightConfig>,
+    #[serde(default)]
+    pub caption_tracks: Vec<SavedCaptionTrack>,
+    #[serde(default)]
+    pub constraints: Vec<PinConstraint>,
+}
+
+/// Keyframed pans and zooms of the whole canvas. Position keyframes are the pan in canvas
+/// pixels from the default view, Zoom keyframes are out of 100 like video zooms.
+/// Times are from the start of the sequence.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct CameraAnimation {
+    pub properties: Vec<AnimationProperty>,
+}
+
+impl CameraAnimation {
+    /// Pan and zoom at a time, holding the first and last keyframes outside of them
+    pub fn framing_at(&self, time: Duration) -> ([f32; 2], f32) {
+        let mut pan = [0.0, 0.0];
+        let mut zoom = 1.0;
+
+        for property in &self.properties {
+            let mut keyframes = property.keyframes.clone();
+            keyframes.sort_by_key(|k| k.time);
+
+            let Some(next_index) = keyframes.iter().position(|k| k.time > time) else {
+                if let Some(last) = keyframes.last() {
+                    apply_camera_value(&last.value, &mut pan, &mut zoom);
+                }
+                continue;
+            };
+            if next_index == 0 {
+                apply_camera_value(&keyframes[0].value, &mut pan, &mut zoom);
+                continue;
+            }
+
+            let mut start = keyframes[next_index - 1].clone();
+            let end = &keyframes[next_index];
+
+            // ranges hold their value until they end
+            if let KeyType::Range(range_data) = &start.key_type {
+                if time < range_data.end_time || range_data.end_time >= end.time {
+                    apply_camera_value(&start.value, &mut pan, &mut zoom);
+                    continue;
+                }
+                start.time = range_data.end_time;
+            }
+
+            match (&start.value, &end.value) {
+                (KeyframeValue::Position(_), KeyframeValue::Position(_)) => {
+                    let position = interpolate_position(&start, end, time.as_secs_f32());
+                    pan = [position[0] as f32, position[1] as f32];
+                }
+                (KeyframeValue::Zoom(start_zoom), KeyframeValue::Zoom(end_zoom)) => {
+                    let progress = eased_progress(&start, end, time);
+                    zoom = (*start_zoom as f32 + (end_zoom - start_zoom) as f32 * progress) / 100.0;
+                }
+                _ => {}
+            }
+        }
+
+        (pan, zoom.max(0.01))
+    }
+}
+
+fn apply_camera_value(value: &KeyframeValue, pan: &mut [f32; 2], zoom: &mut f32) {
+    match value {
+        KeyframeValue::Position(position) => *pan = [position[0] as f32, position[1] as f32],
+        KeyframeValue::Zoom(value) => *zoom = *value as f32 / 100.0,
+        _ => {}
+    }
+}
+
+/// Progress between two keyframes with the start keyframe's easing applied
+fn eased_progress(start: &UIKeyframe, end: &UIKeyframe, time: Duration) -> f32 {
+    let duration = (end.time - start.time).as_secs_f32();
+    let t = ((time.as_secs_f32() - start.time.as_secs_f32()) / duration.max(0.001)).clamp(0.0, 1.0);
+
+    match &start.easing {
+        EasingType::Linear => t,
+        EasingType::EaseIn => t * t,
+        EasingType::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+        EasingType::EaseInOut => {
+            if t < 0.5 {
+                2.0 * t * t
+            } else {
+                1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+            }
+        }
+        EasingType::Spring(spring) => {
+            spring.progress(time.saturating_sub(start.time).as_secs_f32())
+        }
+    }
+}
+
+/// A property's value at some time, interpolated between keyframes as playback does
+#[derive(Clone, PartialEq, Debug)]
+pub enum EvaluatedValue {
+    Position([f32; 2]),
+    Rotation(f32), // degrees
+    Scale(f32),    // out of 100
+    // out of 100 on each axis
+    ScaleXY([f32; 2]),
+    PerspectiveX(f32),
+    PerspectiveY(f32),
+    Opacity(f32), // out of 100
+    Zoom(f32),    // out of 100
+    Custom(Vec<f32>),
+    Warp(MeshWarp),
+    EmissionRate(f32),
+    Speed(f32), // out of 100
+    Percent(f32),
+    Color([f32; 4]), // RGBA out of 255
+}
+
+impl EvaluatedValue {
+    pub fn from_keyframe_value(value: &KeyframeValue) -> Self {
+        match value {
+            KeyframeValue::Position(p) => EvaluatedValue::Position([p[0] as f32, p[1] as f32]),
+            KeyframeValue::Rotation(v) => EvaluatedValue::Rotation(*v as f32),
+            KeyframeValue::Scale(v) => EvaluatedValue::Scale(*v as f32),
+            KeyframeValue::ScaleXY(v) => EvaluatedValue::ScaleXY([v[0] as f32, v[1] as f32]),
+            KeyframeValue::PerspectiveX(v) => EvaluatedValue::PerspectiveX(*v as f32),
+            KeyframeValue::PerspectiveY(v) => EvaluatedValue::PerspectiveY(*v as f32),
+            KeyframeValue::Opacity(v) => EvaluatedValue::Opacity(*v as f32),
+            KeyframeValue::Zoom(v) => EvaluatedValue::Zoom(*v as f32),
+            KeyframeValue::Custom(values) => {
+                EvaluatedValue::Custom(values.iter().map(|v| *v as f32).collect())
+            }
+            KeyframeValue::Warp(warp) => EvaluatedValue::Warp(warp.clone()),
+            KeyframeValue::EmissionRate(v) => EvaluatedValue::EmissionRate(*v as f32),
+            KeyframeValue::Speed(v) => EvaluatedValue::Speed(*v as f32),
+            KeyframeValue::Percent(v) => EvaluatedValue::Percent(*v as f32),
+            KeyframeValue::Color(c, _) => {
+                EvaluatedValue::Color([c[0] as f32, c[1] as f32, c[2] as f32, c[3] as f32])
+            }
+        }
+    }
+}
+
+/// The keyframes either side of a time, which is relative to the start of the animation.
+/// A Range key holds its value until its end time, so inside a range both keyframes carry
+/// that value, and between the end of a range and the next keyframe the start keyframe is
+/// a copy of the range's value at its end time. Returns None before the first keyframe and
+/// from the last keyframe on, where there's nothing to interpolate.
+pub fn surrounding_keyframes(
+    keyframes: &[UIKeyframe],
+    time: Duration,
+) -> Option<(UIKeyframe, UIKeyframe)> {
+    let mut keyframes = keyframes.to_vec();
+    keyframes.sort_by_key(|k| k.time);
+
+    surrounding_sorted_keyframes(&keyframes, time)
+        .map(|(start, end)| (start.into_owned(), end.into_owned()))
+}
+
+/// surrounding_keyframes for keyframes already sorted by time, borrowing them rather than
+/// cloning, as playback does every frame
+pub fn surrounding_sorted_keyframes(
+    keyframes: &[UIKeyframe],
+    time: Duration,
+) -> Option<(Cow<'_, UIKeyframe>, Cow<'_, UIKeyframe>)> {
+    let next_index = keyframes.partition_point(|k| k.time <= time);
+    if next_index == 0 || next_index == keyframes.len() {
+        return None;
+    }
+
+    let previous = &keyframes[next_index - 1];
+    let next = &keyframes[next_index];
+
+    if let KeyType::Range(range_data) = &previous.key_type {
+        let virtual_keyframe = UIKeyframe {
+            id: "virtual".to_string(),
+            time: range_data.end_time,
+            value: previous.value.clone(),
+            easing: EasingType::Linear, // Doesn't matter for static ranges
+            path_type: PathType::Linear, // Doesn't matter for static ranges
+            key_type: KeyType::Frame,
+        };
+
+        if time < range_data.end_time {
+            return Some((Cow::Borrowed(previous), Cow::Owned(virtual_keyframe)));
+        }
+        return Some((Cow::Owned(virtual_keyframe), Cow::Borrowed(next)));
+    }
+
+    Some((Cow::Borrowed(previous), Cow::Borrowed(next)))
+}
+
+/// How far playback is between two keyframes, from 0 to 1. Playback eases in and out
+/// whatever easing the keyframe has, unless it's a spring, which may overshoot 1.
+pub fn playback_progress(start: &UIKeyframe, end: &UIKeyframe, time: Duration) -> f32 {
+    let duration = end.time.saturating_sub(start.time).as_secs_f32();
+    if duration <= 0.0 {
+        return 1.0;
+    }
+    if let EasingType::Spring(spring) = &start.easing {
+        return spring.progress(time.saturating_sub(start.time).as_secs_f32());
+    }
+    let progress = (time.saturating_sub(start.time).as_secs_f32() / duration).clamp(0.0, 1.0);
+
+    if progress < 0.5 {
+        2.0 * progress * progress
+    } else {
+        1.0 - (-2.0 * progress + 2.0).powi(2) / 2.0
+    }
+}
+
+/// Time into a sequence at a playhead time, which wraps back to the start at the sequence's end
+pub fn sequence_time(total_dt: f32, duration_ms: i32) -> Duration {
+    Duration::from_secs_f32(total_dt.max(0.0) % (duration_ms.max(1) as f32 / 1000.0))
+}
+
+/// A property's value at a time relative to the start of its animation, without an editor.
+/// Between keyframes values are interpolated as in playback, see surrounding_keyframes for
+/// Range keys. Before the first keyframe its value is held, and likewise the last keyframe's
+/// from its time on. Playback wraps playhead times with sequence_time, so pass them through
+/// it first to match. Pairs of keyframes with different kinds of
+/// value, and Custom values of different lengths, hold the start value. Returns None for
+/// properties without keyframes.
+pub fn evaluate_property(property: &AnimationProperty, time: Duration) -> Option<EvaluatedValue> {
+    let first = property.keyframes.iter().min_by_key(|k| k.time)?;
+    let last = property.keyframes.iter().max_by_key(|k| k.time)?;
+
+    if time < first.time {
+        return Some(EvaluatedValue::from_keyframe_value(&first.value));
+    }
+
+    let Some((start, end)) = surrounding_keyframes(&property.keyframes, time) else {
+        return Some(EvaluatedValue::from_keyframe_value(&last.value));
+    };
+    let progress = playback_progress(&start, &end, time);
+    let lerp = |a: i32, b: i32| a as f32 + (b - a) as f32 * progress;
+
+    let value = match (&start.value, &end.value) {
+        (KeyframeValue::Position(a), KeyframeValue::Position(b)) => {
+            EvaluatedValue::Position([lerp(a[0], b[0]), lerp(a[1], b[1])])
+        }
+        (KeyframeValue::Rotation(a), KeyframeValue::Rotation(b)) => {
+            EvaluatedValue::Rotation(lerp(*a, *b))
+        }
+        (KeyframeValue::Scale(a), KeyframeValue::Scale(b)) => EvaluatedValue::Scale(lerp(*a, *b)),
+        (KeyframeValue::Scale(_) | KeyframeValue::ScaleXY(_), KeyframeValue::ScaleXY(_))
+        | (KeyframeValue::ScaleXY(_), KeyframeValue::Scale(_)) => {
+            let [a, b] = [&start.value, &end.value].map(|v| v.axis_scales().unwrap_or([100, 100]));
+            EvaluatedValue::ScaleXY([lerp(a[0], b[0]), lerp(a[1], b[1])])
+        }
+        (KeyframeValue::PerspectiveX(a), KeyframeValue::PerspectiveX(b)) => {
+            EvaluatedValue::PerspectiveX(lerp(*a, *b))
+        }
+        (KeyframeValue::PerspectiveY(a), KeyframeValue::PerspectiveY(b)) => {
+            EvaluatedValue::PerspectiveY(lerp(*a, *b))
+        }
+        (KeyframeValue::Opacity(a), KeyframeValue::Opacity(b)) => {
+            EvaluatedValue::Opacity(lerp(*a, *b))
+        }
+        (KeyframeValue::Zoom(a), KeyframeValue::Zoom(b)) => EvaluatedValue::Zoom(lerp(*a, *b)),
+        (KeyframeValue::Custom(a), KeyframeValue::Custom(b)) if a.len() == b.len() => {
+            EvaluatedValue::Custom(a.iter().zip(b.iter()).map(|(a, b)| lerp(*a, *b)).collect())
+        }
+        (KeyframeValue::Warp(a), KeyframeValue::Warp(b)) => {
+            EvaluatedValue::Warp(a.lerp(b, progress))
+        }
+        (KeyframeValue::EmissionRate(a), KeyframeValue::EmissionRate(b)) => {
+            EvaluatedValue::EmissionRate(lerp(*a, *b))
+        }
+        (KeyframeValue::Speed(a), KeyframeValue::Speed(b)) => EvaluatedValue::Speed(lerp(*a, *b)),
+        (KeyframeValue::Percent(a), KeyframeValue::Percent(b)) => {
+            EvaluatedValue::Percent(lerp(*a, *b))
+        }
+        (KeyframeValue::Color(a, interpolation), KeyframeValue::Color(b, _)) => {
+            EvaluatedValue::Color(interpolate_color(*a, *b, progress, *interpolation))
+        }
+        _ => EvaluatedValue::from_keyframe_value(&start.value),
+    };
+
+    Some(value)
+}
+
+/// Blends two RGBA colors out of 255, clamped as springs can carry progress past 1
+pub fn interpolate_color(
+    start: [i32; 4],
+    end: [i32; 4],
+    progress: f32,
+    interpolation: ColorInterpolation,
+) -> [f32; 4] {
+    let lerp = |a: f32, b: f32| a + (b - a) * progress;
+    let alpha = lerp(start[3] as f32, end[3] as f32);
+
+    let [r, g, b] = match interpolation {
+        ColorInterpolation::Rgb => [0, 1, 2].map(|i| lerp(start[i] as f32, end[i] as f32)),
+        ColorInterpolation::Hsv => {
+            let (h1, s1, v1) = rgb_to_hsv(start);
+            let (h2, s2, v2) = rgb_to_hsv(end);
+
+            // grays have no hue of their own, so take the other color's
+            let (h1, h2) = match (s1 == 0.0, s2 == 0.0) {
+                (true, false) => (h2, h2),
+                (false, true) => (h1, h1),
+                _ => (h1, h2),
+            };
+            // the shorter way around the wheel
+            let mut delta = h2 - h1;
+            if delta > 180.0 {
+                delta -= 360.0;
+            } else if delta < -180.0 {
+                delta += 360.0;
+            }
+
+            hsv_to_rgb(
+                (h1 + delta * progress).rem_euclid(360.0),
+                lerp(s1, s2).clamp(0.0, 1.0),
+                lerp(v1, v2).clamp(0.0, 1.0),
+            )
+        }
+    };
+
+    [r, g, b, alpha].map(|c| c.clamp(0.0, 255.0))
+}
+
+/// Hue in degrees, saturation and value from 0 to 1
+fn rgb_to_hsv(color: [i32; 4]) -> (f32, f32, f32) {
+    let [r, g, b] = [0, 1, 2].map(|i| color[i].clamp(0, 255) as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { delta / max };
+
+    (hue, saturation, max)
+}
+
+/// Back to RGB out of 255
+fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [f32; 3] {
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let m = value - chroma;
+
+    let (r, g, b) = match (hue / 60.0) as i32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    [r, g, b].map(|c| (c + m) * 255.0)
+}
+
+/// Below this many animations a step samples them on the calling thread, as handing
+/// them to the thread pool costs more than it saves. From `benches/animation_sampling.rs`,
+/// the pool adds about 10µs a step while an animation of 4 eased properties samples in
+/// about 0.14µs, so it only pays off from a few hundred animations.
+pub const PARALLEL_SAMPLING_MIN_ANIMATIONS: usize = 256;
+
+/// A property's keyframes around a time and how far playback is between them
+#[derive(Clone, Copy, Debug)]
+pub struct TrackSample<'a> {
+    pub start: &'a UIKeyframe,
+    pub end: &'a UIKeyframe,
+    pub progress: f32,
+}
+
+/// One motion path ready for playback
+#[derive(Clone, Debug)]
+pub struct CompiledAnimation {
+    pub object_index: Option<usize>, // in the editor's list for the object type
+    pub tracks: Vec<Vec<UIKeyframe>>, // each property's keyframes, see compile_track
+}
+
+impl CompiledAnimation {
+    /// Samples the tracks at a time relative to the animation's start, None for tracks
+    /// with nothing to interpolate then
+    pub fn sample(&self, time: Duration) -> impl Iterator<Item = Option<TrackSample<'_>>> {
+        self.tracks
+            .iter()
+            .map(move |keyframes| sample_track(keyframes, time))
+    }
+}
+
+/// Sorts a property's keyframes by time and follows each Range key that has a keyframe
+/// after it with a copy holding its value until the range ends. Sampling the result gives
+/// the same keyframes as surrounding_keyframes without making any while playing.
+pub fn compile_track(keyframes: &[UIKeyframe]) -> Vec<UIKeyframe> {
+    let mut sorted = keyframes.to_vec();
+    sorted.sort_by_key(|k| k.time);
+
+    let mut track = Vec::with_capacity(sorted.len());
+    for (i, keyframe) in sorted.iter().enumerate() {
+        track.push(keyframe.clone());
+
+        let (KeyType::Range(range_data), Some(next)) = (&keyframe.key_type, sorted.get(i + 1))
+        else {
+            continue;
+        };
+        track.push(UIKeyframe {
+            id: "virtual".to_string(),
+            time: range_data.end_time.clamp(keyframe.time, next.time),
+            value: keyframe.value.clone(),
+            easing: EasingType::Linear, // Doesn't matter for static ranges
+            path_type: PathType::Linear, // Doesn't matter for static ranges
+            key_type: KeyType::Frame,
+        });
+    }
+
+    track
+}
+
+/// The keyframes of a compiled track around a time, None before the first keyframe and
+/// from the last one on
+pub fn sample_track(track: &[UIKeyframe], time: Duration) -> Option<TrackSample<'_>> {
+    let next_index = track.partition_point(|k| k.time <= time);
+    if next_index == 0 || next_index == track.len() {
+        return None;
+    }
+
+    let start = &track[next_index - 1];
+    let end = &track[next_index];
+    Some(TrackSample {
+        start,
+        end,
+        progress: playback_progress(start, end, time),
+    })
+}
+
+/// A sequence's animations compiled for playback, so steps don't sort keyframes or search
+/// for objects every frame. Immutable, compile a new plan when the sequence changes.
+#[derive(Clone, Debug)]
+pub struct AnimationPlan {
+    pub sequence_id: String,
+    pub fingerprint: u64,
+    pub animations: Vec<CompiledAnimation>, // in polygon_motion_paths order
+    pub by_object_id: HashMap<String, usize>, // object id to animation index
+}
+
+impl AnimationPlan {
+    /// resolve_object finds an object's index from its type and id
+    pub fn compile(
+        sequence: &Sequence,
+        resolve_object: impl Fn(&ObjectType, &str) -> Option<usize>,
+    ) -> Self {
+        let animations = sequence
+            .polygon_motion_paths
+            .iter()
+            .map(|animation| CompiledAnimation {
+                object_index: resolve_object(&animation.object_type, &animation.polygon_id),
+                tracks: animation
+                    .properties
+                    .iter()
+                    .map(|property| compile_track(&property.keyframes))
+                    .collect(),
+            })
+            .collect();
+
+        let by_object_id = sequence
+            .polygon_motion_paths
+            .iter()
+            .enumerate()
+            .map(|(i, animation)| (animation.polygon_id.clone(), i))
+            .collect();
+
+        AnimationPlan {
+            sequence_id: sequence.id.clone(),
+            fingerprint: Self::fingerprint(sequence),
+            animations,
+            by_object_id,
+        }
+    }
+
+    /// Hash of everything the plan is compiled from, far cheaper than compiling
+    pub fn fingerprint(sequence: &Sequence) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        sequence.id.hash(&mut hasher);
+        sequence.polygon_motion_paths.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Whether the plan still matches the sequence
+    pub fn is_current(&self, sequence: &Sequence) -> bool {
+        self.sequence_id == sequence.id && self.fingerprint == Self::fingerprint(sequence)
+    }
+
+    /// Samples animations, given by index and time from their start, in parallel once there
+    /// are enough of them. Results are in the order given.
+    pub fn sample_animations(
+        &self,
+        animations: &[(usize, Duration)],
+    ) -> Vec<Vec<Option<TrackSample<'_>>>> {
+        let sample = |(index, time): &(usize, Duration)| {
+            self.animations
+                .get(*index)
+                .map(|animation| animation.sample(*time).collect())
+                .unwrap_or_default()
+        };
+
+        if animations.len() < PARALLEL_SAMPLING_MIN_ANIMATIONS {
+            animations.iter().map(sample).collect()
+        } else {
+            animations.par_iter().map(sample).collect()
+        }
+    }
+
+    pub fn animation_for(&self, object_id: &str) -> Option<&CompiledAnimation> {
+        self.by_object_id
+            .get(object_id)
+            .and_then(|i| self.animations.get(*i))
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
@@ -47,6 +595,8 @@ pub struct AnimationData {
     pub properties: Vec<AnimationProperty>,
     /// Relative position
     pub position: [i32; 2],
+    /// Motion blur in exports, out of 100 where 100 smears across a whole frame
+    pub motion_blur: i32,
 }
 
 impl Default for AnimationData {
@@ -59,6 +609,7 @@ impl Default for AnimationData {
             start_time_ms: 0,
             properties: Vec::new(),
             position: [0, 0],
+            motion_blur: 0,
         }
     }
 }
@@ -98,6 +649,7 @@ pub enum EasingType {
     EaseIn,
     EaseOut,
     EaseInOut,
+    Spring(SpringConfig), // physically driven toward the next keyframe
 }
 
 /// Represents a keyframe in the UI
@@ -137,11 +689,40 @@ pub enum KeyframeValue {
     Position([i32; 2]),
     Rotation(i32), // stored as degrees
     Scale(i32),    // this will be 100 for default size to work with i32 and Eq
-    PerspectiveX(i32),
-    PerspectiveY(i32),
+    // width and height scales, also out of 100, for squash and stretch
+    ScaleXY([i32; 2]),
+    PerspectiveX(i32), // 3D tilt around the horizontal axis, in degrees
+    PerspectiveY(i32), // 3D tilt around the vertical axis, in degrees
     Opacity(i32), // also out of 100
     Zoom(i32),    // 100 is minimum, needs precision
     Custom(Vec<i32>),
+    Warp(MeshWarp),
+    EmissionRate(i32), // particles per second
+    Speed(i32),        // video playback speed, out of 100
+    Percent(i32),      // widget value, from 0 to 100
+    // RGBA out of 255, for the polygon "fill" and "stroke" properties and the text "color"
+    // and "background_fill" properties. The start keyframe's interpolation blends toward the next.
+    Color([i32; 4], ColorInterpolation),
+}
+
+impl KeyframeValue {
+    /// Width and height scales out of 100 for Scale and ScaleXY values, which blend into
+    /// each other so a uniform scale can be stretched on one axis
+    pub fn axis_scales(&self) -> Option<[i32; 2]> {
+        match self {
+            KeyframeValue::Scale(scale) => Some([*scale, *scale]),
+            KeyframeValue::ScaleXY(scales) => Some(*scales),
+            _ => None,
+        }
+    }
+}
+
+/// How color keyframes blend into the next
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum ColorInterpolation {
+    #[default]
+    Rgb,
+    Hsv, // around the hue wheel, keeping colors saturated midway
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
@@ -263,3 +844,380 @@ fn should_flip_curve(current: &[i32; 2], next: &[i32; 2]) -> bool {
     // This creates more natural arcs for different movement directions
     angle < 0.0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyframe(ms: u64, value: KeyframeValue) -> UIKeyframe {
+        UIKeyframe {
+            id: format!("key-{}", ms),
+            time: Duration::from_millis(ms),
+            value,
+            ..UIKeyframe::default()
+        }
+    }
+
+    fn range_keyframe(ms: u64, end_ms: u64, value: KeyframeValue) -> UIKeyframe {
+        UIKeyframe {
+            key_type: KeyType::Range(RangeData {
+                end_time: Duration::from_millis(end_ms),
+            }),
+            ..keyframe(ms, value)
+        }
+    }
+
+    fn property(keyframes: Vec<UIKeyframe>) -> AnimationProperty {
+        AnimationProperty {
+            name: "Test".to_string(),
+            property_path: "test".to_string(),
+            keyframes,
+            ..AnimationProperty::default()
+        }
+    }
+
+    fn evaluate(keyframes: Vec<UIKeyframe>, ms: u64) -> EvaluatedValue {
+        evaluate_property(&property(keyframes), Duration::from_millis(ms))
+            .expect("Couldn't evaluate property")
+    }
+
+    fn assert_color(value: EvaluatedValue, expected: [f32; 4]) {
+        let EvaluatedValue::Color(color) = value else {
+            panic!("Expected a color, got {:?}", value);
+        };
+        for (channel, expected) in color.iter().zip(expected) {
+            assert!(
+                (channel - expected).abs() < 0.5,
+                "Expected {:?}, got {:?}",
+                expected,
+                color
+            );
+        }
+    }
+
+    fn slide() -> Vec<UIKeyframe> {
+        vec![
+            keyframe(200, KeyframeValue::Position([10, 20])),
+            keyframe(1200, KeyframeValue::Position([110, 220])),
+        ]
+    }
+
+    #[test]
+    fn holds_first_value_before_first_keyframe() {
+        assert_eq!(evaluate(slide(), 0), EvaluatedValue::Position([10.0, 20.0]));
+        assert_eq!(
+            evaluate(slide(), 199),
+            EvaluatedValue::Position([10.0, 20.0])
+        );
+    }
+
+    #[test]
+    fn holds_last_value_from_last_keyframe() {
+        assert_eq!(
+            evaluate(slide(), 1200),
+            EvaluatedValue::Position([110.0, 220.0])
+        );
+        assert_eq!(
+            evaluate(slide(), 5000),
+            EvaluatedValue::Position([110.0, 220.0])
+        );
+    }
+
+    #[test]
+    fn eases_in_and_out_between_keyframes() {
+        // halfway in time is halfway in value, a quarter in is an eighth of the way
+        assert_eq!(
+            evaluate(slide(), 700),
+            EvaluatedValue::Position([60.0, 120.0])
+        );
+        assert_eq!(
+            evaluate(slide(), 450),
+            EvaluatedValue::Position([22.5, 45.0])
+        );
+    }
+
+    #[test]
+    fn no_keyframes_is_none() {
+        assert_eq!(
+            evaluate_property(&property(Vec::new()), Duration::ZERO),
+            None
+        );
+    }
+
+    #[test]
+    fn range_holds_its_value_until_it_ends() {
+        let keyframes = vec![
+            range_keyframe(0, 400, KeyframeValue::Opacity(20)),
+            keyframe(1400, KeyframeValue::Opacity(80)),
+        ];
+
+        assert_eq!(
+            evaluate(keyframes.clone(), 200),
+            EvaluatedValue::Opacity(20.0)
+        );
+        assert_eq!(
+            evaluate(keyframes.clone(), 399),
+            EvaluatedValue::Opacity(20.0)
+        );
+
+        let (start, end) = surrounding_keyframes(&keyframes, Duration::from_millis(200))
+            .expect("Couldn't find keyframes inside the range");
+        assert_eq!(start.time, Duration::ZERO);
+        assert_eq!(end.time, Duration::from_millis(400));
+        assert_eq!(end.value, KeyframeValue::Opacity(20));
+    }
+
+    #[test]
+    fn range_blends_into_next_keyframe_after_it_ends() {
+        let keyframes = vec![
+            range_keyframe(0, 400, KeyframeValue::Opacity(20)),
+            keyframe(1400, KeyframeValue::Opacity(80)),
+        ];
+
+        // halfway from the end of the range to the next keyframe
+        assert_eq!(
+            evaluate(keyframes.clone(), 900),
+            EvaluatedValue::Opacity(50.0)
+        );
+
+        let (start, end) = surrounding_keyframes(&keyframes, Duration::from_millis(900))
+            .expect("Couldn't find keyframes after the range");
+        assert_eq!(start.time, Duration::from_millis(400));
+        assert_eq!(start.value, KeyframeValue::Opacity(20));
+        assert_eq!(start.key_type, KeyType::Frame);
+        assert_eq!(end.time, Duration::from_millis(1400));
+    }
+
+    #[test]
+    fn compiled_tracks_match_surrounding_keyframes() {
+        let keyframes = vec![
+            keyframe(1400, KeyframeValue::Opacity(80)),
+            range_keyframe(0, 400, KeyframeValue::Opacity(20)),
+        ];
+        let track = compile_track(&keyframes);
+
+        for ms in [0, 200, 400, 900, 1399] {
+            let time = Duration::from_millis(ms);
+            let (start, end) =
+                surrounding_keyframes(&keyframes, time).expect("Couldn't find keyframes");
+            let sample = sample_track(&track, time).expect("Couldn't sample track");
+            assert_eq!((sample.start.time, sample.end.time), (start.time, end.time));
+            assert_eq!(sample.start.value, start.value);
+            assert_eq!(sample.progress, playback_progress(&start, &end, time));
+        }
+        assert!(sample_track(&track, Duration::from_millis(1400)).is_none());
+    }
+
+    #[test]
+    fn sorts_keyframes_given_out_of_order() {
+        let keyframes = vec![
+            keyframe(1000, KeyframeValue::Rotation(100)),
+            keyframe(0, KeyframeValue::Rotation(0)),
+            keyframe(2000, KeyframeValue::Rotation(300)),
+        ];
+
+        assert_eq!(
+            evaluate(keyframes.clone(), 500),
+            EvaluatedValue::Rotation(50.0)
+        );
+        assert_eq!(
+            evaluate(keyframes.clone(), 1500),
+            EvaluatedValue::Rotation(200.0)
+        );
+        assert_eq!(
+            evaluate(keyframes.clone(), 2500),
+            EvaluatedValue::Rotation(300.0)
+        );
+    }
+
+    #[test]
+    fn mismatched_values_hold_the_start_value() {
+        let keyframes = vec![
+            keyframe(0, KeyframeValue::Position([5, 5])),
+            keyframe(1000, KeyframeValue::Rotation(90)),
+        ];
+
+        assert_eq!(
+            evaluate(keyframes, 500),
+            EvaluatedValue::Position([5.0, 5.0])
+        );
+    }
+
+    #[test]
+    fn custom_values_of_different_lengths_hold_the_start_value() {
+        let keyframes = vec![
+            keyframe(0, KeyframeValue::Custom(vec![0, 10])),
+            keyframe(1000, KeyframeValue::Custom(vec![10, 20, 30])),
+        ];
+
+        assert_eq!(
+            evaluate(keyframes, 500),
+            EvaluatedValue::Custom(vec![0.0, 10.0])
+        );
+    }
+
+    #[test]
+    fn custom_values_of_the_same_length_blend() {
+        let keyframes = vec![
+            keyframe(0, KeyframeValue::Custom(vec![0, 10])),
+            keyframe(1000, KeyframeValue::Custom(vec![10, 30])),
+        ];
+
+        assert_eq!(
+            evaluate(keyframes, 500),
+            EvaluatedValue::Custom(vec![5.0, 20.0])
+        );
+    }
+
+    #[test]
+    fn scale_blends_into_axis_scales() {
+        let keyframes = vec![
+            keyframe(0, KeyframeValue::Scale(100)),
+            keyframe(1000, KeyframeValue::ScaleXY([200, 50])),
+        ];
+
+        assert_eq!(
+            evaluate(keyframes, 500),
+            EvaluatedValue::ScaleXY([150.0, 75.0])
+        );
+    }
+
+    #[test]
+    fn axis_scales_blend_into_scale() {
+        let keyframes = vec![
+            keyframe(0, KeyframeValue::ScaleXY([200, 50])),
+            keyframe(1000, KeyframeValue::Scale(100)),
+        ];
+
+        assert_eq!(
+            evaluate(keyframes.clone(), 500),
+            EvaluatedValue::ScaleXY([150.0, 75.0])
+        );
+        // once the last keyframe is reached its own kind of value is held
+        assert_eq!(evaluate(keyframes, 1000), EvaluatedValue::Scale(100.0));
+    }
+
+    #[test]
+    fn uniform_scales_stay_uniform() {
+        let keyframes = vec![
+            keyframe(0, KeyframeValue::Scale(100)),
+            keyframe(1000, KeyframeValue::Scale(200)),
+        ];
+
+        assert_eq!(evaluate(keyframes, 500), EvaluatedValue::Scale(150.0));
+    }
+
+    #[test]
+    fn rgb_colors_blend_each_channel() {
+        let keyframes = vec![
+            keyframe(
+                0,
+                KeyframeValue::Color([0, 0, 0, 255], ColorInterpolation::Rgb),
+            ),
+            keyframe(
+                1000,
+                KeyframeValue::Color([255, 100, 50, 55], ColorInterpolation::Rgb),
+            ),
+        ];
+
+        assert_color(evaluate(keyframes, 500), [127.5, 50.0, 25.0, 155.0]);
+    }
+
+    #[test]
+    fn hsv_colors_go_around_the_hue_wheel() {
+        // red to blue the short way passes through magenta rather than gray
+        let keyframes = vec![
+            keyframe(
+                0,
+                KeyframeValue::Color([255, 0, 0, 255], ColorInterpolation::Hsv),
+            ),
+            keyframe(
+                1000,
+                KeyframeValue::Color([0, 0, 255, 255], ColorInterpolation::Hsv),
+            ),
+        ];
+
+        assert_color(evaluate(keyframes, 500), [255.0, 0.0, 255.0, 255.0]);
+    }
+
+    #[test]
+    fn hsv_hue_wraps_around() {
+        // magenta at 300 degrees to yellow at 60 crosses 0, red, halfway
+        let keyframes = vec![
+            keyframe(
+                0,
+                KeyframeValue::Color([255, 0, 255, 255], ColorInterpolation::Hsv),
+            ),
+            keyframe(
+                1000,
+                KeyframeValue::Color([255, 255, 0, 255], ColorInterpolation::Hsv),
+            ),
+        ];
+
+        assert_color(evaluate(keyframes, 500), [255.0, 0.0, 0.0, 255.0]);
+    }
+
+    #[test]
+    fn hsv_grays_take_the_other_hue() {
+        // black has no hue, so it fades into green rather than through red
+        assert_color(
+            EvaluatedValue::Color(interpolate_color(
+                [0, 0, 0, 255],
+                [0, 255, 0, 255],
+                0.5,
+                ColorInterpolation::Hsv,
+            )),
+            [63.75, 127.5, 63.75, 255.0],
+        );
+    }
+
+    #[test]
+    fn springs_overshoot_the_next_keyframe() {
+        let mut start = keyframe(0, KeyframeValue::Position([0, 0]));
+        start.easing = EasingType::Spring(SpringConfig::wobbly());
+        let keyframes = vec![start, keyframe(1000, KeyframeValue::Position([100, 0]))];
+
+        // a wobbly spring peaks about a fifth past the target a quarter second in
+        let EvaluatedValue::Position([x, _]) = evaluate(keyframes.clone(), 262) else {
+            panic!("Expected a position");
+        };
+        assert!(x > 115.0 && x < 125.0, "Expected an overshoot, got {}", x);
+
+        let progress = playback_progress(&keyframes[0], &keyframes[1], Duration::from_millis(262));
+        assert!(progress > 1.0);
+    }
+
+    #[test]
+    fn springs_keep_colors_in_range() {
+        let mut start = keyframe(
+            0,
+            KeyframeValue::Color([0, 0, 0, 255], ColorInterpolation::Rgb),
+        );
+        start.easing = EasingType::Spring(SpringConfig::wobbly());
+        let keyframes = vec![
+            start,
+            keyframe(
+                1000,
+                KeyframeValue::Color([255, 255, 255, 255], ColorInterpolation::Rgb),
+            ),
+        ];
+
+        assert_color(evaluate(keyframes, 262), [255.0, 255.0, 255.0, 255.0]);
+    }
+
+    #[test]
+    fn playback_time_wraps_at_the_sequence_duration() {
+        let keyframes = vec![
+            keyframe(0, KeyframeValue::Zoom(100)),
+            keyframe(1000, KeyframeValue::Zoom(200)),
+        ];
+        let at = |total_dt: f32| {
+            evaluate_property(&property(keyframes.clone()), sequence_time(total_dt, 2000))
+        };
+
+        assert_eq!(at(2.5), Some(EvaluatedValue::Zoom(150.0)));
+        assert_eq!(at(2.5), at(0.5));
+        assert_eq!(at(3.75), Some(EvaluatedValue::Zoom(200.0)));
+        assert_eq!(at(4.0), Some(EvaluatedValue::Zoom(100.0)));
+    }
+}
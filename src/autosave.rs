@@ -0,0 +1,169 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::saved_state::{
+    encode_saved_state, get_project_dir, save_saved_state_raw, write_atomic, SavedState,
+};
+
+/// Present while a project is open, so finding it on open means the last session crashed
+const SESSION_LOCK: &str = "session.lock";
+
+#[derive(Clone, Copy, Debug)]
+pub struct AutosaveSettings {
+    pub interval: Duration,
+    pub backup_count: usize, // older autosaves are deleted
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        AutosaveSettings {
+            interval: Duration::from_secs(60),
+            backup_count: 5,
+        }
+    }
+}
+
+/// An autosave left behind by a session that didn't close cleanly
+#[derive(Clone, Debug)]
+pub struct Recovery {
+    pub path: PathBuf,
+    pub saved_at: SystemTime,
+}
+
+/// Saves copies of the open project into its autosave folder every so often
+pub struct Autosaver {
+    pub settings: AutosaveSettings,
+    project_id: String,
+    last_saved: Instant,
+    last_hash: Option<u64>,
+}
+
+impl Autosaver {
+    /// Starts autosaving a project. Check `find_recovery` first, as this marks a new session.
+    pub fn start(project_id: &str, settings: AutosaveSettings) -> Result<Self, String> {
+        fs::create_dir_all(autosave_dir(project_id))
+            .map_err(|e| format!("Couldn't create autosave folder: {}", e))?;
+        fs::write(
+            get_project_dir(project_id).join(SESSION_LOCK),
+            std::process::id().to_string(),
+        )
+        .map_err(|e| format!("Couldn't write session lock: {}", e))?;
+
+        Ok(Autosaver {
+            settings,
+            project_id: project_id.to_string(),
+            last_saved: Instant::now(),
+            last_hash: None,
+        })
+    }
+
+    /// Saves a copy once the interval has passed, if the project changed since the last one.
+    /// Cheap enough to call every frame. Returns whether a copy was saved.
+    pub fn tick(&mut self, saved_state: &SavedState) -> Result<bool, String> {
+        if self.last_saved.elapsed() < self.settings.interval {
+            return Ok(false);
+        }
+        self.last_saved = Instant::now();
+
+        let mut hasher = DefaultHasher::new();
+        saved_state.hash(&mut hasher);
+        let hash = hasher.finish();
+        if self.last_hash == Some(hash) {
+            return Ok(false);
+        }
+
+        self.save_now(saved_state)?;
+        self.last_hash = Some(hash);
+
+        Ok(true)
+    }
+
+    pub fn save_now(&mut self, saved_state: &SavedState) -> Result<PathBuf, String> {
+        let content = encode_saved_state(saved_state)?;
+        let file_name = format!(
+            "autosave-{}.json",
+            chrono::Local::now().format("%Y%m%d-%H%M%S%.3f")
+        );
+        let path = autosave_dir(&self.project_id).join(file_name);
+
+        write_atomic(&path, &content)
+            .map_err(|e| format!("Couldn't write autosave {}: {}", path.display(), e))?;
+
+        // keep only the newest few
+        for old in list_autosaves(&self.project_id)
+            .into_iter()
+            .skip(self.settings.backup_count.max(1))
+        {
+            fs::remove_file(old).ok();
+        }
+
+        Ok(path)
+    }
+
+    /// Ends the session cleanly, so the next open doesn't offer recovery
+    pub fn finish(self) {
+        discard_recovery(&self.project_id);
+    }
+}
+
+fn autosave_dir(project_id: &str) -> PathBuf {
+    get_project_dir(project_id).join("autosave")
+}
+
+/// A project's autosaves, newest first
+pub fn list_autosaves(project_id: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(autosave_dir(project_id)) else {
+        return Vec::new();
+    };
+
+    let mut autosaves: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("autosave-") && n.ends_with(".json"))
+        })
+        .collect();
+    // timestamps in the names sort by time
+    autosaves.sort();
+    autosaves.reverse();
+
+    autosaves
+}
+
+/// The latest autosave when the last session crashed after saving it
+/// and the project file hasn't been saved since
+pub fn find_recovery(project_id: &str) -> Option<Recovery> {
+    let project_dir = get_project_dir(project_id);
+    if !project_dir.join(SESSION_LOCK).exists() {
+        return None;
+    }
+
+    let path = list_autosaves(project_id).into_iter().next()?;
+    let saved_at = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+    let project_saved_at = fs::metadata(project_dir.join("project_data.json"))
+        .and_then(|m| m.modified())
+        .ok();
+    if project_saved_at.is_some_and(|project_saved_at| project_saved_at >= saved_at) {
+        return None;
+    }
+
+    Some(Recovery { path, saved_at })
+}
+
+/// Puts a recovered autosave back as the project file, returning it to open
+pub fn restore_recovery(recovery: &Recovery) -> anyhow::Result<SavedState> {
+    let saved_state = SavedState::load_with_migrations(&recovery.path)?;
+    save_saved_state_raw(saved_state.clone()).map_err(anyhow::Error::msg)?;
+
+    Ok(saved_state)
+}
+
+/// Forgets a crashed session without restoring it. Its autosaves are kept.
+pub fn discard_recovery(project_id: &str) {
+    fs::remove_file(get_project_dir(project_id).join(SESSION_LOCK)).ok();
+}
@@ -0,0 +1,219 @@
+use std::path::Path;
+
+use crate::animations::KeyframeValue;
+
+/// Samples per analysis frame, and between the starts of frames
+const FRAME_SIZE: usize = 1024;
+const HOP_SIZE: usize = 512;
+/// Time around each frame its onset is compared against, in milliseconds
+const THRESHOLD_WINDOW_MS: f32 = 500.0;
+/// How long before a beat a pulse starts growing
+const PULSE_ATTACK_MS: i32 = 40;
+
+/// How eagerly onsets count as beats
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BeatSettings {
+    pub sensitivity: i32,     // percent, higher finds quieter beats
+    pub min_interval_ms: i32, // between beats, 250 allows up to 240 BPM
+}
+
+impl Default for BeatSettings {
+    fn default() -> Self {
+        Self {
+            sensitivity: 100,
+            min_interval_ms: 250,
+        }
+    }
+}
+
+/// Beats found in a clip, in milliseconds from its start
+#[derive(Clone, PartialEq, Debug)]
+pub struct BeatAnalysis {
+    pub beats_ms: Vec<i32>,
+    pub tempo_bpm: f32, // from the typical gap between beats, 0 with fewer than two
+}
+
+/// Reads a WAV file as mono samples from -1 to 1, and its sample rate. Handles 8, 16, 24
+/// and 32 bit PCM and 32 bit float, so convert other formats first.
+pub fn read_wav(path: &Path) -> Result<(Vec<f32>, u32), String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(format!("{} isn't a WAV file", path.display()));
+    }
+
+    let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+    let u32_at =
+        |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+
+    // format, channels, sample rate and bits per sample
+    let mut format: Option<(u16, usize, u32, usize)> = None;
+    let mut data: Option<&[u8]> = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let size = u32_at(offset + 4) as usize;
+        let body = offset + 8;
+        let end = (body + size).min(bytes.len());
+
+        if id == b"fmt " && size >= 16 && body + 16 <= bytes.len() {
+            let mut audio_format = u16_at(body);
+            // extensible files keep the real format in their sub format
+            if audio_format == 0xFFFE && size >= 26 && body + 26 <= bytes.len() {
+                audio_format = u16_at(body + 24);
+            }
+            format = Some((
+                audio_format,
+                u16_at(body + 2) as usize,
+                u32_at(body + 4),
+                u16_at(body + 14) as usize,
+            ));
+        } else if id == b"data" {
+            data = Some(&bytes[body..end]);
+        }
+
+        // chunks are padded to an even length
+        offset = body + size + (size & 1);
+    }
+
+    let (audio_format, channels, sample_rate, bits) =
+        format.ok_or_else(|| format!("{} has no format chunk", path.display()))?;
+    let data = data.ok_or_else(|| format!("{} has no audio", path.display()))?;
+    if channels == 0 || sample_rate == 0 {
+        return Err(format!("{} has no channels", path.display()));
+    }
+
+    let sample_bytes = bits / 8;
+    let read_sample = |chunk: &[u8]| -> Option<f32> {
+        match (audio_format, bits) {
+            (1, 8) => Some((chunk[0] as f32 - 128.0) / 128.0),
+            (1, 16) => Some(i16::from_le_bytes([chunk[0], chunk[1]]) as f32 / 32768.0),
+            (1, 24) => {
+                let value = i32::from_le_bytes([0, chunk[0], chunk[1], chunk[2]]) >> 8;
+                Some(value as f32 / 8_388_608.0)
+            }
+            (1, 32) => {
+                let value = i32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+                Some(value as f32 / 2_147_483_648.0)
+            }
+            (3, 32) => Some(f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])),
+            _ => None,
+        }
+    };
+    if sample_bytes == 0 || read_sample(&[0; 4]).is_none() {
+        return Err(format!(
+            "{} uses an unsupported sample format",
+            path.display()
+        ));
+    }
+
+    let samples = data
+        .chunks_exact(sample_bytes * channels)
+        .map(|frame| {
+            let sum: f32 = frame
+                .chunks_exact(sample_bytes)
+                .filter_map(|chunk| read_sample(chunk))
+                .sum();
+            sum / channels as f32
+        })
+        .collect();
+
+    Ok((samples, sample_rate))
+}
+
+/// Finds beats as sudden rises in loudness that stand out from the loudness around them
+pub fn detect_beats(samples: &[f32], sample_rate: u32, settings: &BeatSettings) -> BeatAnalysis {
+    let frame_ms = HOP_SIZE as f32 * 1000.0 / sample_rate.max(1) as f32;
+
+    // how much louder each frame is than the one before, in log energy
+    let energies: Vec<f32> = samples
+        .windows(FRAME_SIZE)
+        .step_by(HOP_SIZE)
+        .map(|frame| {
+            let energy = frame.iter().map(|s| s * s).sum::<f32>() / FRAME_SIZE as f32;
+            (energy + 1e-10).ln()
+        })
+        .collect();
+    let onsets: Vec<f32> = energies
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).max(0.0))
+        .collect();
+
+    let window = ((THRESHOLD_WINDOW_MS / frame_ms).round() as usize).max(1);
+    let multiplier = 1.5 * 100.0 / settings.sensitivity.max(1) as f32;
+    let mut beats_ms: Vec<i32> = Vec::new();
+    for (index, onset) in onsets.iter().enumerate() {
+        let (from, to) = (
+            index.saturating_sub(window),
+            (index + window + 1).min(onsets.len()),
+        );
+        let around = &onsets[from..to];
+        let mean = around.iter().sum::<f32>() / around.len() as f32;
+        let is_peak = around.iter().all(|other| other <= onset);
+        if !is_peak || *onset <= mean * multiplier || *onset <= 0.0 {
+            continue;
+        }
+
+        // the onset is between this frame and the next
+        let time_ms = ((index + 1) as f32 * frame_ms).round() as i32;
+        if beats_ms
+            .last()
+            .is_some_and(|last| time_ms - last < settings.min_interval_ms)
+        {
+            continue;
+        }
+        beats_ms.push(time_ms);
+    }
+
+    let mut gaps: Vec<i32> = beats_ms.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    gaps.sort();
+    let tempo_bpm = match gaps.get(gaps.len() / 2) {
+        Some(gap) if *gap > 0 => 60_000.0 / *gap as f32,
+        _ => 0.0,
+    };
+
+    BeatAnalysis {
+        beats_ms,
+        tempo_bpm,
+    }
+}
+
+/// The beat nearest a time, if any are given
+pub fn nearest_beat(beats_ms: &[i32], time_ms: i32) -> Option<i32> {
+    beats_ms
+        .iter()
+        .copied()
+        .min_by_key(|beat| (beat - time_ms).abs())
+}
+
+/// Scale keyframes growing an object on each beat and easing it back, for times relative to
+/// an animation starting at start_ms. Pulses are cut short when beats come close together.
+pub fn pulse_keyframes(
+    beats_ms: &[i32],
+    start_ms: i32,
+    duration_ms: i32,
+    intensity: i32, // percent added to the scale on a beat
+    decay_ms: i32,  // to shrink back
+) -> Vec<(i32, KeyframeValue)> {
+    let beats: Vec<i32> = beats_ms
+        .iter()
+        .map(|beat| beat - start_ms)
+        .filter(|beat| *beat >= PULSE_ATTACK_MS && *beat <= duration_ms)
+        .collect();
+
+    let mut values = vec![(0, KeyframeValue::Scale(100))];
+    for (index, beat) in beats.iter().enumerate() {
+        let next = beats
+            .get(index + 1)
+            .copied()
+            .unwrap_or(duration_ms + PULSE_ATTACK_MS);
+        let settle = (beat + decay_ms.max(1)).min(next - PULSE_ATTACK_MS);
+
+        values.push((beat - PULSE_ATTACK_MS, KeyframeValue::Scale(100)));
+        values.push((*beat, KeyframeValue::Scale(100 + intensity)));
+        values.push((settle.max(beat + 1), KeyframeValue::Scale(100)));
+    }
+    values.push((duration_ms, KeyframeValue::Scale(100)));
+
+    values
+}
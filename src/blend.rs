@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::vertex::Vertex;
+
+/// How an object's color combines with what's drawn beneath it. Each mode is a pipeline
+/// with its own blend state and fragment entry point, see frag_primary.wgsl.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    /// Exact over darker backdrops, brighter backdrops are lightened rather than screened
+    Overlay,
+    Add,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 5] = [
+        BlendMode::Normal,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Overlay,
+        BlendMode::Add,
+    ];
+
+    /// The fragment shader output is prepared per mode, so that source alpha still fades
+    /// the object in and out with these blend factors
+    pub fn fragment_entry_point(&self) -> &'static str {
+        match self {
+            BlendMode::Normal => "fs_main",
+            BlendMode::Multiply => "fs_multiply",
+            BlendMode::Screen | BlendMode::Add => "fs_premultiplied",
+            BlendMode::Overlay => "fs_overlay",
+        }
+    }
+
+    pub fn blend_state(&self) -> wgpu::BlendState {
+        // the backdrop keeps its own alpha for all but normal blending
+        let keep_alpha = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Zero,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        match self {
+            BlendMode::Normal => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+            },
+            // dst * src
+            BlendMode::Multiply => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Dst,
+                    dst_factor: wgpu::BlendFactor::Zero,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: keep_alpha,
+            },
+            // src + dst - src * dst
+            BlendMode::Screen => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::OneMinusDst,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: keep_alpha,
+            },
+            // 2 * src * dst
+            BlendMode::Overlay => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Dst,
+                    dst_factor: wgpu::BlendFactor::Src,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: keep_alpha,
+            },
+            // src + dst
+            BlendMode::Add => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: keep_alpha,
+            },
+        }
+    }
+}
+
+/// Creates a pipeline for each blend mode, sharing the layout and shaders of the main pipeline
+pub fn create_blend_pipelines(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    vertex_shader: &wgpu::ShaderModule,
+    fragment_shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    depth_stencil: Option<wgpu::DepthStencilState>,
+) -> HashMap<BlendMode, wgpu::RenderPipeline> {
+    BlendMode::ALL
+        .iter()
+        .map(|mode| {
+            let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Stunts Engine Blend Pipeline"),
+                layout: Some(layout),
+                multiview: None,
+                vertex: wgpu::VertexState {
+                    module: vertex_shader,
+                    entry_point: "vs_main",
+                    buffers: &[Vertex::desc()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: fragment_shader,
+                    entry_point: mode.fragment_entry_point(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(mode.blend_state()),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    conservative: false,
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                },
+                depth_stencil: depth_stencil.clone(),
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+            });
+
+            (*mode, pipeline)
+        })
+        .collect()
+}
+
+/// The pipeline to draw an object with, falling back to the main pipeline
+pub fn pipeline_for_mode<'a>(
+    render_pipeline: &'a wgpu::RenderPipeline,
+    blend_pipelines: &'a HashMap<BlendMode, wgpu::RenderPipeline>,
+    mode: BlendMode,
+) -> &'a wgpu::RenderPipeline {
+    blend_pipelines.get(&mode).unwrap_or(render_pipeline)
+}
@@ -0,0 +1,73 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::export::watermark::WatermarkSettings;
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct BrandColor {
+    pub name: String,
+    pub color: [i32; 4],
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct BrandFont {
+    pub family: String, // the name text items use to pick it
+    pub path: PathBuf,
+}
+
+impl BrandFont {
+    pub fn read(&self) -> Result<Vec<u8>, String> {
+        fs::read(&self.path).map_err(|e| format!("Couldn't read {}: {}", self.path.display(), e))
+    }
+}
+
+/// A logo, colors and fonts attached to a project, loaded from a JSON manifest like
+/// `{ "name": "Acme", "logo": "logo.png",
+///    "colors": [{ "name": "Primary", "color": [255, 80, 0, 255] }],
+///    "fonts": [{ "family": "Acme Sans", "path": "fonts/AcmeSans.ttf" }] }`
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct BrandKit {
+    pub name: String,
+    #[serde(default)]
+    pub logo: Option<PathBuf>,
+    #[serde(default)]
+    pub colors: Vec<BrandColor>,
+    #[serde(default)]
+    pub fonts: Vec<BrandFont>,
+}
+
+impl BrandKit {
+    /// Reads a manifest, resolving its paths against the manifest's folder
+    pub fn load(manifest_path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(manifest_path)
+            .map_err(|e| format!("Couldn't read {}: {}", manifest_path.display(), e))?;
+        let mut brand_kit: BrandKit = serde_json::from_str(&json)
+            .map_err(|e| format!("Couldn't parse {}: {}", manifest_path.display(), e))?;
+
+        let folder = manifest_path.parent().unwrap_or(Path::new(""));
+        let resolve = |path: &Path| -> Result<PathBuf, String> {
+            let path = folder.join(path);
+            if !path.is_file() {
+                return Err(format!("Brand kit file {} doesn't exist", path.display()));
+            }
+            Ok(path)
+        };
+
+        if let Some(logo) = &brand_kit.logo {
+            brand_kit.logo = Some(resolve(logo)?);
+        }
+        for font in brand_kit.fonts.iter_mut() {
+            font.path = resolve(&font.path)?;
+        }
+
+        Ok(brand_kit)
+    }
+
+    /// A watermark of the logo for the bottom right corner of exports, or None when the
+    /// kit has no logo
+    pub fn logo_watermark(&self) -> Option<WatermarkSettings> {
+        self.logo.clone().map(WatermarkSettings::image)
+    }
+}
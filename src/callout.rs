@@ -0,0 +1,495 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::animations::{AnimationData, AnimationProperty, KeyframeValue, ObjectType, Sequence};
+use crate::blend::BlendMode;
+use crate::editor::Point;
+use crate::group::{GroupChild, SavedGroupConfig};
+use crate::input_overlays::keyframe_property;
+use crate::masking::MaskMode;
+use crate::outline::{points_to_saved, SavedOutline};
+use crate::polygon::{SavedPoint, SavedPolygonConfig, SavedStroke};
+use crate::text_due::SavedTextRendererConfig;
+
+/// How far the Slide preset moves the label, in canvas pixels
+const SLIDE_DISTANCE: i32 = 40;
+/// Scale the Pop preset grows from, out of 100
+const POP_SCALE: i32 = 60;
+
+/// Entrance and exit presets, played as the callout appears and disappears
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum CalloutAnimation {
+    None,
+    #[default]
+    Fade,
+    Pop,   // grows from the label's center
+    Slide, // rises into place, and sinks on exit
+}
+
+/// Connects a leader arrow to a label. The label box and text are grouped under the callout
+/// id, and the arrow is redrawn from the box's edge to the anchor whenever either moves.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct SavedCalloutConfig {
+    pub id: String, // the group
+    pub box_id: String,
+    pub text_id: String,
+    pub arrow_id: String,
+    pub anchor: SavedPoint, // where the arrow points
+    pub thickness: i32,     // of the shaft
+    pub head_size: i32,
+}
+
+/// Look and timing of a new callout
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct CalloutSettings {
+    pub name: String,
+    pub text: String,
+    pub anchor: SavedPoint,
+    pub label_position: SavedPoint, // center of the label box
+    pub font_family: String,
+    pub font_size: i32,
+    pub text_color: [i32; 4],
+    pub box_fill: [i32; 4],
+    pub border_radius: i32,
+    pub padding: i32,
+    pub arrow_fill: [i32; 4],
+    pub thickness: i32,
+    pub head_size: i32,
+    pub layer: i32,
+    pub start_ms: i32, // when the callout shows, in sequence time
+    pub end_ms: i32,
+    pub entrance: CalloutAnimation,
+    pub exit: CalloutAnimation,
+    pub animation_ms: i32, // length of each preset
+}
+
+impl Default for CalloutSettings {
+    fn default() -> Self {
+        CalloutSettings {
+            name: "Callout".to_string(),
+            text: "Look here".to_string(),
+            anchor: SavedPoint { x: 300, y: 300 },
+            label_position: SavedPoint { x: 500, y: 180 },
+            font_family: "Figtree".to_string(),
+            font_size: 28,
+            text_color: [255, 255, 255, 255],
+            box_fill: [30, 30, 30, 255],
+            border_radius: 12,
+            padding: 16,
+            arrow_fill: [30, 30, 30, 255],
+            thickness: 6,
+            head_size: 22,
+            layer: -1,
+            start_ms: 0,
+            end_ms: 3000,
+            entrance: CalloutAnimation::Fade,
+            exit: CalloutAnimation::Fade,
+            animation_ms: 300,
+        }
+    }
+}
+
+/// Builds a callout's label box, text and arrow, returning a sequence holding only the new
+/// objects so they can be restored and merged. Each object is animated across the whole
+/// sequence, hidden outside the callout's time.
+pub fn build_callout(
+    settings: &CalloutSettings,
+    sequence_id: String,
+    sequence_duration_ms: i32,
+) -> Sequence {
+    let mut callout = Sequence {
+        id: sequence_id,
+        name: String::new(),
+        background_fill: None,
+        duration_ms: 0,
+        active_polygons: Vec::new(),
+        polygon_motion_paths: Vec::new(),
+        active_text_items: Vec::new(),
+        active_image_items: Vec::new(),
+        active_video_items: Vec::new(),
+        tags: Vec::new(),
+        outlined_text_items: Vec::new(),
+        active_groups: Vec::new(),
+        active_particle_emitters: Vec::new(),
+        camera_animation: None,
+        active_callouts: Vec::new(),
+        active_spotlights: Vec::new(),
+        caption_tracks: Vec::new(),
+        constraints: Vec::new(),
+    };
+
+    let font_size = settings.font_size;
+    // roughly the text's size, fonts vary
+    let longest_line = settings
+        .text
+        .lines()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0) as i32;
+    let line_count = settings.text.lines().count().max(1) as i32;
+    let text_dimensions = (
+        (longest_line * font_size * 3 / 5) + font_size,
+        line_count * font_size * 8 / 5,
+    );
+    let box_dimensions = (
+        text_dimensions.0 + settings.padding * 2,
+        text_dimensions.1 + settings.padding * 2,
+    );
+
+    let group_id = Uuid::new_v4().to_string();
+    let box_id = Uuid::new_v4().to_string();
+    let text_id = Uuid::new_v4().to_string();
+    let arrow_id = Uuid::new_v4().to_string();
+
+    let label_center = Point {
+        x: settings.label_position.x as f32,
+        y: settings.label_position.y as f32,
+    };
+    let anchor = Point {
+        x: settings.anchor.x as f32,
+        y: settings.anchor.y as f32,
+    };
+    let from = leader_start(
+        label_center,
+        (box_dimensions.0 as f32 / 2.0, box_dimensions.1 as f32 / 2.0),
+        anchor,
+    );
+    let (arrow_center, arrow_dimensions, arrow_points) = arrow_shape(
+        from,
+        anchor,
+        settings.thickness as f32,
+        settings.head_size as f32,
+    );
+    let arrow_position = SavedPoint {
+        x: arrow_center.x as i32,
+        y: arrow_center.y as i32,
+    };
+
+    let polygon = |id: &String, name: &str, fill: [i32; 4], layer: i32| SavedPolygonConfig {
+        id: id.clone(),
+        name: format!("{} {}", settings.name, name),
+        fill,
+        dimensions: box_dimensions,
+        position: settings.label_position.clone(),
+        border_radius: settings.border_radius,
+        stroke: SavedStroke {
+            thickness: 0,
+            fill: [0, 0, 0, 0],
+        },
+        layer,
+        tags: Vec::new(),
+        outline: None,
+        locked: false,
+        hidden: false,
+        opacity: 100,
+        blend_mode: BlendMode::default(),
+        mask_target_id: None,
+        mask_mode: MaskMode::default(),
+    };
+
+    callout
+        .active_polygons
+        .push(polygon(&box_id, "Box", settings.box_fill, settings.layer));
+    // behind the box, and locked as it only follows the label and anchor
+    callout.active_polygons.push(SavedPolygonConfig {
+        dimensions: (arrow_dimensions.0 as i32, arrow_dimensions.1 as i32),
+        position: arrow_position.clone(),
+        border_radius: 0,
+        outline: Some(SavedOutline {
+            points: points_to_saved(&arrow_points),
+            contours: Vec::new(),
+            source: None,
+        }),
+        locked: true,
+        ..polygon(&arrow_id, "Arrow", settings.arrow_fill, settings.layer + 1)
+    });
+
+    callout.active_text_items.push(SavedTextRendererConfig {
+        id: text_id.clone(),
+        name: format!("{} Text", settings.name),
+        text: settings.text.clone(),
+        font_family: settings.font_family.clone(),
+        font_size,
+        dimensions: text_dimensions,
+        position: settings.label_position.clone(),
+        layer: settings.layer - 1,
+        color: settings.text_color,
+        background_fill: None,
+        tags: Vec::new(),
+        warp: None,
+        locked: false,
+        hidden: false,
+        opacity: 100,
+        blend_mode: BlendMode::default(),
+        mask_target_id: None,
+        mask_mode: MaskMode::default(),
+    });
+
+    callout.active_groups.push(SavedGroupConfig {
+        id: group_id.clone(),
+        name: settings.name.clone(),
+        children: vec![
+            GroupChild {
+                object_id: box_id.clone(),
+                object_type: ObjectType::Polygon,
+            },
+            GroupChild {
+                object_id: text_id.clone(),
+                object_type: ObjectType::TextItem,
+            },
+        ],
+        pivot: settings.label_position.clone(),
+        position: settings.label_position.clone(),
+        rotation: 0,
+        scale: 100,
+        tags: Vec::new(),
+        widget: None,
+    });
+
+    let label_position = [settings.label_position.x, settings.label_position.y];
+    let opacity = preset_opacity(settings, sequence_duration_ms);
+
+    // the label's presets play on the group, the arrow follows it and only fades
+    callout.polygon_motion_paths.push(callout_animation(
+        group_id.clone(),
+        ObjectType::Group,
+        vec![
+            preset_position(settings, label_position, sequence_duration_ms),
+            preset_scale(settings, sequence_duration_ms),
+            opacity.clone(),
+        ],
+        sequence_duration_ms,
+    ));
+    callout.polygon_motion_paths.push(callout_animation(
+        arrow_id.clone(),
+        ObjectType::Polygon,
+        vec![
+            still_position(arrow_position.clone(), sequence_duration_ms),
+            opacity,
+        ],
+        sequence_duration_ms,
+    ));
+    for (object_id, object_type) in [
+        (box_id.clone(), ObjectType::Polygon),
+        (text_id.clone(), ObjectType::TextItem),
+    ] {
+        callout.polygon_motion_paths.push(callout_animation(
+            object_id,
+            object_type,
+            vec![still_position(
+                settings.label_position.clone(),
+                sequence_duration_ms,
+            )],
+            sequence_duration_ms,
+        ));
+    }
+
+    callout.active_callouts.push(SavedCalloutConfig {
+        id: group_id,
+        box_id,
+        text_id,
+        arrow_id,
+        anchor: settings.anchor.clone(),
+        thickness: settings.thickness,
+        head_size: settings.head_size,
+    });
+
+    callout
+}
+
+/// Where a line from the label's center towards the anchor leaves the label box. half is
+/// half the box's size. Anchors inside the box start from the center.
+pub fn leader_start(center: Point, half: (f32, f32), anchor: Point) -> Point {
+    let dx = anchor.x - center.x;
+    let dy = anchor.y - center.y;
+
+    let to_edge = |half: f32, delta: f32| {
+        if delta.abs() > f32::EPSILON {
+            half / delta.abs()
+        } else {
+            f32::MAX
+        }
+    };
+    let t = to_edge(half.0, dx).min(to_edge(half.1, dy));
+    if t >= 1.0 {
+        return center;
+    }
+
+    Point {
+        x: center.x + dx * t,
+        y: center.y + dy * t,
+    }
+}
+
+/// An arrow from one point to another as a polygon, returning its center, dimensions and
+/// points normalized to its bounds
+pub fn arrow_shape(
+    from: Point,
+    to: Point,
+    thickness: f32,
+    head_size: f32,
+) -> (Point, (f32, f32), Vec<Point>) {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let length = (dx * dx + dy * dy).sqrt().max(1.0);
+    let (ux, uy) = (dx / length, dy / length);
+    let (nx, ny) = (-uy, ux);
+
+    let head_length = head_size.min(length);
+    let neck = Point {
+        x: to.x - ux * head_length,
+        y: to.y - uy * head_length,
+    };
+    let side = |point: &Point, width: f32| Point {
+        x: point.x + nx * width / 2.0,
+        y: point.y + ny * width / 2.0,
+    };
+
+    let points = [
+        side(&from, thickness),
+        side(&neck, thickness),
+        side(&neck, head_size),
+        to,
+        side(&neck, -head_size),
+        side(&neck, -thickness),
+        side(&from, -thickness),
+    ];
+
+    let min_x = points.iter().map(|p| p.x).fold(f32::MAX, f32::min);
+    let min_y = points.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+    let max_x = points.iter().map(|p| p.x).fold(f32::MIN, f32::max);
+    let max_y = points.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+    let dimensions = ((max_x - min_x).max(1.0), (max_y - min_y).max(1.0));
+
+    let normalized = points
+        .iter()
+        .map(|p| Point {
+            x: (p.x - min_x) / dimensions.0,
+            y: (p.y - min_y) / dimensions.1,
+        })
+        .collect();
+
+    (
+        Point {
+            x: (min_x + max_x) / 2.0,
+            y: (min_y + max_y) / 2.0,
+        },
+        dimensions,
+        normalized,
+    )
+}
+
+/// An animation over the whole sequence
+fn callout_animation(
+    object_id: String,
+    object_type: ObjectType,
+    properties: Vec<AnimationProperty>,
+    sequence_duration_ms: i32,
+) -> AnimationData {
+    AnimationData {
+        id: Uuid::new_v4().to_string(),
+        object_type,
+        polygon_id: object_id,
+        duration: Duration::from_millis(sequence_duration_ms.max(0) as u64),
+        start_time_ms: 0,
+        properties,
+        position: [0, 0],
+        motion_blur: 0,
+    }
+}
+
+fn still_position(position: SavedPoint, sequence_duration_ms: i32) -> AnimationProperty {
+    let position = KeyframeValue::Position([position.x, position.y]);
+
+    keyframe_property(
+        "Position",
+        "position",
+        &[(0, position.clone()), (sequence_duration_ms, position)],
+    )
+}
+
+/// Keyframes hiding the callout outside its time. entrance_ms and exit_ms are how long
+/// each preset takes to change the property, None where the preset leaves it shown.
+fn preset_keyframes(
+    settings: &CalloutSettings,
+    sequence_duration_ms: i32,
+    hidden: KeyframeValue,
+    shown: KeyframeValue,
+    entrance_ms: Option<i32>,
+    exit_ms: Option<i32>,
+) -> Vec<(i32, KeyframeValue)> {
+    let start_ms = settings.start_ms;
+    let end_ms = settings.end_ms.max(start_ms);
+    // each preset gets at most half the callout's time
+    let longest_ms = ((end_ms - start_ms) / 2).max(1);
+
+    let before = match entrance_ms {
+        Some(_) => hidden.clone(),
+        None => shown.clone(),
+    };
+    let after = match exit_ms {
+        Some(_) => hidden,
+        None => shown.clone(),
+    };
+
+    vec![
+        (0, before.clone()),
+        (start_ms, before),
+        (
+            start_ms + entrance_ms.unwrap_or(0).clamp(1, longest_ms),
+            shown.clone(),
+        ),
+        (end_ms - exit_ms.unwrap_or(0).clamp(1, longest_ms), shown),
+        (end_ms, after.clone()),
+        (sequence_duration_ms, after),
+    ]
+}
+
+fn preset_opacity(settings: &CalloutSettings, sequence_duration_ms: i32) -> AnimationProperty {
+    // every preset fades, and None cuts in and out
+    let length = |preset: CalloutAnimation| match preset {
+        CalloutAnimation::None => 1,
+        _ => settings.animation_ms,
+    };
+    let values = preset_keyframes(
+        settings,
+        sequence_duration_ms,
+        KeyframeValue::Opacity(0),
+        KeyframeValue::Opacity(100),
+        Some(length(settings.entrance)),
+        Some(length(settings.exit)),
+    );
+
+    keyframe_property("Opacity", "opacity", &values)
+}
+
+fn preset_scale(settings: &CalloutSettings, sequence_duration_ms: i32) -> AnimationProperty {
+    let values = preset_keyframes(
+        settings,
+        sequence_duration_ms,
+        KeyframeValue::Scale(POP_SCALE),
+        KeyframeValue::Scale(100),
+        (settings.entrance == CalloutAnimation::Pop).then_some(settings.animation_ms),
+        (settings.exit == CalloutAnimation::Pop).then_some(settings.animation_ms),
+    );
+
+    keyframe_property("Scale", "scale", &values)
+}
+
+fn preset_position(
+    settings: &CalloutSettings,
+    position: [i32; 2],
+    sequence_duration_ms: i32,
+) -> AnimationProperty {
+    let values = preset_keyframes(
+        settings,
+        sequence_duration_ms,
+        KeyframeValue::Position([position[0], position[1] + SLIDE_DISTANCE]),
+        KeyframeValue::Position(position),
+        (settings.entrance == CalloutAnimation::Slide).then_some(settings.animation_ms),
+        (settings.exit == CalloutAnimation::Slide).then_some(settings.animation_ms),
+    );
+
+    keyframe_property("Position", "position", &values)
+}
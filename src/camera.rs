@@ -1,4 +1,7 @@
+use std::time::{Duration, Instant};
+
 use cgmath::{Matrix4, Point3, Vector2, Vector3, Rad, perspective, InnerSpace};
+use serde::{Deserialize, Serialize};
 
 use crate::editor::{size_to_normal, Point, WindowSize};
 
@@ -218,11 +221,14 @@ pub struct Camera3D {
     pub window_size: WindowSize,
 }
 
+/// Distance from the canvas at which it fills the view
+pub const CANVAS_CAMERA_DISTANCE: f32 = 2.6;
+
 impl Camera3D {
     pub fn new(window_size: WindowSize) -> Self {
         Self {
             // position: Vector3::new(0.0, 0.0, 0.1),
-            position: Vector3::new(0.0, 0.0, 2.6),
+            position: Vector3::new(0.0, 0.0, CANVAS_CAMERA_DISTANCE),
             target: Vector3::new(0.0, 0.0, 0.0),
             up: Vector3::new(0.0, 1.0, 0.0),
             fovy: Rad(std::f32::consts::FRAC_PI_4),
@@ -290,6 +296,25 @@ impl Camera3D {
         self.position = self.target + new_dir * radius;
     }
 
+    /// Looks straight at the canvas, panned by canvas pixels from the default view and zoomed
+    /// in by the zoom factor, for keyframed camera moves
+    pub fn frame_canvas(&mut self, pan: [f32; 2], zoom: f32) {
+        let x = pan[0] / self.window_size.width as f32 * 2.0;
+        let y = pan[1] / self.window_size.height as f32 * 2.0;
+
+        self.target = Vector3::new(x, y, 0.0);
+        self.position = Vector3::new(x, y, CANVAS_CAMERA_DISTANCE / zoom);
+        self.up = Vector3::new(0.0, 1.0, 0.0);
+    }
+
+    /// Moves the camera toward its target, or away with a negative distance,
+    /// stopping short of passing through it
+    pub fn dolly(&mut self, distance: f32) {
+        let offset = self.position - self.target;
+        let new_distance = (offset.magnitude() - distance).max(self.znear * 2.0);
+        self.position = self.target + offset.normalize() * new_distance;
+    }
+
     pub fn set_aspect_ratio(&mut self, aspect: f32) {
         self.aspect = aspect;
     }
@@ -316,6 +341,98 @@ impl Camera3D {
     }
 }
 
+/// Camera values are saved in thousandths to keep Eq and Hash on saved state
+pub const CAMERA_BOOKMARK_PRECISION: f32 = 1000.0;
+
+/// A named camera pose, saved with the project so a view can be returned to.
+/// Zoom is the distance between position and target.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct CameraBookmark {
+    pub name: String,
+    pub position: [i32; 3],
+    pub target: [i32; 3],
+}
+
+impl CameraBookmark {
+    pub fn from_camera(name: String, camera: &Camera3D) -> Self {
+        let to_saved = |v: Vector3<f32>| {
+            [
+                (v.x * CAMERA_BOOKMARK_PRECISION).round() as i32,
+                (v.y * CAMERA_BOOKMARK_PRECISION).round() as i32,
+                (v.z * CAMERA_BOOKMARK_PRECISION).round() as i32,
+            ]
+        };
+
+        CameraBookmark {
+            name,
+            position: to_saved(camera.position),
+            target: to_saved(camera.target),
+        }
+    }
+
+    pub fn position(&self) -> Vector3<f32> {
+        bookmark_vector(self.position)
+    }
+
+    pub fn target(&self) -> Vector3<f32> {
+        bookmark_vector(self.target)
+    }
+}
+
+fn bookmark_vector(saved: [i32; 3]) -> Vector3<f32> {
+    Vector3::new(
+        saved[0] as f32 / CAMERA_BOOKMARK_PRECISION,
+        saved[1] as f32 / CAMERA_BOOKMARK_PRECISION,
+        saved[2] as f32 / CAMERA_BOOKMARK_PRECISION,
+    )
+}
+
+/// Eases the camera from one pose to another over a duration
+#[derive(Clone, Copy, Debug)]
+pub struct CameraTransition {
+    pub from_position: Vector3<f32>,
+    pub from_target: Vector3<f32>,
+    pub to_position: Vector3<f32>,
+    pub to_target: Vector3<f32>,
+    pub started: Instant,
+    pub duration: Duration,
+}
+
+impl CameraTransition {
+    pub fn new(camera: &Camera3D, bookmark: &CameraBookmark, duration: Duration) -> Self {
+        CameraTransition {
+            from_position: camera.position,
+            from_target: camera.target,
+            to_position: bookmark.position(),
+            to_target: bookmark.target(),
+            started: Instant::now(),
+            duration,
+        }
+    }
+
+    /// Moves the camera to where it should be now, returning true once it has arrived
+    pub fn apply(&self, camera: &mut Camera3D) -> bool {
+        let progress = if self.duration.is_zero() {
+            1.0
+        } else {
+            (self.started.elapsed().as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+        };
+
+        // EaseInOut, like keyframes
+        let eased = if progress < 0.5 {
+            2.0 * progress * progress
+        } else {
+            1.0 - (-2.0 * progress + 2.0).powi(2) / 2.0
+        };
+
+        camera.position = self.from_position + (self.to_position - self.from_position) * eased;
+        camera.target = self.from_target + (self.to_target - self.from_target) * eased;
+        camera.up = Vector3::new(0.0, 1.0, 0.0);
+
+        progress >= 1.0
+    }
+}
+
 use bytemuck::{Pod, Zeroable};
 use cgmath::SquareMatrix;
 
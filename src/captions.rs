@@ -0,0 +1,565 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::animations::{AnimationData, KeyframeValue, ObjectType, Sequence};
+use crate::blend::BlendMode;
+use crate::editor::CANVAS_TOP;
+use crate::input_overlays::keyframe_property;
+use crate::masking::MaskMode;
+use crate::polygon::SavedPoint;
+use crate::saved_state::CanvasSize;
+use crate::text_due::SavedTextRendererConfig;
+
+/// Space between captions and the bottom of the canvas, in canvas pixels
+const CAPTION_MARGIN: i32 = 48;
+
+/// A line of speech and when it's said, in milliseconds from the start of the audio
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct CaptionSegment {
+    pub start_ms: i32,
+    pub end_ms: i32,
+    pub text: String,
+    #[serde(default)]
+    pub text_id: Option<String>, // the text item showing it, once added to a sequence
+}
+
+/// Captions kept together on their own track, so they can be restyled and exported as one
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct SavedCaptionTrack {
+    pub id: String,
+    pub name: String,
+    pub style: CaptionStyle,
+    pub segments: Vec<CaptionSegment>,
+}
+
+/// Looks for new captions
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum CaptionStyle {
+    #[default]
+    Subtitle, // white on a dark band, like broadcast subtitles
+    Bold,    // large yellow type for social video
+    Minimal, // small white type without a band
+}
+
+impl CaptionStyle {
+    pub fn settings(&self) -> CaptionSettings {
+        let defaults = CaptionSettings::default();
+
+        match self {
+            CaptionStyle::Subtitle => defaults,
+            CaptionStyle::Bold => CaptionSettings {
+                style: CaptionStyle::Bold,
+                font_family: "Bungee".to_string(),
+                font_size: 44,
+                color: [255, 220, 0, 255],
+                background_fill: None,
+                max_line_chars: 24,
+                ..defaults
+            },
+            CaptionStyle::Minimal => CaptionSettings {
+                style: CaptionStyle::Minimal,
+                font_size: 26,
+                background_fill: None,
+                ..defaults
+            },
+        }
+    }
+}
+
+/// Look and placement of captions added to a sequence
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct CaptionSettings {
+    pub style: CaptionStyle,
+    pub name: String,
+    pub font_family: String,
+    pub font_size: i32,
+    pub color: [i32; 4],
+    pub background_fill: Option<[i32; 4]>,
+    pub max_line_chars: usize, // longer captions wrap onto more lines
+    pub offset_ms: i32,        // where the audio starts in the sequence
+    pub layer: i32,
+}
+
+impl Default for CaptionSettings {
+    fn default() -> Self {
+        CaptionSettings {
+            style: CaptionStyle::Subtitle,
+            name: "Captions".to_string(),
+            font_family: "Figtree".to_string(),
+            font_size: 32,
+            color: [255, 255, 255, 255],
+            background_fill: Some([0, 0, 0, 160]),
+            max_line_chars: 42,
+            offset_ms: 0,
+            layer: -5,
+        }
+    }
+}
+
+/// Turns recorded speech into timed lines. Backends run wherever they like, such as a
+/// local model or a hosted service.
+pub trait SpeechToText {
+    fn transcribe(&self, audio_path: &Path) -> Result<Vec<CaptionSegment>, String>;
+}
+
+/// Runs the whisper.cpp command line tool. It reads 16 kHz WAV files, so convert other
+/// recordings first.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct WhisperCpp {
+    pub executable: PathBuf,      // such as whisper-cli
+    pub model: PathBuf,           // a ggml model file
+    pub language: Option<String>, // detected when None
+    pub threads: u32,
+}
+
+#[derive(Deserialize)]
+struct WhisperOutput {
+    transcription: Vec<WhisperSegment>,
+}
+
+#[derive(Deserialize)]
+struct WhisperSegment {
+    offsets: WhisperOffsets,
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct WhisperOffsets {
+    from: i64,
+    to: i64,
+}
+
+impl SpeechToText for WhisperCpp {
+    fn transcribe(&self, audio_path: &Path) -> Result<Vec<CaptionSegment>, String> {
+        // whisper.cpp adds the .json extension itself
+        let output_base = std::env::temp_dir().join(format!("stunts-captions-{}", Uuid::new_v4()));
+        let output_path = output_base.with_extension("json");
+
+        let mut command = Command::new(&self.executable);
+        command
+            .arg("--model")
+            .arg(&self.model)
+            .arg("--file")
+            .arg(audio_path)
+            .arg("--threads")
+            .arg(self.threads.max(1).to_string())
+            .arg("--output-json")
+            .arg("--output-file")
+            .arg(&output_base)
+            .arg("--no-prints");
+        if let Some(language) = &self.language {
+            command.arg("--language").arg(language);
+        }
+
+        let output = command
+            .output()
+            .map_err(|e| format!("Couldn't run {}: {}", self.executable.display(), e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Transcription failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        let json = std::fs::read_to_string(&output_path)
+            .map_err(|e| format!("Couldn't read transcription: {}", e))?;
+        let _ = std::fs::remove_file(&output_path);
+        let parsed: WhisperOutput = serde_json::from_str(&json)
+            .map_err(|e| format!("Couldn't parse transcription: {}", e))?;
+
+        Ok(parsed
+            .transcription
+            .into_iter()
+            .map(|segment| CaptionSegment {
+                start_ms: segment.offsets.from.max(0) as i32,
+                end_ms: segment.offsets.to.max(0) as i32,
+                text: segment.text.trim().to_string(),
+                text_id: None,
+            })
+            .filter(|segment| !segment.text.is_empty() && segment.end_ms > segment.start_ms)
+            .collect())
+    }
+}
+
+/// Builds a text item per segment, each shown only while it's said, and a track holding
+/// them. Returns a sequence with only the new objects so they can be restored and merged.
+pub fn build_caption_track(
+    segments: &[CaptionSegment],
+    settings: &CaptionSettings,
+    sequence_id: String,
+    sequence_duration_ms: i32,
+    canvas_size: CanvasSize,
+) -> Sequence {
+    let mut captions = Sequence {
+        id: sequence_id,
+        name: String::new(),
+        background_fill: None,
+        duration_ms: 0,
+        active_polygons: Vec::new(),
+        polygon_motion_paths: Vec::new(),
+        active_text_items: Vec::new(),
+        active_image_items: Vec::new(),
+        active_video_items: Vec::new(),
+        tags: Vec::new(),
+        outlined_text_items: Vec::new(),
+        active_groups: Vec::new(),
+        active_particle_emitters: Vec::new(),
+        camera_animation: None,
+        active_callouts: Vec::new(),
+        active_spotlights: Vec::new(),
+        caption_tracks: Vec::new(),
+        constraints: Vec::new(),
+    };
+
+    let mut track = SavedCaptionTrack {
+        id: Uuid::new_v4().to_string(),
+        name: settings.name.clone(),
+        style: settings.style,
+        segments: Vec::new(),
+    };
+
+    let font_size = settings.font_size;
+    for (index, segment) in segments.iter().enumerate() {
+        let start_ms = segment.start_ms + settings.offset_ms;
+        let end_ms = (segment.end_ms + settings.offset_ms).min(sequence_duration_ms);
+        if end_ms <= start_ms.max(0) {
+            continue;
+        }
+
+        let text = wrap_caption(&segment.text, settings.max_line_chars);
+        // roughly the text's size, fonts vary
+        let longest_line = text.lines().map(|l| l.chars().count()).max().unwrap_or(0) as i32;
+        let line_count = text.lines().count().max(1) as i32;
+        let dimensions = (
+            (longest_line * font_size * 3 / 5) + font_size,
+            line_count * font_size * 8 / 5,
+        );
+        // bottom centered, growing upwards with more lines
+        let position = SavedPoint {
+            x: canvas_size.width as i32 / 2,
+            y: CANVAS_TOP as i32 + canvas_size.height as i32 - CAPTION_MARGIN - dimensions.1 / 2,
+        };
+
+        let text_id = Uuid::new_v4().to_string();
+        captions.active_text_items.push(SavedTextRendererConfig {
+            id: text_id.clone(),
+            name: format!("{} {}", settings.name, index + 1),
+            text,
+            font_family: settings.font_family.clone(),
+            font_size,
+            dimensions,
+            position: position.clone(),
+            layer: settings.layer,
+            color: settings.color,
+            background_fill: settings.background_fill,
+            tags: Vec::new(),
+            warp: None,
+            locked: false,
+            hidden: false,
+            opacity: 100,
+            blend_mode: BlendMode::default(),
+            mask_target_id: None,
+            mask_mode: MaskMode::default(),
+        });
+
+        let position = KeyframeValue::Position([position.x, position.y]);
+        captions.polygon_motion_paths.push(AnimationData {
+            id: Uuid::new_v4().to_string(),
+            object_type: ObjectType::TextItem,
+            polygon_id: text_id.clone(),
+            duration: Duration::from_millis(sequence_duration_ms.max(0) as u64),
+            start_time_ms: 0,
+            properties: vec![
+                keyframe_property(
+                    "Position",
+                    "position",
+                    &[(0, position.clone()), (sequence_duration_ms, position)],
+                ),
+                keyframe_property(
+                    "Opacity",
+                    "opacity",
+                    &shown_between(start_ms, end_ms, sequence_duration_ms),
+                ),
+            ],
+            position: [0, 0],
+            motion_blur: 0,
+        });
+
+        track.segments.push(CaptionSegment {
+            start_ms,
+            end_ms,
+            text: segment.text.clone(),
+            text_id: Some(text_id),
+        });
+    }
+
+    captions.caption_tracks.push(track);
+
+    captions
+}
+
+/// Subtitle files captions are read from and written to
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt, // WebVTT
+}
+
+impl SubtitleFormat {
+    /// From a file's extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+
+        match extension.as_str() {
+            "srt" => Some(SubtitleFormat::Srt),
+            "vtt" => Some(SubtitleFormat::Vtt),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "srt",
+            SubtitleFormat::Vtt => "vtt",
+        }
+    }
+}
+
+/// Captions written out as a subtitle file
+pub fn captions_to_subtitles(segments: &[CaptionSegment], format: SubtitleFormat) -> String {
+    let mut subtitles = match format {
+        SubtitleFormat::Srt => String::new(),
+        SubtitleFormat::Vtt => "WEBVTT\n\n".to_string(),
+    };
+
+    for (index, segment) in segments.iter().enumerate() {
+        subtitles.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            index + 1,
+            subtitle_timestamp(segment.start_ms, format),
+            subtitle_timestamp(segment.end_ms, format),
+            segment.text.trim()
+        ));
+    }
+
+    subtitles
+}
+
+/// Reads the cues of an SRT or WebVTT file. Cue settings, styling and notes are dropped,
+/// as captions take their look from their track.
+pub fn parse_subtitles(text: &str) -> Result<Vec<CaptionSegment>, String> {
+    let text = text.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let mut segments = Vec::new();
+
+    for block in text.split("\n\n") {
+        let mut lines = block.lines().map(str::trim).filter(|l| !l.is_empty());
+        // cue numbers and ids come before the timing line
+        let Some(timing) = lines.by_ref().find(|l| l.contains("-->")) else {
+            continue;
+        };
+
+        let (start, end) = timing
+            .split_once("-->")
+            .ok_or_else(|| format!("Couldn't read cue timing {}", timing))?;
+        // WebVTT cue settings follow the end time
+        let end = end.split_whitespace().next().unwrap_or("");
+        let start_ms = parse_subtitle_timestamp(start.trim())?;
+        let end_ms = parse_subtitle_timestamp(end)?;
+
+        let text = lines
+            .map(strip_subtitle_tags)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if text.trim().is_empty() || end_ms <= start_ms {
+            continue;
+        }
+
+        segments.push(CaptionSegment {
+            start_ms,
+            end_ms,
+            text,
+            text_id: None,
+        });
+    }
+
+    if segments.is_empty() {
+        return Err("No subtitles found".to_string());
+    }
+
+    Ok(segments)
+}
+
+/// Removes caption text items and their animations, for exports that leave captions to a
+/// subtitle file rather than burning them in. Caption tracks are kept for the file.
+pub fn strip_caption_items(sequences: &mut [Sequence]) {
+    for sequence in sequences {
+        let caption_ids: Vec<String> = sequence
+            .caption_tracks
+            .iter()
+            .flat_map(|t| &t.segments)
+            .filter_map(|s| s.text_id.clone())
+            .collect();
+
+        sequence
+            .active_text_items
+            .retain(|t| !caption_ids.contains(&t.id));
+        sequence
+            .polygon_motion_paths
+            .retain(|a| !caption_ids.contains(&a.polygon_id));
+    }
+}
+
+/// Hours, minutes, seconds and milliseconds, as 00:01:02,345 or 00:01:02.345 for WebVTT
+fn subtitle_timestamp(time_ms: i32, format: SubtitleFormat) -> String {
+    let time_ms = time_ms.max(0);
+    let separator = match format {
+        SubtitleFormat::Srt => ',',
+        SubtitleFormat::Vtt => '.',
+    };
+
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        time_ms / 3_600_000,
+        time_ms / 60_000 % 60,
+        time_ms / 1000 % 60,
+        separator,
+        time_ms % 1000
+    )
+}
+
+/// Reads 00:01:02,345, 00:01:02.345 or WebVTT's shorter 01:02.345
+fn parse_subtitle_timestamp(timestamp: &str) -> Result<i32, String> {
+    let invalid = || format!("Couldn't read subtitle time {}", timestamp);
+    let number = |digits: &str| {
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        digits.parse::<i32>().map_err(|_| invalid())
+    };
+
+    let (clock, millis) = timestamp.rsplit_once([',', '.']).ok_or_else(invalid)?;
+    if millis.len() != 3 {
+        return Err(invalid());
+    }
+    let millis = number(millis)?;
+
+    let fields: Vec<&str> = clock.split(':').collect();
+    if !(2..=3).contains(&fields.len()) {
+        return Err(invalid());
+    }
+
+    let mut seconds: i32 = 0;
+    for field in fields {
+        let value = number(field)?;
+        seconds = seconds
+            .checked_mul(60)
+            .and_then(|s| s.checked_add(value))
+            .ok_or_else(invalid)?;
+    }
+
+    seconds
+        .checked_mul(1000)
+        .and_then(|ms| ms.checked_add(millis))
+        .ok_or_else(invalid)
+}
+
+/// Drops markup such as <i> and <c.yellow>, and SRT position tags such as {\an8}
+fn strip_subtitle_tags(line: &str) -> String {
+    let mut text = String::new();
+    let mut closing = None;
+
+    for c in line.chars() {
+        match (closing, c) {
+            (None, '<') => closing = Some('>'),
+            (None, '{') => closing = Some('}'),
+            (Some(end), c) if c == end => closing = None,
+            (Some(_), _) => {}
+            (None, c) => text.push(c),
+        }
+    }
+
+    text
+}
+
+/// Opacity keyframes hiding a caption outside its time, cutting rather than fading
+fn shown_between(
+    start_ms: i32,
+    end_ms: i32,
+    sequence_duration_ms: i32,
+) -> Vec<(i32, KeyframeValue)> {
+    let mut values = Vec::new();
+
+    if start_ms > 0 {
+        values.push((0, KeyframeValue::Opacity(0)));
+        values.push((start_ms - 1, KeyframeValue::Opacity(0)));
+    }
+    values.push((start_ms.max(0), KeyframeValue::Opacity(100)));
+    values.push((end_ms, KeyframeValue::Opacity(100)));
+    if end_ms < sequence_duration_ms {
+        values.push((end_ms + 1, KeyframeValue::Opacity(0)));
+        values.push((sequence_duration_ms, KeyframeValue::Opacity(0)));
+    }
+
+    values
+}
+
+/// Breaks a caption into lines of at most max_chars, between words where it can. Line
+/// breaks already in the caption are kept.
+fn wrap_caption(text: &str, max_chars: usize) -> String {
+    let mut lines: Vec<String> = Vec::new();
+
+    for text_line in text.lines() {
+        let mut line = String::new();
+        for word in text_line.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_srt_and_vtt_timestamps() {
+        assert_eq!(parse_subtitle_timestamp("00:01:02,345"), Ok(62_345));
+        assert_eq!(parse_subtitle_timestamp("01:00:00.001"), Ok(3_600_001));
+        assert_eq!(parse_subtitle_timestamp("01:02.345"), Ok(62_345));
+    }
+
+    #[test]
+    fn rejects_malformed_timestamps() {
+        for timestamp in [
+            "62.345",
+            "00:00:01:02.345",
+            "00:01:02,34",
+            "00:01:02,3456",
+            "00:01:02,-12",
+            "00:-1:02,345",
+            "00::02,345",
+            "00:01:02",
+            "999999:00:00,000",
+        ] {
+            assert!(
+                parse_subtitle_timestamp(timestamp).is_err(),
+                "{} was read",
+                timestamp
+            );
+        }
+    }
+}
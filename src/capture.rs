@@ -1,4 +1,4 @@
-use device_query::{DeviceQuery, DeviceState, MouseState};
+use device_query::{DeviceQuery, DeviceState, Keycode, MouseState};
 use serde_json::json;
 use std::path::PathBuf;
 use std::time::{Duration, SystemTime};
@@ -30,6 +30,11 @@ use windows_capture::settings::{
     MinimumUpdateIntervalSettings, SecondaryWindowSettings, Settings,
 };
 
+/// How often the mouse position is recorded
+const POSITION_INTERVAL_MS: u128 = 100;
+/// How often buttons and keys are polled, short enough to catch quick clicks
+const INPUT_POLL_INTERVAL_MS: u64 = 10;
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct RectInfo {
     pub left: i32,
@@ -50,6 +55,7 @@ pub struct WindowInfo {
 #[derive(Clone)]
 pub struct MouseTrackingState {
     pub mouse_positions: Arc<Mutex<Vec<serde_json::Value>>>,
+    pub input_events: Arc<Mutex<Vec<InputEvent>>>,
     pub start_time: SystemTime,
     pub is_tracking: Arc<AtomicBool>,
     pub is_recording: Arc<AtomicBool>,
@@ -62,6 +68,22 @@ pub struct MousePosition {
     pub timestamp: u128,
 }
 
+/// A click or key press during a recording, timed like mouse positions
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum InputEvent {
+    Click {
+        x: f32,
+        y: f32,
+        button: usize, // 1 is left, 2 right and 3 middle
+        timestamp: u128,
+    },
+    Keys {
+        keys: Vec<String>, // every key held when the last one went down, like ["LControl", "C"]
+        timestamp: u128,
+    },
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct SourceData {
     pub id: String,
@@ -77,12 +99,14 @@ pub struct StCapture {
     pub state: MouseTrackingState,
     pub capture_dir: PathBuf,
     pub video_completion_callback: Option<Arc<dyn Fn(String) + Send + Sync + 'static>>,
+    pub capture_cursor: bool, // off leaves the cursor out, for drawing a custom one over it
 }
 
 impl StCapture {
     pub fn new(capture_dir: PathBuf) -> StCapture {
         let state = MouseTrackingState {
             mouse_positions: Arc::new(Mutex::new(Vec::new())),
+            input_events: Arc::new(Mutex::new(Vec::new())),
             start_time: SystemTime::now(),
             is_tracking: Arc::new(AtomicBool::new(false)),
             is_recording: Arc::new(AtomicBool::new(false)),
@@ -91,7 +115,8 @@ impl StCapture {
         return Self { 
             state, 
             capture_dir, 
-            video_completion_callback: None 
+            video_completion_callback: None,
+            capture_cursor: true,
         };
     }
 
@@ -143,16 +168,41 @@ impl StCapture {
         self.state.is_tracking.store(true, Ordering::SeqCst);
 
         let mouse_positions = self.state.mouse_positions.clone();
+        let input_events = self.state.input_events.clone();
         let start_time = self.state.start_time;
         let is_tracking = self.state.is_tracking.clone();
 
         thread::spawn(move || {
             let device_state = DeviceState::new();
+            let mut last_buttons: Vec<bool> = Vec::new();
+            let mut last_keys: Vec<Keycode> = Vec::new();
+            let mut last_position_time: Option<u128> = None;
+
             while is_tracking.load(Ordering::SeqCst) {
                 let mouse: MouseState = device_state.get_mouse();
+                let keys = device_state.get_keys();
                 let now = SystemTime::now();
                 let timestamp = now.duration_since(start_time).unwrap().as_millis();
 
+                let new_events =
+                    input_events_since(&mouse, &keys, &last_buttons, &last_keys, timestamp);
+                if !new_events.is_empty() {
+                    if let Ok(mut existing_events) = input_events.lock() {
+                        existing_events.extend(new_events);
+                    }
+                }
+                last_buttons = mouse.button_pressed.clone();
+                last_keys = keys;
+
+                thread::sleep(Duration::from_millis(INPUT_POLL_INTERVAL_MS));
+
+                let position_due = last_position_time
+                    .map(|last| timestamp - last >= POSITION_INTERVAL_MS)
+                    .unwrap_or(true);
+                if !position_due {
+                    continue;
+                }
+
                 if let Ok(existing_positions) = &mut mouse_positions.try_lock() {
                     // println!(
                     //     "Tracking mouse {:?} {:?} {:?}",
@@ -168,7 +218,7 @@ impl StCapture {
                     });
 
                     existing_positions.push(position);
-                    thread::sleep(Duration::from_millis(100));
+                    last_position_time = Some(timestamp);
                 } else {
                     println!("Can't acquire lock in stop_mouse_tracking");
                 }
@@ -198,8 +248,19 @@ impl StCapture {
         )
         .map_err(|e| e.to_string())?;
 
+        let input_events = self.state.input_events.lock().unwrap().clone();
+
+        println!("Saving input events {:?}", input_events.len());
+
+        fs::write(
+            file_path.with_file_name("inputEvents.json"),
+            serde_json::to_string_pretty(&input_events).unwrap(),
+        )
+        .map_err(|e| e.to_string())?;
+
         // reset mouse positions
         self.state.mouse_positions = Arc::new(Mutex::new(Vec::new()));
+        self.state.input_events = Arc::new(Mutex::new(Vec::new()));
 
         Ok(file_path
             .to_str()
@@ -283,6 +344,12 @@ impl StCapture {
         // Clone the callback Arc for use in the capture settings
         let callback_clone = self.video_completion_callback.clone();
 
+        let cursor_settings = if self.capture_cursor {
+            CursorCaptureSettings::Default
+        } else {
+            CursorCaptureSettings::WithoutCursor
+        };
+
         // hardcode hd for testing to avoid miscolored recording,
         // TBD: scale to fullscreen width / height for users
         if width > 1920 || height > 1080 {
@@ -308,7 +375,7 @@ impl StCapture {
                 // Item to capture
                 primary_monitor,
                 // Capture cursor settings
-                CursorCaptureSettings::Default,
+                cursor_settings,
                 // Draw border settings
                 DrawBorderSettings::Default,
                 // Secondary window settings, if you want to include secondary windows in the capture
@@ -343,7 +410,7 @@ impl StCapture {
                 // Item to capture
                 target_window,
                 // Capture cursor settings
-                CursorCaptureSettings::Default,
+                cursor_settings,
                 // Draw border settings
                 DrawBorderSettings::Default,
                 // Secondary window settings, if you want to include secondary windows in the capture
@@ -410,6 +477,38 @@ impl StCapture {
     }
 }
 
+/// Clicks for buttons that just went down, and the held keys whenever a key just went down
+fn input_events_since(
+    mouse: &MouseState,
+    keys: &[Keycode],
+    last_buttons: &[bool],
+    last_keys: &[Keycode],
+    timestamp: u128,
+) -> Vec<InputEvent> {
+    let mut events = Vec::new();
+
+    for (button, pressed) in mouse.button_pressed.iter().enumerate() {
+        let was_pressed = last_buttons.get(button).copied().unwrap_or(false);
+        if *pressed && !was_pressed {
+            events.push(InputEvent::Click {
+                x: mouse.coords.0 as f32,
+                y: mouse.coords.1 as f32,
+                button,
+                timestamp,
+            });
+        }
+    }
+
+    if keys.iter().any(|key| !last_keys.contains(key)) {
+        events.push(InputEvent::Keys {
+            keys: keys.iter().map(|key| format!("{:?}", key)).collect(),
+            timestamp,
+        });
+    }
+
+    events
+}
+
 pub fn get_sources() -> Result<Vec<WindowInfo>, String> {
     // use windows::Win32::Foundation::BOOLEAN;
 
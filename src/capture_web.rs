@@ -0,0 +1,142 @@
+// Stands in for capture.rs on wasm32, where there's no screen capture or global mouse.
+// Keeps the same types so the editor builds unchanged, every capture call returns an error.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+
+const UNAVAILABLE: &str = "Screen capture isn't available in the browser";
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RectInfo {
+    pub left: i32,
+    pub right: i32,
+    pub top: i32,
+    pub bottom: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct WindowInfo {
+    pub hwnd: usize,
+    pub title: String,
+    pub rect: RectInfo,
+}
+
+#[derive(Clone)]
+pub struct MouseTrackingState {
+    pub mouse_positions: Arc<Mutex<Vec<serde_json::Value>>>,
+    pub input_events: Arc<Mutex<Vec<InputEvent>>>,
+    pub is_tracking: Arc<AtomicBool>,
+    pub is_recording: Arc<AtomicBool>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+pub struct MousePosition {
+    pub x: f32,
+    pub y: f32,
+    pub timestamp: u128,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum InputEvent {
+    Click {
+        x: f32,
+        y: f32,
+        button: usize,
+        timestamp: u128,
+    },
+    Keys {
+        keys: Vec<String>,
+        timestamp: u128,
+    },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SourceData {
+    pub id: String,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub x: i32,
+    pub y: i32,
+    pub scale_factor: f32,
+}
+
+pub struct StCapture {
+    pub state: MouseTrackingState,
+    pub capture_dir: PathBuf,
+    pub video_completion_callback: Option<Arc<dyn Fn(String) + Send + Sync + 'static>>,
+    pub capture_cursor: bool,
+}
+
+impl StCapture {
+    pub fn new(capture_dir: PathBuf) -> StCapture {
+        StCapture {
+            state: MouseTrackingState {
+                mouse_positions: Arc::new(Mutex::new(Vec::new())),
+                input_events: Arc::new(Mutex::new(Vec::new())),
+                is_tracking: Arc::new(AtomicBool::new(false)),
+                is_recording: Arc::new(AtomicBool::new(false)),
+            },
+            capture_dir,
+            video_completion_callback: None,
+            capture_cursor: true,
+        }
+    }
+
+    pub fn set_video_completion_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(String) + Send + Sync + 'static,
+    {
+        self.video_completion_callback = Some(Arc::new(callback));
+    }
+
+    pub fn save_source_data(
+        &self,
+        hwnd: usize,
+        current_project_id: String,
+    ) -> Result<serde_json::Value, String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn start_mouse_tracking(&mut self) -> Result<bool, String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn stop_mouse_tracking(&mut self, project_id: String) -> Result<String, String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn get_project_data(
+        &self,
+        current_project_id: String,
+    ) -> Result<serde_json::Value, String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn start_video_capture(
+        &mut self,
+        hwnd: usize,
+        width: u32,
+        height: u32,
+        project_id: String,
+    ) -> Result<(), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn stop_video_capture(&mut self, project_id: String) -> Result<(String, String), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+}
+
+pub fn get_sources() -> Result<Vec<WindowInfo>, String> {
+    Err(UNAVAILABLE.to_string())
+}
+
+pub fn get_window_info_by_usize(hwnd_value: usize) -> Result<WindowInfo, String> {
+    Err(UNAVAILABLE.to_string())
+}
@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::animations::{AnimationData, KeyType};
+use crate::motion_presets::{MotionPreset, PresetSettings, SlideEdge};
+
+/// Which of the objects enters first
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum StaggerOrder {
+    #[default]
+    LeftToRight,
+    ByLayer,     // front to back
+    Random(u32), // shuffled by this seed, the same each time
+}
+
+/// One entrance played by several objects in turn, such as the lines of a title card
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct StaggerTemplate {
+    pub entrance: Option<PresetSettings>, // None staggers the objects' own animations
+    pub delay_ms: i32,                    // from one object starting to the next
+    pub order: StaggerOrder,
+}
+
+impl StaggerTemplate {
+    /// Lines rising into place one after another
+    pub fn title_card() -> Self {
+        StaggerTemplate {
+            entrance: Some(PresetSettings {
+                preset: MotionPreset::SlideIn(SlideEdge::Bottom),
+                duration_ms: 800,
+                intensity: 30,
+            }),
+            delay_ms: 150,
+            order: StaggerOrder::ByLayer,
+        }
+    }
+
+    /// Objects popping in across the canvas
+    pub fn cascade() -> Self {
+        StaggerTemplate {
+            entrance: Some(PresetSettings::new(MotionPreset::Pop)),
+            delay_ms: 100,
+            order: StaggerOrder::LeftToRight,
+        }
+    }
+}
+
+/// Indices of objects, given as their x position and layer, in the order they enter
+pub fn stagger_sequence(objects: &[(f32, i32)], order: StaggerOrder) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..objects.len()).collect();
+
+    match order {
+        StaggerOrder::LeftToRight => {
+            indices.sort_by(|a, b| objects[*a].0.total_cmp(&objects[*b].0));
+        }
+        // lower layers are drawn in front
+        StaggerOrder::ByLayer => indices.sort_by_key(|index| objects[*index].1),
+        StaggerOrder::Random(seed) => {
+            indices.shuffle(&mut StdRng::seed_from_u64(seed as u64));
+        }
+    }
+
+    indices
+}
+
+/// Holds an animation at its first keyframes for a delay before playing it, so an entrance
+/// stays hidden until its turn
+pub fn delay_animation(animation: &mut AnimationData, delay_ms: i32) {
+    if delay_ms <= 0 {
+        return;
+    }
+    let delay = Duration::from_millis(delay_ms as u64);
+
+    for property in animation.properties.iter_mut() {
+        for keyframe in property.keyframes.iter_mut() {
+            keyframe.time += delay;
+            if let KeyType::Range(range) = &mut keyframe.key_type {
+                range.end_time += delay;
+            }
+        }
+
+        let Some(first) = property.keyframes.iter().min_by_key(|k| k.time) else {
+            continue;
+        };
+        let mut hold = first.clone();
+        hold.id = Uuid::new_v4().to_string();
+        hold.time = Duration::ZERO;
+        hold.key_type = KeyType::Frame;
+        property.keyframes.insert(0, hold);
+    }
+
+    animation.duration += delay;
+}
@@ -0,0 +1,216 @@
+use uuid::Uuid;
+
+use crate::animations::{AnimationData, AnimationProperty, KeyframeValue, ObjectType, Sequence};
+use crate::particles::SavedParticleEmitterConfig;
+use crate::polygon::SavedPolygonConfig;
+use crate::st_image::SavedStImageConfig;
+use crate::st_video::SavedStVideoConfig;
+use crate::text_due::SavedTextRendererConfig;
+
+/// How far a duplicate is shifted from its original, so both remain visible
+pub const DUPLICATE_OFFSET: i32 = 20;
+
+/// A saved object's config, as held by the clipboard
+#[derive(Clone, Debug)]
+pub enum ClipboardObject {
+    Polygon(SavedPolygonConfig),
+    Text(SavedTextRendererConfig),
+    Image(SavedStImageConfig),
+    Video(SavedStVideoConfig),
+    ParticleEmitter(SavedParticleEmitterConfig),
+}
+
+/// A copied object along with its animation, ready to be pasted into any sequence
+#[derive(Clone, Debug)]
+pub struct ClipboardItem {
+    pub source_sequence_id: String,
+    pub object: ClipboardObject,
+    pub animation: Option<AnimationData>,
+}
+
+impl ClipboardItem {
+    pub fn object_type(&self) -> ObjectType {
+        match self.object {
+            ClipboardObject::Polygon(_) => ObjectType::Polygon,
+            ClipboardObject::Text(_) => ObjectType::TextItem,
+            ClipboardObject::Image(_) => ObjectType::ImageItem,
+            ClipboardObject::Video(_) => ObjectType::VideoItem,
+            ClipboardObject::ParticleEmitter(_) => ObjectType::ParticleEmitter,
+        }
+    }
+}
+
+/// Copies an object and its animation out of a sequence
+pub fn copy_from_sequence(
+    sequence: &Sequence,
+    object_id: &str,
+    object_type: &ObjectType,
+) -> Option<ClipboardItem> {
+    let object = match object_type {
+        ObjectType::Polygon => sequence
+            .active_polygons
+            .iter()
+            .find(|p| p.id == object_id)
+            .map(|p| ClipboardObject::Polygon(p.clone())),
+        ObjectType::TextItem => sequence
+            .active_text_items
+            .iter()
+            .find(|t| t.id == object_id)
+            .map(|t| ClipboardObject::Text(t.clone())),
+        ObjectType::ImageItem => sequence
+            .active_image_items
+            .iter()
+            .find(|i| i.id == object_id)
+            .map(|i| ClipboardObject::Image(i.clone())),
+        ObjectType::VideoItem => sequence
+            .active_video_items
+            .iter()
+            .find(|v| v.id == object_id)
+            .map(|v| ClipboardObject::Video(v.clone())),
+        ObjectType::ParticleEmitter => sequence
+            .active_particle_emitters
+            .iter()
+            .find(|e| e.id == object_id)
+            .map(|e| ClipboardObject::ParticleEmitter(e.clone())),
+        // groups only reference their children, copy the children instead
+        ObjectType::Group => None,
+    }?;
+
+    let animation = sequence
+        .polygon_motion_paths
+        .iter()
+        .find(|a| a.polygon_id == object_id)
+        .cloned();
+
+    Some(ClipboardItem {
+        source_sequence_id: sequence.id.clone(),
+        object,
+        animation,
+    })
+}
+
+fn clone_property(property: &AnimationProperty, offset: [i32; 2]) -> AnimationProperty {
+    let mut property = property.clone();
+
+    property.keyframes.iter_mut().for_each(|k| {
+        k.id = Uuid::new_v4().to_string();
+        if let KeyframeValue::Position(position) = k.value {
+            k.value = KeyframeValue::Position([position[0] + offset[0], position[1] + offset[1]]);
+        }
+    });
+    property.children = property
+        .children
+        .iter()
+        .map(|c| clone_property(c, offset))
+        .collect();
+
+    property
+}
+
+/// Clones animation data for a new object, with fresh keyframe ids and positions shifted by the offset
+pub fn clone_animation_data(
+    animation: &AnimationData,
+    object_id: String,
+    offset: [i32; 2],
+) -> AnimationData {
+    AnimationData {
+        id: Uuid::new_v4().to_string(),
+        object_type: animation.object_type.clone(),
+        polygon_id: object_id,
+        duration: animation.duration,
+        start_time_ms: animation.start_time_ms,
+        properties: animation
+            .properties
+            .iter()
+            .map(|p| clone_property(p, offset))
+            .collect(),
+        position: animation.position,
+        motion_blur: animation.motion_blur,
+    }
+}
+
+/// Gives the copied object a fresh id and name and shifts it by the offset,
+/// returning a sequence holding only the new object so it can be restored and merged
+pub fn prepare_paste(
+    item: &ClipboardItem,
+    target_sequence_id: String,
+    offset: [i32; 2],
+) -> (String, Sequence) {
+    let new_id = Uuid::new_v4().to_string();
+
+    let mut sequence = Sequence {
+        id: target_sequence_id,
+        name: String::new(),
+        background_fill: None,
+        duration_ms: 0,
+        active_polygons: Vec::new(),
+        polygon_motion_paths: Vec::new(),
+        active_text_items: Vec::new(),
+        active_image_items: Vec::new(),
+        active_video_items: Vec::new(),
+        tags: Vec::new(),
+        outlined_text_items: Vec::new(),
+        active_groups: Vec::new(),
+        active_particle_emitters: Vec::new(),
+        camera_animation: None,
+        active_callouts: Vec::new(),
+        active_spotlights: Vec::new(),
+        caption_tracks: Vec::new(),
+        constraints: Vec::new(),
+    };
+
+    match &item.object {
+        ClipboardObject::Polygon(p) => {
+            let mut p = p.clone();
+            p.id = new_id.clone();
+            p.name = format!("{} Copy", p.name);
+            p.position.x += offset[0];
+            p.position.y += offset[1];
+            // a copied glyph no longer belongs to its outlined text
+            if let Some(outline) = &mut p.outline {
+                outline.source = None;
+            }
+            sequence.active_polygons.push(p);
+        }
+        ClipboardObject::Text(t) => {
+            let mut t = t.clone();
+            t.id = new_id.clone();
+            t.name = format!("{} Copy", t.name);
+            t.position.x += offset[0];
+            t.position.y += offset[1];
+            sequence.active_text_items.push(t);
+        }
+        ClipboardObject::Image(i) => {
+            let mut i = i.clone();
+            i.id = new_id.clone();
+            i.name = format!("{} Copy", i.name);
+            i.position.x += offset[0];
+            i.position.y += offset[1];
+            sequence.active_image_items.push(i);
+        }
+        ClipboardObject::Video(v) => {
+            let mut v = v.clone();
+            v.id = new_id.clone();
+            v.name = format!("{} Copy", v.name);
+            v.position.x += offset[0];
+            v.position.y += offset[1];
+            sequence.active_video_items.push(v);
+        }
+        ClipboardObject::ParticleEmitter(e) => {
+            let mut e = e.clone();
+            e.id = new_id.clone();
+            e.name = format!("{} Copy", e.name);
+            e.position.x += offset[0];
+            e.position.y += offset[1];
+            sequence.active_particle_emitters.push(e);
+        }
+    }
+
+    if let Some(animation) = &item.animation {
+        sequence
+            .polygon_motion_paths
+            .push(clone_animation_data(animation, new_id.clone(), offset));
+    }
+
+    (new_id, sequence)
+}
@@ -0,0 +1,305 @@
+use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
+use wgpu::util::DeviceExt;
+
+use crate::shader_reload::{with_validation, ShaderWatcher};
+
+/// The color space a display shows the surface in. Exports are always BT.709 with the
+/// sRGB curve, so previews on other displays are converted to look the same.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum DisplayColorSpace {
+    /// Standard displays, shown as is
+    #[default]
+    Srgb,
+    /// Wide-gamut displays, where unconverted colors look oversaturated
+    DisplayP3,
+    /// Float surfaces on Windows HDR, where 1.0 is 80 nits
+    ExtendedLinearSrgb,
+    /// 10 bit HDR surfaces, BT.2020 primaries with the PQ curve
+    Hdr10,
+}
+
+impl DisplayColorSpace {
+    /// Best guess from the surface format, as wgpu only reports formats.
+    /// Wide-gamut SDR displays use ordinary formats, so set DisplayP3 from the OS's display info.
+    pub fn from_surface_format(format: wgpu::TextureFormat) -> Self {
+        match format {
+            wgpu::TextureFormat::Rgba16Float => DisplayColorSpace::ExtendedLinearSrgb,
+            wgpu::TextureFormat::Rgb10a2Unorm => DisplayColorSpace::Hdr10,
+            _ => DisplayColorSpace::Srgb,
+        }
+    }
+
+    /// BT.709 to display primaries, by columns
+    fn gamut_matrix(&self) -> [[f32; 4]; 3] {
+        match self {
+            DisplayColorSpace::Srgb | DisplayColorSpace::ExtendedLinearSrgb => [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+            ],
+            DisplayColorSpace::DisplayP3 => [
+                [0.8225, 0.0332, 0.0171, 0.0],
+                [0.1774, 0.9669, 0.0724, 0.0],
+                [0.0000, 0.0000, 0.9108, 0.0],
+            ],
+            DisplayColorSpace::Hdr10 => [
+                [0.6274, 0.0691, 0.0164, 0.0],
+                [0.3293, 0.9195, 0.0880, 0.0],
+                [0.0433, 0.0114, 0.8956, 0.0],
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ColorManagementSettings {
+    pub display: DisplayColorSpace,
+    pub sdr_white_nits: f32, // brightness of white on HDR displays
+    pub peak_nits: f32,      // HDR display peak, highlights above it are tone mapped
+    pub tone_map: bool,
+}
+
+impl Default for ColorManagementSettings {
+    fn default() -> Self {
+        ColorManagementSettings {
+            display: DisplayColorSpace::Srgb,
+            sdr_white_nits: 203.0, // BT.2408 reference white
+            peak_nits: 1000.0,
+            tone_map: true,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+struct ColorManagementUniform {
+    gamut: [[f32; 4]; 3],
+    params: [f32; 4], // transfer, white scale, peak, unused
+}
+
+impl ColorManagementUniform {
+    fn new(settings: &ColorManagementSettings, surface_format: wgpu::TextureFormat) -> Self {
+        // transfer: 0 sRGB curve, 1 linear, 2 PQ
+        let (transfer, nits_per_unit) = match settings.display {
+            DisplayColorSpace::Srgb | DisplayColorSpace::DisplayP3 => {
+                // sRGB surface formats apply the curve themselves
+                (if surface_format.is_srgb() { 1.0 } else { 0.0 }, 0.0)
+            }
+            DisplayColorSpace::ExtendedLinearSrgb => (1.0, 80.0),
+            DisplayColorSpace::Hdr10 => (2.0, 10000.0),
+        };
+
+        let (white_scale, peak) = if nits_per_unit > 0.0 {
+            (
+                settings.sdr_white_nits / nits_per_unit,
+                if settings.tone_map {
+                    settings.peak_nits / nits_per_unit
+                } else {
+                    0.0
+                },
+            )
+        } else {
+            (1.0, 0.0)
+        };
+
+        ColorManagementUniform {
+            gamut: settings.display.gamut_matrix(),
+            params: [transfer, white_scale, peak, 0.0],
+        }
+    }
+}
+
+/// Converts a rendered preview to the display's color space. Render the preview into a
+/// non-sRGB texture such as Bgra8Unorm, as the main pipeline does, then draw this into
+/// the surface with that texture bound.
+pub struct ColorManagementPass {
+    pub settings: ColorManagementSettings,
+    pub surface_format: wgpu::TextureFormat,
+    pub pipeline: wgpu::RenderPipeline,
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    uniform_buffer: wgpu::Buffer,
+}
+
+impl ColorManagementPass {
+    pub fn new(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        settings: ColorManagementSettings,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Color Management Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                include_str!("export/shaders/color_management.wgsl").into(),
+            ),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Color Management Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline = create_pipeline(device, &shader, &bind_group_layout, surface_format);
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Color Management Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[ColorManagementUniform::new(
+                &settings,
+                surface_format,
+            )]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        ColorManagementPass {
+            settings,
+            surface_format,
+            pipeline,
+            bind_group_layout,
+            sampler,
+            uniform_buffer,
+        }
+    }
+
+    /// Rebuilds the pipeline from color_management.wgsl in the watched folder, keeping the
+    /// current one if it doesn't compile
+    pub fn reload_shader(
+        &mut self,
+        device: &wgpu::Device,
+        watcher: &ShaderWatcher,
+    ) -> Result<(), String> {
+        let shader = watcher.load(device, "color_management.wgsl")?;
+        self.pipeline = with_validation(device, || {
+            create_pipeline(
+                device,
+                &shader,
+                &self.bind_group_layout,
+                self.surface_format,
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Whether the pass changes anything, so hosts can skip the extra texture when it doesn't
+    pub fn is_needed(&self) -> bool {
+        self.settings.display != DisplayColorSpace::Srgb
+    }
+
+    pub fn update_settings(&mut self, queue: &wgpu::Queue, settings: ColorManagementSettings) {
+        self.settings = settings;
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[ColorManagementUniform::new(
+                &self.settings,
+                self.surface_format,
+            )]),
+        );
+    }
+
+    /// Binds the texture the preview was rendered into, recreate it when that texture changes
+    pub fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        source_view: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Color Management Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(source_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    /// Draws the converted preview over the whole render target
+    pub fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        bind_group: &'a wgpu::BindGroup,
+    ) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+fn create_pipeline(
+    device: &wgpu::Device,
+    shader: &wgpu::ShaderModule,
+    bind_group_layout: &wgpu::BindGroupLayout,
+    surface_format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Color Management Pipeline Layout"),
+        bind_group_layouts: &[bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Color Management Pipeline"),
+        layout: Some(&pipeline_layout),
+        multiview: None,
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format: surface_format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+    })
+}
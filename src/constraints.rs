@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+use crate::animations::ObjectType;
+
+/// Keeps one object at an offset from another, such as a label following a moving shape.
+/// Applied after keyframes each frame, so it overrides the pinned object's own position.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct PinConstraint {
+    pub id: String,
+    pub object_id: String, // the pinned object
+    pub object_type: ObjectType,
+    pub target_id: String, // the object it follows
+    pub target_type: ObjectType,
+    pub offset: [i32; 2], // from the target's position to the pinned object's
+}
+
+/// Whether pinning an object to a target would make a loop, including pinning an object
+/// to itself. Each object has at most one pin, so following targets finds any loop.
+pub fn would_cycle(constraints: &[PinConstraint], object_id: &str, target_id: &str) -> bool {
+    let mut current = target_id;
+
+    // a chain can't be longer than the constraints without looping
+    for _ in 0..=constraints.len() {
+        if current == object_id {
+            return true;
+        }
+        match constraints.iter().find(|c| c.object_id == current) {
+            Some(constraint) => current = &constraint.target_id,
+            None => return false,
+        }
+    }
+
+    true
+}
+
+/// Constraints ordered so targets that are pinned themselves are placed first. Constraints
+/// in a loop, which would_cycle keeps out, are left out.
+pub fn evaluation_order(constraints: &[PinConstraint]) -> Vec<&PinConstraint> {
+    let depth = |constraint: &PinConstraint| -> Option<usize> {
+        let mut depth = 0;
+        let mut current = &constraint.target_id;
+        while let Some(next) = constraints.iter().find(|c| &c.object_id == current) {
+            depth += 1;
+            if depth > constraints.len() {
+                return None;
+            }
+            current = &next.target_id;
+        }
+        Some(depth)
+    };
+
+    let mut ordered: Vec<(usize, &PinConstraint)> = constraints
+        .iter()
+        .filter_map(|c| depth(c).map(|d| (d, c)))
+        .collect();
+    ordered.sort_by_key(|(depth, _)| *depth);
+
+    ordered.into_iter().map(|(_, c)| c).collect()
+}
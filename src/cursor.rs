@@ -0,0 +1,283 @@
+use std::f32::consts::PI;
+
+use image::imageops::{resize, FilterType};
+use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::capture::{MousePosition, SourceData};
+
+/// How long a click squeezes the cursor for
+const CLICK_ANIMATION_MS: u128 = 300;
+/// Size of the built in cursors at scale 100, in source pixels
+const CURSOR_SIZE: u32 = 24;
+
+/// The graphic drawn in place of the recorded cursor
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum CursorStyle {
+    #[default]
+    Arrow,
+    Circle,        // a soft highlight, for recordings that still show the real cursor
+    Image(String), // path to a graphic with its hotspot at the top left
+}
+
+/// A cursor drawn over a captured video along its recorded mouse path. To hide the real
+/// cursor, record with StCapture::capture_cursor off, as it can't be removed afterwards.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct SavedCursorOverlay {
+    pub style: CursorStyle,
+    pub scale: i32,      // out of 100
+    pub color: [i32; 4], // for the built in styles
+    pub smoothing: i32,  // out of 100, 0 follows the recording exactly
+    pub click_animation: bool,
+}
+
+impl Default for SavedCursorOverlay {
+    fn default() -> Self {
+        SavedCursorOverlay {
+            style: CursorStyle::Arrow,
+            scale: 100,
+            color: [255, 255, 255, 255],
+            smoothing: 50,
+            click_animation: true,
+        }
+    }
+}
+
+/// A cursor ready to draw into decoded frames, which keeps it in previews and exports and
+/// under any zoom
+pub struct CursorOverlay {
+    pub config: SavedCursorOverlay,
+    sprite: RgbaImage,
+    hotspot: (f32, f32), // as a fraction of the sprite
+    path: Vec<MousePosition>,
+    clicks: Vec<u128>,
+}
+
+impl CursorOverlay {
+    /// clicks are timestamps from the recording's input events, for the click animation
+    pub fn new(
+        config: SavedCursorOverlay,
+        mouse_positions: &[MousePosition],
+        clicks: Vec<u128>,
+    ) -> Result<Self, String> {
+        let (sprite, hotspot) = match &config.style {
+            CursorStyle::Arrow => (arrow_sprite(config.color), (1.0 / 14.0, 1.0 / 22.0)),
+            CursorStyle::Circle => (circle_sprite(config.color), (0.5, 0.5)),
+            CursorStyle::Image(path) => (
+                image::open(path)
+                    .map_err(|e| format!("Couldn't open cursor image {}: {}", path, e))?
+                    .to_rgba8(),
+                (0.0, 0.0),
+            ),
+        };
+
+        Ok(CursorOverlay {
+            path: smooth_mouse_positions(mouse_positions, config.smoothing),
+            config,
+            sprite,
+            hotspot,
+            clicks,
+        })
+    }
+
+    /// Draws the cursor into a BGRA frame at a time in the recording. source_dimensions are
+    /// the recording's, which may be larger than the frame for proxies.
+    pub fn draw(
+        &self,
+        frame: &mut [u8],
+        frame_dimensions: (u32, u32),
+        source_dimensions: (u32, u32),
+        source_data: &SourceData,
+        time_ms: u128,
+    ) {
+        let (width, height) = frame_dimensions;
+        if frame.len() < width as usize * height as usize * 4 {
+            return;
+        }
+        let Some((x, y)) = position_at(&self.path, time_ms) else {
+            return;
+        };
+
+        let frame_scale = width as f32 / source_dimensions.0.max(1) as f32;
+        let mut scale = self.config.scale.max(1) as f32 / 100.0 * frame_scale;
+        if self.config.click_animation {
+            scale *= self.click_squeeze(time_ms);
+        }
+
+        let sprite_height = (CURSOR_SIZE as f32 * scale).round().max(1.0) as u32;
+        let sprite_width = ((self.sprite.width() as f32 / self.sprite.height().max(1) as f32)
+            * sprite_height as f32)
+            .round()
+            .max(1.0) as u32;
+        let sprite = resize(
+            &self.sprite,
+            sprite_width,
+            sprite_height,
+            FilterType::Triangle,
+        );
+
+        let left = ((x - source_data.x as f32) * frame_scale - self.hotspot.0 * sprite_width as f32)
+            as i32;
+        let top = ((y - source_data.y as f32) * height as f32 / source_dimensions.1.max(1) as f32
+            - self.hotspot.1 * sprite_height as f32) as i32;
+
+        for (sprite_x, sprite_y, pixel) in sprite.enumerate_pixels() {
+            let frame_x = left + sprite_x as i32;
+            let frame_y = top + sprite_y as i32;
+            if frame_x < 0 || frame_y < 0 || frame_x >= width as i32 || frame_y >= height as i32 {
+                continue;
+            }
+
+            let alpha = pixel[3] as f32 / 255.0;
+            if alpha <= 0.0 {
+                continue;
+            }
+
+            let offset = (frame_y as usize * width as usize + frame_x as usize) * 4;
+            // frames are BGRA
+            for (channel, source_channel) in [(0, 2), (1, 1), (2, 0)] {
+                let existing = frame[offset + channel] as f32;
+                frame[offset + channel] =
+                    (existing + (pixel[source_channel] as f32 - existing) * alpha).round() as u8;
+            }
+        }
+    }
+
+    /// Shrinks the cursor briefly after each click and springs it back
+    fn click_squeeze(&self, time_ms: u128) -> f32 {
+        self.clicks
+            .iter()
+            .filter(|click| **click <= time_ms && time_ms - **click < CLICK_ANIMATION_MS)
+            .map(|click| {
+                let progress = (time_ms - click) as f32 / CLICK_ANIMATION_MS as f32;
+                1.0 - 0.25 * (progress * PI).sin()
+            })
+            .fold(1.0, f32::min)
+    }
+}
+
+/// Evens out jitter in a recorded mouse path with an exponential filter run forwards then
+/// backwards, so the cursor doesn't lag behind the footage
+pub fn smooth_mouse_positions(positions: &[MousePosition], smoothing: i32) -> Vec<MousePosition> {
+    let mut smoothed = positions.to_vec();
+    if smoothing <= 0 || smoothed.len() < 3 {
+        return smoothed;
+    }
+
+    // how much of each new sample is kept, 1 keeps all of it
+    let alpha = 1.0 - smoothing.min(100) as f32 / 100.0 * 0.9;
+
+    for i in 1..smoothed.len() {
+        smoothed[i].x = smoothed[i - 1].x + (smoothed[i].x - smoothed[i - 1].x) * alpha;
+        smoothed[i].y = smoothed[i - 1].y + (smoothed[i].y - smoothed[i - 1].y) * alpha;
+    }
+    for i in (0..smoothed.len() - 1).rev() {
+        smoothed[i].x = smoothed[i + 1].x + (smoothed[i].x - smoothed[i + 1].x) * alpha;
+        smoothed[i].y = smoothed[i + 1].y + (smoothed[i].y - smoothed[i + 1].y) * alpha;
+    }
+
+    smoothed
+}
+
+/// The mouse position at a time, linearly between samples
+fn position_at(path: &[MousePosition], time_ms: u128) -> Option<(f32, f32)> {
+    let next_index = path.iter().position(|p| p.timestamp > time_ms);
+    let (start, end) = match next_index {
+        Some(0) => (path.first()?, path.first()?),
+        Some(index) => (&path[index - 1], &path[index]),
+        None => (path.last()?, path.last()?),
+    };
+
+    if end.timestamp <= start.timestamp {
+        return Some((start.x, start.y));
+    }
+
+    let progress = (time_ms - start.timestamp) as f32 / (end.timestamp - start.timestamp) as f32;
+    Some((
+        start.x + (end.x - start.x) * progress,
+        start.y + (end.y - start.y) * progress,
+    ))
+}
+
+/// The classic pointer, outlined in black so it shows on any background
+fn arrow_sprite(color: [i32; 4]) -> RgbaImage {
+    // drawn at 4x and downscaled for smooth edges
+    let points: [(f32, f32); 7] = [
+        (1.0, 1.0),
+        (1.0, 18.0),
+        (5.0, 14.0),
+        (8.0, 21.0),
+        (11.0, 20.0),
+        (8.0, 13.0),
+        (13.0, 13.0),
+    ];
+    let supersample = 4.0;
+    let (width, height) = (14, 22);
+
+    let inside = |x: f32, y: f32| -> bool {
+        // even-odd crossing test
+        let mut inside = false;
+        for i in 0..points.len() {
+            let (x0, y0) = points[i];
+            let (x1, y1) = points[(i + 1) % points.len()];
+            if (y0 > y) != (y1 > y) && x < x0 + (y - y0) / (y1 - y0) * (x1 - x0) {
+                inside = !inside;
+            }
+        }
+        inside
+    };
+
+    let outline = 1.0;
+    let large = RgbaImage::from_fn(
+        (width as f32 * supersample) as u32,
+        (height as f32 * supersample) as u32,
+        |x, y| {
+            let (x, y) = (
+                (x as f32 + 0.5) / supersample,
+                (y as f32 + 0.5) / supersample,
+            );
+            if inside(x, y) {
+                return to_rgba(color);
+            }
+
+            let near_edge = [
+                (-outline, 0.0),
+                (outline, 0.0),
+                (0.0, -outline),
+                (0.0, outline),
+            ]
+            .iter()
+            .any(|(dx, dy)| inside(x + dx, y + dy));
+            if near_edge {
+                Rgba([0, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        },
+    );
+
+    resize(&large, width, height, FilterType::Triangle)
+}
+
+/// A translucent dot centered on the pointer
+fn circle_sprite(color: [i32; 4]) -> RgbaImage {
+    let size = CURSOR_SIZE * 4;
+    let radius = size as f32 / 2.0;
+    let [r, g, b, a] = to_rgba(color).0;
+
+    let large = RgbaImage::from_fn(size, size, |x, y| {
+        let dx = x as f32 + 0.5 - radius;
+        let dy = y as f32 + 0.5 - radius;
+        if (dx * dx + dy * dy).sqrt() <= radius {
+            Rgba([r, g, b, a / 2])
+        } else {
+            Rgba([0, 0, 0, 0])
+        }
+    });
+
+    resize(&large, CURSOR_SIZE, CURSOR_SIZE, FilterType::Triangle)
+}
+
+fn to_rgba(color: [i32; 4]) -> Rgba<u8> {
+    Rgba(color.map(|channel| channel.clamp(0, 255) as u8))
+}
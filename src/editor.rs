@@ -1,38 +1,117 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use cgmath::{Point3, Vector3, Vector4};
 use crate::gpu_resources::GpuResources;
+use cgmath::{Point3, Vector2, Vector3, Vector4};
 
 use cgmath::SquareMatrix;
 
+use crate::animated_image::AnimatedImageMode;
 use crate::animations::{
-    AnimationData, AnimationProperty, BackgroundFill, EasingType, KeyType, KeyframeValue,
-    ObjectType, RangeData, Sequence, UIKeyframe,
+    surrounding_keyframes, AnimationData, AnimationProperty, BackgroundFill, CameraAnimation,
+    ColorInterpolation, EasingType, KeyType, KeyframeValue, ObjectType, RangeData, Sequence,
+    UIKeyframe,
 };
-use crate::camera::{Camera3D as Camera, CameraBinding};
-use crate::capture::{MousePosition, SourceData};
-use crate::dot::RingDot;
-use crate::fonts::FontManager;
+use crate::autosave::{AutosaveSettings, Autosaver};
+use crate::beats::{nearest_beat, pulse_keyframes};
+use crate::blend::BlendMode;
+use crate::brand_kit::BrandKit;
+use crate::callout::{build_callout, CalloutSettings};
+use crate::camera::{Camera3D as Camera, CameraBookmark, CameraTransition};
+use crate::captions::{
+    build_caption_track, captions_to_subtitles, parse_subtitles, CaptionSegment, CaptionSettings,
+    SpeechToText, SubtitleFormat,
+};
+use crate::capture::{InputEvent, MousePosition, SourceData};
+use crate::choreography::{delay_animation, stagger_sequence, StaggerTemplate};
+use crate::clipboard::{copy_from_sequence, prepare_paste, ClipboardItem, DUPLICATE_OFFSET};
+use crate::constraints::{would_cycle, PinConstraint};
+use crate::cursor::SavedCursorOverlay;
+use crate::editor_handle::{EditorCommandQueue, EditorHandle};
+use crate::error::{parse_id, EditorError};
+use crate::events::{EditorEvent, EditorEventHandler, EventBus, SubscriptionId};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::export::exporter::{ExportJob, ExportOutcome, ExportRequest, Exporter};
+use crate::frame_interpolation::FrameInterpolationSettings;
+use crate::gizmos::{Gizmo, GizmoShape};
+use crate::grid::{
+    grid_lines, grid_snap_offset, next_grid_value, ruler_ticks, GridSettings, RULER_SIZE,
+};
+use crate::group::{GroupChild, ObjectGroup, SavedGroupConfig};
+use crate::guides::{create_line_polygon, snap_bounding_box, GuideLine, SnapResult, SnapSettings};
+use crate::input_overlays::{
+    generate_input_overlays, keyframe_property, load_input_events, InputOverlaySettings,
+};
+use crate::masking::MaskMode;
+use crate::matting::{Matte, SavedMatteConfig, SegmentationModelFactory};
 use crate::motion_arrow::MotionArrow;
-use crate::motion_path::MotionPath;
-use crate::polygon::{Polygon, PolygonConfig, Stroke};
-use crate::saved_state::SavedState;
-use crate::st_image::{StImage, StImageConfig};
-use crate::st_video::{StVideo, StVideoConfig};
-use crate::text_due::{TextRenderer, TextRendererConfig};
-use crate::timelines::{SavedTimelineStateConfig, TrackType};
+use crate::motion_path::{path_point_near, set_tangent, MotionPath, TangentControl};
+use crate::motion_presets::{preset_animation, PresetSettings};
+use crate::motion_variation::MotionVariation;
+use crate::nine_slice::NineSliceInsets;
+use crate::onion_skin::{onion_skin_ghosts, OnionSkinSettings};
+use crate::outline::{
+    glyph_animation, points_to_saved, text_to_glyph_outlines, OutlineSource, OutlinedText,
+    SavedOutline,
+};
+use crate::palette::{ColorTarget, SwatchLink};
+use crate::particles::{ParticleEmitter, SavedParticleEmitterConfig};
+use crate::picking::GpuPicker;
+use crate::pii::{scan_video, PiiFinding, PiiScanProgress, PiiScanSettings, TextRecognizer};
+use crate::polygon::{Polygon, PolygonConfig, SavedPoint, SavedPolygonConfig, SavedStroke, Stroke};
+use crate::redaction::{RedactionRegion, RedactionStyle};
+use crate::review::{
+    comments_for_object, comments_in_time_order, set_comment_resolved, CommentAnchor, ReviewComment,
+};
+use crate::safe_areas::{
+    obstruction_zones, outline_lines, safe_area, SafeAreaSettings, ACTION_SAFE, TITLE_SAFE,
+};
 use crate::saved_state::save_saved_state_raw;
+use crate::saved_state::{
+    load_saved_state_file, load_sequence_template, prepare_sequence_import, rescale_sequence,
+    save_sequence_template, split_sequence, CanvasSize, ProjectFormat, SavedState,
+    SequenceTemplate,
+};
+use crate::scene::Scene;
+use crate::scene_generation::{
+    build_scene, layout_instructions, parse_layout, LayoutGenerator, SceneLayout,
+};
+use crate::smart_zoom::{find_active_segments, smart_zoom_property, SmartZoomSettings};
+use crate::spotlight::{build_spotlight, SpotlightSettings};
+use crate::spring::SpringConfig;
+use crate::st_image::{ImageResidency, StImage, StImageConfig};
+use crate::st_video::{
+    FrameSkipPolicy, StVideo, StVideoConfig, VideoPlaybackMode, VideoPlaybackStats, VideoTrim,
+    MAX_VIDEO_SPEED, MIN_VIDEO_SPEED,
+};
+use crate::stabilization::{
+    analyze_video, SavedStabilization, StabilizationProgress, StabilizationSettings,
+};
+use crate::style_presets::{StylePreset, StyledObject};
+use crate::tags::{
+    all_tags, objects_with_tag, remove_tag, sequences_with_tag, upsert_tag, ColorTag, TaggedObject,
+};
+use crate::text_due::{TextRenderer, TextRendererConfig};
+use crate::text_editing::{
+    span_boxes, TextEditState, TextInput, DOUBLE_CLICK_DISTANCE, DOUBLE_CLICK_INTERVAL,
+};
+use crate::timelines::{SequenceTransition, TimelineMarker, TimelineSequence, TrackType};
+use crate::transcode::proxy::VideoProxy;
+use crate::typewriter::{reveal_timings, sound_cues, CharacterReveal, SoundCue, SoundSet};
+use crate::video_prefetch::PrefetchSettings;
+use crate::warp::MeshWarp;
+use crate::widgets::{build_widget, WidgetSettings};
 use crate::{
     capture::StCapture,
-    export::exporter::Exporter,
+    export::lottie::{export_lottie, import_lottie},
 };
 
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
 use uuid::Uuid;
 
 use strum::IntoEnumIterator;
@@ -41,6 +120,20 @@ use strum_macros::EnumIter;
 const NUM_INFERENCE_FEATURES: usize = 7;
 pub const CANVAS_HORIZ_OFFSET: f32 = 0.0;
 pub const CANVAS_VERT_OFFSET: f32 = 0.0;
+pub const CANVAS_TOP: f32 = 50.0; // space above the canvas background
+
+/// Where the pointer can interact, the canvas with some room around it for off-canvas
+/// positioning. Tall canvases reach further down than the window's default.
+pub fn canvas_interactive_bounds(canvas_size: CanvasSize, window_size: &WindowSize) -> BoundingBox {
+    BoundingBox {
+        min: Point { x: 50.0, y: 50.0 }, // account for aside width, allow for some off-canvas positioning
+        max: Point {
+            x: (window_size.width as f32).max(canvas_size.width as f32 + 100.0),
+            // y: window_size.height as f32 - 350.0, // 350.0 for timeline space
+            y: CANVAS_TOP + canvas_size.height as f32 + 100.0, // allow for 50.0 padding below and above the canvas
+        },
+    }
+}
 
 enum ResizableObject {
     Polygon(Polygon),
@@ -69,6 +162,7 @@ pub struct Point {
     pub y: f32,
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct BoundingBox {
     pub min: Point,
     pub max: Point,
@@ -78,7 +172,12 @@ pub struct BoundingBox {
 pub trait Shape {
     fn bounding_box(&self) -> BoundingBox;
     fn contains_point(&self, point: &Point, camera: &Camera) -> bool;
-    fn contains_point_with_tolerance(&self, point: &Point, camera: &Camera, tolerance_percent: f32) -> bool {
+    fn contains_point_with_tolerance(
+        &self,
+        point: &Point,
+        camera: &Camera,
+        tolerance_percent: f32,
+    ) -> bool {
         // Default implementation - subclasses should override for proper enhanced detection
         self.contains_point(point, camera)
     }
@@ -189,11 +288,6 @@ pub fn string_to_u32(s: &str) -> Result<u32, std::num::ParseIntError> {
     }
 }
 
-// pub struct GuideLine {
-//     pub start: Point,
-//     pub end: Point,
-// }
-
 // Define all possible edit operations
 #[derive(Debug)]
 pub enum ObjectProperty {
@@ -246,8 +340,43 @@ pub type OnPathMouseUp =
 pub enum ControlMode {
     Select,
     Pan,
+    Orbit, // drag to swing the camera around the canvas
+    Dolly, // drag up and down to move the camera toward or away from the canvas
+}
+
+/// Part of a sequence to play over and over, in milliseconds of sequence time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LoopRegion {
+    pub start_ms: i32,
+    pub end_ms: i32,
 }
 
+impl LoopRegion {
+    /// Maps a playhead past the end of the region back into it
+    pub fn wrap(&self, time_s: f32) -> f32 {
+        let start_s = self.start_ms as f32 / 1000.0;
+        let end_s = self.end_ms as f32 / 1000.0;
+
+        if time_s < end_s {
+            return time_s;
+        }
+
+        start_s + (time_s - start_s) % (end_s - start_s)
+    }
+}
+
+pub const MIN_PLAYBACK_RATE: f32 = 0.25;
+pub const MAX_PLAYBACK_RATE: f32 = 4.0;
+
+/// Radians the camera orbits per pixel dragged
+const ORBIT_SPEED: f32 = 0.005;
+/// Scene units the camera dollies per pixel dragged
+const DOLLY_SPEED: f32 = 0.01;
+/// Smallest width or height a resize handle can drag an object to
+const MIN_RESIZE_DIMENSION: f32 = 10.0;
+/// How far in pixels a double click can be from a motion path to add a keyframe to it
+const PATH_HIT_TOLERANCE: f32 = 6.0;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum HandlePosition {
     TopLeft,
@@ -260,49 +389,200 @@ pub enum HandlePosition {
     Left,
 }
 
+impl HandlePosition {
+    pub fn cursor_hint(&self) -> CursorHint {
+        match self {
+            HandlePosition::Left | HandlePosition::Right => CursorHint::ResizeHorizontal,
+            HandlePosition::Top | HandlePosition::Bottom => CursorHint::ResizeVertical,
+            HandlePosition::TopLeft | HandlePosition::BottomRight => CursorHint::ResizeDiagonal,
+            HandlePosition::TopRight | HandlePosition::BottomLeft => CursorHint::ResizeAntiDiagonal,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct SelectedObject {
     pub object_id: Uuid,
     pub object_type: crate::animations::ObjectType,
 }
 
+/// Modifier keys held while dragging a resize handle
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResizeModifiers {
+    pub lock_aspect: bool, // usually Shift
+    pub from_center: bool, // usually Alt
+}
+
+/// Bounds after dragging a handle from its starting bounds. Corners follow their own
+/// diagonal, the opposite side stays anchored unless resizing from the center, and a locked
+/// aspect ratio follows whichever axis moved further.
+pub fn resize_bounds(
+    start: BoundingBox,
+    handle_position: HandlePosition,
+    drag_delta: Point,
+    modifiers: ResizeModifiers,
+) -> BoundingBox {
+    // which way each axis grows for this handle, 0 for an axis it doesn't drag
+    let (sign_x, sign_y) = match handle_position {
+        HandlePosition::TopLeft => (-1.0, -1.0),
+        HandlePosition::Top => (0.0, -1.0),
+        HandlePosition::TopRight => (1.0, -1.0),
+        HandlePosition::Right => (1.0, 0.0),
+        HandlePosition::BottomRight => (1.0, 1.0),
+        HandlePosition::Bottom => (0.0, 1.0),
+        HandlePosition::BottomLeft => (-1.0, 1.0),
+        HandlePosition::Left => (-1.0, 0.0),
+    };
+    let growth = if modifiers.from_center { 2.0 } else { 1.0 };
+
+    let start_width = start.max.x - start.min.x;
+    let start_height = start.max.y - start.min.y;
+    let mut width = (start_width + sign_x * drag_delta.x * growth).max(MIN_RESIZE_DIMENSION);
+    let mut height = (start_height + sign_y * drag_delta.y * growth).max(MIN_RESIZE_DIMENSION);
+
+    if modifiers.lock_aspect && start_width > 0.0 && start_height > 0.0 {
+        let scale_x = width / start_width;
+        let scale_y = height / start_height;
+        let x_moved_further = (scale_x - 1.0).abs() >= (scale_y - 1.0).abs();
+        let scale = if sign_y == 0.0 || (sign_x != 0.0 && x_moved_further) {
+            scale_x
+        } else {
+            scale_y
+        };
+        let scale = scale
+            .max(MIN_RESIZE_DIMENSION / start_width)
+            .max(MIN_RESIZE_DIMENSION / start_height);
+        width = start_width * scale;
+        height = start_height * scale;
+    }
+
+    // an axis the handle doesn't drag, like the height on a side handle with a locked aspect,
+    // grows about the center
+    let anchored = |min: f32, max: f32, sign: f32, size: f32| {
+        if modifiers.from_center || sign == 0.0 {
+            let center = (min + max) / 2.0;
+            (center - size / 2.0, center + size / 2.0)
+        } else if sign > 0.0 {
+            (min, min + size)
+        } else {
+            (max - size, max)
+        }
+    };
+    let (min_x, max_x) = anchored(start.min.x, start.max.x, sign_x, width);
+    let (min_y, max_y) = anchored(start.min.y, start.max.y, sign_y, height);
+
+    BoundingBox {
+        min: Point { x: min_x, y: min_y },
+        max: Point { x: max_x, y: max_y },
+    }
+}
+
+/// Rotates an offset by an angle in radians, matching how transforms apply rotation
+fn rotate_offset(offset: Point, angle: f32) -> Point {
+    let (sin, cos) = angle.sin_cos();
+
+    Point {
+        x: offset.x * cos - offset.y * sin,
+        y: offset.x * sin + offset.y * cos,
+    }
+}
+
+/// What's under the mouse, found by handle_mouse_hover
+#[derive(Clone, Debug, PartialEq)]
+pub enum HoverTarget {
+    Object {
+        object_id: Uuid, // grouped objects report their group, as clicking drags it
+        object_type: ObjectType,
+    },
+    ResizeHandle {
+        object_id: Uuid,
+        position: HandlePosition,
+    },
+    PathHandle {
+        object_id: Option<Uuid>,
+        keyframe_id: Option<Uuid>,
+    },
+}
+
+impl HoverTarget {
+    pub fn cursor_hint(&self) -> CursorHint {
+        match self {
+            HoverTarget::Object { .. } => CursorHint::Move,
+            HoverTarget::ResizeHandle { position, .. } => position.cursor_hint(),
+            HoverTarget::PathHandle { .. } => CursorHint::Grab,
+        }
+    }
+}
+
+/// Mouse cursors for hosts to map to their platform's own
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CursorHint {
+    Default,
+    Move,
+    Grab, // motion path handles
+    ResizeHorizontal,
+    ResizeVertical,
+    ResizeDiagonal,     // top left to bottom right
+    ResizeAntiDiagonal, // top right to bottom left
+}
+
 pub struct ResizeHandle {
     pub id: Uuid,
     pub position: HandlePosition,
-    pub polygon: Polygon,
+    pub gizmo: Gizmo,
     pub object_id: Uuid,
 }
 
 pub struct Editor {
     // visual
+    pub scene: Scene, // objects, rendering and playback
     pub st_capture: StCapture,
+    #[cfg(not(target_arch = "wasm32"))]
     pub exporter: Option<Exporter>,
     pub selected_polygon_id: Uuid,
-    pub polygons: Vec<Polygon>,
     pub dragging_polygon: Option<Uuid>,
-    pub static_polygons: Vec<Polygon>,
     pub project_selected: Option<Uuid>,
-    pub text_items: Vec<TextRenderer>,
     pub dragging_text: Option<Uuid>,
-    pub image_items: Vec<StImage>,
     pub dragging_image: Option<Uuid>,
-    pub font_manager: FontManager,
     pub dragging_path: Option<Uuid>,
     pub dragging_path_handle: Option<Uuid>,
     pub dragging_path_object: Option<Uuid>,
     pub dragging_path_keyframe: Option<Uuid>,
+    pub selected_path_keyframe: Option<(Uuid, Uuid)>, // object and keyframe of the clicked handle
+    pub dragging_path_tangent: Option<(Uuid, Uuid, TangentControl)>, // object, keyframe, control
     pub dragging_path_assoc_path: Option<Uuid>,
-    pub cursor_dot: Option<RingDot>,
-    pub video_items: Vec<StVideo>,
+    pub cursor_dot: Option<Gizmo>, // follows the mouse, see Gizmo::dot
     pub dragging_video: Option<Uuid>,
+    pub dragging_group: Option<Uuid>,
+    pub dragging_particle_emitter: Option<Uuid>,
+    pub sound_sets: Vec<SoundSet>,
     pub saved_state: Option<SavedState>,
-    
+    pub autosaver: Option<Autosaver>, // started with start_autosave once a project is open
+    pub events: EventBus,
+    pub commands: EditorCommandQueue,
+
     // resize handles system
     pub selected_object: Option<SelectedObject>,
     pub resize_handles: Vec<ResizeHandle>,
     pub dragging_handle: Option<(Uuid, HandlePosition)>,
-    
+    pub resize_start: Option<BoundingBox>, // bounds of the object when the handle drag began
+    pub resize_modifiers: ResizeModifiers, // set by the host from its modifier keys
+
+    // smart guides
+    pub snap_settings: SnapSettings,
+    pub guide_lines: Vec<GuideLine>,
+    pub drag_snap_offset: Point, // snap correction currently applied to the dragged object
+    pub hover_target: Option<HoverTarget>,
+    pub hover_outline: bool, // outlines hovered objects other than the selected one
+    pub onion_skin_settings: OnionSkinSettings,
+    pub text_editing: Option<TextEditState>,
+    pub last_click: Option<(Instant, Point)>, // for noticing double clicks
+    pub grid_settings: GridSettings,
+    pub safe_area_settings: SafeAreaSettings,
+    pub clipboard: Option<ClipboardItem>,
+
     pub motion_paths: Vec<MotionPath>,
+    pub dirty_motion_paths: Vec<String>, // object ids whose paths wait for update_dirty_motion_paths
     pub motion_arrows: Vec<MotionArrow>,
     pub canvas_hidden: bool,
     pub motion_arrow_just_placed: bool,
@@ -317,15 +597,11 @@ pub struct Editor {
     pub handle_text_click: Option<Arc<TextItemClickHandler>>,
     pub handle_image_click: Option<Arc<ImageItemClickHandler>>,
     pub handle_video_click: Option<Arc<VideoItemClickHandler>>,
-    pub gpu_resources: Option<Arc<GpuResources>>,
-    pub camera: Option<Camera>,
-    pub camera_binding: Option<CameraBinding>,
-    pub model_bind_group_layout: Option<Arc<wgpu::BindGroupLayout>>,
-    pub group_bind_group_layout: Option<Arc<wgpu::BindGroupLayout>>,
     pub window_size_bind_group_layout: Option<Arc<wgpu::BindGroupLayout>>,
     pub window_size_bind_group: Option<wgpu::BindGroup>,
     pub window_size_buffer: Option<Arc<wgpu::Buffer>>,
     pub render_pipeline: Option<Arc<wgpu::RenderPipeline>>,
+    pub gpu_picker: Option<GpuPicker>, // exact hit testing on click, see set_gpu_picking
     pub on_mouse_up: Option<Arc<OnMouseUp>>,
     pub on_handle_mouse_up: Option<Arc<OnHandleMouseUp>>,
     pub on_path_mouse_up: Option<Arc<OnPathMouseUp>>,
@@ -334,16 +610,9 @@ pub struct Editor {
     pub depth_view: Option<wgpu::TextureView>,
 
     // state
-    pub is_playing: bool,
-    pub current_sequence_data: Option<Sequence>,
-    pub last_frame_time: Option<Instant>,
-    pub start_playing_time: Option<Instant>,
-    pub video_is_playing: bool,
-    pub video_start_playing_time: Option<Instant>,
-    pub video_current_sequence_timeline: Option<SavedTimelineStateConfig>,
-    pub video_current_sequences_data: Option<Vec<Sequence>>,
     pub control_mode: ControlMode,
     pub is_panning: bool,
+    pub camera_transition: Option<CameraTransition>,
     pub motion_mode: bool,
 
     // points
@@ -363,9 +632,10 @@ pub struct Editor {
     pub generation_curved: bool,
     pub generation_choreographed: bool,
     pub generation_fade: bool,
+    pub generation_color: bool, // fill or text color fades in from gray and back out
+    pub generation_squash_stretch: bool, // stretched along the motion entering and leaving
 }
 
-
 #[cfg(target_os = "windows")]
 pub fn init_editor_with_model(viewport: Arc<Mutex<Viewport>>, project_id: String) -> Editor {
     // let inference = load_common_motion_2d();
@@ -389,18 +659,13 @@ pub enum InputValue {
 }
 
 impl Editor {
-    pub fn new(
-        viewport: Arc<Mutex<Viewport>>,
-        project_id: String
-    ) -> Self {
+    pub fn new(viewport: Arc<Mutex<Viewport>>, project_id: String) -> Self {
         let viewport_unwrapped = viewport.lock().unwrap();
         let window_size = WindowSize {
             width: viewport_unwrapped.width as u32,
             height: viewport_unwrapped.height as u32,
         };
 
-        let font_manager = FontManager::new();
-
         // Create capture directory for this project
         let project_path = std::env::current_dir()
             .unwrap_or_else(|_| std::path::PathBuf::from("."))
@@ -416,14 +681,14 @@ impl Editor {
         let st_capture = StCapture::new(project_path);
 
         Editor {
+            scene: Scene::new(),
             st_capture,
+            #[cfg(not(target_arch = "wasm32"))]
             exporter: None,
-            font_manager,
             // inference,
             selected_polygon_id: Uuid::nil(),
             last_motion_arrow_object_id: Uuid::nil(),
             last_motion_arrow_object_type: ObjectType::Polygon,
-            polygons: Vec::new(),
             dragging_polygon: None,
             dragging_path_assoc_path: None,
             drag_start: None,
@@ -432,9 +697,6 @@ impl Editor {
             handle_text_click: None,
             handle_image_click: None,
             handle_video_click: None,
-            gpu_resources: None,
-            camera: None,
-            camera_binding: None,
             last_mouse_pos: None,
             last_screen: Point { x: 0.0, y: 0.0 },
             last_world: Point { x: 0.0, y: 0.0 },
@@ -443,48 +705,60 @@ impl Editor {
             global_top_left: Point { x: 0.0, y: 0.0 },
             ndc: Point { x: 0.0, y: 0.0 },
             previous_top_left: Point { x: 0.0, y: 0.0 },
-            is_playing: false,
-            current_sequence_data: None,
-            last_frame_time: None,
-            start_playing_time: None,
-            model_bind_group_layout: None,
-            group_bind_group_layout: None,
             window_size_bind_group_layout: None,
             window_size_bind_group: None,
             window_size_buffer: None,
+            gpu_picker: None,
             render_pipeline: None,
-            static_polygons: Vec::new(),
             on_mouse_up: None,
             current_view: "manage_projects".to_string(),
             project_selected: None,
-            text_items: Vec::new(),
             dragging_text: None,
-            image_items: Vec::new(),
             dragging_image: None,
-            video_is_playing: false,
-            video_start_playing_time: None,
-            video_current_sequence_timeline: None,
-            video_current_sequences_data: None,
             dragging_path: None,
             dragging_path_handle: None,
             on_handle_mouse_up: None,
             on_path_mouse_up: None,
             dragging_path_object: None,
             dragging_path_keyframe: None,
+            selected_path_keyframe: None,
+            dragging_path_tangent: None,
             cursor_dot: None,
             control_mode: ControlMode::Select,
             is_panning: false,
+            camera_transition: None,
             motion_mode: false,
-            video_items: Vec::new(),
             dragging_video: None,
+            dragging_group: None,
+            dragging_particle_emitter: None,
+            sound_sets: Vec::new(),
             saved_state: None,
-            
-            // resize handles system  
+            autosaver: None,
+            events: EventBus::new(),
+            commands: EditorCommandQueue::new(),
+
+            // resize handles system
             selected_object: None,
             resize_handles: Vec::new(),
             dragging_handle: None,
-            
+            resize_start: None,
+            resize_modifiers: ResizeModifiers::default(),
+
+            // smart guides
+            snap_settings: SnapSettings::default(),
+            guide_lines: Vec::new(),
+            drag_snap_offset: Point { x: 0.0, y: 0.0 },
+            hover_target: None,
+            hover_outline: false,
+            onion_skin_settings: OnionSkinSettings::default(),
+            text_editing: None,
+            last_click: None,
+            grid_settings: GridSettings::default(),
+            safe_area_settings: SafeAreaSettings::default(),
+            clipboard: None,
+
             motion_paths: Vec::new(),
+            dirty_motion_paths: Vec::new(),
             motion_arrows: Vec::new(),
             canvas_hidden: false,
             motion_arrow_just_placed: false,
@@ -493,17 +767,12 @@ impl Editor {
             generation_curved: false,
             generation_choreographed: true,
             generation_fade: true,
+            generation_color: false,
+            generation_squash_stretch: false,
             depth_view: None,
             last_motion_arrow_end_positions: None,
             // TODO: update interactive bounds on window resize?
-            interactive_bounds: BoundingBox {
-                min: Point { x: 50.0, y: 50.0 }, // account for aside width, allow for some off-canvas positioning
-                max: Point {
-                    x: window_size.width as f32,
-                    // y: window_size.height as f32 - 350.0, // 350.0 for timeline space
-                    y: 750.0, // allow for 50.0 padding below and above the canvas
-                },
-            },
+            interactive_bounds: canvas_interactive_bounds(CanvasSize::default(), &window_size),
         }
     }
 
@@ -513,21 +782,14 @@ impl Editor {
         object_type: crate::animations::ObjectType,
         // gpu_resources: &GpuResources,
     ) {
-        self.clear_resize_handles();
+        let previous_selection = self.selected_object.take();
+        self.resize_handles.clear();
 
-        let gpu_resources = self.gpu_resources.as_ref().expect("Couldn't get gpu resources");
-        
         let bounding_box = match self.get_object_bounding_box(object_id, &object_type) {
             Some(bbox) => bbox,
-            None => return,
-        };
-        
-        let window_size = if let Some(camera) = &self.camera {
-            camera.window_size
-        } else {
-            return;
+            None => return self.emit_selection_change(previous_selection),
         };
-        
+
         let handle_size = 8.0; // Size of resize handles in pixels
         let handle_positions = [
             HandlePosition::TopLeft,
@@ -542,73 +804,168 @@ impl Editor {
 
         for position in &handle_positions {
             let handle_center = self.get_handle_position(&bounding_box, position);
-            
-            // Create a small square polygon for the handle
-            // bad, we want this in localized units
-            // let handle_points = vec![
-            //     Point { x: -handle_size/2.0, y: -handle_size/2.0 },
-            //     Point { x: handle_size/2.0, y: -handle_size/2.0 },
-            //     Point { x: handle_size/2.0, y: handle_size/2.0 },
-            //     Point { x: -handle_size/2.0, y: handle_size/2.0 },
-            // ];
-
-            let handle_points = vec![
-                Point { x: 0.0, y: 0.0 },
-                Point { x: 1.0, y: 0.0 },
-                Point { x: 1.0, y: 1.0 },
-                Point { x: 0.0, y: 1.0 },
-            ];
 
-            let handle_id = Uuid::new_v4();
-            
-            if let (Some(camera), Some(model_bind_group_layout), Some(group_bind_group_layout)) = (
-                &self.camera,
-                &self.model_bind_group_layout,
-                &self.group_bind_group_layout,
-            ) {
-                let handle_polygon = crate::polygon::Polygon::new(
-                    &window_size,
-                    &gpu_resources.device,
-                    &gpu_resources.queue,
-                    model_bind_group_layout,
-                    group_bind_group_layout,
-                    camera,
-                    handle_points,
-                    (handle_size, handle_size),
-                    handle_center,
-                    0.0, // rotation
-                    0.0, // border_radius
-                    [0.2, 0.6, 1.0, 1.0], // blue fill
-                    crate::polygon::Stroke {
-                        thickness: 2.0,
-                        fill: rgb_to_wgpu(0, 0, 0, 255.0), // black border
-                    },
-                    100, // high z-layer to render on top
-                    handle_id.to_string(),
-                    handle_id,
-                    Uuid::nil()
-                );
-
-                let resize_handle = ResizeHandle {
-                    id: handle_id,
-                    position: *position,
-                    polygon: handle_polygon,
-                    object_id,
-                };
+            // drawn by the gizmo layer, so nothing to create on the gpu
+            let gizmo = Gizmo {
+                shape: GizmoShape::Square,
+                position: handle_center,
+                size: handle_size,
+                rotation: 0.0,
+                fill: [0.2, 0.6, 1.0, 1.0], // blue fill
+                stroke: crate::polygon::Stroke {
+                    thickness: 2.0,
+                    fill: rgb_to_wgpu(0, 0, 0, 255.0), // black border
+                },
+            };
 
-                self.resize_handles.push(resize_handle);
-            }
+            self.resize_handles.push(ResizeHandle {
+                id: Uuid::new_v4(),
+                position: *position,
+                gizmo,
+                object_id,
+            });
         }
-        
+
         self.selected_object = Some(SelectedObject {
             object_id,
             object_type,
         });
+        self.emit_selection_change(previous_selection);
+    }
+
+    /// Resize handles, motion path handles and the cursor dot, for a `GizmoRenderer`
+    pub fn gizmos(&self) -> Vec<Gizmo> {
+        let mut gizmos: Vec<Gizmo> = self.resize_handles.iter().map(|h| h.gizmo).collect();
+        gizmos.extend(
+            self.motion_paths
+                .iter()
+                .flat_map(|path| path.handle_gizmos()),
+        );
+        gizmos.extend(self.cursor_dot);
+
+        gizmos
+    }
+
+    /// Picks objects on click by drawing their ids offscreen, instead of testing their shapes
+    pub fn set_gpu_picking(&mut self, enabled: bool) {
+        if !enabled {
+            self.gpu_picker = None;
+            return;
+        }
+
+        let (
+            Some(gpu_resources),
+            Some(camera),
+            Some(camera_binding),
+            Some(model_bind_group_layout),
+            Some(window_size_bind_group_layout),
+            Some(group_bind_group_layout),
+        ) = (
+            self.scene.gpu_resources.as_ref(),
+            self.scene.camera.as_ref(),
+            self.scene.camera_binding.as_ref(),
+            self.scene.model_bind_group_layout.as_ref(),
+            self.window_size_bind_group_layout.as_ref(),
+            self.scene.group_bind_group_layout.as_ref(),
+        )
+        else {
+            println!("Couldn't enable gpu picking before the editor is set up");
+            return;
+        };
+
+        let layout = gpu_resources
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Picking Pipeline Layout"),
+                bind_group_layouts: &[
+                    &camera_binding.bind_group_layout,
+                    model_bind_group_layout,
+                    window_size_bind_group_layout,
+                    group_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+
+        self.gpu_picker = Some(GpuPicker::new(
+            &gpu_resources.device,
+            &layout,
+            &camera.window_size,
+        ));
     }
 
     pub fn clear_resize_handles(&mut self) {
+        let previous_selection = self.selected_object.take();
         self.resize_handles.clear();
-        self.selected_object = None;
+        self.emit_selection_change(previous_selection);
+    }
+
+    fn emit_selection_change(&mut self, previous_selection: Option<SelectedObject>) {
+        if previous_selection != self.selected_object {
+            self.events
+                .emit(EditorEvent::SelectionChanged(self.selected_object.clone()));
+        }
+    }
+
+    /// Calls the handler with every editor event until unsubscribed
+    pub fn subscribe(&mut self, handler: Box<EditorEventHandler>) -> SubscriptionId {
+        self.events.subscribe(handler)
+    }
+
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.events.unsubscribe(id)
+    }
+
+    /// Passes on events from background work like exports, call once per frame
+    pub fn step_events(&mut self) {
+        self.events.dispatch_queued();
+    }
+
+    /// For posting changes from other threads
+    pub fn handle(&self) -> EditorHandle {
+        self.commands.handle()
+    }
+
+    /// Runs the commands posted through handles since the last call, call once per frame
+    /// on the thread that owns the editor, before stepping animations
+    pub fn apply_commands(&mut self) {
+        while let Some(command) = self.commands.pop() {
+            command(self);
+        }
+    }
+
+    /// Starts playing the current sequence from its beginning
+    pub fn start_playback(&mut self) {
+        self.scene.start_playing_time = Some(Instant::now());
+        self.scene.last_playhead_s = 0.0;
+        self.scene.is_playing = true;
+        self.events.emit(EditorEvent::PlaybackStarted);
+    }
+
+    pub fn stop_playback(&mut self) {
+        self.scene.is_playing = false;
+        self.scene.start_playing_time = None;
+        self.events.emit(EditorEvent::PlaybackStopped);
+    }
+
+    /// Exports on its own thread, reporting through ExportProgress and ExportFinished events
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_export(&mut self, request: ExportRequest) -> ExportJob {
+        let progress_sender = self.events.sender();
+        let complete_sender = self.events.sender();
+
+        ExportJob::spawn(
+            request,
+            Arc::new(move |progress| {
+                progress_sender
+                    .send(EditorEvent::ExportProgress(progress))
+                    .ok();
+            }),
+            Arc::new(move |outcome: ExportOutcome| {
+                complete_sender
+                    .send(EditorEvent::ExportFinished(outcome))
+                    .ok();
+            }),
+        )
     }
 
     fn get_handle_position(&self, bbox: &BoundingBox, position: &HandlePosition) -> Point {
@@ -616,21 +973,50 @@ impl Editor {
         let mid_y = (bbox.min.y + bbox.max.y) / 2.0;
 
         match position {
-            HandlePosition::TopLeft => Point { x: bbox.min.x, y: bbox.min.y },
-            HandlePosition::Top => Point { x: mid_x, y: bbox.min.y },
-            HandlePosition::TopRight => Point { x: bbox.max.x, y: bbox.min.y },
-            HandlePosition::Right => Point { x: bbox.max.x, y: mid_y },
-            HandlePosition::BottomRight => Point { x: bbox.max.x, y: bbox.max.y },
-            HandlePosition::Bottom => Point { x: mid_x, y: bbox.max.y },
-            HandlePosition::BottomLeft => Point { x: bbox.min.x, y: bbox.max.y },
-            HandlePosition::Left => Point { x: bbox.min.x, y: mid_y },
+            HandlePosition::TopLeft => Point {
+                x: bbox.min.x,
+                y: bbox.min.y,
+            },
+            HandlePosition::Top => Point {
+                x: mid_x,
+                y: bbox.min.y,
+            },
+            HandlePosition::TopRight => Point {
+                x: bbox.max.x,
+                y: bbox.min.y,
+            },
+            HandlePosition::Right => Point {
+                x: bbox.max.x,
+                y: mid_y,
+            },
+            HandlePosition::BottomRight => Point {
+                x: bbox.max.x,
+                y: bbox.max.y,
+            },
+            HandlePosition::Bottom => Point {
+                x: mid_x,
+                y: bbox.max.y,
+            },
+            HandlePosition::BottomLeft => Point {
+                x: bbox.min.x,
+                y: bbox.max.y,
+            },
+            HandlePosition::Left => Point {
+                x: bbox.min.x,
+                y: mid_y,
+            },
         }
     }
 
-    fn get_object_bounding_box(&self, object_id: Uuid, object_type: &crate::animations::ObjectType) -> Option<BoundingBox> {
+    fn get_object_bounding_box(
+        &self,
+        object_id: Uuid,
+        object_type: &crate::animations::ObjectType,
+    ) -> Option<BoundingBox> {
         match object_type {
             crate::animations::ObjectType::Polygon => {
-                self.polygons
+                self.scene
+                    .polygons
                     .iter()
                     .find(|p| p.id == object_id)
                     // .map(|p| p.world_bounding_box())
@@ -640,13 +1026,20 @@ impl Editor {
                         let half_width = dims.0 as f32 / 2.0;
                         let half_height = dims.1 as f32 / 2.0;
                         BoundingBox {
-                            min: Point { x: pos.x - half_width, y: pos.y - half_height },
-                            max: Point { x: pos.x + half_width, y: pos.y + half_height },
+                            min: Point {
+                                x: pos.x - half_width,
+                                y: pos.y - half_height,
+                            },
+                            max: Point {
+                                x: pos.x + half_width,
+                                y: pos.y + half_height,
+                            },
                         }
                     })
             }
             crate::animations::ObjectType::TextItem => {
-                self.text_items
+                self.scene
+                    .text_items
                     .iter()
                     .find(|t| t.id == object_id)
                     // .map(|t| {
@@ -663,13 +1056,20 @@ impl Editor {
                         let half_width = dims.0 as f32 / 2.0;
                         let half_height = dims.1 as f32 / 2.0;
                         BoundingBox {
-                            min: Point { x: pos.x - half_width, y: pos.y - half_height },
-                            max: Point { x: pos.x + half_width, y: pos.y + half_height },
+                            min: Point {
+                                x: pos.x - half_width,
+                                y: pos.y - half_height,
+                            },
+                            max: Point {
+                                x: pos.x + half_width,
+                                y: pos.y + half_height,
+                            },
                         }
                     })
             }
             crate::animations::ObjectType::ImageItem => {
-                self.image_items
+                self.scene
+                    .image_items
                     .iter()
                     .find(|i| i.id == object_id.to_string())
                     .map(|i| {
@@ -678,13 +1078,20 @@ impl Editor {
                         let half_width = dims.0 as f32 / 2.0;
                         let half_height = dims.1 as f32 / 2.0;
                         BoundingBox {
-                            min: Point { x: pos.x - half_width, y: pos.y - half_height },
-                            max: Point { x: pos.x + half_width, y: pos.y + half_height },
+                            min: Point {
+                                x: pos.x - half_width,
+                                y: pos.y - half_height,
+                            },
+                            max: Point {
+                                x: pos.x + half_width,
+                                y: pos.y + half_height,
+                            },
                         }
                     })
             }
             crate::animations::ObjectType::VideoItem => {
-                self.video_items
+                self.scene
+                    .video_items
                     .iter()
                     .find(|v| v.id == object_id.to_string())
                     .map(|v| {
@@ -693,137 +1100,436 @@ impl Editor {
                         let half_width = dims.0 as f32 / 2.0;
                         let half_height = dims.1 as f32 / 2.0;
                         BoundingBox {
-                            min: Point { x: pos.x - half_width, y: pos.y - half_height },
-                            max: Point { x: pos.x + half_width, y: pos.y + half_height },
+                            min: Point {
+                                x: pos.x - half_width,
+                                y: pos.y - half_height,
+                            },
+                            max: Point {
+                                x: pos.x + half_width,
+                                y: pos.y + half_height,
+                            },
                         }
                     })
             }
-        }
-    }
-
-    pub fn handle_clicked_at_point(&self, point: &Point, camera: &Camera) -> Option<(Uuid, HandlePosition)> {
-        for handle in &self.resize_handles {
-            if handle.polygon.contains_point(point, camera) {
-                println!("handle clicked");
-                return Some((handle.id, handle.position));
+            crate::animations::ObjectType::Group => self
+                .scene
+                .groups
+                .iter()
+                .find(|g| g.id == object_id)
+                .and_then(|g| self.get_group_bounding_box(g)),
+            crate::animations::ObjectType::ParticleEmitter => {
+                self.scene
+                    .particle_emitters
+                    .iter()
+                    .find(|e| e.id == object_id)
+                    .map(|e| {
+                        let pos = e.transform.position; // This is center position
+                        let dims = e.get_dimensions();
+                        let half_width = dims.0 / 2.0;
+                        let half_height = dims.1 / 2.0;
+                        BoundingBox {
+                            min: Point {
+                                x: pos.x - half_width,
+                                y: pos.y - half_height,
+                            },
+                            max: Point {
+                                x: pos.x + half_width,
+                                y: pos.y + half_height,
+                            },
+                        }
+                    })
             }
         }
-        None
-    }
-
-    pub fn start_handle_drag(&mut self, handle_id: Uuid, position: HandlePosition) {
-        if let Some(handle) = self.resize_handles.iter().find(|h| h.id == handle_id) {
-            // println!("start drag");
-            self.dragging_handle = Some((handle.object_id, position));
-        }
     }
 
-    pub fn resize_selected_object(&mut self, mouse_delta: Point) {
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
-        let gpu_resources = self.gpu_resources.as_ref().expect("Couldn't get gpu resources");
-        let bind_group_layout = self.model_bind_group_layout.as_ref().expect("Couldn't get bind group layout");
-
-        // Extract the needed info first to avoid borrowing conflicts
-        let resize_info = if let (Some((object_id, handle_position)), Some(selected_object)) = 
-            (&self.dragging_handle, &self.selected_object) {
-            Some((*object_id, *handle_position, selected_object.object_type.clone()))
-        } else {
-            None
+    /// The object's current rotation in radians, 0 for objects that can't be found
+    fn get_object_rotation(&self, object_id: Uuid, object_type: &ObjectType) -> f32 {
+        let rotation = match object_type {
+            ObjectType::Polygon => self
+                .scene
+                .polygons
+                .iter()
+                .find(|p| p.id == object_id)
+                .map(|p| p.transform.rotation),
+            ObjectType::TextItem => self
+                .scene
+                .text_items
+                .iter()
+                .find(|t| t.id == object_id)
+                .map(|t| t.transform.rotation),
+            ObjectType::ImageItem => self
+                .scene
+                .image_items
+                .iter()
+                .find(|i| i.id == object_id.to_string())
+                .map(|i| i.transform.rotation),
+            ObjectType::VideoItem => self
+                .scene
+                .video_items
+                .iter()
+                .find(|v| v.id == object_id.to_string())
+                .map(|v| v.transform.rotation),
+            ObjectType::Group => self
+                .scene
+                .groups
+                .iter()
+                .find(|g| g.id == object_id)
+                .map(|g| g.transform.rotation),
+            ObjectType::ParticleEmitter => self
+                .scene
+                .particle_emitters
+                .iter()
+                .find(|e| e.id == object_id)
+                .map(|e| e.transform.rotation),
         };
 
-        if let Some((object_id, handle_position, object_type)) = resize_info {
-            match object_type {
-                crate::animations::ObjectType::Polygon => {
-                    if let Some(polygon) = self.polygons.iter_mut().find(|p| p.id == object_id) {
-                        // println!("resize_selected_object");
-                        let (new_width, new_height) = Self::resize_object((polygon.dimensions.0 as f32, polygon.dimensions.1 as f32), &handle_position, mouse_delta);
-                        
-                        polygon.update_data_from_dimensions(&camera.window_size, &gpu_resources.device, &gpu_resources.queue, &bind_group_layout, 
-                                    (new_width, new_height), 
-                                    &camera);
-                        
-                        // TODO: should happen inside render loop for performance
-                        polygon.transform.update_uniform_buffer(&gpu_resources.queue, &camera.window_size);
-                    }
-                }
-                crate::animations::ObjectType::TextItem => {
-                    if let Some(text) = self.text_items.iter_mut().find(|t| t.id == object_id) {
-                       let (new_width, new_height) = Self::resize_object((text.dimensions.0 as f32, text.dimensions.1 as f32), &handle_position, mouse_delta);
+        rotation.unwrap_or(0.0)
+    }
 
-                       text.update_data_from_dimensions(&camera.window_size, &gpu_resources.device, &gpu_resources.queue, &bind_group_layout, 
-                                    (new_width, new_height), 
-                                    &camera);
+    /// Union of the children's bounding boxes, as drawn through the group transform
+    fn get_group_bounding_box(&self, group: &ObjectGroup) -> Option<BoundingBox> {
+        let mut bounds: Option<BoundingBox> = None;
 
-                        // TODO: should happen inside render loop for performance
-                        text.transform.update_uniform_buffer(&gpu_resources.queue, &camera.window_size);
-                    }
-                }
-                crate::animations::ObjectType::ImageItem => {
-                    if let Some(image) = self.image_items.iter_mut().find(|i| i.id == object_id.to_string()) {
-                        let (new_width, new_height) = Self::resize_object((image.dimensions.0 as f32, image.dimensions.1 as f32), &handle_position, mouse_delta);
+        for child in &group.children {
+            let child_id = match Uuid::parse_str(&child.object_id) {
+                Ok(id) => id,
+                Err(_) => continue,
+            };
+            let child_bbox = match self.get_object_bounding_box(child_id, &child.object_type) {
+                Some(bbox) => bbox,
+                None => continue,
+            };
 
-                        image.update_data_from_dimensions(&camera.window_size, &gpu_resources.device, &gpu_resources.queue, &bind_group_layout, 
-                                    (new_width, new_height), 
-                                    &camera);
+            let corners = [
+                child_bbox.min,
+                Point {
+                    x: child_bbox.max.x,
+                    y: child_bbox.min.y,
+                },
+                child_bbox.max,
+                Point {
+                    x: child_bbox.min.x,
+                    y: child_bbox.max.y,
+                },
+            ];
 
-                        // TODO: should happen inside render loop for performance
-                        image.transform.update_uniform_buffer(&gpu_resources.queue, &camera.window_size);
-                    }
-                }
-                crate::animations::ObjectType::VideoItem => {
-                    if let Some(video) = self.video_items.iter_mut().find(|v| v.id == object_id.to_string()) {
-                       let (new_width, new_height) = Self::resize_object((video.dimensions.0 as f32, video.dimensions.1 as f32), &handle_position, mouse_delta);
+            for corner in corners {
+                let corner = group.to_group_space(corner);
+                bounds = Some(match bounds {
+                    Some(b) => BoundingBox {
+                        min: Point {
+                            x: b.min.x.min(corner.x),
+                            y: b.min.y.min(corner.y),
+                        },
+                        max: Point {
+                            x: b.max.x.max(corner.x),
+                            y: b.max.y.max(corner.y),
+                        },
+                    },
+                    None => BoundingBox {
+                        min: corner,
+                        max: corner,
+                    },
+                });
+            }
+        }
 
-                       video.update_data_from_dimensions(&camera.window_size, &gpu_resources.device, &gpu_resources.queue, &bind_group_layout, 
-                                    (new_width, new_height), 
-                                    &camera);
+        bounds
+    }
 
-                        // TODO: should happen inside render loop for performance
-                        video.transform.update_uniform_buffer(&gpu_resources.queue, &camera.window_size);
-                    }
-                }
-            }
+    /// Maps a world point into the space of the object's group, so grouped objects can be
+    /// hit tested against their own untransformed bounds
+    fn group_local_point(&self, object_id: &str, point: Point) -> Point {
+        match self
+            .scene
+            .groups
+            .iter()
+            .find(|g| g.contains_child(object_id))
+        {
+            Some(group) => group.from_group_space(point),
+            None => point,
+        }
+    }
 
-            // Recreate handles after resizing
-            self.create_resize_handles_for_object(object_id, object_type);
+    pub fn handle_clicked_at_point(&self, point: &Point) -> Option<(Uuid, HandlePosition)> {
+        for handle in &self.resize_handles {
+            if handle.gizmo.contains_point(point) {
+                println!("handle clicked");
+                return Some((handle.id, handle.position));
+            }
         }
+        None
     }
 
-    fn resize_object(
-        dimensions: (f32, f32), // or StVideo, StImage, TextRenderer
-        handle_position: &HandlePosition,
-        mouse_delta: Point,
-    ) -> (f32, f32) {
-        let (current_width, current_height) = dimensions;
+    /// The topmost pickable object at a point. Rotated and concave shapes are tested
+    /// approximately, see GpuPicker for exact picking.
+    fn object_at_point(&self, point: Point) -> Option<InteractionTarget> {
+        let camera = self.scene.camera.as_ref()?;
 
-        let mut new_width = current_width;
-        let mut new_height = current_height;
+        let mut intersecting_objects: Vec<(i32, InteractionTarget)> = Vec::new();
 
-        match handle_position {
-            HandlePosition::Right => {
-                new_width = (current_width + mouse_delta.x).max(10.0);
-            }
-            HandlePosition::Left => {
-                new_width = (current_width - mouse_delta.x).max(10.0);
+        // Collect intersecting polygons
+        for (poly_index, polygon) in self.scene.polygons.iter().enumerate() {
+            // locked and user hidden objects can't be picked on the canvas
+            if polygon.hidden || polygon.locked || polygon.user_hidden {
+                continue;
             }
-            HandlePosition::Bottom => {
-                new_height = (current_height + mouse_delta.y).max(10.0);
+
+            let point = self.group_local_point(&polygon.id.to_string(), point);
+            if polygon.contains_point(&point, &camera) {
+                intersecting_objects.push((polygon.layer, InteractionTarget::Polygon(poly_index)));
             }
-            HandlePosition::Top => {
-                new_height = (current_height - mouse_delta.y).max(10.0);
+        }
+
+        // Collect intersecting text items
+        for (text_index, text_item) in self.scene.text_items.iter().enumerate() {
+            if text_item.hidden || text_item.locked || text_item.user_hidden {
+                continue;
             }
-            _ => {
-                // Corner handles - resize both dimensions
-                new_width = (current_width + mouse_delta.x).max(10.0);
-                new_height = (current_height + mouse_delta.y).max(10.0);
+
+            let point = self.group_local_point(&text_item.id.to_string(), point);
+            if text_item.contains_point(&point, &camera) {
+                intersecting_objects.push((text_item.layer, InteractionTarget::Text(text_index)));
             }
-        };
+        }
 
-        (new_width, new_height)
-    }
+        // Collect intersecting image items
+        for (image_index, image_item) in self.scene.image_items.iter().enumerate() {
+            if image_item.hidden || image_item.locked || image_item.user_hidden {
+                continue;
+            }
 
-    // fn resize_text_item(text_item: &mut crate::text_due::TextRenderer, handle_position: &HandlePosition, mouse_delta: Point, gpu_resources: &GpuResources) {
-    //     let scale_factor = match handle_position {
+            let point = self.group_local_point(&image_item.id, point);
+            if image_item.contains_point(&point, &camera) {
+                intersecting_objects
+                    .push((image_item.layer, InteractionTarget::Image(image_index)));
+            }
+        }
+
+        // Collect intersecting image items
+        for (video_index, video_item) in self.scene.video_items.iter().enumerate() {
+            if video_item.hidden || video_item.locked || video_item.user_hidden {
+                continue;
+            }
+
+            // println!("Checking video point");
+
+            let point = self.group_local_point(&video_item.id, point);
+            if video_item.contains_point(&point, &camera) {
+                // println!("Video contains point");
+                intersecting_objects
+                    .push((video_item.layer, InteractionTarget::Video(video_index)));
+            }
+        }
+
+        // Collect intersecting particle emitters
+        for (emitter_index, emitter) in self.scene.particle_emitters.iter().enumerate() {
+            if emitter.hidden || emitter.locked || emitter.user_hidden {
+                continue;
+            }
+
+            let point = self.group_local_point(&emitter.id.to_string(), point);
+            if emitter.contains_point(&point) {
+                intersecting_objects.push((
+                    emitter.layer,
+                    InteractionTarget::ParticleEmitter(emitter_index),
+                ));
+            }
+        }
+
+        // Sort intersecting objects by layer in descending order (highest layer first)
+        // intersecting_objects.sort_by(|a, b| b.0.cmp(&a.0));
+
+        // sort by lowest layer first, for this system
+        intersecting_objects.sort_by(|a, b| a.0.cmp(&b.0));
+
+        // Return the topmost intersecting object, if any
+        intersecting_objects
+            .into_iter()
+            .next()
+            .map(|(_, target)| target)
+    }
+
+    pub fn start_handle_drag(&mut self, handle_id: Uuid, position: HandlePosition) {
+        if let Some(handle) = self.resize_handles.iter().find(|h| h.id == handle_id) {
+            // println!("start drag");
+            let object_id = handle.object_id;
+            self.dragging_handle = Some((object_id, position));
+            // resizes are measured from where they started, so locked aspect ratios don't drift
+            self.resize_start = self
+                .selected_object
+                .as_ref()
+                .and_then(|s| self.get_object_bounding_box(object_id, &s.object_type));
+        }
+    }
+
+    /// Resizes the object being dragged by a handle, by how far the mouse has moved since the
+    /// drag started. The opposite edge or corner stays put unless resizing from the center,
+    /// following the object's rotation so rotated objects grow along their own axes.
+    pub fn resize_selected_object(&mut self, drag_delta: Point) {
+        let camera = self.scene.camera.as_ref().expect("Couldn't get camera");
+        let window_size = camera.window_size;
+        let gpu_resources = self
+            .scene
+            .gpu_resources
+            .as_ref()
+            .expect("Couldn't get gpu resources");
+        let bind_group_layout = self
+            .scene
+            .model_bind_group_layout
+            .as_ref()
+            .expect("Couldn't get bind group layout");
+
+        // Extract the needed info first to avoid borrowing conflicts
+        let resize_info =
+            if let (Some((object_id, handle_position)), Some(selected_object), Some(start)) = (
+                &self.dragging_handle,
+                &self.selected_object,
+                self.resize_start,
+            ) {
+                Some((
+                    *object_id,
+                    *handle_position,
+                    selected_object.object_type.clone(),
+                    start,
+                ))
+            } else {
+                None
+            };
+
+        if let Some((object_id, handle_position, object_type, start)) = resize_info {
+            // bounds are unrotated, so measure the drag along the object's own axes
+            let rotation = self.get_object_rotation(object_id, &object_type);
+            let local_delta = rotate_offset(drag_delta, -rotation);
+            let bounds = resize_bounds(start, handle_position, local_delta, self.resize_modifiers);
+            let new_width = bounds.max.x - bounds.min.x;
+            let new_height = bounds.max.y - bounds.min.y;
+            let current = self.get_object_bounding_box(object_id, &object_type);
+
+            match object_type {
+                crate::animations::ObjectType::Polygon => {
+                    if let Some(polygon) =
+                        self.scene.polygons.iter_mut().find(|p| p.id == object_id)
+                    {
+                        // println!("resize_selected_object");
+
+                        polygon.update_data_from_dimensions(
+                            &camera.window_size,
+                            &gpu_resources.device,
+                            &gpu_resources.queue,
+                            &bind_group_layout,
+                            (new_width, new_height),
+                            &camera,
+                        );
+
+                        // TODO: should happen inside render loop for performance
+                        polygon
+                            .transform
+                            .update_uniform_buffer(&gpu_resources.queue, &camera.window_size);
+                    }
+                }
+                crate::animations::ObjectType::TextItem => {
+                    if let Some(text) = self.scene.text_items.iter_mut().find(|t| t.id == object_id)
+                    {
+                        text.update_data_from_dimensions(
+                            &camera.window_size,
+                            &gpu_resources.device,
+                            &gpu_resources.queue,
+                            &bind_group_layout,
+                            (new_width, new_height),
+                            &camera,
+                        );
+
+                        // TODO: should happen inside render loop for performance
+                        text.transform
+                            .update_uniform_buffer(&gpu_resources.queue, &camera.window_size);
+                    }
+                }
+                crate::animations::ObjectType::ImageItem => {
+                    if let Some(image) = self
+                        .scene
+                        .image_items
+                        .iter_mut()
+                        .find(|i| i.id == object_id.to_string())
+                    {
+                        image.update_data_from_dimensions(
+                            &camera.window_size,
+                            &gpu_resources.device,
+                            &gpu_resources.queue,
+                            &bind_group_layout,
+                            (new_width, new_height),
+                            &camera,
+                        );
+
+                        // TODO: should happen inside render loop for performance
+                        image
+                            .transform
+                            .update_uniform_buffer(&gpu_resources.queue, &camera.window_size);
+                    }
+                }
+                crate::animations::ObjectType::VideoItem => {
+                    if let Some(video) = self
+                        .scene
+                        .video_items
+                        .iter_mut()
+                        .find(|v| v.id == object_id.to_string())
+                    {
+                        video.update_data_from_dimensions(
+                            &camera.window_size,
+                            &gpu_resources.device,
+                            &gpu_resources.queue,
+                            &bind_group_layout,
+                            (new_width, new_height),
+                            &camera,
+                        );
+
+                        // TODO: should happen inside render loop for performance
+                        video
+                            .transform
+                            .update_uniform_buffer(&gpu_resources.queue, &camera.window_size);
+                    }
+                }
+                crate::animations::ObjectType::Group => {
+                    // groups are sized through their scale rather than their dimensions
+                    return;
+                }
+                crate::animations::ObjectType::ParticleEmitter => {
+                    if let Some(emitter) = self
+                        .scene
+                        .particle_emitters
+                        .iter_mut()
+                        .find(|e| e.id == object_id)
+                    {
+                        // resizing an emitter resizes the area particles spawn within
+                        emitter.config.dimensions = (new_width as i32, new_height as i32);
+                    }
+                }
+            }
+
+            // keep the anchored edge or corner in place, as positions are centers. The center
+            // shifts along the rotated axes, relative to where it was when the drag began.
+            if let Some(current) = current {
+                let shift = rotate_offset(
+                    Point {
+                        x: (bounds.min.x + bounds.max.x - start.min.x - start.max.x) / 2.0,
+                        y: (bounds.min.y + bounds.max.y - start.min.y - start.max.y) / 2.0,
+                    },
+                    rotation,
+                );
+                let delta = Point {
+                    x: (start.min.x + start.max.x - current.min.x - current.max.x) / 2.0 + shift.x,
+                    y: (start.min.y + start.max.y - current.min.y - current.max.y) / 2.0 + shift.y,
+                };
+                self.translate_object(object_id, object_type.clone(), delta, &window_size);
+            }
+
+            // Recreate handles after resizing
+            self.create_resize_handles_for_object(object_id, object_type);
+        }
+    }
+
+    // fn resize_text_item(text_item: &mut crate::text_due::TextRenderer, handle_position: &HandlePosition, mouse_delta: Point, gpu_resources: &GpuResources) {
+    //     let scale_factor = match handle_position {
     //         HandlePosition::Right | HandlePosition::Left => {
     //             let current_width = text_item.dimensions.0 as f32;
     //             let new_width = (current_width + mouse_delta.x).max(10.0);
@@ -962,303 +1668,22 @@ impl Editor {
 
     pub fn finish_handle_drag(&mut self) {
         self.dragging_handle = None;
-    }
-
-    pub fn restore_sequence_objects(
-        &mut self,
-        saved_sequence: &Sequence,
-        // window_size: WindowSize,
-        // camera: &Camera,
-        hidden: bool,
-        // device: &wgpu::Device,
-        // queue: &wgpu::Queue,
-    ) {
-        let gpu_resources = self
-            .gpu_resources
-            .as_ref()
-            .expect("Couldn't get gpu resources");
-        let device = &gpu_resources.device;
-        let queue = &gpu_resources.queue;
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
-        let window_size = camera.window_size;
-
-        saved_sequence.active_polygons.iter().for_each(|p| {
-            // let gpu_resources = self
-            //     .gpu_resources
-            //     .as_ref()
-            //     .expect("Couldn't get GPU Resources");
-
-            // Generate a random number between 0 and 800
-            // let random_number_800 = rng.gen_range(0..=800);
-
-            // Generate a random number between 0 and 450
-            // let random_number_450 = rng.gen_range(0..=450);
-
-            let mut restored_polygon = Polygon::new(
-                &window_size,
-                &device,
-                &queue,
-                &self
-                    .model_bind_group_layout
-                    .as_ref()
-                    .expect("Couldn't get model bind group layout"),
-                &self
-                    .group_bind_group_layout
-                    .as_ref()
-                    .expect("Couldn't get group bind group layout"),
-                &camera,
-                // TODO: restoring triangles or non rectangles?
-                vec![
-                    Point { x: 0.0, y: 0.0 },
-                    Point { x: 1.0, y: 0.0 },
-                    Point { x: 1.0, y: 1.0 },
-                    Point { x: 0.0, y: 1.0 },
-                ],
-                (p.dimensions.0 as f32, p.dimensions.1 as f32),
-                Point {
-                    // x: random_number_800 as f32,
-                    // y: random_number_450 as f32,
-                    x: p.position.x as f32,
-                    y: p.position.y as f32,
-                },
-                // TODO: restore rotation?
-                0.0,
-                p.border_radius as f32,
-                [
-                    p.fill[0] as f32,
-                    p.fill[1] as f32,
-                    p.fill[2] as f32,
-                    p.fill[3] as f32,
-                ],
-                Stroke {
-                    thickness: p.stroke.thickness as f32,
-                    fill: [
-                        p.stroke.fill[0] as f32,
-                        p.stroke.fill[1] as f32,
-                        p.stroke.fill[2] as f32,
-                        p.stroke.fill[3] as f32,
-                    ],
-                },
-                // -2.0,
-                p.layer.clone(),
-                p.name.clone(),
-                Uuid::from_str(&p.id).expect("Couldn't convert string to uuid"),
-                Uuid::from_str(&saved_sequence.id.clone())
-                    .expect("Couldn't convert string to uuid"),
-            );
-
-            restored_polygon.hidden = hidden;
-
-            // editor.add_polygon(restored_polygon);
-            self.polygons.push(restored_polygon);
-
-            println!("Polygon restored...");
-        });
-
-        saved_sequence.active_text_items.iter().for_each(|t| {
-            // let gpu_resources = self
-            //     .gpu_resources
-            //     .as_ref()
-            //     .expect("Couldn't get GPU Resources");
-
-            // TODO: save and restore chosen font
-
-            let position = Point {
-                x: CANVAS_HORIZ_OFFSET + t.position.x as f32,
-                y: CANVAS_VERT_OFFSET + t.position.y as f32,
-            };
-
-            let mut restored_text = TextRenderer::new(
-                &device,
-                &queue,
-                self.model_bind_group_layout
-                    .as_ref()
-                    .expect("Couldn't get model bind group layout"),
-                &self
-                    .group_bind_group_layout
-                    .as_ref()
-                    .expect("Couldn't get group bind group layout"),
-                self.font_manager
-                    .get_font_by_name(&t.font_family)
-                    .expect("Couldn't get font family"),
-                &window_size,
-                t.text.clone(),
-                TextRendererConfig {
-                    id: Uuid::from_str(&t.id).expect("Couldn't convert uuid"),
-                    name: t.name.clone(),
-                    text: t.text.clone(),
-                    font_family: t.font_family.clone(),
-                    dimensions: (t.dimensions.0 as f32, t.dimensions.1 as f32),
-                    position,
-                    layer: t.layer.clone(),
-                    color: t.color.clone(),
-                    font_size: t.font_size.clone(),
-                    background_fill: t.background_fill.unwrap_or([200, 200, 200, 255]),
-                },
-                Uuid::from_str(&t.id).expect("Couldn't convert string to uuid"),
-                Uuid::from_str(&saved_sequence.id.clone())
-                    .expect("Couldn't convert string to uuid"),
-                camera,
-            );
-
-            restored_text.hidden = hidden;
-
-            restored_text.render_text(&device, &queue);
-
-            // editor.add_polygon(restored_polygon);
-            self.text_items.push(restored_text);
-
-            println!("Text restored...");
-        });
-
-        saved_sequence.active_image_items.iter().for_each(|i| {
-            // let gpu_resources = self
-            //     .gpu_resources
-            //     .as_ref()
-            //     .expect("Couldn't get GPU Resources");
-
-            let position = Point {
-                x: CANVAS_HORIZ_OFFSET + i.position.x as f32,
-                y: CANVAS_VERT_OFFSET + i.position.y as f32,
-            };
-
-            let image_config = StImageConfig {
-                id: i.id.clone(),
-                name: i.name.clone(),
-                dimensions: i.dimensions.clone(),
-                path: i.path.clone(),
-                position,
-                layer: i.layer.clone(),
-            };
-
-            let mut restored_image = StImage::new(
-                &device,
-                &queue,
-                // string to Path
-                Path::new(&i.path),
-                image_config,
-                &window_size,
-                self.model_bind_group_layout
-                    .as_ref()
-                    .expect("Couldn't get model bind group layout"),
-                &self
-                    .group_bind_group_layout
-                    .as_ref()
-                    .expect("Couldn't get group bind group layout"),
-                -2.0,
-                i.id.clone(),
-                Uuid::from_str(&saved_sequence.id.clone())
-                    .expect("Couldn't convert string to uuid"),
-            );
-
-            restored_image.hidden = hidden;
-
-            // editor.add_polygon(restored_polygon);
-            self.image_items.push(restored_image);
-
-            println!("Image restored...");
-        });
-
-        saved_sequence.active_video_items.iter().for_each(|i| {
-            // let mut saved_mouse_path = None;
-            let mut source_data_path = None;
-            let mut stored_mouse_positions = None;
-            if let Some(mouse_path) = &i.mouse_path {
-                let mut mouse_pathbuf = Path::new(&mouse_path).to_path_buf();
-                mouse_pathbuf.pop();
-                source_data_path = Some(mouse_pathbuf.join("sourceData.json"));
-
-                if let Ok(positions) = fs::read_to_string(mouse_path) {
-                    if let Ok(mouse_positions) =
-                        serde_json::from_str::<Vec<MousePosition>>(&positions)
-                    {
-                        // saved_mouse_path = Some(mouse_path);
-                        stored_mouse_positions = Some(mouse_positions);
-                    }
-                }
-            }
-
-            let mut stored_source_data = None;
-            if let Some(source_path) = &source_data_path {
-                if let Ok(source_data) = fs::read_to_string(source_path) {
-                    if let Ok(data) = serde_json::from_str::<SourceData>(&source_data) {
-                        stored_source_data = Some(data);
-                    }
-                }
-            }
-
-            println!(
-                "Restoring video source data... {:?} {:?}",
-                source_data_path, stored_source_data
-            );
-
-            let position = Point {
-                x: CANVAS_HORIZ_OFFSET + i.position.x as f32,
-                y: CANVAS_VERT_OFFSET + i.position.y as f32,
-            };
-
-            let video_config = StVideoConfig {
-                id: i.id.clone(),
-                name: i.name.clone(),
-                dimensions: i.dimensions.clone(),
-                path: i.path.clone(),
-                position,
-                layer: i.layer.clone(),
-                mouse_path: i.mouse_path.clone(),
-            };
-
-            let mut restored_video = StVideo::new(
-                &device,
-                &queue,
-                // string to Path
-                Path::new(&i.path),
-                video_config,
-                &window_size,
-                self.model_bind_group_layout
-                    .as_ref()
-                    .expect("Couldn't get model bind group layout"),
-                &self
-                    .group_bind_group_layout
-                    .as_ref()
-                    .expect("Couldn't get group bind group layout"),
-                -2.0,
-                i.id.clone(),
-                Uuid::from_str(&saved_sequence.id.clone())
-                    .expect("Couldn't convert string to uuid"),
-            )
-            .expect("Couldn't restore video");
-
-            restored_video.hidden = hidden;
-
-            // set window data from capture
-            restored_video.source_data = stored_source_data;
-
-            // set mouse positions
-            restored_video.mouse_positions = stored_mouse_positions;
-
-            // render 1 frame to provide preview image
-            restored_video
-                .draw_video_frame(device, queue)
-                .expect("Couldn't draw video frame");
-
-            // editor.add_polygon(restored_polygon);
-            self.video_items.push(restored_video);
-
-            println!("Video restored...");
-        });
+        self.resize_start = None;
     }
 
     pub fn reset_sequence_objects(&mut self) {
-        if let Some(current_sequence) = &self.current_sequence_data {
+        if let Some(current_sequence) = &self.scene.current_sequence_data {
             let gpu_resources = self
+                .scene
                 .gpu_resources
                 .as_ref()
                 .expect("Couldn't get GPU Resources");
-            let camera = self.camera.as_ref().expect("Couldn't get camera");
+            let camera = self.scene.camera.as_ref().expect("Couldn't get camera");
 
             // put all objects back in original positions
             current_sequence.active_polygons.iter().for_each(|p| {
                 let polygon = self
+                    .scene
                     .polygons
                     .iter_mut()
                     .find(|polygon| polygon.id.to_string() == p.id)
@@ -1278,6 +1703,7 @@ impl Editor {
 
             current_sequence.active_text_items.iter().for_each(|t| {
                 let text = self
+                    .scene
                     .text_items
                     .iter_mut()
                     .find(|text| text.id.to_string() == t.id)
@@ -1310,6 +1736,7 @@ impl Editor {
 
             current_sequence.active_image_items.iter().for_each(|i| {
                 let image = self
+                    .scene
                     .image_items
                     .iter_mut()
                     .find(|image| image.id == i.id)
@@ -1331,6 +1758,7 @@ impl Editor {
 
             current_sequence.active_video_items.iter().for_each(|i| {
                 let video = self
+                    .scene
                     .video_items
                     .iter_mut()
                     .find(|video| video.id == i.id)
@@ -1353,18 +1781,72 @@ impl Editor {
 
                 // TODO: reset other properties once scale is figured out
             });
+
+            current_sequence
+                .active_particle_emitters
+                .iter()
+                .for_each(|e| {
+                    let emitter = self
+                        .scene
+                        .particle_emitters
+                        .iter_mut()
+                        .find(|emitter| emitter.id.to_string() == e.id)
+                        .expect("Couldn't find particle emitter");
+
+                    emitter.transform.position.x = e.position.x as f32 + CANVAS_HORIZ_OFFSET;
+                    emitter.transform.position.y = e.position.y as f32 + CANVAS_VERT_OFFSET;
+                    emitter.transform.rotation = 0.0;
+                    emitter.transform.update_scale([1.0, 1.0]);
+
+                    emitter
+                        .transform
+                        .update_uniform_buffer(&gpu_resources.queue, &camera.window_size);
+
+                    emitter.rate = e.rate as f32;
+                    emitter.update_opacity(1.0);
+                    emitter.reset();
+                });
+
+            current_sequence.active_groups.iter().for_each(|g| {
+                let group = self
+                    .scene
+                    .groups
+                    .iter_mut()
+                    .find(|group| group.id.to_string() == g.id)
+                    .expect("Couldn't find group");
+
+                group.transform.position.x = g.position.x as f32 + CANVAS_HORIZ_OFFSET;
+                group.transform.position.y = g.position.y as f32 + CANVAS_VERT_OFFSET;
+                group.transform.update_rotation_degrees(g.rotation as f32);
+                group
+                    .transform
+                    .update_scale([g.scale as f32 / 100.0, g.scale as f32 / 100.0]);
+
+                group.update_uniform_buffer(&gpu_resources.queue);
+            });
         }
+
+        // resetting the opacity flattened spotlights into plain rectangles
+        self.scene.spotlight_regions.clear();
+        let spotlights = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .map(|s| s.active_spotlights.clone())
+            .unwrap_or_default();
+        self.scene.draw_spotlights(&spotlights);
     }
 
     pub fn run_motion_inference(&self) -> Vec<AnimationData> {
         let mut prompt = "".to_string();
         let mut total = 0;
-        for (i, polygon) in self.polygons.iter().enumerate() {
+        for (i, polygon) in self.scene.polygons.iter().enumerate() {
             if !polygon.hidden {
-                let x = polygon.transform.position.x - CANVAS_HORIZ_OFFSET;
-                let x = (x / 800.0) * 100.0; // testing percentage based training
-                let y = polygon.transform.position.y - CANVAS_VERT_OFFSET;
-                let y = (y / 450.0) * 100.0;
+                // testing percentage based training
+                let (x, y) = self.scene.canvas_size.to_inference(
+                    polygon.transform.position.x - CANVAS_HORIZ_OFFSET,
+                    polygon.transform.position.y - CANVAS_VERT_OFFSET,
+                );
 
                 prompt.push_str(&total.to_string());
                 prompt.push_str(", ");
@@ -1389,12 +1871,13 @@ impl Editor {
             }
         }
 
-        for (i, text) in self.text_items.iter().enumerate() {
+        for (i, text) in self.scene.text_items.iter().enumerate() {
             if !text.hidden {
-                let x = text.transform.position.x - CANVAS_HORIZ_OFFSET;
-                let x = (x / 800.0) * 100.0; // testing percentage based training
-                let y = text.transform.position.y - CANVAS_VERT_OFFSET;
-                let y = (y / 450.0) * 100.0;
+                // testing percentage based training
+                let (x, y) = self.scene.canvas_size.to_inference(
+                    text.transform.position.x - CANVAS_HORIZ_OFFSET,
+                    text.transform.position.y - CANVAS_VERT_OFFSET,
+                );
 
                 prompt.push_str(&total.to_string());
                 prompt.push_str(", ");
@@ -1418,12 +1901,13 @@ impl Editor {
             }
         }
 
-        for (i, image) in self.image_items.iter().enumerate() {
+        for (i, image) in self.scene.image_items.iter().enumerate() {
             if !image.hidden {
-                let x = image.transform.position.x - CANVAS_HORIZ_OFFSET;
-                let x = (x / 800.0) * 100.0; // testing percentage based training
-                let y = image.transform.position.y - CANVAS_VERT_OFFSET;
-                let y = (y / 450.0) * 100.0;
+                // testing percentage based training
+                let (x, y) = self.scene.canvas_size.to_inference(
+                    image.transform.position.x - CANVAS_HORIZ_OFFSET,
+                    image.transform.position.y - CANVAS_VERT_OFFSET,
+                );
 
                 prompt.push_str(&total.to_string());
                 prompt.push_str(", ");
@@ -1448,12 +1932,13 @@ impl Editor {
             }
         }
 
-        for (i, video) in self.video_items.iter().enumerate() {
+        for (i, video) in self.scene.video_items.iter().enumerate() {
             if !video.hidden {
-                let x = video.transform.position.x - CANVAS_HORIZ_OFFSET;
-                let x = (x / 800.0) * 100.0; // testing percentage based training
-                let y = video.transform.position.y - CANVAS_VERT_OFFSET;
-                let y = (y / 450.0) * 100.0;
+                // testing percentage based training
+                let (x, y) = self.scene.canvas_size.to_inference(
+                    video.transform.position.x - CANVAS_HORIZ_OFFSET,
+                    video.transform.position.y - CANVAS_VERT_OFFSET,
+                );
 
                 prompt.push_str(&total.to_string());
                 prompt.push_str(", ");
@@ -1501,32 +1986,332 @@ impl Editor {
         Vec::new()
     }
 
-    // pub fn create_motion_paths_from_predictions(
-    //     &self,
-    //     predictions: Vec<f32>,
-    // ) -> Vec<AnimationData> {
-    //     let mut animation_data_vec = Vec::new();
-    //     let values_per_prediction = NUM_INFERENCE_FEATURES; // object_index, time, width, height, x, y
-    //     let keyframes_per_object = 6; // number of keyframes per object
-    //     let timestamp_percs = vec![
-    //         0.0,
-    //         2500.0 / 20000.0,
-    //         5000.0 / 20000.0,
-    //         15000.0 / 20000.0,
-    //         17500.0 / 20000.0,
-    //         20000.0 / 20000.0,
-    //     ];
-
-    //     println!("timestamp_percs {:?}", timestamp_percs);
+    /// A motion inference prompt with just one object, for rerolling its path
+    pub fn object_motion_prompt(&self, object_id: Uuid) -> Option<String> {
+        let (_, dimensions, position) = self.inference_object(object_id)?;
+        let (x, y) = self.scene.canvas_size.to_inference(
+            position.x - CANVAS_HORIZ_OFFSET,
+            position.y - CANVAS_VERT_OFFSET,
+        );
 
-    //     // Calculate total number of objects from predictions
-    //     let total_predictions = predictions.len();
-    //     let num_objects = total_predictions / (values_per_prediction * keyframes_per_object);
+        Some(format!(
+            "0, 5, {}, {}, {}, {}, 0.000, \n",
+            dimensions.0,
+            dimensions.1,
+            x.round() as i32,
+            y.round() as i32
+        ))
+    }
 
-    //     // Get the current positions of all objects
-    //     let mut current_positions = Vec::new();
-    //     let mut total = 0; // use controlled total as get_item_id function filters by hidden
-    //     for (i, polygon) in self.polygons.iter().enumerate() {
+    /// Replaces one object's generated path with one from the predictions for its own
+    /// prompt, varied by the seed and controls, leaving other objects alone. An object
+    /// that's already animated keeps its other keyframes.
+    pub fn reroll_object_motion(
+        &mut self,
+        object_id: Uuid,
+        predictions: &[f32],
+        variation: &MotionVariation,
+    ) -> Result<(), String> {
+        let (object_type, _, position) = self
+            .inference_object(object_id)
+            .ok_or("Couldn't find the object to animate")?;
+        let keyframes_per_object = 6;
+        if predictions.len() < NUM_INFERENCE_FEATURES * keyframes_per_object {
+            return Err("Not enough predictions for a path".to_string());
+        }
+
+        let predicted: Vec<[i32; 2]> = predictions
+            .chunks(NUM_INFERENCE_FEATURES)
+            .take(keyframes_per_object)
+            .map(|row| {
+                let (x, y) = self.scene.canvas_size.from_inference(row[4], row[5]);
+                [x.round() as i32, y.round() as i32]
+            })
+            .collect();
+        // centered on the object, as when generating every object's path
+        let anchor = [
+            (position.x - CANVAS_HORIZ_OFFSET) as i32,
+            (position.y - CANVAS_VERT_OFFSET) as i32,
+        ];
+        let (offset_x, offset_y) = (anchor[0] - predicted[2][0], anchor[1] - predicted[2][1]);
+        let path: Vec<[i32; 2]> = predicted
+            .iter()
+            .map(|p| [p[0] + offset_x, p[1] + offset_y])
+            .collect();
+        let path = variation.apply(&path, anchor);
+
+        let object_key = object_id.to_string();
+        let total_duration = match object_type {
+            ObjectType::VideoItem => self
+                .scene
+                .video_items
+                .iter()
+                .find(|v| v.id == object_key)
+                .map_or(20000.0, |v| v.timeline_duration_ms() as f32),
+            _ => 20000.0,
+        };
+        let mut animation = self
+            .animation_from_path(object_key.clone(), object_type, &path, total_duration)
+            .ok_or("Couldn't make a path for the object")?;
+
+        let sequence = self
+            .scene
+            .current_sequence_data
+            .as_mut()
+            .ok_or("No sequence selected")?;
+        match sequence
+            .polygon_motion_paths
+            .iter_mut()
+            .find(|a| a.polygon_id == object_key)
+        {
+            Some(existing) => {
+                // generated animations start with their position
+                let generated = animation.properties.remove(0);
+                match existing
+                    .properties
+                    .iter_mut()
+                    .find(|p| p.property_path == "position")
+                {
+                    Some(property) => property.keyframes = generated.keyframes,
+                    None => existing.properties.insert(0, generated),
+                }
+            }
+            None => sequence.polygon_motion_paths.push(animation),
+        }
+
+        self.save_current_sequence();
+        let sequence = self
+            .scene
+            .current_sequence_data
+            .clone()
+            .expect("Couldn't get current sequence");
+        self.update_motion_path(&sequence, &object_key)?;
+
+        Ok(())
+    }
+
+    /// Replaces an object's animation with a preset motion, keeping when it starts and its
+    /// motion blur
+    pub fn apply_motion_preset(
+        &mut self,
+        object_id: Uuid,
+        settings: &PresetSettings,
+    ) -> Result<(), String> {
+        let (object_type, dimensions, position) = self
+            .inference_object(object_id)
+            .ok_or("Couldn't find the object to animate")?;
+        let position = [
+            (position.x - CANVAS_HORIZ_OFFSET).round() as i32,
+            (position.y - CANVAS_VERT_OFFSET).round() as i32,
+        ];
+        let object_key = object_id.to_string();
+        let animation = preset_animation(
+            settings,
+            object_key.clone(),
+            object_type,
+            position,
+            dimensions,
+            &self.scene.canvas_size,
+        );
+        self.replace_animation(animation)?;
+
+        self.save_current_sequence();
+        let sequence = self
+            .scene
+            .current_sequence_data
+            .clone()
+            .expect("Couldn't get current sequence");
+        self.update_motion_path(&sequence, &object_key)?;
+
+        Ok(())
+    }
+
+    /// Plays one entrance on several objects in turn, each waiting the template's delay
+    /// after the one before. Without an entrance, the objects' own animations are held
+    /// back instead, on top of any delay they already have.
+    pub fn apply_stagger(
+        &mut self,
+        object_ids: &[Uuid],
+        template: &StaggerTemplate,
+    ) -> Result<(), String> {
+        let mut objects = Vec::new();
+        for object_id in object_ids {
+            let (object_type, dimensions, position) = self
+                .inference_object(*object_id)
+                .ok_or_else(|| format!("Couldn't find object {}", object_id))?;
+            let layer = self.object_layer(*object_id).unwrap_or(0);
+            objects.push((*object_id, object_type, dimensions, position, layer));
+        }
+
+        let placement: Vec<(f32, i32)> = objects
+            .iter()
+            .map(|(_, _, _, position, layer)| (position.x, *layer))
+            .collect();
+        for (turn, index) in stagger_sequence(&placement, template.order)
+            .into_iter()
+            .enumerate()
+        {
+            let (object_id, object_type, dimensions, position, _) = objects[index].clone();
+            let object_key = object_id.to_string();
+            let delay_ms = template.delay_ms.max(0) * turn as i32;
+
+            match &template.entrance {
+                Some(entrance) => {
+                    let position = [
+                        (position.x - CANVAS_HORIZ_OFFSET).round() as i32,
+                        (position.y - CANVAS_VERT_OFFSET).round() as i32,
+                    ];
+                    let mut animation = preset_animation(
+                        entrance,
+                        object_key,
+                        object_type,
+                        position,
+                        dimensions,
+                        &self.scene.canvas_size,
+                    );
+                    delay_animation(&mut animation, delay_ms);
+                    self.replace_animation(animation)?;
+                }
+                None => {
+                    let animation = self
+                        .scene
+                        .current_sequence_data
+                        .as_mut()
+                        .ok_or("No sequence selected")?
+                        .polygon_motion_paths
+                        .iter_mut()
+                        .find(|a| a.polygon_id == object_key)
+                        .ok_or_else(|| format!("No animation found for {}", object_key))?;
+                    delay_animation(animation, delay_ms);
+                }
+            }
+        }
+
+        self.save_current_sequence();
+        let sequence = self
+            .scene
+            .current_sequence_data
+            .clone()
+            .expect("Couldn't get current sequence");
+        self.update_motion_paths(&sequence);
+
+        Ok(())
+    }
+
+    /// Puts an animation in the current sequence in place of the one its object has,
+    /// keeping when that started and its motion blur, without saving
+    fn replace_animation(&mut self, mut animation: AnimationData) -> Result<(), String> {
+        let sequence = self
+            .scene
+            .current_sequence_data
+            .as_mut()
+            .ok_or("No sequence selected")?;
+        match sequence
+            .polygon_motion_paths
+            .iter_mut()
+            .find(|a| a.polygon_id == animation.polygon_id)
+        {
+            Some(existing) => {
+                animation.id = existing.id.clone();
+                animation.start_time_ms = existing.start_time_ms;
+                animation.motion_blur = existing.motion_blur;
+                *existing = animation;
+            }
+            None => sequence.polygon_motion_paths.push(animation),
+        }
+
+        Ok(())
+    }
+
+    fn object_layer(&self, object_id: Uuid) -> Option<i32> {
+        let object_key = object_id.to_string();
+
+        self.scene
+            .polygons
+            .iter()
+            .find(|p| p.id == object_id)
+            .map(|p| p.layer)
+            .or_else(|| {
+                self.scene
+                    .text_items
+                    .iter()
+                    .find(|t| t.id == object_id)
+                    .map(|t| t.layer)
+            })
+            .or_else(|| {
+                self.scene
+                    .image_items
+                    .iter()
+                    .find(|i| i.id == object_key)
+                    .map(|i| i.layer)
+            })
+            .or_else(|| {
+                self.scene
+                    .video_items
+                    .iter()
+                    .find(|v| v.id == object_key)
+                    .map(|v| v.layer)
+            })
+    }
+
+    /// An object's type, size and position, as motion inference sees them
+    fn inference_object(&self, object_id: Uuid) -> Option<(ObjectType, (f32, f32), Vector2<f32>)> {
+        let object_key = object_id.to_string();
+        if let Some(polygon) = self.scene.polygons.iter().find(|p| p.id == object_id) {
+            return Some((
+                ObjectType::Polygon,
+                polygon.dimensions,
+                polygon.transform.position,
+            ));
+        }
+        if let Some(text) = self.scene.text_items.iter().find(|t| t.id == object_id) {
+            return Some((
+                ObjectType::TextItem,
+                text.dimensions,
+                text.transform.position,
+            ));
+        }
+        if let Some(image) = self.scene.image_items.iter().find(|i| i.id == object_key) {
+            let dimensions = (image.dimensions.0 as f32, image.dimensions.1 as f32);
+            return Some((ObjectType::ImageItem, dimensions, image.transform.position));
+        }
+
+        self.scene
+            .video_items
+            .iter()
+            .find(|v| v.id == object_key)
+            .map(|video| {
+                let dimensions = (video.dimensions.0 as f32, video.dimensions.1 as f32);
+                (ObjectType::VideoItem, dimensions, video.transform.position)
+            })
+    }
+
+    // pub fn create_motion_paths_from_predictions(
+    //     &self,
+    //     predictions: Vec<f32>,
+    // ) -> Vec<AnimationData> {
+    //     let mut animation_data_vec = Vec::new();
+    //     let values_per_prediction = NUM_INFERENCE_FEATURES; // object_index, time, width, height, x, y
+    //     let keyframes_per_object = 6; // number of keyframes per object
+    //     let timestamp_percs = vec![
+    //         0.0,
+    //         2500.0 / 20000.0,
+    //         5000.0 / 20000.0,
+    //         15000.0 / 20000.0,
+    //         17500.0 / 20000.0,
+    //         20000.0 / 20000.0,
+    //     ];
+
+    //     println!("timestamp_percs {:?}", timestamp_percs);
+
+    //     // Calculate total number of objects from predictions
+    //     let total_predictions = predictions.len();
+    //     let num_objects = total_predictions / (values_per_prediction * keyframes_per_object);
+
+    //     // Get the current positions of all objects
+    //     let mut current_positions = Vec::new();
+    //     let mut total = 0; // use controlled total as get_item_id function filters by hidden
+    //     for (i, polygon) in self.scene.polygons.iter().enumerate() {
     //         if !polygon.hidden {
     //             current_positions.push((
     //                 total,
@@ -1537,7 +2322,7 @@ impl Editor {
     //             total = total + 1;
     //         }
     //     }
-    //     for (i, text) in self.text_items.iter().enumerate() {
+    //     for (i, text) in self.scene.text_items.iter().enumerate() {
     //         if !text.hidden {
     //             current_positions.push((
     //                 total,
@@ -1548,7 +2333,7 @@ impl Editor {
     //             total = total + 1;
     //         }
     //     }
-    //     for (i, image) in self.image_items.iter().enumerate() {
+    //     for (i, image) in self.scene.image_items.iter().enumerate() {
     //         if !image.hidden {
     //             current_positions.push((
     //                 total,
@@ -1559,7 +2344,7 @@ impl Editor {
     //             total = total + 1;
     //         }
     //     }
-    //     for (i, video) in self.video_items.iter().enumerate() {
+    //     for (i, video) in self.scene.video_items.iter().enumerate() {
     //         if !video.hidden {
     //             current_positions.push((
     //                 total,
@@ -1844,12 +2629,6 @@ impl Editor {
         //     20000.0 / 20000.0,
         // ];
 
-        let timestamp_diffs = vec![
-            // from start
-            0.0, 2500.0, 5000.0, // from end
-            -5000.0, -2500.0, 0.0,
-        ];
-
         // Calculate total number of objects from predictions
         let total_predictions = predictions.len();
         let num_objects = total_predictions / (values_per_prediction * keyframes_per_object);
@@ -1857,7 +2636,7 @@ impl Editor {
         // Get current positions of all objects
         let mut current_positions = Vec::new();
         let mut total = 0;
-        for (i, polygon) in self.polygons.iter().enumerate() {
+        for (i, polygon) in self.scene.polygons.iter().enumerate() {
             if !polygon.hidden {
                 current_positions.push((
                     total,
@@ -1868,7 +2647,7 @@ impl Editor {
                 total = total + 1;
             }
         }
-        for (i, text) in self.text_items.iter().enumerate() {
+        for (i, text) in self.scene.text_items.iter().enumerate() {
             if !text.hidden {
                 current_positions.push((
                     total,
@@ -1879,7 +2658,7 @@ impl Editor {
                 total = total + 1;
             }
         }
-        for (i, image) in self.image_items.iter().enumerate() {
+        for (i, image) in self.scene.image_items.iter().enumerate() {
             if !image.hidden {
                 current_positions.push((
                     total,
@@ -1890,11 +2669,11 @@ impl Editor {
                 total = total + 1;
             }
         }
-        for (i, video) in self.video_items.iter().enumerate() {
+        for (i, video) in self.scene.video_items.iter().enumerate() {
             if !video.hidden {
                 current_positions.push((
                     total,
-                    video.source_duration_ms,
+                    video.timeline_duration_ms(),
                     video.transform.position.x - CANVAS_HORIZ_OFFSET,
                     video.transform.position.y - CANVAS_VERT_OFFSET,
                 ));
@@ -1919,8 +2698,11 @@ impl Editor {
                         continue;
                     }
 
-                    let x = ((predictions[base_idx + 4] * 0.01) * 800.0).round() as i32;
-                    let y = ((predictions[base_idx + 5] * 0.01) * 450.0).round() as i32;
+                    let (x, y) = self
+                        .scene
+                        .canvas_size
+                        .from_inference(predictions[base_idx + 4], predictions[base_idx + 5]);
+                    let (x, y) = (x.round() as i32, y.round() as i32);
 
                     if let (Some(px), Some(py)) = (prev_x, prev_y) {
                         let dx = (x - px) as f32;
@@ -1945,27 +2727,16 @@ impl Editor {
             let object_type = self.get_object_type(object_idx);
 
             let total_duration = match object_type.clone().expect("Couldn't get object type") {
-                ObjectType::VideoItem => {
-                    self.video_items
-                        .iter()
-                        .find(|v| v.id == item_id.clone().expect("Couldn't get item id"))
-                        .expect("Couldn't get video")
-                        .source_duration_ms as f32
-                }
+                ObjectType::VideoItem => self
+                    .scene
+                    .video_items
+                    .iter()
+                    .find(|v| v.id == item_id.clone().expect("Couldn't get item id"))
+                    .expect("Couldn't get video")
+                    .timeline_duration_ms() as f32,
                 _ => 20000.0,
             };
 
-            let timestamps = vec![
-                // from start
-                0.0,
-                2500.0,
-                5000.0,
-                // from end
-                total_duration - 5000.0,
-                total_duration - 2500.0,
-                total_duration,
-            ];
-
             // Determine which path to use
             let path_source_idx = if self.generation_choreographed {
                 longest_path.unwrap_or(object_idx)
@@ -1973,8 +2744,6 @@ impl Editor {
                 object_idx
             };
 
-            let mut position_keyframes = Vec::new();
-
             // Get the object's current position
             let (_, _, current_x, current_y) = current_positions[object_idx];
 
@@ -1983,20 +2752,19 @@ impl Editor {
             //     (timestamp_percs[2] + timestamp_percs[3]) / 2.0 * total_duration;
             let range_center_idx = path_source_idx * (values_per_prediction * keyframes_per_object)
                 + 2 * values_per_prediction;
-            let center_x = ((predictions[range_center_idx + 4] * 0.01) * 800.0).round() as i32;
-            let center_y = ((predictions[range_center_idx + 5] * 0.01) * 450.0).round() as i32;
+            let (center_x, center_y) = self.scene.canvas_size.from_inference(
+                predictions[range_center_idx + 4],
+                predictions[range_center_idx + 5],
+            );
+            let (center_x, center_y) = (center_x.round() as i32, center_y.round() as i32);
 
             // Calculate offset to center the path on the object
             let offset_x = current_x as i32 - center_x;
             let offset_y = current_y as i32 - center_y;
 
-            // Create keyframes with the offset applied
+            // The predicted path, moved so its middle lands on the object
+            let mut path = Vec::new();
             for keyframe_time_idx in 0..keyframes_per_object {
-                if self.generation_count == 4 && (keyframe_time_idx == 1 || keyframe_time_idx == 4)
-                {
-                    continue;
-                }
-
                 let base_idx = path_source_idx * (values_per_prediction * keyframes_per_object)
                     + keyframe_time_idx * values_per_prediction;
 
@@ -2004,191 +2772,328 @@ impl Editor {
                     continue;
                 }
 
-                let predicted_x =
-                    ((predictions[base_idx + 4] * 0.01) * 800.0).round() as i32 + offset_x;
-                let predicted_y =
-                    ((predictions[base_idx + 5] * 0.01) * 450.0).round() as i32 + offset_y;
+                let (predicted_x, predicted_y) = self
+                    .scene
+                    .canvas_size
+                    .from_inference(predictions[base_idx + 4], predictions[base_idx + 5]);
+                path.push([
+                    predicted_x.round() as i32 + offset_x,
+                    predicted_y.round() as i32 + offset_y,
+                ]);
+            }
 
-                // Calculate timestamp based on whether it's relative to start or end
-                let timestamp = if keyframe_time_idx < 3 {
-                    // First three timestamps are relative to start
-                    timestamp_diffs[keyframe_time_idx]
-                } else {
-                    // Last three timestamps are relative to end
-                    total_duration + timestamp_diffs[keyframe_time_idx]
-                };
+            if let Some(item_id) = item_id {
+                animation_data_vec.extend(self.animation_from_path(
+                    item_id,
+                    object_type.unwrap_or(ObjectType::Polygon),
+                    &path,
+                    total_duration,
+                ));
+            }
+        }
 
-                let keyframe = UIKeyframe {
-                    id: Uuid::new_v4().to_string(),
-                    time: Duration::from_millis(timestamp as u64),
-                    value: KeyframeValue::Position([predicted_x, predicted_y]),
-                    easing: EasingType::EaseInOut,
-                    path_type: PathType::Linear,
-                    key_type: KeyType::Frame,
-                };
+        animation_data_vec
+    }
 
-                position_keyframes.push(keyframe);
-            }
+    /// An object's generated animation, following a path of up to six canvas positions
+    /// timed from the start and end of the animation, with the generation options applied
+    fn animation_from_path(
+        &self,
+        item_id: String,
+        object_type: ObjectType,
+        path: &[[i32; 2]],
+        total_duration: f32,
+    ) -> Option<AnimationData> {
+        let timestamp_diffs = vec![
+            // from start
+            0.0, 2500.0, 5000.0, // from end
+            -5000.0, -2500.0, 0.0,
+        ];
 
-            // Handle Range keyframes
-            if position_keyframes.len() == 6 {
-                let forth_keyframe = &position_keyframes.clone()[3];
-                let third_keyframe = &mut position_keyframes[2];
-                third_keyframe.key_type = KeyType::Range(RangeData {
-                    end_time: forth_keyframe.time,
-                });
-                position_keyframes.remove(3);
+        let timestamps = vec![
+            // from start
+            0.0,
+            2500.0,
+            5000.0,
+            // from end
+            total_duration - 5000.0,
+            total_duration - 2500.0,
+            total_duration,
+        ];
+
+        let mut position_keyframes = Vec::new();
+        for (keyframe_time_idx, position) in path.iter().enumerate() {
+            if self.generation_count == 4 && (keyframe_time_idx == 1 || keyframe_time_idx == 4) {
+                continue;
             }
 
-            if position_keyframes.len() == 4 {
-                let mid2_keyframe = &position_keyframes.clone()[2];
-                let mid_keyframe = &mut position_keyframes[1];
-                mid_keyframe.key_type = KeyType::Range(RangeData {
-                    end_time: mid2_keyframe.time,
-                });
-                position_keyframes.remove(2);
+            // Calculate timestamp based on whether it's relative to start or end
+            let timestamp = if keyframe_time_idx < 3 {
+                // First three timestamps are relative to start
+                timestamp_diffs[keyframe_time_idx]
+            } else {
+                // Last three timestamps are relative to end
+                total_duration + timestamp_diffs[keyframe_time_idx]
+            };
+
+            let keyframe = UIKeyframe {
+                id: Uuid::new_v4().to_string(),
+                time: Duration::from_millis(timestamp as u64),
+                value: KeyframeValue::Position(*position),
+                easing: EasingType::EaseInOut,
+                path_type: PathType::Linear,
+                key_type: KeyType::Frame,
+            };
+
+            position_keyframes.push(keyframe);
+        }
+
+        // Handle Range keyframes
+        if position_keyframes.len() == 6 {
+            let forth_keyframe = &position_keyframes.clone()[3];
+            let third_keyframe = &mut position_keyframes[2];
+            third_keyframe.key_type = KeyType::Range(RangeData {
+                end_time: forth_keyframe.time,
+            });
+            position_keyframes.remove(3);
+        }
+
+        if position_keyframes.len() == 4 {
+            let mid2_keyframe = &position_keyframes.clone()[2];
+            let mid_keyframe = &mut position_keyframes[1];
+            mid_keyframe.key_type = KeyType::Range(RangeData {
+                end_time: mid2_keyframe.time,
+            });
+            position_keyframes.remove(2);
+        }
+
+        // Create final keyframes with curves if needed
+        let mut final_position_keyframes: Vec<UIKeyframe> = Vec::new();
+        if self.generation_curved {
+            for keyframe in position_keyframes.iter() {
+                if let Some(prev_keyframe) = final_position_keyframes.last_mut() {
+                    prev_keyframe.path_type = prev_keyframe.calculate_default_curve(&keyframe);
+                }
+                final_position_keyframes.push(keyframe.clone());
             }
+        } else {
+            final_position_keyframes = position_keyframes;
+        }
 
-            // Create final keyframes with curves if needed
-            let mut final_position_keyframes: Vec<UIKeyframe> = Vec::new();
-            if self.generation_curved {
-                for keyframe in position_keyframes.iter() {
-                    if let Some(prev_keyframe) = final_position_keyframes.last_mut() {
-                        prev_keyframe.path_type = prev_keyframe.calculate_default_curve(&keyframe);
-                    }
-                    final_position_keyframes.push(keyframe.clone());
+        if final_position_keyframes.is_empty() {
+            return None;
+        }
+
+        // longer on the axis the object moves along, squashed on the other
+        let stretch_between = |from: &UIKeyframe, to: &UIKeyframe| match (&from.value, &to.value) {
+            (KeyframeValue::Position(a), KeyframeValue::Position(b)) if a != b => {
+                if (b[0] - a[0]).abs() >= (b[1] - a[1]).abs() {
+                    [115, 87]
+                } else {
+                    [87, 115]
                 }
-            } else {
-                final_position_keyframes = position_keyframes;
-            }
-
-            // Create animation data (keep existing code for creating properties)
-            if !final_position_keyframes.is_empty() && item_id.is_some() {
-                let mut properties = vec![
-                    // Position property with predicted values
-                    AnimationProperty {
-                        name: "Position".to_string(),
-                        property_path: "position".to_string(),
-                        children: Vec::new(),
-                        keyframes: final_position_keyframes,
-                        depth: 0,
-                    },
-                    // Default properties for rotation, scale, opacity
-                    AnimationProperty {
-                        name: "Rotation".to_string(),
-                        property_path: "rotation".to_string(),
-                        children: Vec::new(),
-                        keyframes: timestamps
-                            .iter()
-                            .map(|&t| UIKeyframe {
-                                id: Uuid::new_v4().to_string(),
-                                time: Duration::from_millis(t as u64),
-                                value: KeyframeValue::Rotation(0),
-                                easing: EasingType::EaseInOut,
-                                path_type: PathType::Linear,
-                                // should be same as position? or safe to be independent?
-                                key_type: KeyType::Frame,
-                            })
-                            .collect(),
-                        depth: 0,
-                    },
-                    AnimationProperty {
-                        name: "Scale".to_string(),
-                        property_path: "scale".to_string(),
-                        children: Vec::new(),
-                        keyframes: timestamps
-                            .iter()
-                            .map(|&t| UIKeyframe {
-                                id: Uuid::new_v4().to_string(),
-                                time: Duration::from_millis(t as u64),
-                                value: KeyframeValue::Scale(100),
-                                easing: EasingType::EaseInOut,
-                                path_type: PathType::Linear,
-                                // should be same as position? or safe to be independent?
-                                key_type: KeyType::Frame,
-                            })
-                            .collect(),
-                        depth: 0,
-                    },
-                    AnimationProperty {
-                        name: "Opacity".to_string(),
-                        property_path: "opacity".to_string(),
-                        children: Vec::new(),
-                        keyframes: timestamps
-                            .iter()
-                            .enumerate()
-                            .map(|(i, &t)| {
-                                let mut opacity = 100;
-                                if self.generation_fade {
-                                    if i == 0 || i == timestamps.len() - 1 {
-                                        opacity = 0;
-                                    }
-                                }
+            }
+            _ => [100, 100],
+        };
+        let last = final_position_keyframes.len() - 1;
+        let entering = stretch_between(
+            &final_position_keyframes[0],
+            &final_position_keyframes[1.min(last)],
+        );
+        let leaving = stretch_between(
+            &final_position_keyframes[last.saturating_sub(1)],
+            &final_position_keyframes[last],
+        );
 
-                                UIKeyframe {
-                                    id: Uuid::new_v4().to_string(),
-                                    time: Duration::from_millis(t as u64),
-                                    value: KeyframeValue::Opacity(opacity),
-                                    easing: EasingType::EaseInOut,
-                                    path_type: PathType::Linear,
-                                    // should be same as position? or safe to be independent?
-                                    key_type: KeyType::Frame,
-                                }
-                            })
-                            .collect(),
-                        depth: 0,
-                    },
-                ];
-
-                if object_type.as_ref().unwrap_or(&ObjectType::Polygon) == &ObjectType::VideoItem {
-                    properties.push(AnimationProperty {
-                        name: "Zoom / Popout".to_string(),
-                        property_path: "zoom".to_string(),
-                        children: Vec::new(),
-                        keyframes: timestamps
-                            .iter()
-                            .map(|&t| UIKeyframe {
+        let mut properties = vec![
+            // Position property with predicted values
+            AnimationProperty {
+                name: "Position".to_string(),
+                property_path: "position".to_string(),
+                children: Vec::new(),
+                keyframes: final_position_keyframes,
+                depth: 0,
+            },
+            // Default properties for rotation, scale, opacity
+            AnimationProperty {
+                name: "Rotation".to_string(),
+                property_path: "rotation".to_string(),
+                children: Vec::new(),
+                keyframes: timestamps
+                    .iter()
+                    .map(|&t| UIKeyframe {
+                        id: Uuid::new_v4().to_string(),
+                        time: Duration::from_millis(t as u64),
+                        value: KeyframeValue::Rotation(0),
+                        easing: EasingType::EaseInOut,
+                        path_type: PathType::Linear,
+                        // should be same as position? or safe to be independent?
+                        key_type: KeyType::Frame,
+                    })
+                    .collect(),
+                depth: 0,
+            },
+            AnimationProperty {
+                name: "Scale".to_string(),
+                property_path: "scale".to_string(),
+                children: Vec::new(),
+                keyframes: timestamps
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &t)| UIKeyframe {
+                        id: Uuid::new_v4().to_string(),
+                        time: Duration::from_millis(t as u64),
+                        value: match i {
+                            1 if self.generation_squash_stretch => KeyframeValue::ScaleXY(entering),
+                            4 if self.generation_squash_stretch => KeyframeValue::ScaleXY(leaving),
+                            _ => KeyframeValue::Scale(100),
+                        },
+                        easing: EasingType::EaseInOut,
+                        path_type: PathType::Linear,
+                        // should be same as position? or safe to be independent?
+                        key_type: KeyType::Frame,
+                    })
+                    .collect(),
+                depth: 0,
+            },
+            AnimationProperty {
+                name: "Opacity".to_string(),
+                property_path: "opacity".to_string(),
+                children: Vec::new(),
+                keyframes: timestamps
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &t)| {
+                        let mut opacity = 100;
+                        if self.generation_fade {
+                            if i == 0 || i == timestamps.len() - 1 {
+                                opacity = 0;
+                            }
+                        }
+
+                        UIKeyframe {
+                            id: Uuid::new_v4().to_string(),
+                            time: Duration::from_millis(t as u64),
+                            value: KeyframeValue::Opacity(opacity),
+                            easing: EasingType::EaseInOut,
+                            path_type: PathType::Linear,
+                            // should be same as position? or safe to be independent?
+                            key_type: KeyType::Frame,
+                        }
+                    })
+                    .collect(),
+                depth: 0,
+            },
+        ];
+
+        if object_type == ObjectType::VideoItem {
+            properties.push(AnimationProperty {
+                name: "Zoom / Popout".to_string(),
+                property_path: "zoom".to_string(),
+                children: Vec::new(),
+                keyframes: timestamps
+                    .iter()
+                    .map(|&t| UIKeyframe {
+                        id: Uuid::new_v4().to_string(),
+                        time: Duration::from_millis(t as u64),
+                        value: KeyframeValue::Zoom(100),
+                        easing: EasingType::EaseInOut,
+                        path_type: PathType::Linear,
+                        // should be same as position? or safe to be independent?
+                        key_type: KeyType::Frame,
+                    })
+                    .collect(),
+                depth: 0,
+            });
+        }
+
+        if self.generation_color {
+            let color_property = match object_type {
+                ObjectType::Polygon => self
+                    .scene
+                    .polygons
+                    .iter()
+                    .find(|p| p.id.to_string() == item_id)
+                    .map(|p| ("Fill", "fill", p.fill.map(|c| (c * 255.0).round() as i32))),
+                ObjectType::TextItem => self
+                    .scene
+                    .text_items
+                    .iter()
+                    .find(|t| t.id.to_string() == item_id)
+                    .map(|t| ("Color", "color", t.color)),
+                _ => None,
+            };
+
+            if let Some((name, property_path, color)) = color_property {
+                let luma =
+                    (0.299 * color[0] as f32 + 0.587 * color[1] as f32 + 0.114 * color[2] as f32)
+                        .round() as i32;
+                let gray = [luma, luma, luma, color[3]];
+
+                properties.push(AnimationProperty {
+                    name: name.to_string(),
+                    property_path: property_path.to_string(),
+                    children: Vec::new(),
+                    keyframes: timestamps
+                        .iter()
+                        .enumerate()
+                        .map(|(i, &t)| {
+                            let is_end = i == 0 || i == timestamps.len() - 1;
+
+                            UIKeyframe {
                                 id: Uuid::new_v4().to_string(),
                                 time: Duration::from_millis(t as u64),
-                                value: KeyframeValue::Zoom(100),
+                                value: KeyframeValue::Color(
+                                    if is_end { gray } else { color },
+                                    ColorInterpolation::Rgb,
+                                ),
                                 easing: EasingType::EaseInOut,
                                 path_type: PathType::Linear,
-                                // should be same as position? or safe to be independent?
                                 key_type: KeyType::Frame,
-                            })
-                            .collect(),
-                        depth: 0,
-                    });
-                }
-
-                animation_data_vec.push(AnimationData {
-                    id: Uuid::new_v4().to_string(),
-                    object_type: object_type.unwrap_or(ObjectType::Polygon),
-                    polygon_id: item_id.unwrap(),
-                    duration: Duration::from_millis(total_duration as u64),
-                    start_time_ms: 0,
-                    position: [0, 0],
-                    properties,
+                            }
+                        })
+                        .collect(),
+                    depth: 0,
                 });
             }
         }
 
-        animation_data_vec
+        Some(AnimationData {
+            id: Uuid::new_v4().to_string(),
+            object_type,
+            polygon_id: item_id,
+            duration: Duration::from_millis(total_duration as u64),
+            start_time_ms: 0,
+            position: [0, 0],
+            motion_blur: 0,
+            properties,
+        })
     }
 
     // Helper function to get item ID based on object index
     fn get_item_id(&self, object_idx: usize) -> Option<String> {
-        // let polygon_count = self.polygons.len();
-        // let text_count = self.text_items.len();
-        let visible_polygons: Vec<&Polygon> = self.polygons.iter().filter(|p| !p.hidden).collect();
+        // let polygon_count = self.scene.polygons.len();
+        // let text_count = self.scene.text_items.len();
+        let visible_polygons: Vec<&Polygon> =
+            self.scene.polygons.iter().filter(|p| !p.hidden).collect();
         let visible_texts: Vec<&TextRenderer> =
-            self.text_items.iter().filter(|t| !t.hidden).collect();
-        let visible_images: Vec<&StImage> = self.image_items.iter().filter(|i| !i.hidden).collect();
-        let visible_videos: Vec<&StVideo> = self.video_items.iter().filter(|v| !v.hidden).collect();
+            self.scene.text_items.iter().filter(|t| !t.hidden).collect();
+        let visible_images: Vec<&StImage> = self
+            .scene
+            .image_items
+            .iter()
+            .filter(|i| !i.hidden)
+            .collect();
+        let visible_videos: Vec<&StVideo> = self
+            .scene
+            .video_items
+            .iter()
+            .filter(|v| !v.hidden)
+            .collect();
 
-        let polygon_count = self.polygons.iter().filter(|p| !p.hidden).count();
-        let text_count = self.text_items.iter().filter(|t| !t.hidden).count();
-        let image_count = self.image_items.iter().filter(|i| !i.hidden).count();
+        let polygon_count = self.scene.polygons.iter().filter(|p| !p.hidden).count();
+        let text_count = self.scene.text_items.iter().filter(|t| !t.hidden).count();
+        let image_count = self.scene.image_items.iter().filter(|i| !i.hidden).count();
 
         match object_idx {
             idx if idx < polygon_count => Some(visible_polygons[idx].id.clone().to_string()),
@@ -2215,13 +3120,13 @@ impl Editor {
 
     // Helper function to get object type based on object index
     fn get_object_type(&self, object_idx: usize) -> Option<ObjectType> {
-        // let polygon_count = self.polygons.len();
-        // let text_count = self.text_items.len();
+        // let polygon_count = self.scene.polygons.len();
+        // let text_count = self.scene.text_items.len();
 
-        let polygon_count = self.polygons.iter().filter(|p| !p.hidden).count();
-        let text_count = self.text_items.iter().filter(|t| !t.hidden).count();
-        let image_count = self.image_items.iter().filter(|i| !i.hidden).count();
-        let video_count = self.video_items.iter().filter(|i| !i.hidden).count();
+        let polygon_count = self.scene.polygons.iter().filter(|p| !p.hidden).count();
+        let text_count = self.scene.text_items.iter().filter(|t| !t.hidden).count();
+        let image_count = self.scene.image_items.iter().filter(|i| !i.hidden).count();
+        let video_count = self.scene.video_items.iter().filter(|i| !i.hidden).count();
 
         match object_idx {
             idx if idx < polygon_count => Some(ObjectType::Polygon),
@@ -2234,726 +3139,239 @@ impl Editor {
         }
     }
 
-    pub fn step_video_animations(&mut self, camera: &Camera, provided_current_time_s: Option<f64>) {
-        if !self.video_is_playing || self.video_current_sequence_timeline.is_none() {
-            return;
-        }
-
-        let now = std::time::Instant::now();
-        // let dt = if let Some(last_time) = self.last_frame_time {
-        //     (now - last_time).as_secs_f32()
-        // } else {
-        //     0.0
-        // };
-        // let dt = if let Some(provided_dt) = provided_dt {
-        //     provided_dt
-        // } else {
-        //     dt
-        // };
-        let total_dt = if let Some(video_start_playing_time) = self.video_start_playing_time {
-            (now - video_start_playing_time).as_secs_f32()
-        } else {
-            0.0
-        };
-        // self.last_frame_time = Some(now);
-
-        let sequence_timeline = self
-            .video_current_sequence_timeline
+    /// Moves the playhead of the current sequence to a time, setting every object as it is
+    /// then and decoding the exact video frames, for timeline scrubbing. Playback carries on
+    /// from the new playhead. The next render shows the result.
+    pub fn seek_to(&mut self, ms: i32) -> Result<(), String> {
+        let camera = *self.scene.camera.as_ref().ok_or("Couldn't get camera")?;
+        let sequence = self
+            .scene
+            .current_sequence_data
             .as_ref()
-            .expect("Couldn't get current sequence timeline");
-
-        // Convert total_dt from seconds to milliseconds for comparison with timeline
-        let current_time_ms = if let Some(provided_current_time_s) = provided_current_time_s {
-            (provided_current_time_s * 1000.0) as i32
-        } else {
-            (total_dt * 1000.0) as i32
-        };
+            .ok_or("No sequence selected")?;
 
-        // Get the sequences data
-        let video_current_sequences_data = match self.video_current_sequences_data.as_ref() {
-            Some(data) => data,
-            None => return,
-        };
+        // sequence time wraps at its duration, so stop just short of it
+        let ms = ms.clamp(0, (sequence.duration_ms - 1).max(0));
+        let time_s = ms as f32 / 1000.0;
 
-        // let mut elapsed = 0;
-        // let mut current_found = false;
+        self.scene.seek_sequence_videos(time_s)?;
 
-        let mut update_background = false;
+        self.scene.step_animate_sequence(time_s, &camera)?;
+        self.scene.step_particle_emitters(time_s);
+        self.scene.step_animated_images(time_s);
+        self.scene.step_camera_animation(time_s);
 
-        if total_dt <= 1.0 / 60.0 {
-            println!("Update initial background...");
-            update_background = true;
-        }
+        self.move_playhead(time_s);
+        self.update_onion_skin();
 
-        // Iterate through timeline sequences in order
-        for ts in &sequence_timeline.timeline_sequences {
-            // Skip audio tracks as we're only handling video
-            if ts.track_type != TrackType::Video {
-                continue;
-            }
+        Ok(())
+    }
 
-            // slow?
-            let duration_ms = video_current_sequences_data
-                .iter()
-                .find(|s| s.id == ts.sequence_id)
-                .map(|s| s.duration_ms)
-                .unwrap_or(0);
-
-            // dynamic start times
-            // if let Some(current_sequence) = &self.current_sequence_data {
-            //     if !current_found {
-            //         elapsed = elapsed + ts.duration_ms;
-            //     }
-
-            //     if current_sequence.id == ts.sequence_id {
-            //         current_found = true;
-            //     }
-            // } else {
-            //     current_found = true;
-            // }
+    /// Shifts the start instants so playback carries on from the given time at the current rate
+    fn move_playhead(&mut self, time_s: f32) {
+        let playhead_start =
+            Instant::now() - Duration::from_secs_f32(time_s / self.scene.playback_rate);
+        if self.scene.start_playing_time.is_some() {
+            self.scene.start_playing_time = Some(playhead_start);
+        }
+        if self.scene.video_start_playing_time.is_some() {
+            self.scene.video_start_playing_time = Some(playhead_start);
+        }
+        self.scene.last_playhead_s = time_s;
+    }
 
-            // if current_found {}
-            // Check if this sequence should be playing at the current time
-            if current_time_ms >= ts.start_time_ms
-                && current_time_ms < (ts.start_time_ms + duration_ms)
-            {
-                // Find the corresponding sequence data
-                if let Some(sequence) = video_current_sequences_data
-                    .iter()
-                    .find(|s| s.id == ts.sequence_id)
-                {
-                    // Calculate local time within this sequence
-                    let sequence_local_time = (current_time_ms - ts.start_time_ms) as f32 / 1000.0;
-                    if let Some(current_sequence) = &self.current_sequence_data {
-                        // need to somehow efficiently restore polygons for the sequence
-                        // Check id to avoid unnecessary cloning
-                        // plan is to preload with a hidden attribute or similar
-                        if sequence.id != current_sequence.id {
-                            self.current_sequence_data = Some(sequence.clone());
-                            // set hidden attribute on relevant objects
-                            let current_sequence_id = sequence.id.clone();
-
-                            for polygon in self.polygons.iter_mut() {
-                                if polygon.current_sequence_id.to_string() == current_sequence_id {
-                                    polygon.hidden = false;
-                                } else {
-                                    polygon.hidden = true;
-                                }
-                            }
-                            for text in self.text_items.iter_mut() {
-                                if text.current_sequence_id.to_string() == current_sequence_id {
-                                    text.hidden = false;
-                                } else {
-                                    text.hidden = true;
-                                }
-                            }
-                            for image in self.image_items.iter_mut() {
-                                if image.current_sequence_id.to_string() == current_sequence_id {
-                                    image.hidden = false;
-                                } else {
-                                    image.hidden = true;
-                                }
-                            }
-                            for video in self.video_items.iter_mut() {
-                                if video.current_sequence_id.to_string() == current_sequence_id {
-                                    video.hidden = false;
-                                } else {
-                                    video.hidden = true;
-                                }
-                            }
+    /// Plays faster or slower, from 0.25x to 4x, carrying on from the current playhead
+    pub fn set_playback_rate(&mut self, rate: f32) {
+        let time_s = self.scene.playhead_s(
+            self.scene
+                .start_playing_time
+                .or(self.scene.video_start_playing_time),
+        );
+        self.scene.playback_rate = rate.clamp(MIN_PLAYBACK_RATE, MAX_PLAYBACK_RATE);
+        self.move_playhead(time_s);
+    }
 
-                            update_background = true;
-                        }
-                    } else {
-                        self.current_sequence_data = Some(sequence.clone());
-                    }
-                }
+    /// Repeats part of the sequence during playback, or plays through with None
+    pub fn set_loop_region(&mut self, loop_region: Option<LoopRegion>) -> Result<(), String> {
+        if let Some(region) = loop_region {
+            if region.start_ms < 0 || region.end_ms <= region.start_ms {
+                return Err("Loop region must end after it starts".to_string());
             }
         }
 
-        {
-            if update_background {
-                if let Some(current_sequence) = &self.current_sequence_data {
-                    match current_sequence
-                        .background_fill
-                        .as_ref()
-                        .expect("Couldn't get default background fill")
-                    {
-                        BackgroundFill::Color(fill) => {
-                            self.replace_background(
-                                Uuid::from_str(&current_sequence.id)
-                                    .expect("Couldn't convert string to uuid"),
-                                rgb_to_wgpu(
-                                    fill[0] as u8,
-                                    fill[1] as u8,
-                                    fill[2] as u8,
-                                    fill[3] as f32,
-                                ),
-                            );
-                        }
-                        _ => {
-                            println!("Not supported yet...");
-                        }
-                    }
-                }
+        let time_s = self.scene.playhead_s(
+            self.scene
+                .start_playing_time
+                .or(self.scene.video_start_playing_time),
+        );
+        self.scene.loop_region = loop_region;
+
+        // jump into the new region right away rather than when playback reaches its end
+        if let Some(region) = loop_region {
+            let wrapped_s = region.wrap(time_s);
+            if wrapped_s != time_s {
+                self.scene.seek_sequence_videos(wrapped_s)?;
+                self.move_playhead(wrapped_s);
             }
         }
+
+        Ok(())
     }
 
-    pub fn step_motion_path_animations(
+    /// Sets the keyframed camera moves of a sequence, or removes them with None
+    pub fn set_camera_animation(
         &mut self,
-        camera: &Camera,
-        provided_current_time_s: Option<f64>,
-    ) {
-        if !self.is_playing || self.current_sequence_data.is_none() {
-            return;
+        sequence_id: &str,
+        camera_animation: Option<CameraAnimation>,
+    ) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        let sequence = saved_state
+            .sequences
+            .iter_mut()
+            .find(|s| s.id == sequence_id)
+            .ok_or_else(|| format!("No sequence found with id {}", sequence_id))?;
+        sequence.camera_animation = camera_animation.clone();
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            if current_sequence.id == sequence_id {
+                current_sequence.camera_animation = camera_animation;
+            }
         }
 
-        // TODO: disable time based dt determination for export only
-        let now = std::time::Instant::now();
-        // let dt = if let Some(last_time) = self.last_frame_time {
-        //     (now - last_time).as_secs_f32()
-        // } else {
-        //     0.0
-        // };
-        let total_dt = if let Some(start_playing_time) = self.start_playing_time {
-            (now - start_playing_time).as_secs_f32()
-        } else {
-            0.0
-        };
-        let total_dt = if let Some(provided_current_time_s) = provided_current_time_s {
-            provided_current_time_s
-        } else {
-            total_dt as f64
-        };
-        self.last_frame_time = Some(now);
+        save_saved_state_raw(saved_state.clone())?;
 
-        self.step_animate_sequence(total_dt as f32, camera);
+        Ok(())
     }
 
-    /// Steps the currently selected sequence unless one is provided
-    /// TODO: make more efficient
-    pub fn step_animate_sequence(&mut self, total_dt: f32, camera: &Camera) {
-        let gpu_resources = self
-            .gpu_resources
-            .as_ref()
-            .expect("Couldn't get GPU Resources");
-        let sequence = self
-            .current_sequence_data
-            .as_ref()
-            .expect("Couldn't get sequence");
-
-        // Update each animation path
-        for animation in &sequence.polygon_motion_paths {
-            // Group transform position
-            let path_group_position = animation.position;
-
-            // Get current time within animation duration
-            let current_time =
-                Duration::from_secs_f32(total_dt % (sequence.duration_ms / 1000) as f32);
-            let start_time = Duration::from_millis(animation.start_time_ms as u64);
-
-            // Check if the current time is within the animation's active period
-            if current_time < start_time || current_time > start_time + animation.duration {
-                continue;
-            }
-
-            // Find the polygon to update
-            let object_idx = match animation.object_type {
-                ObjectType::Polygon => self
-                    .polygons
-                    .iter()
-                    .position(|p| p.id.to_string() == animation.polygon_id),
-                ObjectType::TextItem => self
-                    .text_items
-                    .iter()
-                    .position(|t| t.id.to_string() == animation.polygon_id),
-                ObjectType::ImageItem => self
-                    .image_items
-                    .iter()
-                    .position(|i| i.id.to_string() == animation.polygon_id),
-                ObjectType::VideoItem => self
-                    .video_items
-                    .iter()
-                    .position(|i| i.id.to_string() == animation.polygon_id),
-            };
-
-            let Some(object_idx) = object_idx else {
-                continue;
+    /// Adds Tilt X and Tilt Y properties to an animation, starting flat, so the object
+    /// can be keyframed tilting toward or away from the viewer
+    pub fn add_tilt_properties(&mut self, animation_id: &str) -> Result<(), String> {
+        let add = |sequence: &mut Sequence| -> bool {
+            let Some(animation) = sequence
+                .polygon_motion_paths
+                .iter_mut()
+                .find(|a| a.id == animation_id)
+            else {
+                return false;
             };
 
-            // Determine whether to draw the video frame based on the frame rate and current time
-            // step rate is throttled to 60FPS
-            // if video frame rate is 60FPS, then call draw on each frame
-            // if video frame rate is 30FPS, then call draw on every other frame
-            let mut animate_properties = false;
-
-            if animation.object_type == ObjectType::VideoItem {
-                let frame_rate = self.video_items[object_idx].source_frame_rate;
-                let source_duration_ms = self.video_items[object_idx].source_duration_ms;
-                let frame_interval = Duration::from_secs_f64(1.0 / frame_rate as f64);
-
-                // Calculate the number of frames that should have been displayed by now
-                let elapsed_time: Duration = current_time - start_time;
-                let current_frame_time = self.video_items[object_idx].num_frames_drawn as f64
-                    * frame_interval.as_secs_f64();
-                // let last_frame_time = self.last_frame_time.expect("Couldn't get last frame time");
-
-                // println!(
-                //     "current times {:?} frame: {:?}",
-                //     current_time.as_secs_f64(),
-                //     current_frame_time
-                // );
-
-                // Only draw the frame if the current time is within the frame's display interval
-                if current_time.as_secs_f64() >= current_frame_time
-                    && current_time.as_secs_f64()
-                        < current_frame_time + frame_interval.as_secs_f64()
-                {
-                    if current_time.as_millis() + 1000 < source_duration_ms as u128 {
-                        self.video_items[object_idx]
-                            .draw_video_frame(&gpu_resources.device, &gpu_resources.queue)
-                            .expect("Couldn't draw video frame");
-
-                        animate_properties = true;
-                        self.video_items[object_idx].num_frames_drawn += 1;
-                    }
-                } else {
-                    // TODO: deteermine distance between current_time and current_frame_time to determine
-                    // how many video frames to draw to catch up
-                    let difference = current_time.as_secs_f64() - current_frame_time;
-                    let catch_up_frames =
-                        (difference / frame_interval.as_secs_f64()).floor() as u32;
-
-                    // Only catch up if we're behind and within the video duration
-                    if catch_up_frames > 0
-                        && current_time.as_millis() + 1000 < source_duration_ms as u128
-                    {
-                        // Limit the maximum number of frames to catch up to avoid excessive CPU usage
-                        let max_catch_up = 5;
-                        let frames_to_draw = catch_up_frames.min(max_catch_up);
-
-                        // println!("frames_to_draw {:?}", frames_to_draw);
-
-                        for _ in 0..frames_to_draw {
-                            self.video_items[object_idx]
-                                .draw_video_frame(&gpu_resources.device, &gpu_resources.queue)
-                                .expect("Couldn't draw catch-up video frame");
-
-                            self.video_items[object_idx].num_frames_drawn += 1;
-                        }
+            let timestamps = [0, animation.duration.as_millis() as i32];
+            for (name, path, value) in [
+                ("Tilt X", "rotation_x", KeyframeValue::PerspectiveX(0)),
+                ("Tilt Y", "rotation_y", KeyframeValue::PerspectiveY(0)),
+            ] {
+                if !animation.properties.iter().any(|p| p.property_path == path) {
+                    animation.properties.push(create_default_property(
+                        name,
+                        path,
+                        value,
+                        &timestamps,
+                    ));
+                }
+            }
 
-                        animate_properties = true;
+            true
+        };
 
-                        // println!(
-                        //     "Caught up {} frames out of {} needed",
-                        //     frames_to_draw, catch_up_frames
-                        // );
-                    }
-                }
-            } else {
-                animate_properties = true;
-            }
-
-            // let mut animate_properties = false;
-
-            // Modified video drawing code
-            // if animation.object_type == ObjectType::VideoItem {
-            //     let frame_rate = self.video_items[object_idx].source_frame_rate;
-            //     let source_duration_ms = self.video_items[object_idx].source_duration_ms;
-
-            //     // Initialize frame timer if not exists
-            //     if self.video_items[object_idx].frame_timer.is_none() {
-            //         self.video_items[object_idx].frame_timer = Some(FrameTimer::new());
-            //     }
-
-            //     // Get number of frames to draw this step
-            //     let frames_to_draw = self.video_items[object_idx]
-            //         .frame_timer
-            //         .as_mut()
-            //         .expect("Couldn't get frame timer")
-            //         .update_and_get_frames_to_draw(current_time, frame_rate as f32);
-
-            //     // Draw the required number of frames
-            //     if frames_to_draw > 0
-            //         && current_time.as_millis() + 1000 < source_duration_ms as u128
-            //     {
-            //         println!("frames_to_draw {:?}", frames_to_draw);
-            //         // Draw each frame
-            //         for _ in 0..frames_to_draw {
-            //             self.video_items[object_idx]
-            //                 .draw_video_frame(&gpu_resources.device, &gpu_resources.queue)
-            //                 .expect("Couldn't draw video frame");
-            //         }
-
-            //         animate_properties = true;
-            //     }
-            // }
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            add(current_sequence);
+        }
 
-            if !animate_properties {
-                return;
-            }
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        let mut found = false;
+        for sequence in saved_state.sequences.iter_mut() {
+            found |= add(sequence);
+        }
 
-            // Go through each property
-            for property in &animation.properties {
-                if property.keyframes.len() < 2 {
-                    continue;
-                }
+        if !found {
+            return Err(format!("No animation found with id {}", animation_id));
+        }
 
-                if start_time > current_time {
-                    continue;
-                }
+        save_saved_state_raw(saved_state.clone())?;
 
-                // Find the surrounding keyframes
-                let (start_frame, end_frame) = self.get_surrounding_keyframes(
-                    &mut property.keyframes.clone(), // do not love clone in loop
-                    current_time - start_time,
-                );
-                let Some((start_frame, end_frame)) = start_frame.zip(end_frame) else {
-                    continue;
-                };
+        Ok(())
+    }
 
-                // Calculate interpolation progress
-                let duration = (end_frame.time - start_frame.time).as_secs_f32(); // duration between keyframes
-                let elapsed = (current_time - start_time - start_frame.time).as_secs_f32(); // elapsed since start keyframe
-                let mut progress = elapsed / duration;
+    /// Saves the current camera pose under a name, replacing any bookmark with that name
+    pub fn save_camera_bookmark(&mut self, name: String) -> Result<(), String> {
+        let camera = self.scene.camera.as_ref().ok_or("Couldn't get camera")?;
+        let bookmark = CameraBookmark::from_camera(name, camera);
+
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        match saved_state
+            .camera_bookmarks
+            .iter_mut()
+            .find(|b| b.name == bookmark.name)
+        {
+            Some(existing) => *existing = bookmark,
+            None => saved_state.camera_bookmarks.push(bookmark),
+        }
 
-                // Apply easing (EaseInOut)
-                progress = if progress < 0.5 {
-                    2.0 * progress * progress
-                } else {
-                    1.0 - (-2.0 * progress + 2.0).powi(2) / 2.0
-                };
+        save_saved_state_raw(saved_state.clone())?;
 
-                // do not update a property when start and end are the same
-                // TODO: make this a setting for zooms so the center_point can continue its interpolation?
-                // if start_frame.value == end_frame.value {
-                //     continue;
-                // }
+        Ok(())
+    }
 
-                // Apply the interpolated value to the object's property
-                match (&start_frame.value, &end_frame.value) {
-                    (KeyframeValue::Position(start), KeyframeValue::Position(end)) => {
-                        let x = self.lerp(start[0], end[0], progress);
-                        let y = self.lerp(start[1], end[1], progress);
-
-                        let position = Point {
-                            x: CANVAS_HORIZ_OFFSET + x + path_group_position[0] as f32,
-                            y: CANVAS_VERT_OFFSET + y + path_group_position[1] as f32,
-                        };
-
-                        match animation.object_type {
-                            ObjectType::Polygon => {
-                                self.polygons[object_idx]
-                                    .transform
-                                    .update_position([position.x, position.y], &camera.window_size);
-                            }
-                            ObjectType::TextItem => {
-                                self.text_items[object_idx]
-                                    .transform
-                                    .update_position([position.x, position.y], &camera.window_size);
-                                self.text_items[object_idx]
-                                    .background_polygon
-                                    .transform
-                                    .update_position([position.x, position.y], &camera.window_size);
-                            }
-                            ObjectType::ImageItem => {
-                                self.image_items[object_idx]
-                                    .transform
-                                    .update_position([position.x, position.y], &camera.window_size);
-                            }
-                            ObjectType::VideoItem => {
-                                self.video_items[object_idx]
-                                    .transform
-                                    .update_position([position.x, position.y], &camera.window_size);
-                            }
-                        }
-                    }
-                    (KeyframeValue::Rotation(start), KeyframeValue::Rotation(end)) => {
-                        // rotation is stored as degrees
-                        let new_rotation = self.lerp(*start, *end, progress);
+    pub fn remove_camera_bookmark(&mut self, name: &str) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        let count = saved_state.camera_bookmarks.len();
+        saved_state.camera_bookmarks.retain(|b| b.name != name);
 
-                        let new_rotation_rad = new_rotation.to_radians();
+        if saved_state.camera_bookmarks.len() == count {
+            return Err(format!("No camera bookmark named {}", name));
+        }
 
-                        match animation.object_type {
-                            ObjectType::Polygon => {
-                                self.polygons[object_idx]
-                                    .transform
-                                    .update_rotation(new_rotation_rad);
-                            }
-                            ObjectType::TextItem => {
-                                self.text_items[object_idx]
-                                    .transform
-                                    .update_rotation(new_rotation_rad);
-                                self.text_items[object_idx]
-                                    .background_polygon
-                                    .transform
-                                    .update_rotation(new_rotation_rad);
-                            }
-                            ObjectType::ImageItem => {
-                                self.image_items[object_idx]
-                                    .transform
-                                    .update_rotation(new_rotation_rad);
-                            }
-                            ObjectType::VideoItem => {
-                                self.video_items[object_idx]
-                                    .transform
-                                    .update_rotation(new_rotation_rad);
-                            }
-                        }
-                    }
-                    (KeyframeValue::Scale(start), KeyframeValue::Scale(end)) => {
-                        // scale is stored out 100 (100 being standard size, ie. 100%)
-                        let new_scale = self.lerp(*start, *end, progress) as f32 / 100.0;
+        save_saved_state_raw(saved_state.clone())?;
 
-                        // TODO: verify scale on all objects as some treat it differently as-is
+        Ok(())
+    }
 
-                        match animation.object_type {
-                            ObjectType::Polygon => {
-                                self.polygons[object_idx]
-                                    .transform
-                                    .update_scale([new_scale, new_scale]);
-                            }
-                            ObjectType::TextItem => {
-                                self.text_items[object_idx]
-                                    .transform
-                                    .update_scale([new_scale, new_scale]);
-                                self.text_items[object_idx]
-                                    .background_polygon
-                                    .transform
-                                    .update_scale([new_scale, new_scale]);
-                            }
-                            ObjectType::ImageItem => {
-                                let original_scale = self.image_items[object_idx].dimensions;
-                                self.image_items[object_idx].transform.update_scale([
-                                    original_scale.0 as f32 * new_scale,
-                                    original_scale.1 as f32 * new_scale,
-                                ]);
-                            }
-                            ObjectType::VideoItem => {
-                                let original_scale = self.video_items[object_idx].dimensions;
-                                self.video_items[object_idx].transform.update_scale([
-                                    original_scale.0 as f32 * new_scale,
-                                    original_scale.1 as f32 * new_scale,
-                                ]);
-                            }
-                        }
-                    }
-                    (KeyframeValue::Opacity(start), KeyframeValue::Opacity(end)) => {
-                        // opacity is out 100 (100%)
-                        let opacity = self.lerp(*start, *end, progress) / 100.0;
+    /// Moves the camera to a bookmarked pose, easing there over the duration.
+    /// A zero duration jumps straight there.
+    pub fn recall_camera_bookmark(&mut self, name: &str, duration: Duration) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_ref()
+            .ok_or("Couldn't get saved state")?;
+        let bookmark = saved_state
+            .camera_bookmarks
+            .iter()
+            .find(|b| b.name == name)
+            .ok_or_else(|| format!("No camera bookmark named {}", name))?;
 
-                        let gpu_resources = self
-                            .gpu_resources
-                            .as_ref()
-                            .expect("Couldn't get gpu resources");
+        let camera = self.scene.camera.as_ref().ok_or("Couldn't get camera")?;
+        self.camera_transition = Some(CameraTransition::new(camera, bookmark, duration));
+        self.step_camera_transition();
 
-                        match animation.object_type {
-                            ObjectType::Polygon => {
-                                self.polygons[object_idx]
-                                    .update_opacity(&gpu_resources.queue, opacity);
-                            }
-                            ObjectType::TextItem => {
-                                self.text_items[object_idx]
-                                    .update_opacity(&gpu_resources.queue, opacity);
-                                self.text_items[object_idx]
-                                    .background_polygon
-                                    .update_opacity(&gpu_resources.queue, opacity);
-                            }
-                            ObjectType::ImageItem => {
-                                self.image_items[object_idx]
-                                    .update_opacity(&gpu_resources.queue, opacity);
-                            }
-                            ObjectType::VideoItem => {
-                                self.video_items[object_idx]
-                                    .update_opacity(&gpu_resources.queue, opacity);
-                            }
-                        }
-                    }
-                    (KeyframeValue::Zoom(start), KeyframeValue::Zoom(end)) => {
-                        let zoom = self.lerp(*start, *end, progress) / 100.0;
+        Ok(())
+    }
 
-                        let gpu_resources = self
-                            .gpu_resources
-                            .as_ref()
-                            .expect("Couldn't get gpu resources");
-
-                        match animation.object_type {
-                            ObjectType::VideoItem => {
-                                let video_item = &mut self.video_items[object_idx];
-                                let elapsed_ms = current_time.as_millis() as u128;
-
-                                let autofollow_delay = 150;
-
-                                if let (Some(mouse_positions), Some(source_data)) = (
-                                    video_item.mouse_positions.as_ref(),
-                                    video_item.source_data.as_ref(),
-                                ) {
-                                    // Check if we need to update the shift points
-                                    let should_update_shift = match video_item.last_shift_time {
-                                        Some(last_shift_time) => {
-                                            elapsed_ms - last_shift_time > autofollow_delay
-                                        }
-                                        None => {
-                                            video_item.last_shift_time = Some(elapsed_ms);
-
-                                            if let Some((start_point, end_point)) = mouse_positions
-                                                .iter()
-                                                .filter(|p| p.timestamp >= elapsed_ms)
-                                                .zip(mouse_positions.iter().filter(|p| {
-                                                    p.timestamp >= elapsed_ms + autofollow_delay
-                                                }))
-                                                .next()
-                                                .map(|(start, end)| {
-                                                    ((*start).clone(), (*end).clone())
-                                                })
-                                            {
-                                                video_item.last_start_point = Some(start_point);
-                                                video_item.last_end_point = Some(end_point);
-                                            }
-
-                                            false
-                                        }
-                                    };
-
-                                    let delay_offset = 500; // Potential time offset for a consistent lag
-                                    let min_distance = 100.0; // Distance to incur a shift
-                                    let base_alpha = 0.01; // Your current default value
-                                    let max_alpha = 0.1; // Maximum blending speed
-                                    let scaling_factor = 0.01; // Controls how quickly alpha increases with distance
-
-                                    // Update shift points if needed
-                                    if should_update_shift {
-                                        if let Some((start_point, end_point)) = mouse_positions
-                                            .iter()
-                                            .filter(|p| {
-                                                p.timestamp
-                                                    >= (elapsed_ms - autofollow_delay)
-                                                        + delay_offset
-                                                    && p.timestamp
-                                                        < video_item.source_duration_ms as u128
-                                            })
-                                            .zip(mouse_positions.iter().filter(|p| {
-                                                p.timestamp >= elapsed_ms + delay_offset
-                                                    && p.timestamp
-                                                        < video_item.source_duration_ms as u128
-                                            }))
-                                            .next()
-                                            .map(|(start, end)| ((*start).clone(), (*end).clone()))
-                                        {
-                                            if let Some(last_start_point) =
-                                                video_item.last_start_point
-                                            {
-                                                if let Some(last_end_point) =
-                                                    video_item.last_end_point
-                                                {
-                                                    let dx = start_point.x - last_start_point.x;
-                                                    let dy = start_point.y - last_start_point.y;
-                                                    let distance = (dx * dx + dy * dy).sqrt(); // Euclidean distance
-
-                                                    let dx2 = end_point.x - last_end_point.x;
-                                                    let dy2 = end_point.y - last_end_point.y;
-                                                    let distance2 = (dx2 * dx2 + dy2 * dy2).sqrt(); // Euclidean distance
-
-                                                    if distance >= min_distance
-                                                        || distance2 >= min_distance
-                                                    {
-                                                        video_item.last_shift_time =
-                                                            Some(elapsed_ms);
-
-                                                        video_item.last_start_point =
-                                                            Some(start_point);
-                                                        video_item.last_end_point = Some(end_point);
-
-                                                        // Use the larger of the two distances
-                                                        let max_distance = distance.max(distance2);
-
-                                                        // Exponential smoothing that plateaus
-                                                        let dynamic_alpha = base_alpha
-                                                            + (max_alpha - base_alpha)
-                                                                * (1.0
-                                                                    - (-scaling_factor
-                                                                        * max_distance)
-                                                                        .exp());
-
-                                                        video_item.dynamic_alpha = dynamic_alpha;
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
+    /// Moves the camera along a bookmark transition, call each frame while one is running
+    pub fn step_camera_transition(&mut self) {
+        let Some(transition) = self.camera_transition else {
+            return;
+        };
+        let Some(camera) = self.scene.camera.as_mut() else {
+            return;
+        };
 
-                                    // Always interpolate between the current shift points
-                                    if let (Some(start), Some(end)) =
-                                        (&video_item.last_start_point, &video_item.last_end_point)
-                                    {
-                                        let clamped_elapsed_ms =
-                                            elapsed_ms.clamp(start.timestamp, end.timestamp);
-
-                                        let time_progress = (clamped_elapsed_ms - start.timestamp)
-                                            as f32
-                                            / (end.timestamp - start.timestamp) as f32;
-
-                                        let interpolated_x =
-                                            start.x + (end.x - start.x) * time_progress;
-                                        let interpolated_y =
-                                            start.y + (end.y - start.y) * time_progress;
-
-                                        let dimensions = video_item.dimensions;
-                                        let source_dimensions = video_item.source_dimensions;
-
-                                        let new_center_point = Point {
-                                            x: ((interpolated_x - source_data.x as f32)
-                                                / source_dimensions.0 as f32)
-                                                * dimensions.0 as f32,
-                                            y: ((interpolated_y - source_data.y as f32)
-                                                / source_dimensions.1 as f32)
-                                                * dimensions.1 as f32,
-                                        };
-
-                                        // Smooth transition with existing center point
-                                        let blended_center_point = if let Some(last_center_point) =
-                                            video_item.last_center_point
-                                        {
-                                            // need to calculate a dynamic alpha based on distance between start and and end point
-                                            // let alpha = 0.01; // this was a close value, but not quite right depending on distance
-                                            let alpha = video_item.dynamic_alpha;
-
-                                            Point {
-                                                x: last_center_point.x * (1.0 - alpha)
-                                                    + new_center_point.x * alpha,
-                                                y: last_center_point.y * (1.0 - alpha)
-                                                    + new_center_point.y * alpha,
-                                            }
-                                        } else {
-                                            new_center_point
-                                        };
-
-                                        video_item.update_zoom(
-                                            &gpu_resources.queue,
-                                            zoom,
-                                            blended_center_point,
-                                        );
-                                        video_item.last_center_point = Some(blended_center_point);
-
-                                        // video_item.update_popout(
-                                        //     &gpu_resources.queue,
-                                        //     blended_center_point,
-                                        //     1.5,
-                                        //     (200.0, 200.0),
-                                        // );
-                                    }
-                                }
-                            }
-                            _ => {
-                                // println!("Zoom not supported here");
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
+        if transition.apply(camera) {
+            self.camera_transition = None;
         }
+
+        self.scene.update_camera_binding();
     }
 
     // pub fn get_surrounding_keyframes<'a>(
@@ -2985,81 +3403,19 @@ impl Editor {
     //     (prev_frame, next_frame)
     // }
 
-    /// Returns a "virtual" keyframe for the end keyframe in case of a Range type
+    /// Returns a "virtual" keyframe for the end keyframe in case of a Range type,
+    /// see animations::surrounding_keyframes
     pub fn get_surrounding_keyframes(
         &self,
         keyframes: &mut [UIKeyframe],
         current_time: Duration,
     ) -> (Option<UIKeyframe>, Option<UIKeyframe>) {
-        let mut prev_frame = None;
-        let mut next_frame = None;
-
-        // TODO: need to pick prev_frame based on timing not index
-        // so just sort the keyframes here
         keyframes.sort_by_key(|k| k.time);
 
-        for (i, frame) in keyframes.iter().enumerate() {
-            if frame.time > current_time {
-                // Check if the previous frame is a range
-                if i > 0 {
-                    if let KeyType::Range(range_data) = &keyframes[i - 1].key_type {
-                        // Case 1: Current time is within the range
-                        if current_time >= keyframes[i - 1].time
-                            && current_time < range_data.end_time
-                        {
-                            // Current time is within a range
-                            prev_frame = Some(keyframes[i - 1].clone());
-                            next_frame = Some(UIKeyframe {
-                                id: "virtual".to_string(),
-                                time: range_data.end_time,
-                                value: keyframes[i - 1].value.clone(),
-                                easing: EasingType::Linear, // Doesn't matter for static ranges
-                                path_type: PathType::Linear, // Doesn't matter for static ranges
-                                key_type: KeyType::Frame, // Virtual keyframe is treated as a frame
-                            });
-                            return (prev_frame, next_frame);
-                        }
-
-                        // Case 2: Current time is after the range but before the next keyframe
-                        if current_time >= range_data.end_time && current_time < frame.time {
-                            prev_frame = Some(UIKeyframe {
-                                id: "virtual".to_string(),
-                                time: range_data.end_time, // End of the range
-                                value: keyframes[i - 1].value.clone(), // Same value as start
-                                easing: EasingType::Linear, // Doesn't matter for static ranges
-                                path_type: PathType::Linear, // Doesn't matter for static ranges
-                                key_type: KeyType::Frame,  // Virtual keyframe is treated as a frame
-                            });
-                            next_frame = Some(frame.clone()); // Next actual keyframe
-                            return (prev_frame, next_frame);
-                        }
-                    }
-                }
-
-                // Regular keyframe logic
-
-                next_frame = Some(frame.clone());
-                prev_frame = if i > 0 {
-                    Some(keyframes[i - 1].clone())
-                } else {
-                    Some(keyframes[keyframes.len() - 1].clone())
-                };
-                break;
-            }
+        match surrounding_keyframes(keyframes, current_time) {
+            Some((prev_frame, next_frame)) => (Some(prev_frame), Some(next_frame)),
+            None => (None, None),
         }
-
-        // Handle wrap-around case
-        // can result in a duration subtraction error
-        // if next_frame.is_none() {
-        //     prev_frame = keyframes.last().cloned();
-        //     next_frame = keyframes.first().cloned();
-        // }
-
-        (prev_frame, next_frame)
-    }
-
-    pub fn lerp(&self, start: i32, end: i32, progress: f32) -> f32 {
-        start as f32 + ((end - start) as f32 * progress)
     }
 
     /// Create motion path visualization for a polygon
@@ -3069,61 +3425,38 @@ impl Editor {
         sequence: &Sequence,
         polygon_id: &str,
         color_index: u32,
-    ) {
-        let animation_data = sequence
-            .polygon_motion_paths
-            .iter()
-            .find(|anim| anim.polygon_id == polygon_id)
-            .expect("Couldn't find animation data for polygon");
-
-        // Find position property
-        let position_property = animation_data
-            .properties
-            .iter()
-            .find(|prop| prop.name.starts_with("Position"))
-            .expect("Couldn't find position property");
-
-        // Sort keyframes by time
-        let mut keyframes = position_property.keyframes.clone();
-        keyframes.sort_by_key(|k| k.time);
-
-        // let new_id = Uuid::new_v4();
-        let new_id = Uuid::from_str(&animation_data.id).expect("Couldn't convert string to uuid");
-        let initial_position = animation_data.position;
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
-        let gpu_resources = self
-            .gpu_resources
-            .as_ref()
-            .expect("Couldn't get GPU Resources");
+    ) -> Result<(), EditorError> {
+        let (keyframes, new_id, initial_position) = motion_path_source(sequence, polygon_id)?;
+        let resources = self.scene.object_resources()?;
+        let gpu_resources = &resources.gpu_resources;
+        let camera = &resources.camera;
 
         // Create MotionPath
         let motion_path = MotionPath::new(
             &gpu_resources.device,
             &gpu_resources.queue,
-            self.model_bind_group_layout
-                .as_ref()
-                .expect("Couldn't get model bind group layout"),
-            self.group_bind_group_layout
-                .as_ref()
-                .expect("Couldn't get model bind group layout"),
+            &resources.model_bind_group_layout,
+            &resources.group_bind_group_layout,
             new_id,
             &camera.window_size,
             keyframes,
             camera,
             sequence,
-            // &mut self.static_polygons,
+            // &mut self.scene.static_polygons,
             color_index,
             polygon_id,
             initial_position,
         );
 
         self.motion_paths.push(motion_path);
+
+        Ok(())
     }
 
     /// Update the motion path visualization when keyframes change
     pub fn update_motion_paths(&mut self, sequence: &Sequence) {
         // Remove existing motion path segments
-        // self.static_polygons.retain(|p| {
+        // self.scene.static_polygons.retain(|p| {
         //     p.name != "motion_path_segment"
         //         && p.name != "motion_path_handle"
         //         && p.name != "motion_path_arrow"
@@ -3131,49 +3464,465 @@ impl Editor {
 
         // Remove existing motion paths
         self.motion_paths.clear();
+        self.dirty_motion_paths.clear();
 
         // Recreate motion paths for all polygons
         let mut color_index = 1;
         for polygon_config in &sequence.active_polygons {
-            self.create_motion_path_visualization(sequence, &polygon_config.id, color_index);
+            if let Err(e) =
+                self.create_motion_path_visualization(sequence, &polygon_config.id, color_index)
+            {
+                println!(
+                    "Couldn't create motion path for {}: {}",
+                    polygon_config.id, e
+                );
+            }
             color_index = color_index + 1;
         }
         // Recreate motion paths for all texts
         for text_config in &sequence.active_text_items {
-            self.create_motion_path_visualization(sequence, &text_config.id, color_index);
+            if let Err(e) =
+                self.create_motion_path_visualization(sequence, &text_config.id, color_index)
+            {
+                println!("Couldn't create motion path for {}: {}", text_config.id, e);
+            }
             color_index = color_index + 1;
         }
         // Recreate motion paths for all images
         for image_config in &sequence.active_image_items {
-            self.create_motion_path_visualization(sequence, &image_config.id, color_index);
+            if let Err(e) =
+                self.create_motion_path_visualization(sequence, &image_config.id, color_index)
+            {
+                println!("Couldn't create motion path for {}: {}", image_config.id, e);
+            }
             color_index = color_index + 1;
         }
         // Recreate motion paths for all videos
         for video_config in &sequence.active_video_items {
-            self.create_motion_path_visualization(sequence, &video_config.id, color_index);
+            if let Err(e) =
+                self.create_motion_path_visualization(sequence, &video_config.id, color_index)
+            {
+                println!("Couldn't create motion path for {}: {}", video_config.id, e);
+            }
             color_index = color_index + 1;
         }
     }
 
-    pub fn update_camera_binding(&mut self) {
-        if self.camera_binding.is_some() {
-            let gpu_resources = self
-                .gpu_resources
-                .as_ref()
-                .expect("Couldn't get gpu resources");
+    /// Updates one object's motion path after its keyframes change, reusing its buffers
+    /// when the path keeps the same handles, segments and arrows
+    pub fn update_motion_path(
+        &mut self,
+        sequence: &Sequence,
+        object_id: &str,
+    ) -> Result<(), EditorError> {
+        let Some(color_index) = motion_path_color_index(sequence, object_id) else {
+            return Ok(());
+        };
+        let (keyframes, _, initial_position) = motion_path_source(sequence, object_id)?;
+        let gpu_resources = self
+            .scene
+            .gpu_resources
+            .clone()
+            .ok_or(EditorError::NotInitialized("gpu resources"))?;
+        let window_size = self
+            .scene
+            .camera
+            .as_ref()
+            .ok_or(EditorError::NotInitialized("camera"))?
+            .window_size;
 
-            self.camera_binding
-                .as_mut()
-                .expect("Couldn't get camera binding")
-                .update_3d(
-                    &gpu_resources.queue,
-                    &self.camera.as_ref().expect("Couldn't get camera"),
-                );
-        }
-    }
+        let existing = self
+            .motion_paths
+            .iter()
+            .position(|path| path.source_polygon_id.to_string() == object_id);
+        if let Some(index) = existing {
+            if self.motion_paths[index].update_keyframes(
+                &gpu_resources.queue,
+                &window_size,
+                &keyframes,
+                initial_position,
+            ) {
+                return Ok(());
+            }
+            self.motion_paths.remove(index);
+        }
+
+        self.create_motion_path_visualization(sequence, object_id, color_index)?;
+
+        // keep the path's place, so overlapping paths draw in the same order
+        if let Some(index) = existing {
+            if let Some(motion_path) = self.motion_paths.pop() {
+                self.motion_paths.insert(index, motion_path);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Queues an object's motion path for `update_dirty_motion_paths`, so a burst of
+    /// keyframe edits only updates it once
+    pub fn mark_motion_path_dirty(&mut self, object_id: &str) {
+        if !self.dirty_motion_paths.iter().any(|id| id == object_id) {
+            self.dirty_motion_paths.push(object_id.to_string());
+        }
+    }
+
+    pub fn update_dirty_motion_paths(&mut self, sequence: &Sequence) -> Result<(), EditorError> {
+        let dirty_motion_paths = std::mem::take(&mut self.dirty_motion_paths);
+        for (i, object_id) in dirty_motion_paths.iter().enumerate() {
+            if let Err(e) = self.update_motion_path(sequence, object_id) {
+                // the rest stay queued for the next update
+                self.dirty_motion_paths
+                    .extend_from_slice(&dirty_motion_paths[i + 1..]);
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds a position keyframe to an object's path, easing like the keyframe before it.
+    /// Returns the new keyframe's id.
+    pub fn insert_path_keyframe(
+        &mut self,
+        object_id: Uuid,
+        time: Duration,
+        position: [i32; 2],
+    ) -> Result<String, String> {
+        let keyframe_id = Uuid::new_v4().to_string();
+        let object_id = object_id.to_string();
+        // whole milliseconds, like keyframes set from the timeline
+        let time = Duration::from_millis(time.as_millis() as u64);
+
+        self.edit_position_keyframes(&object_id, |keyframes| {
+            if keyframes.iter().any(|k| k.time == time) {
+                return Err(format!("There's already a keyframe at {:?}", time));
+            }
+
+            let easing = keyframes
+                .iter()
+                .filter(|k| k.time < time)
+                .max_by_key(|k| k.time)
+                .map_or(EasingType::EaseInOut, |k| k.easing.clone());
+            keyframes.push(UIKeyframe {
+                id: keyframe_id.clone(),
+                time,
+                value: KeyframeValue::Position(position),
+                easing,
+                path_type: PathType::Linear,
+                key_type: KeyType::Frame,
+            });
+            keyframes.sort_by_key(|k| k.time);
+
+            Ok(())
+        })?;
+
+        Ok(keyframe_id)
+    }
+
+    /// Removes a position keyframe from an object's path, keeping at least one
+    pub fn delete_path_keyframe(
+        &mut self,
+        object_id: Uuid,
+        keyframe_id: &str,
+    ) -> Result<(), String> {
+        self.edit_position_keyframes(&object_id.to_string(), |keyframes| {
+            if !keyframes.iter().any(|k| k.id == keyframe_id) {
+                return Err(format!("No keyframe found with id {}", keyframe_id));
+            }
+            if keyframes.len() <= 1 {
+                return Err("Can't delete an object's only position keyframe".to_string());
+            }
+
+            keyframes.retain(|k| k.id != keyframe_id);
+
+            Ok(())
+        })?;
+
+        if self
+            .selected_path_keyframe
+            .is_some_and(|(_, selected)| selected.to_string() == keyframe_id)
+        {
+            self.selected_path_keyframe = None;
+        }
+
+        Ok(())
+    }
+
+    /// Deletes the path handle last clicked, for the host's delete key
+    pub fn delete_selected_path_keyframe(&mut self) -> Result<(), String> {
+        let (object_id, keyframe_id) = self
+            .selected_path_keyframe
+            .ok_or("No path handle selected")?;
+
+        self.delete_path_keyframe(object_id, &keyframe_id.to_string())
+    }
+
+    /// Drives one of an object's properties with a spring toward each of its keyframes in
+    /// turn, or returns it to eased interpolation when given None
+    pub fn set_property_spring(
+        &mut self,
+        object_id: Uuid,
+        property_path: &str,
+        spring: Option<SpringConfig>,
+    ) -> Result<(), String> {
+        let object_id = object_id.to_string();
+        let sequence = self
+            .scene
+            .current_sequence_data
+            .as_mut()
+            .ok_or("No sequence selected")?;
+        let property = sequence
+            .polygon_motion_paths
+            .iter_mut()
+            .find(|a| a.polygon_id == object_id)
+            .ok_or_else(|| format!("No animation found for {}", object_id))?
+            .properties
+            .iter_mut()
+            .find(|p| p.property_path == property_path)
+            .ok_or_else(|| format!("No {} keyframes found for {}", property_path, object_id))?;
+
+        let easing = spring.map_or(EasingType::EaseInOut, EasingType::Spring);
+        for keyframe in property.keyframes.iter_mut() {
+            keyframe.easing = easing.clone();
+        }
+
+        self.save_current_sequence();
+        // springs can overshoot, which the path shows
+        if property_path == "position" {
+            let sequence = self
+                .scene
+                .current_sequence_data
+                .clone()
+                .expect("Couldn't get current sequence");
+            self.update_motion_path(&sequence, &object_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Moves an object's keyframes to their nearest beats, which are in milliseconds from
+    /// the start of the sequence. Keyframes that would land on another stay where they are.
+    pub fn snap_keyframes_to_beats(
+        &mut self,
+        object_id: Uuid,
+        keyframe_ids: &[String],
+        beats_ms: &[i32],
+    ) -> Result<(), String> {
+        let object_id = object_id.to_string();
+        let animation = self
+            .scene
+            .current_sequence_data
+            .as_mut()
+            .ok_or("No sequence selected")?
+            .polygon_motion_paths
+            .iter_mut()
+            .find(|a| a.polygon_id == object_id)
+            .ok_or_else(|| format!("No animation found for {}", object_id))?;
+        let start_ms = animation.start_time_ms;
+        let duration_ms = animation.duration.as_millis() as i32;
+
+        for property in animation.properties.iter_mut() {
+            for index in 0..property.keyframes.len() {
+                let keyframe = &property.keyframes[index];
+                if !keyframe_ids.contains(&keyframe.id) {
+                    continue;
+                }
+                let time_ms = keyframe.time.as_millis() as i32 + start_ms;
+                let Some(beat) = nearest_beat(beats_ms, time_ms) else {
+                    continue;
+                };
+
+                let time = Duration::from_millis((beat - start_ms).clamp(0, duration_ms) as u64);
+                let taken = property
+                    .keyframes
+                    .iter()
+                    .any(|k| k.time == time && k.id != keyframe.id);
+                if !taken {
+                    property.keyframes[index].time = time;
+                }
+            }
+            property.keyframes.sort_by_key(|k| k.time);
+        }
+
+        self.save_current_sequence();
+        let sequence = self
+            .scene
+            .current_sequence_data
+            .clone()
+            .expect("Couldn't get current sequence");
+        self.update_motion_path(&sequence, &object_id)?;
+
+        Ok(())
+    }
+
+    /// Pulses an object's scale on each beat, in milliseconds from the start of the
+    /// sequence, replacing its scale keyframes
+    pub fn add_beat_pulse(
+        &mut self,
+        object_id: Uuid,
+        beats_ms: &[i32],
+        intensity: i32, // percent added to the scale on a beat
+        decay_ms: i32,  // to shrink back
+    ) -> Result<(), String> {
+        let object_id = object_id.to_string();
+        let animation = self
+            .scene
+            .current_sequence_data
+            .as_mut()
+            .ok_or("No sequence selected")?
+            .polygon_motion_paths
+            .iter_mut()
+            .find(|a| a.polygon_id == object_id)
+            .ok_or_else(|| format!("No animation found for {}", object_id))?;
+
+        let values = pulse_keyframes(
+            beats_ms,
+            animation.start_time_ms,
+            animation.duration.as_millis() as i32,
+            intensity,
+            decay_ms,
+        );
+        let pulse = keyframe_property("Scale", "scale", &values);
+        match animation
+            .properties
+            .iter_mut()
+            .find(|p| p.property_path == "scale")
+        {
+            Some(property) => property.keyframes = pulse.keyframes,
+            None => animation.properties.push(pulse),
+        }
+
+        self.save_current_sequence();
+
+        Ok(())
+    }
+
+    /// Moves one of a curved keyframe's control points to a position relative to the path,
+    /// like the keyframe positions
+    pub fn set_path_tangent(
+        &mut self,
+        object_id: Uuid,
+        keyframe_id: &str,
+        control: TangentControl,
+        position: [i32; 2],
+    ) -> Result<(), String> {
+        self.edit_position_keyframes(&object_id.to_string(), |keyframes| {
+            set_tangent(keyframes, keyframe_id, control, position)
+        })
+    }
+
+    /// Follows the mouse with a dragged tangent handle, leaving saving for mouse up
+    fn drag_path_tangent(
+        &mut self,
+        object_id: Uuid,
+        keyframe_id: Uuid,
+        control: TangentControl,
+    ) -> Result<(), String> {
+        let object_id = object_id.to_string();
+        let local = self
+            .path_local_point(&object_id, self.last_top_left)
+            .ok_or_else(|| format!("No animation found for {}", object_id))?;
+        let position = [local.x.round() as i32, local.y.round() as i32];
+
+        self.edit_unsaved_position_keyframes(&object_id, |keyframes| {
+            set_tangent(keyframes, &keyframe_id.to_string(), control, position)
+        })
+    }
+
+    /// Edits an object's position keyframes in the current sequence, saving them and
+    /// redrawing its motion path
+    fn edit_position_keyframes(
+        &mut self,
+        object_id: &str,
+        edit: impl FnOnce(&mut Vec<UIKeyframe>) -> Result<(), String>,
+    ) -> Result<(), String> {
+        self.edit_unsaved_position_keyframes(object_id, edit)?;
+        self.save_current_sequence();
+
+        Ok(())
+    }
+
+    fn edit_unsaved_position_keyframes(
+        &mut self,
+        object_id: &str,
+        edit: impl FnOnce(&mut Vec<UIKeyframe>) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let sequence = self
+            .scene
+            .current_sequence_data
+            .as_mut()
+            .ok_or("No sequence selected")?;
+        let keyframes = &mut sequence
+            .polygon_motion_paths
+            .iter_mut()
+            .find(|a| a.polygon_id == object_id)
+            .ok_or_else(|| format!("No animation found for {}", object_id))?
+            .properties
+            .iter_mut()
+            .find(|p| p.property_path == "position")
+            .ok_or_else(|| format!("No position keyframes found for {}", object_id))?
+            .keyframes;
+        edit(keyframes)?;
+
+        let sequence = sequence.clone();
+        self.update_motion_path(&sequence, object_id)?;
+
+        Ok(())
+    }
+
+    /// Copies the current sequence into the saved state and writes it out
+    fn save_current_sequence(&mut self) {
+        let (Some(sequence), Some(saved_state)) = (
+            self.scene.current_sequence_data.as_ref(),
+            self.saved_state.as_mut(),
+        ) else {
+            return;
+        };
+
+        saved_state
+            .sequences
+            .iter_mut()
+            .filter(|s| s.id == sequence.id)
+            .for_each(|s| *s = sequence.clone());
+        if let Err(e) = save_saved_state_raw(saved_state.clone()) {
+            println!("Couldn't save sequence: {}", e);
+        }
+    }
+
+    /// The object, time and position of the motion path passing nearest a canvas point
+    fn path_point_at(&self, point: Point) -> Option<(Uuid, Duration, [i32; 2])> {
+        let sequence = self.scene.current_sequence_data.as_ref()?;
+
+        self.motion_paths.iter().find_map(|path| {
+            let object_id = path.source_polygon_id.to_string();
+            let local = self.path_local_point(&object_id, point)?;
+            let (keyframes, _, _) = motion_path_source(sequence, &object_id).ok()?;
+
+            path_point_near(&keyframes, local, PATH_HIT_TOLERANCE)
+                .map(|(time, position)| (path.source_polygon_id, time, position))
+        })
+    }
+
+    /// A canvas point relative to an object's path, where its keyframe positions are, which
+    /// is offset by the canvas and the animation's group position
+    fn path_local_point(&self, object_id: &str, point: Point) -> Option<Point> {
+        let animation = self
+            .scene
+            .current_sequence_data
+            .as_ref()?
+            .polygon_motion_paths
+            .iter()
+            .find(|a| a.polygon_id == object_id)?;
+
+        Some(Point {
+            x: point.x - CANVAS_HORIZ_OFFSET - animation.position[0] as f32,
+            y: point.y - CANVAS_VERT_OFFSET - animation.position[1] as f32,
+        })
+    }
 
     pub fn handle_wheel(&mut self, delta: f32, mouse_pos: Point, queue: &wgpu::Queue) {
-        let camera = self.camera.as_mut().expect("Couldnt't get camera");
+        let camera = self.scene.camera.as_mut().expect("Couldnt't get camera");
 
         // let interactive_bounds = BoundingBox {
         //     min: Point { x: 550.0, y: 0.0 }, // account for aside width
@@ -3202,7 +3951,7 @@ impl Editor {
         // let zoom_factor = if delta > 0.0 { 1.1 } else { 0.9 };
         let zoom_factor = delta / 10.0;
         camera.zoom(zoom_factor, mouse_pos);
-        self.update_camera_binding();
+        self.scene.update_camera_binding();
     }
 
     pub fn add_polygon(
@@ -3215,30 +3964,22 @@ impl Editor {
         polygon_name: String,
         new_id: Uuid,
         selected_sequence_id: String,
-    ) {
-        let gpu_resources = self
-            .gpu_resources
-            .as_ref()
-            .expect("Couldn't get gpu resources");
+    ) -> Result<(), EditorError> {
+        let resources = self.scene.object_resources()?;
+        let sequence_id = parse_id(&selected_sequence_id)?;
 
-        let device = &gpu_resources.device;
-        let queue = &gpu_resources.queue;
+        let device = &resources.gpu_resources.device;
+        let queue = &resources.gpu_resources.queue;
 
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
+        let camera = &resources.camera;
         let window_size = &camera.window_size;
 
         let polygon = Polygon::new(
             window_size,
             device,
             queue,
-            &self
-                .model_bind_group_layout
-                .as_ref()
-                .expect("Couldn't get model bind group layout"),
-            &self
-                .group_bind_group_layout
-                .as_ref()
-                .expect("Couldn't get group bind group layout"),
+            &resources.model_bind_group_layout,
+            &resources.group_bind_group_layout,
             camera,
             polygon_config.points,
             polygon_config.dimensions,
@@ -3254,7 +3995,7 @@ impl Editor {
             polygon_config.layer,
             polygon_name,
             new_id,
-            Uuid::from_str(&selected_sequence_id).expect("Couldn't convert string to uuid"),
+            sequence_id,
         );
         // // let world_position = camera.screen_to_world(polygon.transform.position);
         // let world_position = polygon.transform.position;
@@ -3266,11 +4007,13 @@ impl Editor {
         // polygon
         //     .transform
         //     .update_position([world_position.x, world_position.y]);
-        self.polygons.push(polygon);
+        self.scene.polygons.push(polygon);
         // self.run_layers_update();
 
         // TODO: udpate motion paths when adding new polygon
         // self.update_motion_paths(sequence);
+
+        Ok(())
     }
 
     pub fn add_text_item(
@@ -3282,37 +4025,35 @@ impl Editor {
         text_content: String,
         new_id: Uuid,
         selected_sequence_id: String,
-    ) {
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
+    ) -> Result<(), EditorError> {
+        let resources = self.scene.object_resources()?;
+        let sequence_id = parse_id(&selected_sequence_id)?;
 
         let default_font_family = self
+            .scene
             .font_manager
             .get_font_by_name(&text_config.font_family)
-            .expect("Couldn't load default font family");
+            .ok_or_else(|| EditorError::FontNotFound(text_config.font_family.clone()))?;
 
         let mut text_item = TextRenderer::new(
             device,
             queue,
-            &self
-                .model_bind_group_layout
-                .as_ref()
-                .expect("Couldn't get model bind group layout"),
-            &self
-                .group_bind_group_layout
-                .as_ref()
-                .expect("Couldn't get group bind group layout"),
+            &resources.model_bind_group_layout,
+            &resources.group_bind_group_layout,
             default_font_family, // load font data ahead of time
             window_size,
             text_content.clone(),
             text_config,
             new_id,
-            Uuid::from_str(&selected_sequence_id).expect("Couldn't convert string to uuid"),
-            camera,
+            sequence_id,
+            &resources.camera,
         );
 
         text_item.render_text(&device, &queue);
 
-        self.text_items.push(text_item);
+        self.scene.text_items.push(text_item);
+
+        Ok(())
     }
 
     /// Update text item properties including font family
@@ -3320,30 +4061,45 @@ impl Editor {
         &mut self,
         text_id: Uuid,
         property: ObjectProperty,
-    ) -> Result<(), String> {
-        let gpu_resources = self.gpu_resources.as_ref().expect("Couldn't get gpu resources");
+    ) -> Result<(), EditorError> {
+        let gpu_resources = self
+            .scene
+            .gpu_resources
+            .as_ref()
+            .ok_or(EditorError::NotInitialized("gpu resources"))?;
         let device = &gpu_resources.device;
         let queue = &gpu_resources.queue;
 
         // Find the text item by ID
-        let text_item = self.text_items.iter_mut()
+        let text_item = self
+            .scene
+            .text_items
+            .iter_mut()
             .find(|item| item.id == text_id)
-            .ok_or("Text item not found")?;
+            .ok_or(EditorError::ObjectNotFound {
+                object_type: ObjectType::TextItem,
+                id: text_id,
+            })?;
 
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
-        let window_size = camera.window_size;
-        let current_sequence = self.current_sequence_data.as_mut().expect("Couldn't get sequence data");
+        let current_sequence = self
+            .scene
+            .current_sequence_data
+            .as_mut()
+            .ok_or(EditorError::NoCurrentSequence)?;
         let current_sequence_id = current_sequence.id.clone();
 
         match property {
             ObjectProperty::FontFamily(new_font_family) => {
                 // Get the new font data
-                let font_data = self.font_manager.get_font_by_name(&new_font_family)
-                    .ok_or(format!("Font '{}' not found", new_font_family))?;
+                let font_data = self
+                    .scene
+                    .font_manager
+                    .get_font_by_name(&new_font_family)
+                    .ok_or_else(|| EditorError::FontNotFound(new_font_family.clone()))?;
 
                 // Update the font family
                 text_item.update_font_family(font_data);
-                
+
                 // Re-render the text
                 text_item.render_text(device, queue);
 
@@ -3352,7 +4108,7 @@ impl Editor {
                         p.font_family = new_font_family.clone();
                     }
                 });
-            },
+            }
             ObjectProperty::FontSize(new_size) => {
                 text_item.font_size = new_size as i32;
                 text_item.render_text(device, queue);
@@ -3362,7 +4118,7 @@ impl Editor {
                         p.font_size = new_size as i32;
                     }
                 });
-            },
+            }
             ObjectProperty::Text(new_text) => {
                 text_item.text = new_text.clone();
                 text_item.render_text(device, queue);
@@ -3372,29 +4128,30 @@ impl Editor {
                         p.text = new_text.clone();
                     }
                 });
-            },
+            }
             // Handle other properties like position, color, etc.
-            _ => return Err("Property not supported for text items".to_string()),
+            _ => return Err("Property not supported for text items".into()),
         }
 
         // update saved state
-        
+
         // Remove existing background
-        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        let saved_state = self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
 
-        saved_state.sequences
+        saved_state
+            .sequences
             .retain(|p| p.id != current_sequence_id);
 
         saved_state.sequences.push(current_sequence.clone());
 
-        save_saved_state_raw(saved_state.clone());
+        save_saved_state_raw(saved_state.clone())?;
 
         Ok(())
     }
 
     /// Get available font names from font manager
     pub fn get_available_fonts(&self) -> Vec<String> {
-        self.font_manager.get_available_font_names()
+        self.scene.font_manager.get_available_font_names()
     }
 
     pub fn add_image_item(
@@ -3406,28 +4163,29 @@ impl Editor {
         path: &Path,
         new_id: Uuid,
         selected_sequence_id: String,
-    ) {
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
+    ) -> Result<(), EditorError> {
+        let resources = self.scene.object_resources()?;
         let image_item = StImage::new(
             device,
             queue,
             path,
             image_config,
             window_size,
-            &self
-                .model_bind_group_layout
-                .as_ref()
-                .expect("Couldn't get model bind group layout"),
-            &self
-                .group_bind_group_layout
-                .as_ref()
-                .expect("Couldn't get group bind group layout"),
+            &resources.model_bind_group_layout,
+            &resources.group_bind_group_layout,
+            &mut self.scene.image_assets,
             0.0,
             new_id.to_string(),
-            Uuid::from_str(&selected_sequence_id).expect("Couldn't convert string to uuid"),
-        );
+            parse_id(&selected_sequence_id)?,
+        )
+        .map_err(|reason| EditorError::Media {
+            path: path.display().to_string(),
+            reason,
+        })?;
 
-        self.image_items.push(image_item);
+        self.scene.image_items.push(image_item);
+
+        Ok(())
     }
 
     pub fn add_video_item(
@@ -3441,27 +4199,25 @@ impl Editor {
         selected_sequence_id: String,
         mouse_positions: Option<Vec<MousePosition>>,
         stored_source_data: Option<SourceData>,
-    ) {
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
+    ) -> Result<(), EditorError> {
+        let resources = self.scene.object_resources()?;
+        let media_error = |reason: String| EditorError::Media {
+            path: path.display().to_string(),
+            reason,
+        };
         let mut video_item = StVideo::new(
             device,
             queue,
             path,
             video_config,
             window_size,
-            &self
-                .model_bind_group_layout
-                .as_ref()
-                .expect("Couldn't get model bind group layout"),
-            &self
-                .group_bind_group_layout
-                .as_ref()
-                .expect("Couldn't get group bind group layout"),
+            &resources.model_bind_group_layout,
+            &resources.group_bind_group_layout,
             0.0,
             new_id.to_string(),
-            Uuid::from_str(&selected_sequence_id).expect("Couldn't convert string to uuid"),
+            parse_id(&selected_sequence_id)?,
         )
-        .expect("Couldn't create video item");
+        .map_err(|e| media_error(format!("Couldn't open video: {}", e)))?;
 
         // set mouse capture source data if it exists
         video_item.source_data = stored_source_data;
@@ -3472,71 +4228,40 @@ impl Editor {
         // render 1 frame to provide preview image
         video_item
             .draw_video_frame(device, queue)
-            .expect("Couldn't draw video frame");
-
-        self.video_items.push(video_item);
-    }
+            .map_err(|e| media_error(format!("Couldn't decode video: {}", e)))?;
 
-    pub fn replace_background(&mut self, sequence_id: Uuid, fill: [f32; 4]) {
-        println!("replace background {:?} {:?}", sequence_id, fill);
+        if let Some(settings) = self.scene.video_prefetch {
+            video_item.start_prefetch(settings);
+        }
 
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
-        let window_size = camera.window_size;
-        let model_bind_group_layout = self
-            .model_bind_group_layout
-            .as_ref()
-            .expect("Couldn't get bind group layout");
-        let group_bind_group_layout = self
-            .group_bind_group_layout
-            .as_ref()
-            .expect("Couldn't get bind group layout");
+        self.scene.video_items.push(video_item);
 
-        // Remove existing background
-        self.static_polygons
-            .retain(|p| p.name != "canvas_background");
+        Ok(())
+    }
 
-        let gpu_resources = self
-            .gpu_resources
-            .as_ref()
-            .expect("Couldn't get gpu resources");
+    pub fn replace_background(
+        &mut self,
+        sequence_id: Uuid,
+        fill: [f32; 4],
+    ) -> Result<(), EditorError> {
+        self.scene.replace_background(sequence_id, fill)?;
 
-        let canvas_polygon = Polygon::new(
-            &window_size,
-            &gpu_resources.device,
-            &gpu_resources.queue,
-            &model_bind_group_layout,
-            &group_bind_group_layout,
-            &camera,
-            vec![
-                Point { x: 0.0, y: 0.0 },
-                Point { x: 1.0, y: 0.0 },
-                Point { x: 1.0, y: 1.0 },
-                Point { x: 0.0, y: 1.0 },
-            ],
-            // (800.0 as f32, 450.0 as f32),
-            // Point { x: 400.0, y: 225.0 },
-            (1000.0 as f32, 600.0 as f32),
-            Point { x: 500.0, y: 350.0 },
-            0.0,
-            0.0,
-            fill,
-            Stroke {
-                thickness: 0.0,
-                fill: rgb_to_wgpu(0, 0, 0, 255.0),
-            },
-            // 0.0,
-            1, // camera far is -100
-            "canvas_background".to_string(),
-            sequence_id,
-            Uuid::nil(),
-        );
+        // keep the grid, rulers and safe areas aligned with the new canvas
+        self.update_grid_polygons()?;
+        self.update_safe_area_polygons()?;
 
-        self.static_polygons.push(canvas_polygon);
+        Ok(())
     }
 
-    pub fn update_background(&mut self, selected_id: Uuid, key: &str, new_value: InputValue) {
+    pub fn update_background(
+        &mut self,
+        selected_id: Uuid,
+        key: &str,
+        new_value: InputValue,
+    ) -> Result<(), EditorError> {
         // First iteration: find the index of the selected polygon
         let polygon_index = self
+            .scene
             .static_polygons
             .iter()
             .position(|p| p.id == selected_id && p.name == "canvas_background".to_string());
@@ -3544,15 +4269,13 @@ impl Editor {
         if let Some(index) = polygon_index {
             println!("Found selected static_polygon with ID: {}", selected_id);
 
-            let camera = self.camera.as_ref().expect("Couldn't get camera");
+            let resources = self.scene.object_resources()?;
+            let camera = &resources.camera;
 
             // Get the necessary data from editor
             let viewport_width = camera.window_size.width;
             let viewport_height = camera.window_size.height;
-            let gpu_resources = self
-                .gpu_resources
-                .as_ref()
-                .expect("Couldn't get gpu resources");
+            let gpu_resources = &resources.gpu_resources;
             let device = &gpu_resources.device;
             let queue = &gpu_resources.queue;
 
@@ -3562,7 +4285,7 @@ impl Editor {
             };
 
             // Second iteration: update the selected polygon
-            if let Some(selected_polygon) = self.static_polygons.get_mut(index) {
+            if let Some(selected_polygon) = self.scene.static_polygons.get_mut(index) {
                 match new_value {
                     InputValue::Text(s) => match key {
                         _ => println!("No match on input"),
@@ -3572,10 +4295,7 @@ impl Editor {
                             &window_size,
                             &device,
                             &queue,
-                            &self
-                                .model_bind_group_layout
-                                .as_ref()
-                                .expect("Couldn't get model bind group layout"),
+                            &resources.model_bind_group_layout,
                             [
                                 color_to_wgpu(n),
                                 selected_polygon.fill[1],
@@ -3588,10 +4308,7 @@ impl Editor {
                             &window_size,
                             &device,
                             &queue,
-                            &self
-                                .model_bind_group_layout
-                                .as_ref()
-                                .expect("Couldn't get model bind group layout"),
+                            &resources.model_bind_group_layout,
                             [
                                 selected_polygon.fill[0],
                                 color_to_wgpu(n),
@@ -3604,10 +4321,7 @@ impl Editor {
                             &window_size,
                             &device,
                             &queue,
-                            &self
-                                .model_bind_group_layout
-                                .as_ref()
-                                .expect("Couldn't get model bind group layout"),
+                            &resources.model_bind_group_layout,
                             [
                                 selected_polygon.fill[0],
                                 selected_polygon.fill[1],
@@ -3621,29 +4335,35 @@ impl Editor {
                 }
             }
         } else {
-            println!(
-                "No static_polygon found with the selected ID: {}",
-                selected_id
-            );
+            return Err(EditorError::ObjectNotFound {
+                object_type: ObjectType::Polygon,
+                id: selected_id,
+            });
         }
+
+        Ok(())
     }
 
-    pub fn update_polygon(&mut self, selected_id: Uuid, key: &str, new_value: InputValue, auto_save: bool) {
+    pub fn update_polygon(
+        &mut self,
+        selected_id: Uuid,
+        key: &str,
+        new_value: InputValue,
+        auto_save: bool,
+    ) -> Result<(), EditorError> {
         // First iteration: find the index of the selected polygon
-        let polygon_index = self.polygons.iter().position(|p| p.id == selected_id);
+        let polygon_index = self.scene.polygons.iter().position(|p| p.id == selected_id);
 
         if let Some(index) = polygon_index {
             println!("Found selected polygon with ID: {}", selected_id);
 
-            let camera = self.camera.as_ref().expect("Couldn't get camera");
+            let resources = self.scene.object_resources()?;
+            let camera = &resources.camera;
 
             // Get the necessary data from editor
             let viewport_width = camera.window_size.width;
             let viewport_height = camera.window_size.height;
-            let gpu_resources = self
-                .gpu_resources
-                .as_ref()
-                .expect("Couldn't get gpu resources");
+            let gpu_resources = &resources.gpu_resources;
             let device = &gpu_resources.device;
             let queue = &gpu_resources.queue;
 
@@ -3653,14 +4373,15 @@ impl Editor {
             };
 
             // Second iteration: update the selected polygon
-            if let Some(selected_polygon) = self.polygons.get_mut(index) {
+            if let Some(selected_polygon) = self.scene.polygons.get_mut(index) {
                 match new_value {
                     InputValue::Text(s) => match key {
                         _ => println!("No match on input"),
                     },
                     InputValue::Number(n) => match key {
                         "width" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 // if s.id == selected_sequence_id.get() { // would be more efficient for many sequences
                                 s.active_polygons.iter_mut().for_each(|p| {
@@ -3675,16 +4396,14 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 (n, selected_polygon.dimensions.1),
                                 &camera,
                             )
-                        },
+                        }
                         "height" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 s.active_polygons.iter_mut().for_each(|p| {
                                     if p.id == selected_id.to_string() {
@@ -3697,16 +4416,14 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 (selected_polygon.dimensions.0, n),
                                 &camera,
                             )
-                        },
+                        }
                         "border_radius" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 s.active_polygons.iter_mut().for_each(|p| {
                                     if p.id == selected_id.to_string() {
@@ -3719,16 +4436,14 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 n,
                                 &camera,
                             )
-                        },
+                        }
                         "red" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 s.active_polygons.iter_mut().for_each(|p| {
                                     if p.id == selected_id.to_string() {
@@ -3741,10 +4456,7 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 [
                                     color_to_wgpu(n),
                                     selected_polygon.fill[1],
@@ -3753,9 +4465,10 @@ impl Editor {
                                 ],
                                 &camera,
                             )
-                        },
+                        }
                         "green" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 s.active_polygons.iter_mut().for_each(|p| {
                                     if p.id == selected_id.to_string() {
@@ -3768,10 +4481,7 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 [
                                     selected_polygon.fill[0],
                                     color_to_wgpu(n),
@@ -3780,9 +4490,10 @@ impl Editor {
                                 ],
                                 &camera,
                             )
-                        },
+                        }
                         "blue" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 s.active_polygons.iter_mut().for_each(|p| {
                                     if p.id == selected_id.to_string() {
@@ -3795,10 +4506,7 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 [
                                     selected_polygon.fill[0],
                                     selected_polygon.fill[1],
@@ -3807,9 +4515,10 @@ impl Editor {
                                 ],
                                 &camera,
                             )
-                        },
+                        }
                         "stroke_thickness" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 s.active_polygons.iter_mut().for_each(|p| {
                                     if p.id == selected_id.to_string() {
@@ -3822,19 +4531,17 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 Stroke {
                                     thickness: n,
                                     fill: selected_polygon.stroke.fill,
                                 },
                                 &camera,
                             )
-                        },
+                        }
                         "stroke_red" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 s.active_polygons.iter_mut().for_each(|p| {
                                     if p.id == selected_id.to_string() {
@@ -3847,10 +4554,7 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 Stroke {
                                     thickness: selected_polygon.stroke.thickness,
                                     fill: [
@@ -3862,9 +4566,10 @@ impl Editor {
                                 },
                                 &camera,
                             )
-                        },
+                        }
                         "stroke_green" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 s.active_polygons.iter_mut().for_each(|p| {
                                     if p.id == selected_id.to_string() {
@@ -3877,10 +4582,7 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 Stroke {
                                     thickness: selected_polygon.stroke.thickness,
                                     fill: [
@@ -3892,9 +4594,10 @@ impl Editor {
                                 },
                                 &camera,
                             )
-                        },
+                        }
                         "stroke_blue" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 s.active_polygons.iter_mut().for_each(|p| {
                                     if p.id == selected_id.to_string() {
@@ -3907,10 +4610,7 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 Stroke {
                                     thickness: selected_polygon.stroke.thickness,
                                     fill: [
@@ -3922,36 +4622,62 @@ impl Editor {
                                 },
                                 &camera,
                             )
-                        },
+                        }
+                        "opacity" => {
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
+                            saved_state.sequences.iter_mut().for_each(|s| {
+                                s.active_polygons.iter_mut().for_each(|p| {
+                                    if p.id == selected_id.to_string() {
+                                        p.opacity = n as i32;
+                                    }
+                                });
+                            });
+
+                            selected_polygon.update_base_opacity(&queue, n / 100.0)
+                        }
                         _ => println!("No match on input"),
                     },
                 }
             }
         } else {
-            println!("No polygon found with the selected ID: {}", selected_id);
+            return Err(EditorError::ObjectNotFound {
+                object_type: ObjectType::Polygon,
+                id: selected_id,
+            });
         }
 
         if auto_save {
-            save_saved_state_raw(self.saved_state.clone().expect("Couldn't clone saved state"));
+            save_saved_state_raw(self.saved_state.clone().ok_or(EditorError::NoProject)?)?;
         }
+
+        Ok(())
     }
 
-    pub fn update_text(&mut self, selected_id: Uuid, key: &str, new_value: InputValue, auto_save: bool) {
+    pub fn update_text(
+        &mut self,
+        selected_id: Uuid,
+        key: &str,
+        new_value: InputValue,
+        auto_save: bool,
+    ) -> Result<(), EditorError> {
         // First iteration: find the index of the selected polygon
-        let text_index = self.text_items.iter().position(|p| p.id == selected_id);
+        let text_index = self
+            .scene
+            .text_items
+            .iter()
+            .position(|p| p.id == selected_id);
 
         if let Some(index) = text_index {
             println!("Found selected text with ID: {}", selected_id);
 
-            let camera = self.camera.as_ref().expect("Couldn't get camera");
+            let resources = self.scene.object_resources()?;
+            let camera = &resources.camera;
 
             // Get the necessary data from editor
             let viewport_width = camera.window_size.width;
             let viewport_height = camera.window_size.height;
-            let gpu_resources = self
-                .gpu_resources
-                .as_ref()
-                .expect("Couldn't get gpu resources");
+            let gpu_resources = &resources.gpu_resources;
             let device = &gpu_resources.device;
             let queue = &gpu_resources.queue;
 
@@ -3961,14 +4687,15 @@ impl Editor {
             };
 
             // Second iteration: update the selected polygon
-            if let Some(selected_text) = self.text_items.get_mut(index) {
+            if let Some(selected_text) = self.scene.text_items.get_mut(index) {
                 match new_value {
                     InputValue::Text(s) => match key {
                         _ => println!("No match on input"),
                     },
                     InputValue::Number(n) => match key {
                         "width" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 // if s.id == selected_sequence_id.get() { // would be more efficient for many sequences
                                 s.active_text_items.iter_mut().for_each(|p| {
@@ -3983,16 +4710,14 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 (n, selected_text.dimensions.1),
                                 &camera,
                             )
-                        },
+                        }
                         "height" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 s.active_text_items.iter_mut().for_each(|p| {
                                     if p.id == selected_id.to_string() {
@@ -4005,16 +4730,14 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 (selected_text.dimensions.0, n),
                                 &camera,
                             )
-                        },
+                        }
                         "red_fill" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 s.active_text_items.iter_mut().for_each(|p| {
                                     if p.id == selected_id.to_string() {
@@ -4029,10 +4752,7 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 [
                                     n,
                                     selected_text.background_polygon.fill[1],
@@ -4041,9 +4761,10 @@ impl Editor {
                                 ],
                                 &camera,
                             )
-                        },
+                        }
                         "green_fill" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 s.active_text_items.iter_mut().for_each(|p| {
                                     if p.id == selected_id.to_string() {
@@ -4058,10 +4779,7 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 [
                                     selected_text.background_polygon.fill[0],
                                     n,
@@ -4070,9 +4788,10 @@ impl Editor {
                                 ],
                                 &camera,
                             )
-                        },
+                        }
                         "blue_fill" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 s.active_text_items.iter_mut().for_each(|p| {
                                     if p.id == selected_id.to_string() {
@@ -4087,10 +4806,7 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 [
                                     selected_text.background_polygon.fill[0],
                                     selected_text.background_polygon.fill[1],
@@ -4099,23 +4815,47 @@ impl Editor {
                                 ],
                                 &camera,
                             )
-                        },
+                        }
+                        "opacity" => {
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
+                            saved_state.sequences.iter_mut().for_each(|s| {
+                                s.active_text_items.iter_mut().for_each(|p| {
+                                    if p.id == selected_id.to_string() {
+                                        p.opacity = n as i32;
+                                    }
+                                });
+                            });
+
+                            selected_text.update_base_opacity(&queue, n / 100.0)
+                        }
                         _ => println!("No match on input"),
                     },
                 }
             }
         } else {
-            println!("No text found with the selected ID: {}", selected_id);
+            return Err(EditorError::ObjectNotFound {
+                object_type: ObjectType::TextItem,
+                id: selected_id,
+            });
         }
 
         if auto_save {
-            save_saved_state_raw(self.saved_state.clone().expect("Couldn't clone saved state"));
+            save_saved_state_raw(self.saved_state.clone().ok_or(EditorError::NoProject)?)?;
         }
+
+        Ok(())
     }
 
-    pub fn update_image(&mut self, selected_id: Uuid, key: &str, new_value: InputValue) {
+    pub fn update_image(
+        &mut self,
+        selected_id: Uuid,
+        key: &str,
+        new_value: InputValue,
+    ) -> Result<(), EditorError> {
         // First iteration: find the index of the selected polygon
         let image_index = self
+            .scene
             .image_items
             .iter()
             .position(|p| p.id == selected_id.to_string());
@@ -4123,15 +4863,13 @@ impl Editor {
         if let Some(index) = image_index {
             println!("Found selected image with ID: {}", selected_id);
 
-            let camera = self.camera.as_ref().expect("Couldn't get camera");
+            let resources = self.scene.object_resources()?;
+            let camera = &resources.camera;
 
             // Get the necessary data from editor
             let viewport_width = camera.window_size.width;
             let viewport_height = camera.window_size.height;
-            let gpu_resources = self
-                .gpu_resources
-                .as_ref()
-                .expect("Couldn't get gpu resources");
+            let gpu_resources = &resources.gpu_resources;
             let device = &gpu_resources.device;
             let queue = &gpu_resources.queue;
 
@@ -4141,14 +4879,15 @@ impl Editor {
             };
 
             // Second iteration: update the selected polygon
-            if let Some(selected_image) = self.image_items.get_mut(index) {
+            if let Some(selected_image) = self.scene.image_items.get_mut(index) {
                 match new_value {
                     InputValue::Text(s) => match key {
                         _ => println!("No match on input"),
                     },
                     InputValue::Number(n) => match key {
                         "width" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 // if s.id == selected_sequence_id.get() { // would be more efficient for many sequences
                                 s.active_image_items.iter_mut().for_each(|p| {
@@ -4163,16 +4902,14 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 (n as f32, selected_image.dimensions.1 as f32),
                                 &camera,
                             )
-                        },
+                        }
                         "height" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 s.active_image_items.iter_mut().for_each(|p| {
                                     if p.id == selected_id.to_string() {
@@ -4185,28 +4922,75 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 (selected_image.dimensions.0 as f32, n as f32),
                                 &camera,
                             )
-                        },
+                        }
+                        "opacity" => {
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
+                            saved_state.sequences.iter_mut().for_each(|s| {
+                                s.active_image_items.iter_mut().for_each(|p| {
+                                    if p.id == selected_id.to_string() {
+                                        p.opacity = n as i32;
+                                    }
+                                });
+                            });
+
+                            selected_image.update_base_opacity(&queue, n / 100.0)
+                        }
+                        "nine_slice_left" | "nine_slice_top" | "nine_slice_right"
+                        | "nine_slice_bottom" => {
+                            // zero on every side turns nine-slice off
+                            let mut insets = selected_image.nine_slice.unwrap_or_default();
+                            let inset = n.max(0.0) as u32;
+                            match key {
+                                "nine_slice_left" => insets.left = inset,
+                                "nine_slice_top" => insets.top = inset,
+                                "nine_slice_right" => insets.right = inset,
+                                _ => insets.bottom = inset,
+                            }
+                            let nine_slice =
+                                Some(insets).filter(|i| *i != NineSliceInsets::default());
+
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
+                            saved_state.sequences.iter_mut().for_each(|s| {
+                                s.active_image_items.iter_mut().for_each(|p| {
+                                    if p.id == selected_id.to_string() {
+                                        p.nine_slice = nine_slice;
+                                    }
+                                });
+                            });
+
+                            selected_image.update_nine_slice(&device, &queue, nine_slice)
+                        }
                         _ => println!("No match on input"),
                     },
                 }
             }
         } else {
-            println!("No image found with the selected ID: {}", selected_id);
+            return Err(EditorError::ObjectNotFound {
+                object_type: ObjectType::ImageItem,
+                id: selected_id,
+            });
         }
 
-        save_saved_state_raw(self.saved_state.clone().expect("Couldn't clone saved state"));
+        save_saved_state_raw(self.saved_state.clone().ok_or(EditorError::NoProject)?)?;
+
+        Ok(())
     }
 
-    pub fn update_video(&mut self, selected_id: Uuid, key: &str, new_value: InputValue) {
+    pub fn update_video(
+        &mut self,
+        selected_id: Uuid,
+        key: &str,
+        new_value: InputValue,
+    ) -> Result<(), EditorError> {
         // First iteration: find the index of the selected polygon
         let video_index = self
+            .scene
             .video_items
             .iter()
             .position(|p| p.id == selected_id.to_string());
@@ -4214,15 +4998,13 @@ impl Editor {
         if let Some(index) = video_index {
             println!("Found selected video with ID: {}", selected_id);
 
-            let camera = self.camera.as_ref().expect("Couldn't get camera");
+            let resources = self.scene.object_resources()?;
+            let camera = &resources.camera;
 
             // Get the necessary data from editor
             let viewport_width = camera.window_size.width;
             let viewport_height = camera.window_size.height;
-            let gpu_resources = self
-                .gpu_resources
-                .as_ref()
-                .expect("Couldn't get gpu resources");
+            let gpu_resources = &resources.gpu_resources;
             let device = &gpu_resources.device;
             let queue = &gpu_resources.queue;
 
@@ -4232,14 +5014,15 @@ impl Editor {
             };
 
             // Second iteration: update the selected polygon
-            if let Some(selected_video) = self.video_items.get_mut(index) {
+            if let Some(selected_video) = self.scene.video_items.get_mut(index) {
                 match new_value {
                     InputValue::Text(s) => match key {
                         _ => println!("No match on input"),
                     },
                     InputValue::Number(n) => match key {
                         "width" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 // if s.id == selected_sequence_id.get() { // would be more efficient for many sequences
                                 s.active_video_items.iter_mut().for_each(|p| {
@@ -4249,21 +5032,19 @@ impl Editor {
                                 });
                                 // }
                             });
-                                
+
                             selected_video.update_data_from_dimensions(
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 (n as f32, selected_video.dimensions.1 as f32),
                                 &camera,
                             )
-                        },
+                        }
                         "height" => {
-                            let saved_state = self.saved_state.as_mut().expect("Couldn't get saved_state");
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
                             saved_state.sequences.iter_mut().for_each(|s| {
                                 s.active_video_items.iter_mut().for_each(|p| {
                                     if p.id == selected_id.to_string() {
@@ -4276,32 +5057,47 @@ impl Editor {
                                 &window_size,
                                 &device,
                                 &queue,
-                                &self
-                                    .model_bind_group_layout
-                                    .as_ref()
-                                    .expect("Couldn't get model bind group layout"),
+                                &resources.model_bind_group_layout,
                                 (selected_video.dimensions.0 as f32, n as f32),
                                 &camera,
                             )
-                        },
+                        }
+                        "opacity" => {
+                            let saved_state =
+                                self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
+                            saved_state.sequences.iter_mut().for_each(|s| {
+                                s.active_video_items.iter_mut().for_each(|p| {
+                                    if p.id == selected_id.to_string() {
+                                        p.opacity = n as i32;
+                                    }
+                                });
+                            });
+
+                            selected_video.update_base_opacity(&queue, n / 100.0)
+                        }
                         _ => println!("No match on input"),
                     },
                 }
             }
         } else {
-            println!("No image found with the selected ID: {}", selected_id);
+            return Err(EditorError::ObjectNotFound {
+                object_type: ObjectType::VideoItem,
+                id: selected_id,
+            });
         }
 
-        save_saved_state_raw(self.saved_state.clone().expect("Couldn't clone saved state"));
+        save_saved_state_raw(self.saved_state.clone().ok_or(EditorError::NoProject)?)?;
+
+        Ok(())
     }
 
     pub fn get_object_width(&self, selected_id: Uuid, object_type: ObjectType) -> f32 {
         match object_type {
             ObjectType::Polygon => {
-                let polygon_index = self.polygons.iter().position(|p| p.id == selected_id);
+                let polygon_index = self.scene.polygons.iter().position(|p| p.id == selected_id);
 
                 if let Some(index) = polygon_index {
-                    if let Some(selected_polygon) = self.polygons.get(index) {
+                    if let Some(selected_polygon) = self.scene.polygons.get(index) {
                         return selected_polygon.dimensions.0;
                     } else {
                         return 0.0;
@@ -4309,10 +5105,14 @@ impl Editor {
                 }
             }
             ObjectType::TextItem => {
-                let polygon_index = self.text_items.iter().position(|p| p.id == selected_id);
+                let polygon_index = self
+                    .scene
+                    .text_items
+                    .iter()
+                    .position(|p| p.id == selected_id);
 
                 if let Some(index) = polygon_index {
-                    if let Some(selected_polygon) = self.text_items.get(index) {
+                    if let Some(selected_polygon) = self.scene.text_items.get(index) {
                         return selected_polygon.dimensions.0;
                     } else {
                         return 0.0;
@@ -4321,12 +5121,13 @@ impl Editor {
             }
             ObjectType::ImageItem => {
                 let polygon_index = self
+                    .scene
                     .image_items
                     .iter()
                     .position(|p| p.id == selected_id.to_string());
 
                 if let Some(index) = polygon_index {
-                    if let Some(selected_polygon) = self.image_items.get(index) {
+                    if let Some(selected_polygon) = self.scene.image_items.get(index) {
                         return selected_polygon.dimensions.0 as f32;
                     } else {
                         return 0.0;
@@ -4335,18 +5136,34 @@ impl Editor {
             }
             ObjectType::VideoItem => {
                 let polygon_index = self
+                    .scene
                     .video_items
                     .iter()
                     .position(|p| p.id == selected_id.to_string());
 
                 if let Some(index) = polygon_index {
-                    if let Some(selected_polygon) = self.video_items.get(index) {
+                    if let Some(selected_polygon) = self.scene.video_items.get(index) {
                         return selected_polygon.dimensions.0 as f32;
                     } else {
                         return 0.0;
                     }
                 }
             }
+            ObjectType::Group => {
+                if let Some(bbox) = self.get_object_bounding_box(selected_id, &ObjectType::Group) {
+                    return bbox.max.x - bbox.min.x;
+                }
+            }
+            ObjectType::ParticleEmitter => {
+                if let Some(emitter) = self
+                    .scene
+                    .particle_emitters
+                    .iter()
+                    .find(|e| e.id == selected_id)
+                {
+                    return emitter.get_dimensions().0;
+                }
+            }
         }
 
         0.0
@@ -4355,10 +5172,10 @@ impl Editor {
     pub fn get_object_height(&self, selected_id: Uuid, object_type: ObjectType) -> f32 {
         match object_type {
             ObjectType::Polygon => {
-                let polygon_index = self.polygons.iter().position(|p| p.id == selected_id);
+                let polygon_index = self.scene.polygons.iter().position(|p| p.id == selected_id);
 
                 if let Some(index) = polygon_index {
-                    if let Some(selected_polygon) = self.polygons.get(index) {
+                    if let Some(selected_polygon) = self.scene.polygons.get(index) {
                         return selected_polygon.dimensions.1;
                     } else {
                         return 0.0;
@@ -4366,10 +5183,14 @@ impl Editor {
                 }
             }
             ObjectType::TextItem => {
-                let polygon_index = self.text_items.iter().position(|p| p.id == selected_id);
+                let polygon_index = self
+                    .scene
+                    .text_items
+                    .iter()
+                    .position(|p| p.id == selected_id);
 
                 if let Some(index) = polygon_index {
-                    if let Some(selected_polygon) = self.text_items.get(index) {
+                    if let Some(selected_polygon) = self.scene.text_items.get(index) {
                         return selected_polygon.dimensions.1;
                     } else {
                         return 0.0;
@@ -4378,12 +5199,13 @@ impl Editor {
             }
             ObjectType::ImageItem => {
                 let polygon_index = self
+                    .scene
                     .image_items
                     .iter()
                     .position(|p| p.id == selected_id.to_string());
 
                 if let Some(index) = polygon_index {
-                    if let Some(selected_polygon) = self.image_items.get(index) {
+                    if let Some(selected_polygon) = self.scene.image_items.get(index) {
                         return selected_polygon.dimensions.1 as f32;
                     } else {
                         return 0.0;
@@ -4392,28 +5214,48 @@ impl Editor {
             }
             ObjectType::VideoItem => {
                 let polygon_index = self
+                    .scene
                     .video_items
                     .iter()
                     .position(|p| p.id == selected_id.to_string());
 
                 if let Some(index) = polygon_index {
-                    if let Some(selected_polygon) = self.video_items.get(index) {
+                    if let Some(selected_polygon) = self.scene.video_items.get(index) {
                         return selected_polygon.dimensions.1 as f32;
                     } else {
                         return 0.0;
                     }
                 }
             }
+            ObjectType::Group => {
+                if let Some(bbox) = self.get_object_bounding_box(selected_id, &ObjectType::Group) {
+                    return bbox.max.y - bbox.min.y;
+                }
+            }
+            ObjectType::ParticleEmitter => {
+                if let Some(emitter) = self
+                    .scene
+                    .particle_emitters
+                    .iter()
+                    .find(|e| e.id == selected_id)
+                {
+                    return emitter.get_dimensions().1;
+                }
+            }
         }
 
         0.0
     }
 
     pub fn get_fill_red(&self, selected_id: Uuid) -> f32 {
-        let polygon_index = self.text_items.iter().position(|p| p.id == selected_id);
+        let polygon_index = self
+            .scene
+            .text_items
+            .iter()
+            .position(|p| p.id == selected_id);
 
         if let Some(index) = polygon_index {
-            if let Some(selected_polygon) = self.text_items.get(index) {
+            if let Some(selected_polygon) = self.scene.text_items.get(index) {
                 return selected_polygon.background_polygon.fill[0];
             } else {
                 return 0.0;
@@ -4424,10 +5266,14 @@ impl Editor {
     }
 
     pub fn get_fill_green(&self, selected_id: Uuid) -> f32 {
-        let polygon_index = self.text_items.iter().position(|p| p.id == selected_id);
+        let polygon_index = self
+            .scene
+            .text_items
+            .iter()
+            .position(|p| p.id == selected_id);
 
         if let Some(index) = polygon_index {
-            if let Some(selected_polygon) = self.text_items.get(index) {
+            if let Some(selected_polygon) = self.scene.text_items.get(index) {
                 return selected_polygon.background_polygon.fill[1];
             } else {
                 return 0.0;
@@ -4438,10 +5284,14 @@ impl Editor {
     }
 
     pub fn get_fill_blue(&self, selected_id: Uuid) -> f32 {
-        let polygon_index = self.text_items.iter().position(|p| p.id == selected_id);
+        let polygon_index = self
+            .scene
+            .text_items
+            .iter()
+            .position(|p| p.id == selected_id);
 
         if let Some(index) = polygon_index {
-            if let Some(selected_polygon) = self.text_items.get(index) {
+            if let Some(selected_polygon) = self.scene.text_items.get(index) {
                 return selected_polygon.background_polygon.fill[2];
             } else {
                 return 0.0;
@@ -4453,12 +5303,13 @@ impl Editor {
 
     pub fn get_background_red(&self, selected_id: Uuid) -> f32 {
         let polygon_index = self
+            .scene
             .static_polygons
             .iter()
             .position(|p| p.id == selected_id);
 
         if let Some(index) = polygon_index {
-            if let Some(selected_polygon) = self.static_polygons.get(index) {
+            if let Some(selected_polygon) = self.scene.static_polygons.get(index) {
                 return selected_polygon.fill[0];
             } else {
                 return 0.0;
@@ -4470,12 +5321,13 @@ impl Editor {
 
     pub fn get_background_green(&self, selected_id: Uuid) -> f32 {
         let polygon_index = self
+            .scene
             .static_polygons
             .iter()
             .position(|p| p.id == selected_id);
 
         if let Some(index) = polygon_index {
-            if let Some(selected_polygon) = self.static_polygons.get(index) {
+            if let Some(selected_polygon) = self.scene.static_polygons.get(index) {
                 return selected_polygon.fill[1];
             } else {
                 return 0.0;
@@ -4487,12 +5339,13 @@ impl Editor {
 
     pub fn get_background_blue(&self, selected_id: Uuid) -> f32 {
         let polygon_index = self
+            .scene
             .static_polygons
             .iter()
             .position(|p| p.id == selected_id);
 
         if let Some(index) = polygon_index {
-            if let Some(selected_polygon) = self.static_polygons.get(index) {
+            if let Some(selected_polygon) = self.scene.static_polygons.get(index) {
                 return selected_polygon.fill[2];
             } else {
                 return 0.0;
@@ -4503,10 +5356,10 @@ impl Editor {
     }
 
     pub fn get_polygon_red(&self, selected_id: Uuid) -> f32 {
-        let polygon_index = self.polygons.iter().position(|p| p.id == selected_id);
+        let polygon_index = self.scene.polygons.iter().position(|p| p.id == selected_id);
 
         if let Some(index) = polygon_index {
-            if let Some(selected_polygon) = self.polygons.get(index) {
+            if let Some(selected_polygon) = self.scene.polygons.get(index) {
                 return selected_polygon.fill[0];
             } else {
                 return 0.0;
@@ -4517,10 +5370,10 @@ impl Editor {
     }
 
     pub fn get_polygon_green(&self, selected_id: Uuid) -> f32 {
-        let polygon_index = self.polygons.iter().position(|p| p.id == selected_id);
+        let polygon_index = self.scene.polygons.iter().position(|p| p.id == selected_id);
 
         if let Some(index) = polygon_index {
-            if let Some(selected_polygon) = self.polygons.get(index) {
+            if let Some(selected_polygon) = self.scene.polygons.get(index) {
                 return selected_polygon.fill[1];
             } else {
                 return 0.0;
@@ -4531,10 +5384,10 @@ impl Editor {
     }
 
     pub fn get_polygon_blue(&self, selected_id: Uuid) -> f32 {
-        let polygon_index = self.polygons.iter().position(|p| p.id == selected_id);
+        let polygon_index = self.scene.polygons.iter().position(|p| p.id == selected_id);
 
         if let Some(index) = polygon_index {
-            if let Some(selected_polygon) = self.polygons.get(index) {
+            if let Some(selected_polygon) = self.scene.polygons.get(index) {
                 return selected_polygon.fill[2];
             } else {
                 return 0.0;
@@ -4545,10 +5398,10 @@ impl Editor {
     }
 
     pub fn get_polygon_border_radius(&self, selected_id: Uuid) -> f32 {
-        let polygon_index = self.polygons.iter().position(|p| p.id == selected_id);
+        let polygon_index = self.scene.polygons.iter().position(|p| p.id == selected_id);
 
         if let Some(index) = polygon_index {
-            if let Some(selected_polygon) = self.polygons.get(index) {
+            if let Some(selected_polygon) = self.scene.polygons.get(index) {
                 return selected_polygon.border_radius;
             } else {
                 return 0.0;
@@ -4559,10 +5412,10 @@ impl Editor {
     }
 
     pub fn get_polygon_stroke_thickness(&self, selected_id: Uuid) -> f32 {
-        let polygon_index = self.polygons.iter().position(|p| p.id == selected_id);
+        let polygon_index = self.scene.polygons.iter().position(|p| p.id == selected_id);
 
         if let Some(index) = polygon_index {
-            if let Some(selected_polygon) = self.polygons.get(index) {
+            if let Some(selected_polygon) = self.scene.polygons.get(index) {
                 return selected_polygon.stroke.thickness;
             } else {
                 return 0.0;
@@ -4573,10 +5426,10 @@ impl Editor {
     }
 
     pub fn get_polygon_stroke_red(&self, selected_id: Uuid) -> f32 {
-        let polygon_index = self.polygons.iter().position(|p| p.id == selected_id);
+        let polygon_index = self.scene.polygons.iter().position(|p| p.id == selected_id);
 
         if let Some(index) = polygon_index {
-            if let Some(selected_polygon) = self.polygons.get(index) {
+            if let Some(selected_polygon) = self.scene.polygons.get(index) {
                 return selected_polygon.stroke.fill[0];
             } else {
                 return 0.0;
@@ -4587,10 +5440,10 @@ impl Editor {
     }
 
     pub fn get_polygon_stroke_green(&self, selected_id: Uuid) -> f32 {
-        let polygon_index = self.polygons.iter().position(|p| p.id == selected_id);
+        let polygon_index = self.scene.polygons.iter().position(|p| p.id == selected_id);
 
         if let Some(index) = polygon_index {
-            if let Some(selected_polygon) = self.polygons.get(index) {
+            if let Some(selected_polygon) = self.scene.polygons.get(index) {
                 return selected_polygon.stroke.fill[1];
             } else {
                 return 0.0;
@@ -4601,10 +5454,10 @@ impl Editor {
     }
 
     pub fn get_polygon_stroke_blue(&self, selected_id: Uuid) -> f32 {
-        let polygon_index = self.polygons.iter().position(|p| p.id == selected_id);
+        let polygon_index = self.scene.polygons.iter().position(|p| p.id == selected_id);
 
         if let Some(index) = polygon_index {
-            if let Some(selected_polygon) = self.polygons.get(index) {
+            if let Some(selected_polygon) = self.scene.polygons.get(index) {
                 return selected_polygon.stroke.fill[2];
             } else {
                 return 0.0;
@@ -4614,248 +5467,639 @@ impl Editor {
         0.0
     }
 
-    pub fn update_text_font_family(&mut self, font_id: String, selected_text_id: Uuid) {
+    pub fn update_text_font_family(
+        &mut self,
+        font_id: String,
+        selected_text_id: Uuid,
+    ) -> Result<(), EditorError> {
         let gpu_resources = self
+            .scene
             .gpu_resources
             .as_ref()
-            .expect("Couldn't get gpu resources");
+            .ok_or(EditorError::NotInitialized("gpu resources"))?;
 
         let new_font_family = self
+            .scene
             .font_manager
             .get_font_by_name(&font_id)
-            .expect("Couldn't load default font family");
+            .ok_or_else(|| EditorError::FontNotFound(font_id.clone()))?;
 
         let text_item = self
+            .scene
             .text_items
             .iter_mut()
             .find(|t| t.id == selected_text_id)
-            .expect("Couldn't find text item");
+            .ok_or(EditorError::ObjectNotFound {
+                object_type: ObjectType::TextItem,
+                id: selected_text_id,
+            })?;
 
         text_item.font_family = font_id.clone();
         text_item.update_font_family(new_font_family);
         text_item.render_text(&gpu_resources.device, &gpu_resources.queue);
+
+        Ok(())
     }
 
-    pub fn update_text_color(&mut self, selected_text_id: Uuid, color: [i32; 4]) {
+    pub fn update_text_color(
+        &mut self,
+        selected_text_id: Uuid,
+        color: [i32; 4],
+    ) -> Result<(), EditorError> {
         let gpu_resources = self
+            .scene
             .gpu_resources
             .as_ref()
-            .expect("Couldn't get gpu resources");
+            .ok_or(EditorError::NotInitialized("gpu resources"))?;
 
         let text_item = self
+            .scene
             .text_items
             .iter_mut()
             .find(|t| t.id == selected_text_id)
-            .expect("Couldn't find text item");
+            .ok_or(EditorError::ObjectNotFound {
+                object_type: ObjectType::TextItem,
+                id: selected_text_id,
+            })?;
 
         text_item.color = color;
         text_item.render_text(&gpu_resources.device, &gpu_resources.queue);
+
+        Ok(())
     }
 
-    pub fn update_text_size(&mut self, selected_text_id: Uuid, size: i32) {
+    pub fn update_text_size(
+        &mut self,
+        selected_text_id: Uuid,
+        size: i32,
+    ) -> Result<(), EditorError> {
         let gpu_resources = self
+            .scene
             .gpu_resources
             .as_ref()
-            .expect("Couldn't get gpu resources");
+            .ok_or(EditorError::NotInitialized("gpu resources"))?;
 
         let text_item = self
+            .scene
             .text_items
             .iter_mut()
             .find(|t| t.id == selected_text_id)
-            .expect("Couldn't find text item");
+            .ok_or(EditorError::ObjectNotFound {
+                object_type: ObjectType::TextItem,
+                id: selected_text_id,
+            })?;
 
         text_item.font_size = size;
         text_item.render_text(&gpu_resources.device, &gpu_resources.queue);
+
+        Ok(())
     }
 
-    pub fn update_text_content(&mut self, selected_text_id: Uuid, content: String) {
+    pub fn update_text_content(
+        &mut self,
+        selected_text_id: Uuid,
+        content: String,
+    ) -> Result<(), EditorError> {
         let gpu_resources = self
+            .scene
             .gpu_resources
             .as_ref()
-            .expect("Couldn't get gpu resources");
+            .ok_or(EditorError::NotInitialized("gpu resources"))?;
 
         let text_item = self
+            .scene
             .text_items
             .iter_mut()
             .find(|t| t.id == selected_text_id)
-            .expect("Couldn't find text item");
+            .ok_or(EditorError::ObjectNotFound {
+                object_type: ObjectType::TextItem,
+                id: selected_text_id,
+            })?;
 
         text_item.text = content;
         text_item.render_text(&gpu_resources.device, &gpu_resources.queue);
-    }
-
-    // pub fn update_date_from_window_resize(
-    //     &mut self,
-    //     window_size: &WindowSize,
-    //     device: &wgpu::Device,
-    // ) {
-    //     let camera = self.camera.as_ref().expect("Couldn't get camera");
-    //     for (poly_index, polygon) in self.polygons.iter_mut().enumerate() {
-    //         polygon.update_data_from_window_size(window_size, device, &camera);
-    //     }
-    // }
 
-    pub fn handle_mouse_down(
-        &mut self,
-        window_size: &WindowSize,
-        device: &wgpu::Device,
-    ) -> Option<ObjectEditConfig> {
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
+        Ok(())
+    }
 
-        if self.last_screen.x < self.interactive_bounds.min.x
-            || self.last_screen.x > self.interactive_bounds.max.x
-            || self.last_screen.y < self.interactive_bounds.min.y
-            || self.last_screen.y > self.interactive_bounds.max.y
+    /// Starts editing a text item on the canvas with the caret at the end, finishing any other
+    /// edit first. Hosts send key and IME events to handle_text_input until editing ends.
+    pub fn begin_text_editing(&mut self, text_id: Uuid) -> Result<(), EditorError> {
+        if self
+            .text_editing
+            .as_ref()
+            .map_or(false, |e| e.text_id == text_id)
         {
-            return None;
+            return Ok(());
         }
+        self.end_text_editing(true);
 
-        if self.canvas_hidden {
-            return None;
+        let text_item = self
+            .scene
+            .text_items
+            .iter()
+            .find(|t| t.id == text_id)
+            .ok_or(EditorError::ObjectNotFound {
+                object_type: ObjectType::TextItem,
+                id: text_id,
+            })?;
+        if text_item.locked {
+            return Err("Locked text items can't be edited".into());
         }
 
-        // Handle motion mode - start placing motion arrow
-        if self.motion_mode {
-            self.drag_start = Some(self.last_top_left);
-            return None;
-        }
+        self.text_editing = Some(TextEditState::new(text_id, text_item.text.clone()));
+        self.refresh_text_editing();
+        self.events
+            .emit(EditorEvent::TextEditingChanged(Some(text_id)));
 
-        // First, check if panning
-        if self.control_mode == ControlMode::Pan {
-            self.is_panning = true;
-            self.drag_start = Some(self.last_top_left);
+        Ok(())
+    }
 
-            return None;
+    /// Applies key or IME input to the text being edited. Committing returns the edit for the
+    /// undo stack when the text changed.
+    pub fn handle_text_input(&mut self, input: TextInput) -> Option<ObjectEditConfig> {
+        let editing = self.text_editing.as_mut()?;
+
+        match input {
+            TextInput::Commit => self.end_text_editing(true),
+            TextInput::Cancel => self.end_text_editing(false),
+            input => {
+                if editing.apply(&input) {
+                    self.refresh_text_editing();
+                }
+                None
+            }
         }
+    }
 
-        // Next, check if we're clicking on a motion path handle to drag
-        // for (poly_index, polygon) in self.static_polygons.iter_mut().enumerate() {
-        //     if polygon.name != "motion_path_handle".to_string() {
-        //         continue;
-        //     }
+    /// Stops editing, saving the text when committing and restoring it otherwise. An
+    /// unfinished composition is dropped.
+    pub fn end_text_editing(&mut self, commit: bool) -> Option<ObjectEditConfig> {
+        let editing = self.text_editing.take()?;
+        self.scene
+            .static_polygons
+            .retain(|p| p.name != "text_caret" && p.name != "text_selection");
+        self.events.emit(EditorEvent::TextEditingChanged(None));
+
+        if commit && editing.text != editing.original_text {
+            let new_text = ObjectProperty::Text(editing.text.clone());
+            if let Err(e) = self.update_text_property(editing.text_id, new_text) {
+                println!("Couldn't save text: {}", e);
+                return None;
+            }
 
-        //     if polygon.contains_point(&self.last_top_left, &camera) {
-        //         self.dragging_path_handle = Some(polygon.id);
-        //         self.dragging_path_object = polygon.source_polygon_id;
-        //         self.dragging_path_keyframe = polygon.source_keyframe_id;
-        //         self.drag_start = Some(self.last_top_left);
+            return Some(ObjectEditConfig {
+                object_id: editing.text_id,
+                object_type: ObjectType::TextItem,
+                field_name: "text".to_string(),
+                old_value: ObjectProperty::Text(editing.original_text),
+                new_value: ObjectProperty::Text(editing.text),
+            });
+        }
 
-        //         return None; // nothing to add to undo stack
-        //     }
-        // }
+        let gpu_resources = self.scene.gpu_resources.as_ref()?;
+        if let Some(text_item) = self
+            .scene
+            .text_items
+            .iter_mut()
+            .find(|t| t.id == editing.text_id)
+        {
+            text_item.update_text(
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                editing.original_text,
+            );
+        }
 
-        for (path_index, path) in self.motion_paths.iter_mut().enumerate() {
-            for (poly_index, polygon) in path.static_polygons.iter_mut().enumerate() {
-                // check if we're clicking on a motion path handle to drag
-                if polygon.name == "motion_path_handle".to_string() {
-                    if polygon.contains_point(&self.last_top_left, &camera) {
-                        self.dragging_path_handle = Some(polygon.id);
-                        self.dragging_path_assoc_path = polygon.source_path_id;
-                        self.dragging_path_object = polygon.source_polygon_id;
-                        self.dragging_path_keyframe = polygon.source_keyframe_id;
-                        self.drag_start = Some(self.last_top_left);
+        None
+    }
 
-                        return None; // nothing to add to undo stack
-                    }
-                }
-                // disable because dragging along with selected object makes this redundant and confusing
-                // if polygon.name == "motion_path_segment".to_string() {
-                //     if polygon.contains_point(&self.last_top_left, &camera) {
-                //         self.dragging_path = Some(path.id);
-                //         self.dragging_path_object = polygon.source_polygon_id;
-                //         self.drag_start = Some(self.last_top_left);
+    /// Where the caret is drawn while editing, for hosts to place the IME candidate window
+    pub fn text_caret_bounds(&self) -> Option<BoundingBox> {
+        let editing = self.text_editing.as_ref()?;
+        let text_item = self
+            .scene
+            .text_items
+            .iter()
+            .find(|t| t.id == editing.text_id)?;
+        let caret = editing
+            .stops
+            .iter()
+            .find(|s| s.byte_offset == editing.display_caret())?;
+        let position = text_item.transform.position;
 
-                //         return None; // nothing to add to undo stack
-                //     }
-                // }
-            }
+        Some(BoundingBox {
+            min: Point {
+                x: position.x + caret.x,
+                y: position.y + caret.top,
+            },
+            max: Point {
+                x: position.x + caret.x,
+                y: position.y + caret.bottom,
+            },
+        })
+    }
+
+    /// Blinks the caret while editing text, call once a frame
+    pub fn step_text_caret(&mut self) {
+        let editing = match self.text_editing.as_mut() {
+            Some(editing) => editing,
+            None => return,
+        };
+        let visible = editing.caret_blink_visible();
+        if visible == editing.caret_visible {
+            return;
         }
+        editing.caret_visible = visible;
 
-        // First, check for resize handle clicks (highest priority)
-        if let Some((handle_id, handle_position)) = self.handle_clicked_at_point(&self.last_top_left, &camera) {
-            self.start_handle_drag(handle_id, handle_position);
-            self.drag_start = Some(self.last_top_left);
-            return None; // No undo needed for handle drag start
+        let Some(gpu_resources) = self.scene.gpu_resources.as_ref() else {
+            return;
+        };
+        for caret in self
+            .scene
+            .static_polygons
+            .iter_mut()
+            .filter(|p| p.name == "text_caret")
+        {
+            caret.update_opacity(&gpu_resources.queue, if visible { 1.0 } else { 0.0 });
         }
+    }
 
-        // Finally, check for object interation
-        let mut intersecting_objects: Vec<(i32, InteractionTarget)> = Vec::new();
+    /// Moves the caret to the character nearest a point, returning false when a click is
+    /// outside the text being edited. Extending drags out a selection from the anchor.
+    fn place_text_caret(&mut self, point: Point, extend: bool) -> bool {
+        let Some(camera) = self.scene.camera.as_ref() else {
+            return false;
+        };
+        let editing = match self.text_editing.as_mut() {
+            Some(editing) => editing,
+            None => return false,
+        };
+        let text_item = match self
+            .scene
+            .text_items
+            .iter()
+            .find(|t| t.id == editing.text_id)
+        {
+            Some(text_item) => text_item,
+            None => return false,
+        };
+        if !extend && !text_item.contains_point(&point, camera) {
+            return false;
+        }
 
-        // Collect intersecting polygons
-        for (poly_index, polygon) in self.polygons.iter().enumerate() {
-            if polygon.hidden {
-                continue;
-            }
+        let offset = editing.offset_at(Point {
+            x: point.x - text_item.transform.position.x,
+            y: point.y - text_item.transform.position.y,
+        });
+        // the stops include the composition, which clicking drops
+        let composed = editing.composition.take().map_or(0, |c| c.len());
+        let offset = match offset {
+            offset if offset <= editing.caret => offset,
+            offset if offset < editing.caret + composed => editing.caret,
+            offset => offset - composed,
+        };
 
-            if polygon.contains_point(&self.last_top_left, &camera) {
-                intersecting_objects.push((polygon.layer, InteractionTarget::Polygon(poly_index)));
-            }
+        editing.set_caret(offset, extend);
+        if !extend {
+            editing.selecting = true;
         }
+        self.refresh_text_editing();
 
-        // Collect intersecting text items
-        for (text_index, text_item) in self.text_items.iter().enumerate() {
-            if text_item.hidden {
-                continue;
-            }
+        true
+    }
 
-            if text_item.contains_point(&self.last_top_left, &camera) {
-                intersecting_objects.push((text_item.layer, InteractionTarget::Text(text_index)));
-            }
+    /// Shows the text being edited with any composition, then redraws the caret, selection
+    /// and composition underline. Rotation isn't followed, the overlay stays upright.
+    fn refresh_text_editing(&mut self) {
+        self.scene
+            .static_polygons
+            .retain(|p| p.name != "text_caret" && p.name != "text_selection");
+
+        let Ok(resources) = self.scene.object_resources() else {
+            return;
+        };
+        let camera = &resources.camera;
+        let gpu_resources = &resources.gpu_resources;
+        let model_bind_group_layout = &resources.model_bind_group_layout;
+        let group_bind_group_layout = &resources.group_bind_group_layout;
+
+        let editing = match self.text_editing.as_mut() {
+            Some(editing) => editing,
+            None => return,
+        };
+        let text_item = match self
+            .scene
+            .text_items
+            .iter_mut()
+            .find(|t| t.id == editing.text_id)
+        {
+            Some(text_item) => text_item,
+            None => return,
+        };
+
+        let display_text = editing.display_text();
+        if text_item.text != display_text {
+            text_item.update_text(&gpu_resources.device, &gpu_resources.queue, display_text);
         }
+        editing.stops = text_item.caret_stops();
 
-        // Collect intersecting image items
-        for (image_index, image_item) in self.image_items.iter().enumerate() {
-            if image_item.hidden {
-                continue;
-            }
+        let position = text_item.transform.position;
+        let to_line = |start: Point, end: Point| GuideLine {
+            start: Point {
+                x: position.x + start.x,
+                y: position.y + start.y,
+            },
+            end: Point {
+                x: position.x + end.x,
+                y: position.y + end.y,
+            },
+        };
 
-            if image_item.contains_point(&self.last_top_left, &camera) {
-                intersecting_objects
-                    .push((image_item.layer, InteractionTarget::Image(image_index)));
+        let mut overlay: Vec<(GuideLine, [f32; 4], &str)> = Vec::new();
+        let selection_fill = rgb_to_wgpu(60, 130, 246, 90.0);
+        let caret_fill = rgb_to_wgpu(20, 20, 20, 255.0);
+
+        match &editing.composition {
+            // IME compositions are underlined rather than selected
+            Some(_) => {
+                for span in span_boxes(&editing.stops, editing.caret, editing.display_caret()) {
+                    let underline = to_line(
+                        Point {
+                            x: span.min.x,
+                            y: span.max.y - 2.0,
+                        },
+                        span.max,
+                    );
+                    overlay.push((underline, caret_fill, "text_selection"));
+                }
+            }
+            None => {
+                let (start, end) = editing.selection();
+                for span in span_boxes(&editing.stops, start, end) {
+                    overlay.push((
+                        to_line(span.min, span.max),
+                        selection_fill,
+                        "text_selection",
+                    ));
+                }
             }
         }
 
-        // Collect intersecting image items
-        for (video_index, video_item) in self.video_items.iter().enumerate() {
-            if video_item.hidden {
-                continue;
+        if let Some(caret) = editing
+            .stops
+            .iter()
+            .find(|s| s.byte_offset == editing.display_caret())
+        {
+            let caret_line = to_line(
+                Point {
+                    x: caret.x,
+                    y: caret.top,
+                },
+                Point {
+                    x: caret.x,
+                    y: caret.bottom,
+                },
+            );
+            overlay.push((caret_line, caret_fill, "text_caret"));
+        }
+
+        for (line, fill, name) in overlay {
+            let thickness = if name == "text_caret" { 2.0 } else { 1.0 };
+            let overlay_polygon = create_line_polygon(
+                &camera.window_size,
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                model_bind_group_layout,
+                group_bind_group_layout,
+                camera,
+                &line,
+                thickness,
+                fill,
+                100, // high z-layer to render on top
+                name.to_string(),
+            );
+
+            self.scene.static_polygons.push(overlay_polygon);
+        }
+    }
+
+    // pub fn update_date_from_window_resize(
+    //     &mut self,
+    //     window_size: &WindowSize,
+    //     device: &wgpu::Device,
+    // ) {
+    //     let camera = self.scene.camera.as_ref().expect("Couldn't get camera");
+    //     for (poly_index, polygon) in self.scene.polygons.iter_mut().enumerate() {
+    //         polygon.update_data_from_window_size(window_size, device, &camera);
+    //     }
+    // }
+
+    pub fn handle_mouse_down(
+        &mut self,
+        window_size: &WindowSize,
+        device: &wgpu::Device,
+    ) -> Option<ObjectEditConfig> {
+        // while editing text, clicks in it move the caret and clicks elsewhere finish editing
+        if self.text_editing.is_some() && !self.canvas_hidden {
+            if self.place_text_caret(self.last_top_left, false) {
+                return None;
             }
+            if let Some(edit) = self.end_text_editing(true) {
+                return Some(edit);
+            }
+        }
 
-            // println!("Checking video point");
+        let camera = self.scene.camera.as_ref().expect("Couldn't get camera");
 
-            if video_item.contains_point(&self.last_top_left, &camera) {
-                // println!("Video contains point");
-                intersecting_objects
-                    .push((video_item.layer, InteractionTarget::Video(video_index)));
+        if self.last_screen.x < self.interactive_bounds.min.x
+            || self.last_screen.x > self.interactive_bounds.max.x
+            || self.last_screen.y < self.interactive_bounds.min.y
+            || self.last_screen.y > self.interactive_bounds.max.y
+        {
+            return None;
+        }
+
+        if self.canvas_hidden {
+            return None;
+        }
+
+        // Handle motion mode - start placing motion arrow
+        if self.motion_mode {
+            self.drag_start = Some(self.last_top_left);
+            return None;
+        }
+
+        // First, check if panning, orbiting or dollying
+        if matches!(
+            self.control_mode,
+            ControlMode::Pan | ControlMode::Orbit | ControlMode::Dolly
+        ) {
+            self.is_panning = true;
+            self.drag_start = Some(self.last_top_left);
+
+            return None;
+        }
+
+        // Next, check if we're clicking on a motion path handle to drag
+        // for (poly_index, polygon) in self.scene.static_polygons.iter_mut().enumerate() {
+        //     if polygon.name != "motion_path_handle".to_string() {
+        //         continue;
+        //     }
+
+        //     if polygon.contains_point(&self.last_top_left, &camera) {
+        //         self.dragging_path_handle = Some(polygon.id);
+        //         self.dragging_path_object = polygon.source_polygon_id;
+        //         self.dragging_path_keyframe = polygon.source_keyframe_id;
+        //         self.drag_start = Some(self.last_top_left);
+
+        //         return None; // nothing to add to undo stack
+        //     }
+        // }
+
+        for (path_index, path) in self.motion_paths.iter_mut().enumerate() {
+            for (poly_index, polygon) in path.static_polygons.iter_mut().enumerate() {
+                // check if we're clicking on a curve's tangent handle to drag
+                if let Some(control) = TangentControl::from_handle_name(&polygon.name) {
+                    if polygon.contains_point(&self.last_top_left, &camera) {
+                        self.dragging_path_tangent = polygon
+                            .source_polygon_id
+                            .zip(polygon.source_keyframe_id)
+                            .map(|(object_id, keyframe_id)| (object_id, keyframe_id, control));
+                        self.drag_start = Some(self.last_top_left);
+
+                        return None; // saved on mouse up
+                    }
+                }
+                // check if we're clicking on a motion path handle to drag
+                if polygon.name == "motion_path_handle".to_string() {
+                    if polygon.contains_point(&self.last_top_left, &camera) {
+                        self.dragging_path_handle = Some(polygon.id);
+                        self.dragging_path_assoc_path = polygon.source_path_id;
+                        self.dragging_path_object = polygon.source_polygon_id;
+                        self.dragging_path_keyframe = polygon.source_keyframe_id;
+                        self.selected_path_keyframe =
+                            polygon.source_polygon_id.zip(polygon.source_keyframe_id);
+                        self.drag_start = Some(self.last_top_left);
+
+                        return None; // nothing to add to undo stack
+                    }
+                }
+                // disable because dragging along with selected object makes this redundant and confusing
+                // if polygon.name == "motion_path_segment".to_string() {
+                //     if polygon.contains_point(&self.last_top_left, &camera) {
+                //         self.dragging_path = Some(path.id);
+                //         self.dragging_path_object = polygon.source_polygon_id;
+                //         self.drag_start = Some(self.last_top_left);
+
+                //         return None; // nothing to add to undo stack
+                //     }
+                // }
             }
         }
 
-        // Sort intersecting objects by layer in descending order (highest layer first)
-        // intersecting_objects.sort_by(|a, b| b.0.cmp(&a.0));
+        // First, check for resize handle clicks (highest priority)
+        if let Some((handle_id, handle_position)) =
+            self.handle_clicked_at_point(&self.last_top_left)
+        {
+            self.start_handle_drag(handle_id, handle_position);
+            self.drag_start = Some(self.last_top_left);
+            return None; // No undo needed for handle drag start
+        }
 
-        // sort by lowest layer first, for this system
-        intersecting_objects.sort_by(|a, b| a.0.cmp(&b.0));
+        let is_double_click = self.last_click.map_or(false, |(time, point)| {
+            time.elapsed() < DOUBLE_CLICK_INTERVAL
+                && (point.x - self.last_top_left.x).abs() <= DOUBLE_CLICK_DISTANCE
+                && (point.y - self.last_top_left.y).abs() <= DOUBLE_CLICK_DISTANCE
+        });
+        self.last_click = Some((Instant::now(), self.last_top_left));
 
-        // Return the topmost intersecting object, if any
-        let target = intersecting_objects
-            .into_iter()
-            .next()
-            .map(|(_, target)| target);
+        // Finally, check for object interation
+        let mut target = self.object_at_point(self.last_top_left);
+
+        // the tests above are approximate for rotated and concave shapes, the picker is exact
+        if let Some(mut picker) = self.gpu_picker.take() {
+            let gpu_resources = self
+                .scene
+                .gpu_resources
+                .as_ref()
+                .expect("Couldn't get gpu resources");
+            target = picker.pick(
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                self,
+                self.last_screen,
+            );
+            self.gpu_picker = Some(picker);
+        }
+
+        // double clicking text edits it in place
+        if let (true, Some(InteractionTarget::Text(index))) = (is_double_click, &target) {
+            let text_id = self.scene.text_items[*index].id;
+            if self.begin_text_editing(text_id).is_ok() {
+                self.last_click = None;
+                self.place_text_caret(self.last_top_left, false);
+                return None;
+            }
+        }
+
+        // double clicking a motion path adds a keyframe where it was clicked
+        if is_double_click {
+            if let Some((object_id, time, position)) = self.path_point_at(self.last_top_left) {
+                self.last_click = None;
+                match self.insert_path_keyframe(object_id, time, position) {
+                    Ok(keyframe_id) => {
+                        self.selected_path_keyframe = parse_id(&keyframe_id)
+                            .ok()
+                            .map(|keyframe_id| (object_id, keyframe_id));
+                    }
+                    Err(e) => println!("Couldn't add keyframe: {}", e),
+                }
+                return None;
+            }
+        }
 
         if let Some(target) = target {
+            // clicking any child of a group selects and drags the whole group
+            let target_id = match target {
+                InteractionTarget::Polygon(index) => self.scene.polygons[index].id.to_string(),
+                InteractionTarget::Text(index) => self.scene.text_items[index].id.to_string(),
+                InteractionTarget::Image(index) => self.scene.image_items[index].id.clone(),
+                InteractionTarget::Video(index) => self.scene.video_items[index].id.clone(),
+                InteractionTarget::ParticleEmitter(index) => {
+                    self.scene.particle_emitters[index].id.to_string()
+                }
+            };
+            if let Some(group_id) = self
+                .scene
+                .groups
+                .iter()
+                .find(|g| g.contains_child(&target_id))
+                .map(|g| g.id)
+            {
+                self.dragging_group = Some(group_id);
+                self.drag_start = Some(self.last_top_left);
+
+                self.selected_polygon_id = group_id;
+
+                self.create_resize_handles_for_object(group_id, ObjectType::Group);
+
+                return None; // nothing to add to undo stack
+            }
+
             match target {
                 InteractionTarget::Polygon(index) => {
-                    let polygon_config = self.polygons[index].to_config();
+                    let polygon_config = self.scene.polygons[index].to_config();
 
                     self.dragging_polygon = Some(polygon_config.id);
                     self.drag_start = Some(self.last_top_left);
 
                     self.selected_polygon_id = polygon_config.id;
-                        
+
                     // Create resize handles for selected polygon
                     self.create_resize_handles_for_object(
                         polygon_config.id,
-                        crate::animations::ObjectType::Polygon
+                        crate::animations::ObjectType::Polygon,
                     );
 
                     // TODO: make DRY with below
@@ -4865,26 +6109,23 @@ impl Editor {
                             .as_ref()
                             .expect("Couldn't get handler");
                         let mut handle_click = handler_creator().expect("Couldn't get handler");
-                        handle_click(
-                            polygon_config.id,
-                            polygon_config,
-                        );
+                        handle_click(polygon_config.id, polygon_config);
                     }
 
                     return None; // nothing to add to undo stack
                 }
                 InteractionTarget::Text(index) => {
-                    let text_item_config = self.text_items[index].to_config();
+                    let text_item_config = self.scene.text_items[index].to_config();
 
                     self.dragging_text = Some(text_item_config.id);
                     self.drag_start = Some(self.last_top_left);
 
                     self.selected_polygon_id = text_item_config.id; // TODO: separate property for each object type?
-                    
+
                     // Create resize handles for selected text item
                     self.create_resize_handles_for_object(
                         text_item_config.id,
-                        crate::animations::ObjectType::TextItem
+                        crate::animations::ObjectType::TextItem,
                     );
 
                     // TODO: make DRY with below
@@ -4894,31 +6135,29 @@ impl Editor {
                             .as_ref()
                             .expect("Couldn't get handler");
                         let mut handle_click = handler_creator().expect("Couldn't get handler");
-                        handle_click(
-                            text_item_config.id,
-                            text_item_config,
-                        );
+                        handle_click(text_item_config.id, text_item_config);
                     }
 
                     return None; // nothing to add to undo stack
                 }
                 InteractionTarget::Image(index) => {
-                    let image_item_config = self.image_items[index].to_config();
+                    let image_item_config = self.scene.image_items[index].to_config();
 
-                    self.dragging_image =
-                        Some(Uuid::from_str(&image_item_config.id).expect("Couldn't convert to uuid"));
+                    self.dragging_image = Some(
+                        Uuid::from_str(&image_item_config.id).expect("Couldn't convert to uuid"),
+                    );
                     self.drag_start = Some(self.last_top_left);
 
                     let uuid = Uuid::from_str(&image_item_config.id.clone())
-                            .expect("Couldn't convert string to uuid");
+                        .expect("Couldn't convert string to uuid");
 
                     self.selected_polygon_id = uuid; // TODO: separate property for each object type?
-                                                         // polygon.old_points = Some(polygon.points.clone());
-                        
+                                                     // polygon.old_points = Some(polygon.points.clone());
+
                     // Create resize handles for selected image item
                     self.create_resize_handles_for_object(
                         uuid,
-                        crate::animations::ObjectType::ImageItem
+                        crate::animations::ObjectType::ImageItem,
                     );
 
                     // TODO: make DRY with below
@@ -4928,31 +6167,29 @@ impl Editor {
                             .as_ref()
                             .expect("Couldn't get handler");
                         let mut handle_click = handler_creator().expect("Couldn't get handler");
-                        
-                        handle_click(
-                            uuid,
-                            image_item_config,
-                        );
+
+                        handle_click(uuid, image_item_config);
                     }
 
                     return None; // nothing to add to undo stack
                 }
                 InteractionTarget::Video(index) => {
-                    let video_item_config = self.video_items[index].to_config();
+                    let video_item_config = self.scene.video_items[index].to_config();
 
-                    self.dragging_video =
-                        Some(Uuid::from_str(&video_item_config.id).expect("Couldn't convert to uuid"));
+                    self.dragging_video = Some(
+                        Uuid::from_str(&video_item_config.id).expect("Couldn't convert to uuid"),
+                    );
                     self.drag_start = Some(self.last_top_left);
 
                     let uuid = Uuid::from_str(&video_item_config.id.clone())
-                            .expect("Couldn't convert string to uuid");
+                        .expect("Couldn't convert string to uuid");
 
                     self.selected_polygon_id = uuid; // TODO: separate property for each object type?
-                    
+
                     // Create resize handles for selected video item
                     self.create_resize_handles_for_object(
                         uuid,
-                        crate::animations::ObjectType::VideoItem
+                        crate::animations::ObjectType::VideoItem,
                     );
 
                     if self.handle_video_click.is_some() {
@@ -4961,13 +6198,26 @@ impl Editor {
                             .as_ref()
                             .expect("Couldn't get handler");
                         let mut handle_click = handler_creator().expect("Couldn't get handler");
-                        
-                        handle_click(
-                            uuid,
-                            video_item_config,
-                        );
+
+                        handle_click(uuid, video_item_config);
                     }
 
+                    return None; // nothing to add to undo stack
+                }
+                InteractionTarget::ParticleEmitter(index) => {
+                    let emitter_id = self.scene.particle_emitters[index].id;
+
+                    self.dragging_particle_emitter = Some(emitter_id);
+                    self.drag_start = Some(self.last_top_left);
+
+                    self.selected_polygon_id = emitter_id;
+
+                    // Create resize handles for selected emitter, which resize its spawn area
+                    self.create_resize_handles_for_object(
+                        emitter_id,
+                        crate::animations::ObjectType::ParticleEmitter,
+                    );
+
                     return None; // nothing to add to undo stack
                 }
             }
@@ -4976,28 +6226,327 @@ impl Editor {
         None
     }
 
-    pub fn handle_mouse_move(
-        &mut self,
-        window_size: &WindowSize,
-        device: &wgpu::Device,
-        queue: &wgpu::Queue,
-        x: f32,
-        y: f32,
-    ) {
-        if self.canvas_hidden {
-            return;
+    /// Finds what's under the mouse, for hosts to set the cursor from its cursor_hint.
+    /// Call after handle_mouse_move. Emits HoverChanged when the target changes.
+    pub fn handle_mouse_hover(&mut self) -> Option<HoverTarget> {
+        if self.canvas_hidden
+            || self.last_screen.x < self.interactive_bounds.min.x
+            || self.last_screen.x > self.interactive_bounds.max.x
+            || self.last_screen.y < self.interactive_bounds.min.y
+            || self.last_screen.y > self.interactive_bounds.max.y
+        {
+            self.set_hover_target(None);
+            return None;
         }
 
-        let camera = self.camera.as_mut().expect("Couldn't get camera");
-        let mouse_pos = Point { x, y };
-        
-        let ray = visualize_ray_intersection(window_size, x, y, &camera);
+        // keep the cursor steady while dragging
+        if self.drag_start.is_some() {
+            return self.hover_target.clone();
+        }
 
-        // let ray = screen_to_world_perspective_correct(x, y, window_size, &camera);
+        let target = self.hover_target_at(self.last_top_left);
+        self.set_hover_target(target.clone());
 
-        let top_left = ray.top_left;
+        target
+    }
 
-        // println!("top_left {:?}", top_left);
+    /// Same priority as handle_mouse_down: path handles, then resize handles, then objects
+    fn hover_target_at(&self, point: Point) -> Option<HoverTarget> {
+        let camera = self.scene.camera.as_ref()?;
+
+        for path in &self.motion_paths {
+            for polygon in &path.static_polygons {
+                let is_handle = polygon.name == "motion_path_handle"
+                    || TangentControl::from_handle_name(&polygon.name).is_some();
+                if is_handle && polygon.contains_point(&point, camera) {
+                    return Some(HoverTarget::PathHandle {
+                        object_id: polygon.source_polygon_id,
+                        keyframe_id: polygon.source_keyframe_id,
+                    });
+                }
+            }
+        }
+
+        if let Some(handle) = self
+            .resize_handles
+            .iter()
+            .find(|h| h.gizmo.contains_point(&point))
+        {
+            return Some(HoverTarget::ResizeHandle {
+                object_id: handle.object_id,
+                position: handle.position,
+            });
+        }
+
+        let (object_id, object_type) = match self.object_at_point(point)? {
+            InteractionTarget::Polygon(index) => (
+                self.scene.polygons[index].id.to_string(),
+                ObjectType::Polygon,
+            ),
+            InteractionTarget::Text(index) => (
+                self.scene.text_items[index].id.to_string(),
+                ObjectType::TextItem,
+            ),
+            InteractionTarget::Image(index) => (
+                self.scene.image_items[index].id.clone(),
+                ObjectType::ImageItem,
+            ),
+            InteractionTarget::Video(index) => (
+                self.scene.video_items[index].id.clone(),
+                ObjectType::VideoItem,
+            ),
+            InteractionTarget::ParticleEmitter(index) => (
+                self.scene.particle_emitters[index].id.to_string(),
+                ObjectType::ParticleEmitter,
+            ),
+        };
+
+        if let Some(group) = self
+            .scene
+            .groups
+            .iter()
+            .find(|g| g.contains_child(&object_id))
+        {
+            return Some(HoverTarget::Object {
+                object_id: group.id,
+                object_type: ObjectType::Group,
+            });
+        }
+
+        Some(HoverTarget::Object {
+            object_id: Uuid::parse_str(&object_id).ok()?,
+            object_type,
+        })
+    }
+
+    fn set_hover_target(&mut self, target: Option<HoverTarget>) {
+        if self.hover_target == target {
+            return;
+        }
+
+        self.hover_target = target.clone();
+        self.update_hover_outline();
+        self.events.emit(EditorEvent::HoverChanged(target));
+    }
+
+    /// Rebuilds the outline around the hovered object, skipped for the selected one
+    /// which already shows its resize handles
+    pub fn update_hover_outline(&mut self) {
+        self.scene
+            .static_polygons
+            .retain(|p| p.name != "hover_outline");
+
+        if !self.hover_outline {
+            return;
+        }
+
+        let bbox = match &self.hover_target {
+            Some(HoverTarget::Object {
+                object_id,
+                object_type,
+            }) if *object_id != self.selected_polygon_id => {
+                match self.get_object_bounding_box(*object_id, object_type) {
+                    Some(bbox) => bbox,
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+
+        let Ok(resources) = self.scene.object_resources() else {
+            return;
+        };
+        let camera = &resources.camera;
+        let window_size = camera.window_size;
+        let gpu_resources = &resources.gpu_resources;
+        let model_bind_group_layout = &resources.model_bind_group_layout;
+        let group_bind_group_layout = &resources.group_bind_group_layout;
+
+        let corners = [
+            bbox.min,
+            Point {
+                x: bbox.max.x,
+                y: bbox.min.y,
+            },
+            bbox.max,
+            Point {
+                x: bbox.min.x,
+                y: bbox.max.y,
+            },
+        ];
+        for i in 0..corners.len() {
+            let edge = GuideLine {
+                start: corners[i],
+                end: corners[(i + 1) % corners.len()],
+            };
+            let outline_polygon = create_line_polygon(
+                &window_size,
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                model_bind_group_layout,
+                group_bind_group_layout,
+                camera,
+                &edge,
+                1.0,
+                rgb_to_wgpu(60, 130, 246, 255.0),
+                100, // high z-layer to render on top
+                "hover_outline".to_string(),
+            );
+
+            self.scene.static_polygons.push(outline_polygon);
+        }
+    }
+
+    /// Turns onion skinning on or off, or changes how many ghosts are shown
+    pub fn set_onion_skin_settings(&mut self, onion_skin_settings: OnionSkinSettings) {
+        self.onion_skin_settings = onion_skin_settings;
+        self.update_onion_skin();
+    }
+
+    /// Redraws ghosts of the selected object at its keyframes around the playhead. Polygons
+    /// are ghosted with their own shape, other objects as tinted boxes of their size.
+    pub fn update_onion_skin(&mut self) {
+        self.scene
+            .static_polygons
+            .retain(|p| p.name != "onion_skin");
+
+        if !self.onion_skin_settings.enabled {
+            return;
+        }
+
+        let Some(selected) = self.selected_object.as_ref() else {
+            return;
+        };
+        let Some(sequence) = self.scene.current_sequence_data.as_ref() else {
+            return;
+        };
+        let object_id = selected.object_id.to_string();
+        let Some(animation) = sequence
+            .polygon_motion_paths
+            .iter()
+            .find(|a| a.polygon_id == object_id)
+        else {
+            return;
+        };
+        let Some(bbox) = self.get_object_bounding_box(selected.object_id, &selected.object_type)
+        else {
+            return;
+        };
+
+        let time = Duration::from_secs_f32(self.scene.last_playhead_s)
+            .saturating_sub(Duration::from_millis(animation.start_time_ms.max(0) as u64));
+        let ghosts = onion_skin_ghosts(animation, time, &self.onion_skin_settings);
+        let polygon =
+            self.scene.polygons.iter().find(|p| {
+                p.id == selected.object_id && selected.object_type == ObjectType::Polygon
+            });
+
+        let Ok(resources) = self.scene.object_resources() else {
+            return;
+        };
+        let camera = &resources.camera;
+        let window_size = camera.window_size;
+        let gpu_resources = &resources.gpu_resources;
+        let model_bind_group_layout = &resources.model_bind_group_layout;
+        let group_bind_group_layout = &resources.group_bind_group_layout;
+
+        let mut ghost_polygons = Vec::new();
+
+        for ghost in &ghosts {
+            let center = Point {
+                x: CANVAS_HORIZ_OFFSET + ghost.position[0] as f32,
+                y: CANVAS_VERT_OFFSET + ghost.position[1] as f32,
+            };
+
+            let ghost_polygon = match polygon {
+                Some(polygon) => {
+                    let faded =
+                        |color: [f32; 4]| [color[0], color[1], color[2], color[3] * ghost.opacity];
+                    Polygon::new(
+                        &window_size,
+                        &gpu_resources.device,
+                        &gpu_resources.queue,
+                        model_bind_group_layout,
+                        group_bind_group_layout,
+                        camera,
+                        polygon.points.clone(),
+                        polygon.dimensions,
+                        center,
+                        polygon.transform.rotation,
+                        polygon.border_radius,
+                        faded(polygon.fill),
+                        Stroke {
+                            thickness: polygon.stroke.thickness,
+                            fill: faded(polygon.stroke.fill),
+                        },
+                        polygon.layer,
+                        "onion_skin".to_string(),
+                        Uuid::new_v4(),
+                        Uuid::nil(),
+                    )
+                }
+                None => {
+                    let half_width = (bbox.max.x - bbox.min.x) / 2.0;
+                    let half_height = (bbox.max.y - bbox.min.y) / 2.0;
+                    let tint = if ghost.is_previous {
+                        rgb_to_wgpu(255, 80, 80, 255.0 * ghost.opacity)
+                    } else {
+                        rgb_to_wgpu(80, 160, 255, 255.0 * ghost.opacity)
+                    };
+                    // a line spanning the box's corners fills it
+                    create_line_polygon(
+                        &window_size,
+                        &gpu_resources.device,
+                        &gpu_resources.queue,
+                        model_bind_group_layout,
+                        group_bind_group_layout,
+                        camera,
+                        &GuideLine {
+                            start: Point {
+                                x: center.x - half_width,
+                                y: center.y - half_height,
+                            },
+                            end: Point {
+                                x: center.x + half_width,
+                                y: center.y + half_height,
+                            },
+                        },
+                        1.0,
+                        tint,
+                        0,
+                        "onion_skin".to_string(),
+                    )
+                }
+            };
+
+            ghost_polygons.push(ghost_polygon);
+        }
+
+        self.scene.static_polygons.extend(ghost_polygons);
+    }
+
+    pub fn handle_mouse_move(
+        &mut self,
+        window_size: &WindowSize,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        x: f32,
+        y: f32,
+    ) {
+        if self.canvas_hidden {
+            return;
+        }
+
+        let camera = self.scene.camera.as_mut().expect("Couldn't get camera");
+        let mouse_pos = Point { x, y };
+
+        let ray = visualize_ray_intersection(window_size, x, y, &camera);
+
+        // let ray = screen_to_world_perspective_correct(x, y, window_size, &camera);
+
+        let top_left = ray.top_left;
+
+        // println!("top_left {:?}", top_left);
 
         // let top_left = camera.screen_to_world(x, y);
         // let top_left = mouse_pos;
@@ -5005,6 +6554,7 @@ impl Editor {
         // let top_left = Point { x: ray[0], y: ray[1] };
 
         self.global_top_left = top_left;
+        let previous_screen = self.last_screen;
         self.last_screen = Point { x, y };
 
         if self.last_screen.x < self.interactive_bounds.min.x
@@ -5028,8 +6578,7 @@ impl Editor {
         if let Some(dot) = &mut self.cursor_dot {
             // let ndc_position = point_to_ndc(self.last_top_left, &window_size);
             // println!("move dot {:?}", self.last_top_left);
-            dot.transform
-                .update_position([self.last_top_left.x, self.last_top_left.y], window_size);
+            dot.position = self.last_top_left;
         }
 
         // handle panning
@@ -5042,7 +6591,7 @@ impl Editor {
             // camera.position = Vector2::new(new_x, new_y);
             camera.position = Vector3::new(new_x, new_y, 0.0);
 
-            // self.update_camera_binding(); // call in render loop, much more efficient
+            // self.scene.update_camera_binding(); // call in render loop, much more efficient
             // self.interactive_bounds = BoundingBox {
             //     max: Point {
             //         x: self.interactive_bounds.max.x + dx,
@@ -5055,20 +6604,27 @@ impl Editor {
             // }
         }
 
+        // orbiting and dollying use screen movement, as the canvas point under the mouse moves with the camera
+        if self.control_mode == ControlMode::Orbit && self.is_panning {
+            let dx = self.last_screen.x - previous_screen.x;
+            let dy = self.last_screen.y - previous_screen.y;
+            camera.orbit(-dx * ORBIT_SPEED, dy * ORBIT_SPEED);
+        }
+
+        if self.control_mode == ControlMode::Dolly && self.is_panning {
+            let dy = previous_screen.y - self.last_screen.y;
+            camera.dolly(dy * DOLLY_SPEED);
+        }
+
         // handle resize handle dragging
         if let Some((object_id, handle_position)) = self.dragging_handle {
             if let Some(start) = self.drag_start {
-                // let mouse_delta = Point {
-                //     x: self.last_top_left.x - start.x,
-                //     y: self.last_top_left.y - start.y,
-                // };
-                
-                let mouse_delta = Point {
-                    x: self.last_top_left.x - self.previous_top_left.x,
-                    y: self.last_top_left.y - self.previous_top_left.y,
+                let drag_delta = Point {
+                    x: self.last_top_left.x - start.x,
+                    y: self.last_top_left.y - start.y,
                 };
 
-                self.resize_selected_object(mouse_delta);
+                self.resize_selected_object(drag_delta);
             }
         }
 
@@ -5096,21 +6652,27 @@ impl Editor {
             }
         }
 
+        // handle curve tangent handles, reshaping the path as they move
+        if let Some((object_id, keyframe_id, control)) = self.dragging_path_tangent {
+            if let Err(e) = self.drag_path_tangent(object_id, keyframe_id, control) {
+                println!("Couldn't move tangent handle: {}", e);
+            }
+        }
+
         // handle dragging to move objects (polygons, images, text, etc)
         if let Some(poly_id) = self.dragging_polygon {
             if let Some(start) = self.drag_start {
                 self.move_polygon(self.last_top_left, start, poly_id, window_size, device);
 
-                if let Some(path) = self.motion_paths.iter()
-                    .find(|p| p.source_polygon_id == poly_id) {
-                    self.move_path(
-                        self.last_top_left,
-                        start,
-                        path.id,
-                        window_size,
-                        device,
-                    );
+                if let Some(path) = self
+                    .motion_paths
+                    .iter()
+                    .find(|p| p.source_polygon_id == poly_id)
+                {
+                    self.move_path(self.last_top_left, start, path.id, window_size, device);
                 }
+
+                self.apply_drag_snapping(poly_id, ObjectType::Polygon, window_size);
             }
         }
 
@@ -5118,16 +6680,15 @@ impl Editor {
             if let Some(start) = self.drag_start {
                 self.move_text(self.last_top_left, start, text_id, window_size, device);
 
-                if let Some(path) = self.motion_paths.iter()
-                    .find(|p| p.source_polygon_id == text_id) {
-                    self.move_path(
-                        self.last_top_left,
-                        start,
-                        path.id,
-                        window_size,
-                        device,
-                    );
+                if let Some(path) = self
+                    .motion_paths
+                    .iter()
+                    .find(|p| p.source_polygon_id == text_id)
+                {
+                    self.move_path(self.last_top_left, start, path.id, window_size, device);
                 }
+
+                self.apply_drag_snapping(text_id, ObjectType::TextItem, window_size);
             }
         }
 
@@ -5135,16 +6696,15 @@ impl Editor {
             if let Some(start) = self.drag_start {
                 self.move_image(self.last_top_left, start, image_id, window_size, device);
 
-                if let Some(path) = self.motion_paths.iter()
-                    .find(|p| p.source_polygon_id == image_id) {
-                    self.move_path(
-                        self.last_top_left,
-                        start,
-                        path.id,
-                        window_size,
-                        device,
-                    );
+                if let Some(path) = self
+                    .motion_paths
+                    .iter()
+                    .find(|p| p.source_polygon_id == image_id)
+                {
+                    self.move_path(self.last_top_left, start, path.id, window_size, device);
                 }
+
+                self.apply_drag_snapping(image_id, ObjectType::ImageItem, window_size);
             }
         }
 
@@ -5152,19 +6712,51 @@ impl Editor {
             if let Some(start) = self.drag_start {
                 self.move_video(self.last_top_left, start, video_id, window_size, device);
 
-                if let Some(path) = self.motion_paths.iter()
-                    .find(|p| p.source_polygon_id == video_id) {
-                    self.move_path(
-                        self.last_top_left,
-                        start,
-                        path.id,
-                        window_size,
-                        device,
-                    );
+                if let Some(path) = self
+                    .motion_paths
+                    .iter()
+                    .find(|p| p.source_polygon_id == video_id)
+                {
+                    self.move_path(self.last_top_left, start, path.id, window_size, device);
                 }
+
+                self.apply_drag_snapping(video_id, ObjectType::VideoItem, window_size);
+            }
+        }
+
+        if let Some(group_id) = self.dragging_group {
+            if let Some(start) = self.drag_start {
+                let delta = Point {
+                    x: self.last_top_left.x - start.x,
+                    y: self.last_top_left.y - start.y,
+                };
+                self.translate_object(group_id, ObjectType::Group, delta, window_size);
+                self.drag_start = Some(self.last_top_left);
+
+                self.apply_drag_snapping(group_id, ObjectType::Group, window_size);
             }
         }
 
+        if let Some(emitter_id) = self.dragging_particle_emitter {
+            if let Some(start) = self.drag_start {
+                let delta = Point {
+                    x: self.last_top_left.x - start.x,
+                    y: self.last_top_left.y - start.y,
+                };
+                self.translate_object(emitter_id, ObjectType::ParticleEmitter, delta, window_size);
+                self.drag_start = Some(self.last_top_left);
+
+                self.apply_drag_snapping(emitter_id, ObjectType::ParticleEmitter, window_size);
+            }
+        }
+
+        // dragging out a text selection while editing
+        if self.text_editing.as_ref().map_or(false, |e| e.selecting) {
+            self.place_text_caret(self.last_top_left, true);
+        }
+
+        self.scene.sync_callouts();
+
         self.previous_top_left = self.last_top_left;
     }
 
@@ -5173,57 +6765,88 @@ impl Editor {
             return None;
         }
 
+        if let Some(editing) = self.text_editing.as_mut().filter(|e| e.selecting) {
+            editing.selecting = false;
+            return None;
+        }
+
         let action_edit = None;
 
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
+        let Some(camera) = self.scene.camera.as_ref() else {
+            return None;
+        };
 
         // Handle motion mode - complete motion arrow placement
         if self.motion_mode {
             if let Some(start_pos) = self.drag_start {
                 let end_pos = self.last_top_left;
-                
+
                 // Find object dimensions at start position
                 let mut object_dimensions: Option<(f32, f32)> = None;
                 let mut object_id = Uuid::nil();
                 let mut object_type = ObjectType::Polygon;
-                
+
                 // Check for objects at start position with enhanced detection for easier UX
-                for polygon in &self.polygons {
-                    if !polygon.hidden && polygon.contains_point_with_tolerance(&start_pos, &camera, 25.0) {
+                for polygon in &self.scene.polygons {
+                    if !polygon.hidden
+                        && polygon.contains_point_with_tolerance(&start_pos, &camera, 25.0)
+                    {
                         object_id = polygon.id;
-                        object_dimensions = Some((polygon.dimensions.0 as f32, polygon.dimensions.1 as f32));
+                        object_dimensions =
+                            Some((polygon.dimensions.0 as f32, polygon.dimensions.1 as f32));
                         object_type = ObjectType::Polygon;
                         break;
                     }
                 }
-                
+
                 if object_dimensions.is_none() {
-                    for text_item in &self.text_items {
-                        if !text_item.hidden && text_item.contains_point_with_tolerance(&start_pos, &camera, 25.0) {
+                    for text_item in &self.scene.text_items {
+                        if !text_item.hidden
+                            && text_item.contains_point_with_tolerance(&start_pos, &camera, 25.0)
+                        {
                             object_id = text_item.id;
-                            object_dimensions = Some((text_item.dimensions.0 as f32, text_item.dimensions.1 as f32));
+                            object_dimensions = Some((
+                                text_item.dimensions.0 as f32,
+                                text_item.dimensions.1 as f32,
+                            ));
                             object_type = ObjectType::TextItem;
                             break;
                         }
                     }
                 }
-                
+
                 if object_dimensions.is_none() {
-                    for image_item in &self.image_items {
-                        if !image_item.hidden && image_item.contains_point_with_tolerance(&start_pos, &camera, 25.0) {
-                            object_id = Uuid::from_str(&image_item.id).expect("Couldn't make uuid");
-                            object_dimensions = Some((image_item.dimensions.0 as f32, image_item.dimensions.1 as f32));
+                    for image_item in &self.scene.image_items {
+                        if !image_item.hidden
+                            && image_item.contains_point_with_tolerance(&start_pos, &camera, 25.0)
+                        {
+                            let Ok(id) = parse_id(&image_item.id) else {
+                                continue;
+                            };
+                            object_id = id;
+                            object_dimensions = Some((
+                                image_item.dimensions.0 as f32,
+                                image_item.dimensions.1 as f32,
+                            ));
                             object_type = ObjectType::ImageItem;
                             break;
                         }
                     }
                 }
-                
+
                 if object_dimensions.is_none() {
-                    for video_item in &self.video_items {
-                        if !video_item.hidden && video_item.contains_point_with_tolerance(&start_pos, &camera, 25.0) {
-                            object_id = Uuid::from_str(&video_item.id).expect("Couldn't make uuid");
-                            object_dimensions = Some((video_item.dimensions.0 as f32, video_item.dimensions.1 as f32));
+                    for video_item in &self.scene.video_items {
+                        if !video_item.hidden
+                            && video_item.contains_point_with_tolerance(&start_pos, &camera, 25.0)
+                        {
+                            let Ok(id) = parse_id(&video_item.id) else {
+                                continue;
+                            };
+                            object_id = id;
+                            object_dimensions = Some((
+                                video_item.dimensions.0 as f32,
+                                video_item.dimensions.1 as f32,
+                            ));
                             object_type = ObjectType::VideoItem;
                             break;
                         }
@@ -5234,19 +6857,25 @@ impl Editor {
                 self.last_motion_arrow_object_type = object_type;
                 self.last_motion_arrow_object_dimensions = object_dimensions;
                 self.last_motion_arrow_end_positions = Some((start_pos, end_pos));
-                
+
                 // Create motion arrow
-                if let (Some(gpu_resources), Some(camera)) = (&self.gpu_resources, &self.camera) {
-                    if let (Some(model_layout), Some(group_layout)) = 
-                        (&self.model_bind_group_layout, &self.group_bind_group_layout) {
+                if let (Some(gpu_resources), Some(camera)) =
+                    (&self.scene.gpu_resources, &self.scene.camera)
+                {
+                    if let (Some(model_layout), Some(group_layout)) = (
+                        &self.scene.model_bind_group_layout,
+                        &self.scene.group_bind_group_layout,
+                    ) {
                         let window_size = camera.window_size;
-                        
+
                         let arrow_id = Uuid::new_v4();
-                        let sequence_id = self.current_sequence_data
+                        let sequence_id = self
+                            .scene
+                            .current_sequence_data
                             .as_ref()
                             .map(|seq| Uuid::parse_str(&seq.id).unwrap_or(Uuid::nil()))
                             .unwrap_or(Uuid::nil());
-                        
+
                         let motion_arrow = MotionArrow::new(
                             &window_size,
                             &gpu_resources.device,
@@ -5266,15 +6895,17 @@ impl Editor {
                             arrow_id,
                             sequence_id,
                         );
-                        
+
                         self.motion_arrows.push(motion_arrow);
                         self.canvas_hidden = true;
                         self.motion_arrow_just_placed = true;
-                        println!("Motion arrow created from ({}, {}) to ({}, {})", 
-                            start_pos.x, start_pos.y, end_pos.x, end_pos.y);
+                        println!(
+                            "Motion arrow created from ({}, {}) to ({}, {})",
+                            start_pos.x, start_pos.y, end_pos.x, end_pos.y
+                        );
                     }
                 }
-                
+
                 self.motion_mode = false;
                 self.drag_start = None;
             }
@@ -5289,7 +6920,6 @@ impl Editor {
             return None;
         }
 
-
         // handle object on mouse up
         if let Some(poly_id) = self.dragging_polygon {
             self.sync_object_position_to_saved_data(poly_id, ObjectType::Polygon);
@@ -5301,15 +6931,29 @@ impl Editor {
         } else if let Some(video_id) = self.dragging_video {
             let uuid_video_id = video_id;
             self.sync_object_position_to_saved_data(uuid_video_id, ObjectType::VideoItem);
-
+        } else if let Some(group_id) = self.dragging_group {
+            self.sync_object_position_to_saved_data(group_id, ObjectType::Group);
+            self.save_callout_arrow(&group_id.to_string());
+        } else if let Some(emitter_id) = self.dragging_particle_emitter {
+            self.sync_object_position_to_saved_data(emitter_id, ObjectType::ParticleEmitter);
         } else if let Some(path_id) = self.dragging_path {
-
         } else if let Some(handle_id) = self.dragging_path_handle {
-
+        } else if self.dragging_path_tangent.is_some() {
+            self.save_current_sequence();
         } else if let Some(handle_id) = self.dragging_handle {
             // TODO: need self.sync_object_size_to_saved_date() use self.selected_object.object_id and object_type
             self.sync_object_size_to_saved_date();
-        } 
+
+            // anchored resizes move the object's center as well
+            if let Some(selected) = self.selected_object.clone() {
+                if selected.object_type != ObjectType::Group {
+                    self.sync_object_position_to_saved_data(
+                        selected.object_id,
+                        selected.object_type,
+                    );
+                }
+            }
+        }
 
         // if object_id != Uuid::nil() && active_point.is_some() {
         //     if let Some(on_mouse_up_creator) = &self.on_mouse_up {
@@ -5430,17 +7074,22 @@ impl Editor {
         self.dragging_text = None;
         self.dragging_image = None;
         self.dragging_video = None;
+        self.dragging_group = None;
+        self.dragging_particle_emitter = None;
         self.drag_start = None;
         self.dragging_path = None;
         self.dragging_path_assoc_path = None;
         self.dragging_path_handle = None;
         self.dragging_handle = None;
+        self.resize_start = None;
         self.dragging_path_object = None;
         self.dragging_path_keyframe = None;
+        self.dragging_path_tangent = None;
         self.is_panning = false;
 
         // self.dragging_edge = None;
-        // self.guide_lines.clear();
+        self.drag_snap_offset = Point { x: 0.0, y: 0.0 };
+        self.clear_guide_lines();
         // self.update_cursor();
 
         action_edit
@@ -5448,29 +7097,41 @@ impl Editor {
 
     pub fn sync_object_size_to_saved_date(&mut self) {
         // TODO: use self.selected_object.object_id and object_type
-        let selected_object = self.selected_object.as_ref().expect("Couldn't get selected object");
+        let selected_object = self
+            .selected_object
+            .as_ref()
+            .expect("Couldn't get selected object");
         let object_id = selected_object.object_id;
         let object_type = selected_object.object_type.clone();
-        let current_sequence_id = self.current_sequence_data.as_ref().expect("Couldn't get sequence data").id.clone();
+        let current_sequence_id = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .expect("Couldn't get sequence data")
+            .id
+            .clone();
 
         match object_type {
             ObjectType::Polygon => {
-                if let Some(polygon) = self.polygons.iter().find(|p| p.id == object_id) {
+                if let Some(polygon) = self.scene.polygons.iter().find(|p| p.id == object_id) {
                     // Update current_sequence_data
-                    if let Some(current_sequence) = &mut self.current_sequence_data {
-                        if let Some(saved_polygon) = current_sequence.active_polygons
+                    if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+                        if let Some(saved_polygon) = current_sequence
+                            .active_polygons
                             .iter_mut()
-                            .find(|p| p.id == object_id.to_string()) {
-                            saved_polygon.dimensions = (polygon.dimensions.0 as i32, polygon.dimensions.1 as i32);
+                            .find(|p| p.id == object_id.to_string())
+                        {
+                            saved_polygon.dimensions =
+                                (polygon.dimensions.0 as i32, polygon.dimensions.1 as i32);
                         }
                     }
-                    
+
                     // Update saved_state
                     if let Some(saved_state) = &mut self.saved_state {
                         for sequence in &mut saved_state.sequences {
                             if sequence.id == current_sequence_id {
                                 // Update the sequence with the current_sequence_data
-                                if let Some(current_sequence) = &self.current_sequence_data {
+                                if let Some(current_sequence) = &self.scene.current_sequence_data {
                                     *sequence = current_sequence.clone();
                                 }
                                 break;
@@ -5478,24 +7139,27 @@ impl Editor {
                         }
                     }
                 }
-            },
+            }
             ObjectType::TextItem => {
-                if let Some(text_item) = self.text_items.iter().find(|t| t.id == object_id) {
+                if let Some(text_item) = self.scene.text_items.iter().find(|t| t.id == object_id) {
                     // Update current_sequence_data
-                    if let Some(current_sequence) = &mut self.current_sequence_data {
-                        if let Some(saved_text) = current_sequence.active_text_items
+                    if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+                        if let Some(saved_text) = current_sequence
+                            .active_text_items
                             .iter_mut()
-                            .find(|t| t.id == object_id.to_string()) {
-                            saved_text.dimensions = (text_item.dimensions.0 as i32, text_item.dimensions.1 as i32);
+                            .find(|t| t.id == object_id.to_string())
+                        {
+                            saved_text.dimensions =
+                                (text_item.dimensions.0 as i32, text_item.dimensions.1 as i32);
                         }
                     }
-                    
+
                     // Update saved_state
                     if let Some(saved_state) = &mut self.saved_state {
                         for sequence in &mut saved_state.sequences {
                             if sequence.id == current_sequence_id {
                                 // Update the sequence with the current_sequence_data
-                                if let Some(current_sequence) = &self.current_sequence_data {
+                                if let Some(current_sequence) = &self.scene.current_sequence_data {
                                     *sequence = current_sequence.clone();
                                 }
                                 break;
@@ -5503,70 +7167,127 @@ impl Editor {
                         }
                     }
                 }
-            },
+            }
             ObjectType::ImageItem => {
-                if let Some(image_item) = self.image_items.iter().find(|i| i.id == object_id.to_string()) {
+                if let Some(image_item) = self
+                    .scene
+                    .image_items
+                    .iter()
+                    .find(|i| i.id == object_id.to_string())
+                {
                     // Update current_sequence_data
-                    if let Some(current_sequence) = &mut self.current_sequence_data {
-                        if let Some(saved_image) = current_sequence.active_image_items
+                    if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+                        if let Some(saved_image) = current_sequence
+                            .active_image_items
                             .iter_mut()
-                            .find(|i| i.id == object_id.to_string()) {
-                            saved_image.dimensions = (image_item.transform.scale.x as u32, image_item.transform.scale.y as u32);
+                            .find(|i| i.id == object_id.to_string())
+                        {
+                            saved_image.dimensions = (
+                                image_item.transform.scale.x as u32,
+                                image_item.transform.scale.y as u32,
+                            );
                         }
                     }
-                    
+
                     // Update saved_state
                     if let Some(saved_state) = &mut self.saved_state {
                         for sequence in &mut saved_state.sequences {
                             if sequence.id == current_sequence_id {
                                 // Update the sequence with the current_sequence_data
-                                if let Some(current_sequence) = &self.current_sequence_data {
+                                if let Some(current_sequence) = &self.scene.current_sequence_data {
                                     *sequence = current_sequence.clone();
                                 }
                                 break;
                             }
                         }
                     }
-
                 }
-            },
+            }
             ObjectType::VideoItem => {
-                if let Some(video_item) = self.video_items.iter().find(|v| v.id == object_id.to_string()) {
+                if let Some(video_item) = self
+                    .scene
+                    .video_items
+                    .iter()
+                    .find(|v| v.id == object_id.to_string())
+                {
                     // Update current_sequence_data
-                    if let Some(current_sequence) = &mut self.current_sequence_data {
-                        if let Some(saved_video) = current_sequence.active_video_items
+                    if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+                        if let Some(saved_video) = current_sequence
+                            .active_video_items
                             .iter_mut()
-                            .find(|v| v.id == object_id.to_string()) {
-                            saved_video.dimensions = (video_item.transform.scale.x as u32, video_item.transform.scale.y as u32);
-
+                            .find(|v| v.id == object_id.to_string())
+                        {
+                            saved_video.dimensions = (
+                                video_item.transform.scale.x as u32,
+                                video_item.transform.scale.y as u32,
+                            );
                         }
                     }
-                    
+
                     // Update saved_state
                     if let Some(saved_state) = &mut self.saved_state {
                         for sequence in &mut saved_state.sequences {
                             if sequence.id == current_sequence_id {
                                 // Update the sequence with the current_sequence_data
-                                if let Some(current_sequence) = &self.current_sequence_data {
+                                if let Some(current_sequence) = &self.scene.current_sequence_data {
                                     *sequence = current_sequence.clone();
                                 }
                                 break;
                             }
                         }
                     }
+                }
+            }
+            ObjectType::Group => {
+                // groups have no dimensions of their own, only a scale
+            }
+            ObjectType::ParticleEmitter => {
+                if let Some(emitter) = self
+                    .scene
+                    .particle_emitters
+                    .iter()
+                    .find(|e| e.id == object_id)
+                {
+                    // Update current_sequence_data
+                    if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+                        if let Some(saved_emitter) = current_sequence
+                            .active_particle_emitters
+                            .iter_mut()
+                            .find(|e| e.id == object_id.to_string())
+                        {
+                            saved_emitter.dimensions = emitter.config.dimensions;
+                        }
+                    }
 
+                    // Update saved_state
+                    if let Some(saved_state) = &mut self.saved_state {
+                        for sequence in &mut saved_state.sequences {
+                            if sequence.id == current_sequence_id {
+                                // Update the sequence with the current_sequence_data
+                                if let Some(current_sequence) = &self.scene.current_sequence_data {
+                                    *sequence = current_sequence.clone();
+                                }
+                                break;
+                            }
+                        }
+                    }
                 }
-            },
+            }
         }
+
+        self.events.emit(EditorEvent::ObjectResized {
+            object_id,
+            object_type,
+        });
     }
 
     pub fn reset_bounds(&mut self, window_size: &WindowSize) {
-        let camera = self.camera.as_mut().expect("Couldn't get camera");
+        let camera = self.scene.camera.as_mut().expect("Couldn't get camera");
 
         // camera.position = Vector2::new(0.0, 0.0);
         camera.position = Vector3::new(0.0, 0.0, 0.0);
         // camera.zoom = 1.0;
-        self.update_camera_binding();
+        self.scene.update_camera_binding();
         self.interactive_bounds = BoundingBox {
             min: Point { x: 550.0, y: 0.0 }, // account for aside width, allow for some off-canvas positioning
             max: Point {
@@ -5585,13 +7306,11 @@ impl Editor {
         window_size: &WindowSize,
         device: &wgpu::Device,
     ) {
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
+        let camera = self.scene.camera.as_ref().expect("Couldn't get camera");
         let aspect_ratio = camera.window_size.width as f32 / camera.window_size.height as f32;
         let dx = mouse_pos.x - start.x;
         let dy = mouse_pos.y - start.y;
 
-        
-        
         let bounding_box = match self.get_object_bounding_box(poly_id, &ObjectType::Polygon) {
             Some(bbox) => bbox,
             None => return,
@@ -5614,9 +7333,8 @@ impl Editor {
             .map(|position| (*position, self.get_handle_position(&bounding_box, position)))
             .collect();
 
-        
-
         let polygon = self
+            .scene
             .polygons
             .iter_mut()
             .find(|p| p.id == poly_id)
@@ -5632,22 +7350,22 @@ impl Editor {
         polygon.update_data_from_position(
             window_size,
             device,
-            self.model_bind_group_layout
+            self.scene
+                .model_bind_group_layout
                 .as_ref()
                 .expect("Couldn't get bind group layout"),
             new_position,
             &camera,
         );
 
-        
-
         // Step 2: Update transforms using the collected centers
         for (position, handle_center) in handle_centers {
-            if let Some(handle) = self.resize_handles.iter_mut().find(|h| 
-                h.object_id == polygon.id && h.position == position
-            ) {
-                // handle.polygon.transform.position = handle_center;
-                handle.polygon.transform.update_position([handle_center.x, handle_center.y], &camera.window_size);
+            if let Some(handle) = self
+                .resize_handles
+                .iter_mut()
+                .find(|h| h.object_id == polygon.id && h.position == position)
+            {
+                handle.gizmo.position = handle_center;
             }
         }
 
@@ -5663,11 +7381,12 @@ impl Editor {
         window_size: &WindowSize,
         device: &wgpu::Device,
     ) {
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
+        let camera = self.scene.camera.as_ref().expect("Couldn't get camera");
         let aspect_ratio = camera.window_size.width as f32 / camera.window_size.height as f32;
         let dx = mouse_pos.x - start.x;
         let dy = mouse_pos.y - start.y;
         let polygon = self
+            .scene
             .static_polygons
             .iter_mut()
             .find(|p| p.id == poly_id)
@@ -5683,7 +7402,8 @@ impl Editor {
         polygon.update_data_from_position(
             window_size,
             device,
-            self.model_bind_group_layout
+            self.scene
+                .model_bind_group_layout
                 .as_ref()
                 .expect("Couldn't get bind group layout"),
             new_position,
@@ -5703,7 +7423,7 @@ impl Editor {
         window_size: &WindowSize,
         device: &wgpu::Device,
     ) {
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
+        let camera = self.scene.camera.as_ref().expect("Couldn't get camera");
         let aspect_ratio = camera.window_size.width as f32 / camera.window_size.height as f32;
         let dx = mouse_pos.x - start.x;
         let dy = mouse_pos.y - start.y;
@@ -5728,7 +7448,8 @@ impl Editor {
         polygon.update_data_from_position(
             window_size,
             device,
-            self.model_bind_group_layout
+            self.scene
+                .model_bind_group_layout
                 .as_ref()
                 .expect("Couldn't get bind group layout"),
             new_position,
@@ -5748,7 +7469,7 @@ impl Editor {
         device: &wgpu::Device,
     ) {
         // println!("move_path {:?} {:?}", self.dragging_path_handle, self.dragging_polygon);
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
+        let camera = self.scene.camera.as_ref().expect("Couldn't get camera");
         let aspect_ratio = camera.window_size.width as f32 / camera.window_size.height as f32;
         let dx = mouse_pos.x - start.x;
         let dy = mouse_pos.y - start.y;
@@ -5768,7 +7489,8 @@ impl Editor {
         path.update_data_from_position(
             window_size,
             device,
-            self.model_bind_group_layout
+            self.scene
+                .model_bind_group_layout
                 .as_ref()
                 .expect("Couldn't get bind group layout"),
             new_position,
@@ -5787,7 +7509,7 @@ impl Editor {
         window_size: &WindowSize,
         device: &wgpu::Device,
     ) {
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
+        let camera = self.scene.camera.as_ref().expect("Couldn't get camera");
         let aspect_ratio = camera.window_size.width as f32 / camera.window_size.height as f32;
         let dx = mouse_pos.x - start.x;
         let dy = mouse_pos.y - start.y;
@@ -5814,10 +7536,9 @@ impl Editor {
             .map(|position| (*position, self.get_handle_position(&bounding_box, position)))
             .collect();
 
-
-
-        // let text_item = &mut self.text_items[text_index];
+        // let text_item = &mut self.scene.text_items[text_index];
         let text_item = self
+            .scene
             .text_items
             .iter_mut()
             .find(|t| t.id == text_id)
@@ -5837,13 +7558,14 @@ impl Editor {
             .transform
             .update_position([new_position.x, new_position.y], window_size);
 
-            // Step 2: Update transforms using the collected centers
+        // Step 2: Update transforms using the collected centers
         for (position, handle_center) in handle_centers {
-            if let Some(handle) = self.resize_handles.iter_mut().find(|h| 
-                h.object_id == text_item.id && h.position == position
-            ) {
-                // handle.polygon.transform.position = handle_center;
-                handle.polygon.transform.update_position([handle_center.x, handle_center.y], &camera.window_size);
+            if let Some(handle) = self
+                .resize_handles
+                .iter_mut()
+                .find(|h| h.object_id == text_item.id && h.position == position)
+            {
+                handle.gizmo.position = handle_center;
             }
         }
 
@@ -5859,7 +7581,7 @@ impl Editor {
         window_size: &WindowSize,
         device: &wgpu::Device,
     ) {
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
+        let camera = self.scene.camera.as_ref().expect("Couldn't get camera");
         let aspect_ratio = camera.window_size.width as f32 / camera.window_size.height as f32;
         let dx = mouse_pos.x - start.x;
         let dy = mouse_pos.y - start.y;
@@ -5885,10 +7607,10 @@ impl Editor {
             .iter()
             .map(|position| (*position, self.get_handle_position(&bounding_box, position)))
             .collect();
-    
 
-        // let image_item = &mut self.image_items[image_index];
+        // let image_item = &mut self.scene.image_items[image_index];
         let image_item = self
+            .scene
             .image_items
             .iter_mut()
             .find(|i| i.id == image_id.to_string())
@@ -5904,14 +7626,14 @@ impl Editor {
             .transform
             .update_position([new_position.x, new_position.y], window_size);
 
-
-            // Step 2: Update transforms using the collected centers
+        // Step 2: Update transforms using the collected centers
         for (position, handle_center) in handle_centers {
-            if let Some(handle) = self.resize_handles.iter_mut().find(|h| 
-                h.object_id.to_string() == image_item.id && h.position == position
-            ) {
-                // handle.polygon.transform.position = handle_center;
-                handle.polygon.transform.update_position([handle_center.x, handle_center.y], &camera.window_size);
+            if let Some(handle) = self
+                .resize_handles
+                .iter_mut()
+                .find(|h| h.object_id.to_string() == image_item.id && h.position == position)
+            {
+                handle.gizmo.position = handle_center;
             }
         }
 
@@ -5927,7 +7649,7 @@ impl Editor {
         window_size: &WindowSize,
         device: &wgpu::Device,
     ) {
-        let camera = self.camera.as_ref().expect("Couldn't get camera");
+        let camera = self.scene.camera.as_ref().expect("Couldn't get camera");
         let aspect_ratio = camera.window_size.width as f32 / camera.window_size.height as f32;
         let dx = mouse_pos.x - start.x;
         let dy = mouse_pos.y - start.y;
@@ -5951,287 +7673,5209 @@ impl Editor {
         // Step 1: Collect handle centers for each position
         let handle_centers: Vec<(HandlePosition, _)> = handle_positions
             .iter()
-            .map(|position| (*position, self.get_handle_position(&bounding_box, position)))
-            .collect();
+            .map(|position| (*position, self.get_handle_position(&bounding_box, position)))
+            .collect();
+
+        // let image_item = &mut self.scene.image_items[image_index];
+        let video_item = self
+            .scene
+            .video_items
+            .iter_mut()
+            .find(|i| i.id == video_id.to_string())
+            .expect("Couldn't find video item");
+        let new_position = Point {
+            x: video_item.transform.position.x + (dx * 0.9), // not sure relation with aspect_ratio?
+            y: video_item.transform.position.y + dy,
+        };
+
+        // println!("move_video {:?}", new_position);
+
+        video_item
+            .transform
+            .update_position([new_position.x, new_position.y], window_size);
+
+        // Step 2: Update transforms using the collected centers
+        for (position, handle_center) in handle_centers {
+            if let Some(handle) = self
+                .resize_handles
+                .iter_mut()
+                .find(|h| h.object_id.to_string() == video_item.id && h.position == position)
+            {
+                handle.gizmo.position = handle_center;
+            }
+        }
+
+        self.drag_start = Some(mouse_pos);
+        // self.update_guide_lines(poly_index, window_size);
+    }
+
+    /// Snaps the dragged object to the canvas, other objects and optionally the grid, showing guide lines for any alignment.
+    /// The previous snap correction is undone first so the object doesn't stick once the mouse moves away.
+    pub fn apply_drag_snapping(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+        window_size: &WindowSize,
+    ) {
+        if !self.snap_settings.enabled && !self.grid_settings.snap_to_grid {
+            return;
+        }
+
+        let bounding_box = match self.get_object_bounding_box(object_id, &object_type) {
+            Some(bbox) => bbox,
+            None => return,
+        };
+
+        // position the mouse alone would have produced
+        let unsnapped = BoundingBox {
+            min: Point {
+                x: bounding_box.min.x - self.drag_snap_offset.x,
+                y: bounding_box.min.y - self.drag_snap_offset.y,
+            },
+            max: Point {
+                x: bounding_box.max.x - self.drag_snap_offset.x,
+                y: bounding_box.max.y - self.drag_snap_offset.y,
+            },
+        };
+
+        let canvas = self.scene.get_canvas_bounding_box();
+
+        let mut snap = if self.snap_settings.enabled {
+            let targets = self.get_snap_targets(object_id);
+            snap_bounding_box(
+                &unsnapped,
+                &targets,
+                canvas.as_ref(),
+                self.snap_settings.threshold,
+            )
+        } else {
+            SnapResult {
+                offset: Point { x: 0.0, y: 0.0 },
+                guide_lines: Vec::new(),
+                snapped_x: false,
+                snapped_y: false,
+            }
+        };
+
+        // smart guides win, the grid quantizes whichever axes they left alone
+        if self.grid_settings.snap_to_grid {
+            if let Some(canvas) = &canvas {
+                let grid_offset = grid_snap_offset(&unsnapped, canvas, self.grid_settings.spacing);
+                if !snap.snapped_x {
+                    snap.offset.x = grid_offset.x;
+                }
+                if !snap.snapped_y {
+                    snap.offset.y = grid_offset.y;
+                }
+            }
+        }
+
+        let correction = Point {
+            x: snap.offset.x - self.drag_snap_offset.x,
+            y: snap.offset.y - self.drag_snap_offset.y,
+        };
+
+        self.translate_object(object_id, object_type, correction, window_size);
+        self.drag_snap_offset = snap.offset;
+
+        self.guide_lines = snap.guide_lines;
+        self.update_guide_line_polygons();
+    }
+
+    /// Bounding boxes of all visible objects other than the one being dragged
+    fn get_snap_targets(&self, exclude_id: Uuid) -> Vec<BoundingBox> {
+        let mut targets = Vec::new();
+
+        for polygon in self
+            .scene
+            .polygons
+            .iter()
+            .filter(|p| !p.hidden && p.id != exclude_id)
+        {
+            if let Some(bbox) = self.get_object_bounding_box(polygon.id, &ObjectType::Polygon) {
+                targets.push(bbox);
+            }
+        }
+
+        for text_item in self
+            .scene
+            .text_items
+            .iter()
+            .filter(|t| !t.hidden && t.id != exclude_id)
+        {
+            if let Some(bbox) = self.get_object_bounding_box(text_item.id, &ObjectType::TextItem) {
+                targets.push(bbox);
+            }
+        }
+
+        for image_item in self
+            .scene
+            .image_items
+            .iter()
+            .filter(|i| !i.hidden && i.id != exclude_id.to_string())
+        {
+            if let Ok(image_id) = Uuid::from_str(&image_item.id) {
+                if let Some(bbox) = self.get_object_bounding_box(image_id, &ObjectType::ImageItem) {
+                    targets.push(bbox);
+                }
+            }
+        }
+
+        for video_item in self
+            .scene
+            .video_items
+            .iter()
+            .filter(|v| !v.hidden && v.id != exclude_id.to_string())
+        {
+            if let Ok(video_id) = Uuid::from_str(&video_item.id) {
+                if let Some(bbox) = self.get_object_bounding_box(video_id, &ObjectType::VideoItem) {
+                    targets.push(bbox);
+                }
+            }
+        }
+
+        targets
+    }
+
+    /// Changes the project's canvas size. With rescale_content, objects and motion paths
+    /// in every sequence move and scale to keep their place on the canvas, otherwise they
+    /// stay where they are. New sizes of objects in the current sequence show once it's
+    /// restored again, positions update right away.
+    pub fn set_canvas_size(
+        &mut self,
+        canvas_size: CanvasSize,
+        rescale_content: bool,
+    ) -> Result<(), String> {
+        if canvas_size.width == 0 || canvas_size.height == 0 {
+            return Err("Canvas size must not be zero".to_string());
+        }
+
+        let previous = self.scene.canvas_size;
+        self.scene.canvas_size = canvas_size;
+        if let Some(camera) = self.scene.camera.as_ref() {
+            self.interactive_bounds = canvas_interactive_bounds(canvas_size, &camera.window_size);
+        }
+
+        if rescale_content {
+            if let Some(sequence) = self.scene.current_sequence_data.as_mut() {
+                rescale_sequence(sequence, previous, canvas_size);
+            }
+        }
+
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        saved_state.canvas_size = canvas_size;
+        if rescale_content {
+            saved_state
+                .sequences
+                .iter_mut()
+                .for_each(|sequence| rescale_sequence(sequence, previous, canvas_size));
+        }
+        save_saved_state_raw(saved_state.clone())?;
+
+        let Some(sequence) = self.scene.current_sequence_data.clone() else {
+            return Ok(());
+        };
+
+        if rescale_content {
+            self.reset_sequence_objects();
+        }
+
+        let fill = self
+            .scene
+            .static_polygons
+            .iter()
+            .find(|p| p.name == "canvas_background")
+            .map(|p| p.fill);
+        if let Some(fill) = fill {
+            let sequence_id =
+                Uuid::from_str(&sequence.id).map_err(|e| format!("Invalid sequence id: {}", e))?;
+            self.replace_background(sequence_id, fill)?;
+        }
+
+        Ok(())
+    }
+
+    /// Pins an object to follow another at an offset, by default the offset between them now.
+    /// Replaces any pin the object already has. Pins are applied when the sequence plays or
+    /// seeks, after keyframes.
+    pub fn pin_object(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+        target_id: Uuid,
+        target_type: ObjectType,
+        offset: Option<[i32; 2]>,
+    ) -> Result<(), String> {
+        let sequence = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .ok_or("No sequence selected")?;
+        let (object_key, target_key) = (object_id.to_string(), target_id.to_string());
+        let other_pins: Vec<PinConstraint> = sequence
+            .constraints
+            .iter()
+            .filter(|c| c.object_id != object_key)
+            .cloned()
+            .collect();
+        if would_cycle(&other_pins, &object_key, &target_key) {
+            return Err("Pinning these objects would make a loop".to_string());
+        }
+
+        let offset = match offset {
+            Some(offset) => offset,
+            None => {
+                let object_position = self
+                    .scene
+                    .object_position(object_id, &object_type)
+                    .ok_or("Couldn't find the object to pin")?;
+                let target_position = self
+                    .scene
+                    .object_position(target_id, &target_type)
+                    .ok_or("Couldn't find the object to pin to")?;
+                [
+                    (object_position.x - target_position.x).round() as i32,
+                    (object_position.y - target_position.y).round() as i32,
+                ]
+            }
+        };
+
+        let constraint = PinConstraint {
+            id: Uuid::new_v4().to_string(),
+            object_id: object_key.clone(),
+            object_type,
+            target_id: target_key,
+            target_type,
+            offset,
+        };
+        self.edit_constraints(|constraints| {
+            constraints.retain(|c| c.object_id != object_key);
+            constraints.push(constraint);
+        })?;
+        self.scene.apply_pin_constraints();
+
+        Ok(())
+    }
+
+    /// Lets a pinned object move on its own again
+    pub fn unpin_object(&mut self, object_id: Uuid) -> Result<(), String> {
+        let object_key = object_id.to_string();
+        self.edit_constraints(|constraints| constraints.retain(|c| c.object_id != object_key))
+    }
+
+    fn edit_constraints(
+        &mut self,
+        edit: impl FnOnce(&mut Vec<PinConstraint>),
+    ) -> Result<(), String> {
+        let sequence = self
+            .scene
+            .current_sequence_data
+            .as_mut()
+            .ok_or("No sequence selected")?;
+        edit(&mut sequence.constraints);
+        self.save_current_sequence();
+
+        Ok(())
+    }
+
+    /// Shifts an object along with its resize handles and motion path
+    fn translate_object(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+        delta: Point,
+        window_size: &WindowSize,
+    ) {
+        if delta.x == 0.0 && delta.y == 0.0 {
+            return;
+        }
+
+        match object_type {
+            ObjectType::Polygon => {
+                if let Some(polygon) = self.scene.polygons.iter_mut().find(|p| p.id == object_id) {
+                    let new_position = [
+                        polygon.transform.position.x + delta.x,
+                        polygon.transform.position.y + delta.y,
+                    ];
+                    polygon.transform.update_position(new_position, window_size);
+                }
+            }
+            ObjectType::TextItem => {
+                if let Some(text_item) =
+                    self.scene.text_items.iter_mut().find(|t| t.id == object_id)
+                {
+                    let new_position = [
+                        text_item.transform.position.x + delta.x,
+                        text_item.transform.position.y + delta.y,
+                    ];
+                    text_item
+                        .transform
+                        .update_position(new_position, window_size);
+                    text_item
+                        .background_polygon
+                        .transform
+                        .update_position(new_position, window_size);
+                }
+            }
+            ObjectType::ImageItem => {
+                if let Some(image_item) = self
+                    .scene
+                    .image_items
+                    .iter_mut()
+                    .find(|i| i.id == object_id.to_string())
+                {
+                    let new_position = [
+                        image_item.transform.position.x + delta.x,
+                        image_item.transform.position.y + delta.y,
+                    ];
+                    image_item
+                        .transform
+                        .update_position(new_position, window_size);
+                }
+            }
+            ObjectType::VideoItem => {
+                if let Some(video_item) = self
+                    .scene
+                    .video_items
+                    .iter_mut()
+                    .find(|v| v.id == object_id.to_string())
+                {
+                    let new_position = [
+                        video_item.transform.position.x + delta.x,
+                        video_item.transform.position.y + delta.y,
+                    ];
+                    video_item
+                        .transform
+                        .update_position(new_position, window_size);
+                }
+            }
+            ObjectType::Group => {
+                if let Some(group) = self.scene.groups.iter_mut().find(|g| g.id == object_id) {
+                    let new_position = [
+                        group.transform.position.x + delta.x,
+                        group.transform.position.y + delta.y,
+                    ];
+                    group.transform.update_position(new_position, window_size);
+                    if let Some(gpu_resources) = &self.scene.gpu_resources {
+                        group.update_uniform_buffer(&gpu_resources.queue);
+                    }
+                }
+            }
+            ObjectType::ParticleEmitter => {
+                if let Some(emitter) = self
+                    .scene
+                    .particle_emitters
+                    .iter_mut()
+                    .find(|e| e.id == object_id)
+                {
+                    let new_position = [
+                        emitter.transform.position.x + delta.x,
+                        emitter.transform.position.y + delta.y,
+                    ];
+                    emitter.transform.update_position(new_position, window_size);
+                }
+            }
+        }
+
+        for handle in self
+            .resize_handles
+            .iter_mut()
+            .filter(|h| h.object_id == object_id)
+        {
+            handle.gizmo.position.x += delta.x;
+            handle.gizmo.position.y += delta.y;
+        }
+
+        // paths are only drawn once rendering is set up
+        let Ok(resources) = self.scene.object_resources() else {
+            return;
+        };
+        if let Some(path) = self
+            .motion_paths
+            .iter_mut()
+            .find(|p| p.source_polygon_id == object_id)
+        {
+            let new_position = Point {
+                x: path.transform.position.x + delta.x,
+                y: path.transform.position.y + delta.y,
+            };
+            path.update_data_from_position(
+                window_size,
+                &resources.gpu_resources.device,
+                &resources.model_bind_group_layout,
+                new_position,
+                &resources.camera,
+            );
+        }
+    }
+
+    /// Moves the selected object and its motion path by a distance in canvas pixels, for
+    /// arrow key nudging. With grid snapping on, each nudge steps to the next grid line
+    /// along that axis instead.
+    pub fn nudge_selected(&mut self, dx: f32, dy: f32) -> Result<(), String> {
+        let (object_id, object_type) = self.selected_for_transform()?;
+
+        let mut delta = Point { x: dx, y: dy };
+        if self.grid_settings.snap_to_grid {
+            let bounding_box = self.get_object_bounding_box(object_id, &object_type);
+            if let (Some(bounding_box), Some(canvas)) =
+                (bounding_box, self.scene.get_canvas_bounding_box())
+            {
+                let spacing = self.grid_settings.spacing;
+                delta = Point {
+                    x: next_grid_value(bounding_box.min.x, canvas.min.x, spacing, dx)
+                        - bounding_box.min.x,
+                    y: next_grid_value(bounding_box.min.y, canvas.min.y, spacing, dy)
+                        - bounding_box.min.y,
+                };
+            }
+        }
+
+        self.move_object_by(object_id, object_type, delta)
+    }
+
+    /// Moves the selected object so its center is at a canvas position, onto the nearest
+    /// grid line when snapping to the grid
+    pub fn set_position(&mut self, x: f32, y: f32) -> Result<(), String> {
+        let (object_id, object_type) = self.selected_for_transform()?;
+        let bounding_box = self
+            .get_object_bounding_box(object_id, &object_type)
+            .ok_or_else(|| "Couldn't find the selected object".to_string())?;
+
+        let center = Point {
+            x: (bounding_box.min.x + bounding_box.max.x) / 2.0,
+            y: (bounding_box.min.y + bounding_box.max.y) / 2.0,
+        };
+        let mut delta = Point {
+            x: x - center.x,
+            y: y - center.y,
+        };
+        if self.grid_settings.snap_to_grid {
+            if let Some(canvas) = self.scene.get_canvas_bounding_box() {
+                let moved = BoundingBox {
+                    min: Point {
+                        x: bounding_box.min.x + delta.x,
+                        y: bounding_box.min.y + delta.y,
+                    },
+                    max: Point {
+                        x: bounding_box.max.x + delta.x,
+                        y: bounding_box.max.y + delta.y,
+                    },
+                };
+                let offset = grid_snap_offset(&moved, &canvas, self.grid_settings.spacing);
+                delta.x += offset.x;
+                delta.y += offset.y;
+            }
+        }
+
+        self.move_object_by(object_id, object_type, delta)
+    }
+
+    /// Sets the selected object's rotation in degrees at the playhead, replacing its
+    /// Rotation keyframe there or adding one
+    pub fn set_rotation(&mut self, degrees: i32) -> Result<(), String> {
+        self.set_selected_keyframe("rotation", KeyframeValue::Rotation(degrees))
+    }
+
+    /// Sets the selected object's scale, out of 100, at the playhead, replacing its Scale
+    /// keyframe there or adding one
+    pub fn set_scale(&mut self, scale: i32) -> Result<(), String> {
+        self.set_selected_keyframe("scale", KeyframeValue::Scale(scale.max(1)))
+    }
+
+    /// Sets the selected object's width and height scales, out of 100, at the playhead, for
+    /// squashing and stretching it
+    pub fn set_scale_xy(&mut self, scale: [i32; 2]) -> Result<(), String> {
+        self.set_selected_keyframe("scale", KeyframeValue::ScaleXY(scale.map(|s| s.max(1))))
+    }
+
+    /// The selected object, when it can be transformed
+    fn selected_for_transform(&self) -> Result<(Uuid, ObjectType), String> {
+        let selected = self
+            .selected_object
+            .as_ref()
+            .ok_or_else(|| "No object selected".to_string())?;
+        if self.scene.current_sequence_data.is_none() {
+            return Err("No sequence selected".to_string());
+        }
+
+        Ok((selected.object_id, selected.object_type.clone()))
+    }
+
+    /// Moves an object like a drag would, saving its position and regenerating its motion path
+    fn move_object_by(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+        delta: Point,
+    ) -> Result<(), String> {
+        let window_size = self
+            .scene
+            .camera
+            .as_ref()
+            .ok_or_else(|| "Couldn't get camera".to_string())?
+            .window_size;
+
+        self.translate_object(object_id, object_type.clone(), delta, &window_size);
+        self.sync_object_position_to_saved_data(object_id, object_type.clone());
+        if object_type == ObjectType::Group {
+            self.scene.sync_callouts();
+            self.save_callout_arrow(&object_id.to_string());
+        }
+
+        if let Some(sequence) = self.scene.current_sequence_data.clone() {
+            self.update_motion_paths(&sequence);
+        }
+
+        Ok(())
+    }
+
+    /// Writes a value into the selected object's keyframes at the playhead, then shows it
+    fn set_selected_keyframe(
+        &mut self,
+        property_path: &str,
+        value: KeyframeValue,
+    ) -> Result<(), String> {
+        let (object_id, _) = self.selected_for_transform()?;
+        let object_id = object_id.to_string();
+        let playhead_ms = (self.scene.last_playhead_s * 1000.0) as i32;
+
+        let current_sequence = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .ok_or_else(|| "No sequence selected".to_string())?;
+        let current_sequence_id = current_sequence.id.clone();
+        let animation = current_sequence
+            .polygon_motion_paths
+            .iter()
+            .find(|a| a.polygon_id == object_id)
+            .ok_or_else(|| "The selected object has no animation".to_string())?;
+        if !animation
+            .properties
+            .iter()
+            .any(|p| p.property_path == property_path)
+        {
+            return Err(format!(
+                "The selected object has no {} property",
+                property_path
+            ));
+        }
+
+        // keyframe times are from the animation's start
+        let time = Duration::from_millis(
+            (playhead_ms - animation.start_time_ms).clamp(0, animation.duration.as_millis() as i32)
+                as u64,
+        );
+
+        let apply = |sequence: &mut Sequence| {
+            if sequence.id != current_sequence_id {
+                return;
+            }
+            let properties = sequence
+                .polygon_motion_paths
+                .iter_mut()
+                .filter(|a| a.polygon_id == object_id)
+                .flat_map(|a| a.properties.iter_mut())
+                .filter(|p| p.property_path == property_path);
+            for property in properties {
+                if let Some(keyframe) = property.keyframes.iter_mut().find(|k| k.time == time) {
+                    keyframe.value = value.clone();
+                    continue;
+                }
+
+                // eases like the keyframe before it
+                let previous = property
+                    .keyframes
+                    .iter()
+                    .filter(|k| k.time < time)
+                    .max_by_key(|k| k.time)
+                    .or_else(|| property.keyframes.first());
+                property.keyframes.push(UIKeyframe {
+                    id: Uuid::new_v4().to_string(),
+                    time,
+                    value: value.clone(),
+                    easing: previous.map_or(EasingType::EaseInOut, |k| k.easing.clone()),
+                    path_type: PathType::Linear,
+                    key_type: KeyType::Frame,
+                });
+                property.keyframes.sort_by_key(|k| k.time);
+            }
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        self.seek_to(playhead_ms)
+    }
+
+    /// Rebuilds the thin static polygons used to draw the current guide lines
+    fn update_guide_line_polygons(&mut self) {
+        self.scene
+            .static_polygons
+            .retain(|p| p.name != "guide_line");
+
+        if self.guide_lines.is_empty() {
+            return;
+        }
+
+        let Ok(resources) = self.scene.object_resources() else {
+            return;
+        };
+        let camera = &resources.camera;
+        let window_size = camera.window_size;
+        let gpu_resources = &resources.gpu_resources;
+        let model_bind_group_layout = &resources.model_bind_group_layout;
+        let group_bind_group_layout = &resources.group_bind_group_layout;
+
+        for guide_line in &self.guide_lines {
+            let guide_polygon = create_line_polygon(
+                &window_size,
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                model_bind_group_layout,
+                group_bind_group_layout,
+                camera,
+                guide_line,
+                1.0,
+                rgb_to_wgpu(255, 0, 200, 255.0), // magenta, like most design tools
+                100,                             // high z-layer to render on top
+                "guide_line".to_string(),
+            );
+
+            self.scene.static_polygons.push(guide_polygon);
+        }
+    }
+
+    /// Applies new grid settings and rebuilds the grid and ruler overlay
+    pub fn set_grid_settings(&mut self, grid_settings: GridSettings) -> Result<(), EditorError> {
+        self.grid_settings = grid_settings;
+        self.update_grid_polygons()
+    }
+
+    /// Rebuilds the grid lines and rulers as static geometry around the canvas
+    pub fn update_grid_polygons(&mut self) -> Result<(), EditorError> {
+        self.scene.static_polygons.retain(|p| {
+            p.name != "grid_line" && p.name != "ruler_background" && p.name != "ruler_tick"
+        });
+
+        if !self.grid_settings.visible && !self.grid_settings.show_rulers {
+            return Ok(());
+        }
+
+        let canvas = match self.scene.get_canvas_bounding_box() {
+            Some(canvas) => canvas,
+            None => return Ok(()),
+        };
+
+        let resources = self.scene.object_resources()?;
+        let camera = &resources.camera;
+        let window_size = camera.window_size;
+        let gpu_resources = &resources.gpu_resources;
+        let model_bind_group_layout = &resources.model_bind_group_layout;
+        let group_bind_group_layout = &resources.group_bind_group_layout;
+
+        let mut overlay_polygons = Vec::new();
+
+        if self.grid_settings.visible {
+            for (line, is_major) in grid_lines(&canvas, &self.grid_settings) {
+                overlay_polygons.push(create_line_polygon(
+                    &window_size,
+                    &gpu_resources.device,
+                    &gpu_resources.queue,
+                    model_bind_group_layout,
+                    group_bind_group_layout,
+                    camera,
+                    &line,
+                    1.0,
+                    if is_major {
+                        rgb_to_wgpu(0, 0, 0, 60.0)
+                    } else {
+                        rgb_to_wgpu(0, 0, 0, 25.0)
+                    },
+                    0, // just above the canvas background
+                    "grid_line".to_string(),
+                ));
+            }
+        }
+
+        if self.grid_settings.show_rulers {
+            let ruler_strips = [
+                // top
+                GuideLine {
+                    start: Point {
+                        x: canvas.min.x - RULER_SIZE,
+                        y: canvas.min.y - RULER_SIZE / 2.0,
+                    },
+                    end: Point {
+                        x: canvas.max.x,
+                        y: canvas.min.y - RULER_SIZE / 2.0,
+                    },
+                },
+                // left
+                GuideLine {
+                    start: Point {
+                        x: canvas.min.x - RULER_SIZE / 2.0,
+                        y: canvas.min.y,
+                    },
+                    end: Point {
+                        x: canvas.min.x - RULER_SIZE / 2.0,
+                        y: canvas.max.y,
+                    },
+                },
+            ];
+
+            for strip in &ruler_strips {
+                overlay_polygons.push(create_line_polygon(
+                    &window_size,
+                    &gpu_resources.device,
+                    &gpu_resources.queue,
+                    model_bind_group_layout,
+                    group_bind_group_layout,
+                    camera,
+                    strip,
+                    RULER_SIZE,
+                    rgb_to_wgpu(240, 240, 240, 255.0),
+                    2,
+                    "ruler_background".to_string(),
+                ));
+            }
+
+            for tick in ruler_ticks(&canvas, &self.grid_settings) {
+                overlay_polygons.push(create_line_polygon(
+                    &window_size,
+                    &gpu_resources.device,
+                    &gpu_resources.queue,
+                    model_bind_group_layout,
+                    group_bind_group_layout,
+                    camera,
+                    &tick.line,
+                    1.0,
+                    rgb_to_wgpu(80, 80, 80, 255.0),
+                    1,
+                    "ruler_tick".to_string(),
+                ));
+            }
+        }
+
+        self.scene.static_polygons.extend(overlay_polygons);
+
+        Ok(())
+    }
+
+    /// Shows or hides the safe-area guides and platform overlay
+    pub fn set_safe_area_settings(
+        &mut self,
+        safe_area_settings: SafeAreaSettings,
+    ) -> Result<(), EditorError> {
+        self.safe_area_settings = safe_area_settings;
+        self.update_safe_area_polygons()
+    }
+
+    /// Rebuilds the safe-area outlines and obstruction zones as static geometry on the canvas
+    pub fn update_safe_area_polygons(&mut self) -> Result<(), EditorError> {
+        self.scene
+            .static_polygons
+            .retain(|p| p.name != "safe_area_line" && p.name != "obstruction_zone");
+
+        if !self.safe_area_settings.any_visible() {
+            return Ok(());
+        }
+
+        let canvas = match self.scene.get_canvas_bounding_box() {
+            Some(canvas) => canvas,
+            None => return Ok(()),
+        };
+
+        let resources = self.scene.object_resources()?;
+        let camera = &resources.camera;
+        let window_size = camera.window_size;
+        let gpu_resources = &resources.gpu_resources;
+        let model_bind_group_layout = &resources.model_bind_group_layout;
+        let group_bind_group_layout = &resources.group_bind_group_layout;
+
+        let mut lines = Vec::new();
+        if self.safe_area_settings.action_safe {
+            lines.extend(outline_lines(&safe_area(&canvas, ACTION_SAFE)));
+        }
+        if self.safe_area_settings.title_safe {
+            lines.extend(outline_lines(&safe_area(&canvas, TITLE_SAFE)));
+        }
+        let zones = match self.safe_area_settings.platform {
+            Some(platform) => obstruction_zones(&canvas, platform),
+            None => Vec::new(),
+        };
+
+        let mut overlay_polygons = Vec::new();
+
+        for line in &lines {
+            overlay_polygons.push(create_line_polygon(
+                &window_size,
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                model_bind_group_layout,
+                group_bind_group_layout,
+                camera,
+                line,
+                1.0,
+                rgb_to_wgpu(0, 200, 255, 200.0),
+                100,
+                "safe_area_line".to_string(),
+            ));
+        }
+
+        for zone in &zones {
+            // a line spanning the zone's corners fills it
+            overlay_polygons.push(create_line_polygon(
+                &window_size,
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                model_bind_group_layout,
+                group_bind_group_layout,
+                camera,
+                &GuideLine {
+                    start: zone.min,
+                    end: zone.max,
+                },
+                1.0,
+                rgb_to_wgpu(255, 60, 60, 60.0),
+                99,
+                "obstruction_zone".to_string(),
+            ));
+        }
+
+        self.scene.static_polygons.extend(overlay_polygons);
+
+        Ok(())
+    }
+
+    pub fn clear_guide_lines(&mut self) {
+        self.guide_lines.clear();
+        self.scene
+            .static_polygons
+            .retain(|p| p.name != "guide_line");
+    }
+
+    fn is_close(&self, a: f32, b: f32, threshold: f32) -> bool {
+        (a - b).abs() < threshold
+    }
+
+    pub fn hide_all_objects(&mut self) {
+        // Remove objects
+        self.scene.polygons.iter_mut().for_each(|p| {
+            p.hidden = true;
+        });
+        self.scene.text_items.iter_mut().for_each(|t| {
+            t.hidden = true;
+        });
+        self.scene.image_items.iter_mut().for_each(|i| {
+            i.hidden = true;
+        });
+        self.scene.video_items.iter_mut().for_each(|v| {
+            v.hidden = true;
+        });
+
+        // Remove existing motion path segments
+        // self.scene.static_polygons.retain(|p| {
+        //     p.name != "motion_path_segment"
+        //         && p.name != "motion_path_handle"
+        //         && p.name != "motion_path_arrow"
+        // });
+        // Remove existing motion paths
+        self.motion_paths.clear();
+    }
+
+    pub fn recreate_depth_view(&mut self, gpu_resources: &GpuResources, width: u32, height: u32) {
+        let depth_texture = gpu_resources
+            .device
+            .create_texture(&wgpu::TextureDescriptor {
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Depth24Plus,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                label: Some("Stunts Engine Depth Texture"),
+                view_formats: &[],
+            });
+
+        self.depth_view = Some(depth_texture.create_view(&wgpu::TextureViewDescriptor::default()));
+    }
+
+    /// Syncs object position from its current transform to both current_sequence_data and saved_state
+    pub fn sync_object_position_to_saved_data(&mut self, object_id: Uuid, object_type: ObjectType) {
+        let current_sequence_id = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .expect("Couldn't get sequence data")
+            .id
+            .clone();
+
+        match object_type {
+            ObjectType::Polygon => {
+                if let Some(polygon) = self.scene.polygons.iter().find(|p| p.id == object_id) {
+                    let current_pos = [
+                        polygon.transform.position.x as i32,
+                        polygon.transform.position.y as i32,
+                    ];
+
+                    // Update current_sequence_data
+                    if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+                        if let Some(saved_polygon) = current_sequence
+                            .active_polygons
+                            .iter_mut()
+                            .find(|p| p.id == object_id.to_string())
+                        {
+                            saved_polygon.position.x = current_pos[0];
+                            saved_polygon.position.y = current_pos[1];
+                        }
+
+                        // get self.motion_paths path.source_polygon_id which matches polygon.id, grab its transform.position, and set that on animated_data
+                        if let Some(path) = self
+                            .motion_paths
+                            .iter()
+                            .find(|p| p.source_polygon_id == object_id)
+                        {
+                            let current_pos = [
+                                path.transform.position.x as i32,
+                                path.transform.position.y as i32,
+                            ];
+
+                            // Update associated motion path in AnimationData
+                            if let Some(animation_data) = current_sequence
+                                .polygon_motion_paths
+                                .iter_mut()
+                                .find(|a| a.polygon_id == object_id.to_string())
+                            {
+                                animation_data.position = current_pos;
+                            }
+                        }
+                    }
+
+                    // Update saved_state
+                    if let Some(saved_state) = &mut self.saved_state {
+                        for sequence in &mut saved_state.sequences {
+                            if sequence.id == current_sequence_id {
+                                // Update the sequence with the current_sequence_data
+                                if let Some(current_sequence) = &self.scene.current_sequence_data {
+                                    *sequence = current_sequence.clone();
+                                }
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            ObjectType::TextItem => {
+                if let Some(text_item) = self.scene.text_items.iter().find(|t| t.id == object_id) {
+                    let current_pos = [
+                        text_item.transform.position.x as i32,
+                        text_item.transform.position.y as i32,
+                    ];
+
+                    // Update current_sequence_data
+                    if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+                        if let Some(saved_text) = current_sequence
+                            .active_text_items
+                            .iter_mut()
+                            .find(|t| t.id == object_id.to_string())
+                        {
+                            saved_text.position.x = current_pos[0];
+                            saved_text.position.y = current_pos[1];
+                        }
+                        // get self.motion_paths path.source_polygon_id which matches polygon.id, grab its transform.position, and set that on animated_data
+                        if let Some(path) = self
+                            .motion_paths
+                            .iter()
+                            .find(|p| p.source_polygon_id == object_id)
+                        {
+                            let current_pos = [
+                                path.transform.position.x as i32,
+                                path.transform.position.y as i32,
+                            ];
+
+                            // Update associated motion path in AnimationData
+                            if let Some(animation_data) = current_sequence
+                                .polygon_motion_paths
+                                .iter_mut()
+                                .find(|a| a.polygon_id == object_id.to_string())
+                            {
+                                animation_data.position = current_pos;
+                            }
+                        }
+                    }
+
+                    // Update saved_state
+                    if let Some(saved_state) = &mut self.saved_state {
+                        for sequence in &mut saved_state.sequences {
+                            if sequence.id == current_sequence_id {
+                                // Update the sequence with the current_sequence_data
+                                if let Some(current_sequence) = &self.scene.current_sequence_data {
+                                    *sequence = current_sequence.clone();
+                                }
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            ObjectType::ImageItem => {
+                if let Some(image_item) = self
+                    .scene
+                    .image_items
+                    .iter()
+                    .find(|i| i.id == object_id.to_string())
+                {
+                    let current_pos = [
+                        image_item.transform.position.x as i32,
+                        image_item.transform.position.y as i32,
+                    ];
+
+                    // Update current_sequence_data
+                    if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+                        if let Some(saved_image) = current_sequence
+                            .active_image_items
+                            .iter_mut()
+                            .find(|i| i.id == object_id.to_string())
+                        {
+                            saved_image.position.x = current_pos[0];
+                            saved_image.position.y = current_pos[1];
+                        }
+                        // get self.motion_paths path.source_polygon_id which matches polygon.id, grab its transform.position, and set that on animated_data
+                        if let Some(path) = self
+                            .motion_paths
+                            .iter()
+                            .find(|p| p.source_polygon_id == object_id)
+                        {
+                            let current_pos = [
+                                path.transform.position.x as i32,
+                                path.transform.position.y as i32,
+                            ];
+
+                            // Update associated motion path in AnimationData
+                            if let Some(animation_data) = current_sequence
+                                .polygon_motion_paths
+                                .iter_mut()
+                                .find(|a| a.polygon_id == object_id.to_string())
+                            {
+                                animation_data.position = current_pos;
+                            }
+                        }
+                    }
+
+                    // Update saved_state
+                    if let Some(saved_state) = &mut self.saved_state {
+                        for sequence in &mut saved_state.sequences {
+                            if sequence.id == current_sequence_id {
+                                // Update the sequence with the current_sequence_data
+                                if let Some(current_sequence) = &self.scene.current_sequence_data {
+                                    *sequence = current_sequence.clone();
+                                }
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            ObjectType::VideoItem => {
+                if let Some(video_item) = self
+                    .scene
+                    .video_items
+                    .iter()
+                    .find(|v| v.id == object_id.to_string())
+                {
+                    let current_pos = [
+                        video_item.transform.position.x as i32,
+                        video_item.transform.position.y as i32,
+                    ];
+
+                    // Update current_sequence_data
+                    if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+                        if let Some(saved_video) = current_sequence
+                            .active_video_items
+                            .iter_mut()
+                            .find(|v| v.id == object_id.to_string())
+                        {
+                            saved_video.position.x = current_pos[0];
+                            saved_video.position.y = current_pos[1];
+                        }
+                        // get self.motion_paths path.source_polygon_id which matches polygon.id, grab its transform.position, and set that on animated_data
+                        if let Some(path) = self
+                            .motion_paths
+                            .iter()
+                            .find(|p| p.source_polygon_id == object_id)
+                        {
+                            let current_pos = [
+                                path.transform.position.x as i32,
+                                path.transform.position.y as i32,
+                            ];
+
+                            // Update associated motion path in AnimationData
+                            if let Some(animation_data) = current_sequence
+                                .polygon_motion_paths
+                                .iter_mut()
+                                .find(|a| a.polygon_id == object_id.to_string())
+                            {
+                                animation_data.position = current_pos;
+                            }
+                        }
+                    }
+
+                    // Update saved_state
+                    if let Some(saved_state) = &mut self.saved_state {
+                        for sequence in &mut saved_state.sequences {
+                            if sequence.id == current_sequence_id {
+                                // Update the sequence with the current_sequence_data
+                                if let Some(current_sequence) = &self.scene.current_sequence_data {
+                                    *sequence = current_sequence.clone();
+                                }
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            ObjectType::Group => {
+                if let Some(group) = self.scene.groups.iter().find(|g| g.id == object_id) {
+                    // Update current_sequence_data
+                    if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+                        if let Some(saved_group) = current_sequence
+                            .active_groups
+                            .iter_mut()
+                            .find(|g| g.id == object_id.to_string())
+                        {
+                            group.update_saved_config(saved_group);
+                        }
+                    }
+
+                    // Update saved_state
+                    if let Some(saved_state) = &mut self.saved_state {
+                        for sequence in &mut saved_state.sequences {
+                            if sequence.id == current_sequence_id {
+                                // Update the sequence with the current_sequence_data
+                                if let Some(current_sequence) = &self.scene.current_sequence_data {
+                                    *sequence = current_sequence.clone();
+                                }
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            ObjectType::ParticleEmitter => {
+                if let Some(emitter) = self
+                    .scene
+                    .particle_emitters
+                    .iter()
+                    .find(|e| e.id == object_id)
+                {
+                    let current_pos = emitter.to_saved_config().position;
+
+                    // Update current_sequence_data
+                    if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+                        if let Some(saved_emitter) = current_sequence
+                            .active_particle_emitters
+                            .iter_mut()
+                            .find(|e| e.id == object_id.to_string())
+                        {
+                            saved_emitter.position = current_pos;
+                        }
+                    }
+
+                    // Update saved_state
+                    if let Some(saved_state) = &mut self.saved_state {
+                        for sequence in &mut saved_state.sequences {
+                            if sequence.id == current_sequence_id {
+                                // Update the sequence with the current_sequence_data
+                                if let Some(current_sequence) = &self.scene.current_sequence_data {
+                                    *sequence = current_sequence.clone();
+                                }
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(saved_state) = &self.saved_state {
+            if let Err(e) = save_saved_state_raw(saved_state.clone()) {
+                println!("Couldn't save object position: {}", e);
+            }
+        }
+
+        self.events.emit(EditorEvent::ObjectMoved {
+            object_id,
+            object_type,
+        });
+    }
+
+    /// Sets how the timeline blends into a sequence from the one before it, None for a hard cut
+    pub fn set_sequence_transition(
+        &mut self,
+        timeline_sequence_id: &str,
+        transition: Option<SequenceTransition>,
+    ) -> Result<(), String> {
+        if transition.as_ref().is_some_and(|t| t.duration_ms <= 0) {
+            return Err("Transition duration must be positive".to_string());
+        }
+
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        let timeline_sequence = saved_state
+            .timeline_state
+            .timeline_sequences
+            .iter_mut()
+            .find(|ts| ts.id == timeline_sequence_id)
+            .ok_or("Couldn't find timeline sequence")?;
+        timeline_sequence.transition_in = transition.clone();
+
+        // playback reads the timeline it was started with
+        if let Some(timeline) = self.scene.video_current_sequence_timeline.as_mut() {
+            if let Some(ts) = timeline
+                .timeline_sequences
+                .iter_mut()
+                .find(|ts| ts.id == timeline_sequence_id)
+            {
+                ts.transition_in = transition;
+            }
+        }
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Rewrites the project file in another format, which later saves keep using
+    pub fn set_project_format(&mut self, file_format: ProjectFormat) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        saved_state.file_format = file_format;
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Adds a swatch to the project palette, returning its id
+    pub fn add_swatch(&mut self, name: String, color: [i32; 4]) -> Result<String, String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        let swatch_id = saved_state.palette.add_swatch(name, color);
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(swatch_id)
+    }
+
+    /// Removes a swatch, leaving objects that used it their current color
+    pub fn remove_swatch(&mut self, swatch_id: &str) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        saved_state.palette.remove_swatch(swatch_id)?;
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Changes a swatch's color. Linked objects are recolored with it when update_linked is
+    /// set, otherwise they're unlinked and keep the old color.
+    pub fn update_swatch(
+        &mut self,
+        swatch_id: &str,
+        color: [i32; 4],
+        update_linked: bool,
+    ) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        saved_state.palette.swatch_mut(swatch_id)?.color = color;
+        let links = saved_state.palette.links_to(swatch_id);
+
+        if update_linked {
+            for link in links {
+                let object_id = parse_id(&link.object_id).map_err(|e| e.to_string())?;
+                self.set_object_color(object_id, &link.object_type, link.target, color)?;
+            }
+        } else {
+            saved_state
+                .palette
+                .links
+                .retain(|l| l.swatch_id != swatch_id);
+        }
+
+        let saved_state = self
+            .saved_state
+            .as_ref()
+            .ok_or("Couldn't get saved state")?;
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Applies a swatch to one of an object's colors. Linked colors follow later changes
+    /// to the swatch.
+    pub fn apply_swatch(
+        &mut self,
+        swatch_id: &str,
+        object_id: Uuid,
+        object_type: ObjectType,
+        target: ColorTarget,
+        linked: bool,
+    ) -> Result<(), String> {
+        let color = self
+            .saved_state
+            .as_ref()
+            .ok_or("Couldn't get saved state")?
+            .palette
+            .swatch(swatch_id)
+            .ok_or_else(|| format!("No swatch found with id {}", swatch_id))?
+            .color;
+        self.set_object_color(object_id, &object_type, target, color)?;
+
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        saved_state.palette.use_color(color);
+        if linked {
+            saved_state.palette.link(SwatchLink {
+                swatch_id: swatch_id.to_string(),
+                object_id: object_id.to_string(),
+                object_type,
+                target,
+            });
+        } else {
+            saved_state.palette.unlink(&object_id.to_string(), target);
+        }
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Applies a color from outside the palette, unlinking that color from any swatch
+    pub fn apply_color(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+        target: ColorTarget,
+        color: [i32; 4],
+    ) -> Result<(), String> {
+        self.set_object_color(object_id, &object_type, target, color)?;
+
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        saved_state.palette.use_color(color);
+        saved_state.palette.unlink(&object_id.to_string(), target);
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Sets one of an object's colors on the canvas and in its saved sequence, without saving
+    fn set_object_color(
+        &mut self,
+        object_id: Uuid,
+        object_type: &ObjectType,
+        target: ColorTarget,
+        color: [i32; 4],
+    ) -> Result<(), String> {
+        match (object_type, target) {
+            (ObjectType::Polygon, ColorTarget::Fill | ColorTarget::Stroke)
+            | (ObjectType::TextItem, ColorTarget::Fill | ColorTarget::Text) => {}
+            _ => {
+                return Err(format!(
+                    "Can't apply a {:?} color to a {:?}",
+                    target, object_type
+                ))
+            }
+        }
+
+        let id = object_id.to_string();
+        let apply = |sequence: &mut Sequence| {
+            for polygon in sequence.active_polygons.iter_mut().filter(|p| p.id == id) {
+                match target {
+                    ColorTarget::Stroke => polygon.stroke.fill = color,
+                    _ => polygon.fill = color,
+                }
+            }
+            for text_item in sequence.active_text_items.iter_mut().filter(|t| t.id == id) {
+                match target {
+                    ColorTarget::Text => text_item.color = color,
+                    _ => text_item.background_fill = Some(color),
+                }
+            }
+        };
+        if let Some(current_sequence) = self.scene.current_sequence_data.as_mut() {
+            apply(current_sequence);
+        }
+        if let Some(saved_state) = self.saved_state.as_mut() {
+            saved_state.sequences.iter_mut().for_each(apply);
+        }
+
+        // objects in other sequences are only saved, they pick the color up when opened
+        let resources = self.scene.object_resources().map_err(|e| e.to_string())?;
+        let camera = &resources.camera;
+        let device = &resources.gpu_resources.device;
+        let queue = &resources.gpu_resources.queue;
+        let fill = rgb_to_wgpu(
+            color[0] as u8,
+            color[1] as u8,
+            color[2] as u8,
+            color[3] as f32,
+        );
+
+        match object_type {
+            ObjectType::Polygon => {
+                if let Some(polygon) = self.scene.polygons.iter_mut().find(|p| p.id == object_id) {
+                    match target {
+                        ColorTarget::Stroke => polygon.update_data_from_stroke(
+                            &camera.window_size,
+                            device,
+                            queue,
+                            &resources.model_bind_group_layout,
+                            Stroke {
+                                thickness: polygon.stroke.thickness,
+                                fill,
+                            },
+                            camera,
+                        ),
+                        _ => polygon.update_data_from_fill(
+                            &camera.window_size,
+                            device,
+                            queue,
+                            &resources.model_bind_group_layout,
+                            fill,
+                            camera,
+                        ),
+                    }
+                }
+            }
+            _ => {
+                if let Some(text_item) =
+                    self.scene.text_items.iter_mut().find(|t| t.id == object_id)
+                {
+                    match target {
+                        ColorTarget::Text => {
+                            text_item.color = color;
+                            text_item.render_text(device, queue);
+                        }
+                        _ => text_item.background_polygon.update_data_from_fill(
+                            &camera.window_size,
+                            device,
+                            queue,
+                            &resources.model_bind_group_layout,
+                            fill,
+                            camera,
+                        ),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Saves a style preset to the project, replacing any with the same id
+    pub fn save_style_preset(&mut self, preset: StylePreset) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        match saved_state
+            .style_presets
+            .iter_mut()
+            .find(|p| p.id == preset.id)
+        {
+            Some(existing) => *existing = preset,
+            None => saved_state.style_presets.push(preset),
+        }
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    pub fn remove_style_preset(&mut self, preset_id: &str) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        let count = saved_state.style_presets.len();
+        saved_state.style_presets.retain(|p| p.id != preset_id);
+        if saved_state.style_presets.len() == count {
+            return Err(format!("No style preset found with id {}", preset_id));
+        }
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Restyles every text item and polygon in a sequence with a saved preset. Colors the
+    /// preset sets are unlinked from their swatches.
+    pub fn apply_style_preset(&mut self, preset_id: &str, sequence_id: &str) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        let preset = saved_state
+            .style_presets
+            .iter()
+            .find(|p| p.id == preset_id)
+            .cloned()
+            .ok_or_else(|| format!("No style preset found with id {}", preset_id))?;
+        let sequence = saved_state
+            .sequences
+            .iter_mut()
+            .find(|s| s.id == sequence_id)
+            .ok_or_else(|| format!("No sequence found with id {}", sequence_id))?;
+
+        let styled = preset.apply_to_sequence(sequence);
+
+        for object in &styled {
+            let targets: &[(bool, ColorTarget)] = match object.object_type {
+                ObjectType::Polygon => &[
+                    (preset.polygon_fill.is_some(), ColorTarget::Fill),
+                    (preset.polygon_stroke.is_some(), ColorTarget::Stroke),
+                ],
+                _ => &[
+                    (preset.text_background.is_some(), ColorTarget::Fill),
+                    (preset.text_color.is_some(), ColorTarget::Text),
+                ],
+            };
+            for (_, target) in targets.iter().filter(|(set, _)| *set) {
+                saved_state.palette.unlink(&object.object_id, *target);
+            }
+        }
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        // other sequences pick the style up when opened
+        let is_current = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .map_or(false, |s| s.id == sequence_id);
+        if !is_current {
+            return Ok(());
+        }
+        if let Some(current_sequence) = self.scene.current_sequence_data.as_mut() {
+            preset.apply_to_sequence(current_sequence);
+        }
+
+        self.refresh_styled_objects(&preset, &styled)?;
+
+        if let Some(BackgroundFill::Color(fill)) = &preset.background_fill {
+            self.replace_background(
+                parse_id(sequence_id).map_err(|e| e.to_string())?,
+                rgb_to_wgpu(fill[0] as u8, fill[1] as u8, fill[2] as u8, fill[3] as f32),
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        Ok(())
+    }
+
+    /// Updates the canvas copies of objects a style preset changed
+    fn refresh_styled_objects(
+        &mut self,
+        preset: &StylePreset,
+        styled: &[StyledObject],
+    ) -> Result<(), String> {
+        let resources = self.scene.object_resources().map_err(|e| e.to_string())?;
+        let camera = &resources.camera;
+        let device = &resources.gpu_resources.device;
+        let queue = &resources.gpu_resources.queue;
+        let layout = &resources.model_bind_group_layout;
+        let to_wgpu = |c: [i32; 4]| rgb_to_wgpu(c[0] as u8, c[1] as u8, c[2] as u8, c[3] as f32);
+
+        let font_data = match &preset.font_family {
+            Some(font_family) => Some(
+                self.scene
+                    .font_manager
+                    .get_font_by_name(font_family)
+                    .ok_or_else(|| format!("Couldn't find font {}", font_family))?,
+            ),
+            None => None,
+        };
+
+        for object in styled {
+            let object_id = parse_id(&object.object_id).map_err(|e| e.to_string())?;
+
+            match object.object_type {
+                ObjectType::Polygon => {
+                    let Some(polygon) = self.scene.polygons.iter_mut().find(|p| p.id == object_id)
+                    else {
+                        continue;
+                    };
+
+                    if let Some(fill) = preset.polygon_fill {
+                        polygon.update_data_from_fill(
+                            &camera.window_size,
+                            device,
+                            queue,
+                            layout,
+                            to_wgpu(fill),
+                            camera,
+                        );
+                    }
+                    if let Some(stroke) = &preset.polygon_stroke {
+                        polygon.update_data_from_stroke(
+                            &camera.window_size,
+                            device,
+                            queue,
+                            layout,
+                            Stroke {
+                                thickness: stroke.thickness as f32,
+                                fill: to_wgpu(stroke.fill),
+                            },
+                            camera,
+                        );
+                    }
+                    if let Some(border_radius) = preset.border_radius {
+                        polygon.update_data_from_border_radius(
+                            &camera.window_size,
+                            device,
+                            queue,
+                            layout,
+                            border_radius as f32,
+                            camera,
+                        );
+                    }
+                }
+                _ => {
+                    let Some(text_item) =
+                        self.scene.text_items.iter_mut().find(|t| t.id == object_id)
+                    else {
+                        continue;
+                    };
+
+                    if let (Some(font_family), Some(font_data)) = (&preset.font_family, font_data) {
+                        text_item.font_family = font_family.clone();
+                        text_item.update_font_family(font_data);
+                    }
+                    if let Some(font_size) = preset.font_size {
+                        text_item.font_size = font_size;
+                    }
+                    if let Some(color) = preset.text_color {
+                        text_item.color = color;
+                    }
+                    if let Some(background) = preset.text_background {
+                        text_item.background_polygon.update_data_from_fill(
+                            &camera.window_size,
+                            device,
+                            queue,
+                            layout,
+                            to_wgpu(background),
+                            camera,
+                        );
+                    }
+                    text_item.render_text(device, queue);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Attaches a brand kit from its manifest, registering its fonts and adding its colors
+    /// to the palette
+    pub fn attach_brand_kit(&mut self, manifest_path: &Path) -> Result<(), String> {
+        let brand_kit = BrandKit::load(manifest_path)?;
+        self.register_brand_fonts(&brand_kit)?;
+
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        for brand_color in &brand_kit.colors {
+            let exists = saved_state
+                .palette
+                .swatches
+                .iter()
+                .any(|s| s.name == brand_color.name && s.color == brand_color.color);
+            if !exists {
+                saved_state
+                    .palette
+                    .add_swatch(brand_color.name.clone(), brand_color.color);
+            }
+        }
+        saved_state.brand_kit = Some(brand_kit);
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Detaches the brand kit. Its swatches stay in the palette and text items keep its fonts
+    /// until the project is reopened.
+    pub fn detach_brand_kit(&mut self) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        saved_state.brand_kit = None;
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Makes a brand kit's fonts available to text items. Call after opening a project
+    /// with a brand kit, before its sequences are restored.
+    pub fn register_brand_fonts(&mut self, brand_kit: &BrandKit) -> Result<(), String> {
+        for font in &brand_kit.fonts {
+            self.scene
+                .font_manager
+                .register_font(&font.family, font.read()?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Autosaves the open project every settings.interval, from step_autosave
+    pub fn start_autosave(&mut self, settings: AutosaveSettings) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_ref()
+            .ok_or("Couldn't get saved state")?;
+        if let Some(previous) = self.autosaver.take() {
+            previous.finish();
+        }
+        self.autosaver = Some(Autosaver::start(&saved_state.id, settings)?);
+
+        Ok(())
+    }
+
+    /// Call every frame, saves a copy of the project when one is due
+    pub fn step_autosave(&mut self) {
+        let (Some(autosaver), Some(saved_state)) =
+            (self.autosaver.as_mut(), self.saved_state.as_ref())
+        else {
+            return;
+        };
+
+        if let Err(e) = autosaver.tick(saved_state) {
+            println!("Couldn't autosave: {}", e);
+        }
+    }
+
+    /// Stops autosaving, marking the session as closed cleanly
+    pub fn stop_autosave(&mut self) {
+        if let Some(autosaver) = self.autosaver.take() {
+            autosaver.finish();
+        }
+    }
+
+    /// Saves timeline edits, passing them on to playback, which reads the timeline it started with
+    fn save_timeline(&mut self) {
+        let Some(saved_state) = self.saved_state.as_ref() else {
+            return;
+        };
+
+        if self.scene.video_current_sequence_timeline.is_some() {
+            self.scene.video_current_sequence_timeline = Some(saved_state.timeline_state.clone());
+        }
+
+        if let Err(e) = save_saved_state_raw(saved_state.clone()) {
+            println!("Couldn't save timeline: {}", e);
+        }
+
+        self.events.emit(EditorEvent::TimelineChanged);
+    }
+
+    /// Places a sequence on the timeline, returning the new entry's id
+    pub fn insert_timeline_sequence(
+        &mut self,
+        sequence_id: &str,
+        track_type: TrackType,
+        start_time_ms: i32,
+    ) -> Result<String, String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        if !saved_state.sequences.iter().any(|s| s.id == sequence_id) {
+            return Err(format!("No sequence found with id {}", sequence_id));
+        }
+
+        let timeline_sequence = TimelineSequence {
+            id: Uuid::new_v4().to_string(),
+            sequence_id: sequence_id.to_string(),
+            track_type,
+            start_time_ms,
+            transition_in: None,
+        };
+        let id = timeline_sequence.id.clone();
+        saved_state
+            .timeline_state
+            .insert(timeline_sequence, &saved_state.sequences)?;
+
+        self.save_timeline();
+
+        Ok(id)
+    }
+
+    /// Takes an entry off the timeline. With ripple, later entries move back to close the gap.
+    pub fn remove_timeline_sequence(
+        &mut self,
+        timeline_sequence_id: &str,
+        ripple: bool,
+    ) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        saved_state
+            .timeline_state
+            .remove(timeline_sequence_id, ripple, &saved_state.sequences)?;
+
+        self.save_timeline();
+
+        Ok(())
+    }
+
+    /// Moves an entry to another place among those on its track, which are laid back to back
+    pub fn reorder_timeline_sequence(
+        &mut self,
+        timeline_sequence_id: &str,
+        new_track_index: usize,
+    ) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        saved_state.timeline_state.reorder(
+            timeline_sequence_id,
+            new_track_index,
+            &saved_state.sequences,
+        )?;
+
+        self.save_timeline();
+
+        Ok(())
+    }
+
+    /// Moves every entry on a track from from_ms on by delta_ms, to open or close a gap
+    pub fn ripple_shift_timeline(
+        &mut self,
+        track_type: TrackType,
+        from_ms: i32,
+        delta_ms: i32,
+    ) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        saved_state.timeline_state.ripple_shift(
+            &track_type,
+            from_ms,
+            delta_ms,
+            &saved_state.sequences,
+        )?;
+
+        self.save_timeline();
+
+        Ok(())
+    }
+
+    /// Adds a marker to the timeline, returning its id
+    pub fn add_timeline_marker(
+        &mut self,
+        time_ms: i32,
+        label: String,
+        color: [i32; 4],
+    ) -> Result<String, String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+
+        let id = Uuid::new_v4().to_string();
+        saved_state.timeline_state.add_marker(TimelineMarker {
+            id: id.clone(),
+            time_ms,
+            label,
+            color,
+        })?;
+
+        self.save_timeline();
+
+        Ok(id)
+    }
+
+    pub fn remove_timeline_marker(&mut self, marker_id: &str) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        saved_state.timeline_state.remove_marker(marker_id)?;
+
+        self.save_timeline();
+
+        Ok(())
+    }
+
+    pub fn move_timeline_marker(&mut self, marker_id: &str, time_ms: i32) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        saved_state.timeline_state.move_marker(marker_id, time_ms)?;
+
+        self.save_timeline();
+
+        Ok(())
+    }
+
+    /// Cuts a timeline entry in two at at_ms on the timeline. Its sequence is split too,
+    /// with the second half added to the project as a new sequence. Returns the new entry's id.
+    pub fn split_timeline_sequence(
+        &mut self,
+        timeline_sequence_id: &str,
+        at_ms: i32,
+    ) -> Result<String, String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        let timeline = &mut saved_state.timeline_state.timeline_sequences;
+        let index = timeline
+            .iter()
+            .position(|ts| ts.id == timeline_sequence_id)
+            .ok_or("Couldn't find timeline sequence")?;
+        let entry = timeline[index].clone();
+
+        // both entries would change with the sequence
+        if timeline
+            .iter()
+            .filter(|ts| ts.sequence_id == entry.sequence_id)
+            .count()
+            > 1
+        {
+            return Err("Can't split a sequence that's on the timeline more than once".to_string());
+        }
+
+        let sequence_index = saved_state
+            .sequences
+            .iter()
+            .position(|s| s.id == entry.sequence_id)
+            .ok_or("Couldn't find sequence")?;
+        let (first, second) = split_sequence(
+            &saved_state.sequences[sequence_index],
+            at_ms - entry.start_time_ms,
+        )?;
+
+        let new_entry = TimelineSequence {
+            id: Uuid::new_v4().to_string(),
+            sequence_id: second.id.clone(),
+            track_type: entry.track_type,
+            start_time_ms: at_ms,
+            transition_in: None,
+        };
+        let new_id = new_entry.id.clone();
+        timeline.insert(index + 1, new_entry);
+
+        if self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .is_some_and(|s| s.id == first.id)
+        {
+            self.scene.current_sequence_data = Some(first.clone());
+        }
+        saved_state.sequences[sequence_index] = first;
+        saved_state.sequences.insert(sequence_index + 1, second);
+
+        self.save_timeline();
+
+        Ok(new_id)
+    }
+
+    /// Copies a sequence, with new ids for everything in it, onto the end of the video track.
+    /// Returns the copy's id.
+    pub fn duplicate_sequence(&mut self, sequence_id: &str) -> Result<String, String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        let copy_id = saved_state.duplicate_sequence(sequence_id)?;
+
+        self.save_timeline();
+
+        Ok(copy_id)
+    }
+
+    /// Saves a sequence to the templates folder, returning the template's path
+    pub fn save_sequence_as_template(
+        &self,
+        sequence_id: &str,
+        name: String,
+    ) -> Result<PathBuf, String> {
+        let saved_state = self
+            .saved_state
+            .as_ref()
+            .ok_or("Couldn't get saved state")?;
+        let sequence = saved_state
+            .sequences
+            .iter()
+            .find(|s| s.id == sequence_id)
+            .ok_or_else(|| format!("No sequence found with id {}", sequence_id))?;
+
+        save_sequence_template(&SequenceTemplate {
+            name,
+            canvas_size: saved_state.canvas_size,
+            sequence: sequence.clone(),
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// Exports a sequence's shapes and text as a Lottie animation for web and mobile apps
+    pub fn export_sequence_lottie(&self, sequence_id: &str, path: &Path) -> Result<(), String> {
+        let saved_state = self
+            .saved_state
+            .as_ref()
+            .ok_or("Couldn't get saved state")?;
+        let sequence = saved_state
+            .sequences
+            .iter()
+            .find(|s| s.id == sequence_id)
+            .ok_or_else(|| format!("No sequence found with id {}", sequence_id))?;
+
+        export_lottie(path, sequence, saved_state.canvas_size)
+    }
+
+    /// Adds a Lottie animation's shapes and text as a new sequence at the end of the
+    /// video track, fitted to the canvas. Returns the new sequence's id.
+    pub fn import_lottie_sequence(&mut self, path: &Path) -> Result<String, String> {
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        let sequence = import_lottie(path, saved_state.canvas_size)?;
+        let sequence_id = sequence.id.clone();
+        saved_state.append_sequence(sequence);
+
+        self.save_timeline();
+
+        Ok(sequence_id)
+    }
+
+    /// Adds a template's sequence onto the end of the video track, returning its id
+    pub fn add_sequence_from_template(&mut self, template_path: &Path) -> Result<String, String> {
+        let template = load_sequence_template(template_path).map_err(|e| e.to_string())?;
+        let template_dir = template_path.parent().unwrap_or(Path::new("."));
+
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        let sequence_id = saved_state.add_template(&template, template_dir)?;
+
+        self.save_timeline();
+
+        Ok(sequence_id)
+    }
+
+    /// Copies sequences from another project file into this project, placing them one after
+    /// another at the end of the video track. Returns the ids the sequences have here.
+    pub fn import_sequences_from(
+        &mut self,
+        path: &Path,
+        sequence_ids: &[String],
+    ) -> Result<Vec<String>, String> {
+        let source = load_saved_state_file(path).map_err(|e| e.to_string())?;
+        let project_dir = path.parent().unwrap_or(Path::new("."));
+
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        let imported = prepare_sequence_import(&source, sequence_ids, saved_state, project_dir)?;
+
+        let mut new_ids = Vec::new();
+        for sequence in imported {
+            new_ids.push(sequence.id.clone());
+            saved_state.append_sequence(sequence);
+        }
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(new_ids)
+    }
+
+    pub fn add_sequence_tag(
+        &mut self,
+        sequence_id: String,
+        tag: ColorTag,
+    ) -> Result<(), EditorError> {
+        self.update_sequence_tags(sequence_id, |tags| upsert_tag(tags, tag.clone()))
+    }
+
+    pub fn remove_sequence_tag(
+        &mut self,
+        sequence_id: String,
+        tag_name: &str,
+    ) -> Result<(), EditorError> {
+        self.update_sequence_tags(sequence_id, |tags| remove_tag(tags, tag_name))
+    }
+
+    pub fn add_object_tag(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+        tag: ColorTag,
+    ) -> Result<(), EditorError> {
+        self.update_object_tags(object_id, object_type, |tags| upsert_tag(tags, tag.clone()))
+    }
+
+    pub fn remove_object_tag(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+        tag_name: &str,
+    ) -> Result<(), EditorError> {
+        self.update_object_tags(object_id, object_type, |tags| remove_tag(tags, tag_name))
+    }
+
+    /// Lists all objects across all sequences which carry the given tag
+    pub fn get_objects_with_tag(&self, tag_name: &str) -> Vec<TaggedObject> {
+        match &self.saved_state {
+            Some(saved_state) => objects_with_tag(saved_state, tag_name),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn get_sequences_with_tag(&self, tag_name: &str) -> Vec<Sequence> {
+        match &self.saved_state {
+            Some(saved_state) => sequences_with_tag(saved_state, tag_name)
+                .into_iter()
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn get_all_tags(&self) -> Vec<ColorTag> {
+        match &self.saved_state {
+            Some(saved_state) => all_tags(saved_state),
+            None => Vec::new(),
+        }
+    }
+
+    /// Locks an object so it can't be selected or dragged on the canvas. Locking a group
+    /// locks each of its children.
+    pub fn set_object_locked(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+        locked: bool,
+    ) -> Result<(), String> {
+        self.update_object_flags(object_id, object_type, &mut |object_locked, _| {
+            *object_locked = locked
+        })?;
+
+        if locked && self.selected_polygon_id == object_id {
+            self.selected_polygon_id = Uuid::nil();
+            self.clear_resize_handles();
+        }
+
+        Ok(())
+    }
+
+    /// Hides an object from the canvas, preview and export. This is separate from `hidden`,
+    /// which the editor sets when switching sequences.
+    pub fn set_object_hidden(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+        hidden: bool,
+    ) -> Result<(), String> {
+        self.update_object_flags(object_id, object_type, &mut |_, object_hidden| {
+            *object_hidden = hidden
+        })?;
+
+        if hidden && self.selected_polygon_id == object_id {
+            self.selected_polygon_id = Uuid::nil();
+            self.clear_resize_handles();
+        }
+
+        Ok(())
+    }
+
+    /// Sets how an object blends with what's beneath it
+    pub fn set_object_blend_mode(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+        blend_mode: BlendMode,
+    ) -> Result<(), String> {
+        let object_id_string = object_id.to_string();
+
+        let found = match object_type {
+            ObjectType::Polygon => self
+                .scene
+                .polygons
+                .iter_mut()
+                .find(|p| p.id == object_id)
+                .map(|p| p.blend_mode = blend_mode),
+            ObjectType::TextItem => self
+                .scene
+                .text_items
+                .iter_mut()
+                .find(|t| t.id == object_id)
+                .map(|t| t.blend_mode = blend_mode),
+            ObjectType::ImageItem => self
+                .scene
+                .image_items
+                .iter_mut()
+                .find(|i| i.id == object_id_string)
+                .map(|i| i.blend_mode = blend_mode),
+            ObjectType::VideoItem => self
+                .scene
+                .video_items
+                .iter_mut()
+                .find(|v| v.id == object_id_string)
+                .map(|v| v.blend_mode = blend_mode),
+            ObjectType::Group | ObjectType::ParticleEmitter => {
+                return Err(format!(
+                    "{:?} objects don't support blend modes",
+                    object_type
+                ));
+            }
+        };
+
+        if found.is_none() {
+            return Err(format!("No object found with id {}", object_id));
+        }
+
+        let apply = |sequence: &mut Sequence| match object_type {
+            ObjectType::Polygon => sequence
+                .active_polygons
+                .iter_mut()
+                .filter(|p| p.id == object_id_string)
+                .for_each(|p| p.blend_mode = blend_mode),
+            ObjectType::TextItem => sequence
+                .active_text_items
+                .iter_mut()
+                .filter(|t| t.id == object_id_string)
+                .for_each(|t| t.blend_mode = blend_mode),
+            ObjectType::ImageItem => sequence
+                .active_image_items
+                .iter_mut()
+                .filter(|i| i.id == object_id_string)
+                .for_each(|i| i.blend_mode = blend_mode),
+            ObjectType::VideoItem => sequence
+                .active_video_items
+                .iter_mut()
+                .filter(|v| v.id == object_id_string)
+                .for_each(|v| v.blend_mode = blend_mode),
+            ObjectType::Group | ObjectType::ParticleEmitter => {}
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Source and on-GPU resolution of an image, which differ when it was downscaled
+    pub fn image_residency(&self, image_id: Uuid) -> Result<ImageResidency, String> {
+        let image_id = image_id.to_string();
+
+        self.scene
+            .image_items
+            .iter()
+            .find(|i| i.id == image_id)
+            .map(|i| i.residency)
+            .ok_or_else(|| format!("No image item found with id {}", image_id))
+    }
+
+    /// Limits the longest side of an image on the GPU, reloading it from disk.
+    /// None uses the device limit.
+    pub fn set_image_max_resident_size(
+        &mut self,
+        image_id: Uuid,
+        max_resident_size: Option<u32>,
+    ) -> Result<(), String> {
+        let image_id = image_id.to_string();
+
+        let gpu_resources = self
+            .scene
+            .gpu_resources
+            .as_ref()
+            .ok_or("Couldn't get gpu resources")?;
+        let bind_group_layout = self
+            .scene
+            .model_bind_group_layout
+            .as_ref()
+            .ok_or("Couldn't get model bind group layout")?;
+
+        self.scene
+            .image_items
+            .iter_mut()
+            .find(|i| i.id == image_id)
+            .ok_or_else(|| format!("No image item found with id {}", image_id))?
+            .update_max_resident_size(
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                bind_group_layout,
+                &mut self.scene.image_assets,
+                max_resident_size,
+            )?;
+
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .active_image_items
+                .iter_mut()
+                .filter(|i| i.id == image_id)
+                .for_each(|i| i.max_resident_size = max_resident_size)
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Limits the longest side of every image texture in the editor, reloading them.
+    /// Exports use None to render from the full resolution originals.
+    pub fn set_max_image_texture_size(
+        &mut self,
+        max_texture_size: Option<u32>,
+    ) -> Result<(), String> {
+        let gpu_resources = self
+            .scene
+            .gpu_resources
+            .as_ref()
+            .ok_or("Couldn't get gpu resources")?;
+        let bind_group_layout = self
+            .scene
+            .model_bind_group_layout
+            .as_ref()
+            .ok_or("Couldn't get model bind group layout")?;
+
+        self.scene.image_assets.max_texture_size = max_texture_size;
+
+        for image_item in self.scene.image_items.iter_mut() {
+            let max_resident_size = image_item.residency.max_resident_size;
+            image_item.update_max_resident_size(
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                bind_group_layout,
+                &mut self.scene.image_assets,
+                max_resident_size,
+            )?;
+        }
+
+        self.scene.image_assets.release_unused();
+
+        Ok(())
+    }
+
+    /// Plays an animated GIF or WebP with the sequence, or only shows its first frame
+    pub fn set_image_animation_mode(
+        &mut self,
+        image_id: Uuid,
+        animation_mode: AnimatedImageMode,
+    ) -> Result<(), String> {
+        let image_id = image_id.to_string();
+
+        let gpu_resources = self
+            .scene
+            .gpu_resources
+            .as_ref()
+            .ok_or("Couldn't get gpu resources")?;
+        let bind_group_layout = self
+            .scene
+            .model_bind_group_layout
+            .as_ref()
+            .ok_or("Couldn't get model bind group layout")?;
+
+        self.scene
+            .image_items
+            .iter_mut()
+            .find(|i| i.id == image_id)
+            .ok_or_else(|| format!("No image item found with id {}", image_id))?
+            .update_animation_mode(
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                bind_group_layout,
+                &mut self.scene.image_assets,
+                animation_mode,
+            )?;
+
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .active_image_items
+                .iter_mut()
+                .filter(|i| i.id == image_id)
+                .for_each(|i| i.animation_mode = animation_mode)
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Makes an object a mask for another, or a regular object again with None.
+    /// The mask isn't drawn, its target is only drawn where the mask covers.
+    pub fn set_object_mask(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+        mask_target_id: Option<Uuid>,
+        mask_mode: MaskMode,
+    ) -> Result<(), String> {
+        let object_id_string = object_id.to_string();
+        let mask_target_id = mask_target_id.map(|id| id.to_string());
+
+        if mask_target_id.as_deref() == Some(object_id_string.as_str()) {
+            return Err("An object can't mask itself".to_string());
+        }
+
+        let found = match object_type {
+            ObjectType::Polygon => self
+                .scene
+                .polygons
+                .iter_mut()
+                .find(|p| p.id == object_id)
+                .map(|p| {
+                    p.mask_target_id = mask_target_id.clone();
+                    p.mask_mode = mask_mode;
+                }),
+            ObjectType::TextItem => self
+                .scene
+                .text_items
+                .iter_mut()
+                .find(|t| t.id == object_id)
+                .map(|t| {
+                    t.mask_target_id = mask_target_id.clone();
+                    t.mask_mode = mask_mode;
+                }),
+            ObjectType::ImageItem => self
+                .scene
+                .image_items
+                .iter_mut()
+                .find(|i| i.id == object_id_string)
+                .map(|i| {
+                    i.mask_target_id = mask_target_id.clone();
+                    i.mask_mode = mask_mode;
+                }),
+            ObjectType::VideoItem => self
+                .scene
+                .video_items
+                .iter_mut()
+                .find(|v| v.id == object_id_string)
+                .map(|v| {
+                    v.mask_target_id = mask_target_id.clone();
+                    v.mask_mode = mask_mode;
+                }),
+            ObjectType::Group | ObjectType::ParticleEmitter => {
+                return Err(format!("{:?} objects can't be used as masks", object_type));
+            }
+        };
+
+        if found.is_none() {
+            return Err(format!("No object found with id {}", object_id));
+        }
+
+        let apply = |sequence: &mut Sequence| match object_type {
+            ObjectType::Polygon => sequence
+                .active_polygons
+                .iter_mut()
+                .filter(|p| p.id == object_id_string)
+                .for_each(|p| {
+                    p.mask_target_id = mask_target_id.clone();
+                    p.mask_mode = mask_mode;
+                }),
+            ObjectType::TextItem => sequence
+                .active_text_items
+                .iter_mut()
+                .filter(|t| t.id == object_id_string)
+                .for_each(|t| {
+                    t.mask_target_id = mask_target_id.clone();
+                    t.mask_mode = mask_mode;
+                }),
+            ObjectType::ImageItem => sequence
+                .active_image_items
+                .iter_mut()
+                .filter(|i| i.id == object_id_string)
+                .for_each(|i| {
+                    i.mask_target_id = mask_target_id.clone();
+                    i.mask_mode = mask_mode;
+                }),
+            ObjectType::VideoItem => sequence
+                .active_video_items
+                .iter_mut()
+                .filter(|v| v.id == object_id_string)
+                .for_each(|v| {
+                    v.mask_target_id = mask_target_id.clone();
+                    v.mask_mode = mask_mode;
+                }),
+            ObjectType::Group | ObjectType::ParticleEmitter => {}
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Registers a sound set for typewriter reveals, replacing any set with the same name
+    pub fn register_sound_set(&mut self, sound_set: SoundSet) {
+        self.sound_sets.retain(|s| s.name != sound_set.name);
+        self.sound_sets.push(sound_set);
+    }
+
+    /// Per-character timing for typing out a text item, starting at `start_ms` in the sequence
+    pub fn get_typewriter_reveals(
+        &self,
+        text_id: Uuid,
+        start_ms: i32,
+        chars_per_second: f32,
+    ) -> Result<Vec<CharacterReveal>, String> {
+        let text_item = self
+            .scene
+            .text_items
+            .iter()
+            .find(|t| t.id == text_id)
+            .ok_or_else(|| format!("No text item found with id {}", text_id))?;
+
+        Ok(reveal_timings(&text_item.text, start_ms, chars_per_second))
+    }
+
+    /// Sound cues for a typewriter reveal, for the host to mix into the export's audio.
+    /// Export itself is video only for now.
+    pub fn get_typewriter_sound_cues(
+        &self,
+        text_id: Uuid,
+        start_ms: i32,
+        chars_per_second: f32,
+        sound_set_name: &str,
+        seed: u32,
+    ) -> Result<Vec<SoundCue>, String> {
+        let sound_set = self
+            .sound_sets
+            .iter()
+            .find(|s| s.name == sound_set_name)
+            .ok_or_else(|| format!("No sound set registered named {}", sound_set_name))?;
+        let reveals = self.get_typewriter_reveals(text_id, start_ms, chars_per_second)?;
+
+        Ok(sound_cues(&reveals, sound_set, seed))
+    }
+
+    /// Turns on generated in-between frames for a video, used when exporting at a higher
+    /// frame rate than the video was captured at
+    pub fn set_video_frame_interpolation(
+        &mut self,
+        video_id: Uuid,
+        settings: Option<FrameInterpolationSettings>,
+    ) -> Result<(), String> {
+        let video_id = video_id.to_string();
+
+        let video_item = self
+            .scene
+            .video_items
+            .iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| format!("No video item found with id {}", video_id))?;
+        video_item.update_frame_interpolation(settings.clone());
+
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .active_video_items
+                .iter_mut()
+                .filter(|v| v.id == video_id)
+                .for_each(|v| v.frame_interpolation = settings.clone());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Starts analyzing a video's camera shake on a background thread. The receiver reports
+    /// progress, then the corrections to pass to `set_video_stabilization`.
+    pub fn stabilize_video(
+        &self,
+        video_id: Uuid,
+        settings: StabilizationSettings,
+    ) -> Result<UnboundedReceiver<StabilizationProgress>, String> {
+        let video_id = video_id.to_string();
+
+        let video_item = self
+            .scene
+            .video_items
+            .iter()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| format!("No video item found with id {}", video_id))?;
+
+        let (progress_tx, progress_rx) = unbounded_channel();
+        let path = video_item.path.clone();
+        let source_dimensions = video_item.source_dimensions;
+        let source_duration_ms = video_item.source_duration_ms;
+        let source_frame_rate = video_item.source_frame_rate;
+
+        std::thread::spawn(move || {
+            analyze_video(
+                path,
+                source_dimensions,
+                source_duration_ms,
+                source_frame_rate,
+                settings,
+                progress_tx,
+            );
+        });
+
+        Ok(progress_rx)
+    }
+
+    /// Applies stabilization corrections to a video, or removes them with None
+    pub fn set_video_stabilization(
+        &mut self,
+        video_id: Uuid,
+        stabilization: Option<SavedStabilization>,
+    ) -> Result<(), String> {
+        let video_id = video_id.to_string();
+        let gpu_resources = self
+            .scene
+            .gpu_resources
+            .as_ref()
+            .ok_or("Couldn't get gpu resources")?;
+
+        let video_item = self
+            .scene
+            .video_items
+            .iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| format!("No video item found with id {}", video_id))?;
+        video_item.update_stabilization(&gpu_resources.queue, stabilization.clone());
+
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .active_video_items
+                .iter_mut()
+                .filter(|v| v.id == video_id)
+                .for_each(|v| v.stabilization = stabilization.clone());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Plays a video faster or slower, out of 100 so 200 is a 2x timelapse and 50 is half
+    /// speed slow motion. Speed keyframes from add_speed_ramp take over from this.
+    pub fn set_video_speed(&mut self, video_id: Uuid, speed: i32) -> Result<(), String> {
+        if !(MIN_VIDEO_SPEED..=MAX_VIDEO_SPEED).contains(&speed) {
+            return Err(format!(
+                "Speed must be between {} and {}",
+                MIN_VIDEO_SPEED, MAX_VIDEO_SPEED
+            ));
+        }
+
+        let video_id = video_id.to_string();
+        let video_item = self
+            .scene
+            .video_items
+            .iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| format!("No video item found with id {}", video_id))?;
+        video_item.speed = speed as f32 / 100.0;
+
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .active_video_items
+                .iter_mut()
+                .filter(|v| v.id == video_id)
+                .for_each(|v| v.speed = speed);
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Decodes every video ahead of playback on worker threads, or on demand with None
+    pub fn set_video_prefetch(&mut self, settings: Option<PrefetchSettings>) {
+        self.scene.video_prefetch = settings;
+
+        for video_item in self.scene.video_items.iter_mut() {
+            let result = match settings {
+                Some(settings) => {
+                    video_item.start_prefetch(settings);
+                    Ok(())
+                }
+                None => video_item.stop_prefetch(),
+            };
+            if let Err(e) = result {
+                println!("Couldn't stop prefetching {}: {}", video_item.id, e);
+            }
+        }
+    }
+
+    /// Records a proxy from transcode::proxy::generate_proxy for a video, or drops it with
+    /// None. The editor previews from it once the sequence is restored again.
+    pub fn set_video_proxy(
+        &mut self,
+        video_id: Uuid,
+        proxy: Option<VideoProxy>,
+    ) -> Result<(), String> {
+        let video_id = video_id.to_string();
+
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .active_video_items
+                .iter_mut()
+                .filter(|v| v.id == video_id)
+                .for_each(|v| v.proxy = proxy.clone())
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Sets how a video catches up when playback falls behind it, or follows the editor's
+    /// frame_skip_policy with None
+    pub fn set_video_frame_skip_policy(
+        &mut self,
+        video_id: Uuid,
+        policy: Option<FrameSkipPolicy>,
+    ) -> Result<(), String> {
+        let video_id = video_id.to_string();
+        let video_item = self
+            .scene
+            .video_items
+            .iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| format!("No video item found with id {}", video_id))?;
+        video_item.frame_skip_policy = policy;
+
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .active_video_items
+                .iter_mut()
+                .filter(|v| v.id == video_id)
+                .for_each(|v| v.frame_skip_policy = policy);
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Frames shown and skipped and how far behind each video is, since playback started
+    pub fn video_playback_stats(&self) -> Vec<(String, VideoPlaybackStats)> {
+        self.scene
+            .video_items
+            .iter()
+            .map(|v| (v.id.clone(), v.playback_stats))
+            .collect()
+    }
+
+    /// Adds a Speed property to a video's animation, starting at the video's speed, so its
+    /// speed can be ramped with keyframes
+    pub fn add_speed_ramp(&mut self, animation_id: &str) -> Result<(), String> {
+        let add = |sequence: &mut Sequence| -> bool {
+            let Some(animation) = sequence
+                .polygon_motion_paths
+                .iter_mut()
+                .find(|a| a.id == animation_id && a.object_type == ObjectType::VideoItem)
+            else {
+                return false;
+            };
+            let speed = sequence
+                .active_video_items
+                .iter()
+                .find(|v| v.id == animation.polygon_id)
+                .map(|v| v.speed)
+                .unwrap_or(100);
+
+            if !animation
+                .properties
+                .iter()
+                .any(|p| p.property_path == "speed")
+            {
+                let timestamps = [0, animation.duration.as_millis() as i32];
+                animation.properties.push(create_default_property(
+                    "Speed",
+                    "speed",
+                    KeyframeValue::Speed(speed),
+                    &timestamps,
+                ));
+            }
+
+            true
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            add(current_sequence);
+        }
+
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        let mut found = false;
+        for sequence in saved_state.sequences.iter_mut() {
+            found |= add(sequence);
+        }
+
+        if !found {
+            return Err(format!("No video animation found with id {}", animation_id));
+        }
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Plays only part of a video between in and out points, or all of it with None
+    pub fn set_video_trim(
+        &mut self,
+        video_id: Uuid,
+        trim: Option<VideoTrim>,
+    ) -> Result<(), String> {
+        let video_id = video_id.to_string();
+        let gpu_resources = self
+            .scene
+            .gpu_resources
+            .as_ref()
+            .ok_or("Couldn't get gpu resources")?;
+
+        let video_item = self
+            .scene
+            .video_items
+            .iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| format!("No video item found with id {}", video_id))?;
+        video_item.update_trim(&gpu_resources.queue, trim)?;
+
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .active_video_items
+                .iter_mut()
+                .filter(|v| v.id == video_id)
+                .for_each(|v| v.trim = trim);
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Sets whether a video plays once, loops or ping-pongs. Repeating videos have their
+    /// animation stretched to the end of the sequence so they keep playing.
+    pub fn set_video_playback_mode(
+        &mut self,
+        video_id: Uuid,
+        mode: VideoPlaybackMode,
+    ) -> Result<(), String> {
+        let video_id = video_id.to_string();
+
+        let video_item = self
+            .scene
+            .video_items
+            .iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| format!("No video item found with id {}", video_id))?;
+        video_item.playback_mode = mode;
+
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .active_video_items
+                .iter_mut()
+                .filter(|v| v.id == video_id)
+                .for_each(|v| v.playback_mode = mode);
+
+            if mode == VideoPlaybackMode::Once {
+                return;
+            }
+
+            let sequence_duration_ms = sequence.duration_ms;
+            sequence
+                .polygon_motion_paths
+                .iter_mut()
+                .filter(|a| a.polygon_id == video_id && a.object_type == ObjectType::VideoItem)
+                .for_each(|a| {
+                    let remaining_ms = (sequence_duration_ms - a.start_time_ms).max(0) as u64;
+                    a.duration = a.duration.max(Duration::from_millis(remaining_ms));
+                });
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Adds click ripples and keystroke captions over a screen capture from the clicks and
+    /// keys recorded with it, returning the new objects' ids
+    pub fn add_input_overlays(
+        &mut self,
+        video_id: Uuid,
+        settings: &InputOverlaySettings,
+    ) -> Result<Vec<Uuid>, String> {
+        let video_id = video_id.to_string();
+        let current_sequence = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .ok_or_else(|| "No sequence selected".to_string())?;
+        let current_sequence_id = current_sequence.id.clone();
+
+        let mouse_path = current_sequence
+            .active_video_items
+            .iter()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| format!("No video item found with id {}", video_id))?
+            .mouse_path
+            .clone()
+            .ok_or("This video wasn't captured with mouse tracking")?;
+        let video_start_ms = current_sequence
+            .polygon_motion_paths
+            .iter()
+            .find(|a| a.polygon_id == video_id)
+            .map(|a| a.start_time_ms)
+            .unwrap_or(0);
+
+        let video_item = self
+            .scene
+            .video_items
+            .iter()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| format!("No video item found with id {}", video_id))?;
+        let source_data = video_item
+            .source_data
+            .as_ref()
+            .ok_or("This video has no capture source data")?;
+
+        let events = load_input_events(Path::new(&mouse_path))?;
+        let overlays = generate_input_overlays(
+            &events,
+            video_item,
+            source_data,
+            video_start_ms,
+            current_sequence_id.clone(),
+            current_sequence.duration_ms,
+            settings,
+        );
+
+        self.scene
+            .restore_sequence_objects(&overlays, false)
+            .map_err(|e| e.to_string())?;
+
+        let apply = |sequence: &mut Sequence| {
+            if sequence.id != current_sequence_id {
+                return;
+            }
+            sequence
+                .active_polygons
+                .extend(overlays.active_polygons.iter().cloned());
+            sequence
+                .active_text_items
+                .extend(overlays.active_text_items.iter().cloned());
+            sequence
+                .polygon_motion_paths
+                .extend(overlays.polygon_motion_paths.iter().cloned());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(overlays
+            .polygon_motion_paths
+            .iter()
+            .filter_map(|a| Uuid::from_str(&a.polygon_id).ok())
+            .collect())
+    }
+
+    /// Replaces a captured video's Zoom keyframes with ones that zoom in while the mouse is
+    /// active and back out while it's idle, returning how many zoom segments were found.
+    /// The existing autofollow keeps the zoomed view on the mouse.
+    pub fn generate_smart_zoom(
+        &mut self,
+        video_id: Uuid,
+        settings: &SmartZoomSettings,
+    ) -> Result<usize, String> {
+        let video_id = video_id.to_string();
+
+        let video_item = self
+            .scene
+            .video_items
+            .iter()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| format!("No video item found with id {}", video_id))?;
+        let mouse_positions = video_item
+            .mouse_positions
+            .as_ref()
+            .ok_or("This video wasn't captured with mouse tracking")?;
+
+        let animation_duration_ms = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .ok_or_else(|| "No sequence selected".to_string())?
+            .polygon_motion_paths
+            .iter()
+            .find(|a| a.polygon_id == video_id)
+            .map(|a| a.duration.as_millis() as i64)
+            .ok_or_else(|| format!("No video animation found for {}", video_id))?;
+
+        let segments = find_active_segments(mouse_positions, settings);
+        let trim_start_ms = video_item.trim_start_ms();
+        let speed = video_item.speed.max(0.01);
+        let zoom_property = smart_zoom_property(
+            &segments,
+            |timestamp| ((timestamp as i64 - trim_start_ms) as f32 / speed) as i64,
+            animation_duration_ms,
+            settings,
+        );
+
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .polygon_motion_paths
+                .iter_mut()
+                .filter(|a| a.polygon_id == video_id && a.object_type == ObjectType::VideoItem)
+                .for_each(|a| {
+                    a.properties.retain(|p| p.property_path != "zoom");
+                    a.properties.push(zoom_property.clone());
+                });
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(segments.len())
+    }
+
+    /// Draws a custom cursor over a captured video along its smoothed mouse path, or removes
+    /// it with None
+    pub fn set_video_cursor(
+        &mut self,
+        video_id: Uuid,
+        cursor: Option<SavedCursorOverlay>,
+    ) -> Result<(), String> {
+        let video_id = video_id.to_string();
+
+        let video_item = self
+            .scene
+            .video_items
+            .iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| format!("No video item found with id {}", video_id))?;
+        let clicks = recorded_clicks(video_item.mouse_path.as_deref());
+        video_item.set_cursor(cursor.clone(), clicks)?;
+
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .active_video_items
+                .iter_mut()
+                .filter(|v| v.id == video_id)
+                .for_each(|v| v.cursor = cursor.clone());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Sets how much an object's animation blurs in exports, out of 100, with 0 turning it off
+    pub fn set_motion_blur(&mut self, animation_id: &str, amount: i32) -> Result<(), String> {
+        let amount = amount.clamp(0, 100);
+        let apply = |sequence: &mut Sequence| -> bool {
+            let mut found = false;
+            sequence
+                .polygon_motion_paths
+                .iter_mut()
+                .filter(|a| a.id == animation_id)
+                .for_each(|a| {
+                    a.motion_blur = amount;
+                    found = true;
+                });
+            found
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        let mut found = false;
+        for sequence in saved_state.sequences.iter_mut() {
+            found |= apply(sequence);
+        }
+
+        if !found {
+            return Err(format!("No animation found with id {}", animation_id));
+        }
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Registers a segmentation model for background removal, replacing any with the same name
+    pub fn register_segmentation_model(&mut self, name: String, factory: SegmentationModelFactory) {
+        self.scene.segmentation_models.insert(name, factory);
+    }
+
+    /// Removes the background from a video, or turns removal off with None
+    pub fn set_video_matte(
+        &mut self,
+        video_id: Uuid,
+        config: Option<SavedMatteConfig>,
+    ) -> Result<(), String> {
+        let video_id = video_id.to_string();
+        let matte = config
+            .clone()
+            .map(|config| Matte::from_config(config, &self.scene.segmentation_models));
+
+        let video_item = self
+            .scene
+            .video_items
+            .iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| format!("No video item found with id {}", video_id))?;
+        video_item.matte = matte;
+
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .active_video_items
+                .iter_mut()
+                .filter(|v| v.id == video_id)
+                .for_each(|v| v.matte = config.clone());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Uses the video's current frame as the empty background for its threshold matte
+    pub fn capture_matte_background(&mut self, video_id: Uuid) -> Result<(), String> {
+        let video_id = video_id.to_string();
+
+        let video_item = self
+            .scene
+            .video_items
+            .iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| format!("No video item found with id {}", video_id))?;
+
+        video_item
+            .matte
+            .as_mut()
+            .ok_or("Background removal isn't enabled for this video")?
+            .capture_background()
+    }
+
+    /// Starts scanning a video for text that looks like emails or account numbers on a
+    /// background thread, reading frames with the given OCR engine. Pass the findings to
+    /// `redact_pii_findings` to hide them.
+    pub fn scan_video_for_pii(
+        &self,
+        video_id: Uuid,
+        recognizer: Box<dyn TextRecognizer>,
+        settings: PiiScanSettings,
+    ) -> Result<UnboundedReceiver<PiiScanProgress>, String> {
+        let video_id = video_id.to_string();
+
+        let video_item = self
+            .scene
+            .video_items
+            .iter()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| format!("No video item found with id {}", video_id))?;
+
+        let (progress_tx, progress_rx) = unbounded_channel();
+        let path = video_item.path.clone();
+        let source_dimensions = video_item.source_dimensions;
+        let source_duration_ms = video_item.source_duration_ms;
+        let source_frame_rate = video_item.source_frame_rate;
+
+        std::thread::spawn(move || {
+            scan_video(
+                path,
+                source_dimensions,
+                source_duration_ms,
+                source_frame_rate,
+                recognizer,
+                settings,
+                progress_tx,
+            );
+        });
+
+        Ok(progress_rx)
+    }
+
+    /// Turns scan findings into redactions on the video, returning the new region ids
+    pub fn redact_pii_findings(
+        &mut self,
+        video_id: Uuid,
+        findings: &[PiiFinding],
+        style: RedactionStyle,
+    ) -> Result<Vec<String>, String> {
+        let regions: Vec<RedactionRegion> =
+            findings.iter().map(|f| f.to_redaction(style)).collect();
+        let region_ids = regions.iter().map(|r| r.id.clone()).collect();
+
+        self.update_video_redactions(video_id, |redactions| {
+            redactions.extend(regions.iter().cloned())
+        })?;
+
+        Ok(region_ids)
+    }
+
+    /// Adds a redacted region to a video, returning its id
+    pub fn add_video_redaction(
+        &mut self,
+        video_id: Uuid,
+        region: RedactionRegion,
+    ) -> Result<String, String> {
+        let region_id = region.id.clone();
+        self.update_video_redactions(video_id, |redactions| redactions.push(region.clone()))?;
+
+        Ok(region_id)
+    }
+
+    /// Replaces a redacted region with the same id, e.g. after adding keyframes to it
+    pub fn update_video_redaction(
+        &mut self,
+        video_id: Uuid,
+        region: RedactionRegion,
+    ) -> Result<(), String> {
+        self.update_video_redactions(video_id, |redactions| {
+            redactions
+                .iter_mut()
+                .filter(|r| r.id == region.id)
+                .for_each(|r| *r = region.clone());
+        })
+    }
+
+    pub fn remove_video_redaction(
+        &mut self,
+        video_id: Uuid,
+        region_id: &str,
+    ) -> Result<(), String> {
+        self.update_video_redactions(video_id, |redactions| {
+            redactions.retain(|r| r.id != region_id)
+        })
+    }
+
+    fn update_video_redactions(
+        &mut self,
+        video_id: Uuid,
+        update: impl Fn(&mut Vec<RedactionRegion>),
+    ) -> Result<(), String> {
+        let video_id = video_id.to_string();
+
+        let video_item = self
+            .scene
+            .video_items
+            .iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| format!("No video item found with id {}", video_id))?;
+        update(&mut video_item.redactions);
+
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .active_video_items
+                .iter_mut()
+                .filter(|v| v.id == video_id)
+                .for_each(|v| update(&mut v.redactions));
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Adds a review comment to the project and returns its id
+    pub fn add_comment(
+        &mut self,
+        author: String,
+        body: String,
+        sequence_id: Option<String>,
+        anchor: Option<CommentAnchor>,
+        time_ms: Option<i32>,
+    ) -> Result<String, EditorError> {
+        let comment = ReviewComment::new(author, body, sequence_id, anchor, time_ms);
+        let comment_id = comment.id.clone();
+
+        let saved_state = self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
+        saved_state.comments.push(comment);
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(comment_id)
+    }
+
+    pub fn remove_comment(&mut self, comment_id: &str) -> Result<(), EditorError> {
+        let saved_state = self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
+        saved_state.comments.retain(|c| c.id != comment_id);
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    pub fn resolve_comment(&mut self, comment_id: &str) -> Result<(), EditorError> {
+        self.set_comment_resolved(comment_id, true)
+    }
+
+    pub fn unresolve_comment(&mut self, comment_id: &str) -> Result<(), EditorError> {
+        self.set_comment_resolved(comment_id, false)
+    }
+
+    fn set_comment_resolved(
+        &mut self,
+        comment_id: &str,
+        resolved: bool,
+    ) -> Result<(), EditorError> {
+        let saved_state = self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
+        set_comment_resolved(&mut saved_state.comments, comment_id, resolved)?;
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Lists comments in playhead order, optionally limited to one sequence
+    pub fn list_comments(
+        &self,
+        sequence_id: Option<&str>,
+        include_resolved: bool,
+    ) -> Vec<ReviewComment> {
+        match &self.saved_state {
+            Some(saved_state) => {
+                comments_in_time_order(&saved_state.comments, sequence_id, include_resolved)
+            }
+            None => Vec::new(),
+        }
+    }
+
+    pub fn get_object_comments(&self, object_id: Uuid) -> Vec<ReviewComment> {
+        match &self.saved_state {
+            Some(saved_state) => comments_for_object(&saved_state.comments, &object_id.to_string()),
+            None => Vec::new(),
+        }
+    }
+
+    /// Copies an object, along with its animation, to the editor's clipboard
+    pub fn copy_object(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+    ) -> Result<(), EditorError> {
+        let item = self.find_clipboard_item(object_id, &object_type)?;
+
+        self.clipboard = Some(item);
+
+        Ok(())
+    }
+
+    /// Pastes the clipboard into the target sequence as a new object with fresh ids.
+    /// Pasting back into the source sequence offsets the copy so it doesn't cover the original.
+    pub fn paste_object(&mut self, target_sequence_id: String) -> Result<Uuid, EditorError> {
+        let item = self.clipboard.clone().ok_or("Nothing to paste")?;
+
+        let offset = if item.source_sequence_id == target_sequence_id {
+            [DUPLICATE_OFFSET, DUPLICATE_OFFSET]
+        } else {
+            [0, 0]
+        };
+
+        self.paste_clipboard_item(&item, target_sequence_id, offset)
+    }
+
+    /// Duplicates an object within its own sequence, leaving the clipboard untouched
+    pub fn duplicate_object(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+    ) -> Result<Uuid, EditorError> {
+        let item = self.find_clipboard_item(object_id, &object_type)?;
+        let target_sequence_id = item.source_sequence_id.clone();
+
+        self.paste_clipboard_item(
+            &item,
+            target_sequence_id,
+            [DUPLICATE_OFFSET, DUPLICATE_OFFSET],
+        )
+    }
+
+    fn find_clipboard_item(
+        &self,
+        object_id: Uuid,
+        object_type: &ObjectType,
+    ) -> Result<ClipboardItem, EditorError> {
+        let saved_state = self.saved_state.as_ref().ok_or(EditorError::NoProject)?;
+
+        saved_state
+            .sequences
+            .iter()
+            .find_map(|s| copy_from_sequence(s, &object_id.to_string(), object_type))
+            .ok_or_else(|| EditorError::ObjectNotFound {
+                object_type: object_type.clone(),
+                id: object_id,
+            })
+    }
+
+    fn paste_clipboard_item(
+        &mut self,
+        item: &ClipboardItem,
+        target_sequence_id: String,
+        offset: [i32; 2],
+    ) -> Result<Uuid, EditorError> {
+        let saved_state = self.saved_state.as_ref().ok_or(EditorError::NoProject)?;
+        if !saved_state
+            .sequences
+            .iter()
+            .any(|s| s.id == target_sequence_id)
+        {
+            return Err(format!("No sequence found with id {}", target_sequence_id).into());
+        }
+
+        let (new_id, pasted) = prepare_paste(item, target_sequence_id.clone(), offset);
+
+        // create the gpu resources, visible only if the target is the sequence being edited
+        let is_current = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .map(|s| s.id == target_sequence_id)
+            .unwrap_or(false);
+        self.scene.restore_sequence_objects(&pasted, !is_current)?;
+
+        let apply = |sequence: &mut Sequence| {
+            if sequence.id != target_sequence_id {
+                return;
+            }
+            sequence
+                .active_polygons
+                .extend(pasted.active_polygons.iter().cloned());
+            sequence
+                .active_text_items
+                .extend(pasted.active_text_items.iter().cloned());
+            sequence
+                .active_image_items
+                .extend(pasted.active_image_items.iter().cloned());
+            sequence
+                .active_video_items
+                .extend(pasted.active_video_items.iter().cloned());
+            sequence
+                .active_particle_emitters
+                .extend(pasted.active_particle_emitters.iter().cloned());
+            sequence
+                .polygon_motion_paths
+                .extend(pasted.polygon_motion_paths.iter().cloned());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        parse_id(&new_id)
+    }
+
+    /// Converts a text item into one editable polygon per glyph, so letters can be
+    /// animated, recolored or reshaped individually. The original text is kept on the
+    /// sequence for restore_outlined_text and reconvert_outlined_text.
+    pub fn convert_text_to_outlines(&mut self, text_id: Uuid) -> Result<Vec<Uuid>, EditorError> {
+        let text_id_string = text_id.to_string();
+
+        let text_item = self
+            .scene
+            .text_items
+            .iter()
+            .find(|t| t.id == text_id)
+            .ok_or(EditorError::ObjectNotFound {
+                object_type: ObjectType::TextItem,
+                id: text_id,
+            })?;
+        let current_sequence_id = text_item.current_sequence_id;
+        let sequence_id = current_sequence_id.to_string();
+
+        let font_data = self
+            .scene
+            .font_manager
+            .get_font_by_name(&text_item.font_family)
+            .ok_or_else(|| EditorError::FontNotFound(text_item.font_family.clone()))?;
+
+        let outlines = text_to_glyph_outlines(
+            font_data,
+            &text_item.text,
+            text_item.font_size as f32,
+            text_item.dimensions.0,
+        )?;
+
+        let saved_state = self.saved_state.as_ref().ok_or(EditorError::NoProject)?;
+        let sequence = saved_state
+            .sequences
+            .iter()
+            .find(|s| s.id == sequence_id)
+            .ok_or_else(|| format!("No sequence found with id {}", sequence_id))?;
+        let saved_text = sequence
+            .active_text_items
+            .iter()
+            .find(|t| t.id == text_id_string)
+            .cloned()
+            .ok_or_else(|| format!("Text item {} is not saved in its sequence", text_id))?;
+        let text_animation = sequence
+            .polygon_motion_paths
+            .iter()
+            .find(|a| a.polygon_id == text_id_string)
+            .cloned();
+
+        let resources = self.scene.object_resources()?;
+        let device = &resources.gpu_resources.device;
+        let queue = &resources.gpu_resources.queue;
+        let camera = &resources.camera;
+        let window_size = camera.window_size;
+        let model_bind_group_layout = &resources.model_bind_group_layout;
+        let group_bind_group_layout = &resources.group_bind_group_layout;
+
+        let color = saved_text.color;
+        let fill = rgb_to_wgpu(
+            color[0] as u8,
+            color[1] as u8,
+            color[2] as u8,
+            color[3] as f32,
+        );
+
+        let mut glyph_polygons = Vec::new();
+        let mut saved_polygons = Vec::new();
+        let mut glyph_animations = Vec::new();
+
+        for outline in outlines {
+            let id = Uuid::new_v4();
+            let name = format!("{} {}", saved_text.name, outline.character);
+            let position = Point {
+                x: saved_text.position.x as f32 + outline.offset.x,
+                y: saved_text.position.y as f32 + outline.offset.y,
+            };
+
+            let mut polygon = Polygon::new(
+                &window_size,
+                device,
+                queue,
+                model_bind_group_layout,
+                group_bind_group_layout,
+                camera,
+                outline.points.clone(),
+                outline.dimensions,
+                position,
+                0.0,
+                0.0,
+                fill,
+                Stroke {
+                    thickness: 0.0,
+                    fill: rgb_to_wgpu(0, 0, 0, 255.0),
+                },
+                saved_text.layer,
+                name.clone(),
+                id,
+                current_sequence_id,
+            );
+            polygon.update_data_from_contours(
+                &window_size,
+                device,
+                queue,
+                model_bind_group_layout,
+                outline.points.clone(),
+                outline.contours.clone(),
+                camera,
+            );
+
+            saved_polygons.push(SavedPolygonConfig {
+                id: id.to_string(),
+                name,
+                fill: color,
+                dimensions: (outline.dimensions.0 as i32, outline.dimensions.1 as i32),
+                position: SavedPoint {
+                    x: position.x as i32,
+                    y: position.y as i32,
+                },
+                border_radius: 0,
+                stroke: SavedStroke {
+                    thickness: 0,
+                    fill: [0, 0, 0, 255],
+                },
+                layer: saved_text.layer,
+                tags: saved_text.tags.clone(),
+                outline: Some(SavedOutline {
+                    points: points_to_saved(&outline.points),
+                    contours: outline
+                        .contours
+                        .iter()
+                        .map(|c| points_to_saved(c))
+                        .collect(),
+                    source: Some(OutlineSource {
+                        text_id: text_id_string.clone(),
+                        glyph_index: outline.glyph_index as i32,
+                        character: outline.character.to_string(),
+                    }),
+                }),
+                locked: saved_text.locked,
+                hidden: saved_text.hidden,
+                opacity: saved_text.opacity,
+                blend_mode: saved_text.blend_mode,
+                mask_target_id: saved_text.mask_target_id.clone(),
+                mask_mode: saved_text.mask_mode,
+            });
+
+            if let Some(text_animation) = &text_animation {
+                glyph_animations.push(glyph_animation(
+                    text_animation,
+                    id.to_string(),
+                    outline.offset,
+                ));
+            }
+
+            glyph_polygons.push(polygon);
+        }
+
+        let glyph_ids: Vec<Uuid> = glyph_polygons.iter().map(|p| p.id).collect();
+
+        let apply = |sequence: &mut Sequence| {
+            if sequence.id != sequence_id {
+                return;
+            }
+            sequence
+                .active_text_items
+                .retain(|t| t.id != text_id_string);
+            sequence
+                .polygon_motion_paths
+                .retain(|a| a.polygon_id != text_id_string);
+            sequence
+                .active_polygons
+                .extend(saved_polygons.iter().cloned());
+            sequence
+                .polygon_motion_paths
+                .extend(glyph_animations.iter().cloned());
+            sequence.outlined_text_items.push(OutlinedText {
+                text: saved_text.clone(),
+                animation: text_animation.clone(),
+            });
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        self.scene.text_items.retain(|t| t.id != text_id);
+        self.scene.polygons.extend(glyph_polygons);
+
+        Ok(glyph_ids)
+    }
+
+    /// Removes a text item's glyph polygons and brings back the original text
+    pub fn restore_outlined_text(&mut self, text_id: Uuid) -> Result<(), EditorError> {
+        let text_id_string = text_id.to_string();
+        let resources = self.scene.object_resources()?;
+
+        let saved_state = self.saved_state.as_ref().ok_or(EditorError::NoProject)?;
+        let (sequence_id, outlined_text) = saved_state
+            .sequences
+            .iter()
+            .find_map(|s| {
+                s.outlined_text_items
+                    .iter()
+                    .find(|o| o.text.id == text_id_string)
+                    .map(|o| (s.id.clone(), o.clone()))
+            })
+            .ok_or_else(|| format!("No outlined text found with id {}", text_id))?;
+
+        let is_glyph = |p: &SavedPolygonConfig| {
+            p.outline
+                .as_ref()
+                .and_then(|o| o.source.as_ref())
+                .map(|source| source.text_id == text_id_string)
+                .unwrap_or(false)
+        };
+
+        let glyph_ids: Vec<String> = saved_state
+            .sequences
+            .iter()
+            .filter(|s| s.id == sequence_id)
+            .flat_map(|s| s.active_polygons.iter().filter(|p| is_glyph(p)))
+            .map(|p| p.id.clone())
+            .collect();
+
+        let apply = |sequence: &mut Sequence| {
+            if sequence.id != sequence_id {
+                return;
+            }
+            sequence
+                .active_polygons
+                .retain(|p| !glyph_ids.contains(&p.id));
+            sequence
+                .polygon_motion_paths
+                .retain(|a| !glyph_ids.contains(&a.polygon_id));
+            sequence
+                .outlined_text_items
+                .retain(|o| o.text.id != text_id_string);
+            sequence.active_text_items.push(outlined_text.text.clone());
+            if let Some(animation) = &outlined_text.animation {
+                sequence.polygon_motion_paths.push(animation.clone());
+            }
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        self.scene
+            .polygons
+            .retain(|p| !glyph_ids.contains(&p.id.to_string()));
+
+        let gpu_resources = resources.gpu_resources;
+        let window_size = resources.camera.window_size;
+        let t = outlined_text.text;
+
+        self.add_text_item(
+            &window_size,
+            &gpu_resources.device,
+            &gpu_resources.queue,
+            TextRendererConfig {
+                id: text_id,
+                name: t.name.clone(),
+                text: t.text.clone(),
+                font_family: t.font_family.clone(),
+                dimensions: (t.dimensions.0 as f32, t.dimensions.1 as f32),
+                position: Point {
+                    x: CANVAS_HORIZ_OFFSET + t.position.x as f32,
+                    y: CANVAS_VERT_OFFSET + t.position.y as f32,
+                },
+                layer: t.layer,
+                color: t.color,
+                font_size: t.font_size,
+                background_fill: t.background_fill.unwrap_or([200, 200, 200, 255]),
+            },
+            t.text.clone(),
+            text_id,
+            sequence_id,
+        )?;
+
+        Ok(())
+    }
+
+    /// Replaces the retained text's content and outlines it again, keeping its animation
+    pub fn reconvert_outlined_text(
+        &mut self,
+        text_id: Uuid,
+        text: String,
+    ) -> Result<Vec<Uuid>, EditorError> {
+        let text_id_string = text_id.to_string();
+
+        let update = |sequence: &mut Sequence| {
+            sequence
+                .outlined_text_items
+                .iter_mut()
+                .filter(|o| o.text.id == text_id_string)
+                .for_each(|o| o.text.text = text.clone());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            update(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
+        saved_state.sequences.iter_mut().for_each(|s| update(s));
+
+        self.restore_outlined_text(text_id)?;
+        self.convert_text_to_outlines(text_id)
+    }
+
+    /// Applies a mesh warp to an image or text item, or removes it with None.
+    /// To animate it, keyframe KeyframeValue::Warp values of the same grid size.
+    pub fn set_object_warp(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+        warp: Option<MeshWarp>,
+    ) -> Result<(), String> {
+        let gpu_resources = self
+            .scene
+            .gpu_resources
+            .clone()
+            .expect("Couldn't get gpu resources");
+        let device = &gpu_resources.device;
+        let queue = &gpu_resources.queue;
+
+        match object_type {
+            ObjectType::TextItem => {
+                let text_item = self
+                    .scene
+                    .text_items
+                    .iter_mut()
+                    .find(|t| t.id == object_id)
+                    .ok_or_else(|| format!("No text item found with id {}", object_id))?;
+                text_item.update_warp(device, queue, warp.clone());
+            }
+            ObjectType::ImageItem => {
+                let image_item = self
+                    .scene
+                    .image_items
+                    .iter_mut()
+                    .find(|i| i.id == object_id.to_string())
+                    .ok_or_else(|| format!("No image item found with id {}", object_id))?;
+                image_item.update_warp(device, queue, warp.clone());
+            }
+            _ => {
+                return Err(format!("{:?} does not support mesh warps", object_type));
+            }
+        }
+
+        let object_id = object_id.to_string();
+
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .active_text_items
+                .iter_mut()
+                .filter(|t| t.id == object_id)
+                .for_each(|t| t.warp = warp.clone());
+            sequence
+                .active_image_items
+                .iter_mut()
+                .filter(|i| i.id == object_id)
+                .for_each(|i| i.warp = warp.clone());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Groups objects of the current sequence under one transform, pivoting around their
+    /// combined center. The group gets its own animation track, which moves, rotates and
+    /// scales the children together on top of their own animations.
+    pub fn create_group(
+        &mut self,
+        name: String,
+        children: Vec<(Uuid, ObjectType)>,
+    ) -> Result<Uuid, String> {
+        let current_sequence = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .ok_or_else(|| "No sequence selected".to_string())?;
+        let current_sequence_id = current_sequence.id.clone();
+        let duration_ms = current_sequence.duration_ms;
+
+        if children.is_empty() {
+            return Err("A group needs at least one object".to_string());
+        }
+
+        let mut bounds: Option<BoundingBox> = None;
+        for (child_id, child_type) in &children {
+            if *child_type == ObjectType::Group {
+                return Err("Groups cannot be nested".to_string());
+            }
+            if self
+                .scene
+                .groups
+                .iter()
+                .any(|g| g.contains_child(&child_id.to_string()))
+            {
+                return Err(format!("{:?} {} is already grouped", child_type, child_id));
+            }
+
+            let bbox = self
+                .get_object_bounding_box(*child_id, child_type)
+                .ok_or_else(|| format!("No {:?} found with id {}", child_type, child_id))?;
+
+            bounds = Some(match bounds {
+                Some(b) => BoundingBox {
+                    min: Point {
+                        x: b.min.x.min(bbox.min.x),
+                        y: b.min.y.min(bbox.min.y),
+                    },
+                    max: Point {
+                        x: b.max.x.max(bbox.max.x),
+                        y: b.max.y.max(bbox.max.y),
+                    },
+                },
+                None => bbox,
+            });
+        }
+
+        let bounds = bounds.expect("Couldn't get group bounds");
+        let pivot = SavedPoint {
+            x: ((bounds.min.x + bounds.max.x) / 2.0 - CANVAS_HORIZ_OFFSET) as i32,
+            y: ((bounds.min.y + bounds.max.y) / 2.0 - CANVAS_VERT_OFFSET) as i32,
+        };
+
+        let group_id = Uuid::new_v4();
+        let saved_group = SavedGroupConfig {
+            id: group_id.to_string(),
+            name,
+            children: children
+                .iter()
+                .map(|(child_id, child_type)| GroupChild {
+                    object_id: child_id.to_string(),
+                    object_type: child_type.clone(),
+                })
+                .collect(),
+            pivot: pivot.clone(),
+            position: pivot.clone(),
+            rotation: 0,
+            scale: 100,
+            tags: Vec::new(),
+            widget: None,
+        };
+
+        let timestamps = [0, duration_ms];
+        let group_animation = AnimationData {
+            id: Uuid::new_v4().to_string(),
+            object_type: ObjectType::Group,
+            polygon_id: group_id.to_string(),
+            duration: Duration::from_millis(duration_ms as u64),
+            start_time_ms: 0,
+            position: [0, 0],
+            motion_blur: 0,
+            properties: vec![
+                create_default_property(
+                    "Position",
+                    "position",
+                    KeyframeValue::Position([pivot.x, pivot.y]),
+                    &timestamps,
+                ),
+                create_default_property(
+                    "Rotation",
+                    "rotation",
+                    KeyframeValue::Rotation(0),
+                    &timestamps,
+                ),
+                create_default_property("Scale", "scale", KeyframeValue::Scale(100), &timestamps),
+                create_default_property(
+                    "Opacity",
+                    "opacity",
+                    KeyframeValue::Opacity(100),
+                    &timestamps,
+                ),
+            ],
+        };
+
+        let gpu_resources = self
+            .scene
+            .gpu_resources
+            .clone()
+            .expect("Couldn't get gpu resources");
+        let window_size = self
+            .scene
+            .camera
+            .as_ref()
+            .expect("Couldn't get camera")
+            .window_size;
+        let group = ObjectGroup::new(
+            &gpu_resources.device,
+            &gpu_resources.queue,
+            self.scene
+                .group_bind_group_layout
+                .as_ref()
+                .expect("Couldn't get group bind group layout"),
+            &window_size,
+            &saved_group,
+            Uuid::from_str(&current_sequence_id).expect("Couldn't convert string to uuid"),
+        );
+
+        self.scene
+            .bind_group_children(&group.children, Some(&group));
+        self.scene.groups.push(group);
+
+        let apply = |sequence: &mut Sequence| {
+            if sequence.id != current_sequence_id {
+                return;
+            }
+            sequence.active_groups.push(saved_group.clone());
+            sequence.polygon_motion_paths.push(group_animation.clone());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(group_id)
+    }
+
+    /// Removes a group and its animation track. Children are moved to where the group
+    /// currently draws their centers, while the group's rotation and scale are dropped.
+    pub fn ungroup(&mut self, group_id: Uuid) -> Result<(), String> {
+        let group_index = self
+            .scene
+            .groups
+            .iter()
+            .position(|g| g.id == group_id)
+            .ok_or_else(|| format!("No group found with id {}", group_id))?;
+        let group = self.scene.groups.remove(group_index);
+
+        self.scene.bind_group_children(&group.children, None);
+
+        let window_size = self
+            .scene
+            .camera
+            .as_ref()
+            .expect("Couldn't get camera")
+            .window_size;
+        for child in &group.children {
+            let child_id = match Uuid::parse_str(&child.object_id) {
+                Ok(id) => id,
+                Err(_) => continue,
+            };
+            let Some(bbox) = self.get_object_bounding_box(child_id, &child.object_type) else {
+                continue;
+            };
+
+            let center = Point {
+                x: (bbox.min.x + bbox.max.x) / 2.0,
+                y: (bbox.min.y + bbox.max.y) / 2.0,
+            };
+            let grouped_center = group.to_group_space(center);
+            let delta = Point {
+                x: grouped_center.x - center.x,
+                y: grouped_center.y - center.y,
+            };
+
+            self.translate_object(child_id, child.object_type.clone(), delta, &window_size);
+            self.sync_object_position_to_saved_data(child_id, child.object_type.clone());
+        }
+
+        let group_id = group_id.to_string();
+        let apply = |sequence: &mut Sequence| {
+            sequence.active_groups.retain(|g| g.id != group_id);
+            sequence
+                .polygon_motion_paths
+                .retain(|a| a.polygon_id != group_id);
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Rotates and scales a group around its pivot, rotation in degrees and scale as a multiplier
+    pub fn transform_group(
+        &mut self,
+        group_id: Uuid,
+        rotation: f32,
+        scale: f32,
+    ) -> Result<(), String> {
+        let gpu_resources = self
+            .scene
+            .gpu_resources
+            .clone()
+            .expect("Couldn't get gpu resources");
+
+        let group = self
+            .scene
+            .groups
+            .iter_mut()
+            .find(|g| g.id == group_id)
+            .ok_or_else(|| format!("No group found with id {}", group_id))?;
+
+        group.transform.update_rotation_degrees(rotation);
+        group.transform.update_scale([scale, scale]);
+        group.update_uniform_buffer(&gpu_resources.queue);
+
+        self.sync_object_position_to_saved_data(group_id, ObjectType::Group);
+
+        Ok(())
+    }
+
+    /// Asks a layout backend for a scene matching a description, such as "title card with
+    /// three bullet points sliding in", and adds it to the current sequence. Blocks while
+    /// the backend runs.
+    pub fn generate_scene(
+        &mut self,
+        backend: &dyn LayoutGenerator,
+        prompt: &str,
+        font_family: &str,
+    ) -> Result<(), String> {
+        let instructions = layout_instructions(&self.scene.canvas_size);
+        let response = backend.generate_layout(&instructions, prompt)?;
+        let layout = parse_layout(&response)?;
+
+        self.add_scene_layout(&layout, font_family)
+    }
+
+    /// Adds a layout's objects and their motion to the current sequence, in front of the
+    /// objects already there
+    pub fn add_scene_layout(
+        &mut self,
+        layout: &SceneLayout,
+        font_family: &str,
+    ) -> Result<(), String> {
+        let current_sequence = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .ok_or_else(|| "No sequence selected".to_string())?;
+        let current_sequence_id = current_sequence.id.clone();
+
+        // lower layers are drawn in front
+        let front_layer = self
+            .scene
+            .polygons
+            .iter()
+            .map(|p| p.layer)
+            .chain(self.scene.text_items.iter().map(|t| t.layer))
+            .chain(self.scene.image_items.iter().map(|i| i.layer))
+            .chain(self.scene.video_items.iter().map(|v| v.layer))
+            .min()
+            .unwrap_or(0);
+        let scene = build_scene(
+            layout,
+            current_sequence_id.clone(),
+            current_sequence.duration_ms,
+            &self.scene.canvas_size,
+            font_family,
+            front_layer - layout.objects.len() as i32,
+        );
+
+        self.scene
+            .restore_sequence_objects(&scene, false)
+            .map_err(|e| e.to_string())?;
+
+        let apply = |sequence: &mut Sequence| {
+            if sequence.id != current_sequence_id {
+                return;
+            }
+            sequence
+                .active_polygons
+                .extend(scene.active_polygons.iter().cloned());
+            sequence
+                .active_text_items
+                .extend(scene.active_text_items.iter().cloned());
+            sequence
+                .polygon_motion_paths
+                .extend(scene.polygon_motion_paths.iter().cloned());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
+    }
+
+    /// Adds a progress bar, gauge or counter to the current sequence. Its parts are grouped
+    /// and the group's animation gets a Value property of Percent keyframes, which playback
+    /// turns into the fill's length and the label's number.
+    pub fn add_widget(&mut self, settings: &WidgetSettings) -> Result<Uuid, String> {
+        let current_sequence = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .ok_or_else(|| "No sequence selected".to_string())?;
+        let current_sequence_id = current_sequence.id.clone();
+        let duration_ms = current_sequence.duration_ms;
+
+        let mut widget = build_widget(settings, current_sequence_id.clone());
+        let group = widget.active_groups[0].clone();
+
+        let timestamps = [0, duration_ms];
+        // parts keep still, every object needs a track for its motion path
+        let parts: Vec<(String, ObjectType, SavedPoint)> = widget
+            .active_polygons
+            .iter()
+            .map(|p| (p.id.clone(), ObjectType::Polygon, p.position.clone()))
+            .chain(
+                widget
+                    .active_text_items
+                    .iter()
+                    .map(|t| (t.id.clone(), ObjectType::TextItem, t.position.clone())),
+            )
+            .collect();
+        for (part_id, object_type, position) in parts {
+            widget.polygon_motion_paths.push(AnimationData {
+                id: Uuid::new_v4().to_string(),
+                object_type,
+                polygon_id: part_id,
+                duration: Duration::from_millis(duration_ms as u64),
+                start_time_ms: 0,
+                position: [0, 0],
+                motion_blur: 0,
+                properties: vec![create_default_property(
+                    "Position",
+                    "position",
+                    KeyframeValue::Position([position.x, position.y]),
+                    &timestamps,
+                )],
+            });
+        }
+
+        widget.polygon_motion_paths.push(AnimationData {
+            id: Uuid::new_v4().to_string(),
+            object_type: ObjectType::Group,
+            polygon_id: group.id.clone(),
+            duration: Duration::from_millis(duration_ms as u64),
+            start_time_ms: 0,
+            position: [0, 0],
+            motion_blur: 0,
+            properties: vec![
+                create_default_property(
+                    "Position",
+                    "position",
+                    KeyframeValue::Position([group.position.x, group.position.y]),
+                    &timestamps,
+                ),
+                create_default_property(
+                    "Rotation",
+                    "rotation",
+                    KeyframeValue::Rotation(0),
+                    &timestamps,
+                ),
+                create_default_property("Scale", "scale", KeyframeValue::Scale(100), &timestamps),
+                create_default_property(
+                    "Opacity",
+                    "opacity",
+                    KeyframeValue::Opacity(100),
+                    &timestamps,
+                ),
+                create_default_property(
+                    "Value",
+                    "value",
+                    KeyframeValue::Percent(settings.value),
+                    &timestamps,
+                ),
+            ],
+        });
+
+        self.scene
+            .restore_sequence_objects(&widget, false)
+            .map_err(|e| e.to_string())?;
+
+        let apply = |sequence: &mut Sequence| {
+            if sequence.id != current_sequence_id {
+                return;
+            }
+            sequence
+                .active_polygons
+                .extend(widget.active_polygons.iter().cloned());
+            sequence
+                .active_text_items
+                .extend(widget.active_text_items.iter().cloned());
+            sequence
+                .active_groups
+                .extend(widget.active_groups.iter().cloned());
+            sequence
+                .polygon_motion_paths
+                .extend(widget.polygon_motion_paths.iter().cloned());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Uuid::from_str(&group.id).map_err(|e| e.to_string())
+    }
+
+    /// Adds a callout to the current sequence, an arrow pointing at the anchor from a label
+    /// box with text. The box and text are grouped so they move together, and the arrow
+    /// stays connected to them, see sync_callouts.
+    pub fn add_callout(&mut self, settings: &CalloutSettings) -> Result<Uuid, String> {
+        let current_sequence = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .ok_or_else(|| "No sequence selected".to_string())?;
+        let current_sequence_id = current_sequence.id.clone();
+
+        let callout = build_callout(
+            settings,
+            current_sequence_id.clone(),
+            current_sequence.duration_ms,
+        );
+
+        self.scene
+            .restore_sequence_objects(&callout, false)
+            .map_err(|e| e.to_string())?;
+
+        let apply = |sequence: &mut Sequence| {
+            if sequence.id != current_sequence_id {
+                return;
+            }
+            sequence
+                .active_polygons
+                .extend(callout.active_polygons.iter().cloned());
+            sequence
+                .active_text_items
+                .extend(callout.active_text_items.iter().cloned());
+            sequence
+                .active_groups
+                .extend(callout.active_groups.iter().cloned());
+            sequence
+                .polygon_motion_paths
+                .extend(callout.polygon_motion_paths.iter().cloned());
+            sequence
+                .active_callouts
+                .extend(callout.active_callouts.iter().cloned());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Uuid::from_str(&callout.active_callouts[0].id).map_err(|e| e.to_string())
+    }
+
+    /// Moves where a callout's arrow points, in canvas pixels
+    pub fn set_callout_anchor(
+        &mut self,
+        callout_id: Uuid,
+        anchor: SavedPoint,
+    ) -> Result<(), String> {
+        let callout_id = callout_id.to_string();
+        let current_sequence = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .ok_or_else(|| "No sequence selected".to_string())?;
+        if !current_sequence
+            .active_callouts
+            .iter()
+            .any(|c| c.id == callout_id)
+        {
+            return Err(format!("No callout found with id {}", callout_id));
+        }
+
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .active_callouts
+                .iter_mut()
+                .filter(|c| c.id == callout_id)
+                .for_each(|c| c.anchor = anchor.clone());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self
+            .saved_state
+            .as_mut()
+            .ok_or("Couldn't get saved state")?;
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        self.scene.sync_callouts();
+        // saves the state too
+        self.save_callout_arrow(&callout_id);
+
+        Ok(())
+    }
+
+    /// Saves a callout's arrow as currently drawn, after its label or anchor moves. Does
+    /// nothing for other ids, such as plain groups.
+    fn save_callout_arrow(&mut self, callout_id: &str) {
+        let Some(current_sequence) = &self.scene.current_sequence_data else {
+            return;
+        };
+        let current_sequence_id = current_sequence.id.clone();
+        let Some(arrow) = current_sequence
+            .active_callouts
+            .iter()
+            .find(|c| c.id == callout_id)
+            .and_then(|c| {
+                self.scene
+                    .polygons
+                    .iter()
+                    .find(|p| p.id.to_string() == c.arrow_id)
+            })
+        else {
+            return;
+        };
+
+        let arrow_id = arrow.id.to_string();
+        let position = SavedPoint {
+            x: (arrow.transform.position.x - CANVAS_HORIZ_OFFSET) as i32,
+            y: (arrow.transform.position.y - CANVAS_VERT_OFFSET) as i32,
+        };
+        let dimensions = (arrow.dimensions.0 as i32, arrow.dimensions.1 as i32);
+        let outline = SavedOutline {
+            points: points_to_saved(&arrow.points),
+            contours: Vec::new(),
+            source: None,
+        };
+
+        let apply = |sequence: &mut Sequence| {
+            if sequence.id != current_sequence_id {
+                return;
+            }
+            if let Some(saved_arrow) = sequence
+                .active_polygons
+                .iter_mut()
+                .find(|p| p.id == arrow_id)
+            {
+                saved_arrow.position = position.clone();
+                saved_arrow.dimensions = dimensions;
+                saved_arrow.outline = Some(outline.clone());
+            }
+            sequence
+                .polygon_motion_paths
+                .iter_mut()
+                .filter(|a| a.polygon_id == arrow_id)
+                .flat_map(|a| a.properties.iter_mut())
+                .filter(|p| p.property_path == "position")
+                .flat_map(|p| p.keyframes.iter_mut())
+                .for_each(|k| k.value = KeyframeValue::Position([position.x, position.y]));
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let Some(saved_state) = self.saved_state.as_mut() else {
+            return;
+        };
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        if let Err(e) = save_saved_state_raw(saved_state.clone()) {
+            println!("Couldn't save callout arrow: {}", e);
+        }
+    }
+
+    /// Adds a spotlight to the current sequence, dimming the canvas outside a region.
+    /// Keyframe its Spotlight property to move, resize or soften the region.
+    pub fn add_spotlight(&mut self, settings: &SpotlightSettings) -> Result<Uuid, String> {
+        let current_sequence = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .ok_or_else(|| "No sequence selected".to_string())?;
+        let current_sequence_id = current_sequence.id.clone();
+
+        let spotlight = build_spotlight(
+            settings,
+            current_sequence_id.clone(),
+            current_sequence.duration_ms,
+            self.scene.canvas_size,
+        );
+
+        self.scene
+            .restore_sequence_objects(&spotlight, false)
+            .map_err(|e| e.to_string())?;
+
+        let apply = |sequence: &mut Sequence| {
+            if sequence.id != current_sequence_id {
+                return;
+            }
+            sequence
+                .active_polygons
+                .extend(spotlight.active_polygons.iter().cloned());
+            sequence
+                .polygon_motion_paths
+                .extend(spotlight.polygon_motion_paths.iter().cloned());
+            sequence
+                .active_spotlights
+                .extend(spotlight.active_spotlights.iter().cloned());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Uuid::from_str(&spotlight.active_spotlights[0].id).map_err(|e| e.to_string())
+    }
+
+    /// Adds timed captions to the current sequence on a new caption track, each segment
+    /// as a text item shown while it's said
+    pub fn add_captions(
+        &mut self,
+        segments: &[CaptionSegment],
+        settings: &CaptionSettings,
+    ) -> Result<Uuid, String> {
+        let current_sequence = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .ok_or_else(|| "No sequence selected".to_string())?;
+        let current_sequence_id = current_sequence.id.clone();
+
+        let captions = build_caption_track(
+            segments,
+            settings,
+            current_sequence_id.clone(),
+            current_sequence.duration_ms,
+            self.scene.canvas_size,
+        );
+
+        self.scene
+            .restore_sequence_objects(&captions, false)
+            .map_err(|e| e.to_string())?;
+
+        let apply = |sequence: &mut Sequence| {
+            if sequence.id != current_sequence_id {
+                return;
+            }
+            sequence
+                .active_text_items
+                .extend(captions.active_text_items.iter().cloned());
+            sequence
+                .polygon_motion_paths
+                .extend(captions.polygon_motion_paths.iter().cloned());
+            sequence
+                .caption_tracks
+                .extend(captions.caption_tracks.iter().cloned());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Uuid::from_str(&captions.caption_tracks[0].id).map_err(|e| e.to_string())
+    }
+
+    /// Transcribes recorded audio and adds it as captions. Blocks while the backend runs,
+    /// so hosts with a worker thread can call transcribe there and pass the result to
+    /// add_captions instead.
+    pub fn transcribe_captions(
+        &mut self,
+        backend: &dyn SpeechToText,
+        audio_path: &Path,
+        settings: &CaptionSettings,
+    ) -> Result<Uuid, String> {
+        let segments = backend.transcribe(audio_path)?;
+        if segments.is_empty() {
+            return Err("No speech found in the recording".to_string());
+        }
+
+        self.add_captions(&segments, settings)
+    }
+
+    /// Adds the cues of an .srt or .vtt file as captions on a new caption track
+    pub fn import_subtitles(
+        &mut self,
+        path: &Path,
+        settings: &CaptionSettings,
+    ) -> Result<Uuid, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+        let segments = parse_subtitles(&text)?;
+
+        self.add_captions(&segments, settings)
+    }
+
+    /// Writes a caption track of the current sequence to a subtitle file, as SRT or
+    /// WebVTT from the path's extension
+    pub fn save_captions(&self, track_id: Uuid, path: &Path) -> Result<(), String> {
+        let format = SubtitleFormat::from_path(path)
+            .ok_or_else(|| format!("{} isn't an .srt or .vtt file", path.display()))?;
+        let track_id = track_id.to_string();
+        let track = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .and_then(|s| s.caption_tracks.iter().find(|t| t.id == track_id))
+            .ok_or_else(|| format!("No caption track found with id {}", track_id))?;
+
+        fs::write(path, captions_to_subtitles(&track.segments, format))
+            .map_err(|e| format!("Couldn't write {}: {}", path.display(), e))
+    }
+
+    /// Adds a particle emitter to the current sequence, with its own animation track so the
+    /// emission rate can be keyframed, e.g. a burst of confetti that tapers off
+    pub fn add_particle_emitter(
+        &mut self,
+        config: SavedParticleEmitterConfig,
+    ) -> Result<Uuid, String> {
+        let current_sequence = self
+            .scene
+            .current_sequence_data
+            .as_ref()
+            .ok_or_else(|| "No sequence selected".to_string())?;
+        let current_sequence_id = current_sequence.id.clone();
+        let duration_ms = current_sequence.duration_ms;
+
+        let emitter_id =
+            Uuid::from_str(&config.id).map_err(|e| format!("Invalid emitter id: {}", e))?;
+
+        let gpu_resources = self
+            .scene
+            .gpu_resources
+            .clone()
+            .expect("Couldn't get gpu resources");
+        let window_size = self
+            .scene
+            .camera
+            .as_ref()
+            .expect("Couldn't get camera")
+            .window_size;
+        let emitter = ParticleEmitter::new(
+            &gpu_resources.device,
+            &gpu_resources.queue,
+            self.scene
+                .model_bind_group_layout
+                .as_ref()
+                .expect("Couldn't get model bind group layout"),
+            self.scene
+                .group_bind_group_layout
+                .as_ref()
+                .expect("Couldn't get group bind group layout"),
+            &window_size,
+            &config,
+            Uuid::from_str(&current_sequence_id).expect("Couldn't convert string to uuid"),
+        );
+        self.scene.particle_emitters.push(emitter);
+
+        let timestamps = [0, duration_ms];
+        let emitter_animation = AnimationData {
+            id: Uuid::new_v4().to_string(),
+            object_type: ObjectType::ParticleEmitter,
+            polygon_id: config.id.clone(),
+            duration: Duration::from_millis(duration_ms as u64),
+            start_time_ms: 0,
+            position: [0, 0],
+            motion_blur: 0,
+            properties: vec![
+                create_default_property(
+                    "Position",
+                    "position",
+                    KeyframeValue::Position([config.position.x, config.position.y]),
+                    &timestamps,
+                ),
+                create_default_property(
+                    "Emission Rate",
+                    "emission_rate",
+                    KeyframeValue::EmissionRate(config.rate),
+                    &timestamps,
+                ),
+                create_default_property(
+                    "Opacity",
+                    "opacity",
+                    KeyframeValue::Opacity(100),
+                    &timestamps,
+                ),
+            ],
+        };
+
+        let apply = |sequence: &mut Sequence| {
+            if sequence.id != current_sequence_id {
+                return;
+            }
+            sequence.active_particle_emitters.push(config.clone());
+            sequence
+                .polygon_motion_paths
+                .push(emitter_animation.clone());
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
+
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(emitter_id)
+    }
+
+    /// Replaces an emitter's parameters, keeping its id, position and layer. The emitter is
+    /// rebuilt since the sprite may have changed.
+    pub fn update_particle_emitter(
+        &mut self,
+        emitter_id: Uuid,
+        config: SavedParticleEmitterConfig,
+    ) -> Result<(), String> {
+        let emitter_index = self
+            .scene
+            .particle_emitters
+            .iter()
+            .position(|e| e.id == emitter_id)
+            .ok_or_else(|| format!("No particle emitter found with id {}", emitter_id))?;
+
+        let existing = self.scene.particle_emitters[emitter_index].to_saved_config();
+        let config = SavedParticleEmitterConfig {
+            id: existing.id,
+            position: existing.position,
+            layer: existing.layer,
+            locked: existing.locked,
+            hidden: existing.hidden,
+            ..config
+        };
+
+        let gpu_resources = self
+            .scene
+            .gpu_resources
+            .clone()
+            .expect("Couldn't get gpu resources");
+        let window_size = self
+            .scene
+            .camera
+            .as_ref()
+            .expect("Couldn't get camera")
+            .window_size;
+        let mut emitter = ParticleEmitter::new(
+            &gpu_resources.device,
+            &gpu_resources.queue,
+            self.scene
+                .model_bind_group_layout
+                .as_ref()
+                .expect("Couldn't get model bind group layout"),
+            self.scene
+                .group_bind_group_layout
+                .as_ref()
+                .expect("Couldn't get group bind group layout"),
+            &window_size,
+            &config,
+            self.scene.particle_emitters[emitter_index].current_sequence_id,
+        );
+        emitter.hidden = self.scene.particle_emitters[emitter_index].hidden;
+        emitter.user_hidden = self.scene.particle_emitters[emitter_index].user_hidden;
+        emitter.locked = self.scene.particle_emitters[emitter_index].locked;
+        self.scene.particle_emitters[emitter_index] = emitter;
+
+        // keep it bound to its group, if any
+        let emitter_id_string = emitter_id.to_string();
+        if let Some(group_index) = self
+            .scene
+            .groups
+            .iter()
+            .position(|g| g.contains_child(&emitter_id_string))
+        {
+            let group = self.scene.groups.remove(group_index);
+            self.scene.bind_group_children(
+                &[GroupChild {
+                    object_id: emitter_id_string.clone(),
+                    object_type: ObjectType::ParticleEmitter,
+                }],
+                Some(&group),
+            );
+            self.scene.groups.insert(group_index, group);
+        }
 
-        // let image_item = &mut self.image_items[image_index];
-        let video_item = self
-            .video_items
-            .iter_mut()
-            .find(|i| i.id == video_id.to_string())
-            .expect("Couldn't find video item");
-        let new_position = Point {
-            x: video_item.transform.position.x + (dx * 0.9), // not sure relation with aspect_ratio?
-            y: video_item.transform.position.y + dy,
+        let apply = |sequence: &mut Sequence| {
+            sequence
+                .active_particle_emitters
+                .iter_mut()
+                .filter(|e| e.id == emitter_id_string)
+                .for_each(|e| *e = config.clone());
         };
 
-        // println!("move_video {:?}", new_position);
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
 
-        video_item
-            .transform
-            .update_position([new_position.x, new_position.y], window_size);
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
 
-        
-    // Step 2: Update transforms using the collected centers
-        for (position, handle_center) in handle_centers {
-            if let Some(handle) = self.resize_handles.iter_mut().find(|h| 
-                h.object_id.to_string() == video_item.id && h.position == position
-            ) {
-                // handle.polygon.transform.position = handle_center;
-                handle.polygon.transform.update_position([handle_center.x, handle_center.y], &camera.window_size);
-            }
-        }
+        save_saved_state_raw(saved_state.clone())?;
 
-        self.drag_start = Some(mouse_pos);
-        // self.update_guide_lines(poly_index, window_size);
+        Ok(())
     }
 
-    fn is_close(&self, a: f32, b: f32, threshold: f32) -> bool {
-        (a - b).abs() < threshold
-    }
+    fn update_sequence_tags<F>(
+        &mut self,
+        sequence_id: String,
+        mut update: F,
+    ) -> Result<(), EditorError>
+    where
+        F: FnMut(&mut Vec<ColorTag>),
+    {
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            if current_sequence.id == sequence_id {
+                update(&mut current_sequence.tags);
+            }
+        }
 
-    pub fn hide_all_objects(&mut self) {
-        // Remove objects
-        self.polygons.iter_mut().for_each(|p| {
-            p.hidden = true;
-        });
-        self.text_items.iter_mut().for_each(|t| {
-            t.hidden = true;
-        });
-        self.image_items.iter_mut().for_each(|i| {
-            i.hidden = true;
-        });
-        self.video_items.iter_mut().for_each(|v| {
-            v.hidden = true;
+        let saved_state = self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
+        saved_state.sequences.iter_mut().for_each(|s| {
+            if s.id == sequence_id {
+                update(&mut s.tags);
+            }
         });
 
-        // Remove existing motion path segments
-        // self.static_polygons.retain(|p| {
-        //     p.name != "motion_path_segment"
-        //         && p.name != "motion_path_handle"
-        //         && p.name != "motion_path_arrow"
-        // });
-        // Remove existing motion paths
-        self.motion_paths.clear();
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
     }
 
-    pub fn recreate_depth_view(&mut self, gpu_resources: &GpuResources, width: u32, height: u32) {
-        let depth_texture = gpu_resources.device.create_texture(&wgpu::TextureDescriptor {
-            size: wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Depth24Plus,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
-            label: Some("Stunts Engine Depth Texture"),
-            view_formats: &[],
-        });
+    fn update_object_tags<F>(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+        mut update: F,
+    ) -> Result<(), EditorError>
+    where
+        F: FnMut(&mut Vec<ColorTag>),
+    {
+        let object_id = object_id.to_string();
+
+        let mut apply = |sequence: &mut Sequence| match object_type {
+            ObjectType::Polygon => sequence
+                .active_polygons
+                .iter_mut()
+                .filter(|p| p.id == object_id)
+                .for_each(|p| update(&mut p.tags)),
+            ObjectType::TextItem => sequence
+                .active_text_items
+                .iter_mut()
+                .filter(|t| t.id == object_id)
+                .for_each(|t| update(&mut t.tags)),
+            ObjectType::ImageItem => sequence
+                .active_image_items
+                .iter_mut()
+                .filter(|i| i.id == object_id)
+                .for_each(|i| update(&mut i.tags)),
+            ObjectType::VideoItem => sequence
+                .active_video_items
+                .iter_mut()
+                .filter(|v| v.id == object_id)
+                .for_each(|v| update(&mut v.tags)),
+            ObjectType::Group => sequence
+                .active_groups
+                .iter_mut()
+                .filter(|g| g.id == object_id)
+                .for_each(|g| update(&mut g.tags)),
+            ObjectType::ParticleEmitter => sequence
+                .active_particle_emitters
+                .iter_mut()
+                .filter(|e| e.id == object_id)
+                .for_each(|e| update(&mut e.tags)),
+        };
 
-        self.depth_view = Some(depth_texture.create_view(&wgpu::TextureViewDescriptor::default()));
-    }
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
+        }
 
-    /// Syncs object position from its current transform to both current_sequence_data and saved_state
-    pub fn sync_object_position_to_saved_data(&mut self, object_id: Uuid, object_type: ObjectType) {
-        let current_sequence_id = self.current_sequence_data.as_ref().expect("Couldn't get sequence data").id.clone();
+        let saved_state = self.saved_state.as_mut().ok_or(EditorError::NoProject)?;
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
 
-        match object_type {
-            ObjectType::Polygon => {
-                if let Some(polygon) = self.polygons.iter().find(|p| p.id == object_id) {
-                    let current_pos = [
-                        polygon.transform.position.x as i32,
-                        polygon.transform.position.y as i32
-                    ];
-                    
-                    // Update current_sequence_data
-                    if let Some(current_sequence) = &mut self.current_sequence_data {
-                        if let Some(saved_polygon) = current_sequence.active_polygons
-                            .iter_mut()
-                            .find(|p| p.id == object_id.to_string()) {
-                            saved_polygon.position.x = current_pos[0];
-                            saved_polygon.position.y = current_pos[1];
-                        }
+        save_saved_state_raw(saved_state.clone())?;
 
-                        // get self.motion_paths path.source_polygon_id which matches polygon.id, grab its transform.position, and set that on animated_data
-                        if let Some(path) = self.motion_paths.iter()
-                            .find(|p| p.source_polygon_id == object_id) {
-                            let current_pos = [
-                                path.transform.position.x as i32,
-                                path.transform.position.y as i32
-                            ];
+        Ok(())
+    }
 
-                            // Update associated motion path in AnimationData
-                            if let Some(animation_data) = current_sequence.polygon_motion_paths
-                                .iter_mut()
-                                .find(|a| a.polygon_id == object_id.to_string()) {
-                                animation_data.position = current_pos;
-                            }
-                        }
-                    }
-                    
-                    // Update saved_state
-                    if let Some(saved_state) = &mut self.saved_state {
-                        for sequence in &mut saved_state.sequences {
-                            if sequence.id == current_sequence_id {
-                                // Update the sequence with the current_sequence_data
-                                if let Some(current_sequence) = &self.current_sequence_data {
-                                    *sequence = current_sequence.clone();
-                                }
-                                break;
-                            }
-                        }
-                    }
-                }
-            },
-            ObjectType::TextItem => {
-                if let Some(text_item) = self.text_items.iter().find(|t| t.id == object_id) {
-                    let current_pos = [
-                        text_item.transform.position.x as i32,
-                        text_item.transform.position.y as i32
-                    ];
-                    
-                    // Update current_sequence_data
-                    if let Some(current_sequence) = &mut self.current_sequence_data {
-                        if let Some(saved_text) = current_sequence.active_text_items
-                            .iter_mut()
-                            .find(|t| t.id == object_id.to_string()) {
-                            saved_text.position.x = current_pos[0];
-                            saved_text.position.y = current_pos[1];
-                        }
-                        // get self.motion_paths path.source_polygon_id which matches polygon.id, grab its transform.position, and set that on animated_data
-                        if let Some(path) = self.motion_paths.iter()
-                            .find(|p| p.source_polygon_id == object_id) {
-                            let current_pos = [
-                                path.transform.position.x as i32,
-                                path.transform.position.y as i32
-                            ];
+    /// Updates the locked and user hidden flags of an object, in the editor and saved state
+    fn update_object_flags(
+        &mut self,
+        object_id: Uuid,
+        object_type: ObjectType,
+        update: &mut dyn FnMut(&mut bool, &mut bool),
+    ) -> Result<(), String> {
+        let object_id_string = object_id.to_string();
 
-                            // Update associated motion path in AnimationData
-                            if let Some(animation_data) = current_sequence.polygon_motion_paths
-                                .iter_mut()
-                                .find(|a| a.polygon_id == object_id.to_string()) {
-                                animation_data.position = current_pos;
-                            }
-                        }
-                    }
-                    
-                                       // Update saved_state
-                    if let Some(saved_state) = &mut self.saved_state {
-                        for sequence in &mut saved_state.sequences {
-                            if sequence.id == current_sequence_id {
-                                // Update the sequence with the current_sequence_data
-                                if let Some(current_sequence) = &self.current_sequence_data {
-                                    *sequence = current_sequence.clone();
-                                }
-                                break;
-                            }
-                        }
-                    }
-                }
-            },
-            ObjectType::ImageItem => {
-                if let Some(image_item) = self.image_items.iter().find(|i| i.id == object_id.to_string()) {
-                    let current_pos = [
-                        image_item.transform.position.x as i32,
-                        image_item.transform.position.y as i32
-                    ];
-                    
-                    // Update current_sequence_data
-                    if let Some(current_sequence) = &mut self.current_sequence_data {
-                        if let Some(saved_image) = current_sequence.active_image_items
-                            .iter_mut()
-                            .find(|i| i.id == object_id.to_string()) {
-                            saved_image.position.x = current_pos[0];
-                            saved_image.position.y = current_pos[1];
-                        }
-                        // get self.motion_paths path.source_polygon_id which matches polygon.id, grab its transform.position, and set that on animated_data
-                        if let Some(path) = self.motion_paths.iter()
-                            .find(|p| p.source_polygon_id == object_id) {
-                            let current_pos = [
-                                path.transform.position.x as i32,
-                                path.transform.position.y as i32
-                            ];
+        if object_type == ObjectType::Group {
+            let children = self
+                .scene
+                .groups
+                .iter()
+                .find(|g| g.id == object_id)
+                .map(|g| g.children.clone())
+                .ok_or_else(|| format!("No group found with id {}", object_id))?;
+
+            for child in children {
+                let child_id =
+                    Uuid::from_str(&child.object_id).expect("Couldn't convert string to uuid");
+                self.update_object_flags(child_id, child.object_type, update)?;
+            }
 
-                            // Update associated motion path in AnimationData
-                            if let Some(animation_data) = current_sequence.polygon_motion_paths
-                                .iter_mut()
-                                .find(|a| a.polygon_id == object_id.to_string()) {
-                                animation_data.position = current_pos;
-                            }
-                        }
-                    }
-                    
-                                        // Update saved_state
-                    if let Some(saved_state) = &mut self.saved_state {
-                        for sequence in &mut saved_state.sequences {
-                            if sequence.id == current_sequence_id {
-                                // Update the sequence with the current_sequence_data
-                                if let Some(current_sequence) = &self.current_sequence_data {
-                                    *sequence = current_sequence.clone();
-                                }
-                                break;
-                            }
-                        }
-                    }
+            return Ok(());
+        }
 
-                }
-            },
-            ObjectType::VideoItem => {
-                if let Some(video_item) = self.video_items.iter().find(|v| v.id == object_id.to_string()) {
-                    let current_pos = [
-                        video_item.transform.position.x as i32,
-                        video_item.transform.position.y as i32
-                    ];
-                    
-                    // Update current_sequence_data
-                    if let Some(current_sequence) = &mut self.current_sequence_data {
-                        if let Some(saved_video) = current_sequence.active_video_items
-                            .iter_mut()
-                            .find(|v| v.id == object_id.to_string()) {
-                            saved_video.position.x = current_pos[0];
-                            saved_video.position.y = current_pos[1];
-                        }
-                        // get self.motion_paths path.source_polygon_id which matches polygon.id, grab its transform.position, and set that on animated_data
-                        if let Some(path) = self.motion_paths.iter()
-                            .find(|p| p.source_polygon_id == object_id) {
-                            let current_pos = [
-                                path.transform.position.x as i32,
-                                path.transform.position.y as i32
-                            ];
+        let found = match object_type {
+            ObjectType::Polygon => self
+                .scene
+                .polygons
+                .iter_mut()
+                .find(|p| p.id == object_id)
+                .map(|p| update(&mut p.locked, &mut p.user_hidden)),
+            ObjectType::TextItem => self
+                .scene
+                .text_items
+                .iter_mut()
+                .find(|t| t.id == object_id)
+                .map(|t| update(&mut t.locked, &mut t.user_hidden)),
+            ObjectType::ImageItem => self
+                .scene
+                .image_items
+                .iter_mut()
+                .find(|i| i.id == object_id_string)
+                .map(|i| update(&mut i.locked, &mut i.user_hidden)),
+            ObjectType::VideoItem => self
+                .scene
+                .video_items
+                .iter_mut()
+                .find(|v| v.id == object_id_string)
+                .map(|v| update(&mut v.locked, &mut v.user_hidden)),
+            ObjectType::ParticleEmitter => self
+                .scene
+                .particle_emitters
+                .iter_mut()
+                .find(|e| e.id == object_id)
+                .map(|e| update(&mut e.locked, &mut e.user_hidden)),
+            ObjectType::Group => None,
+        };
 
-                            // Update associated motion path in AnimationData
-                            if let Some(animation_data) = current_sequence.polygon_motion_paths
-                                .iter_mut()
-                                .find(|a| a.polygon_id == object_id.to_string()) {
-                                animation_data.position = current_pos;
-                            }
-                        }
-                    }
-                    
-                                        // Update saved_state
-                    if let Some(saved_state) = &mut self.saved_state {
-                        for sequence in &mut saved_state.sequences {
-                            if sequence.id == current_sequence_id {
-                                // Update the sequence with the current_sequence_data
-                                if let Some(current_sequence) = &self.current_sequence_data {
-                                    *sequence = current_sequence.clone();
-                                }
-                                break;
-                            }
-                        }
-                    }
+        if found.is_none() {
+            return Err(format!("No object found with id {}", object_id));
+        }
 
-                }
-            },
+        let mut apply = |sequence: &mut Sequence| match object_type {
+            ObjectType::Polygon => sequence
+                .active_polygons
+                .iter_mut()
+                .filter(|p| p.id == object_id_string)
+                .for_each(|p| update(&mut p.locked, &mut p.hidden)),
+            ObjectType::TextItem => sequence
+                .active_text_items
+                .iter_mut()
+                .filter(|t| t.id == object_id_string)
+                .for_each(|t| update(&mut t.locked, &mut t.hidden)),
+            ObjectType::ImageItem => sequence
+                .active_image_items
+                .iter_mut()
+                .filter(|i| i.id == object_id_string)
+                .for_each(|i| update(&mut i.locked, &mut i.hidden)),
+            ObjectType::VideoItem => sequence
+                .active_video_items
+                .iter_mut()
+                .filter(|v| v.id == object_id_string)
+                .for_each(|v| update(&mut v.locked, &mut v.hidden)),
+            ObjectType::ParticleEmitter => sequence
+                .active_particle_emitters
+                .iter_mut()
+                .filter(|e| e.id == object_id_string)
+                .for_each(|e| update(&mut e.locked, &mut e.hidden)),
+            ObjectType::Group => {}
+        };
+
+        if let Some(current_sequence) = &mut self.scene.current_sequence_data {
+            apply(current_sequence);
         }
 
-        save_saved_state_raw(self.saved_state.clone().expect("Couldn't get saved state"));
+        let saved_state = self.saved_state.as_mut().expect("Couldn't get saved state");
+        saved_state.sequences.iter_mut().for_each(|s| apply(s));
+
+        save_saved_state_raw(saved_state.clone())?;
+
+        Ok(())
     }
 }
 
+/// Click timestamps recorded alongside a capture's mouse positions, if there are any
+pub(crate) fn recorded_clicks(mouse_path: Option<&str>) -> Vec<u128> {
+    let Some(events) = mouse_path.and_then(|path| load_input_events(Path::new(path)).ok()) else {
+        return Vec::new();
+    };
+
+    events
+        .iter()
+        .filter_map(|event| match event {
+            InputEvent::Click { timestamp, .. } => Some(*timestamp),
+            _ => None,
+        })
+        .collect()
+}
+
 // Helper function to create default properties with constant values
 fn create_default_property(
     name: &str,
@@ -6301,6 +12945,26 @@ pub struct CurveData {
     pub control_point2: Option<ControlPoint>,
 }
 
+impl CurveData {
+    /// The curve's two control points between these positions, filling in defaults a third
+    /// and two thirds of the way along for any left unset
+    pub fn control_points(&self, start: [i32; 2], end: [i32; 2]) -> ((f32, f32), (f32, f32)) {
+        let p0 = (start[0] as f32, start[1] as f32);
+        let p3 = (end[0] as f32, end[1] as f32);
+
+        let p1 = self.control_point1.as_ref().map_or_else(
+            || (p0.0 + (p3.0 - p0.0) * 0.33, p0.1 + (p3.1 - p0.1) * 0.33),
+            |cp| (cp.x as f32, cp.y as f32),
+        );
+        let p2 = self.control_point2.as_ref().map_or_else(
+            || (p0.0 + (p3.0 - p0.0) * 0.66, p0.1 + (p3.1 - p0.1) * 0.66),
+            |cp| (cp.x as f32, cp.y as f32),
+        );
+
+        (p1, p2)
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
 pub enum PathType {
     Linear,
@@ -6313,6 +12977,48 @@ pub enum PathType {
 //     }
 // }
 
+/// An object's sorted position keyframes, path id and starting position
+fn motion_path_source(
+    sequence: &Sequence,
+    object_id: &str,
+) -> Result<(Vec<UIKeyframe>, Uuid, [i32; 2]), EditorError> {
+    let animation_data = sequence
+        .polygon_motion_paths
+        .iter()
+        .find(|anim| anim.polygon_id == object_id)
+        .ok_or_else(|| EditorError::Other(format!("No animation found for {}", object_id)))?;
+
+    // Find position property
+    let position_property = animation_data
+        .properties
+        .iter()
+        .find(|prop| prop.name.starts_with("Position"))
+        .ok_or_else(|| {
+            EditorError::Other(format!("No position keyframes found for {}", object_id))
+        })?;
+
+    // Sort keyframes by time
+    let mut keyframes = position_property.keyframes.clone();
+    keyframes.sort_by_key(|k| k.time);
+
+    let path_id = parse_id(&animation_data.id)?;
+
+    Ok((keyframes, path_id, animation_data.position))
+}
+
+/// Paths are colored in the order `update_motion_paths` creates them
+fn motion_path_color_index(sequence: &Sequence, object_id: &str) -> Option<u32> {
+    sequence
+        .active_polygons
+        .iter()
+        .map(|p| &p.id)
+        .chain(sequence.active_text_items.iter().map(|t| &t.id))
+        .chain(sequence.active_image_items.iter().map(|i| &i.id))
+        .chain(sequence.active_video_items.iter().map(|v| &v.id))
+        .position(|id| id == object_id)
+        .map(|index| index as u32 + 1)
+}
+
 /// Creates curves in between keyframes, on the same path, rather than sharing a curve with another
 /// but it's better this way, as using a keyframe as a middle point on a curve leads to various problems
 pub fn interpolate_position(start: &UIKeyframe, end: &UIKeyframe, time: f32) -> [i32; 2] {
@@ -6324,7 +13030,7 @@ pub fn interpolate_position(start: &UIKeyframe, end: &UIKeyframe, time: f32) ->
             let current_time = time - (start.time).as_secs_f32();
             let t = current_time / total_time;
 
-            match start.easing {
+            match &start.easing {
                 EasingType::Linear => t,
                 EasingType::EaseIn => t * t,
                 EasingType::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
@@ -6335,6 +13041,7 @@ pub fn interpolate_position(start: &UIKeyframe, end: &UIKeyframe, time: f32) ->
                         1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
                     }
                 }
+                EasingType::Spring(spring) => spring.progress(current_time),
             }
         };
 
@@ -6370,15 +13077,7 @@ pub fn interpolate_position(start: &UIKeyframe, end: &UIKeyframe, time: f32) ->
                 let p3 = (end_pos[0] as f32, end_pos[1] as f32);
 
                 // Use control points if available, otherwise generate default ones
-                let p1 = curve_data.control_point1.as_ref().map_or_else(
-                    || (p0.0 + (p3.0 - p0.0) * 0.33, p0.1 + (p3.1 - p0.1) * 0.33),
-                    |cp| (cp.x as f32, cp.y as f32),
-                );
-
-                let p2 = curve_data.control_point2.as_ref().map_or_else(
-                    || (p0.0 + (p3.0 - p0.0) * 0.66, p0.1 + (p3.1 - p0.1) * 0.66),
-                    |cp| (cp.x as f32, cp.y as f32),
-                );
+                let (p1, p2) = curve_data.control_points(*start_pos, *end_pos);
 
                 // Cubic Bezier curve formula
                 let t = progress;
@@ -6404,7 +13103,6 @@ pub fn interpolate_position(start: &UIKeyframe, end: &UIKeyframe, time: f32) ->
     }
 }
 
-
 #[derive(Debug)]
 pub struct Ray {
     // pub origin: Point3<f32>,
@@ -6424,8 +13122,6 @@ impl Ray {
     }
 }
 
-
-
 // old
 // pub fn visualize_ray_intersection(
 //     // device: &wgpu::Device,
@@ -6531,7 +13227,7 @@ pub fn visualize_ray_intersection(
 ) -> Ray {
     // let scale_factor = camera.zoom;
     let scale_factor = 1.0;
-    
+
     // let wgpu_viewport_width = window_size.width as f32 - 180.0;
     // let wgpu_viewport_height = window_size.height as f32 - 120.0;
     let wgpu_viewport_width = window_size.width as f32;
@@ -6573,12 +13269,11 @@ fn screen_to_world_perspective_correct(
     mouse_x: f32,
     mouse_y: f32,
     window_size: &WindowSize,
-    camera: &Camera
-    // viewport_width: f32,
-    // viewport_height: f32,
-    // view_matrix: &Matrix4<f32>,
-    // projection_matrix: &Matrix4<f32>,
-    // target_z: f32  // World Z where you want the cursor
+    camera: &Camera, // viewport_width: f32,
+                     // viewport_height: f32,
+                     // view_matrix: &Matrix4<f32>,
+                     // projection_matrix: &Matrix4<f32>,
+                     // target_z: f32  // World Z where you want the cursor
 ) -> Vector3<f32> {
     let target_z = 0.0;
     let projection_matrix = camera.get_projection();
@@ -6590,16 +13285,16 @@ fn screen_to_world_perspective_correct(
     // Convert to NDC (this IS needed for proper perspective correction)
     let ndc_x = (mouse_x / viewport_width) * 2.0 - 1.0;
     let ndc_y = 1.0 - (mouse_y / viewport_height) * 2.0;
-    
+
     // Create ray from near to far plane
     let near_point = Vector4::new(ndc_x, ndc_y, -1.0, 1.0);
     let far_point = Vector4::new(ndc_x, ndc_y, 1.0, 1.0);
-    
+
     let inv_view_proj = (projection_matrix * view_matrix).invert().unwrap();
-    
+
     let near_world = inv_view_proj * near_point;
     let far_world = inv_view_proj * far_point;
-    
+
     let near_world = Vector3::new(
         near_world.x / near_world.w,
         near_world.y / near_world.w,
@@ -6610,11 +13305,11 @@ fn screen_to_world_perspective_correct(
         far_world.y / far_world.w,
         far_world.z / far_world.w,
     );
-    
+
     // Intersect ray with plane at target_z
     let ray_dir = far_world - near_world;
     let t = (target_z - near_world.z) / ray_dir.z;
-    
+
     near_world + ray_dir * t
 }
 
@@ -6646,6 +13341,7 @@ pub enum InteractionTarget {
     Text(usize),
     Image(usize),
     Video(usize),
+    ParticleEmitter(usize),
 }
 
 pub fn get_color(color_index: u32) -> u32 {
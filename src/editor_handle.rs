@@ -0,0 +1,81 @@
+use std::sync::mpsc;
+
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use crate::editor::Editor;
+
+pub type EditorCommand = Box<dyn FnOnce(&mut Editor) + Send>;
+
+const EDITOR_CLOSED: &str = "Editor has closed";
+
+/// Commands posted from other threads, waiting for the render thread
+pub struct EditorCommandQueue {
+    sender: UnboundedSender<EditorCommand>,
+    receiver: UnboundedReceiver<EditorCommand>,
+}
+
+impl EditorCommandQueue {
+    pub fn new() -> Self {
+        let (sender, receiver) = unbounded_channel();
+
+        EditorCommandQueue { sender, receiver }
+    }
+
+    pub fn handle(&self) -> EditorHandle {
+        EditorHandle {
+            sender: self.sender.clone(),
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<EditorCommand> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl Default for EditorCommandQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lets threads that don't own the editor, like exports, capture or asset loading, change it.
+/// Commands run in the order they were posted, the next time the render thread
+/// calls `Editor::apply_commands`, so they never race with drawing.
+#[derive(Clone)]
+pub struct EditorHandle {
+    sender: UnboundedSender<EditorCommand>,
+}
+
+impl EditorHandle {
+    /// Queues a command without waiting for it
+    pub fn post<F>(&self, command: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut Editor) + Send + 'static,
+    {
+        self.sender
+            .send(Box::new(command))
+            .map_err(|_| EDITOR_CLOSED.to_string())
+    }
+
+    /// Queues a command and blocks until the render thread has run it, returning its result.
+    /// Calling this from the render thread itself never returns.
+    pub fn call<F, R>(&self, command: F) -> Result<R, String>
+    where
+        F: FnOnce(&mut Editor) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (result_sender, result_receiver) = mpsc::channel();
+        self.post(move |editor| {
+            result_sender.send(command(editor)).ok();
+        })?;
+
+        result_receiver
+            .recv()
+            .map_err(|_| EDITOR_CLOSED.to_string())
+    }
+
+    /// Whether the editor is still around to run commands
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+}
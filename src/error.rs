@@ -0,0 +1,73 @@
+use std::fmt;
+use std::str::FromStr;
+
+use uuid::Uuid;
+
+use crate::animations::ObjectType;
+
+/// Why an editor call couldn't be carried out, without panicking the host app
+#[derive(Clone, Debug, PartialEq)]
+pub enum EditorError {
+    /// Set up by the host before objects can be made, e.g. "gpu resources" or "camera"
+    NotInitialized(&'static str),
+    NoProject,
+    NoCurrentSequence,
+    InvalidId(String),
+    ObjectNotFound {
+        object_type: ObjectType,
+        id: Uuid,
+    },
+    FontNotFound(String),
+    /// A file that couldn't be opened or decoded
+    Media {
+        path: String,
+        reason: String,
+    },
+    Other(String),
+}
+
+impl fmt::Display for EditorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditorError::NotInitialized(resource) => {
+                write!(f, "Editor has no {} yet", resource)
+            }
+            EditorError::NoProject => write!(f, "No project is open"),
+            EditorError::NoCurrentSequence => write!(f, "No sequence is selected"),
+            EditorError::InvalidId(id) => write!(f, "Invalid id {}", id),
+            EditorError::ObjectNotFound { object_type, id } => {
+                write!(f, "No {:?} found with id {}", object_type, id)
+            }
+            EditorError::FontNotFound(font_family) => {
+                write!(f, "Font '{}' not found", font_family)
+            }
+            EditorError::Media { path, reason } => write!(f, "Couldn't load {}: {}", path, reason),
+            EditorError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for EditorError {}
+
+// the rest of the editor still reports errors as strings
+impl From<String> for EditorError {
+    fn from(message: String) -> Self {
+        EditorError::Other(message)
+    }
+}
+
+impl From<&str> for EditorError {
+    fn from(message: &str) -> Self {
+        EditorError::Other(message.to_string())
+    }
+}
+
+impl From<EditorError> for String {
+    fn from(error: EditorError) -> Self {
+        error.to_string()
+    }
+}
+
+pub fn parse_id(id: &str) -> Result<Uuid, EditorError> {
+    Uuid::from_str(id).map_err(|_| EditorError::InvalidId(id.to_string()))
+}
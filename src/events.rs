@@ -0,0 +1,95 @@
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use uuid::Uuid;
+
+use crate::animations::ObjectType;
+use crate::editor::{HoverTarget, SelectedObject};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::export::exporter::{ExportFrameProgress, ExportOutcome};
+
+/// Changes in the editor that host UIs may want to show
+#[derive(Clone, Debug)]
+pub enum EditorEvent {
+    SelectionChanged(Option<SelectedObject>), // None when nothing is selected
+    HoverChanged(Option<HoverTarget>),        // None when the mouse leaves everything
+    TextEditingChanged(Option<Uuid>),         // the text item edited on the canvas, if any
+    ObjectMoved {
+        object_id: Uuid,
+        object_type: ObjectType,
+    },
+    ObjectResized {
+        object_id: Uuid,
+        object_type: ObjectType,
+    },
+    PlaybackStarted,
+    PlaybackStopped,
+    TimelineChanged,
+    #[cfg(not(target_arch = "wasm32"))]
+    ExportProgress(ExportFrameProgress),
+    #[cfg(not(target_arch = "wasm32"))]
+    ExportFinished(ExportOutcome),
+}
+
+pub type EditorEventHandler = dyn FnMut(&EditorEvent) + Send;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SubscriptionId(u64);
+
+/// Passes editor events to every subscriber, in the order they subscribed
+pub struct EventBus {
+    next_id: u64,
+    subscribers: Vec<(SubscriptionId, Box<EditorEventHandler>)>,
+    sender: UnboundedSender<EditorEvent>,
+    receiver: UnboundedReceiver<EditorEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, receiver) = unbounded_channel();
+
+        EventBus {
+            next_id: 0,
+            subscribers: Vec::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn subscribe(&mut self, handler: Box<EditorEventHandler>) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.subscribers.push((id, handler));
+        id
+    }
+
+    /// Returns whether the subscription was still active
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let count = self.subscribers.len();
+        self.subscribers
+            .retain(|(subscription_id, _)| *subscription_id != id);
+        self.subscribers.len() != count
+    }
+
+    pub fn emit(&mut self, event: EditorEvent) {
+        for (_, handler) in self.subscribers.iter_mut() {
+            handler(&event);
+        }
+    }
+
+    /// For threads without the editor, like exports. Their events are held until `dispatch_queued`.
+    pub fn sender(&self) -> UnboundedSender<EditorEvent> {
+        self.sender.clone()
+    }
+
+    /// Emits events sent from other threads, call once per frame
+    pub fn dispatch_queued(&mut self) {
+        while let Ok(event) = self.receiver.try_recv() {
+            self.emit(event);
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use super::exporter::{
+    ExportCancelToken, ExportFrameProgress, ExportJob, ExportOutcome, ExportRequest,
+};
+use crate::templating::{fill_sequences, fill_template, template_variables, TemplateRow};
+
+/// How often a running batch checks whether it was cancelled
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Renders one export per row of a dataset, filling the {{variables}} in text items and
+/// in the output path from each row, such as "renders/{{name}}.mp4"
+#[derive(Clone)]
+pub struct BatchRequest {
+    pub template: ExportRequest, // its output path is replaced by the pattern's
+    pub rows: Vec<TemplateRow>,
+    pub output_pattern: String,
+}
+
+impl BatchRequest {
+    /// The export for one row
+    pub fn row_request(&self, row: &TemplateRow) -> Result<ExportRequest, String> {
+        if self.rows.len() > 1 && template_variables(&self.output_pattern).is_empty() {
+            return Err(
+                "The output path needs a variable so rows don't overwrite each other".to_string(),
+            );
+        }
+
+        Ok(ExportRequest {
+            output_path: fill_template(&self.output_pattern, row)?,
+            sequences: fill_sequences(&self.template.sequences, row)?,
+            ..self.template.clone()
+        })
+    }
+}
+
+pub type BatchProgressCallback = Arc<dyn Fn(usize, ExportFrameProgress) + Send + Sync + 'static>;
+pub type BatchRowCompleteCallback = Arc<dyn Fn(usize, ExportOutcome) + Send + Sync + 'static>;
+
+/// Exports running one after another on their own thread. A row that fails to fill or
+/// export is reported and the batch moves on to the next.
+pub struct BatchJob {
+    pub cancel_token: ExportCancelToken,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl BatchJob {
+    /// Starts the batch. on_progress is called with the row's index after every frame,
+    /// on_row_complete once for each row, including those skipped after cancelling.
+    pub fn spawn(
+        request: BatchRequest,
+        on_progress: BatchProgressCallback,
+        on_row_complete: BatchRowCompleteCallback,
+    ) -> Self {
+        let cancel_token = ExportCancelToken::default();
+        let thread_token = cancel_token.clone();
+
+        let handle = std::thread::spawn(move || {
+            for (index, row) in request.rows.iter().enumerate() {
+                if thread_token.is_cancelled() {
+                    on_row_complete(index, ExportOutcome::Cancelled);
+                    continue;
+                }
+
+                let row_request = match request.row_request(row) {
+                    Ok(row_request) => row_request,
+                    Err(e) => {
+                        on_row_complete(index, ExportOutcome::Error(e));
+                        continue;
+                    }
+                };
+
+                let row_progress = on_progress.clone();
+                let row_complete = on_row_complete.clone();
+                let job = ExportJob::spawn(
+                    row_request,
+                    Arc::new(move |progress| row_progress(index, progress)),
+                    Arc::new(move |outcome| row_complete(index, outcome)),
+                );
+                while !job.is_finished() {
+                    if thread_token.is_cancelled() {
+                        job.cancel();
+                    }
+                    std::thread::sleep(CANCEL_POLL_INTERVAL);
+                }
+                job.wait();
+            }
+        });
+
+        BatchJob {
+            cancel_token,
+            handle: Some(handle),
+        }
+    }
+
+    /// Stops the row being exported and skips the rest
+    pub fn cancel(&self) {
+        self.cancel_token.cancel();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.as_ref().map_or(true, |h| h.is_finished())
+    }
+
+    /// Blocks until every row has been reported
+    pub fn wait(mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.join().ok();
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::timelines::TimelineMarker;
+
+/// Ways timeline markers can be exported as chapters alongside a video
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChapterFormat {
+    Mp4Metadata, // a Nero chpl box, read by VLC, ffmpeg and most players
+    YouTubeText, // a .chapters.txt next to the video, for pasting into a description
+}
+
+/// Writes the markers out in each format, after the video has been finalized
+pub fn export_chapters(
+    video_path: &Path,
+    markers: &[TimelineMarker],
+    formats: &[ChapterFormat],
+) -> Result<(), String> {
+    if markers.is_empty() {
+        return Ok(());
+    }
+
+    for format in formats {
+        match format {
+            ChapterFormat::Mp4Metadata => add_mp4_chapters(video_path, markers)?,
+            ChapterFormat::YouTubeText => {
+                let text_path = youtube_chapters_path(video_path);
+                fs::write(&text_path, youtube_chapters(markers))
+                    .map_err(|e| format!("Couldn't write {}: {}", text_path.display(), e))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn youtube_chapters_path(video_path: &Path) -> PathBuf {
+    video_path.with_extension("chapters.txt")
+}
+
+/// One "MM:SS Label" line per marker. YouTube wants the first chapter at 00:00,
+/// so an Intro chapter is added when no marker is there.
+pub fn youtube_chapters(markers: &[TimelineMarker]) -> String {
+    let mut lines = Vec::new();
+    if markers.iter().all(|m| m.time_ms >= 1000) {
+        lines.push(format!("{} Intro", youtube_timestamp(0)));
+    }
+    for marker in markers {
+        lines.push(format!(
+            "{} {}",
+            youtube_timestamp(marker.time_ms),
+            marker.label
+        ));
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn youtube_timestamp(time_ms: i32) -> String {
+    let seconds = time_ms.max(0) / 1000;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+struct BoxHeader {
+    offset: u64,
+    size: u64,
+    kind: [u8; 4],
+}
+
+fn read_top_level_boxes(file: &mut File) -> Result<Vec<BoxHeader>, String> {
+    let file_len = file.metadata().map_err(|e| e.to_string())?.len();
+    let mut boxes = Vec::new();
+    let mut offset = 0;
+
+    while offset + 8 <= file_len {
+        let mut header = [0u8; 8];
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| file.read_exact(&mut header))
+            .map_err(|e| e.to_string())?;
+
+        let mut size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+        if size == 1 {
+            let mut large_size = [0u8; 8];
+            file.read_exact(&mut large_size)
+                .map_err(|e| e.to_string())?;
+            size = u64::from_be_bytes(large_size);
+        } else if size == 0 {
+            size = file_len - offset;
+        }
+        if size < 8 {
+            return Err(format!("Malformed MP4 box at {}", offset));
+        }
+
+        boxes.push(BoxHeader {
+            offset,
+            size,
+            kind: [header[4], header[5], header[6], header[7]],
+        });
+        offset += size;
+    }
+
+    Ok(boxes)
+}
+
+/// Child boxes of a box's payload, as (kind, whole box)
+fn child_boxes(payload: &[u8]) -> Result<Vec<([u8; 4], &[u8])>, String> {
+    let mut children = Vec::new();
+    let mut offset = 0;
+
+    while offset + 8 <= payload.len() {
+        let header = &payload[offset..offset + 8];
+        let mut size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        if size == 0 {
+            size = payload.len() - offset;
+        }
+        if size < 8 || offset + size > payload.len() {
+            return Err("Malformed MP4 movie header".to_string());
+        }
+
+        children.push((
+            [header[4], header[5], header[6], header[7]],
+            &payload[offset..offset + size],
+        ));
+        offset += size;
+    }
+
+    Ok(children)
+}
+
+fn mp4_box(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(payload.len() + 8);
+    data.extend_from_slice(&(payload.len() as u32 + 8).to_be_bytes());
+    data.extend_from_slice(kind);
+    data.extend_from_slice(payload);
+    data
+}
+
+/// Nero chapter list, with start times in 100ns units
+fn chpl_box(markers: &[TimelineMarker]) -> Vec<u8> {
+    let markers = &markers[..markers.len().min(255)];
+
+    let mut payload = vec![1, 0, 0, 0, 0, 0, 0, 0]; // version 1, flags, reserved
+    payload.push(markers.len() as u8);
+    for marker in markers {
+        let start = marker.time_ms.max(0) as u64 * 10_000;
+        payload.extend_from_slice(&start.to_be_bytes());
+
+        let mut title_len = marker.label.len().min(255);
+        while !marker.label.is_char_boundary(title_len) {
+            title_len -= 1;
+        }
+        payload.push(title_len as u8);
+        payload.extend_from_slice(&marker.label.as_bytes()[..title_len]);
+    }
+
+    mp4_box(b"chpl", &payload)
+}
+
+/// The moov box with the chapter list in its udta box, replacing any there already
+fn moov_with_chapters(moov: &[u8], chpl: &[u8]) -> Result<Vec<u8>, String> {
+    let mut payload = Vec::new();
+    let mut added = false;
+
+    for (kind, child) in child_boxes(&moov[8..])? {
+        if &kind != b"udta" {
+            payload.extend_from_slice(child);
+            continue;
+        }
+
+        let mut udta = Vec::new();
+        for (udta_kind, udta_child) in child_boxes(&child[8..])? {
+            if &udta_kind != b"chpl" {
+                udta.extend_from_slice(udta_child);
+            }
+        }
+        udta.extend_from_slice(chpl);
+        payload.extend(mp4_box(b"udta", &udta));
+        added = true;
+    }
+
+    if !added {
+        payload.extend(mp4_box(b"udta", chpl));
+    }
+
+    Ok(mp4_box(b"moov", &payload))
+}
+
+/// Adds the markers to an exported MP4 as chapters, in place
+pub fn add_mp4_chapters(path: &Path, markers: &[TimelineMarker]) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(|e| format!("Couldn't open {}: {}", path.display(), e))?;
+
+    let boxes = read_top_level_boxes(&mut file)?;
+    let moov_index = boxes
+        .iter()
+        .position(|b| &b.kind == b"moov")
+        .ok_or("Couldn't find the MP4 movie header")?;
+
+    // growing moov would move any media after it, breaking the chunk offsets pointing there
+    if boxes[moov_index + 1..].iter().any(|b| &b.kind == b"mdat") {
+        return Err("Can't add chapters when the movie header comes before the media".to_string());
+    }
+
+    let moov = &boxes[moov_index];
+    let mut moov_bytes = vec![0u8; moov.size as usize];
+    let mut rest = Vec::new();
+    file.seek(SeekFrom::Start(moov.offset))
+        .and_then(|_| file.read_exact(&mut moov_bytes))
+        .and_then(|_| file.read_to_end(&mut rest))
+        .map_err(|e| e.to_string())?;
+    if u32::from_be_bytes([moov_bytes[0], moov_bytes[1], moov_bytes[2], moov_bytes[3]]) == 1 {
+        return Err("Large MP4 movie headers aren't supported".to_string());
+    }
+
+    let new_moov = moov_with_chapters(&moov_bytes, &chpl_box(markers))?;
+
+    file.seek(SeekFrom::Start(moov.offset))
+        .and_then(|_| file.write_all(&new_moov))
+        .and_then(|_| file.write_all(&rest))
+        .and_then(|_| file.set_len(moov.offset + new_moov.len() as u64 + rest.len() as u64))
+        .map_err(|e| format!("Couldn't write chapters to {}: {}", path.display(), e))
+}
@@ -4,39 +4,232 @@ const VIDEO_WIDTH: u32 = 1920; // HD resolution
 const VIDEO_HEIGHT: u32 = 1080;
 const VIDEO_FPS: u32 = 60; // Higher framerate for smoother output
 const VIDEO_FRAME_DURATION: i64 = 10 * 1000 * 1000 / VIDEO_FPS as i64;
-const VIDEO_BIT_RATE: u32 = 5_000_000; // 5 Mbps for HD
+
+/// Which H.264 encoder an export uses
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum EncoderBackend {
+    /// The first hardware encoder found, or software when there's none
+    Auto,
+    /// NVIDIA
+    Nvenc,
+    /// Intel
+    QuickSync,
+    /// AMD
+    Amf,
+    /// Media Foundation's own H.264 encoder, which runs on the CPU and is always there
+    Software,
+}
+
+impl EncoderBackend {
+    /// PCI vendor id Media Foundation reports for the hardware encoder
+    fn vendor_id(&self) -> Option<&'static str> {
+        match self {
+            EncoderBackend::Nvenc => Some("VEN_10DE"),
+            EncoderBackend::QuickSync => Some("VEN_8086"),
+            EncoderBackend::Amf => Some("VEN_1002"),
+            EncoderBackend::Auto | EncoderBackend::Software => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum EncodeQuality {
+    Draft,    // small files for review
+    Standard, // the old fixed 5 Mbps at 1080p60
+    High,
+}
+
+impl EncodeQuality {
+    /// Bits per pixel per frame, so bitrate scales with resolution and frame rate
+    fn bits_per_pixel(&self) -> f64 {
+        match self {
+            EncodeQuality::Draft => 0.02,
+            EncodeQuality::Standard => 0.04,
+            EncodeQuality::High => 0.1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct EncoderSettings {
+    pub backend: EncoderBackend,
+    pub quality: EncodeQuality,
+    pub frame_size: (u32, u32),        // match the rendered frames
+    pub bitrate: Option<u32>,          // bits per second, overrides quality
+    pub keyframe_interval_frames: u32, // lower seeks faster in editors, higher is smaller
+}
+
+impl Default for EncoderSettings {
+    fn default() -> Self {
+        EncoderSettings {
+            backend: EncoderBackend::Auto,
+            quality: EncodeQuality::Standard,
+            frame_size: (VIDEO_WIDTH, VIDEO_HEIGHT),
+            bitrate: None,
+            keyframe_interval_frames: VIDEO_FPS * 2,
+        }
+    }
+}
+
+impl EncoderSettings {
+    pub fn bitrate(&self) -> u32 {
+        self.bitrate.unwrap_or_else(|| {
+            (self.quality.bits_per_pixel()
+                * self.frame_size.0 as f64
+                * self.frame_size.1 as f64
+                * VIDEO_FPS as f64) as u32
+        })
+    }
+}
+
+/// Hardware H.264 encoders on this machine, in Media Foundation's order of preference.
+/// Call with Media Foundation started.
+pub fn detect_hardware_encoders() -> Vec<EncoderBackend> {
+    let output_type = MFT_REGISTER_TYPE_INFO {
+        guidMajorType: MFMediaType_Video,
+        guidSubtype: MFVideoFormat_H264,
+    };
+
+    let mut activates: *mut Option<IMFActivate> = std::ptr::null_mut();
+    let mut count: u32 = 0;
+
+    let mut backends = Vec::new();
+    unsafe {
+        if MFTEnumEx(
+            MFT_CATEGORY_VIDEO_ENCODER,
+            MFT_ENUM_FLAG_HARDWARE | MFT_ENUM_FLAG_SORTANDFILTER,
+            None,
+            Some(&output_type as *const _),
+            &mut activates,
+            &mut count,
+        )
+        .is_err()
+            || activates.is_null()
+        {
+            return backends;
+        }
+
+        for i in 0..count as usize {
+            // take ownership so each activate is released
+            let Some(activate) = std::ptr::read(activates.add(i)) else {
+                continue;
+            };
+
+            let mut vendor = [0u16; 64];
+            if activate
+                .GetString(&MFT_ENUM_HARDWARE_VENDOR_ID_Attribute, &mut vendor, None)
+                .is_err()
+            {
+                continue;
+            }
+            let vendor = String::from_utf16_lossy(&vendor);
+            let vendor = vendor.trim_end_matches('\0');
+
+            let backend = [
+                EncoderBackend::Nvenc,
+                EncoderBackend::QuickSync,
+                EncoderBackend::Amf,
+            ]
+            .into_iter()
+            .find(|b| {
+                b.vendor_id()
+                    .is_some_and(|id| vendor.eq_ignore_ascii_case(id))
+            });
+
+            if let Some(backend) = backend {
+                if !backends.contains(&backend) {
+                    backends.push(backend);
+                }
+            }
+        }
+
+        CoTaskMemFree(Some(activates as *const _));
+    }
+
+    backends
+}
 
 pub struct VideoEncoder {
     sink_writer: Option<IMFSinkWriter>,
     stream_index: u32,
     frame_count: u64,
+    frame_size: (u32, u32),
+    pub backend: EncoderBackend, // the encoder in use, never Auto
 }
 
 impl VideoEncoder {
     pub fn new(output_path: &str) -> windows::core::Result<Self> {
+        Self::with_settings(output_path, EncoderSettings::default())
+    }
+
+    pub fn with_settings(
+        output_path: &str,
+        settings: EncoderSettings,
+    ) -> windows::core::Result<Self> {
         // Initialize COM and Media Foundation
         unsafe {
             CoInitializeEx(None, COINIT_MULTITHREADED).unwrap();
             MFStartup(MF_VERSION, MFSTARTUP_FULL)?;
         }
 
+        // a missing hardware encoder falls back to software rather than failing the export
+        let detected = detect_hardware_encoders();
+        let backend = match settings.backend {
+            EncoderBackend::Auto => detected
+                .first()
+                .copied()
+                .unwrap_or(EncoderBackend::Software),
+            EncoderBackend::Software => EncoderBackend::Software,
+            requested if detected.contains(&requested) => requested,
+            requested => {
+                println!("{:?} encoder not found, encoding in software", requested);
+                EncoderBackend::Software
+            }
+        };
+        println!("Encoding with {:?}", backend);
+
         let mut encoder = VideoEncoder {
             sink_writer: None,
             stream_index: 0,
             frame_count: 0,
+            frame_size: settings.frame_size,
+            backend,
         };
 
-        encoder.initialize_sink_writer(output_path)?;
+        if let Err(e) = encoder.initialize_sink_writer(output_path, &settings) {
+            if encoder.backend == EncoderBackend::Software {
+                return Err(e);
+            }
+
+            println!("Hardware encoder failed, encoding in software: {}", e);
+            encoder.backend = EncoderBackend::Software;
+            encoder.initialize_sink_writer(output_path, &settings)?;
+        }
+
         Ok(encoder)
     }
 
-    fn initialize_sink_writer(&mut self, output_path: &str) -> windows::core::Result<()> {
+    fn initialize_sink_writer(
+        &mut self,
+        output_path: &str,
+        settings: &EncoderSettings,
+    ) -> windows::core::Result<()> {
         unsafe {
+            // Media Foundation picks the hardware encoder itself, which is the first one
+            // detected, so a specific backend only takes effect on machines where it's first
+            let mut attributes: Option<IMFAttributes> = None;
+            MFCreateAttributes(&mut attributes, 1)?;
+            let attributes = attributes.expect("Couldn't get sink writer attributes");
+            attributes.SetUINT32(
+                &MF_READWRITE_ENABLE_HARDWARE_TRANSFORMS,
+                (self.backend != EncoderBackend::Software) as u32,
+            )?;
+
             // Create sink writer
             let wide_path: Vec<u16> = output_path.encode_utf16().chain(Some(0)).collect();
             // let mut sink_writer = None;
             let sink_writer =
-                MFCreateSinkWriterFromURL(PCWSTR(wide_path.as_ptr()), None, None)?;
+                MFCreateSinkWriterFromURL(PCWSTR(wide_path.as_ptr()), None, &attributes)?;
 
             // Configure output media type (H264)
             let media_type_out = {
@@ -46,13 +239,22 @@ impl VideoEncoder {
 
                 type_out.SetGUID(&MF_MT_MAJOR_TYPE, &MFMediaType_Video)?;
                 type_out.SetGUID(&MF_MT_SUBTYPE, &MFVideoFormat_H264)?;
-                type_out.SetUINT32(&MF_MT_AVG_BITRATE, VIDEO_BIT_RATE)?;
+                type_out.SetUINT32(&MF_MT_AVG_BITRATE, settings.bitrate())?;
+                type_out.SetUINT32(
+                    &MF_MT_MAX_KEYFRAME_SPACING,
+                    settings.keyframe_interval_frames.max(1),
+                )?;
                 type_out.SetUINT32(&MF_MT_INTERLACE_MODE, MFVideoInterlace_Progressive.0 as u32)?;
                 // MFSetAttributeSize(&type_out, &MF_MT_FRAME_SIZE, VIDEO_WIDTH, VIDEO_HEIGHT)?;
                 // MFSetAttributeRatio(&type_out, &MF_MT_FRAME_RATE, VIDEO_FPS, 1)?;
                 // MFSetAttributeRatio(&type_out, &MF_MT_PIXEL_ASPECT_RATIO, 1, 1)?;
 
-                mf_set_attribute_size(&type_out, &MF_MT_FRAME_SIZE, VIDEO_WIDTH, VIDEO_HEIGHT)?;
+                mf_set_attribute_size(
+                    &type_out,
+                    &MF_MT_FRAME_SIZE,
+                    self.frame_size.0,
+                    self.frame_size.1,
+                )?;
                 mf_set_attribute_ratio(&type_out, &MF_MT_FRAME_RATE, VIDEO_FPS, 1)?;
                 mf_set_attribute_ratio(&type_out, &MF_MT_PIXEL_ASPECT_RATIO, 1, 1)?;
 
@@ -77,7 +279,12 @@ impl VideoEncoder {
                 // MFSetAttributeRatio(&type_in, &MF_MT_FRAME_RATE, VIDEO_FPS, 1)?;
                 // MFSetAttributeRatio(&type_in, &MF_MT_PIXEL_ASPECT_RATIO, 1, 1)?;
 
-                mf_set_attribute_size(&type_in, &MF_MT_FRAME_SIZE, VIDEO_WIDTH, VIDEO_HEIGHT)?;
+                mf_set_attribute_size(
+                    &type_in,
+                    &MF_MT_FRAME_SIZE,
+                    self.frame_size.0,
+                    self.frame_size.1,
+                )?;
                 mf_set_attribute_ratio(&type_in, &MF_MT_FRAME_RATE, VIDEO_FPS, 1)?;
                 mf_set_attribute_ratio(&type_in, &MF_MT_PIXEL_ASPECT_RATIO, 1, 1)?;
 
@@ -96,8 +303,8 @@ impl VideoEncoder {
             let sink_writer = self.sink_writer.as_ref().unwrap();
 
             // Calculate buffer size and stride
-            let stride = VIDEO_WIDTH as u32 * 4; // 4 bytes per pixel (RGBA)
-            let buffer_size = stride * VIDEO_HEIGHT;
+            let stride = self.frame_size.0 * 4; // 4 bytes per pixel (RGBA)
+            let buffer_size = stride * self.frame_size.1;
 
             // Create and fill the media buffer
             // let mut media_buffer = None;
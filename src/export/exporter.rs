@@ -1,9 +1,27 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 use tokio::sync::mpsc::{UnboundedSender};
 
-use super::{encode::VideoEncoder, frame_buffer::FrameCaptureBuffer, pipeline::ExportPipeline};
-use crate::{animations::Sequence, editor::WindowSize, timelines::SavedTimelineStateConfig};
+use super::{
+    chapters::{export_chapters, ChapterFormat},
+    encode::{EncoderSettings, VideoEncoder},
+    frame_buffer::{FrameAccumulator, FrameCaptureBuffer},
+    pipeline::ExportPipeline,
+    subtitles::export_subtitles,
+    watermark::{Watermark, WatermarkSettings},
+};
+use crate::{
+    animations::Sequence,
+    brand_kit::BrandKit,
+    captions::{strip_caption_items, SubtitleFormat},
+    editor::WindowSize,
+    saved_state::CanvasSize,
+    timelines::SavedTimelineStateConfig,
+};
 
 // Progress message sent from export thread to UI
 #[derive(Debug, Clone)]
@@ -13,15 +31,247 @@ pub enum ExportProgress {
     Error(String),
 }
 
+/// How far along an export is, sent after every frame
+#[derive(Debug, Clone, Copy)]
+pub struct ExportFrameProgress {
+    pub frames_completed: u32,
+    pub total_frames: u32,
+    pub eta: Duration, // from the average frame time so far
+}
+
+/// How many frames a render wrote, and whether it was cancelled before the last one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderedFrames {
+    Finished(u32),
+    Cancelled(u32),
+}
+
+impl RenderedFrames {
+    pub fn count(&self) -> u32 {
+        match self {
+            RenderedFrames::Finished(count) | RenderedFrames::Cancelled(count) => *count,
+        }
+    }
+}
+
+impl ExportFrameProgress {
+    pub fn percent(&self) -> f32 {
+        if self.total_frames == 0 {
+            return 100.0;
+        }
+        self.frames_completed as f32 / self.total_frames as f32 * 100.0
+    }
+}
+
+/// How an export job ended, sent once
+#[derive(Debug, Clone)]
+pub enum ExportOutcome {
+    Complete(String), // output path
+    Cancelled,        // the partial file is removed
+    Error(String),
+}
+
+/// Shared flag that stops an export before its next frame
+#[derive(Clone, Default, Debug)]
+pub struct ExportCancelToken(Arc<AtomicBool>);
+
+impl ExportCancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Everything an export job needs, moved onto its thread
+#[derive(Clone)]
+pub struct ExportRequest {
+    pub output_path: String,
+    pub encoder_settings: EncoderSettings,
+    pub window_size: WindowSize,
+    pub sequences: Vec<Sequence>,
+    pub saved_timeline_state_config: SavedTimelineStateConfig,
+    pub video_width: u32,
+    pub video_height: u32,
+    pub total_duration_s: f64,
+    pub project_id: String,
+    pub canvas_size: CanvasSize,
+    pub chapter_formats: Vec<ChapterFormat>, // for the timeline's markers, empty for none
+    pub burn_captions: bool,                 // draws caption tracks into the frames
+    pub subtitle_formats: Vec<SubtitleFormat>, // caption files written next to the video
+    pub brand_kit: Option<BrandKit>,         // the project's, its fonts are used by text items
+    pub stamp_logo: bool,                    // draws the brand kit's logo into the frames
+    pub watermark: Option<WatermarkSettings>, // composited over every frame, not on the canvas
+}
+
+/// Shorter side of exported frames, so landscape exports are 1920x1080 and portrait 1080x1920
+pub const EXPORT_SHORT_SIDE: u32 = 1080;
+
+impl ExportRequest {
+    /// Sets the video size from the canvas, keeping its aspect ratio
+    pub fn fit_video_to_canvas(&mut self) {
+        (self.video_width, self.video_height) = self.canvas_size.export_size(EXPORT_SHORT_SIDE);
+    }
+
+    /// The sequences as drawn, without caption text items unless they're burned in
+    pub fn sequences_to_render(&self) -> Vec<Sequence> {
+        let mut sequences = self.sequences.clone();
+        if !self.burn_captions {
+            strip_caption_items(&mut sequences);
+        }
+
+        sequences
+    }
+}
+
+pub type ExportProgressCallback = Arc<dyn Fn(ExportFrameProgress) + Send + Sync + 'static>;
+pub type ExportCompleteCallback = Arc<dyn Fn(ExportOutcome) + Send + Sync + 'static>;
+
+/// An export running on its own thread, so the editor stays responsive
+pub struct ExportJob {
+    pub cancel_token: ExportCancelToken,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ExportJob {
+    /// Starts the export. on_progress is called from the export thread after every frame,
+    /// on_complete once when the export finishes, fails or is cancelled.
+    pub fn spawn(
+        request: ExportRequest,
+        on_progress: ExportProgressCallback,
+        on_complete: ExportCompleteCallback,
+    ) -> Self {
+        let cancel_token = ExportCancelToken::default();
+        let thread_token = cancel_token.clone();
+
+        let handle = std::thread::spawn(move || {
+            // COM objects in the encoder belong to the thread that made them
+            let encoder_settings = EncoderSettings {
+                frame_size: (request.video_width, request.video_height),
+                ..request.encoder_settings
+            };
+            let video_encoder =
+                match VideoEncoder::with_settings(&request.output_path, encoder_settings) {
+                    Ok(video_encoder) => video_encoder,
+                    Err(e) => {
+                        on_complete(ExportOutcome::Error(format!(
+                            "Couldn't get video encoder: {}",
+                            e
+                        )));
+                        return;
+                    }
+                };
+            let mut exporter = Exporter {
+                video_encoder,
+                canvas_size: request.canvas_size,
+                brand_kit: request.brand_kit.clone(),
+                stamp_logo: request.stamp_logo,
+                watermark: request.watermark.clone(),
+            };
+            let markers = request.saved_timeline_state_config.markers.clone();
+
+            let result = pollster::block_on(exporter.render(
+                request.window_size,
+                request.sequences_to_render(),
+                request.saved_timeline_state_config.clone(),
+                request.video_width,
+                request.video_height,
+                request.total_duration_s,
+                request.project_id,
+                &thread_token,
+                &mut |progress| on_progress(progress),
+            ));
+
+            // finalizes the file
+            drop(exporter);
+
+            let outcome = match result {
+                Ok(RenderedFrames::Cancelled(_)) => {
+                    std::fs::remove_file(&request.output_path).ok();
+                    ExportOutcome::Cancelled
+                }
+                Ok(RenderedFrames::Finished(_)) => match export_chapters(
+                    Path::new(&request.output_path),
+                    &markers,
+                    &request.chapter_formats,
+                )
+                .map_err(|e| format!("Couldn't export chapters: {}", e))
+                .and_then(|_| {
+                    export_subtitles(
+                        Path::new(&request.output_path),
+                        &request.sequences,
+                        &request.saved_timeline_state_config,
+                        &request.subtitle_formats,
+                    )
+                    .map_err(|e| format!("Couldn't export subtitles: {}", e))
+                }) {
+                    Ok(()) => ExportOutcome::Complete(request.output_path),
+                    Err(e) => ExportOutcome::Error(e),
+                },
+                Err(e) => {
+                    std::fs::remove_file(&request.output_path).ok();
+                    ExportOutcome::Error(e)
+                }
+            };
+            on_complete(outcome);
+        });
+
+        ExportJob {
+            cancel_token,
+            handle: Some(handle),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel_token.cancel();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.as_ref().map_or(true, |h| h.is_finished())
+    }
+
+    /// Blocks until the export thread exits, after on_complete has been called
+    pub fn wait(mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.join().ok();
+        }
+    }
+}
+
 pub struct Exporter {
     pub video_encoder: VideoEncoder,
+    pub canvas_size: CanvasSize,     // the project's, read when running
+    pub brand_kit: Option<BrandKit>, // read when running
+    pub stamp_logo: bool,
+    pub watermark: Option<WatermarkSettings>,
 }
 
 impl Exporter {
     pub fn new(output_path: &str) -> Self {
         println!("Preparing video encoder...");
         let video_encoder = VideoEncoder::new(output_path).expect("Couldn't get video encoder");
-        Exporter { video_encoder }
+        Exporter {
+            video_encoder,
+            canvas_size: CanvasSize::default(),
+            brand_kit: None,
+            stamp_logo: false,
+            watermark: None,
+        }
+    }
+
+    pub fn with_settings(output_path: &str, settings: EncoderSettings) -> Self {
+        println!("Preparing video encoder...");
+        let video_encoder =
+            VideoEncoder::with_settings(output_path, settings).expect("Couldn't get video encoder");
+        Exporter {
+            video_encoder,
+            canvas_size: CanvasSize::default(),
+            brand_kit: None,
+            stamp_logo: false,
+            watermark: None,
+        }
     }
 
     pub async fn run(
@@ -35,50 +285,176 @@ impl Exporter {
         progress_tx: UnboundedSender<ExportProgress>,
         project_id: String,
     ) -> Result<Arc<u32>, String> {
-        println!("Preparing wgpu pipeline...");
-        let mut wgpu_pipeline = ExportPipeline::new();
-        wgpu_pipeline
-            .initialize(
+        let total_frames = self
+            .render(
                 window_size,
                 sequences,
                 saved_timeline_state_config,
                 video_width,
                 video_height,
+                total_duration_s,
                 project_id,
+                &ExportCancelToken::default(),
+                &mut |progress| {
+                    // Send progress updates every 60 frames
+                    if progress.frames_completed % 60 == 1 {
+                        println!("export progress {:?}", progress.percent());
+                        progress_tx
+                            .send(ExportProgress::Progress(progress.percent()))
+                            .ok();
+                    }
+                },
             )
-            .await;
+            .await?;
 
-        println!("Preparing frame buffer...");
-        let frame_buffer = FrameCaptureBuffer::new(
-            &wgpu_pipeline
-                .gpu_resources
-                .as_ref()
-                .expect("Couldn't get gpu resources")
-                .device,
+        Ok(Arc::new(total_frames.count()))
+    }
+
+    /// Renders and encodes every frame, stopping early when cancelled.
+    async fn render(
+        &mut self,
+        window_size: WindowSize,
+        sequences: Vec<Sequence>,
+        saved_timeline_state_config: SavedTimelineStateConfig,
+        video_width: u32,
+        video_height: u32,
+        total_duration_s: f64,
+        project_id: String,
+        cancel_token: &ExportCancelToken,
+        on_frame: &mut dyn FnMut(ExportFrameProgress),
+    ) -> Result<RenderedFrames, String> {
+        let video_encoder = &mut self.video_encoder;
+
+        render_frames(
+            self.canvas_size,
+            self.brand_kit.as_ref(),
+            self.stamp_logo,
+            self.watermark.as_ref(),
+            window_size,
+            sequences,
+            saved_timeline_state_config,
             video_width,
             video_height,
-        );
-        wgpu_pipeline.frame_buffer = Some(frame_buffer);
-
-        // Calculate total frames based on sequence duration
-        const FPS: f64 = 60.0;
-        // let total_duration = sequences.iter()
-        //     .map(|seq| seq.duration)
-        //     .sum::<f64>();
-        let total_frames = (total_duration_s * FPS).ceil() as u32;
-
-        println!(
-            "total_frames {:?}, total_duration_s: {:?}",
-            total_frames, total_duration_s
-        );
-
-        // Frame loop
-        for frame_index in 0..total_frames {
-            // Calculate current time position
-            let current_time = frame_index as f64 / FPS;
+            total_duration_s,
+            project_id,
+            cancel_token,
+            &mut |frame_bytes| {
+                video_encoder
+                    .write_frame(frame_bytes)
+                    .map_err(|e| e.to_string())
+            },
+            on_frame,
+        )
+        .await
+    }
+}
+
+/// Renders every frame at 60fps without a window, handing each to write_frame as BGRA
+/// bytes, and stopping early when cancelled.
+pub async fn render_frames(
+    canvas_size: CanvasSize,
+    brand_kit: Option<&BrandKit>,
+    stamp_logo: bool,
+    watermark: Option<&WatermarkSettings>,
+    window_size: WindowSize,
+    sequences: Vec<Sequence>,
+    saved_timeline_state_config: SavedTimelineStateConfig,
+    video_width: u32,
+    video_height: u32,
+    total_duration_s: f64,
+    project_id: String,
+    cancel_token: &ExportCancelToken,
+    write_frame: &mut dyn FnMut(&[u8]) -> Result<(), String>,
+    on_frame: &mut dyn FnMut(ExportFrameProgress),
+) -> Result<RenderedFrames, String> {
+    println!("Preparing wgpu pipeline...");
+    let has_motion_blur = sequences
+        .iter()
+        .flat_map(|s| &s.polygon_motion_paths)
+        .any(|a| a.motion_blur > 0);
+
+    let mut wgpu_pipeline = ExportPipeline::new();
+    wgpu_pipeline.canvas_size = canvas_size;
+    wgpu_pipeline.brand_kit = brand_kit.cloned();
+    wgpu_pipeline
+        .initialize(
+            window_size,
+            sequences,
+            saved_timeline_state_config,
+            video_width,
+            video_height,
+            project_id,
+        )
+        .await?;
+
+    println!("Preparing frame buffer...");
+    let frame_buffer = FrameCaptureBuffer::new(
+        &wgpu_pipeline
+            .gpu_resources
+            .as_ref()
+            .expect("Couldn't get gpu resources")
+            .device,
+        video_width,
+        video_height,
+    );
+    wgpu_pipeline.frame_buffer = Some(frame_buffer);
+
+    // Calculate total frames based on sequence duration
+    const FPS: f64 = 60.0;
+    // let total_duration = sequences.iter()
+    //     .map(|seq| seq.duration)
+    //     .sum::<f64>();
+    let total_frames = (total_duration_s * FPS).ceil() as u32;
+
+    println!(
+        "total_frames {:?}, total_duration_s: {:?}",
+        total_frames, total_duration_s
+    );
+
+    // each sample is rendered a fraction of a frame earlier, ending on the frame itself
+    let blur_samples = if has_motion_blur {
+        wgpu_pipeline.motion_blur_samples.max(1)
+    } else {
+        1
+    };
+    let mut accumulator = FrameAccumulator::new();
+
+    // rendered once, using the export editor's fonts so brand kit fonts work
+    let logo_watermark = brand_kit
+        .filter(|_| stamp_logo)
+        .and_then(|b| b.logo_watermark());
+    let font_manager = &wgpu_pipeline
+        .export_editor
+        .as_ref()
+        .expect("Couldn't get export editor")
+        .scene
+        .font_manager;
+    let watermarks = watermark
+        .into_iter()
+        .chain(logo_watermark.as_ref())
+        .map(|settings| Watermark::prepare(settings, font_manager, video_width, video_height))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Frame loop
+    let started = Instant::now();
+    for frame_index in 0..total_frames {
+        if cancel_token.is_cancelled() {
+            println!("Export cancelled at frame {:?}", frame_index);
+            return Ok(RenderedFrames::Cancelled(frame_index));
+        }
+
+        // Calculate current time position
+        let current_time = frame_index as f64 / FPS;
+
+        let mut frame_bytes = Vec::new();
+        for sample in 0..blur_samples {
+            let remaining = (blur_samples - 1 - sample) as f64 / blur_samples as f64;
+            wgpu_pipeline.set_motion_blur_shift(Duration::from_secs_f64(remaining / FPS));
 
             // Render frame
-            wgpu_pipeline.render_frame(current_time);
+            wgpu_pipeline
+                .render_frame(current_time)
+                .map_err(|e| format!("Couldn't render frame {}: {}", frame_index, e))?;
 
             // Get frame buffer and extract data
             let frame_buffer = wgpu_pipeline
@@ -86,7 +462,7 @@ impl Exporter {
                 .as_ref()
                 .expect("Couldn't get frame buffer");
 
-            let frame_bytes = frame_buffer
+            let sample_bytes = frame_buffer
                 .get_frame_data(
                     &wgpu_pipeline
                         .gpu_resources
@@ -96,21 +472,32 @@ impl Exporter {
                 )
                 .await;
 
-            // Write frame to video
-            self.video_encoder
-                .write_frame(&frame_bytes)
-                .expect("Couldn't write frame");
-
-            // Send progress updates every 60 frames
-            if frame_index % 60 == 0 {
-                let progress = (frame_index as f32 / total_frames as f32) * 100.0;
-                println!("export progress {:?}", progress);
-                progress_tx.send(ExportProgress::Progress(progress)).ok();
+            if blur_samples > 1 {
+                accumulator.add(&sample_bytes);
+            } else {
+                frame_bytes = sample_bytes;
             }
         }
+        if blur_samples > 1 {
+            frame_bytes = accumulator.finish();
+        }
+        for watermark in &watermarks {
+            watermark.stamp(&mut frame_bytes, video_width);
+        }
 
-        println!("Export finished!");
+        write_frame(&frame_bytes)
+            .map_err(|e| format!("Couldn't write frame {}: {}", frame_index, e))?;
 
-        Ok(Arc::new(total_frames))
+        let frames_completed = frame_index + 1;
+        let per_frame = started.elapsed() / frames_completed;
+        on_frame(ExportFrameProgress {
+            frames_completed,
+            total_frames,
+            eta: per_frame * (total_frames - frames_completed),
+        });
     }
+
+    println!("Export finished!");
+
+    Ok(RenderedFrames::Finished(total_frames))
 }
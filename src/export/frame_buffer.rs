@@ -126,6 +126,48 @@ impl FrameCaptureBuffer {
     }
 }
 
+/// Averages several renders of a frame, for motion blur
+pub struct FrameAccumulator {
+    sums: Vec<u32>,
+    count: u32,
+}
+
+impl FrameAccumulator {
+    pub fn new() -> Self {
+        FrameAccumulator {
+            sums: Vec::new(),
+            count: 0,
+        }
+    }
+
+    pub fn add(&mut self, frame: &[u8]) {
+        if self.sums.len() != frame.len() {
+            self.sums = vec![0; frame.len()];
+            self.count = 0;
+        }
+
+        for (sum, value) in self.sums.iter_mut().zip(frame) {
+            *sum += *value as u32;
+        }
+        self.count += 1;
+    }
+
+    /// The averaged frame, leaving the accumulator empty for the next one
+    pub fn finish(&mut self) -> Vec<u8> {
+        let count = self.count.max(1);
+        let frame = self
+            .sums
+            .iter()
+            .map(|sum| ((sum + count / 2) / count) as u8)
+            .collect();
+
+        self.sums.iter_mut().for_each(|sum| *sum = 0);
+        self.count = 0;
+
+        frame
+    }
+}
+
 // Helper struct for buffer dimensions
 #[derive(Debug)]
 struct BufferDimensions {
@@ -0,0 +1,126 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+
+use super::exporter::{
+    render_frames, ExportCancelToken, ExportFrameProgress, ExportRequest, RenderedFrames,
+};
+
+/// Frames are rendered at this rate, and GIFs keep every few of them
+const RENDER_FPS: u32 = 60;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct GifSettings {
+    pub frame_rate: u32, // divisors of 60 keep even timing, GIFs above 30 play back unevenly
+    pub speed: i32,      // 1 is the best colors and slowest, 30 the fastest
+    pub looping: bool,
+}
+
+impl Default for GifSettings {
+    fn default() -> Self {
+        GifSettings {
+            frame_rate: 15,
+            speed: 10,
+            looping: true,
+        }
+    }
+}
+
+/// Writes rendered frames into an animated GIF, dropping the ones between its frame rate
+pub struct GifWriter {
+    encoder: GifEncoder<BufWriter<File>>,
+    frame_size: (u32, u32),
+    frame_step: u32,
+    frames_seen: u32,
+}
+
+impl GifWriter {
+    pub fn create(
+        path: &Path,
+        frame_size: (u32, u32),
+        settings: GifSettings,
+    ) -> Result<Self, String> {
+        let file =
+            File::create(path).map_err(|e| format!("Couldn't create {}: {}", path.display(), e))?;
+        let mut encoder =
+            GifEncoder::new_with_speed(BufWriter::new(file), settings.speed.clamp(1, 30));
+        if settings.looping {
+            encoder
+                .set_repeat(Repeat::Infinite)
+                .map_err(|e| format!("Couldn't set GIF looping: {}", e))?;
+        }
+
+        Ok(GifWriter {
+            encoder,
+            frame_size,
+            frame_step: (RENDER_FPS / settings.frame_rate.clamp(1, RENDER_FPS)).max(1),
+            frames_seen: 0,
+        })
+    }
+
+    /// Takes every rendered frame as BGRA, like the video encoder
+    pub fn write_frame(&mut self, frame_data: &[u8]) -> Result<(), String> {
+        let frame_index = self.frames_seen;
+        self.frames_seen += 1;
+        if frame_index % self.frame_step != 0 {
+            return Ok(());
+        }
+
+        let mut rgba = frame_data.to_vec();
+        for pixel in rgba.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+        let image = RgbaImage::from_raw(self.frame_size.0, self.frame_size.1, rgba)
+            .ok_or("Frame doesn't match the GIF size")?;
+        let delay = Delay::from_numer_denom_ms(self.frame_step * 1000, RENDER_FPS);
+
+        self.encoder
+            .encode_frame(Frame::from_parts(image, 0, 0, delay))
+            .map_err(|e| format!("Couldn't write GIF frame {}: {}", frame_index, e))
+    }
+}
+
+/// Renders the request into an animated GIF at its output path, blocking until done.
+/// The request's encoder settings, chapters and subtitle files don't apply.
+pub fn export_gif(
+    request: ExportRequest,
+    settings: GifSettings,
+    cancel_token: &ExportCancelToken,
+    on_progress: &mut dyn FnMut(ExportFrameProgress),
+) -> Result<(), String> {
+    let output_path = Path::new(&request.output_path);
+    let mut writer = GifWriter::create(
+        output_path,
+        (request.video_width, request.video_height),
+        settings,
+    )?;
+
+    let result = pollster::block_on(render_frames(
+        request.canvas_size,
+        request.brand_kit.as_ref(),
+        request.stamp_logo,
+        request.watermark.as_ref(),
+        request.window_size,
+        request.sequences_to_render(),
+        request.saved_timeline_state_config,
+        request.video_width,
+        request.video_height,
+        request.total_duration_s,
+        request.project_id,
+        cancel_token,
+        &mut |frame_data| writer.write_frame(frame_data),
+        on_progress,
+    ));
+
+    // writes the trailer
+    drop(writer);
+
+    if !matches!(result, Ok(RenderedFrames::Finished(_))) {
+        std::fs::remove_file(output_path).ok();
+    }
+
+    result.map(|_| ())
+}
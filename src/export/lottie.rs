@@ -0,0 +1,985 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::animations::{
+    AnimationData, AnimationProperty, BackgroundFill, EasingType, KeyType, KeyframeValue,
+    ObjectType, Sequence, UIKeyframe,
+};
+use crate::blend::BlendMode;
+use crate::editor::PathType;
+use crate::masking::MaskMode;
+use crate::polygon::{SavedPoint, SavedPolygonConfig, SavedStroke};
+use crate::saved_state::CanvasSize;
+use crate::text_due::SavedTextRendererConfig;
+
+/// Frame rate of exported animations. Keyframes are placed at the nearest frame.
+pub const LOTTIE_FRAME_RATE: f32 = 60.0;
+
+/// Bodymovin version the output follows
+const LOTTIE_VERSION: &str = "5.7.4";
+
+/// Converts a sequence's polygons and text, with their position, scale, rotation
+/// and opacity keyframes, into Lottie JSON. Images, videos and other effects are left out.
+pub fn sequence_to_lottie(sequence: &Sequence, canvas_size: CanvasSize) -> Value {
+    let out_frame = ms_to_frame(sequence.duration_ms).max(1.0);
+
+    // Lottie draws the first layer on top, while higher layers are on top here
+    let mut objects: Vec<(i32, Value)> = Vec::new();
+    for polygon in sequence.active_polygons.iter().filter(|p| !p.hidden) {
+        let animation = find_animation(sequence, &polygon.id, ObjectType::Polygon);
+        objects.push((polygon.layer, polygon_layer(polygon, animation, out_frame)));
+    }
+    for text in sequence.active_text_items.iter().filter(|t| !t.hidden) {
+        let animation = find_animation(sequence, &text.id, ObjectType::TextItem);
+        objects.push((text.layer, text_layer(text, animation, out_frame)));
+    }
+    objects.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let mut layers: Vec<Value> = objects.into_iter().map(|(_, layer)| layer).collect();
+    if let Some(BackgroundFill::Color(color)) = &sequence.background_fill {
+        layers.push(background_layer(color, canvas_size, out_frame));
+    }
+    for (index, layer) in layers.iter_mut().enumerate() {
+        layer["ind"] = json!(index + 1);
+    }
+
+    let fonts: Vec<Value> = unique_fonts(&sequence.active_text_items)
+        .into_iter()
+        .map(|family| {
+            json!({
+                "fName": family,
+                "fFamily": family,
+                "fStyle": "Regular",
+                "ascent": 75,
+            })
+        })
+        .collect();
+
+    json!({
+        "v": LOTTIE_VERSION,
+        "nm": sequence.name,
+        "fr": LOTTIE_FRAME_RATE,
+        "ip": 0,
+        "op": out_frame,
+        "w": canvas_size.width,
+        "h": canvas_size.height,
+        "ddd": 0,
+        "assets": [],
+        "fonts": { "list": fonts },
+        "layers": layers,
+    })
+}
+
+/// Writes a sequence out as a .json Lottie file
+pub fn export_lottie(
+    path: &Path,
+    sequence: &Sequence,
+    canvas_size: CanvasSize,
+) -> Result<(), String> {
+    let lottie = sequence_to_lottie(sequence, canvas_size);
+    let content = serde_json::to_string(&lottie)
+        .map_err(|e| format!("Couldn't serialize Lottie animation: {}", e))?;
+
+    fs::write(path, content).map_err(|e| format!("Couldn't write {}: {}", path.display(), e))
+}
+
+fn ms_to_frame(time_ms: i32) -> f32 {
+    (time_ms as f32 * LOTTIE_FRAME_RATE / 1000.0).round()
+}
+
+fn find_animation<'a>(
+    sequence: &'a Sequence,
+    object_id: &str,
+    object_type: ObjectType,
+) -> Option<&'a AnimationData> {
+    sequence
+        .polygon_motion_paths
+        .iter()
+        .find(|a| a.polygon_id == object_id && a.object_type == object_type)
+}
+
+fn unique_fonts(text_items: &[SavedTextRendererConfig]) -> Vec<String> {
+    let mut fonts: Vec<String> = Vec::new();
+    for text in text_items {
+        if !fonts.contains(&text.font_family) {
+            fonts.push(text.font_family.clone());
+        }
+    }
+    fonts
+}
+
+/// Colors are 0-255 here and 0-1 in Lottie
+fn lottie_color(color: &[i32; 4]) -> Value {
+    json!([
+        color[0] as f32 / 255.0,
+        color[1] as f32 / 255.0,
+        color[2] as f32 / 255.0,
+        1
+    ])
+}
+
+fn alpha_percent(color: &[i32; 4]) -> f32 {
+    color[3] as f32 / 255.0 * 100.0
+}
+
+fn static_value(value: Value) -> Value {
+    json!({ "a": 0, "k": value })
+}
+
+/// Bezier handles approximating each easing curve
+fn easing_handles(easing: &EasingType) -> (Value, Value) {
+    let (out_x, out_y, in_x, in_y) = match easing {
+        EasingType::Linear => (0.0, 0.0, 1.0, 1.0),
+        EasingType::EaseIn => (0.11, 0.0, 0.5, 0.0),
+        EasingType::EaseOut => (0.5, 1.0, 0.89, 1.0),
+        EasingType::EaseInOut => (0.45, 0.0, 0.55, 1.0),
+        // overshoots once, without the spring's later bounces
+        EasingType::Spring(_) => (0.35, 1.3, 0.6, 1.0),
+    };
+    (
+        json!({ "x": [out_x], "y": [out_y] }),
+        json!({ "x": [in_x], "y": [in_y] }),
+    )
+}
+
+/// One animated property, keyed at (frame, value, easing into the next key)
+fn animated_value(mut keys: Vec<(f32, Value, EasingType)>, fallback: Value) -> Value {
+    keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+    keys.dedup_by(|next, prev| next.0 == prev.0);
+
+    match keys.len() {
+        0 => static_value(fallback),
+        1 => static_value(keys.remove(0).1),
+        _ => {
+            let last = keys.len() - 1;
+            let keyframes: Vec<Value> = keys
+                .iter()
+                .enumerate()
+                .map(|(index, (frame, value, easing))| {
+                    if index == last {
+                        return json!({ "t": frame, "s": value });
+                    }
+                    let (out_handle, in_handle) = easing_handles(easing);
+                    json!({ "t": frame, "s": value, "o": out_handle, "i": in_handle })
+                })
+                .collect();
+            json!({ "a": 1, "k": keyframes })
+        }
+    }
+}
+
+/// The layer transform from the object's resting state and its motion path
+fn transform(
+    animation: Option<&AnimationData>,
+    position: [i32; 2],
+    anchor: [f32; 2],
+    base_opacity: i32,
+) -> Value {
+    let mut positions = Vec::new();
+    let mut scales = Vec::new();
+    let mut rotations = Vec::new();
+    let mut opacities = Vec::new();
+
+    if let Some(animation) = animation {
+        let group = animation.position;
+        let keyframes = animation.properties.iter().flat_map(|p| p.keyframes.iter());
+
+        for keyframe in keyframes {
+            let start_ms = animation.start_time_ms + keyframe.time.as_millis() as i32;
+            let times = match &keyframe.key_type {
+                KeyType::Frame => vec![(start_ms, keyframe.easing.clone())],
+                // a range holds its value until its end time
+                KeyType::Range(range) => vec![
+                    (start_ms, EasingType::Linear),
+                    (
+                        animation.start_time_ms + range.end_time.as_millis() as i32,
+                        keyframe.easing.clone(),
+                    ),
+                ],
+            };
+
+            for (time_ms, easing) in times {
+                let frame = ms_to_frame(time_ms);
+                match &keyframe.value {
+                    KeyframeValue::Position(p) => positions.push((
+                        frame,
+                        json!([p[0] + group[0], p[1] + group[1], 0]),
+                        easing,
+                    )),
+                    KeyframeValue::Scale(s) => scales.push((frame, json!([s, s, 100]), easing)),
+                    KeyframeValue::ScaleXY([x, y]) => {
+                        scales.push((frame, json!([x, y, 100]), easing))
+                    }
+                    KeyframeValue::Rotation(r) => rotations.push((frame, json!(r), easing)),
+                    KeyframeValue::Opacity(o) => opacities.push((
+                        frame,
+                        json!(*o as f32 * base_opacity as f32 / 100.0),
+                        easing,
+                    )),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    json!({
+        "a": static_value(json!([anchor[0], anchor[1], 0])),
+        "p": animated_value(positions, json!([position[0], position[1], 0])),
+        "s": animated_value(scales, json!([100, 100, 100])),
+        "r": animated_value(rotations, json!(0)),
+        "o": animated_value(opacities, json!(base_opacity)),
+    })
+}
+
+fn base_layer(name: &str, kind: i32, out_frame: f32, transform: Value) -> Value {
+    json!({
+        "ddd": 0,
+        "ind": 0,
+        "ty": kind,
+        "nm": name,
+        "sr": 1,
+        "ks": transform,
+        "ao": 0,
+        "ip": 0,
+        "op": out_frame,
+        "st": 0,
+        "bm": 0,
+    })
+}
+
+/// A shape layer with a rounded rectangle centered on its position
+fn polygon_layer(
+    polygon: &SavedPolygonConfig,
+    animation: Option<&AnimationData>,
+    out_frame: f32,
+) -> Value {
+    let transform = transform(
+        animation,
+        [polygon.position.x, polygon.position.y],
+        [0.0, 0.0],
+        polygon.opacity,
+    );
+
+    // earlier items draw on top, so the stroke goes before the fill
+    let mut items = vec![json!({
+        "ty": "rc",
+        "nm": "Rectangle",
+        "d": 1,
+        "p": static_value(json!([0, 0])),
+        "s": static_value(json!([polygon.dimensions.0, polygon.dimensions.1])),
+        "r": static_value(json!(polygon.border_radius)),
+    })];
+    if polygon.stroke.thickness > 0 {
+        items.push(json!({
+            "ty": "st",
+            "nm": "Stroke",
+            "c": static_value(lottie_color(&polygon.stroke.fill)),
+            "o": static_value(json!(alpha_percent(&polygon.stroke.fill))),
+            "w": static_value(json!(polygon.stroke.thickness)),
+            "lc": 2,
+            "lj": 2,
+        }));
+    }
+    items.push(json!({
+        "ty": "fl",
+        "nm": "Fill",
+        "c": static_value(lottie_color(&polygon.fill)),
+        "o": static_value(json!(alpha_percent(&polygon.fill))),
+        "r": 1,
+    }));
+    items.push(json!({
+        "ty": "tr",
+        "p": static_value(json!([0, 0])),
+        "a": static_value(json!([0, 0])),
+        "s": static_value(json!([100, 100])),
+        "r": static_value(json!(0)),
+        "o": static_value(json!(100)),
+    }));
+
+    let mut layer = base_layer(&polygon.name, 4, out_frame, transform);
+    layer["shapes"] = json!([{ "ty": "gr", "nm": polygon.name, "it": items }]);
+    layer
+}
+
+/// A text layer centered on its position like the text renderer. Lottie text has no
+/// background, and players substitute the font if it isn't installed.
+fn text_layer(
+    text: &SavedTextRendererConfig,
+    animation: Option<&AnimationData>,
+    out_frame: f32,
+) -> Value {
+    let font_size = text.font_size as f32;
+    // Lottie places text on its baseline, so lift the anchor to the middle of the line
+    let transform = transform(
+        animation,
+        [text.position.x, text.position.y],
+        [0.0, -font_size * 0.35],
+        text.opacity,
+    );
+
+    let document = json!({
+        "s": font_size,
+        "f": text.font_family,
+        "t": text.text.replace('\n', "\r"),
+        "j": 2, // centered
+        "tr": 0,
+        "lh": font_size * 1.2,
+        "ls": 0,
+        "fc": lottie_color(&text.color),
+    });
+
+    let mut layer = base_layer(&text.name, 5, out_frame, transform);
+    layer["t"] = json!({
+        "d": { "k": [{ "s": document, "t": 0 }] },
+        "p": {},
+        "m": { "g": 1, "a": static_value(json!([0, 0])) },
+        "a": [],
+    });
+    layer
+}
+
+/// A solid layer filling the canvas behind everything
+fn background_layer(color: &[i32; 4], canvas_size: CanvasSize, out_frame: f32) -> Value {
+    let hex = format!(
+        "#{:02x}{:02x}{:02x}",
+        color[0].clamp(0, 255),
+        color[1].clamp(0, 255),
+        color[2].clamp(0, 255)
+    );
+    let transform = json!({
+        "a": static_value(json!([0, 0, 0])),
+        "p": static_value(json!([0, 0, 0])),
+        "s": static_value(json!([100, 100, 100])),
+        "r": static_value(json!(0)),
+        "o": static_value(json!(alpha_percent(color))),
+    });
+
+    let mut layer = base_layer("Background", 1, out_frame, transform);
+    layer["sc"] = json!(hex);
+    layer["sw"] = json!(canvas_size.width);
+    layer["sh"] = json!(canvas_size.height);
+    layer
+}
+
+/// A Lottie property's keys as (frame, value, easing into the next key)
+type LottieKeys = Vec<(f32, Vec<f32>, EasingType)>;
+
+/// Frame timing of a Lottie file, for turning its frames into sequence time
+struct LottieTiming {
+    frame_rate: f32,
+    in_frame: f32,
+}
+
+impl LottieTiming {
+    fn to_ms(&self, frame: f32) -> u64 {
+        ((frame - self.in_frame) / self.frame_rate * 1000.0).max(0.0) as u64
+    }
+}
+
+/// Fits the Lottie canvas into the project canvas, centered
+struct LottiePlacement {
+    scale: f32,
+    offset: [f32; 2],
+}
+
+impl LottiePlacement {
+    fn point(&self, x: f32, y: f32) -> [i32; 2] {
+        [
+            (x * self.scale + self.offset[0]).round() as i32,
+            (y * self.scale + self.offset[1]).round() as i32,
+        ]
+    }
+
+    fn size(&self, size: f32) -> i32 {
+        (size * self.scale).round() as i32
+    }
+}
+
+/// Reads a Lottie file into a new sequence, see `lottie_to_sequence`
+pub fn import_lottie(path: &Path, canvas_size: CanvasSize) -> Result<Sequence, String> {
+    let content = fs::read(path).map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+    let lottie: Value = serde_json::from_slice(&content)
+        .map_err(|e| format!("Couldn't parse Lottie file {}: {}", path.display(), e))?;
+
+    lottie_to_sequence(&lottie, canvas_size)
+}
+
+/// Builds a sequence from a Lottie animation's rectangle, ellipse, solid and text layers,
+/// keyframing their position, scale, rotation and opacity. Easing curves become the
+/// closest easing type. Other layers, parenting and in/out points are left out.
+pub fn lottie_to_sequence(lottie: &Value, canvas_size: CanvasSize) -> Result<Sequence, String> {
+    let layers = lottie["layers"]
+        .as_array()
+        .ok_or("Lottie file has no layers")?;
+
+    let frame_rate = lottie["fr"].as_f64().unwrap_or(LOTTIE_FRAME_RATE as f64) as f32;
+    if frame_rate <= 0.0 {
+        return Err("Lottie file has an invalid frame rate".to_string());
+    }
+    let timing = LottieTiming {
+        frame_rate,
+        in_frame: lottie["ip"].as_f64().unwrap_or(0.0) as f32,
+    };
+    let out_frame = lottie["op"].as_f64().unwrap_or(0.0) as f32;
+    let duration_ms = timing.to_ms(out_frame).max(1) as i32;
+
+    let (width, height) = (
+        lottie["w"].as_f64().unwrap_or(canvas_size.width as f64) as f32,
+        lottie["h"].as_f64().unwrap_or(canvas_size.height as f64) as f32,
+    );
+    let scale = (canvas_size.width as f32 / width.max(1.0))
+        .min(canvas_size.height as f32 / height.max(1.0));
+    let placement = LottiePlacement {
+        scale,
+        offset: [
+            (canvas_size.width as f32 - width * scale) / 2.0,
+            (canvas_size.height as f32 - height * scale) / 2.0,
+        ],
+    };
+
+    let mut sequence = Sequence {
+        id: Uuid::new_v4().to_string(),
+        name: lottie["nm"].as_str().unwrap_or("Lottie Import").to_string(),
+        background_fill: None,
+        duration_ms,
+        active_polygons: Vec::new(),
+        polygon_motion_paths: Vec::new(),
+        active_text_items: Vec::new(),
+        active_image_items: Vec::new(),
+        active_video_items: Vec::new(),
+        tags: Vec::new(),
+        outlined_text_items: Vec::new(),
+        active_groups: Vec::new(),
+        active_particle_emitters: Vec::new(),
+        camera_animation: None,
+        active_callouts: Vec::new(),
+        active_spotlights: Vec::new(),
+        caption_tracks: Vec::new(),
+        constraints: Vec::new(),
+    };
+
+    for (index, layer) in layers.iter().enumerate() {
+        // the first Lottie layer is drawn on top
+        let layer_index = (layers.len() - index) as i32;
+        let name = layer["nm"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Layer {}", index + 1));
+        let id = Uuid::new_v4().to_string();
+
+        match layer["ty"].as_i64() {
+            // a solid filling the canvas at the bottom is the background
+            Some(1) if index == layers.len() - 1 && solid_fills_canvas(layer, width, height) => {
+                sequence.background_fill = Some(BackgroundFill::Color(hex_color(
+                    layer["sc"].as_str().unwrap_or("#000000"),
+                )));
+            }
+            Some(1) | Some(4) => {
+                let Some(shape) = lottie_shape(layer) else {
+                    println!(
+                        "Skipping Lottie layer {}, it has no rectangle or ellipse",
+                        name
+                    );
+                    continue;
+                };
+                let animation = layer_animation(
+                    layer,
+                    &id,
+                    ObjectType::Polygon,
+                    shape.center,
+                    &timing,
+                    &placement,
+                    duration_ms,
+                );
+                sequence.active_polygons.push(imported_polygon(
+                    id,
+                    name,
+                    layer_index,
+                    &animation,
+                    &shape,
+                    &placement,
+                ));
+                sequence.polygon_motion_paths.push(animation);
+            }
+            Some(5) => {
+                let document = &layer["t"]["d"]["k"][0]["s"];
+                let font_size = document["s"].as_f64().unwrap_or(24.0) as f32;
+                let text = document["t"].as_str().unwrap_or("").replace('\r', "\n");
+                let color = lottie_to_color(&numbers(&document["fc"]), 100.0);
+
+                // the text renderer centers on its position, Lottie sits on the baseline
+                let center = [0.0, -font_size * 0.35];
+                let animation = layer_animation(
+                    layer,
+                    &id,
+                    ObjectType::TextItem,
+                    center,
+                    &timing,
+                    &placement,
+                    duration_ms,
+                );
+                let position = initial_position(&animation);
+                let font_size = placement.size(font_size).max(1);
+                let longest_line = text.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+
+                sequence.active_text_items.push(SavedTextRendererConfig {
+                    id,
+                    name,
+                    dimensions: (
+                        (longest_line as i32 * font_size * 3 / 5).max(font_size),
+                        text.lines().count().max(1) as i32 * font_size * 6 / 5,
+                    ),
+                    text,
+                    font_family: document["f"].as_str().unwrap_or("Aleo").to_string(),
+                    font_size,
+                    position: SavedPoint {
+                        x: position[0],
+                        y: position[1],
+                    },
+                    layer: layer_index,
+                    color,
+                    background_fill: None,
+                    tags: Vec::new(),
+                    warp: None,
+                    locked: false,
+                    hidden: false,
+                    opacity: 100,
+                    blend_mode: BlendMode::Normal,
+                    mask_target_id: None,
+                    mask_mode: MaskMode::Clip,
+                });
+                sequence.polygon_motion_paths.push(animation);
+            }
+            _ => println!(
+                "Skipping Lottie layer {}, only shape, solid and text layers are imported",
+                name
+            ),
+        }
+    }
+
+    Ok(sequence)
+}
+
+/// A solid or a shape layer's first rectangle or ellipse, in the layer's own space
+struct LottieShape {
+    size: [f32; 2],
+    center: [f32; 2],
+    radius: f32,
+    fill: [i32; 4],
+    stroke: Option<(f32, [i32; 4])>,
+}
+
+fn lottie_shape(layer: &Value) -> Option<LottieShape> {
+    if layer["ty"].as_i64() == Some(1) {
+        let size = [
+            layer["sw"].as_f64().unwrap_or(0.0) as f32,
+            layer["sh"].as_f64().unwrap_or(0.0) as f32,
+        ];
+        return Some(LottieShape {
+            size,
+            center: [size[0] / 2.0, size[1] / 2.0],
+            radius: 0.0,
+            fill: hex_color(layer["sc"].as_str().unwrap_or("#000000")),
+            stroke: None,
+        });
+    }
+
+    let shapes = &layer["shapes"];
+    let (size, center, radius) = if let Some(rect) = find_shape_item(shapes, "rc") {
+        (
+            static_numbers(&rect["s"]),
+            static_numbers(&rect["p"]),
+            first(&static_numbers(&rect["r"])),
+        )
+    } else {
+        let ellipse = find_shape_item(shapes, "el")?;
+        let size = static_numbers(&ellipse["s"]);
+        let radius = first(&size).min(size.get(1).copied().unwrap_or(0.0)) / 2.0;
+        (size, static_numbers(&ellipse["p"]), radius)
+    };
+
+    Some(LottieShape {
+        size: [first(&size), size.get(1).copied().unwrap_or(0.0)],
+        center: [first(&center), center.get(1).copied().unwrap_or(0.0)],
+        radius,
+        fill: find_shape_item(shapes, "fl")
+            .map(shape_color)
+            .unwrap_or([0, 0, 0, 0]),
+        stroke: find_shape_item(shapes, "st")
+            .map(|stroke| (first(&static_numbers(&stroke["w"])), shape_color(stroke))),
+    })
+}
+
+fn imported_polygon(
+    id: String,
+    name: String,
+    layer: i32,
+    animation: &AnimationData,
+    shape: &LottieShape,
+    placement: &LottiePlacement,
+) -> SavedPolygonConfig {
+    let position = initial_position(animation);
+    let (thickness, stroke_fill) = shape.stroke.unwrap_or((0.0, [0, 0, 0, 255]));
+
+    SavedPolygonConfig {
+        id,
+        name,
+        fill: shape.fill,
+        dimensions: (placement.size(shape.size[0]), placement.size(shape.size[1])),
+        position: SavedPoint {
+            x: position[0],
+            y: position[1],
+        },
+        border_radius: placement.size(shape.radius),
+        stroke: SavedStroke {
+            thickness: placement.size(thickness),
+            fill: stroke_fill,
+        },
+        layer,
+        tags: Vec::new(),
+        outline: None,
+        locked: false,
+        hidden: false,
+        opacity: 100,
+        blend_mode: BlendMode::Normal,
+        mask_target_id: None,
+        mask_mode: MaskMode::Clip,
+    }
+}
+
+fn initial_position(animation: &AnimationData) -> [i32; 2] {
+    animation
+        .properties
+        .iter()
+        .flat_map(|p| p.keyframes.iter())
+        .find_map(|k| match k.value {
+            KeyframeValue::Position(position) => Some(position),
+            _ => None,
+        })
+        .unwrap_or([0, 0])
+}
+
+/// The layer's transform keyframes as a motion path. `center` is the object's center
+/// in the layer's own space, which may be away from its anchor point.
+fn layer_animation(
+    layer: &Value,
+    object_id: &str,
+    object_type: ObjectType,
+    center: [f32; 2],
+    timing: &LottieTiming,
+    placement: &LottiePlacement,
+    duration_ms: i32,
+) -> AnimationData {
+    let transform = &layer["ks"];
+    // keyframe times are from the layer's start
+    let start_frame = layer["st"].as_f64().unwrap_or(0.0) as f32;
+    let anchor = static_numbers(&transform["a"]);
+    let offset = [
+        center[0] - first(&anchor),
+        center[1] - anchor.get(1).copied().unwrap_or(0.0),
+    ];
+
+    let rotation = if transform["r"].is_object() {
+        &transform["r"]
+    } else {
+        &transform["rz"]
+    };
+    let to_ms = |frame: f32| timing.to_ms(frame + start_frame);
+
+    let properties = vec![
+        imported_property(
+            "Position",
+            "position",
+            position_keys(&transform["p"]),
+            &to_ms,
+            duration_ms,
+            |v| {
+                KeyframeValue::Position(placement.point(
+                    first(v) + offset[0],
+                    v.get(1).copied().unwrap_or(0.0) + offset[1],
+                ))
+            },
+        ),
+        imported_property(
+            "Rotation",
+            "rotation",
+            property_keys(rotation),
+            &to_ms,
+            duration_ms,
+            |v| KeyframeValue::Rotation(first(v).round() as i32),
+        ),
+        imported_property(
+            "Scale",
+            "scale",
+            property_keys(&transform["s"]),
+            &to_ms,
+            duration_ms,
+            // scale is out of 100 in both, kept uniform unless the axes differ
+            |v| {
+                let x = v.first().copied().unwrap_or(100.0).round() as i32;
+                let y = v.get(1).map_or(x, |y| y.round() as i32);
+                if x == y {
+                    KeyframeValue::Scale(x)
+                } else {
+                    KeyframeValue::ScaleXY([x, y])
+                }
+            },
+        ),
+        imported_property(
+            "Opacity",
+            "opacity",
+            property_keys(&transform["o"]),
+            &to_ms,
+            duration_ms,
+            |v| KeyframeValue::Opacity(v.first().copied().unwrap_or(100.0).round() as i32),
+        ),
+    ];
+
+    AnimationData {
+        id: Uuid::new_v4().to_string(),
+        object_type,
+        polygon_id: object_id.to_string(),
+        duration: Duration::from_millis(duration_ms as u64),
+        start_time_ms: 0,
+        properties,
+        position: [0, 0],
+        motion_blur: 0,
+    }
+}
+
+/// A motion path property from Lottie keys. A value that doesn't change is kept
+/// at the start and end of the sequence, like generated motion paths.
+fn imported_property(
+    name: &str,
+    property_path: &str,
+    keys: LottieKeys,
+    to_ms: &dyn Fn(f32) -> u64,
+    duration_ms: i32,
+    to_value: impl Fn(&[f32]) -> KeyframeValue,
+) -> AnimationProperty {
+    let mut keys: Vec<(u64, KeyframeValue, EasingType)> = keys
+        .into_iter()
+        .map(|(frame, value, easing)| (to_ms(frame), to_value(&value), easing))
+        .collect();
+    keys.dedup_by(|next, prev| next.0 == prev.0);
+
+    if keys.len() < 2 {
+        let value = keys.pop().map(|key| key.1).unwrap_or_else(|| to_value(&[]));
+        keys = vec![
+            (0, value.clone(), EasingType::Linear),
+            (duration_ms as u64, value, EasingType::Linear),
+        ];
+    }
+
+    AnimationProperty {
+        name: name.to_string(),
+        property_path: property_path.to_string(),
+        children: Vec::new(),
+        keyframes: keys
+            .into_iter()
+            .map(|(time_ms, value, easing)| UIKeyframe {
+                id: Uuid::new_v4().to_string(),
+                time: Duration::from_millis(time_ms),
+                value,
+                easing,
+                path_type: PathType::Linear,
+                key_type: KeyType::Frame,
+            })
+            .collect(),
+        depth: 0,
+    }
+}
+
+fn numbers(value: &Value) -> Vec<f32> {
+    match value {
+        Value::Number(n) => vec![n.as_f64().unwrap_or(0.0) as f32],
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|v| v.as_f64())
+            .map(|v| v as f32)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn first(values: &[f32]) -> f32 {
+    values.first().copied().unwrap_or(0.0)
+}
+
+/// A property's value at its first key, for things that can't be animated here
+fn static_numbers(property: &Value) -> Vec<f32> {
+    property_keys(property)
+        .into_iter()
+        .next()
+        .map(|key| key.1)
+        .unwrap_or_default()
+}
+
+/// Sorts a Lottie bezier curve into the closest easing, by whether it starts
+/// or ends flatter than a straight line
+fn easing_from_handles(out_handle: &Value, in_handle: &Value) -> EasingType {
+    // handles may be per dimension, the first stands for all
+    let (out_x, out_y) = (
+        first(&numbers(&out_handle["x"])),
+        first(&numbers(&out_handle["y"])),
+    );
+    let (in_x, in_y) = (
+        first(&numbers(&in_handle["x"])),
+        first(&numbers(&in_handle["y"])),
+    );
+
+    let slow_start = out_x > 0.05 && out_y < out_x * 0.5;
+    let slow_end = in_x < 0.95 && 1.0 - in_y < (1.0 - in_x) * 0.5;
+    match (slow_start, slow_end) {
+        (true, true) => EasingType::EaseInOut,
+        (true, false) => EasingType::EaseIn,
+        (false, true) => EasingType::EaseOut,
+        (false, false) => EasingType::Linear,
+    }
+}
+
+fn property_keys(property: &Value) -> LottieKeys {
+    let k = &property["k"];
+    let animated = k
+        .as_array()
+        .and_then(|keys| keys.first())
+        .is_some_and(|key| key.is_object());
+    if !animated {
+        let value = numbers(k);
+        return if value.is_empty() {
+            Vec::new()
+        } else {
+            vec![(0.0, value, EasingType::Linear)]
+        };
+    }
+
+    let mut keys = Vec::new();
+    let mut previous_end: Option<Vec<f32>> = None;
+    for key in k.as_array().into_iter().flatten() {
+        let frame = key["t"].as_f64().unwrap_or(0.0) as f32;
+        // older files leave out the last key's value, giving it as the previous key's end
+        let value = match numbers(&key["s"]) {
+            value if !value.is_empty() => value,
+            _ => match previous_end.take() {
+                Some(value) => value,
+                None => continue,
+            },
+        };
+        // holds have no counterpart, so they ease straight to the next key
+        let easing = if key["h"].as_i64() == Some(1) {
+            EasingType::Linear
+        } else {
+            easing_from_handles(&key["o"], &key["i"])
+        };
+        previous_end = Some(numbers(&key["e"])).filter(|end| !end.is_empty());
+        keys.push((frame, value, easing));
+    }
+
+    keys
+}
+
+/// Position keys, joining positions split into separately keyed x and y
+fn position_keys(position: &Value) -> LottieKeys {
+    if position["s"].as_bool() != Some(true) {
+        return property_keys(position);
+    }
+
+    let x = property_keys(&position["x"]);
+    let y = property_keys(&position["y"]);
+    let mut frames: Vec<f32> = x.iter().chain(y.iter()).map(|key| key.0).collect();
+    frames.sort_by(|a, b| a.total_cmp(b));
+    frames.dedup();
+
+    frames
+        .into_iter()
+        .map(|frame| {
+            let easing = x
+                .iter()
+                .chain(y.iter())
+                .find(|key| key.0 == frame)
+                .map(|key| key.2.clone())
+                .unwrap_or(EasingType::Linear);
+            (
+                frame,
+                vec![sample_keys(&x, frame), sample_keys(&y, frame)],
+                easing,
+            )
+        })
+        .collect()
+}
+
+/// A single dimension's value at a frame, straight between keys
+fn sample_keys(keys: &LottieKeys, frame: f32) -> f32 {
+    let value = |key: &(f32, Vec<f32>, EasingType)| first(&key.1);
+    match keys.iter().position(|key| key.0 > frame) {
+        None => keys.last().map(value).unwrap_or(0.0),
+        Some(0) => value(&keys[0]),
+        Some(next) => {
+            let (from, to) = (&keys[next - 1], &keys[next]);
+            let progress = (frame - from.0) / (to.0 - from.0);
+            value(from) + (value(to) - value(from)) * progress
+        }
+    }
+}
+
+/// The first item of a kind in a shape layer, looking inside groups
+fn find_shape_item<'a>(items: &'a Value, kind: &str) -> Option<&'a Value> {
+    for item in items.as_array()? {
+        if item["ty"] == kind {
+            return Some(item);
+        }
+        if item["ty"] == "gr" {
+            if let Some(found) = find_shape_item(&item["it"], kind) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+/// Colors are 0-1 in Lottie with opacity out of 100
+fn lottie_to_color(color: &[f32], opacity: f32) -> [i32; 4] {
+    let channel = |index: usize| (color.get(index).copied().unwrap_or(0.0) * 255.0).round() as i32;
+    [
+        channel(0),
+        channel(1),
+        channel(2),
+        (opacity * 2.55).round() as i32,
+    ]
+}
+
+fn shape_color(item: &Value) -> [i32; 4] {
+    let opacity = static_numbers(&item["o"]).first().copied().unwrap_or(100.0);
+    lottie_to_color(&static_numbers(&item["c"]), opacity)
+}
+
+fn hex_color(hex: &str) -> [i32; 4] {
+    let hex = hex.trim_start_matches('#');
+    let channel = |index: usize| {
+        hex.get(index * 2..index * 2 + 2)
+            .and_then(|c| i32::from_str_radix(c, 16).ok())
+            .unwrap_or(0)
+    };
+    [channel(0), channel(1), channel(2), 255]
+}
+
+fn solid_fills_canvas(layer: &Value, width: f32, height: f32) -> bool {
+    let anchor = static_numbers(&layer["ks"]["a"]);
+    // unmoved, with its top left on the canvas's
+    let unmoved = match position_keys(&layer["ks"]["p"]).as_slice() {
+        [] => true,
+        [(_, position, _)] => {
+            first(position) == first(&anchor)
+                && position.get(1).copied().unwrap_or(0.0) == anchor.get(1).copied().unwrap_or(0.0)
+        }
+        _ => false,
+    };
+
+    unmoved
+        && layer["sw"].as_f64().unwrap_or(0.0) as f32 >= width
+        && layer["sh"].as_f64().unwrap_or(0.0) as f32 >= height
+}
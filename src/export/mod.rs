@@ -1,4 +1,15 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
+pub mod chapters;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod encode;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod exporter;
 pub mod frame_buffer;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod gif;
+pub mod lottie;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod pipeline;
+pub mod subtitles;
+pub mod watermark;
@@ -1,14 +1,26 @@
 use crate::{
     animations::Sequence,
+    blend::{create_blend_pipelines, pipeline_for_mode, BlendMode},
+    brand_kit::BrandKit,
     camera::{Camera3D as Camera, CameraBinding},
     editor::{
         Editor, Viewport, WindowSize, WindowSizeShader,
     },
-    timelines::SavedTimelineStateConfig,
+    instancing::{create_instanced_pipeline, PolygonInstancer},
+    masking::{
+        begin_masked_draw, create_mask_pipelines, depth_stencil_state, MaskPipelines,
+        MASK_DEPTH_FORMAT,
+    },
+    render_order::{draw_order, DrawTarget},
+    saved_state::CanvasSize,
+    shader_reload::{with_validation, ShaderWatcher},
+    timelines::{set_transition_scissor, SavedTimelineStateConfig},
     vertex::Vertex,
 };
-use crate::gpu_resources::GpuResources;
+use crate::gpu_resources::{request_adapter, required_limits, AdapterPreference, GpuResources};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use wgpu::{util::DeviceExt, RenderPipeline};
 
 use super::frame_buffer::FrameCaptureBuffer;
@@ -19,13 +31,23 @@ pub struct ExportPipeline {
     pub gpu_resources: Option<Arc<GpuResources>>,
     pub camera: Option<Camera>,
     pub camera_binding: Option<CameraBinding>,
+    pub pipeline_layout: Option<wgpu::PipelineLayout>,
     pub render_pipeline: Option<RenderPipeline>,
+    pub blend_pipelines: Option<HashMap<BlendMode, RenderPipeline>>,
+    pub mask_pipelines: Option<MaskPipelines>,
+    pub instanced_pipeline: Option<RenderPipeline>,
+    pub polygon_instancer: Option<PolygonInstancer>,
     pub texture: Option<Arc<wgpu::Texture>>,
     pub view: Option<Arc<wgpu::TextureView>>,
     pub depth_view: Option<wgpu::TextureView>,
     pub window_size_bind_group: Option<wgpu::BindGroup>,
     pub export_editor: Option<Editor>,
     pub frame_buffer: Option<FrameCaptureBuffer>,
+    pub adapter_preference: AdapterPreference, // read when initializing
+    pub canvas_size: CanvasSize,               // read when initializing
+    pub brand_kit: Option<BrandKit>,           // read when initializing, for its fonts
+    pub shader_watcher: Option<ShaderWatcher>, // dev mode only
+    pub motion_blur_samples: u32, // renders averaged per frame when anything has motion blur
 }
 
 impl ExportPipeline {
@@ -36,13 +58,23 @@ impl ExportPipeline {
             gpu_resources: None,
             camera: None,
             camera_binding: None,
+            pipeline_layout: None,
             render_pipeline: None,
+            blend_pipelines: None,
+            mask_pipelines: None,
+            instanced_pipeline: None,
+            polygon_instancer: None,
             texture: None,
             view: None,
             depth_view: None,
             window_size_bind_group: None,
             export_editor: None,
             frame_buffer: None,
+            adapter_preference: AdapterPreference::from_env(),
+            canvas_size: CanvasSize::default(),
+            brand_kit: None,
+            shader_watcher: ShaderWatcher::from_env(),
+            motion_blur_samples: 8,
         }
     }
 
@@ -54,7 +86,7 @@ impl ExportPipeline {
         video_width: u32,
         video_height: u32,
         project_id: String,
-    ) {
+    ) -> Result<(), String> {
         let mut camera = Camera::new(
             //window_size
             WindowSize {
@@ -81,18 +113,21 @@ impl ExportPipeline {
 
         // create a dedicated editor so it can be used in the async thread
         let mut export_editor = Editor::new(viewport, project_id.clone());
+        export_editor.scene.canvas_size = self.canvas_size;
+        if let Some(brand_kit) = &self.brand_kit {
+            export_editor.register_brand_fonts(brand_kit)?;
+        }
+        // full resolution images and videos, the editor's are downscaled
+        export_editor.scene.image_assets.max_texture_size = None;
+        export_editor.scene.use_video_proxies = false;
 
         // continue on with wgpu items
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
             ..Default::default()
         });
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: None, // no surface desired for export
-                force_fallback_adapter: false,
-            })
+        // no surface desired for export
+        let adapter = request_adapter(&instance, &self.adapter_preference, None)
             .await
             .expect("Couldn't get gpu adapter");
 
@@ -100,6 +135,7 @@ impl ExportPipeline {
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: None,
+                    required_limits: required_limits(&adapter),
                     ..Default::default()
                 },
                 None,
@@ -120,7 +156,7 @@ impl ExportPipeline {
             mip_level_count: 1,
             sample_count: 1, // used in a multisampled environment
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Depth24Plus,
+            format: MASK_DEPTH_FORMAT,
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
             label: Some("Stunts Engine Export Depth Texture"),
             view_formats: &[],
@@ -128,14 +164,6 @@ impl ExportPipeline {
 
         let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        let depth_stencil_state = wgpu::DepthStencilState {
-            format: wgpu::TextureFormat::Depth24Plus,
-            depth_write_enabled: true,
-            depth_compare: wgpu::CompareFunction::Less,
-            stencil: wgpu::StencilState::default(),
-            bias: wgpu::DepthBiasState::default(),
-        };
-
         let model_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 entries: &[
@@ -263,63 +291,14 @@ impl ExportPipeline {
         let swapchain_format = wgpu::TextureFormat::Bgra8Unorm;
         // let swapchain_format = wgpu::TextureFormat::Rgba8Unorm;
 
-        // Configure the render pipeline
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Stunts Engine Export Render Pipeline"),
-            layout: Some(&pipeline_layout),
-            multiview: None,
-            // cache: None,
-            vertex: wgpu::VertexState {
-                module: &shader_module_vert_primary,
-                entry_point: "vs_main", // name of the entry point in your vertex shader
-                buffers: &[Vertex::desc()], // Make sure your Vertex::desc() matches your vertex structure
-                // compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader_module_frag_primary,
-                entry_point: "fs_main", // name of the entry point in your fragment shader
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: swapchain_format,
-                    // blend: Some(wgpu::BlendState::REPLACE),
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::One,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                    }),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                // compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            // primitive: wgpu::PrimitiveState::default(),
-            // depth_stencil: None,
-            // multisample: wgpu::MultisampleState::default(),
-            primitive: wgpu::PrimitiveState {
-                conservative: false,
-                topology: wgpu::PrimitiveTopology::TriangleList, // how vertices are assembled into geometric primitives
-                // strip_index_format: Some(wgpu::IndexFormat::Uint32),
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw, // Counter-clockwise is considered the front face
-                // none cull_mode
-                cull_mode: None,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                // Other properties such as conservative rasterization can be set here
-                unclipped_depth: false,
-            },
-            depth_stencil: Some(depth_stencil_state), // Optional, only if you are using depth testing
-            multisample: wgpu::MultisampleState {
-                // count: 4, // effect performance
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-        });
+        let (render_pipeline, blend_pipelines, mask_pipelines, instanced_pipeline) =
+            create_pipelines(
+                &device,
+                &pipeline_layout,
+                &shader_module_vert_primary,
+                &shader_module_frag_primary,
+                swapchain_format,
+            );
 
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             size: wgpu::Extent3d {
@@ -347,14 +326,22 @@ impl ExportPipeline {
 
         camera_binding.update_3d(&queue, &camera);
 
+        let polygon_instancer = PolygonInstancer::new(
+            &device,
+            &queue,
+            &model_bind_group_layout,
+            &group_bind_group_layout,
+            &camera.window_size,
+        );
+
         let gpu_resources = GpuResources::new(adapter, device, queue);
 
         let gpu_resources = Arc::new(gpu_resources);
 
         // set needed editor properties
-        export_editor.model_bind_group_layout = Some(model_bind_group_layout);
-        export_editor.group_bind_group_layout = Some(group_bind_group_layout);
-        export_editor.gpu_resources = Some(gpu_resources.clone());
+        export_editor.scene.model_bind_group_layout = Some(model_bind_group_layout);
+        export_editor.scene.group_bind_group_layout = Some(group_bind_group_layout);
+        export_editor.scene.gpu_resources = Some(gpu_resources.clone());
 
         // let gpu_resources = export_editor
         //     .gpu_resources
@@ -362,11 +349,11 @@ impl ExportPipeline {
         //     .expect("Couldn't get gpu resources");
 
         // begin playback
-        export_editor.camera = Some(camera);
+        export_editor.scene.camera = Some(camera);
 
         // restore objects to the editor
-        sequences.iter().enumerate().for_each(|(i, s)| {
-            export_editor.restore_sequence_objects(
+        for (i, s) in sequences.iter().enumerate() {
+            export_editor.scene.restore_sequence_objects(
                 &s,
                 // WindowSize {
                 //     // width: window_size.width as u32,
@@ -378,37 +365,112 @@ impl ExportPipeline {
                 if i == 0 { false } else { true },
                 // &gpu_resources.device,
                 // &gpu_resources.queue,
-            );
-        });
+            )?;
+        }
         
         let now = std::time::Instant::now();
-        export_editor.video_start_playing_time = Some(now.clone());
+        export_editor.scene.video_start_playing_time = Some(now.clone());
 
-        export_editor.video_current_sequence_timeline = Some(video_current_sequence_timeline);
-        export_editor.video_current_sequences_data = Some(sequences);
+        export_editor.scene.video_current_sequence_timeline = Some(video_current_sequence_timeline);
+        export_editor.scene.video_current_sequences_data = Some(sequences);
 
-        export_editor.video_is_playing = true;
+        export_editor.scene.video_is_playing = true;
+        export_editor.scene.interpolate_video_frames = true;
 
         // also set motion path playing
-        export_editor.start_playing_time = Some(now);
-        export_editor.is_playing = true;
+        export_editor.scene.start_playing_time = Some(now);
+        export_editor.scene.is_playing = true;
 
         println!("Video exporting!");
 
         // self.device = Some(device);
         // self.queue = Some(queue);
-        self.gpu_resources = export_editor.gpu_resources.clone();
+        self.gpu_resources = export_editor.scene.gpu_resources.clone();
         self.camera = Some(camera);
         self.camera_binding = Some(camera_binding);
+        self.pipeline_layout = Some(pipeline_layout);
         self.render_pipeline = Some(render_pipeline);
+        self.blend_pipelines = Some(blend_pipelines);
+        self.mask_pipelines = Some(mask_pipelines);
+        self.instanced_pipeline = Some(instanced_pipeline);
+        self.polygon_instancer = Some(polygon_instancer);
         self.texture = Some(texture);
         self.view = Some(view);
         self.depth_view = Some(depth_view);
         self.window_size_bind_group = Some(window_size_bind_group);
         self.export_editor = Some(export_editor);
+
+        Ok(())
     }
 
-    pub fn render_frame(&mut self, current_time: f64) {
+    /// Rebuilds the pipelines when the watched shaders change, keeping the current ones
+    /// if the new shaders don't compile
+    pub fn reload_changed_shaders(&mut self) {
+        let Some(watcher) = self.shader_watcher.as_mut() else {
+            return;
+        };
+
+        let changed = watcher.poll();
+        if !changed
+            .iter()
+            .any(|name| name == "vert_primary.wgsl" || name == "frag_primary.wgsl")
+        {
+            return;
+        }
+
+        match self.rebuild_pipelines() {
+            Ok(()) => println!("Shaders reloaded: {:?}", changed),
+            Err(e) => println!("Keeping previous shaders: {}", e),
+        }
+    }
+
+    fn rebuild_pipelines(&mut self) -> Result<(), String> {
+        let watcher = self
+            .shader_watcher
+            .as_ref()
+            .ok_or("Shader watching is off")?;
+        let gpu_resources = self
+            .gpu_resources
+            .as_ref()
+            .ok_or("Couldn't get gpu resources")?;
+        let layout = self
+            .pipeline_layout
+            .as_ref()
+            .ok_or("Couldn't get pipeline layout")?;
+        let format = self
+            .texture
+            .as_ref()
+            .ok_or("Couldn't get texture")?
+            .format();
+        let device = &gpu_resources.device;
+
+        let vertex_shader = watcher.load(device, "vert_primary.wgsl")?;
+        let fragment_shader = watcher.load(device, "frag_primary.wgsl")?;
+
+        // entry points are only checked against the shaders when the pipelines are made
+        let (render_pipeline, blend_pipelines, mask_pipelines, instanced_pipeline) =
+            with_validation(device, || {
+                create_pipelines(device, layout, &vertex_shader, &fragment_shader, format)
+            })?;
+
+        self.render_pipeline = Some(render_pipeline);
+        self.blend_pipelines = Some(blend_pipelines);
+        self.mask_pipelines = Some(mask_pipelines);
+        self.instanced_pipeline = Some(instanced_pipeline);
+
+        Ok(())
+    }
+
+    /// Samples blurred animations this far before the frame's time, see Editor::motion_blur_shift
+    pub fn set_motion_blur_shift(&mut self, shift: Duration) {
+        if let Some(editor) = self.export_editor.as_mut() {
+            editor.scene.motion_blur_shift = shift;
+        }
+    }
+
+    pub fn render_frame(&mut self, current_time: f64) -> Result<(), String> {
+        self.reload_changed_shaders();
+
         let editor = self.export_editor.as_mut().expect("Couldn't get editor");
         let gpu_resources = self
             .gpu_resources
@@ -427,9 +489,25 @@ impl ExportPipeline {
             .render_pipeline
             .as_ref()
             .expect("Couldn't get render pipeline");
+        let blend_pipelines = self
+            .blend_pipelines
+            .as_ref()
+            .expect("Couldn't get blend pipelines");
+        let mask_pipelines = self
+            .mask_pipelines
+            .as_ref()
+            .expect("Couldn't get mask pipelines");
+        let instanced_pipeline = self
+            .instanced_pipeline
+            .as_ref()
+            .expect("Couldn't get instanced pipeline");
+        let polygon_instancer = self
+            .polygon_instancer
+            .as_mut()
+            .expect("Couldn't get polygon instancer");
         let camera_binding = self
             .camera_binding
-            .as_ref()
+            .as_mut()
             .expect("Couldn't get camera binding");
         let window_size_bind_group = self
             .window_size_bind_group
@@ -462,7 +540,10 @@ impl ExportPipeline {
                         load: wgpu::LoadOp::Clear(1.0), // Clear to max depth
                         store: wgpu::StoreOp::Store,
                     }),
-                    stencil_ops: None, // Set this if using stencil
+                    stencil_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(0), // masks write to the stencil
+                        store: wgpu::StoreOp::Discard,
+                    }),
                 }),
                 timestamp_writes: None,
                 occlusion_query_set: None,
@@ -471,14 +552,50 @@ impl ExportPipeline {
             render_pass.set_pipeline(&render_pipeline);
 
             // actual rendering commands
-            editor.step_video_animations(&camera, Some(current_time));
-            editor.step_motion_path_animations(&camera, Some(current_time));
+            editor.scene.step_video_animations(&camera, Some(current_time))?;
+            editor.scene.step_motion_path_animations(&camera, Some(current_time))?;
+            editor.scene.step_sequence_transition();
+
+            // wipes clip each sequence's objects to their side of the frame
+            let (frame_width, frame_height) = (texture.width(), texture.height());
+            let transition = editor.scene.active_transition.as_ref();
+
+            // camera keyframes move the editor's camera
+            if let Some(editor_camera) = editor.scene.camera.as_ref() {
+                camera_binding.update_3d(&queue, editor_camera);
+            }
 
             render_pass.set_bind_group(0, &camera_binding.bind_group, &[]);
             render_pass.set_bind_group(2, window_size_bind_group, &[]);
 
+            // masks aren't drawn below, only into the stencil along with their target
+            for polygon in editor.scene.polygons.iter().filter(|p| p.mask_target_id.is_some()) {
+                polygon
+                    .transform
+                    .update_uniform_buffer(&queue, &camera.window_size);
+            }
+            for text_item in editor.scene.text_items.iter().filter(|t| t.mask_target_id.is_some()) {
+                text_item
+                    .transform
+                    .update_uniform_buffer(&queue, &camera.window_size);
+            }
+            for st_image in editor.scene.image_items.iter().filter(|i| i.mask_target_id.is_some()) {
+                st_image
+                    .transform
+                    .update_uniform_buffer(&queue, &camera.window_size);
+            }
+            for st_video in editor.scene.video_items.iter().filter(|v| v.mask_target_id.is_some()) {
+                st_video
+                    .transform
+                    .update_uniform_buffer(&queue, &camera.window_size);
+            }
+            let mut stencil_reference = 0;
+
+            // polygons sharing a shape are drawn together after the static polygons
+            polygon_instancer.prepare(device, queue, &editor.scene, &camera.window_size);
+
             // draw static (internal) polygons
-            for (poly_index, polygon) in editor.static_polygons.iter().enumerate() {
+            for (poly_index, polygon) in editor.scene.static_polygons.iter().enumerate() {
                 polygon
                     .transform
                     .update_uniform_buffer(&queue, &camera.window_size);
@@ -490,102 +607,221 @@ impl ExportPipeline {
                 render_pass.draw_indexed(0..polygon.indices.len() as u32, 0, 0..1);
             }
 
-            // draw polygons
-            for (poly_index, polygon) in editor.polygons.iter().enumerate() {
-                if !polygon.hidden {
-                    polygon
-                        .transform
-                        .update_uniform_buffer(&queue, &camera.window_size);
-                    render_pass.set_bind_group(1, &polygon.bind_group, &[]);
-                    render_pass.set_bind_group(3, &polygon.group_bind_group, &[]);
-                    render_pass.set_vertex_buffer(0, polygon.vertex_buffer.slice(..));
-                    render_pass.set_index_buffer(
-                        polygon.index_buffer.slice(..),
-                        wgpu::IndexFormat::Uint32,
-                    );
-                    render_pass.draw_indexed(0..polygon.indices.len() as u32, 0, 0..1);
-                }
-            }
+            polygon_instancer.draw(
+                &mut render_pass,
+                instanced_pipeline,
+                transition,
+                frame_width,
+                frame_height,
+            );
 
-            // draw text items
-            for (text_index, text_item) in editor.text_items.iter().enumerate() {
-                if !text_item.hidden {
-                    if !text_item.background_polygon.hidden {
+            // transparent objects are drawn back to front, whatever their type
+            let view_camera = editor.scene.camera.as_ref().unwrap_or(camera);
+            for target in draw_order(&editor.scene, view_camera) {
+                match target {
+                    DrawTarget::Polygon(index) => {
+                        let polygon = &editor.scene.polygons[index];
+                        if polygon_instancer.is_batched(&polygon.id) {
+                            continue;
+                        }
+
+                        render_pass.set_pipeline(pipeline_for_mode(
+                            render_pipeline,
+                            blend_pipelines,
+                            polygon.blend_mode,
+                        ));
+                        begin_masked_draw(
+                            &mut render_pass,
+                            &editor.scene,
+                            &polygon.id.to_string(),
+                            mask_pipelines,
+                            polygon.blend_mode,
+                            &mut stencil_reference,
+                        );
+                        set_transition_scissor(
+                            &mut render_pass,
+                            transition,
+                            &polygon.current_sequence_id.to_string(),
+                            frame_width,
+                            frame_height,
+                        );
+                        polygon
+                            .transform
+                            .update_uniform_buffer(&queue, &camera.window_size);
+                        render_pass.set_bind_group(1, &polygon.bind_group, &[]);
+                        render_pass.set_bind_group(3, &polygon.group_bind_group, &[]);
+                        render_pass.set_vertex_buffer(0, polygon.vertex_buffer.slice(..));
+                        render_pass.set_index_buffer(
+                            polygon.index_buffer.slice(..),
+                            wgpu::IndexFormat::Uint32,
+                        );
+                        render_pass.draw_indexed(0..polygon.indices.len() as u32, 0, 0..1);
+                    }
+                    DrawTarget::TextBackground(index) => {
+                        let text_item = &editor.scene.text_items[index];
+                        let background = &text_item.background_polygon;
+
+                        // the text's own masks and blend mode cover its background too
+                        render_pass.set_pipeline(pipeline_for_mode(
+                            render_pipeline,
+                            blend_pipelines,
+                            text_item.blend_mode,
+                        ));
+                        begin_masked_draw(
+                            &mut render_pass,
+                            &editor.scene,
+                            &text_item.id.to_string(),
+                            mask_pipelines,
+                            text_item.blend_mode,
+                            &mut stencil_reference,
+                        );
+                        set_transition_scissor(
+                            &mut render_pass,
+                            transition,
+                            &text_item.current_sequence_id.to_string(),
+                            frame_width,
+                            frame_height,
+                        );
+                        background
+                            .transform
+                            .update_uniform_buffer(&queue, &camera.window_size);
+                        render_pass.set_bind_group(1, &background.bind_group, &[]);
+                        render_pass.set_bind_group(3, &background.group_bind_group, &[]);
+                        render_pass.set_vertex_buffer(0, background.vertex_buffer.slice(..));
+                        render_pass.set_index_buffer(
+                            background.index_buffer.slice(..),
+                            wgpu::IndexFormat::Uint32,
+                        );
+                        render_pass.draw_indexed(0..background.indices.len() as u32, 0, 0..1);
+                    }
+                    DrawTarget::Text(index) => {
+                        let text_item = &editor.scene.text_items[index];
+
+                        render_pass.set_pipeline(pipeline_for_mode(
+                            render_pipeline,
+                            blend_pipelines,
+                            text_item.blend_mode,
+                        ));
+                        begin_masked_draw(
+                            &mut render_pass,
+                            &editor.scene,
+                            &text_item.id.to_string(),
+                            mask_pipelines,
+                            text_item.blend_mode,
+                            &mut stencil_reference,
+                        );
+                        set_transition_scissor(
+                            &mut render_pass,
+                            transition,
+                            &text_item.current_sequence_id.to_string(),
+                            frame_width,
+                            frame_height,
+                        );
                         text_item
-                            .background_polygon
                             .transform
-                            .update_uniform_buffer(&gpu_resources.queue, &camera.window_size);
-
-                        render_pass.set_bind_group(
-                            1,
-                            &text_item.background_polygon.bind_group,
-                            &[],
+                            .update_uniform_buffer(&queue, &camera.window_size);
+                        render_pass.set_bind_group(1, &text_item.bind_group, &[]);
+                        render_pass.set_bind_group(3, &text_item.group_bind_group, &[]);
+                        render_pass.set_vertex_buffer(0, text_item.vertex_buffer.slice(..));
+                        render_pass.set_index_buffer(
+                            text_item.index_buffer.slice(..),
+                            wgpu::IndexFormat::Uint32,
                         );
-                        render_pass.set_bind_group(
-                            3,
-                            &text_item.background_polygon.group_bind_group,
-                            &[],
+                        render_pass.draw_indexed(0..text_item.indices.len() as u32, 0, 0..1);
+                    }
+                    DrawTarget::Image(index) => {
+                        let st_image = &editor.scene.image_items[index];
+
+                        render_pass.set_pipeline(pipeline_for_mode(
+                            render_pipeline,
+                            blend_pipelines,
+                            st_image.blend_mode,
+                        ));
+                        begin_masked_draw(
+                            &mut render_pass,
+                            &editor.scene,
+                            &st_image.id.clone(),
+                            mask_pipelines,
+                            st_image.blend_mode,
+                            &mut stencil_reference,
                         );
-                        render_pass.set_vertex_buffer(
-                            0,
-                            text_item.background_polygon.vertex_buffer.slice(..),
+                        set_transition_scissor(
+                            &mut render_pass,
+                            transition,
+                            &st_image.current_sequence_id.to_string(),
+                            frame_width,
+                            frame_height,
                         );
+                        st_image
+                            .transform
+                            .update_uniform_buffer(&queue, &camera.window_size);
+                        render_pass.set_bind_group(1, &st_image.bind_group, &[]);
+                        render_pass.set_bind_group(3, &st_image.group_bind_group, &[]);
+                        render_pass.set_vertex_buffer(0, st_image.vertex_buffer.slice(..));
                         render_pass.set_index_buffer(
-                            text_item.background_polygon.index_buffer.slice(..),
+                            st_image.index_buffer.slice(..),
                             wgpu::IndexFormat::Uint32,
                         );
-                        render_pass.draw_indexed(
-                            0..text_item.background_polygon.indices.len() as u32,
-                            0,
-                            0..1,
+                        render_pass.draw_indexed(0..st_image.indices.len() as u32, 0, 0..1);
+                    }
+                    DrawTarget::Video(index) => {
+                        let st_video = &editor.scene.video_items[index];
+
+                        render_pass.set_pipeline(pipeline_for_mode(
+                            render_pipeline,
+                            blend_pipelines,
+                            st_video.blend_mode,
+                        ));
+                        begin_masked_draw(
+                            &mut render_pass,
+                            &editor.scene,
+                            &st_video.id.clone(),
+                            mask_pipelines,
+                            st_video.blend_mode,
+                            &mut stencil_reference,
+                        );
+                        set_transition_scissor(
+                            &mut render_pass,
+                            transition,
+                            &st_video.current_sequence_id.to_string(),
+                            frame_width,
+                            frame_height,
                         );
+                        st_video
+                            .transform
+                            .update_uniform_buffer(&queue, &camera.window_size);
+                        render_pass.set_bind_group(1, &st_video.bind_group, &[]);
+                        render_pass.set_bind_group(3, &st_video.group_bind_group, &[]);
+                        render_pass.set_vertex_buffer(0, st_video.vertex_buffer.slice(..));
+                        render_pass.set_index_buffer(
+                            st_video.index_buffer.slice(..),
+                            wgpu::IndexFormat::Uint32,
+                        );
+                        render_pass.draw_indexed(0..st_video.indices.len() as u32, 0, 0..1);
+                    }
+                    DrawTarget::ParticleEmitter(index) => {
+                        let emitter = &editor.scene.particle_emitters[index];
+
+                        render_pass.set_pipeline(&render_pipeline);
+                        set_transition_scissor(
+                            &mut render_pass,
+                            transition,
+                            &emitter.current_sequence_id.to_string(),
+                            frame_width,
+                            frame_height,
+                        );
+                        emitter
+                            .transform
+                            .update_uniform_buffer(&queue, &camera.window_size);
+                        render_pass.set_bind_group(1, &emitter.bind_group, &[]);
+                        render_pass.set_bind_group(3, &emitter.group_bind_group, &[]);
+                        render_pass.set_vertex_buffer(0, emitter.vertex_buffer.slice(..));
+                        render_pass.set_index_buffer(
+                            emitter.index_buffer.slice(..),
+                            wgpu::IndexFormat::Uint32,
+                        );
+                        render_pass.draw_indexed(0..emitter.index_count, 0, 0..1);
                     }
-
-                    text_item
-                        .transform
-                        .update_uniform_buffer(&queue, &camera.window_size);
-                    render_pass.set_bind_group(1, &text_item.bind_group, &[]);
-                    render_pass.set_bind_group(3, &text_item.group_bind_group, &[]);
-                    render_pass.set_vertex_buffer(0, text_item.vertex_buffer.slice(..));
-                    render_pass.set_index_buffer(
-                        text_item.index_buffer.slice(..),
-                        wgpu::IndexFormat::Uint32,
-                    );
-                    render_pass.draw_indexed(0..text_item.indices.len() as u32, 0, 0..1);
-                }
-            }
-
-            // draw image items
-            for (image_index, st_image) in editor.image_items.iter().enumerate() {
-                if !st_image.hidden {
-                    st_image
-                        .transform
-                        .update_uniform_buffer(&queue, &camera.window_size);
-                    render_pass.set_bind_group(1, &st_image.bind_group, &[]);
-                    render_pass.set_bind_group(3, &st_image.group_bind_group, &[]);
-                    render_pass.set_vertex_buffer(0, st_image.vertex_buffer.slice(..));
-                    render_pass.set_index_buffer(
-                        st_image.index_buffer.slice(..),
-                        wgpu::IndexFormat::Uint32,
-                    );
-                    render_pass.draw_indexed(0..st_image.indices.len() as u32, 0, 0..1);
-                }
-            }
-
-            // draw video items
-            for (video_index, st_video) in editor.video_items.iter().enumerate() {
-                if !st_video.hidden {
-                    st_video
-                        .transform
-                        .update_uniform_buffer(&queue, &camera.window_size);
-                    render_pass.set_bind_group(1, &st_video.bind_group, &[]);
-                    render_pass.set_bind_group(3, &st_video.group_bind_group, &[]);
-                    render_pass.set_vertex_buffer(0, st_video.vertex_buffer.slice(..));
-                    render_pass.set_index_buffer(
-                        st_video.index_buffer.slice(..),
-                        wgpu::IndexFormat::Uint32,
-                    );
-                    render_pass.draw_indexed(0..st_video.indices.len() as u32, 0, 0..1);
                 }
             }
 
@@ -597,5 +833,113 @@ impl ExportPipeline {
             let command_buffer = encoder.finish();
             queue.submit(std::iter::once(command_buffer));
         }
+
+        Ok(())
     }
 }
+
+/// The main, blend mode and mask pipelines, all drawing with the same shaders
+fn create_pipelines(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    vertex_shader: &wgpu::ShaderModule,
+    fragment_shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+) -> (
+    RenderPipeline,
+    HashMap<BlendMode, RenderPipeline>,
+    MaskPipelines,
+    RenderPipeline,
+) {
+    let depth_stencil_state = depth_stencil_state();
+
+    // Configure the render pipeline
+    let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Stunts Engine Export Render Pipeline"),
+        layout: Some(layout),
+        multiview: None,
+        // cache: None,
+        vertex: wgpu::VertexState {
+            module: vertex_shader,
+            entry_point: "vs_main", // name of the entry point in your vertex shader
+            buffers: &[Vertex::desc()], // Make sure your Vertex::desc() matches your vertex structure
+            // compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: fragment_shader,
+            entry_point: "fs_main", // name of the entry point in your fragment shader
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                // blend: Some(wgpu::BlendState::REPLACE),
+                blend: Some(wgpu::BlendState {
+                    color: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::SrcAlpha,
+                        dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                    alpha: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::One,
+                        dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                }),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            // compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        // primitive: wgpu::PrimitiveState::default(),
+        // depth_stencil: None,
+        // multisample: wgpu::MultisampleState::default(),
+        primitive: wgpu::PrimitiveState {
+            conservative: false,
+            topology: wgpu::PrimitiveTopology::TriangleList, // how vertices are assembled into geometric primitives
+            // strip_index_format: Some(wgpu::IndexFormat::Uint32),
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw, // Counter-clockwise is considered the front face
+            // none cull_mode
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            // Other properties such as conservative rasterization can be set here
+            unclipped_depth: false,
+        },
+        depth_stencil: Some(depth_stencil_state.clone()), // Optional, only if you are using depth testing
+        multisample: wgpu::MultisampleState {
+            // count: 4, // effect performance
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+    });
+
+    let blend_pipelines = create_blend_pipelines(
+        device,
+        layout,
+        vertex_shader,
+        fragment_shader,
+        format,
+        Some(depth_stencil_state),
+    );
+
+    let mask_pipelines = create_mask_pipelines(
+        device,
+        layout,
+        vertex_shader,
+        fragment_shader,
+        format,
+    );
+
+    let instanced_pipeline = create_instanced_pipeline(
+        device,
+        layout,
+        vertex_shader,
+        fragment_shader,
+        format,
+    );
+
+    (
+        render_pipeline,
+        blend_pipelines,
+        mask_pipelines,
+        instanced_pipeline,
+    )
+}
@@ -0,0 +1,83 @@
+struct ColorManagementUniforms {
+    // columns of the BT.709 to display primaries matrix
+    gamut_0: vec4<f32>,
+    gamut_1: vec4<f32>,
+    gamut_2: vec4<f32>,
+    // transfer (0 sRGB curve, 1 linear, 2 PQ), white scale, peak, unused
+    params: vec4<f32>,
+};
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+@group(0) @binding(2) var<uniform> uniforms: ColorManagementUniforms;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+};
+
+// one triangle covering the screen
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let x = f32((vertex_index << 1u) & 2u);
+    let y = f32(vertex_index & 2u);
+    out.clip_position = vec4<f32>(x * 2.0 - 1.0, 1.0 - y * 2.0, 0.0, 1.0);
+    out.tex_coords = vec2<f32>(x, y);
+    return out;
+}
+
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    let low = c / 12.92;
+    let high = pow((c + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, c <= vec3<f32>(0.04045));
+}
+
+fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+    let low = c * 12.92;
+    let high = 1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, c <= vec3<f32>(0.0031308));
+}
+
+// c is in units of 10000 nits
+fn linear_to_pq(c: vec3<f32>) -> vec3<f32> {
+    let m1 = 0.1593017578125;
+    let m2 = 78.84375;
+    let c1 = 0.8359375;
+    let c2 = 18.8515625;
+    let c3 = 18.6875;
+    let p = pow(max(c, vec3<f32>(0.0)), vec3<f32>(m1));
+    return pow((c1 + c2 * p) / (1.0 + c3 * p), vec3<f32>(m2));
+}
+
+// Reinhard shoulder above 80% of the peak, leaving everything below untouched
+fn tone_map(c: vec3<f32>, peak: f32) -> vec3<f32> {
+    let knee = peak * 0.8;
+    let over = max(c - vec3<f32>(knee), vec3<f32>(0.0));
+    let range = peak - knee;
+    let compressed = knee + over / (vec3<f32>(1.0) + over / range);
+    return select(c, compressed, c > vec3<f32>(knee));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let source = textureSample(source_texture, source_sampler, in.tex_coords);
+
+    // preview output is BT.709 primaries with the sRGB curve, as in exports
+    let linear = srgb_to_linear(clamp(source.rgb, vec3<f32>(0.0), vec3<f32>(1.0)));
+    let gamut = mat3x3<f32>(uniforms.gamut_0.xyz, uniforms.gamut_1.xyz, uniforms.gamut_2.xyz);
+    var color = max(gamut * linear, vec3<f32>(0.0)) * uniforms.params.y;
+
+    if (uniforms.params.z > 0.0) {
+        color = tone_map(color, uniforms.params.z);
+    }
+
+    let transfer = u32(uniforms.params.x);
+    if (transfer == 0u) {
+        color = linear_to_srgb(color);
+    } else if (transfer == 2u) {
+        color = linear_to_pq(color);
+    }
+
+    return vec4<f32>(color, source.a);
+}
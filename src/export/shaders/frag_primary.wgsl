@@ -10,4 +10,37 @@ struct FragmentInput {
 fn fs_main(in: FragmentInput) -> @location(0) vec4<f32> {
     let tex_color = textureSample(texture, texture_sampler, in.tex_coords);
     return tex_color * in.color;
-}
\ No newline at end of file
+}
+// Blend mode variants, each paired with a blend state in blend.rs
+
+// blended as dst * src, fading toward white leaves the backdrop unchanged
+@fragment
+fn fs_multiply(in: FragmentInput) -> @location(0) vec4<f32> {
+    let color = textureSample(texture, texture_sampler, in.tex_coords) * in.color;
+    return vec4<f32>(mix(vec3<f32>(1.0), color.rgb, color.a), 1.0);
+}
+
+// blended as 2 * src * dst, fading toward mid gray leaves the backdrop unchanged
+@fragment
+fn fs_overlay(in: FragmentInput) -> @location(0) vec4<f32> {
+    let color = textureSample(texture, texture_sampler, in.tex_coords) * in.color;
+    return vec4<f32>(mix(vec3<f32>(0.5), color.rgb, color.a), 1.0);
+}
+
+// screen and add, fading toward black leaves the backdrop unchanged
+@fragment
+fn fs_premultiplied(in: FragmentInput) -> @location(0) vec4<f32> {
+    let color = textureSample(texture, texture_sampler, in.tex_coords) * in.color;
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
+
+// Masks only write the stencil, dropping fragments under half opacity so alpha masks
+// follow the shape of their texture
+@fragment
+fn fs_mask(in: FragmentInput) -> @location(0) vec4<f32> {
+    let color = textureSample(texture, texture_sampler, in.tex_coords) * in.color;
+    if (color.a < 0.5) {
+        discard;
+    }
+    return color;
+}
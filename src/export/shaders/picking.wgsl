@@ -0,0 +1,70 @@
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+struct ModelUniforms {
+    model: mat4x4<f32>
+};
+
+struct GroupUniforms {
+    group: mat4x4<f32>
+};
+
+struct WindowSize {
+    width: f32,
+    height: f32,
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+@group(1) @binding(0) var<uniform> model_uniforms: ModelUniforms;
+@group(1) @binding(1) var texture: texture_2d<f32>;
+@group(1) @binding(2) var texture_sampler: sampler;
+@group(2) @binding(0) var<uniform> window_size: WindowSize;
+@group(3) @binding(0) var<uniform> group_uniforms: GroupUniforms;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) @interpolate(flat) object_id: u32,
+};
+
+// Positioned like vs_main in vert_primary.wgsl. Each object is drawn as a single instance
+// whose index is its picking id.
+@vertex
+fn vs_main(
+    vertex: VertexInput,
+    @builtin(instance_index) instance_index: u32,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    let model_pos = group_uniforms.group * model_uniforms.model * vec4<f32>(vertex.position, 1.0);
+
+    let tilt_w = model_pos.w;
+    var ndc_pos = model_pos.xyz / tilt_w;
+    ndc_pos.x = (ndc_pos.x / window_size.width) * 2.0 - 1.0;
+    ndc_pos.y = ((ndc_pos.y / window_size.height) * 2.0 - 1.0);
+
+    out.clip_position = camera.view_proj * vec4<f32>(ndc_pos, 1.0) * tilt_w;
+    out.tex_coords = vertex.tex_coords;
+    out.color = vertex.color;
+    out.object_id = instance_index;
+
+    return out;
+}
+
+// Nearly transparent pixels, like the gaps between glyphs, let clicks through
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) u32 {
+    let alpha = textureSample(texture, texture_sampler, in.tex_coords).a * in.color.a;
+    if (alpha < 0.05) {
+        discard;
+    }
+    return in.object_id;
+}
@@ -42,16 +42,50 @@ fn vs_main(
     // Also apply group transform
     let model_pos = group_uniforms.group * model_uniforms.model * vec4<f32>(vertex.position, 1.0);
     
-    // Then convert to NDC space
-    var ndc_pos = model_pos.xyz;
+    out.clip_position = to_clip_position(model_pos);
+    out.tex_coords = vertex.tex_coords;
+    out.color = vertex.color;
+    
+    return out;
+}
+
+fn to_clip_position(model_pos: vec4<f32>) -> vec4<f32> {
+    // Convert to NDC space, dividing out any 3D tilt perspective
+    let tilt_w = model_pos.w;
+    var ndc_pos = model_pos.xyz / tilt_w;
     ndc_pos.x = (ndc_pos.x / window_size.width) * 2.0 - 1.0;
     ndc_pos.y = ((ndc_pos.y / window_size.height) * 2.0 - 1.0); // Flip Y coordinate
     
-    // Finally apply camera transform
-    out.clip_position = camera.view_proj * vec4<f32>(ndc_pos, 1.0);
+    // Finally apply camera transform, scaling by the tilt w so textures are interpolated
+    // with perspective across tilted objects
+    return camera.view_proj * vec4<f32>(ndc_pos, 1.0) * tilt_w;
+}
+
+struct InstanceInput {
+    @location(3) model_0: vec4<f32>,
+    @location(4) model_1: vec4<f32>,
+    @location(5) model_2: vec4<f32>,
+    @location(6) model_3: vec4<f32>,
+    @location(7) fill: vec4<f32>,
+    @location(8) stroke: vec4<f32>,
+};
+
+// Polygons sharing one mesh, drawn in a single call. Each instance brings its own
+// model matrix and colors, and the mesh's vertex alpha picks fill (0) or stroke (1).
+@vertex
+fn vs_instanced(
+    vertex: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    let model_pos = model * vec4<f32>(vertex.position, 1.0);
+
+    out.clip_position = to_clip_position(model_pos);
     out.tex_coords = vertex.tex_coords;
-    out.color = vertex.color;
-    
+    out.color = mix(instance.fill, instance.stroke, vertex.color.a);
+
     return out;
 }
 
@@ -0,0 +1,63 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::animations::Sequence;
+use crate::captions::{captions_to_subtitles, CaptionSegment, SubtitleFormat};
+use crate::timelines::{SavedTimelineStateConfig, TrackType};
+
+/// Writes the captions of the exported sequences next to the video in each format
+pub fn export_subtitles(
+    video_path: &Path,
+    sequences: &[Sequence],
+    timeline: &SavedTimelineStateConfig,
+    formats: &[SubtitleFormat],
+) -> Result<(), String> {
+    let captions = timeline_captions(sequences, timeline);
+    if captions.is_empty() {
+        return Ok(());
+    }
+
+    for format in formats {
+        let subtitles_path = subtitles_path(video_path, *format);
+        fs::write(&subtitles_path, captions_to_subtitles(&captions, *format))
+            .map_err(|e| format!("Couldn't write {}: {}", subtitles_path.display(), e))?;
+    }
+
+    Ok(())
+}
+
+pub fn subtitles_path(video_path: &Path, format: SubtitleFormat) -> PathBuf {
+    video_path.with_extension(format.extension())
+}
+
+/// Captions of every sequence on the video track, moved to when the sequence plays
+pub fn timeline_captions(
+    sequences: &[Sequence],
+    timeline: &SavedTimelineStateConfig,
+) -> Vec<CaptionSegment> {
+    let mut captions: Vec<CaptionSegment> = timeline
+        .timeline_sequences
+        .iter()
+        .filter(|ts| ts.track_type == TrackType::Video)
+        .filter_map(|ts| {
+            sequences
+                .iter()
+                .find(|s| s.id == ts.sequence_id)
+                .map(|s| (ts.start_time_ms, s))
+        })
+        .flat_map(|(start_time_ms, sequence)| {
+            sequence
+                .caption_tracks
+                .iter()
+                .flat_map(|t| &t.segments)
+                .map(move |segment| CaptionSegment {
+                    start_ms: segment.start_ms + start_time_ms,
+                    end_ms: segment.end_ms + start_time_ms,
+                    ..segment.clone()
+                })
+        })
+        .collect();
+    captions.sort_by_key(|c| c.start_ms);
+
+    captions
+}
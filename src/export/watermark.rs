@@ -0,0 +1,208 @@
+use std::path::PathBuf;
+
+use fontdue::layout::{CoordinateSystem, Layout, TextStyle};
+use fontdue::Font;
+use image::{imageops, Rgba, RgbaImage};
+
+use crate::fonts::FontManager;
+
+/// What a watermark shows
+#[derive(Clone, PartialEq, Debug)]
+pub enum WatermarkContent {
+    Image {
+        path: PathBuf,
+        width: f32, // as a fraction of the frame's width
+    },
+    Text {
+        text: String,
+        font_family: String,
+        size: f32, // line height as a fraction of the frame's shorter side
+        color: [i32; 4],
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum WatermarkPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+    Center,
+}
+
+/// An image or text composited over every exported frame, without being part of the canvas
+#[derive(Clone, PartialEq, Debug)]
+pub struct WatermarkSettings {
+    pub content: WatermarkContent,
+    pub position: WatermarkPosition,
+    pub opacity: f32, // 0.0 to 1.0
+    pub margin: f32,  // gap to the frame's edges, as a fraction of the shorter side
+}
+
+impl WatermarkSettings {
+    pub fn image(path: PathBuf) -> Self {
+        WatermarkSettings {
+            content: WatermarkContent::Image { path, width: 0.12 },
+            position: WatermarkPosition::default(),
+            opacity: 0.8,
+            margin: 0.03,
+        }
+    }
+
+    pub fn text(text: String) -> Self {
+        WatermarkSettings {
+            content: WatermarkContent::Text {
+                text,
+                font_family: "Aleo".to_string(),
+                size: 0.04,
+                color: [255, 255, 255, 255],
+            },
+            position: WatermarkPosition::default(),
+            opacity: 0.6,
+            margin: 0.03,
+        }
+    }
+}
+
+/// A watermark rendered for one frame size, ready to stamp
+pub struct Watermark {
+    pub image: RgbaImage,
+    pub left: i64,
+    pub top: i64,
+    pub opacity: f32,
+}
+
+impl Watermark {
+    /// Loads or renders the watermark's image and places it in frames of this size
+    pub fn prepare(
+        settings: &WatermarkSettings,
+        font_manager: &FontManager,
+        frame_width: u32,
+        frame_height: u32,
+    ) -> Result<Self, String> {
+        let short_side = frame_width.min(frame_height) as f32;
+
+        let image = match &settings.content {
+            WatermarkContent::Image { path, width } => {
+                let image = image::open(path)
+                    .map_err(|e| format!("Couldn't open {}: {}", path.display(), e))?
+                    .to_rgba8();
+                if image.width() == 0 || image.height() == 0 {
+                    return Err(format!("Watermark {} is empty", path.display()));
+                }
+
+                let scaled_width = ((frame_width as f32 * width).round() as u32).max(1);
+                let scaled_height = ((scaled_width as f32 * image.height() as f32
+                    / image.width() as f32)
+                    .round() as u32)
+                    .max(1);
+                imageops::resize(
+                    &image,
+                    scaled_width,
+                    scaled_height,
+                    imageops::FilterType::Lanczos3,
+                )
+            }
+            WatermarkContent::Text {
+                text,
+                font_family,
+                size,
+                color,
+            } => {
+                let font_data = font_manager
+                    .get_font_by_name(font_family)
+                    .ok_or_else(|| format!("Couldn't find font {}", font_family))?;
+                let font = Font::from_bytes(font_data, fontdue::FontSettings::default())
+                    .map_err(|e| format!("Couldn't load font {}: {}", font_family, e))?;
+                render_text(&font, text, (short_side * size).max(1.0), *color)?
+            }
+        };
+
+        let margin = (short_side * settings.margin).round() as i64;
+        let (width, height) = (image.width() as i64, image.height() as i64);
+        let (right, bottom) = (
+            frame_width as i64 - margin - width,
+            frame_height as i64 - margin - height,
+        );
+        let (left, top) = match settings.position {
+            WatermarkPosition::TopLeft => (margin, margin),
+            WatermarkPosition::TopRight => (right, margin),
+            WatermarkPosition::BottomLeft => (margin, bottom),
+            WatermarkPosition::BottomRight => (right, bottom),
+            WatermarkPosition::Center => (
+                (frame_width as i64 - width) / 2,
+                (frame_height as i64 - height) / 2,
+            ),
+        };
+
+        Ok(Watermark {
+            image,
+            left,
+            top,
+            opacity: settings.opacity.clamp(0.0, 1.0),
+        })
+    }
+
+    /// Blends the watermark into a BGRA frame
+    pub fn stamp(&self, frame: &mut [u8], frame_width: u32) {
+        for (x, y, pixel) in self.image.enumerate_pixels() {
+            let (frame_x, frame_y) = (self.left + x as i64, self.top + y as i64);
+            if frame_x < 0 || frame_y < 0 || frame_x >= frame_width as i64 {
+                continue;
+            }
+
+            let index = (frame_y as usize * frame_width as usize + frame_x as usize) * 4;
+            let Some(target) = frame.get_mut(index..index + 4) else {
+                continue;
+            };
+
+            let alpha = pixel[3] as f32 / 255.0 * self.opacity;
+            // frames are BGRA, the watermark RGBA
+            for (channel, value) in [pixel[2], pixel[1], pixel[0]].into_iter().enumerate() {
+                target[channel] =
+                    (target[channel] as f32 * (1.0 - alpha) + value as f32 * alpha).round() as u8;
+            }
+        }
+    }
+}
+
+/// Rasterizes a line of text into an image just big enough to hold it
+fn render_text(font: &Font, text: &str, px: f32, color: [i32; 4]) -> Result<RgbaImage, String> {
+    let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+    layout.append(&[font], &TextStyle::new(text, px, 0));
+
+    let glyphs = layout.glyphs();
+    let width = glyphs
+        .iter()
+        .map(|g| g.x + g.width as f32)
+        .fold(0.0, f32::max)
+        .ceil() as u32;
+    let height = layout.height().ceil() as u32;
+    if width == 0 || height == 0 {
+        return Err("Watermark text is empty".to_string());
+    }
+
+    let mut image = RgbaImage::new(width, height);
+    for glyph in glyphs {
+        let (metrics, coverage) = font.rasterize_config(glyph.key);
+        for (index, value) in coverage.iter().enumerate() {
+            let x = glyph.x as i64 + (index % metrics.width) as i64;
+            let y = glyph.y as i64 + (index / metrics.width) as i64;
+            if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+                continue;
+            }
+
+            // overlapping glyphs keep the most coverage
+            let alpha = (*value as f32 / 255.0 * color[3] as f32).round() as u8;
+            let alpha = alpha.max(image.get_pixel(x as u32, y as u32)[3]);
+            image.put_pixel(
+                x as u32,
+                y as u32,
+                Rgba([color[0] as u8, color[1] as u8, color[2] as u8, alpha]),
+            );
+        }
+    }
+
+    Ok(image)
+}
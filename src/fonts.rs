@@ -2,13 +2,13 @@
 
 // A font cache
 pub struct FontManager {
-    pub font_data: [(String, Vec<u8>, String); 60],
+    pub font_data: Vec<(String, Vec<u8>, String)>, // name, bytes, style
 }
 
 impl FontManager {
     /// Loads about 60 fonts into memory
     pub fn new() -> Self {
-        let font_data: [(String, Vec<u8>, String); 60] = [
+        let font_data: Vec<(String, Vec<u8>, String)> = vec![
             (
                 "Actor".to_string(),
                 include_bytes!("./fonts/actor/Actor-Regular.ttf").to_vec(),
@@ -321,6 +321,19 @@ impl FontManager {
             .map(|(_, bytes, _)| bytes.as_slice())
     }
 
+    /// Adds a font from outside the bundled set, replacing any font with the same name
+    pub fn register_font(&mut self, name: &str, bytes: Vec<u8>) -> Result<(), String> {
+        fontdue::Font::from_bytes(bytes.as_slice(), fontdue::FontSettings::default())
+            .map_err(|e| format!("Couldn't load font {}: {}", name, e))?;
+
+        self.font_data
+            .retain(|(font_name, _, _)| !font_name.eq_ignore_ascii_case(name));
+        self.font_data
+            .push((name.to_string(), bytes, "Regular".to_string()));
+
+        Ok(())
+    }
+
     /// Get a list of all available font names
     pub fn get_available_font_names(&self) -> Vec<String> {
         self.font_data.iter().map(|(name, _, _)| name.clone()).collect()
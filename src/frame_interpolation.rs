@@ -0,0 +1,304 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+/// Fractions of the way between two source frames are cached in hundredths
+const CACHE_STEPS: f32 = 100.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub enum InterpolationMode {
+    /// Cross-fades neighbouring frames, cheap but ghosts on fast motion
+    Blend,
+    /// Moves pixels along block matched motion vectors before blending
+    OpticalFlow,
+}
+
+/// How intermediate frames are generated when a video has fewer frames than the export
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct FrameInterpolationSettings {
+    pub mode: InterpolationMode,
+    pub block_size: i32, // pixels per motion vector, larger is faster but blockier
+    pub search_radius: i32, // pixels searched, larger catches faster motion but is slower
+    pub cache_size: i32, // interpolated frames kept for reuse
+}
+
+impl FrameInterpolationSettings {
+    pub fn fast() -> Self {
+        FrameInterpolationSettings {
+            mode: InterpolationMode::Blend,
+            block_size: 32,
+            search_radius: 0,
+            cache_size: 4,
+        }
+    }
+
+    pub fn quality() -> Self {
+        FrameInterpolationSettings {
+            mode: InterpolationMode::OpticalFlow,
+            block_size: 16,
+            search_radius: 12,
+            cache_size: 8,
+        }
+    }
+}
+
+/// Motion from the previous frame to the next, one vector per block
+pub struct FlowField {
+    pub columns: usize,
+    pub rows: usize,
+    pub block_size: usize,
+    pub vectors: Vec<(f32, f32)>,
+}
+
+impl FlowField {
+    /// Vector at a pixel, bilinearly interpolated between block centers so block edges don't tear
+    fn vector_at(&self, x: f32, y: f32) -> (f32, f32) {
+        let half = self.block_size as f32 / 2.0;
+        let gx = ((x - half) / self.block_size as f32).clamp(0.0, (self.columns - 1) as f32);
+        let gy = ((y - half) / self.block_size as f32).clamp(0.0, (self.rows - 1) as f32);
+
+        let x0 = gx.floor() as usize;
+        let y0 = gy.floor() as usize;
+        let x1 = (x0 + 1).min(self.columns - 1);
+        let y1 = (y0 + 1).min(self.rows - 1);
+        let fx = gx - x0 as f32;
+        let fy = gy - y0 as f32;
+
+        let v = |cx: usize, cy: usize| self.vectors[cy * self.columns + cx];
+        let lerp =
+            |a: (f32, f32), b: (f32, f32), t: f32| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+
+        lerp(
+            lerp(v(x0, y0), v(x1, y0), fx),
+            lerp(v(x0, y1), v(x1, y1), fx),
+            fy,
+        )
+    }
+}
+
+/// Keeps the last two decoded frames of a video and generates frames between them.
+/// Frames are BGRA, as decoded for the video texture.
+pub struct FrameInterpolator {
+    pub settings: FrameInterpolationSettings,
+    pub width: u32,
+    pub height: u32,
+    previous: Option<Vec<u8>>,
+    next: Option<Vec<u8>>,
+    pair_index: u64, // counts frame pairs so cached results aren't reused across pairs
+    flow: Option<FlowField>,
+    cache: VecDeque<(u64, i32, Vec<u8>)>,
+}
+
+impl FrameInterpolator {
+    pub fn new(settings: FrameInterpolationSettings, width: u32, height: u32) -> Self {
+        FrameInterpolator {
+            settings,
+            width,
+            height,
+            previous: None,
+            next: None,
+            pair_index: 0,
+            flow: None,
+            cache: VecDeque::new(),
+        }
+    }
+
+    /// Adds a newly decoded frame, which becomes the end of the interpolated pair
+    pub fn push_frame(&mut self, frame: Vec<u8>) {
+        self.previous = self.next.take();
+        self.next = Some(frame);
+        self.pair_index += 1;
+        self.flow = None;
+    }
+
+    pub fn reset(&mut self) {
+        self.previous = None;
+        self.next = None;
+        self.flow = None;
+        self.cache.clear();
+    }
+
+    /// Frame `t` of the way from the previous to the next decoded frame,
+    /// or None until two frames have been decoded
+    pub fn interpolate(&mut self, t: f32) -> Option<&[u8]> {
+        let (Some(previous), Some(next)) = (&self.previous, &self.next) else {
+            return None;
+        };
+
+        // decoded frames with row padding aren't supported
+        let frame_len = self.width as usize * self.height as usize * 4;
+        if previous.len() < frame_len || next.len() < frame_len {
+            return None;
+        }
+
+        let t = t.clamp(0.0, 1.0);
+        let step = (t * CACHE_STEPS).round() as i32;
+        let pair_index = self.pair_index;
+
+        if let Some(position) = self
+            .cache
+            .iter()
+            .position(|(pair, cached_step, _)| *pair == pair_index && *cached_step == step)
+        {
+            return Some(&self.cache[position].2);
+        }
+
+        let t = step as f32 / CACHE_STEPS;
+        let frame = match self.settings.mode {
+            InterpolationMode::Blend => blend_frames(previous, next, t),
+            InterpolationMode::OpticalFlow => {
+                if self.flow.is_none() {
+                    self.flow = Some(estimate_flow(
+                        previous,
+                        next,
+                        self.width as usize,
+                        self.height as usize,
+                        self.settings.block_size.max(4) as usize,
+                        self.settings.search_radius.max(0),
+                    ));
+                }
+
+                let flow = self.flow.as_ref().expect("Couldn't get flow field");
+                flow_interpolate(
+                    previous,
+                    next,
+                    self.width as usize,
+                    self.height as usize,
+                    flow,
+                    t,
+                )
+            }
+        };
+
+        self.cache.push_back((pair_index, step, frame));
+        while self.cache.len() > self.settings.cache_size.max(1) as usize {
+            self.cache.pop_front();
+        }
+
+        self.cache.back().map(|(_, _, frame)| frame.as_slice())
+    }
+}
+
+pub fn blend_frames(previous: &[u8], next: &[u8], t: f32) -> Vec<u8> {
+    previous
+        .iter()
+        .zip(next.iter())
+        .map(|(a, b)| (*a as f32 + (*b as f32 - *a as f32) * t).round() as u8)
+        .collect()
+}
+
+fn luma(frame: &[u8], width: usize, height: usize) -> Vec<u8> {
+    (0..width * height)
+        .map(|i| {
+            let p = &frame[i * 4..i * 4 + 4];
+            // BGRA
+            ((p[2] as u32 * 77 + p[1] as u32 * 150 + p[0] as u32 * 29) >> 8) as u8
+        })
+        .collect()
+}
+
+/// Block matching motion estimation, finding where each block of the previous frame moved to
+pub fn estimate_flow(
+    previous: &[u8],
+    next: &[u8],
+    width: usize,
+    height: usize,
+    block_size: usize,
+    search_radius: i32,
+) -> FlowField {
+    let columns = (width / block_size).max(1);
+    let rows = (height / block_size).max(1);
+    let previous_luma = luma(previous, width, height);
+    let next_luma = luma(next, width, height);
+
+    // every other pixel is enough to rank candidates
+    let sample_step = 2;
+
+    let mut vectors = Vec::with_capacity(columns * rows);
+    for row in 0..rows {
+        for column in 0..columns {
+            let bx = (column * block_size) as i32;
+            let by = (row * block_size) as i32;
+
+            let mut best = (0, 0);
+            let mut best_cost = u32::MAX;
+
+            for dy in -search_radius..=search_radius {
+                for dx in -search_radius..=search_radius {
+                    if bx + dx < 0
+                        || by + dy < 0
+                        || bx + dx + block_size as i32 > width as i32
+                        || by + dy + block_size as i32 > height as i32
+                    {
+                        continue;
+                    }
+
+                    let mut cost = 0u32;
+                    for y in (0..block_size).step_by(sample_step) {
+                        let source_row = (by as usize + y) * width + bx as usize;
+                        let target_row = ((by + dy) as usize + y) * width + (bx + dx) as usize;
+                        for x in (0..block_size).step_by(sample_step) {
+                            cost += (previous_luma[source_row + x] as i32
+                                - next_luma[target_row + x] as i32)
+                                .unsigned_abs();
+                        }
+                        if cost >= best_cost {
+                            break;
+                        }
+                    }
+
+                    // prefer no motion when costs tie, which keeps flat areas still
+                    let tie_break = (dx.abs() + dy.abs()) as u32;
+                    if cost + tie_break < best_cost {
+                        best_cost = cost + tie_break;
+                        best = (dx, dy);
+                    }
+                }
+            }
+
+            vectors.push((best.0 as f32, best.1 as f32));
+        }
+    }
+
+    FlowField {
+        columns,
+        rows,
+        block_size,
+        vectors,
+    }
+}
+
+/// Samples both frames along the motion vectors, so moving content is placed where it
+/// would be at `t` rather than ghosted at both ends
+pub fn flow_interpolate(
+    previous: &[u8],
+    next: &[u8],
+    width: usize,
+    height: usize,
+    flow: &FlowField,
+    t: f32,
+) -> Vec<u8> {
+    let mut frame = vec![0u8; width * height * 4];
+
+    let sample = |frame: &[u8], x: f32, y: f32| {
+        let x = (x.round() as i32).clamp(0, width as i32 - 1) as usize;
+        let y = (y.round() as i32).clamp(0, height as i32 - 1) as usize;
+        let i = (y * width + x) * 4;
+        [frame[i], frame[i + 1], frame[i + 2], frame[i + 3]]
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let (vx, vy) = flow.vector_at(x as f32, y as f32);
+            let a = sample(previous, x as f32 - vx * t, y as f32 - vy * t);
+            let b = sample(next, x as f32 + vx * (1.0 - t), y as f32 + vy * (1.0 - t));
+
+            let i = (y * width + x) * 4;
+            for c in 0..4 {
+                frame[i + c] = (a[c] as f32 + (b[c] as f32 - a[c] as f32) * t).round() as u8;
+            }
+        }
+    }
+
+    frame
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use cgmath::{Matrix4, Rad, SquareMatrix, Vector3};
+use wgpu::util::DeviceExt;
+
+use crate::editor::{Point, WindowSize};
+use crate::instancing::{tessellate_for_instancing, InstanceRaw, InstancedMesh};
+use crate::polygon::{create_default_bind_group, Stroke};
+use crate::transform::{create_empty_group_transform, matrix4_to_raw_array};
+use crate::vertex::get_z_layer;
+
+/// Above every object, like the handles that were polygons before
+pub const GIZMO_LAYER: f32 = 100.0;
+
+/// Meshes for sizes not drawn for this many frames are freed
+const MAX_IDLE_FRAMES: u32 = 120;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GizmoShape {
+    Square,
+    Circle,
+}
+
+/// An editor overlay such as a resize handle, path handle or cursor dot. Gizmos are plain
+/// data, drawn by a `GizmoRenderer` without buffers of their own.
+#[derive(Clone, Copy, Debug)]
+pub struct Gizmo {
+    pub shape: GizmoShape,
+    pub position: Point, // center, in the same space as object positions
+    pub size: f32,
+    pub rotation: f32,
+    pub fill: [f32; 4],
+    pub stroke: Stroke,
+}
+
+impl Gizmo {
+    /// A filled circle without a stroke, like the cursor dot
+    pub fn dot(position: Point, size: f32, fill: [f32; 4]) -> Self {
+        Gizmo {
+            shape: GizmoShape::Circle,
+            position,
+            size,
+            rotation: 0.0,
+            fill,
+            stroke: Stroke {
+                thickness: 0.0,
+                fill,
+            },
+        }
+    }
+
+    pub fn contains_point(&self, point: &Point) -> bool {
+        let dx = point.x - self.position.x;
+        let dy = point.y - self.position.y;
+        let half_size = self.size / 2.0;
+
+        match self.shape {
+            GizmoShape::Circle => dx * dx + dy * dy <= half_size * half_size,
+            GizmoShape::Square => {
+                // undo the rotation to test against the square's own axes
+                let (sin, cos) = (-self.rotation).sin_cos();
+                let local_x = dx * cos - dy * sin;
+                let local_y = dx * sin + dy * cos;
+
+                local_x.abs() <= half_size && local_y.abs() <= half_size
+            }
+        }
+    }
+
+    fn instance(&self) -> InstanceRaw {
+        let translation = Matrix4::from_translation(Vector3::new(
+            self.position.x,
+            self.position.y,
+            get_z_layer(GIZMO_LAYER),
+        ));
+        let rotation = Matrix4::from_angle_z(Rad(self.rotation));
+
+        InstanceRaw {
+            model: matrix4_to_raw_array(&(translation * rotation)),
+            fill: self.fill,
+            stroke: self.stroke.fill,
+        }
+    }
+}
+
+/// Gizmos with equal keys share a mesh
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct GizmoKey {
+    shape: GizmoShape,
+    size: u32,
+    stroke_thickness: u32,
+}
+
+impl GizmoKey {
+    fn new(gizmo: &Gizmo) -> Self {
+        GizmoKey {
+            shape: gizmo.shape,
+            size: gizmo.size.to_bits(),
+            stroke_thickness: gizmo.stroke.thickness.to_bits(),
+        }
+    }
+
+    fn create_mesh(&self, device: &wgpu::Device) -> InstancedMesh {
+        let size = f32::from_bits(self.size);
+        let border_radius = match self.shape {
+            GizmoShape::Square => 0.0,
+            GizmoShape::Circle => size / 2.0,
+        };
+
+        let geometry = tessellate_for_instancing(
+            vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 1.0, y: 0.0 },
+                Point { x: 1.0, y: 1.0 },
+                Point { x: 0.0, y: 1.0 },
+            ],
+            &[],
+            (size, size),
+            border_radius,
+            f32::from_bits(self.stroke_thickness),
+        );
+
+        InstancedMesh::from_geometry(device, &geometry, String::new())
+    }
+}
+
+/// Draws all gizmos with one instanced call per shape and size, using the pipeline from
+/// `create_instanced_pipeline`. Only the instance data is uploaded when gizmos change.
+pub struct GizmoRenderer {
+    meshes: HashMap<GizmoKey, InstancedMesh>,
+    bind_group: wgpu::BindGroup,
+    group_bind_group: wgpu::BindGroup,
+}
+
+impl GizmoRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        model_bind_group_layout: &wgpu::BindGroupLayout,
+        group_bind_group_layout: &wgpu::BindGroupLayout,
+        window_size: &WindowSize,
+    ) -> Self {
+        let raw_matrix = matrix4_to_raw_array(&Matrix4::<f32>::identity());
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Gizmo Uniform Buffer"),
+            contents: bytemuck::cast_slice(&raw_matrix),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bind_group =
+            create_default_bind_group(device, queue, model_bind_group_layout, &uniform_buffer);
+        let (group_bind_group, _) =
+            create_empty_group_transform(device, group_bind_group_layout, window_size);
+
+        GizmoRenderer {
+            meshes: HashMap::new(),
+            bind_group,
+            group_bind_group,
+        }
+    }
+
+    /// Uploads instance data for the gizmos to draw, such as `Editor::gizmos`
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, gizmos: &[Gizmo]) {
+        let mut batches: HashMap<GizmoKey, Vec<InstanceRaw>> = HashMap::new();
+        for gizmo in gizmos {
+            batches
+                .entry(GizmoKey::new(gizmo))
+                .or_default()
+                .push(gizmo.instance());
+        }
+
+        for mesh in self.meshes.values_mut() {
+            mesh.instance_count = 0;
+            mesh.idle_frames += 1;
+        }
+
+        for (key, instances) in batches {
+            self.meshes
+                .entry(key)
+                .or_insert_with(|| key.create_mesh(device))
+                .write_instances(device, queue, &instances);
+        }
+
+        self.meshes
+            .retain(|_, mesh| mesh.idle_frames < MAX_IDLE_FRAMES);
+    }
+
+    /// Draws the gizmos from the last `prepare`, after the objects so they stay on top
+    pub fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        instanced_pipeline: &'a wgpu::RenderPipeline,
+    ) {
+        render_pass.set_pipeline(instanced_pipeline);
+        render_pass.set_bind_group(1, &self.bind_group, &[]);
+        render_pass.set_bind_group(3, &self.group_bind_group, &[]);
+
+        for mesh in self.meshes.values().filter(|mesh| mesh.instance_count > 0) {
+            mesh.draw(render_pass);
+        }
+    }
+}
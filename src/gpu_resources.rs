@@ -1,6 +1,112 @@
 use std::sync::Arc;
 use wgpu::{Adapter, Device, Queue, Surface};
 
+/// Which GPU to run on. Set `STUNTS_GPU_ADAPTER` to "high-performance", "low-power",
+/// "software" or part of an adapter name to choose without code changes.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub enum AdapterPreference {
+    #[default]
+    Default,
+    HighPerformance,
+    LowPower,
+    /// The first adapter whose name contains this, ignoring case
+    Named(String),
+    /// A CPU rasterizer such as WARP or llvmpipe, for VMs and CI without a GPU
+    Software,
+}
+
+impl AdapterPreference {
+    pub fn from_env() -> Self {
+        match std::env::var("STUNTS_GPU_ADAPTER") {
+            Ok(value) => match value.to_lowercase().as_str() {
+                "" | "default" => AdapterPreference::Default,
+                "high-performance" => AdapterPreference::HighPerformance,
+                "low-power" => AdapterPreference::LowPower,
+                "software" | "fallback" | "cpu" => AdapterPreference::Software,
+                _ => AdapterPreference::Named(value),
+            },
+            Err(_) => AdapterPreference::Default,
+        }
+    }
+}
+
+/// Finds an adapter matching the preference, able to present to the surface if one is given
+pub async fn request_adapter(
+    instance: &wgpu::Instance,
+    preference: &AdapterPreference,
+    compatible_surface: Option<&Surface<'_>>,
+) -> Result<Adapter, String> {
+    let supports_surface = |adapter: &Adapter| {
+        compatible_surface.map_or(true, |surface| adapter.is_surface_supported(surface))
+    };
+
+    let power_preference = match preference {
+        AdapterPreference::HighPerformance => wgpu::PowerPreference::HighPerformance,
+        AdapterPreference::LowPower => wgpu::PowerPreference::LowPower,
+        _ => wgpu::PowerPreference::default(),
+    };
+
+    // browsers only offer the adapter they choose, so these are native only
+    match preference {
+        #[cfg(not(target_arch = "wasm32"))]
+        AdapterPreference::Named(name) => {
+            let name = name.to_lowercase();
+            return instance
+                .enumerate_adapters(wgpu::Backends::all())
+                .into_iter()
+                .find(|adapter| {
+                    adapter.get_info().name.to_lowercase().contains(&name)
+                        && supports_surface(adapter)
+                })
+                .ok_or_else(|| format!("No gpu adapter found named {}", name));
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        AdapterPreference::Software => {
+            if let Some(adapter) = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference,
+                    compatible_surface,
+                    force_fallback_adapter: true,
+                })
+                .await
+            {
+                return Ok(adapter);
+            }
+
+            // not every backend offers a fallback adapter, but CPU adapters work the same
+            return instance
+                .enumerate_adapters(wgpu::Backends::all())
+                .into_iter()
+                .find(|adapter| {
+                    adapter.get_info().device_type == wgpu::DeviceType::Cpu
+                        && supports_surface(adapter)
+                })
+                .ok_or_else(|| "No software gpu adapter found".to_string());
+        }
+        _ => {}
+    }
+
+    instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference,
+            compatible_surface,
+            force_fallback_adapter: false,
+        })
+        .await
+        .ok_or_else(|| "Couldn't get gpu adapter".to_string())
+}
+
+/// Limits to request from an adapter, lowered for software and older adapters which
+/// can't meet the defaults
+pub fn required_limits(adapter: &Adapter) -> wgpu::Limits {
+    let info = adapter.get_info();
+    if info.device_type == wgpu::DeviceType::Cpu || info.backend == wgpu::Backend::Gl {
+        wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits())
+    } else {
+        wgpu::Limits::default()
+    }
+}
+
 /// GPU resources wrapper for compatibility with the stunts-engine
 /// This replaces the floem_renderer::gpu_resources::GpuResources
 /// 
@@ -11,6 +117,7 @@ pub struct GpuResources {
     pub surface: Option<Arc<Surface<'static>>>,
     pub device: Arc<Device>,
     pub queue: Arc<Queue>,
+    pub adapter_info: Option<wgpu::AdapterInfo>, // None when created from another renderer's device
 }
 
 impl GpuResources {
@@ -20,24 +127,60 @@ impl GpuResources {
             surface: None,
             device,
             queue,
+            adapter_info: None,
         }
     }
 
     /// Create GpuResources with full wgpu resources (for standalone usage)
-    pub fn new(_adapter: Adapter, device: Device, queue: Queue) -> Self {
+    pub fn new(adapter: Adapter, device: Device, queue: Queue) -> Self {
         Self {
             surface: None,
             device: Arc::new(device),
             queue: Arc::new(queue),
+            adapter_info: Some(adapter.get_info()),
         }
     }
 
+    /// Creates a device with no window, on the preferred adapter, for exports and tests
+    pub async fn headless(preference: &AdapterPreference) -> Result<Self, String> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+
+        let adapter = request_adapter(&instance, preference, None).await?;
+        let info = adapter.get_info();
+        println!("Using gpu adapter {} ({:?})", info.name, info.backend);
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: None,
+                    required_limits: required_limits(&adapter),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self::new(adapter, device, queue))
+    }
+
+    /// Whether rendering runs on the CPU, which is much slower
+    pub fn is_software(&self) -> bool {
+        self.adapter_info
+            .as_ref()
+            .map_or(false, |info| info.device_type == wgpu::DeviceType::Cpu)
+    }
+
     /// Create GpuResources with surface
-    pub fn with_surface(_adapter: Adapter, device: Device, queue: Queue, surface: Arc<Surface<'static>>) -> Self {
+    pub fn with_surface(adapter: Adapter, device: Device, queue: Queue, surface: Arc<Surface<'static>>) -> Self {
         Self {
             surface: Some(surface),
             device: Arc::new(device),
             queue: Arc::new(queue),
+            adapter_info: Some(adapter.get_info()),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,162 @@
+use crate::editor::{BoundingBox, Point};
+use crate::guides::GuideLine;
+
+/// Thickness of the ruler strips drawn outside the canvas edges
+pub const RULER_SIZE: f32 = 20.0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct GridSettings {
+    pub visible: bool,
+    pub show_rulers: bool,
+    pub snap_to_grid: bool,
+    /// Distance between grid lines in pixels
+    pub spacing: f32,
+    /// Every nth line is drawn as a major line and ruler tick
+    pub major_every: u32,
+}
+
+impl Default for GridSettings {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            show_rulers: false,
+            snap_to_grid: false,
+            spacing: 20.0,
+            major_every: 5,
+        }
+    }
+}
+
+/// A ruler tick, with the canvas-relative value a host can use as its label
+#[derive(Clone, Copy, Debug)]
+pub struct RulerTick {
+    pub line: GuideLine,
+    pub value: f32,
+    pub is_major: bool,
+}
+
+/// Rounds a canvas-relative coordinate to the nearest grid line
+pub fn snap_value_to_grid(value: f32, origin: f32, spacing: f32) -> f32 {
+    if spacing <= 0.0 {
+        return value;
+    }
+
+    ((value - origin) / spacing).round() * spacing + origin
+}
+
+/// The next grid line past a coordinate in a direction, for stepping along the grid. A
+/// coordinate already on a line steps to the one after it.
+pub fn next_grid_value(value: f32, origin: f32, spacing: f32, direction: f32) -> f32 {
+    if spacing <= 0.0 || direction == 0.0 {
+        return value;
+    }
+
+    let steps = (value - origin) / spacing;
+    // small enough to absorb rounding from earlier moves
+    let tolerance = 0.001;
+    let next = if direction > 0.0 {
+        (steps + tolerance).floor() + 1.0
+    } else {
+        (steps - tolerance).ceil() - 1.0
+    };
+
+    next * spacing + origin
+}
+
+/// Offset which moves the box's top left corner onto the nearest grid intersection
+pub fn grid_snap_offset(moving: &BoundingBox, canvas: &BoundingBox, spacing: f32) -> Point {
+    Point {
+        x: snap_value_to_grid(moving.min.x, canvas.min.x, spacing) - moving.min.x,
+        y: snap_value_to_grid(moving.min.y, canvas.min.y, spacing) - moving.min.y,
+    }
+}
+
+/// Vertical and horizontal lines covering the canvas, paired with whether each is a major line
+pub fn grid_lines(canvas: &BoundingBox, settings: &GridSettings) -> Vec<(GuideLine, bool)> {
+    let mut lines = Vec::new();
+
+    if settings.spacing <= 0.0 {
+        return lines;
+    }
+
+    let major_every = settings.major_every.max(1);
+
+    let mut index = 0;
+    let mut x = canvas.min.x;
+    while x <= canvas.max.x {
+        lines.push((
+            GuideLine {
+                start: Point { x, y: canvas.min.y },
+                end: Point { x, y: canvas.max.y },
+            },
+            index % major_every == 0,
+        ));
+        index += 1;
+        x = canvas.min.x + index as f32 * settings.spacing;
+    }
+
+    let mut index = 0;
+    let mut y = canvas.min.y;
+    while y <= canvas.max.y {
+        lines.push((
+            GuideLine {
+                start: Point { x: canvas.min.x, y },
+                end: Point { x: canvas.max.x, y },
+            },
+            index % major_every == 0,
+        ));
+        index += 1;
+        y = canvas.min.y + index as f32 * settings.spacing;
+    }
+
+    lines
+}
+
+/// Ticks for the rulers along the top and left edges, just outside the canvas
+pub fn ruler_ticks(canvas: &BoundingBox, settings: &GridSettings) -> Vec<RulerTick> {
+    let mut ticks = Vec::new();
+
+    if settings.spacing <= 0.0 {
+        return ticks;
+    }
+
+    let major_every = settings.major_every.max(1);
+
+    let mut index = 0;
+    let mut x = canvas.min.x;
+    while x <= canvas.max.x {
+        let is_major = index % major_every == 0;
+        let length = if is_major { RULER_SIZE } else { RULER_SIZE / 3.0 };
+
+        ticks.push(RulerTick {
+            line: GuideLine {
+                start: Point { x, y: canvas.min.y - length },
+                end: Point { x, y: canvas.min.y },
+            },
+            value: x - canvas.min.x,
+            is_major,
+        });
+        index += 1;
+        x = canvas.min.x + index as f32 * settings.spacing;
+    }
+
+    let mut index = 0;
+    let mut y = canvas.min.y;
+    while y <= canvas.max.y {
+        let is_major = index % major_every == 0;
+        let length = if is_major { RULER_SIZE } else { RULER_SIZE / 3.0 };
+
+        ticks.push(RulerTick {
+            line: GuideLine {
+                start: Point { x: canvas.min.x - length, y },
+                end: Point { x: canvas.min.x, y },
+            },
+            value: y - canvas.min.y,
+            is_major,
+        });
+        index += 1;
+        y = canvas.min.y + index as f32 * settings.spacing;
+    }
+
+    ticks
+}
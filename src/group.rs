@@ -0,0 +1,191 @@
+use cgmath::{Matrix4, Rad, Vector2, Vector3};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use wgpu::util::DeviceExt;
+
+use crate::animations::ObjectType;
+use crate::editor::{Point, WindowSize, CANVAS_HORIZ_OFFSET, CANVAS_VERT_OFFSET};
+use crate::polygon::SavedPoint;
+use crate::tags::ColorTag;
+use crate::transform::{matrix4_to_raw_array, tilt_matrix, Transform};
+use crate::widgets::SavedWidgetConfig;
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct GroupChild {
+    pub object_id: String,
+    pub object_type: ObjectType,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct SavedGroupConfig {
+    pub id: String,
+    pub name: String,
+    pub children: Vec<GroupChild>,
+    /// Center of the children when grouped, which the group rotates and scales around
+    pub pivot: SavedPoint,
+    /// Current center of the group, equal to the pivot until the group is moved
+    pub position: SavedPoint,
+    pub rotation: i32, // degrees
+    pub scale: i32,    // out of 100, like scale keyframes
+    #[serde(default)]
+    pub tags: Vec<ColorTag>,
+    #[serde(default)]
+    pub widget: Option<SavedWidgetConfig>, // progress bars, gauges and counters
+}
+
+/// Parents several objects under one transform. The group matrix is written to a uniform
+/// buffer which every child binds as its group bind group (3), so children keep their own
+/// transforms and the group moves, rotates and scales them together.
+pub struct ObjectGroup {
+    pub id: Uuid,
+    pub current_sequence_id: Uuid,
+    pub name: String,
+    pub children: Vec<GroupChild>,
+    pub pivot: Point, // world space
+    pub transform: Transform,
+    pub bind_group: wgpu::BindGroup,
+    pub hidden: bool,
+    pub widget: Option<SavedWidgetConfig>,
+    pub widget_percent: Option<f32>, // last value drawn, None until playback draws one
+}
+
+impl ObjectGroup {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        group_bind_group_layout: &wgpu::BindGroupLayout,
+        window_size: &WindowSize,
+        config: &SavedGroupConfig,
+        current_sequence_id: Uuid,
+    ) -> Self {
+        let pivot = Point {
+            x: CANVAS_HORIZ_OFFSET + config.pivot.x as f32,
+            y: CANVAS_VERT_OFFSET + config.pivot.y as f32,
+        };
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Group Uniform Buffer"),
+            contents: bytemuck::cast_slice(&matrix4_to_raw_array(&Matrix4::from_scale(1.0))),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: group_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: None,
+        });
+
+        let mut transform = Transform::new(
+            Vector2::new(
+                CANVAS_HORIZ_OFFSET + config.position.x as f32,
+                CANVAS_VERT_OFFSET + config.position.y as f32,
+            ),
+            0.0,
+            Vector2::new(config.scale as f32 / 100.0, config.scale as f32 / 100.0),
+            uniform_buffer,
+            window_size,
+        );
+        transform.update_rotation_degrees(config.rotation as f32);
+
+        let group = ObjectGroup {
+            id: Uuid::parse_str(&config.id).expect("Couldn't convert string to uuid"),
+            current_sequence_id,
+            name: config.name.clone(),
+            children: config.children.clone(),
+            pivot,
+            transform,
+            bind_group,
+            hidden: false,
+            widget: config.widget.clone(),
+            widget_percent: None,
+        };
+
+        group.update_uniform_buffer(queue);
+
+        group
+    }
+
+    /// Moves children from the pivot to the group position, rotating and scaling around the pivot
+    pub fn group_matrix(&self) -> Matrix4<f32> {
+        let to_pivot = Matrix4::from_translation(Vector3::new(-self.pivot.x, -self.pivot.y, 0.0));
+        let tilt = tilt_matrix(self.transform.rotation_x, self.transform.rotation_y);
+        let rotation = Matrix4::from_angle_z(Rad(self.transform.rotation));
+        let scale =
+            Matrix4::from_nonuniform_scale(self.transform.scale.x, self.transform.scale.y, 1.0);
+        let to_position = Matrix4::from_translation(Vector3::new(
+            self.transform.position.x,
+            self.transform.position.y,
+            0.0,
+        ));
+
+        to_position * tilt * rotation * scale * to_pivot
+    }
+
+    pub fn update_uniform_buffer(&self, queue: &wgpu::Queue) {
+        let raw_matrix = matrix4_to_raw_array(&self.group_matrix());
+        queue.write_buffer(
+            &self.transform.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&raw_matrix),
+        );
+    }
+
+    /// A bind group for a child, sharing this group's uniform buffer
+    pub fn create_child_bind_group(
+        &self,
+        device: &wgpu::Device,
+        group_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: group_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: self.transform.uniform_buffer.as_entire_binding(),
+            }],
+            label: None,
+        })
+    }
+
+    /// Where a point given in the children's own space is drawn, used for bounds and hit testing
+    pub fn to_group_space(&self, point: Point) -> Point {
+        let transformed = self.group_matrix() * cgmath::Vector4::new(point.x, point.y, 0.0, 1.0);
+
+        Point {
+            x: transformed.x,
+            y: transformed.y,
+        }
+    }
+
+    /// Maps a world point back into the children's own space, the inverse of to_group_space
+    pub fn from_group_space(&self, point: Point) -> Point {
+        let dx = point.x - self.transform.position.x;
+        let dy = point.y - self.transform.position.y;
+
+        let cos = (-self.transform.rotation).cos();
+        let sin = (-self.transform.rotation).sin();
+        let rotated_x = dx * cos - dy * sin;
+        let rotated_y = dx * sin + dy * cos;
+
+        Point {
+            x: rotated_x / self.transform.scale.x + self.pivot.x,
+            y: rotated_y / self.transform.scale.y + self.pivot.y,
+        }
+    }
+
+    /// Writes the current transform into the saved config, leaving its children and tags as-is
+    pub fn update_saved_config(&self, config: &mut SavedGroupConfig) {
+        config.position = SavedPoint {
+            x: (self.transform.position.x - CANVAS_HORIZ_OFFSET) as i32,
+            y: (self.transform.position.y - CANVAS_VERT_OFFSET) as i32,
+        };
+        config.rotation = self.transform.rotation.to_degrees() as i32;
+        config.scale = (self.transform.scale.x * 100.0) as i32;
+    }
+
+    pub fn contains_child(&self, object_id: &str) -> bool {
+        self.children.iter().any(|c| c.object_id == object_id)
+    }
+}
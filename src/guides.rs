@@ -0,0 +1,197 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::camera::Camera3D as Camera;
+use crate::editor::{rgb_to_wgpu, BoundingBox, Point, WindowSize};
+use crate::polygon::{Polygon, Stroke};
+
+/// A temporary alignment guide shown while dragging
+#[derive(Clone, Copy, Debug)]
+pub struct GuideLine {
+    pub start: Point,
+    pub end: Point,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SnapSettings {
+    pub enabled: bool,
+    /// Max distance in pixels at which an edge or center will snap
+    pub threshold: f32,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 6.0,
+        }
+    }
+}
+
+pub struct SnapResult {
+    /// Offset to apply to the moving object so it lines up
+    pub offset: Point,
+    pub guide_lines: Vec<GuideLine>,
+    pub snapped_x: bool,
+    pub snapped_y: bool,
+}
+
+// min, center, max along one axis
+fn x_stops(bbox: &BoundingBox) -> [f32; 3] {
+    [bbox.min.x, (bbox.min.x + bbox.max.x) / 2.0, bbox.max.x]
+}
+
+fn y_stops(bbox: &BoundingBox) -> [f32; 3] {
+    [bbox.min.y, (bbox.min.y + bbox.max.y) / 2.0, bbox.max.y]
+}
+
+/// Finds the closest alignment along one axis, returning (offset, matched line, target index)
+fn closest_stop(
+    moving_stops: [f32; 3],
+    target_stops: &[[f32; 3]],
+    threshold: f32,
+) -> Option<(f32, f32, usize)> {
+    let mut best: Option<(f32, f32, usize)> = None;
+
+    for (target_index, stops) in target_stops.iter().enumerate() {
+        for target in stops {
+            for moving in &moving_stops {
+                let offset = target - moving;
+
+                if offset.abs() > threshold {
+                    continue;
+                }
+
+                let is_better = match best {
+                    Some((best_offset, _, _)) => offset.abs() < best_offset.abs(),
+                    None => true,
+                };
+
+                if is_better {
+                    best = Some((offset, *target, target_index));
+                }
+            }
+        }
+    }
+
+    best
+}
+
+/// Snaps the moving bounding box to the canvas and the other objects' edges and centers.
+/// The canvas, when provided, is treated as the last target.
+pub fn snap_bounding_box(
+    moving: &BoundingBox,
+    targets: &[BoundingBox],
+    canvas: Option<&BoundingBox>,
+    threshold: f32,
+) -> SnapResult {
+    let mut all_targets: Vec<BoundingBox> = targets.to_vec();
+    if let Some(canvas) = canvas {
+        all_targets.push(*canvas);
+    }
+
+    let target_x_stops: Vec<[f32; 3]> = all_targets.iter().map(x_stops).collect();
+    let target_y_stops: Vec<[f32; 3]> = all_targets.iter().map(y_stops).collect();
+
+    let mut offset = Point { x: 0.0, y: 0.0 };
+    let mut guide_lines = Vec::new();
+    let mut snapped_x = false;
+    let mut snapped_y = false;
+
+    if let Some((offset_x, line_x, target_index)) =
+        closest_stop(x_stops(moving), &target_x_stops, threshold)
+    {
+        offset.x = offset_x;
+        snapped_x = true;
+
+        // vertical guide spanning both the moving object and its target
+        let target = &all_targets[target_index];
+        guide_lines.push(GuideLine {
+            start: Point {
+                x: line_x,
+                y: moving.min.y.min(target.min.y),
+            },
+            end: Point {
+                x: line_x,
+                y: moving.max.y.max(target.max.y),
+            },
+        });
+    }
+
+    if let Some((offset_y, line_y, target_index)) =
+        closest_stop(y_stops(moving), &target_y_stops, threshold)
+    {
+        offset.y = offset_y;
+        snapped_y = true;
+
+        // horizontal guide spanning both the moving object and its target
+        let target = &all_targets[target_index];
+        guide_lines.push(GuideLine {
+            start: Point {
+                x: moving.min.x.min(target.min.x),
+                y: line_y,
+            },
+            end: Point {
+                x: moving.max.x.max(target.max.x),
+                y: line_y,
+            },
+        });
+    }
+
+    SnapResult {
+        offset,
+        guide_lines,
+        snapped_x,
+        snapped_y,
+    }
+}
+
+/// Creates a thin static polygon covering the line, used for guides, grid lines and ruler ticks
+pub fn create_line_polygon(
+    window_size: &WindowSize,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    model_bind_group_layout: &Arc<wgpu::BindGroupLayout>,
+    group_bind_group_layout: &Arc<wgpu::BindGroupLayout>,
+    camera: &Camera,
+    line: &GuideLine,
+    thickness: f32,
+    fill: [f32; 4],
+    layer: i32,
+    name: String,
+) -> Polygon {
+    let width = (line.end.x - line.start.x).abs().max(thickness);
+    let height = (line.end.y - line.start.y).abs().max(thickness);
+
+    Polygon::new(
+        window_size,
+        device,
+        queue,
+        model_bind_group_layout,
+        group_bind_group_layout,
+        camera,
+        vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 1.0, y: 0.0 },
+            Point { x: 1.0, y: 1.0 },
+            Point { x: 0.0, y: 1.0 },
+        ],
+        (width, height),
+        Point {
+            x: (line.start.x + line.end.x) / 2.0,
+            y: (line.start.y + line.end.y) / 2.0,
+        },
+        0.0,
+        0.0,
+        fill,
+        Stroke {
+            thickness: 0.0,
+            fill: rgb_to_wgpu(0, 0, 0, 255.0),
+        },
+        layer,
+        name,
+        Uuid::new_v4(),
+        Uuid::nil(),
+    )
+}
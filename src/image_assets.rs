@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use wgpu::{Device, Queue};
+
+use crate::st_image::{create_image_texture, ImageResidency};
+
+/// Longest side of image textures in the editor, large enough for a 4K canvas
+pub const DEFAULT_MAX_TEXTURE_SIZE: u32 = 4096;
+
+/// A decoded image on the GPU, shared by every image item showing the same file
+pub struct ImageAsset {
+    pub texture: Arc<wgpu::Texture>,
+    pub residency: ImageResidency,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+struct ImageAssetKey {
+    path: String,
+    limit: Option<u32>,
+}
+
+/// Caches image textures by path so a photo used several times is only decoded and
+/// uploaded once. Textures are downscaled to max_texture_size with mipmaps, while the
+/// original file stays untouched for exports, which use None for full resolution.
+pub struct ImageAssetCache {
+    pub max_texture_size: Option<u32>,
+    assets: HashMap<ImageAssetKey, ImageAsset>,
+}
+
+impl Default for ImageAssetCache {
+    fn default() -> Self {
+        ImageAssetCache {
+            max_texture_size: Some(DEFAULT_MAX_TEXTURE_SIZE),
+            assets: HashMap::new(),
+        }
+    }
+}
+
+impl ImageAssetCache {
+    /// The texture for an image file, loading it on first use. An image's own size limit
+    /// applies on top of max_texture_size.
+    pub fn load(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        path: &Path,
+        max_resident_size: Option<u32>,
+    ) -> Result<(Arc<wgpu::Texture>, ImageResidency), String> {
+        let key = ImageAssetKey {
+            path: path.display().to_string(),
+            limit: self.effective_limit(max_resident_size),
+        };
+
+        if let Some(asset) = self.assets.get(&key) {
+            return Ok((asset.texture.clone(), asset.residency));
+        }
+
+        let (texture, residency) = self.load_unshared(device, queue, path, max_resident_size)?;
+        self.assets.insert(
+            key,
+            ImageAsset {
+                texture: texture.clone(),
+                residency,
+            },
+        );
+
+        Ok((texture, residency))
+    }
+
+    /// A texture of its own with the same limits, for images that write into it like
+    /// animated ones
+    pub fn load_unshared(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        path: &Path,
+        max_resident_size: Option<u32>,
+    ) -> Result<(Arc<wgpu::Texture>, ImageResidency), String> {
+        let img = image::open(path)
+            .map_err(|e| format!("Couldn't open image {}: {}", path.display(), e))?;
+        let limit = self.effective_limit(max_resident_size);
+        let (texture, _, mut residency) = create_image_texture(device, queue, &img, limit);
+        // report the image's own limit rather than the combined one
+        residency.max_resident_size = max_resident_size;
+
+        Ok((Arc::new(texture), residency))
+    }
+
+    /// Frees textures no image item uses anymore
+    pub fn release_unused(&mut self) {
+        self.assets
+            .retain(|_, asset| Arc::strong_count(&asset.texture) > 1);
+    }
+
+    /// GPU memory held by cached textures
+    pub fn gpu_bytes(&self) -> u64 {
+        self.assets
+            .values()
+            .map(|asset| asset.residency.gpu_bytes())
+            .sum()
+    }
+
+    fn effective_limit(&self, max_resident_size: Option<u32>) -> Option<u32> {
+        match (self.max_texture_size, max_resident_size) {
+            (Some(max_texture_size), Some(size)) => Some(max_texture_size.min(size)),
+            (max_texture_size, size) => max_texture_size.or(size),
+        }
+    }
+}
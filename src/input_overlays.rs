@@ -0,0 +1,398 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::animations::{
+    AnimationData, AnimationProperty, EasingType, KeyType, KeyframeValue, ObjectType, Sequence,
+    UIKeyframe,
+};
+use crate::blend::BlendMode;
+use crate::capture::{InputEvent, SourceData};
+use crate::editor::{PathType, CANVAS_HORIZ_OFFSET, CANVAS_VERT_OFFSET};
+use crate::masking::MaskMode;
+use crate::polygon::{SavedPoint, SavedPolygonConfig, SavedStroke};
+use crate::st_video::StVideo;
+use crate::text_due::SavedTextRendererConfig;
+
+/// How long a ripple takes to fade in
+const RIPPLE_FADE_IN_MS: i32 = 60;
+/// How long keystrokes take to fade in and out
+const KEYSTROKE_FADE_MS: i32 = 120;
+/// Space between keystrokes and the bottom of the video
+const KEYSTROKE_MARGIN: i32 = 40;
+
+/// Look and timing of generated click and keystroke overlays
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct InputOverlaySettings {
+    pub clicks: bool,
+    pub keystrokes: bool,
+    pub include_typing: bool, // false shows only shortcuts and special keys like Enter
+    pub ripple_size: i32,     // diameter once fully grown, in canvas pixels
+    pub ripple_fill: [i32; 4],
+    pub ripple_duration_ms: i32,
+    pub keystroke_font_family: String,
+    pub keystroke_font_size: i32,
+    pub keystroke_color: [i32; 4],
+    pub keystroke_background: [i32; 4],
+    pub keystroke_duration_ms: i32, // shortened when the next keystroke comes sooner
+}
+
+impl Default for InputOverlaySettings {
+    fn default() -> Self {
+        InputOverlaySettings {
+            clicks: true,
+            keystrokes: true,
+            include_typing: false,
+            ripple_size: 60,
+            ripple_fill: [255, 200, 40, 200],
+            ripple_duration_ms: 450,
+            keystroke_font_family: "Figtree".to_string(),
+            keystroke_font_size: 28,
+            keystroke_color: [255, 255, 255, 255],
+            keystroke_background: [20, 20, 20, 220],
+            keystroke_duration_ms: 1200,
+        }
+    }
+}
+
+/// Reads the clicks and keys recorded next to a capture's mousePositions.json
+pub fn load_input_events(mouse_path: &Path) -> Result<Vec<InputEvent>, String> {
+    let events_path = mouse_path.with_file_name("inputEvents.json");
+    let events = fs::read_to_string(&events_path)
+        .map_err(|e| format!("Couldn't read {}: {}", events_path.display(), e))?;
+
+    serde_json::from_str(&events)
+        .map_err(|e| format!("Couldn't parse {}: {}", events_path.display(), e))
+}
+
+/// Builds ripple polygons for clicks and text items for keystrokes over a captured video,
+/// returning a sequence holding only the new objects so they can be restored and merged.
+/// Each overlay is animated across the whole sequence, hidden until its event. Timing
+/// follows the video's trim and speed, though not speed ramps, and positions assume the
+/// video isn't zoomed.
+pub fn generate_input_overlays(
+    events: &[InputEvent],
+    video: &StVideo,
+    source_data: &SourceData,
+    video_start_ms: i32,
+    sequence_id: String,
+    sequence_duration_ms: i32,
+    settings: &InputOverlaySettings,
+) -> Sequence {
+    let mut overlays = Sequence {
+        id: sequence_id,
+        name: String::new(),
+        background_fill: None,
+        duration_ms: 0,
+        active_polygons: Vec::new(),
+        polygon_motion_paths: Vec::new(),
+        active_text_items: Vec::new(),
+        active_image_items: Vec::new(),
+        active_video_items: Vec::new(),
+        tags: Vec::new(),
+        outlined_text_items: Vec::new(),
+        active_groups: Vec::new(),
+        active_particle_emitters: Vec::new(),
+        camera_animation: None,
+        active_callouts: Vec::new(),
+        active_spotlights: Vec::new(),
+        caption_tracks: Vec::new(),
+        constraints: Vec::new(),
+    };
+
+    // when in the sequence an event shows, if the trimmed video shows it at all
+    let trim_start_ms = video.trim_start_ms();
+    let playable_duration_ms = video.playable_duration_ms();
+    let sequence_time = |timestamp: u128| -> Option<i32> {
+        let source_ms = timestamp as i64 - trim_start_ms;
+        if source_ms < 0 || source_ms > playable_duration_ms {
+            return None;
+        }
+
+        let time_ms = video_start_ms + (source_ms as f32 / video.speed.max(0.01)) as i32;
+        (time_ms < sequence_duration_ms).then_some(time_ms)
+    };
+
+    // the video's top left, as the transform holds its center
+    let origin = [
+        video.transform.position.x - CANVAS_HORIZ_OFFSET - video.dimensions.0 as f32 / 2.0,
+        video.transform.position.y - CANVAS_VERT_OFFSET - video.dimensions.1 as f32 / 2.0,
+    ];
+    let canvas_point = |x: f32, y: f32| -> [i32; 2] {
+        [
+            (origin[0]
+                + (x - source_data.x as f32) / video.source_dimensions.0 as f32
+                    * video.dimensions.0 as f32) as i32,
+            (origin[1]
+                + (y - source_data.y as f32) / video.source_dimensions.1 as f32
+                    * video.dimensions.1 as f32) as i32,
+        ]
+    };
+
+    let keystrokes: Vec<(i32, String)> = events
+        .iter()
+        .filter_map(|event| match event {
+            InputEvent::Keys { keys, timestamp } if settings.keystrokes => {
+                let label = keystroke_label(keys, settings.include_typing)?;
+                Some((sequence_time(*timestamp)?, label))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let clicks: Vec<(i32, [i32; 2])> = events
+        .iter()
+        .filter_map(|event| match event {
+            InputEvent::Click {
+                x, y, timestamp, ..
+            } if settings.clicks => Some((sequence_time(*timestamp)?, canvas_point(*x, *y))),
+            _ => None,
+        })
+        .collect();
+
+    for (start_ms, position) in clicks {
+        let id = Uuid::new_v4().to_string();
+        let end_ms = start_ms + settings.ripple_duration_ms;
+
+        overlays.active_polygons.push(SavedPolygonConfig {
+            id: id.clone(),
+            name: format!("Click {}", overlays.active_polygons.len() + 1),
+            fill: settings.ripple_fill,
+            dimensions: (settings.ripple_size, settings.ripple_size),
+            position: SavedPoint {
+                x: position[0],
+                y: position[1],
+            },
+            border_radius: settings.ripple_size / 2,
+            stroke: SavedStroke {
+                thickness: 0,
+                fill: [0, 0, 0, 0],
+            },
+            layer: video.layer - 1,
+            tags: Vec::new(),
+            outline: None,
+            locked: false,
+            hidden: false,
+            opacity: 100,
+            blend_mode: BlendMode::default(),
+            mask_target_id: None,
+            mask_mode: MaskMode::default(),
+        });
+
+        overlays.polygon_motion_paths.push(overlay_animation(
+            id,
+            ObjectType::Polygon,
+            position,
+            sequence_duration_ms,
+            vec![
+                keyframe_property(
+                    "Opacity",
+                    "opacity",
+                    &[
+                        (0, KeyframeValue::Opacity(0)),
+                        (start_ms, KeyframeValue::Opacity(0)),
+                        (start_ms + RIPPLE_FADE_IN_MS, KeyframeValue::Opacity(100)),
+                        (end_ms, KeyframeValue::Opacity(0)),
+                        (sequence_duration_ms, KeyframeValue::Opacity(0)),
+                    ],
+                ),
+                keyframe_property(
+                    "Scale",
+                    "scale",
+                    &[
+                        (0, KeyframeValue::Scale(30)),
+                        (start_ms, KeyframeValue::Scale(30)),
+                        (end_ms, KeyframeValue::Scale(100)),
+                        (sequence_duration_ms, KeyframeValue::Scale(100)),
+                    ],
+                ),
+            ],
+        ));
+    }
+
+    let position = [
+        (origin[0] + video.dimensions.0 as f32 / 2.0) as i32,
+        (origin[1] + video.dimensions.1 as f32) as i32
+            - KEYSTROKE_MARGIN
+            - settings.keystroke_font_size,
+    ];
+
+    for (index, (start_ms, label)) in keystrokes.iter().enumerate() {
+        let next_ms = keystrokes
+            .get(index + 1)
+            .map(|(next_ms, _)| *next_ms)
+            .unwrap_or(sequence_duration_ms);
+        let end_ms = (start_ms + settings.keystroke_duration_ms).min(next_ms);
+        let fade_ms = KEYSTROKE_FADE_MS.min((end_ms - start_ms) / 2);
+
+        let id = Uuid::new_v4().to_string();
+        let font_size = settings.keystroke_font_size;
+
+        overlays.active_text_items.push(SavedTextRendererConfig {
+            id: id.clone(),
+            name: format!("Keys {}", label),
+            text: label.clone(),
+            font_family: settings.keystroke_font_family.clone(),
+            font_size,
+            // roughly the label's width with some padding, fonts vary
+            dimensions: (
+                (label.chars().count() as i32 * font_size * 3 / 5) + font_size,
+                font_size * 8 / 5,
+            ),
+            position: SavedPoint {
+                x: position[0],
+                y: position[1],
+            },
+            layer: video.layer - 2,
+            color: settings.keystroke_color,
+            background_fill: Some(settings.keystroke_background),
+            tags: Vec::new(),
+            warp: None,
+            locked: false,
+            hidden: false,
+            opacity: 100,
+            blend_mode: BlendMode::default(),
+            mask_target_id: None,
+            mask_mode: MaskMode::default(),
+        });
+
+        overlays.polygon_motion_paths.push(overlay_animation(
+            id,
+            ObjectType::TextItem,
+            position,
+            sequence_duration_ms,
+            vec![keyframe_property(
+                "Opacity",
+                "opacity",
+                &[
+                    (0, KeyframeValue::Opacity(0)),
+                    (*start_ms, KeyframeValue::Opacity(0)),
+                    (start_ms + fade_ms, KeyframeValue::Opacity(100)),
+                    (end_ms - fade_ms, KeyframeValue::Opacity(100)),
+                    (end_ms, KeyframeValue::Opacity(0)),
+                    (sequence_duration_ms, KeyframeValue::Opacity(0)),
+                ],
+            )],
+        ));
+    }
+
+    overlays
+}
+
+/// Readable keys like "Ctrl + Shift + P", modifiers first. None for plain typing unless
+/// it's included, and for modifiers pressed on their own.
+pub fn keystroke_label(keys: &[String], include_typing: bool) -> Option<String> {
+    let modifier_order = ["Ctrl", "Alt", "Shift", "Win"];
+
+    let mut modifiers = Vec::new();
+    let mut others = Vec::new();
+    for key in keys {
+        let name = key_name(key);
+        if modifier_order.contains(&name.as_str()) {
+            if !modifiers.contains(&name) {
+                modifiers.push(name);
+            }
+        } else {
+            others.push(name);
+        }
+    }
+
+    if others.is_empty() {
+        return None;
+    }
+
+    let is_shortcut = modifiers.iter().any(|m| m != "Shift");
+    let is_typing = others
+        .iter()
+        .all(|key| key.chars().count() == 1 || key == "Space");
+    if !include_typing && !is_shortcut && is_typing {
+        return None;
+    }
+
+    modifiers.sort_by_key(|m| modifier_order.iter().position(|o| o == m));
+    modifiers.extend(others);
+
+    Some(modifiers.join(" + "))
+}
+
+/// Display name for a recorded key
+fn key_name(key: &str) -> String {
+    match key {
+        "LControl" | "RControl" => "Ctrl".to_string(),
+        "LAlt" | "RAlt" => "Alt".to_string(),
+        "LShift" | "RShift" => "Shift".to_string(),
+        "LMeta" | "RMeta" | "Meta" | "Command" | "RCommand" | "LOption" | "ROption" => {
+            "Win".to_string()
+        }
+        "Escape" => "Esc".to_string(),
+        _ => key
+            .strip_prefix("Key")
+            .or_else(|| key.strip_prefix("Numpad"))
+            .filter(|digit| digit.len() == 1)
+            .unwrap_or(key)
+            .to_string(),
+    }
+}
+
+/// An animation over the whole sequence that keeps the overlay in place
+fn overlay_animation(
+    object_id: String,
+    object_type: ObjectType,
+    position: [i32; 2],
+    sequence_duration_ms: i32,
+    mut properties: Vec<AnimationProperty>,
+) -> AnimationData {
+    properties.insert(
+        0,
+        keyframe_property(
+            "Position",
+            "position",
+            &[
+                (0, KeyframeValue::Position(position)),
+                (sequence_duration_ms, KeyframeValue::Position(position)),
+            ],
+        ),
+    );
+
+    AnimationData {
+        id: Uuid::new_v4().to_string(),
+        object_type,
+        polygon_id: object_id,
+        duration: Duration::from_millis(sequence_duration_ms.max(0) as u64),
+        start_time_ms: 0,
+        properties,
+        position: [0, 0],
+        motion_blur: 0,
+    }
+}
+
+/// Keyframes at the given times, dropping any that don't come after the one before
+pub fn keyframe_property(name: &str, path: &str, values: &[(i32, KeyframeValue)]) -> AnimationProperty {
+    let mut keyframes: Vec<UIKeyframe> = Vec::with_capacity(values.len());
+
+    for (time_ms, value) in values {
+        let time = Duration::from_millis((*time_ms).max(0) as u64);
+        if keyframes.last().map(|k| k.time >= time).unwrap_or(false) {
+            continue;
+        }
+
+        keyframes.push(UIKeyframe {
+            id: Uuid::new_v4().to_string(),
+            time,
+            value: value.clone(),
+            easing: EasingType::EaseOut,
+            path_type: PathType::Linear,
+            key_type: KeyType::Frame,
+        });
+    }
+
+    AnimationProperty {
+        name: name.to_string(),
+        property_path: path.to_string(),
+        children: Vec::new(),
+        keyframes,
+        depth: 0,
+    }
+}
@@ -0,0 +1,431 @@
+use std::collections::{HashMap, HashSet};
+
+use bytemuck::{Pod, Zeroable};
+use cgmath::{Matrix4, SquareMatrix};
+use lyon_tessellation::VertexBuffers;
+use uuid::Uuid;
+use wgpu::util::DeviceExt;
+
+use crate::animations::ObjectType;
+use crate::blend::BlendMode;
+use crate::editor::{Point, WindowSize};
+use crate::masking::depth_stencil_state;
+use crate::polygon::{create_default_bind_group, tessellate_polygon, Polygon, Stroke};
+use crate::scene::Scene;
+use crate::timelines::{set_transition_scissor, ActiveTransition};
+use crate::transform::{create_empty_group_transform, matrix4_to_raw_array};
+use crate::vertex::Vertex;
+
+/// Shapes shared by fewer polygons than this keep their own draw calls
+pub const MIN_BATCH_SIZE: usize = 4;
+
+/// Pooled meshes unused for this many frames are freed
+const MAX_IDLE_FRAMES: u32 = 120;
+
+/// Per-polygon data for instanced draws, read by `vs_instanced`
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+pub struct InstanceRaw {
+    pub model: [[f32; 4]; 4],
+    pub fill: [f32; 4],
+    pub stroke: [f32; 4],
+}
+
+impl InstanceRaw {
+    pub fn from_polygon(polygon: &Polygon, window_size: &WindowSize) -> Self {
+        let model = polygon.transform.update_transform(window_size);
+
+        // vertex colors already include the animated opacity, and stroke vertices come last
+        let fill = polygon
+            .vertices
+            .first()
+            .map(|vertex| vertex.color)
+            .unwrap_or(polygon.fill);
+        let stroke = polygon
+            .vertices
+            .last()
+            .map(|vertex| vertex.color)
+            .unwrap_or(polygon.stroke.fill);
+
+        InstanceRaw {
+            model: matrix4_to_raw_array(&model),
+            fill,
+            stroke,
+        }
+    }
+
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 6] = wgpu::vertex_attr_array![
+            3 => Float32x4, // model matrix columns
+            4 => Float32x4,
+            5 => Float32x4,
+            6 => Float32x4,
+            7 => Float32x4, // fill
+            8 => Float32x4, // stroke
+        ];
+
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<InstanceRaw>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// Polygons with equal keys tessellate to the same mesh. Sequences are kept apart
+/// since transitions clip each one separately.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+struct ShapeKey {
+    sequence_id: Uuid,
+    points: Vec<[u32; 2]>,
+    contours: Vec<Vec<[u32; 2]>>,
+    dimensions: [u32; 2],
+    border_radius: u32,
+    stroke_thickness: u32,
+}
+
+impl ShapeKey {
+    fn new(polygon: &Polygon) -> Self {
+        let bits = |points: &[Point]| {
+            points
+                .iter()
+                .map(|point| [point.x.to_bits(), point.y.to_bits()])
+                .collect::<Vec<_>>()
+        };
+
+        ShapeKey {
+            sequence_id: polygon.current_sequence_id,
+            points: bits(&polygon.points),
+            contours: polygon
+                .contours
+                .iter()
+                .map(|contour| bits(contour))
+                .collect(),
+            dimensions: [
+                polygon.dimensions.0.to_bits(),
+                polygon.dimensions.1.to_bits(),
+            ],
+            border_radius: polygon.border_radius.to_bits(),
+            stroke_thickness: polygon.stroke.thickness.to_bits(),
+        }
+    }
+}
+
+/// One tessellated shape and the instances drawn with it
+pub(crate) struct InstancedMesh {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+    instance_buffer: wgpu::Buffer,
+    instance_capacity: usize,
+    pub(crate) instance_count: u32,
+    sequence_id: String,
+    pub(crate) idle_frames: u32,
+}
+
+impl InstancedMesh {
+    fn new(device: &wgpu::Device, polygon: &Polygon) -> Self {
+        let geometry = tessellate_for_instancing(
+            polygon.points.clone(),
+            &polygon.contours,
+            polygon.dimensions,
+            polygon.border_radius,
+            polygon.stroke.thickness,
+        );
+
+        Self::from_geometry(device, &geometry, polygon.current_sequence_id.to_string())
+    }
+
+    pub(crate) fn from_geometry(
+        device: &wgpu::Device,
+        geometry: &VertexBuffers<Vertex, u32>,
+        sequence_id: String,
+    ) -> Self {
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instanced Vertex Buffer"),
+            contents: bytemuck::cast_slice(&geometry.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instanced Index Buffer"),
+            contents: bytemuck::cast_slice(&geometry.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        InstancedMesh {
+            vertex_buffer,
+            index_buffer,
+            index_count: geometry.indices.len() as u32,
+            instance_buffer: create_instance_buffer(device, MIN_BATCH_SIZE),
+            instance_capacity: MIN_BATCH_SIZE,
+            instance_count: 0,
+            sequence_id,
+            idle_frames: 0,
+        }
+    }
+
+    pub(crate) fn write_instances(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        instances: &[InstanceRaw],
+    ) {
+        // grow by doubling so a rising particle count doesn't reallocate every frame
+        if instances.len() > self.instance_capacity {
+            self.instance_capacity = instances.len().next_power_of_two();
+            self.instance_buffer = create_instance_buffer(device, self.instance_capacity);
+        }
+
+        queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(instances));
+        self.instance_count = instances.len() as u32;
+        self.idle_frames = 0;
+    }
+
+    /// Draws every instance, with the instanced pipeline and bind groups already set
+    pub(crate) fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..self.instance_count);
+    }
+}
+
+/// Vertex alpha marks fill (0) and stroke (1) for the shader to color per instance
+pub(crate) fn tessellate_for_instancing(
+    points: Vec<Point>,
+    contours: &[Vec<Point>],
+    dimensions: (f32, f32),
+    border_radius: f32,
+    stroke_thickness: f32,
+) -> VertexBuffers<Vertex, u32> {
+    tessellate_polygon(
+        points,
+        contours,
+        dimensions,
+        border_radius,
+        [0.0, 0.0, 0.0, 0.0],
+        Stroke {
+            thickness: stroke_thickness,
+            fill: [1.0, 1.0, 1.0, 1.0],
+        },
+    )
+}
+
+fn create_instance_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Instance Buffer"),
+        size: (capacity * std::mem::size_of::<InstanceRaw>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// Draws many polygons of the same shape, such as confetti rectangles, with one call per shape
+/// instead of one per polygon. Meshes and instance buffers are pooled between frames.
+pub struct PolygonInstancer {
+    meshes: HashMap<ShapeKey, InstancedMesh>,
+    bind_group: wgpu::BindGroup,
+    group_bind_group: wgpu::BindGroup,
+    pub batched_ids: HashSet<Uuid>, // drawn by `draw`, to be skipped by the regular polygon loop
+}
+
+impl PolygonInstancer {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        model_bind_group_layout: &wgpu::BindGroupLayout,
+        group_bind_group_layout: &wgpu::BindGroupLayout,
+        window_size: &WindowSize,
+    ) -> Self {
+        // the model matrix comes from each instance instead
+        let raw_matrix = matrix4_to_raw_array(&Matrix4::<f32>::identity());
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instanced Uniform Buffer"),
+            contents: bytemuck::cast_slice(&raw_matrix),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bind_group =
+            create_default_bind_group(device, queue, model_bind_group_layout, &uniform_buffer);
+        let (group_bind_group, _) =
+            create_empty_group_transform(device, group_bind_group_layout, window_size);
+
+        PolygonInstancer {
+            meshes: HashMap::new(),
+            bind_group,
+            group_bind_group,
+            batched_ids: HashSet::new(),
+        }
+    }
+
+    /// Batches the scene's polygons and uploads their instance data, call once per frame
+    /// after animations have stepped
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        scene: &Scene,
+        window_size: &WindowSize,
+    ) {
+        let unbatchable = unbatchable_ids(scene);
+        let mut batches: HashMap<ShapeKey, Vec<&Polygon>> = HashMap::new();
+        for polygon in scene.polygons.iter() {
+            if is_batchable(polygon) && !unbatchable.contains(&polygon.id.to_string()) {
+                batches
+                    .entry(ShapeKey::new(polygon))
+                    .or_default()
+                    .push(polygon);
+            }
+        }
+
+        self.batched_ids.clear();
+        for mesh in self.meshes.values_mut() {
+            mesh.instance_count = 0;
+            mesh.idle_frames += 1;
+        }
+
+        for (key, polygons) in batches {
+            if polygons.len() < MIN_BATCH_SIZE {
+                continue;
+            }
+
+            let instances: Vec<InstanceRaw> = polygons
+                .iter()
+                .map(|polygon| InstanceRaw::from_polygon(polygon, window_size))
+                .collect();
+            let mesh = self
+                .meshes
+                .entry(key)
+                .or_insert_with(|| InstancedMesh::new(device, polygons[0]));
+            mesh.write_instances(device, queue, &instances);
+
+            self.batched_ids
+                .extend(polygons.iter().map(|polygon| polygon.id));
+        }
+
+        self.meshes
+            .retain(|_, mesh| mesh.idle_frames < MAX_IDLE_FRAMES);
+    }
+
+    pub fn is_batched(&self, polygon_id: &Uuid) -> bool {
+        self.batched_ids.contains(polygon_id)
+    }
+
+    /// Draws the batches from the last `prepare`, leaving the pipeline and scissor changed
+    pub fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        instanced_pipeline: &'a wgpu::RenderPipeline,
+        transition: Option<&ActiveTransition>,
+        frame_width: u32,
+        frame_height: u32,
+    ) {
+        if self.batched_ids.is_empty() {
+            return;
+        }
+
+        render_pass.set_pipeline(instanced_pipeline);
+        render_pass.set_bind_group(1, &self.bind_group, &[]);
+        render_pass.set_bind_group(3, &self.group_bind_group, &[]);
+
+        for mesh in self.meshes.values().filter(|mesh| mesh.instance_count > 0) {
+            set_transition_scissor(
+                render_pass,
+                transition,
+                &mesh.sequence_id,
+                frame_width,
+                frame_height,
+            );
+            mesh.draw(render_pass);
+        }
+    }
+}
+
+fn is_batchable(polygon: &Polygon) -> bool {
+    !polygon.hidden
+        && !polygon.user_hidden
+        && polygon.mask_target_id.is_none()
+        && polygon.blend_mode == BlendMode::Normal
+}
+
+/// Group children have their own group transform, and masked objects draw their masks first
+fn unbatchable_ids(scene: &Scene) -> HashSet<String> {
+    let mut ids: HashSet<String> = scene
+        .groups
+        .iter()
+        .flat_map(|group| group.children.iter())
+        .filter(|child| child.object_type == ObjectType::Polygon)
+        .map(|child| child.object_id.clone())
+        .collect();
+
+    ids.extend(
+        scene
+            .polygons
+            .iter()
+            .filter_map(|p| p.mask_target_id.clone()),
+    );
+    ids.extend(
+        scene
+            .text_items
+            .iter()
+            .filter_map(|t| t.mask_target_id.clone()),
+    );
+    ids.extend(
+        scene
+            .image_items
+            .iter()
+            .filter_map(|i| i.mask_target_id.clone()),
+    );
+    ids.extend(
+        scene
+            .video_items
+            .iter()
+            .filter_map(|v| v.mask_target_id.clone()),
+    );
+
+    ids
+}
+
+/// Like the main pipeline, with each polygon's transform and colors read from the instance buffer
+pub fn create_instanced_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    vertex_shader: &wgpu::ShaderModule,
+    fragment_shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Stunts Engine Instanced Pipeline"),
+        layout: Some(layout),
+        multiview: None,
+        vertex: wgpu::VertexState {
+            module: vertex_shader,
+            entry_point: "vs_instanced",
+            buffers: &[Vertex::desc(), InstanceRaw::desc()],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: fragment_shader,
+            entry_point: BlendMode::Normal.fragment_entry_point(),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(BlendMode::Normal.blend_state()),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            conservative: false,
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+        },
+        depth_stencil: Some(depth_stencil_state()),
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+    })
+}
@@ -1,22 +1,81 @@
 #![allow(unused_variables)]
 
+pub mod animated_image;
 pub mod animations;
+pub mod autosave;
+pub mod beats;
+pub mod blend;
+pub mod brand_kit;
+pub mod callout;
 pub mod camera;
+pub mod captions;
+#[cfg_attr(target_arch = "wasm32", path = "capture_web.rs")]
 pub mod capture;
+pub mod choreography;
+pub mod clipboard;
+pub mod color_management;
+pub mod constraints;
+pub mod cursor;
 pub mod dot;
 pub mod editor;
+pub mod editor_handle;
+pub mod error;
+pub mod events;
 pub mod export;
 pub mod fonts;
+pub mod frame_interpolation;
+pub mod gizmos;
 pub mod gpu_resources;
+pub mod grid;
+pub mod group;
+pub mod guides;
+pub mod image_assets;
+pub mod input_overlays;
+pub mod instancing;
+pub mod masking;
+pub mod matting;
+pub mod migrations;
 pub mod motion_arrow;
 pub mod motion_path;
+pub mod motion_presets;
+pub mod motion_variation;
+pub mod nine_slice;
+pub mod onion_skin;
+pub mod outline;
+pub mod palette;
+pub mod particles;
+pub mod picking;
+pub mod pii;
 pub mod polygon;
+pub mod preview;
+pub mod redaction;
+pub mod render_order;
+pub mod review;
+pub mod safe_areas;
 pub mod saved_state;
+pub mod scene;
+pub mod scene_generation;
+pub mod shader_reload;
+pub mod smart_zoom;
+pub mod spotlight;
+pub mod spring;
 pub mod st_image;
 pub mod st_video;
+pub mod stabilization;
+pub mod style_presets;
+pub mod tags;
+pub mod templating;
 pub mod text;
 pub mod text_due;
+pub mod text_editing;
+pub mod thumbnails;
 pub mod timelines;
 pub mod transcode;
 pub mod transform;
+pub mod typewriter;
 pub mod vertex;
+pub mod video_prefetch;
+pub mod warp;
+pub mod widgets;
+#[cfg(all(target_arch = "wasm32", feature = "web"))]
+pub mod web;
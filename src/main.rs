@@ -1,26 +1,436 @@
 #![allow(unused_variables)]
 
+mod animated_image;
 mod animations;
+mod autosave;
+mod beats;
+mod blend;
+mod brand_kit;
+mod callout;
 mod camera;
+mod captions;
 mod capture;
+mod choreography;
+mod clipboard;
+mod color_management;
+mod constraints;
+mod cursor;
 mod dot;
 mod editor;
+mod editor_handle;
+mod error;
+mod events;
 mod export;
 mod fonts;
+mod frame_interpolation;
+mod gizmos;
+mod grid;
+mod group;
+mod guides;
+mod image_assets;
+mod input_overlays;
+mod instancing;
+mod masking;
+mod matting;
+mod migrations;
 mod motion_arrow;
 mod motion_path;
+mod motion_presets;
+mod motion_variation;
+mod nine_slice;
+mod onion_skin;
+mod outline;
+mod palette;
+mod particles;
+mod picking;
+mod pii;
 mod polygon;
+mod preview;
+mod redaction;
+mod render_order;
+mod review;
+mod safe_areas;
+mod scene;
+mod scene_generation;
+mod shader_reload;
+mod smart_zoom;
+mod spotlight;
+mod spring;
 mod st_image;
 mod st_video;
+mod stabilization;
+mod style_presets;
+mod tags;
+mod templating;
 mod text;
 mod text_due;
+mod text_editing;
+mod thumbnails;
 mod timelines;
 mod transcode;
 mod transform;
+mod typewriter;
 mod vertex;
+mod video_prefetch;
+mod warp;
+mod widgets;
 mod gpu_resources;
 mod saved_state;
 
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use captions::SubtitleFormat;
+use editor::WindowSize;
+use export::{
+    chapters::ChapterFormat,
+    encode::{EncodeQuality, EncoderBackend, EncoderSettings},
+    exporter::{
+        ExportCancelToken, ExportFrameProgress, ExportJob, ExportOutcome, ExportRequest,
+        EXPORT_SHORT_SIDE,
+    },
+    gif::{export_gif, GifSettings},
+    watermark::{WatermarkPosition, WatermarkSettings},
+};
+use saved_state::{load_saved_state_file, SavedState};
+use timelines::TrackType;
+
+const USAGE: &str = "Usage: stunts-engine <command> <project file> [options]
+
+Commands:
+  info      Show the project's canvas, sequences and timeline
+  list      List the sequences in timeline order
+  render    Render the project's video track to an mp4 or gif
+
+Render options:
+  -o, --output <path>       Output file, .gif for an animated GIF (default: <project>.mp4)
+  --size <pixels>           Shorter side of the output, keeping the canvas's shape (default: 1080)
+  --quality <q>             draft, standard or high
+  --bitrate <kbps>          Overrides --quality
+  --encoder <e>             auto, nvenc, quicksync, amf or software
+  --keyframe-interval <n>   Frames between keyframes
+  --chapters <formats>      Comma separated: mp4, youtube
+  --subtitles <formats>     Write captions next to the video, comma separated: srt, vtt
+  --no-burn-captions        Leave captions out of the video's frames
+  --stamp-logo              Draw the brand kit's logo into the bottom right corner
+  --watermark <image>       Draw an image over every frame
+  --watermark-text <text>   Draw text over every frame, instead of an image
+  --watermark-position <p>  top-left, top-right, bottom-left, bottom-right or center
+  --gif-fps <fps>           GIF frame rate (default: 15)
+  --gif-no-loop             Play the GIF once";
+
 fn main() {
-    println!("Hello, Stunts!");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if let Err(e) = run(&args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) if command != "-h" && command != "--help" => (command, rest),
+        _ => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+    };
+
+    if !["info", "list", "render"].contains(&command.as_str()) {
+        return Err(format!("Unknown command {}\n\n{}", command, USAGE));
+    }
+
+    let (project_path, options) = parse_options(rest)?;
+    let project_path = project_path.ok_or_else(|| format!("Missing project file\n\n{}", USAGE))?;
+    let saved_state = load_saved_state_file(Path::new(&project_path))
+        .map_err(|e| format!("Couldn't load {}: {}", project_path, e))?;
+
+    match command.as_str() {
+        "info" => print_info(&saved_state),
+        "list" => print_sequences(&saved_state),
+        _ => render(&saved_state, &project_path, &options)?,
+    }
+
+    Ok(())
+}
+
+/// Splits arguments into the project file and --flag values. Flags without a value map to "".
+fn parse_options(args: &[String]) -> Result<(Option<String>, HashMap<String, String>), String> {
+    const OPTIONS: &[&str] = &[
+        "--output",
+        "--size",
+        "--quality",
+        "--bitrate",
+        "--encoder",
+        "--keyframe-interval",
+        "--chapters",
+        "--subtitles",
+        "--gif-fps",
+        "--watermark",
+        "--watermark-text",
+        "--watermark-position",
+    ];
+    const SWITCHES: &[&str] = &["--gif-no-loop", "--no-burn-captions", "--stamp-logo"];
+
+    let mut project_path = None;
+    let mut options = HashMap::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let name = match arg.as_str() {
+            "-o" => "--output",
+            name => name,
+        };
+        if !name.starts_with("--") {
+            if project_path.replace(arg.clone()).is_some() {
+                return Err(format!("Unexpected argument {}", arg));
+            }
+            continue;
+        }
+
+        let value = if SWITCHES.contains(&name) {
+            String::new()
+        } else if !OPTIONS.contains(&name) {
+            return Err(format!("Unknown option {}", name));
+        } else {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", name))?
+                .clone()
+        };
+        options.insert(name.to_string(), value);
+    }
+
+    Ok((project_path, options))
+}
+
+fn parse_number(options: &HashMap<String, String>, name: &str) -> Result<Option<u32>, String> {
+    options
+        .get(name)
+        .map(|value| {
+            value
+                .parse::<u32>()
+                .map_err(|_| format!("{} must be a whole number, not {}", name, value))
+        })
+        .transpose()
+}
+
+/// The video track's total length
+fn video_duration_ms(saved_state: &SavedState) -> i32 {
+    saved_state
+        .timeline_state
+        .track_end_ms(&TrackType::Video, &saved_state.sequences)
+}
+
+fn print_info(saved_state: &SavedState) {
+    println!("Project:    {}", saved_state.id);
+    println!(
+        "Canvas:     {}x{}",
+        saved_state.canvas_size.width, saved_state.canvas_size.height
+    );
+    println!("Format:     {:?}", saved_state.file_format);
+    println!("Sequences:  {}", saved_state.sequences.len());
+    println!(
+        "Duration:   {:.2}s",
+        video_duration_ms(saved_state) as f32 / 1000.0
+    );
+    println!("Markers:    {}", saved_state.timeline_state.markers.len());
+    println!("Comments:   {}", saved_state.comments.len());
+}
+
+fn print_sequences(saved_state: &SavedState) {
+    let mut timeline_sequences: Vec<_> = saved_state
+        .timeline_state
+        .timeline_sequences
+        .iter()
+        .collect();
+    timeline_sequences.sort_by_key(|ts| (ts.track_type == TrackType::Audio, ts.start_time_ms));
+
+    for timeline_sequence in timeline_sequences {
+        let Some(sequence) = saved_state
+            .sequences
+            .iter()
+            .find(|s| s.id == timeline_sequence.sequence_id)
+        else {
+            continue;
+        };
+
+        println!(
+            "{}  {:?} {:>8.2}s {:>8.2}s  {} ({} shapes, {} texts, {} images, {} videos)",
+            sequence.id,
+            timeline_sequence.track_type,
+            timeline_sequence.start_time_ms as f32 / 1000.0,
+            sequence.duration_ms as f32 / 1000.0,
+            sequence.name,
+            sequence.active_polygons.len(),
+            sequence.active_text_items.len(),
+            sequence.active_image_items.len(),
+            sequence.active_video_items.len(),
+        );
+    }
+}
+
+fn render(
+    saved_state: &SavedState,
+    project_path: &str,
+    options: &HashMap<String, String>,
+) -> Result<(), String> {
+    let output_path = options.get("--output").cloned().unwrap_or_else(|| {
+        Path::new(project_path)
+            .with_extension("mp4")
+            .to_string_lossy()
+            .to_string()
+    });
+    let is_gif = Path::new(&output_path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gif"));
+
+    let short_side = parse_number(options, "--size")?.unwrap_or(EXPORT_SHORT_SIDE);
+    let (video_width, video_height) = saved_state.canvas_size.export_size(short_side);
+
+    let mut encoder_settings = EncoderSettings::default();
+    if let Some(quality) = options.get("--quality") {
+        encoder_settings.quality = match quality.as_str() {
+            "draft" => EncodeQuality::Draft,
+            "standard" => EncodeQuality::Standard,
+            "high" => EncodeQuality::High,
+            _ => return Err(format!("Unknown quality {}", quality)),
+        };
+    }
+    if let Some(encoder) = options.get("--encoder") {
+        encoder_settings.backend = match encoder.as_str() {
+            "auto" => EncoderBackend::Auto,
+            "nvenc" => EncoderBackend::Nvenc,
+            "quicksync" => EncoderBackend::QuickSync,
+            "amf" => EncoderBackend::Amf,
+            "software" => EncoderBackend::Software,
+            _ => return Err(format!("Unknown encoder {}", encoder)),
+        };
+    }
+    encoder_settings.bitrate = parse_number(options, "--bitrate")?.map(|kbps| kbps * 1000);
+    if let Some(interval) = parse_number(options, "--keyframe-interval")? {
+        encoder_settings.keyframe_interval_frames = interval.max(1);
+    }
+
+    let mut chapter_formats = Vec::new();
+    for format in options
+        .get("--chapters")
+        .map(|formats| formats.split(',').collect())
+        .unwrap_or_else(Vec::new)
+    {
+        chapter_formats.push(match format.trim() {
+            "mp4" => ChapterFormat::Mp4Metadata,
+            "youtube" => ChapterFormat::YouTubeText,
+            _ => return Err(format!("Unknown chapter format {}", format)),
+        });
+    }
+
+    let mut subtitle_formats = Vec::new();
+    for format in options
+        .get("--subtitles")
+        .map(|formats| formats.split(',').collect())
+        .unwrap_or_else(Vec::new)
+    {
+        subtitle_formats.push(match format.trim() {
+            "srt" => SubtitleFormat::Srt,
+            "vtt" => SubtitleFormat::Vtt,
+            _ => return Err(format!("Unknown subtitle format {}", format)),
+        });
+    }
+
+    let mut watermark = match (options.get("--watermark"), options.get("--watermark-text")) {
+        (Some(image), _) => Some(WatermarkSettings::image(image.into())),
+        (None, Some(text)) => Some(WatermarkSettings::text(text.clone())),
+        (None, None) => None,
+    };
+    if let (Some(watermark), Some(position)) =
+        (watermark.as_mut(), options.get("--watermark-position"))
+    {
+        watermark.position = match position.as_str() {
+            "top-left" => WatermarkPosition::TopLeft,
+            "top-right" => WatermarkPosition::TopRight,
+            "bottom-left" => WatermarkPosition::BottomLeft,
+            "bottom-right" => WatermarkPosition::BottomRight,
+            "center" => WatermarkPosition::Center,
+            _ => return Err(format!("Unknown watermark position {}", position)),
+        };
+    }
+
+    let request = ExportRequest {
+        output_path: output_path.clone(),
+        encoder_settings,
+        window_size: WindowSize {
+            width: video_width,
+            height: video_height,
+        },
+        sequences: saved_state.sequences.clone(),
+        saved_timeline_state_config: saved_state.timeline_state.clone(),
+        video_width,
+        video_height,
+        total_duration_s: video_duration_ms(saved_state) as f64 / 1000.0,
+        project_id: saved_state.id.clone(),
+        canvas_size: saved_state.canvas_size,
+        chapter_formats,
+        burn_captions: !options.contains_key("--no-burn-captions"),
+        subtitle_formats,
+        brand_kit: saved_state.brand_kit.clone(),
+        stamp_logo: options.contains_key("--stamp-logo"),
+        watermark,
+    };
+
+    println!(
+        "Rendering {}x{} to {}...",
+        video_width, video_height, output_path
+    );
+
+    if is_gif {
+        let gif_settings = GifSettings {
+            frame_rate: parse_number(options, "--gif-fps")?
+                .unwrap_or(GifSettings::default().frame_rate),
+            looping: !options.contains_key("--gif-no-loop"),
+            ..GifSettings::default()
+        };
+        export_gif(
+            request,
+            gif_settings,
+            &ExportCancelToken::default(),
+            &mut print_progress,
+        )?;
+        println!("\nSaved {}", output_path);
+        return Ok(());
+    }
+
+    let outcome = Arc::new(Mutex::new(None));
+    let job_outcome = outcome.clone();
+    let job = ExportJob::spawn(
+        request,
+        Arc::new(print_progress),
+        Arc::new(move |result: ExportOutcome| {
+            *job_outcome.lock().expect("Couldn't lock export outcome") = Some(result);
+        }),
+    );
+    job.wait();
+
+    let outcome = outcome.lock().expect("Couldn't lock export outcome").take();
+    match outcome {
+        Some(ExportOutcome::Complete(path)) => {
+            println!("\nSaved {}", path);
+            Ok(())
+        }
+        Some(ExportOutcome::Cancelled) => Err("\nExport cancelled".to_string()),
+        Some(ExportOutcome::Error(e)) => Err(format!("\nExport failed: {}", e)),
+        None => Err("\nExport stopped without finishing".to_string()),
+    }
+}
+
+fn print_progress(progress: ExportFrameProgress) {
+    print!(
+        "\r{:>5.1}%  frame {}/{}  {}s left   ",
+        progress.percent(),
+        progress.frames_completed,
+        progress.total_frames,
+        progress.eta.as_secs()
+    );
+    std::io::Write::flush(&mut std::io::stdout()).ok();
 }
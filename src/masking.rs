@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::blend::{create_blend_pipelines, BlendMode};
+use crate::scene::Scene;
+use crate::vertex::Vertex;
+
+/// Masks are drawn into the stencil buffer, so the depth attachment needs a stencil aspect
+pub const MASK_DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
+
+/// How an object limits the object named by its `mask_target_id`.
+/// A mask isn't drawn itself, its target is only drawn where the mask covers.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum MaskMode {
+    /// The mask's shape clips the target, e.g. a video inside a rounded rectangle
+    #[default]
+    Clip,
+    /// Only the parts of the mask at least half opaque clip the target, e.g. video inside text
+    Alpha,
+}
+
+pub struct MaskPipelines {
+    pub clip: wgpu::RenderPipeline,
+    pub alpha: wgpu::RenderPipeline,
+    /// Blend mode pipelines which only draw where the stencil matches the reference
+    pub masked: HashMap<BlendMode, wgpu::RenderPipeline>,
+}
+
+/// Depth testing as usual, with the stencil left alone, for drawing unmasked objects
+pub fn depth_stencil_state() -> wgpu::DepthStencilState {
+    wgpu::DepthStencilState {
+        format: MASK_DEPTH_FORMAT,
+        depth_write_enabled: true,
+        depth_compare: wgpu::CompareFunction::Less,
+        stencil: wgpu::StencilState::default(),
+        bias: wgpu::DepthBiasState::default(),
+    }
+}
+
+pub fn create_mask_pipelines(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    vertex_shader: &wgpu::ShaderModule,
+    fragment_shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+) -> MaskPipelines {
+    let write_stencil = wgpu::StencilFaceState {
+        compare: wgpu::CompareFunction::Always,
+        fail_op: wgpu::StencilOperation::Keep,
+        depth_fail_op: wgpu::StencilOperation::Keep,
+        pass_op: wgpu::StencilOperation::Replace,
+    };
+
+    // masks leave color and depth untouched, so they don't hide anything beneath them
+    let mask_pipeline = |entry_point: &str| {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Stunts Engine Mask Pipeline"),
+            layout: Some(layout),
+            multiview: None,
+            vertex: wgpu::VertexState {
+                module: vertex_shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: fragment_shader,
+                entry_point,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::empty(),
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                conservative: false,
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: MASK_DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState {
+                    front: write_stencil,
+                    back: write_stencil,
+                    read_mask: 0xff,
+                    write_mask: 0xff,
+                },
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+        })
+    };
+
+    let test_stencil = wgpu::StencilFaceState {
+        compare: wgpu::CompareFunction::Equal,
+        fail_op: wgpu::StencilOperation::Keep,
+        depth_fail_op: wgpu::StencilOperation::Keep,
+        pass_op: wgpu::StencilOperation::Keep,
+    };
+
+    let masked = create_blend_pipelines(
+        device,
+        layout,
+        vertex_shader,
+        fragment_shader,
+        format,
+        Some(wgpu::DepthStencilState {
+            stencil: wgpu::StencilState {
+                front: test_stencil,
+                back: test_stencil,
+                read_mask: 0xff,
+                write_mask: 0,
+            },
+            ..depth_stencil_state()
+        }),
+    );
+
+    MaskPipelines {
+        clip: mask_pipeline("fs_main"),
+        alpha: mask_pipeline("fs_mask"),
+        masked,
+    }
+}
+
+/// Buffers and bind groups needed to draw any kind of object
+pub struct ObjectBuffers<'a> {
+    pub bind_group: &'a wgpu::BindGroup,
+    pub group_bind_group: &'a wgpu::BindGroup,
+    pub vertex_buffer: &'a wgpu::Buffer,
+    pub index_buffer: &'a wgpu::Buffer,
+    pub index_count: u32,
+}
+
+impl<'a> ObjectBuffers<'a> {
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_bind_group(1, self.bind_group, &[]);
+        render_pass.set_bind_group(3, self.group_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+    }
+}
+
+/// Visible objects acting as masks for the given target
+pub fn masks_for<'a>(scene: &'a Scene, target_id: &str) -> Vec<(ObjectBuffers<'a>, MaskMode)> {
+    let targets = |mask_target_id: &Option<String>| mask_target_id.as_deref() == Some(target_id);
+    let mut masks = Vec::new();
+
+    for polygon in scene.polygons.iter() {
+        if !polygon.hidden && !polygon.user_hidden && targets(&polygon.mask_target_id) {
+            masks.push((
+                ObjectBuffers {
+                    bind_group: &polygon.bind_group,
+                    group_bind_group: &polygon.group_bind_group,
+                    vertex_buffer: &polygon.vertex_buffer,
+                    index_buffer: &polygon.index_buffer,
+                    index_count: polygon.indices.len() as u32,
+                },
+                polygon.mask_mode,
+            ));
+        }
+    }
+
+    for text_item in scene.text_items.iter() {
+        if !text_item.hidden && !text_item.user_hidden && targets(&text_item.mask_target_id) {
+            masks.push((
+                ObjectBuffers {
+                    bind_group: &text_item.bind_group,
+                    group_bind_group: &text_item.group_bind_group,
+                    vertex_buffer: &text_item.vertex_buffer,
+                    index_buffer: &text_item.index_buffer,
+                    index_count: text_item.indices.len() as u32,
+                },
+                text_item.mask_mode,
+            ));
+        }
+    }
+
+    for st_image in scene.image_items.iter() {
+        if !st_image.hidden && !st_image.user_hidden && targets(&st_image.mask_target_id) {
+            masks.push((
+                ObjectBuffers {
+                    bind_group: &st_image.bind_group,
+                    group_bind_group: &st_image.group_bind_group,
+                    vertex_buffer: &st_image.vertex_buffer,
+                    index_buffer: &st_image.index_buffer,
+                    index_count: st_image.indices.len() as u32,
+                },
+                st_image.mask_mode,
+            ));
+        }
+    }
+
+    for st_video in scene.video_items.iter() {
+        if !st_video.hidden && !st_video.user_hidden && targets(&st_video.mask_target_id) {
+            masks.push((
+                ObjectBuffers {
+                    bind_group: &st_video.bind_group,
+                    group_bind_group: &st_video.group_bind_group,
+                    vertex_buffer: &st_video.vertex_buffer,
+                    index_buffer: &st_video.index_buffer,
+                    index_count: st_video.indices.len() as u32,
+                },
+                st_video.mask_mode,
+            ));
+        }
+    }
+
+    masks
+}
+
+/// Draws the target's masks into the stencil buffer and switches to the pipeline which only
+/// draws inside them. Does nothing when the target has no masks. With several masks the
+/// target shows inside any of them.
+///
+/// Each masked object uses a new stencil reference, so the stencil only needs clearing once
+/// per frame. Past 255 masked objects references are reused.
+pub fn begin_masked_draw<'a>(
+    render_pass: &mut wgpu::RenderPass<'a>,
+    scene: &'a Scene,
+    target_id: &str,
+    mask_pipelines: &'a MaskPipelines,
+    blend_mode: BlendMode,
+    stencil_reference: &mut u32,
+) {
+    let masks = masks_for(scene, target_id);
+    if masks.is_empty() {
+        return;
+    }
+
+    *stencil_reference = *stencil_reference % 255 + 1;
+    render_pass.set_stencil_reference(*stencil_reference);
+
+    for (mask, mode) in masks.iter() {
+        render_pass.set_pipeline(match mode {
+            MaskMode::Clip => &mask_pipelines.clip,
+            MaskMode::Alpha => &mask_pipelines.alpha,
+        });
+        mask.draw(render_pass);
+    }
+
+    if let Some(pipeline) = mask_pipelines.masked.get(&blend_mode) {
+        render_pass.set_pipeline(pipeline);
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+/// Separates a person from their background. Implement this to plug in a segmentation
+/// model, register it with `Editor::register_segmentation_model` and refer to it by name.
+pub trait SegmentationModel: Send {
+    /// Foreground alpha per pixel, 0 to 255, for a BGRA frame
+    fn segment(&mut self, frame: &[u8], width: u32, height: u32) -> Result<Vec<u8>, String>;
+}
+
+/// Models keep state between frames, so each video gets its own instance
+pub type SegmentationModelFactory = Arc<dyn Fn() -> Box<dyn SegmentationModel> + Send + Sync>;
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub enum MatteSource {
+    /// Pixels that differ from a still plate of the empty background are kept,
+    /// works without a model when the camera doesn't move
+    Threshold,
+    /// A registered segmentation model, by name
+    Model(String),
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct SavedMatteConfig {
+    pub source: MatteSource,
+    pub threshold: i32, // color distance from the background plate, 0 to 255
+    pub softness: i32,  // range above the threshold over which pixels fade in
+    pub feather: i32,   // pixels the matte edge is blurred by
+}
+
+impl Default for SavedMatteConfig {
+    fn default() -> Self {
+        SavedMatteConfig {
+            source: MatteSource::Threshold,
+            threshold: 40,
+            softness: 30,
+            feather: 2,
+        }
+    }
+}
+
+/// Cuts out the background of a video's frames by writing an alpha matte into them
+pub struct Matte {
+    pub config: SavedMatteConfig,
+    model: Option<Box<dyn SegmentationModel>>,
+    background: Option<Vec<u8>>,
+    last_frame: Option<Vec<u8>>,
+}
+
+impl Matte {
+    /// Without a model the threshold matte is used
+    pub fn new(config: SavedMatteConfig, model: Option<Box<dyn SegmentationModel>>) -> Self {
+        Matte {
+            config,
+            model,
+            background: None,
+            last_frame: None,
+        }
+    }
+
+    /// Creates the model from the factory registered under the config's model name,
+    /// falling back to the threshold matte if there isn't one
+    pub fn from_config(
+        config: SavedMatteConfig,
+        models: &HashMap<String, SegmentationModelFactory>,
+    ) -> Self {
+        let model = match &config.source {
+            MatteSource::Model(name) => match models.get(name) {
+                Some(factory) => Some(factory()),
+                None => {
+                    println!(
+                        "No segmentation model registered named {}, using threshold matte",
+                        name
+                    );
+                    None
+                }
+            },
+            MatteSource::Threshold => None,
+        };
+
+        Matte::new(config, model)
+    }
+
+    /// Uses the last frame seen as the empty background for the threshold matte.
+    /// Without this the first frame is used, so step out of shot when recording starts.
+    pub fn capture_background(&mut self) -> Result<(), String> {
+        let frame = self
+            .last_frame
+            .clone()
+            .ok_or("No frame has been drawn yet")?;
+        self.background = Some(frame);
+
+        Ok(())
+    }
+
+    /// Writes the matte into the alpha channel of a decoded BGRA frame
+    pub fn apply(&mut self, frame: &mut [u8], width: u32, height: u32) {
+        // decoded frames with row padding aren't supported
+        let pixel_count = width as usize * height as usize;
+        if frame.len() < pixel_count * 4 {
+            return;
+        }
+
+        self.last_frame = Some(frame.to_vec());
+
+        let mut alpha = None;
+        if let Some(model) = self.model.as_mut() {
+            match model.segment(frame, width, height) {
+                Ok(model_alpha) if model_alpha.len() >= pixel_count => alpha = Some(model_alpha),
+                Ok(_) => println!("Segmentation model returned a matte of the wrong size"),
+                Err(e) => println!("Segmentation model failed, using threshold matte: {}", e),
+            }
+        }
+
+        let alpha = match alpha {
+            Some(alpha) => alpha,
+            None => {
+                if self.background.is_none() {
+                    self.background = Some(frame.to_vec());
+                }
+                let background = self.background.as_ref().expect("Couldn't get background");
+                threshold_matte(frame, background, pixel_count, &self.config)
+            }
+        };
+
+        let alpha = feather_matte(
+            &alpha,
+            width as usize,
+            height as usize,
+            self.config.feather.max(0) as usize,
+        );
+
+        for (i, a) in alpha.iter().take(pixel_count).enumerate() {
+            frame[i * 4 + 3] = *a;
+        }
+    }
+}
+
+/// Alpha from how far each pixel's color is from the background plate
+pub fn threshold_matte(
+    frame: &[u8],
+    background: &[u8],
+    pixel_count: usize,
+    config: &SavedMatteConfig,
+) -> Vec<u8> {
+    let threshold = config.threshold.max(0) as f32;
+    let softness = config.softness.max(1) as f32;
+
+    (0..pixel_count)
+        .map(|i| {
+            let p = &frame[i * 4..i * 4 + 3];
+            let Some(b) = background.get(i * 4..i * 4 + 3) else {
+                return 255;
+            };
+
+            let distance = ((p[0] as f32 - b[0] as f32).powi(2)
+                + (p[1] as f32 - b[1] as f32).powi(2)
+                + (p[2] as f32 - b[2] as f32).powi(2))
+            .sqrt();
+
+            (((distance - threshold) / softness).clamp(0.0, 1.0) * 255.0) as u8
+        })
+        .collect()
+}
+
+/// Box blurs the matte horizontally then vertically, softening its edge
+pub fn feather_matte(alpha: &[u8], width: usize, height: usize, radius: usize) -> Vec<u8> {
+    if radius == 0 || width == 0 || height == 0 {
+        return alpha.to_vec();
+    }
+
+    let blur =
+        |source: &[u8], length: usize, lines: usize, index: &dyn Fn(usize, usize) -> usize| {
+            let mut result = source.to_vec();
+            for line in 0..lines {
+                for position in 0..length {
+                    let start = position.saturating_sub(radius);
+                    let end = (position + radius + 1).min(length);
+                    let sum: u32 = (start..end).map(|p| source[index(line, p)] as u32).sum();
+                    result[index(line, position)] = (sum / (end - start) as u32) as u8;
+                }
+            }
+            result
+        };
+
+    let horizontal = blur(alpha, width, height, &|y, x| y * width + x);
+    blur(&horizontal, height, width, &|x, y| y * width + x)
+}
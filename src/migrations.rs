@@ -0,0 +1,222 @@
+use serde_json::Value;
+
+/// Version written into new project files. Bump it when adding a migration.
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// Upgrades a project file by one version, working on the raw JSON so fields
+/// that have since been renamed or removed can still be read
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+/// MIGRATIONS[n] upgrades version n to n + 1
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1];
+
+// a version bump without its migration fails to build
+const _: () = assert!(MIGRATIONS.len() == CURRENT_SCHEMA_VERSION as usize);
+
+/// Files from before versioning had no canvas size and were always 1000x600.
+/// Writing it out keeps them that way if the default canvas changes.
+fn migrate_v0_to_v1(project: &mut Value) -> Result<(), String> {
+    let project = project
+        .as_object_mut()
+        .ok_or("Project file isn't a JSON object")?;
+
+    project
+        .entry("canvas_size")
+        .or_insert_with(|| serde_json::json!({ "width": 1000, "height": 600 }));
+
+    Ok(())
+}
+
+/// The version a project file was written with, 0 for files from before versioning
+pub fn schema_version(project: &Value) -> u32 {
+    project
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32
+}
+
+/// Runs every migration the file needs in order, returning the version it started at
+pub fn migrate(project: &mut Value) -> Result<u32, String> {
+    let from_version = schema_version(project);
+    if from_version > CURRENT_SCHEMA_VERSION {
+        return Err(format!(
+            "Project was saved by a newer version of Stunts (format {}, this version reads up to {})",
+            from_version, CURRENT_SCHEMA_VERSION
+        ));
+    }
+
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from_version as usize) {
+        migration(project)
+            .map_err(|e| format!("Couldn't upgrade project from format {}: {}", version, e))?;
+    }
+
+    if let Some(project) = project.as_object_mut() {
+        project.insert(
+            "schema_version".to_string(),
+            Value::from(CURRENT_SCHEMA_VERSION),
+        );
+    }
+
+    Ok(from_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::saved_state::{load_saved_state_file, CanvasSize, SavedState};
+    use serde_json::json;
+
+    fn v0_project() -> Value {
+        json!({
+            "id": "project",
+            "sequences": [],
+            "timeline_state": { "timeline_sequences": [] }
+        })
+    }
+
+    #[test]
+    fn v0_gains_the_old_canvas_size() {
+        let mut project = v0_project();
+
+        assert_eq!(migrate(&mut project), Ok(0));
+        assert_eq!(
+            project["canvas_size"],
+            json!({ "width": 1000, "height": 600 })
+        );
+        assert_eq!(schema_version(&project), CURRENT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn v0_keeps_a_canvas_size_it_already_has() {
+        let mut project = v0_project();
+        project["canvas_size"] = json!({ "width": 1080, "height": 1920 });
+
+        migrate(&mut project).expect("Couldn't migrate project");
+        assert_eq!(
+            project["canvas_size"],
+            json!({ "width": 1080, "height": 1920 })
+        );
+    }
+
+    #[test]
+    fn current_version_passes_through_unchanged() {
+        let mut project = v0_project();
+        project["schema_version"] = json!(CURRENT_SCHEMA_VERSION);
+        let original = project.clone();
+
+        assert_eq!(migrate(&mut project), Ok(CURRENT_SCHEMA_VERSION));
+        assert_eq!(project, original);
+    }
+
+    #[test]
+    fn newer_version_is_an_error() {
+        let mut project = v0_project();
+        project["schema_version"] = json!(CURRENT_SCHEMA_VERSION + 1);
+        let original = project.clone();
+
+        assert!(migrate(&mut project).is_err());
+        assert_eq!(project, original);
+    }
+
+    #[test]
+    fn non_object_is_an_error() {
+        assert!(migrate(&mut json!([])).is_err());
+    }
+
+    #[test]
+    fn decodes_and_migrates_json() {
+        let content = serde_json::to_vec(&v0_project()).expect("Couldn't write JSON");
+        let (saved_state, from_version) =
+            SavedState::decode_with_migrations(&content).expect("Couldn't decode project");
+
+        assert_eq!(from_version, 0);
+        assert_eq!(saved_state.id, "project");
+        assert_eq!(saved_state.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(
+            saved_state.canvas_size,
+            CanvasSize {
+                width: 1000,
+                height: 600
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_and_migrates_message_pack() {
+        let content = rmp_serde::to_vec_named(&v0_project()).expect("Couldn't write MessagePack");
+        let (saved_state, from_version) =
+            SavedState::decode_with_migrations(&content).expect("Couldn't decode project");
+
+        assert_eq!(from_version, 0);
+        assert_eq!(saved_state.id, "project");
+        assert_eq!(saved_state.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(
+            saved_state.canvas_size,
+            CanvasSize {
+                width: 1000,
+                height: 600
+            }
+        );
+    }
+
+    fn v0_project_file() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("stunts-migrations-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("Couldn't create test folder");
+        let path = dir.join("project_data.json");
+        std::fs::write(&path, serde_json::to_vec(&v0_project()).unwrap())
+            .expect("Couldn't write test project");
+        path
+    }
+
+    #[test]
+    fn loading_upgrades_the_file_once_and_keeps_a_backup() {
+        let path = v0_project_file();
+        let original = std::fs::read(&path).unwrap();
+        let backup_path = path.with_file_name("project_data.v0.json");
+
+        SavedState::load_with_migrations(&path).expect("Couldn't load project");
+        assert_eq!(std::fs::read(&backup_path).unwrap(), original);
+
+        let upgraded: Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(schema_version(&upgraded), CURRENT_SCHEMA_VERSION);
+
+        // the upgraded file loads as it is, leaving the backup alone
+        std::fs::write(&backup_path, b"kept").unwrap();
+        SavedState::load_with_migrations(&path).expect("Couldn't load project");
+        assert_eq!(std::fs::read(&backup_path).unwrap(), b"kept");
+
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn loading_from_any_path_writes_nothing() {
+        let path = v0_project_file();
+        let original = std::fs::read(&path).unwrap();
+
+        let saved_state = load_saved_state_file(&path).expect("Couldn't load project");
+        assert_eq!(saved_state.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(std::fs::read(&path).unwrap(), original);
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn backups_are_named_for_their_format() {
+        let path = v0_project_file();
+        let packed = rmp_serde::to_vec_named(&v0_project()).unwrap();
+        std::fs::write(&path, &packed).unwrap();
+
+        SavedState::load_with_migrations(&path).expect("Couldn't load project");
+        assert_eq!(
+            std::fs::read(path.with_file_name("project_data.v0.msgpack")).unwrap(),
+            packed
+        );
+        assert!(!path.with_file_name("project_data.v0.json").exists());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -3,13 +3,18 @@ use cgmath::{Matrix4, Vector2};
 use image::GenericImageView;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 use wgpu::util::DeviceExt;
 use wgpu::{Device, Queue};
 
 use crate::animations::{EasingType, KeyType, KeyframeValue, Sequence, UIKeyframe};
 use crate::camera::Camera3D as Camera;
-use crate::editor::{get_full_color, interpolate_position, rgb_to_wgpu, Point};
+use crate::editor::{
+    get_full_color, interpolate_position, rgb_to_wgpu, ControlPoint, PathType, Point,
+    CANVAS_HORIZ_OFFSET, CANVAS_VERT_OFFSET,
+};
+use crate::gizmos::{Gizmo, GizmoShape};
 use crate::polygon::{Polygon, Stroke};
 use crate::transform::matrix4_to_raw_array;
 use crate::{
@@ -34,6 +39,32 @@ pub struct MotionPath {
     pub source_polygon_id: Uuid,
 }
 
+/// Which of a curved keyframe pair's control points a tangent handle moves. Both are kept
+/// on the earlier keyframe's path_type.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TangentControl {
+    Out, // control_point1, leaving the earlier keyframe
+    In,  // control_point2, arriving at the later keyframe
+}
+
+impl TangentControl {
+    fn handle_name(&self) -> &'static str {
+        match self {
+            TangentControl::Out => "motion_path_tangent_out",
+            TangentControl::In => "motion_path_tangent_in",
+        }
+    }
+
+    /// The control a path polygon moves, if it's a tangent handle
+    pub fn from_handle_name(name: &str) -> Option<Self> {
+        match name {
+            "motion_path_tangent_out" => Some(TangentControl::Out),
+            "motion_path_tangent_in" => Some(TangentControl::In),
+            _ => None,
+        }
+    }
+}
+
 impl MotionPath {
     pub fn new(
         device: &Device,
@@ -57,36 +88,13 @@ impl MotionPath {
 
         let mut static_polygons = Vec::new();
 
-        // Create path segments between consecutive keyframes
-        let mut pairs_done = 0;
-        for window in keyframes.windows(2) {
-            let start_kf = &window[0];
-            let end_kf = &window[1];
-
-            let start_kf_id =
-                Uuid::from_str(&start_kf.id).expect("Couldn't convert string to uuid");
-            let end_kf_id = Uuid::from_str(&end_kf.id).expect("Couldn't convert string to uuid");
-
-            if let (KeyframeValue::Position(start_pos), KeyframeValue::Position(end_pos)) =
-                (&start_kf.value, &end_kf.value)
-            {
-                let start_point = Point {
-                    x: start_pos[0] as f32,
-                    y: start_pos[1] as f32,
-                };
-                let end_point = Point {
-                    x: end_pos[0] as f32,
-                    y: end_pos[1] as f32,
-                };
-
-                // Create intermediate points for curved paths if using non-linear easing
-                let num_segments = match start_kf.easing {
-                    EasingType::Linear => 1,
-                    _ => 9, // More segments for smooth curves
-                };
-
-                if pairs_done == 0 {
-                    // handle for first keyframe in path
+        for piece in path_pieces(&keyframes) {
+            let mut polygon = match piece {
+                PathPiece::Handle {
+                    keyframe_id,
+                    point,
+                    rotation,
+                } => {
                     let mut handle = create_path_handle(
                         &window_size,
                         &device,
@@ -94,144 +102,113 @@ impl MotionPath {
                         &model_bind_group_layout,
                         &group_bind_group_layout,
                         &camera,
-                        start_point,
+                        point,
                         12.0, // width and height
                         sequence.id.clone(),
                         path_fill,
-                        0.0,
+                        rotation,
                     );
 
                     handle.source_polygon_id = Some(polygon_id);
-                    handle.source_keyframe_id = Some(start_kf_id);
+                    handle.source_keyframe_id = Some(keyframe_id);
                     handle.source_path_id = Some(new_id);
-
-                    handle.update_group_position(initial_position);
-
-                    static_polygons.push(handle);
+                    handle
                 }
-
-                // handles for remaining keyframes
-
-                let mut handle = match &end_kf.key_type {
-                    KeyType::Frame => create_path_handle(
+                PathPiece::Segment {
+                    start,
+                    end,
+                    rotation,
+                    length,
+                } => {
+                    let mut segment = create_path_segment(
                         &window_size,
                         &device,
                         &queue,
                         &model_bind_group_layout,
                         &group_bind_group_layout,
                         &camera,
-                        end_point,
-                        12.0, // width and height
+                        start,
+                        end,
+                        2.0, // thickness of the path
                         sequence.id.clone(),
                         path_fill,
-                        0.0,
-                    ),
-                    KeyType::Range(range_data) => create_path_handle(
+                        rotation,
+                        length,
+                    );
+
+                    segment.source_path_id = Some(new_id);
+                    segment.source_polygon_id = Some(polygon_id);
+                    segment
+                }
+                PathPiece::TangentLine {
+                    start,
+                    end,
+                    rotation,
+                    length,
+                } => {
+                    let mut line = create_path_segment(
                         &window_size,
                         &device,
                         &queue,
                         &model_bind_group_layout,
                         &group_bind_group_layout,
                         &camera,
-                        end_point,
-                        12.0, // width and height
+                        start,
+                        end,
+                        1.0, // thinner than the path itself
                         sequence.id.clone(),
                         path_fill,
-                        45.0,
-                    ),
-                };
-
-                handle.source_polygon_id = Some(polygon_id);
-                handle.source_keyframe_id = Some(end_kf_id);
-                handle.source_path_id = Some(new_id);
-
-                handle.update_group_position(initial_position);
-
-                static_polygons.push(handle);
-
-                let segment_duration =
-                    (end_kf.time.as_secs_f32() - start_kf.time.as_secs_f32()) / num_segments as f32;
-
-                let mut odd = false;
-                for i in 0..num_segments {
-                    let t1 = start_kf.time.as_secs_f32() + segment_duration * i as f32;
-                    let t2 = start_kf.time.as_secs_f32() + segment_duration * (i + 1) as f32;
-
-                    // println!("pos1");
-                    let pos1 = interpolate_position(start_kf, end_kf, t1);
-                    // println!("pos2");
-                    let pos2 = interpolate_position(start_kf, end_kf, t2);
-
-                    let path_start = Point {
-                        x: pos1[0] as f32,
-                        y: pos1[1] as f32,
-                    };
-
-                    let path_end = Point {
-                        x: pos2[0] as f32,
-                        y: pos2[1] as f32,
-                    };
-
-                    // Calculate rotation angle from start to end point
-                    let dx = path_end.x - path_start.x;
-                    let dy = path_end.y - path_start.y;
-                    let rotation = dy.atan2(dx);
-
-                    // Calculate length of the segment
-                    let length = (dx * dx + dy * dy).sqrt();
-
-                    // println!("length {:?}", length);
+                        rotation,
+                        length,
+                    );
 
-                    let mut segment = create_path_segment(
+                    line.name = String::from("motion_path_tangent_line");
+                    line
+                }
+                PathPiece::TangentHandle {
+                    keyframe_id,
+                    control,
+                    point,
+                } => {
+                    let mut handle = create_path_handle(
                         &window_size,
                         &device,
                         &queue,
                         &model_bind_group_layout,
                         &group_bind_group_layout,
                         &camera,
-                        path_start,
-                        path_end,
-                        2.0, // thickness of the path
+                        point,
+                        8.0, // width and height
                         sequence.id.clone(),
                         path_fill,
-                        rotation,
-                        length,
+                        0.0,
                     );
 
-                    segment.source_path_id = Some(new_id);
-                    segment.source_polygon_id = Some(polygon_id);
-
-                    segment.update_group_position(initial_position);
-
-                    static_polygons.push(segment);
-
-                    // arrow for indicating direction of motion
-                    if odd {
-                        let arrow_orientation_offset = -std::f32::consts::FRAC_PI_2; // for upward-facing arrow
-                        let mut arrow = create_path_arrow(
-                            &window_size,
-                            &device,
-                            &queue,
-                            &model_bind_group_layout,
-                            &group_bind_group_layout,
-                            &camera,
-                            path_end,
-                            15.0, // width and height
-                            sequence.id.clone(),
-                            path_fill,
-                            rotation + arrow_orientation_offset,
-                        );
-
-                        arrow.update_group_position(initial_position);
-
-                        static_polygons.push(arrow);
-                    }
-
-                    odd = !odd;
+                    handle.name = String::from(control.handle_name());
+                    handle.source_polygon_id = Some(polygon_id);
+                    handle.source_keyframe_id = Some(keyframe_id);
+                    handle.source_path_id = Some(new_id);
+                    handle
                 }
-
-                pairs_done = pairs_done + 1;
-            }
+                // arrow for indicating direction of motion
+                PathPiece::Arrow { point, rotation } => create_path_arrow(
+                    &window_size,
+                    &device,
+                    &queue,
+                    &model_bind_group_layout,
+                    &group_bind_group_layout,
+                    &camera,
+                    point,
+                    15.0, // width and height
+                    sequence.id.clone(),
+                    path_fill,
+                    rotation,
+                ),
+            };
+
+            polygon.update_group_position(initial_position);
+
+            static_polygons.push(polygon);
         }
 
         let empty_buffer = Matrix4::<f32>::identity();
@@ -290,6 +267,106 @@ impl MotionPath {
         });
     }
 
+    /// The keyframe and tangent handles, drawn by the gizmo layer where the hidden handle
+    /// polygons are
+    pub fn handle_gizmos(&self) -> impl Iterator<Item = Gizmo> + '_ {
+        self.static_polygons.iter().filter_map(|p| {
+            let shape = if p.name == "motion_path_handle" {
+                GizmoShape::Square
+            } else if TangentControl::from_handle_name(&p.name).is_some() {
+                GizmoShape::Circle
+            } else {
+                return None;
+            };
+
+            Some(Gizmo {
+                shape,
+                position: Point {
+                    x: self.transform.position.x + p.transform.position.x,
+                    y: self.transform.position.y + p.transform.position.y,
+                },
+                size: p.dimensions.0,
+                rotation: p.transform.rotation,
+                fill: p.fill,
+                stroke: p.stroke,
+            })
+        })
+    }
+
+    /// Moves the path's handles, segments and arrows to match edited keyframes, writing into
+    /// the existing buffers. Returns false without finishing when the keyframes need a different
+    /// number or kind of pieces, in which case the path should be recreated.
+    pub fn update_keyframes(
+        &mut self,
+        queue: &wgpu::Queue,
+        window_size: &WindowSize,
+        keyframes: &[UIKeyframe],
+        initial_position: [i32; 2],
+    ) -> bool {
+        let pieces = path_pieces(keyframes);
+        if pieces.len() != self.static_polygons.len()
+            || pieces
+                .iter()
+                .zip(self.static_polygons.iter())
+                .any(|(piece, polygon)| polygon.name != piece.name())
+        {
+            return false;
+        }
+
+        for (piece, polygon) in pieces.into_iter().zip(self.static_polygons.iter_mut()) {
+            let (point, rotation) = match piece {
+                PathPiece::Handle {
+                    keyframe_id,
+                    point,
+                    rotation,
+                } => {
+                    polygon.source_keyframe_id = Some(keyframe_id);
+                    (point, rotation)
+                }
+                PathPiece::Segment {
+                    start,
+                    end,
+                    rotation,
+                    length,
+                }
+                | PathPiece::TangentLine {
+                    start,
+                    end,
+                    rotation,
+                    length,
+                } => {
+                    if !polygon.update_dimensions_in_place(queue, (length, polygon.dimensions.1)) {
+                        return false;
+                    }
+                    (segment_midpoint(start, end), rotation)
+                }
+                PathPiece::TangentHandle {
+                    keyframe_id, point, ..
+                } => {
+                    polygon.source_keyframe_id = Some(keyframe_id);
+                    (point, 0.0)
+                }
+                PathPiece::Arrow { point, rotation } => (point, rotation),
+            };
+
+            polygon.transform.update_position(
+                [CANVAS_HORIZ_OFFSET + point.x, CANVAS_VERT_OFFSET + point.y],
+                window_size,
+            );
+            polygon.transform.update_rotation(rotation);
+            polygon.transform.update_uniform_buffer(queue, window_size);
+            polygon.update_group_position(initial_position);
+        }
+
+        self.transform.update_position(
+            [initial_position[0] as f32, initial_position[1] as f32],
+            window_size,
+        );
+        self.transform.update_uniform_buffer(queue, window_size);
+
+        true
+    }
+
     // pub fn contains_point(&self, point: &Point, camera: &Camera) -> bool {
     //     let untranslated = Point {
     //         x: point.x - (self.transform.position.x),
@@ -318,6 +395,268 @@ impl MotionPath {
     // }
 }
 
+/// One of the polygons a path is drawn with, in the order they're created
+enum PathPiece {
+    Handle {
+        keyframe_id: Uuid,
+        point: Point,
+        rotation: f32,
+    },
+    Segment {
+        start: Point,
+        end: Point,
+        rotation: f32,
+        length: f32,
+    },
+    Arrow {
+        point: Point,
+        rotation: f32,
+    },
+    // from a curved keyframe pair's end to its control point
+    TangentLine {
+        start: Point,
+        end: Point,
+        rotation: f32,
+        length: f32,
+    },
+    TangentHandle {
+        keyframe_id: Uuid, // the earlier keyframe, which holds the curve
+        control: TangentControl,
+        point: Point,
+    },
+}
+
+impl PathPiece {
+    fn name(&self) -> &'static str {
+        match self {
+            PathPiece::Handle { .. } => "motion_path_handle",
+            PathPiece::Segment { .. } => "motion_path_segment",
+            PathPiece::Arrow { .. } => "motion_path_arrow",
+            PathPiece::TangentLine { .. } => "motion_path_tangent_line",
+            PathPiece::TangentHandle { control, .. } => control.handle_name(),
+        }
+    }
+}
+
+/// Lays out a path's handles, segments and arrows from its sorted position keyframes
+fn path_pieces(keyframes: &[UIKeyframe]) -> Vec<PathPiece> {
+    let mut pieces = Vec::new();
+
+    // Create path segments between consecutive keyframes
+    let mut pairs_done = 0;
+    for window in keyframes.windows(2) {
+        let start_kf = &window[0];
+        let end_kf = &window[1];
+
+        let start_kf_id = Uuid::from_str(&start_kf.id).expect("Couldn't convert string to uuid");
+        let end_kf_id = Uuid::from_str(&end_kf.id).expect("Couldn't convert string to uuid");
+
+        if let (KeyframeValue::Position(start_pos), KeyframeValue::Position(end_pos)) =
+            (&start_kf.value, &end_kf.value)
+        {
+            let start_point = Point {
+                x: start_pos[0] as f32,
+                y: start_pos[1] as f32,
+            };
+            let end_point = Point {
+                x: end_pos[0] as f32,
+                y: end_pos[1] as f32,
+            };
+
+            // Create intermediate points for curved paths or non-linear easing
+            let num_segments = match (&start_kf.easing, &start_kf.path_type) {
+                (EasingType::Linear, PathType::Linear) => 1,
+                _ => 9, // More segments for smooth curves
+            };
+
+            if pairs_done == 0 {
+                // handle for first keyframe in path
+                pieces.push(PathPiece::Handle {
+                    keyframe_id: start_kf_id,
+                    point: start_point,
+                    rotation: 0.0,
+                });
+            }
+
+            // handles for remaining keyframes
+            pieces.push(PathPiece::Handle {
+                keyframe_id: end_kf_id,
+                point: end_point,
+                rotation: match &end_kf.key_type {
+                    KeyType::Frame => 0.0,
+                    KeyType::Range(_) => 45.0,
+                },
+            });
+
+            let segment_duration =
+                (end_kf.time.as_secs_f32() - start_kf.time.as_secs_f32()) / num_segments as f32;
+
+            let mut odd = false;
+            for i in 0..num_segments {
+                let t1 = start_kf.time.as_secs_f32() + segment_duration * i as f32;
+                let t2 = start_kf.time.as_secs_f32() + segment_duration * (i + 1) as f32;
+
+                let pos1 = interpolate_position(start_kf, end_kf, t1);
+                let pos2 = interpolate_position(start_kf, end_kf, t2);
+
+                let path_start = Point {
+                    x: pos1[0] as f32,
+                    y: pos1[1] as f32,
+                };
+
+                let path_end = Point {
+                    x: pos2[0] as f32,
+                    y: pos2[1] as f32,
+                };
+
+                // Calculate rotation angle from start to end point
+                let dx = path_end.x - path_start.x;
+                let dy = path_end.y - path_start.y;
+                let rotation = dy.atan2(dx);
+
+                // Calculate length of the segment
+                let length = (dx * dx + dy * dy).sqrt();
+
+                pieces.push(PathPiece::Segment {
+                    start: path_start,
+                    end: path_end,
+                    rotation,
+                    length,
+                });
+
+                if odd {
+                    let arrow_orientation_offset = -std::f32::consts::FRAC_PI_2; // for upward-facing arrow
+                    pieces.push(PathPiece::Arrow {
+                        point: path_end,
+                        rotation: rotation + arrow_orientation_offset,
+                    });
+                }
+
+                odd = !odd;
+            }
+
+            // tangent handles for dragging the curve's control points
+            if let PathType::Bezier(curve) = &start_kf.path_type {
+                let (cp1, cp2) = curve.control_points(*start_pos, *end_pos);
+                let tangents = [
+                    (TangentControl::Out, start_point, cp1),
+                    (TangentControl::In, end_point, cp2),
+                ];
+
+                for (control, anchor, (x, y)) in tangents {
+                    let point = Point { x, y };
+                    let (dx, dy) = (point.x - anchor.x, point.y - anchor.y);
+
+                    pieces.push(PathPiece::TangentLine {
+                        start: anchor,
+                        end: point,
+                        rotation: dy.atan2(dx),
+                        length: (dx * dx + dy * dy).sqrt(),
+                    });
+                    pieces.push(PathPiece::TangentHandle {
+                        keyframe_id: start_kf_id,
+                        control,
+                        point,
+                    });
+                }
+            }
+
+            pairs_done = pairs_done + 1;
+        }
+    }
+
+    pieces
+}
+
+/// Samples per keyframe pair when finding the point on a path nearest a click
+const PATH_HIT_SAMPLES: usize = 24;
+
+/// The time and position where a path passes nearest a point, when it passes within
+/// tolerance of it. The point is relative to the path, like keyframe positions.
+pub fn path_point_near(
+    keyframes: &[UIKeyframe],
+    point: Point,
+    tolerance: f32,
+) -> Option<(Duration, [i32; 2])> {
+    let mut nearest: Option<(f32, Duration, [i32; 2])> = None;
+
+    for window in keyframes.windows(2) {
+        let (start_kf, end_kf) = (&window[0], &window[1]);
+        if !matches!(
+            (&start_kf.value, &end_kf.value),
+            (KeyframeValue::Position(_), KeyframeValue::Position(_))
+        ) {
+            continue;
+        }
+
+        let start_s = start_kf.time.as_secs_f32();
+        let step_s = (end_kf.time.as_secs_f32() - start_s) / PATH_HIT_SAMPLES as f32;
+
+        for i in 0..PATH_HIT_SAMPLES {
+            let t1 = start_s + step_s * i as f32;
+            let t2 = t1 + step_s;
+            let pos1 = interpolate_position(start_kf, end_kf, t1);
+            let pos2 = interpolate_position(start_kf, end_kf, t2);
+
+            // project the point onto the sampled piece of path
+            let (x1, y1) = (pos1[0] as f32, pos1[1] as f32);
+            let (dx, dy) = (pos2[0] as f32 - x1, pos2[1] as f32 - y1);
+            let length_squared = dx * dx + dy * dy;
+            let along = if length_squared > 0.0 {
+                (((point.x - x1) * dx + (point.y - y1) * dy) / length_squared).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let (x, y) = (x1 + dx * along, y1 + dy * along);
+            let distance = ((point.x - x).powi(2) + (point.y - y).powi(2)).sqrt();
+
+            if distance <= tolerance && nearest.map_or(true, |(d, _, _)| distance < d) {
+                nearest = Some((
+                    distance,
+                    Duration::from_secs_f32(t1 + step_s * along),
+                    [x.round() as i32, y.round() as i32],
+                ));
+            }
+        }
+    }
+
+    nearest.map(|(_, time, position)| (time, position))
+}
+
+/// Moves one of a curved keyframe's control points, given relative to the path
+pub fn set_tangent(
+    keyframes: &mut [UIKeyframe],
+    keyframe_id: &str,
+    control: TangentControl,
+    position: [i32; 2],
+) -> Result<(), String> {
+    let keyframe = keyframes
+        .iter_mut()
+        .find(|k| k.id == keyframe_id)
+        .ok_or_else(|| format!("No keyframe found with id {}", keyframe_id))?;
+    let PathType::Bezier(curve) = &mut keyframe.path_type else {
+        return Err(format!("Keyframe {} isn't curved", keyframe_id));
+    };
+
+    let control_point = Some(ControlPoint {
+        x: position[0],
+        y: position[1],
+    });
+    match control {
+        TangentControl::Out => curve.control_point1 = control_point,
+        TangentControl::In => curve.control_point2 = control_point,
+    }
+
+    Ok(())
+}
+
+fn segment_midpoint(start: Point, end: Point) -> Point {
+    Point {
+        x: (start.x + end.x) / 2.0,
+        y: (start.y + end.y) / 2.0,
+    }
+}
+
 /// Creates a path segment using a rotated square
 fn create_path_segment(
     window_size: &WindowSize,
@@ -335,10 +674,7 @@ fn create_path_segment(
     length: f32,
 ) -> Polygon {
     // Calculate segment midpoint for position
-    let position = Point {
-        x: (start.x + end.x) / 2.0,
-        y: (start.y + end.y) / 2.0,
-    };
+    let position = segment_midpoint(start, end);
 
     // Create polygon using default square points
     let polygon = Polygon::new(
@@ -394,7 +730,7 @@ fn create_path_handle(
 ) -> Polygon {
     // println!("make handle");
 
-    let polygon = Polygon::new(
+    let mut polygon = Polygon::new(
         window_size,
         device,
         queue,
@@ -428,6 +764,9 @@ fn create_path_handle(
     // polygon.transform.layer = -100.05;
     // polygon.transform.update_uniform_buffer(queue, window_size);
 
+    // kept for dragging, but drawn by the gizmo layer
+    polygon.hidden = true;
+
     polygon
 }
 
@@ -0,0 +1,262 @@
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::animations::{AnimationData, AnimationProperty, EasingType, KeyframeValue, ObjectType};
+use crate::input_overlays::keyframe_property;
+use crate::saved_state::CanvasSize;
+
+/// How far Shake moves the object either way at full intensity, in canvas pixels
+const SHAKE_DISTANCE: f32 = 12.0;
+/// Time between Shake's keyframes
+const SHAKE_STEP_MS: i32 = 60;
+/// Height Bounce drops from at full intensity, in canvas pixels
+const BOUNCE_HEIGHT: f32 = 120.0;
+/// Share of its height each bounce reaches again
+const BOUNCE_RESTITUTION: f32 = 0.3;
+/// Orbit's radius when the object sits on the center
+const ORBIT_RADIUS: f32 = 60.0;
+/// Keyframes for each turn of Orbit
+const ORBIT_STEPS: i32 = 16;
+
+/// The canvas edge Slide In enters from
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum SlideEdge {
+    #[default]
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// Canned motions built from keyframes, a predictable alternative to motion inference
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub enum MotionPreset {
+    SlideIn(SlideEdge), // from just off the canvas to where the object sits
+    Bounce,             // drops onto where the object sits and bounces to rest
+    Orbit([i32; 2]),    // circles this point on the canvas, a turn for each 100 of intensity
+    Shake,              // back and forth, dying down
+    Pop,                // grows from nothing, overshooting a little
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct PresetSettings {
+    pub preset: MotionPreset,
+    pub duration_ms: i32,
+    pub intensity: i32, // percent, 100 for the preset's usual distance or overshoot
+}
+
+impl PresetSettings {
+    pub fn new(preset: MotionPreset) -> Self {
+        PresetSettings {
+            preset,
+            duration_ms: 1000,
+            intensity: 100,
+        }
+    }
+}
+
+/// An animation playing a preset on an object, from its position and size on the canvas
+pub fn preset_animation(
+    settings: &PresetSettings,
+    object_id: String,
+    object_type: ObjectType,
+    position: [i32; 2],
+    dimensions: (f32, f32),
+    canvas_size: &CanvasSize,
+) -> AnimationData {
+    let duration_ms = settings.duration_ms.max(1);
+    let intensity = settings.intensity.max(0) as f32 / 100.0;
+
+    let properties = match settings.preset {
+        MotionPreset::SlideIn(edge) => {
+            // far enough that none of the object shows
+            let (width, height) = (canvas_size.width as i32, canvas_size.height as i32);
+            let (half_width, half_height) = (
+                (dimensions.0 / 2.0).ceil() as i32,
+                (dimensions.1 / 2.0).ceil() as i32,
+            );
+            let start = match edge {
+                SlideEdge::Left => [-half_width, position[1]],
+                SlideEdge::Right => [width + half_width, position[1]],
+                SlideEdge::Top => [position[0], -half_height],
+                SlideEdge::Bottom => [position[0], height + half_height],
+            };
+            // overshoots the rest position by a tenth of the slide
+            let overshoot = [
+                position[0] + ((position[0] - start[0]) as f32 * 0.1 * intensity).round() as i32,
+                position[1] + ((position[1] - start[1]) as f32 * 0.1 * intensity).round() as i32,
+            ];
+
+            vec![keyframe_property(
+                "Position",
+                "position",
+                &[
+                    (0, KeyframeValue::Position(start)),
+                    (duration_ms * 7 / 10, KeyframeValue::Position(overshoot)),
+                    (duration_ms, KeyframeValue::Position(position)),
+                ],
+            )]
+        }
+        MotionPreset::Bounce => {
+            vec![bounce_position(position, duration_ms, intensity)]
+        }
+        MotionPreset::Orbit(center) => {
+            vec![orbit_position(position, center, duration_ms, intensity)]
+        }
+        MotionPreset::Shake => {
+            let steps = (duration_ms / SHAKE_STEP_MS).max(2);
+            let values: Vec<(i32, KeyframeValue)> = (0..=steps)
+                .map(|step| {
+                    let remaining = 1.0 - step as f32 / steps as f32;
+                    let side = if step % 2 == 0 { -1.0 } else { 1.0 };
+                    let offset = if step == 0 || step == steps {
+                        0
+                    } else {
+                        (side * SHAKE_DISTANCE * intensity * remaining).round() as i32
+                    };
+
+                    (
+                        duration_ms * step / steps,
+                        KeyframeValue::Position([position[0] + offset, position[1]]),
+                    )
+                })
+                .collect();
+
+            vec![keyframe_property("Position", "position", &values)]
+        }
+        MotionPreset::Pop => {
+            let overshoot = 100 + (20.0 * intensity).round() as i32;
+
+            vec![
+                keyframe_property(
+                    "Position",
+                    "position",
+                    &[
+                        (0, KeyframeValue::Position(position)),
+                        (duration_ms, KeyframeValue::Position(position)),
+                    ],
+                ),
+                keyframe_property(
+                    "Scale",
+                    "scale",
+                    &[
+                        (0, KeyframeValue::Scale(0)),
+                        (duration_ms * 6 / 10, KeyframeValue::Scale(overshoot)),
+                        (duration_ms, KeyframeValue::Scale(100)),
+                    ],
+                ),
+                keyframe_property(
+                    "Opacity",
+                    "opacity",
+                    &[
+                        (0, KeyframeValue::Opacity(0)),
+                        (duration_ms * 3 / 10, KeyframeValue::Opacity(100)),
+                        (duration_ms, KeyframeValue::Opacity(100)),
+                    ],
+                ),
+            ]
+        }
+    };
+
+    AnimationData {
+        id: Uuid::new_v4().to_string(),
+        object_type,
+        polygon_id: object_id,
+        duration: Duration::from_millis(duration_ms as u64),
+        start_time_ms: 0,
+        properties,
+        position: [0, 0],
+        motion_blur: 0,
+    }
+}
+
+/// Falls from above and bounces, each bounce lower and quicker, like a dropped ball
+fn bounce_position(position: [i32; 2], duration_ms: i32, intensity: f32) -> AnimationProperty {
+    let drop = BOUNCE_HEIGHT * intensity;
+    let heights: Vec<f32> = (1..=3)
+        .map(|bounce| drop * BOUNCE_RESTITUTION.powi(bounce))
+        .collect();
+
+    // falling takes time in proportion to the square root of the height
+    let total_time = drop.sqrt() + heights.iter().map(|h| 2.0 * h.sqrt()).sum::<f32>();
+    let time_ms = |time: f32| {
+        if total_time > 0.0 {
+            (time / total_time * duration_ms as f32).round() as i32
+        } else {
+            0
+        }
+    };
+    let at =
+        |height: f32| KeyframeValue::Position([position[0], position[1] - height.round() as i32]);
+
+    let mut values = vec![(0, at(drop))];
+    let mut time = drop.sqrt();
+    values.push((time_ms(time), at(0.0)));
+    for height in heights {
+        time += height.sqrt();
+        values.push((time_ms(time), at(height)));
+        time += height.sqrt();
+        values.push((time_ms(time), at(0.0)));
+    }
+
+    let mut property = keyframe_property("Position", "position", &values);
+    // speeding up on the way down, slowing on the way up
+    for (index, keyframe) in property.keyframes.iter_mut().enumerate() {
+        keyframe.easing = if index % 2 == 0 {
+            EasingType::EaseIn
+        } else {
+            EasingType::EaseOut
+        };
+    }
+
+    property
+}
+
+/// Circles a center starting from where the object sits, back to there at the end
+fn orbit_position(
+    position: [i32; 2],
+    center: [i32; 2],
+    duration_ms: i32,
+    intensity: f32,
+) -> AnimationProperty {
+    let (dx, dy) = (
+        (position[0] - center[0]) as f32,
+        (position[1] - center[1]) as f32,
+    );
+    let (radius, start_angle) = if dx == 0.0 && dy == 0.0 {
+        (ORBIT_RADIUS, 0.0)
+    } else {
+        ((dx * dx + dy * dy).sqrt(), dy.atan2(dx))
+    };
+    // an object on the center orbits around itself, starting to its right
+    let center = if dx == 0.0 && dy == 0.0 {
+        [center[0] - ORBIT_RADIUS as i32, center[1]]
+    } else {
+        center
+    };
+
+    let turns = intensity.round().max(1.0) as i32;
+    let steps = ORBIT_STEPS * turns;
+    let values: Vec<(i32, KeyframeValue)> = (0..=steps)
+        .map(|step| {
+            let angle = start_angle + TAU * step as f32 / ORBIT_STEPS as f32;
+            (
+                duration_ms * step / steps,
+                KeyframeValue::Position([
+                    center[0] + (radius * angle.cos()).round() as i32,
+                    center[1] + (radius * angle.sin()).round() as i32,
+                ]),
+            )
+        })
+        .collect();
+
+    let mut property = keyframe_property("Position", "position", &values);
+    for keyframe in property.keyframes.iter_mut() {
+        keyframe.easing = EasingType::Linear;
+    }
+
+    property
+}
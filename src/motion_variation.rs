@@ -0,0 +1,55 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Controls for rerolling one object's generated path. The same seed and controls give
+/// the same path, so a reroll the user liked can be made again.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MotionVariation {
+    pub seed: u32,
+    pub amplitude: i32,      // percent of the predicted distance from the object
+    pub direction_bias: i32, // degrees the path is turned about the object, clockwise
+    pub jitter: i32,         // most pixels the seed nudges each keyframe by
+}
+
+impl Default for MotionVariation {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            amplitude: 100,
+            direction_bias: 0,
+            jitter: 24,
+        }
+    }
+}
+
+impl MotionVariation {
+    /// Varies a path of canvas positions about an anchor, usually where the object sits.
+    /// Besides the nudges, the seed turns the whole path by up to 20 degrees either way.
+    pub fn apply(&self, path: &[[i32; 2]], anchor: [i32; 2]) -> Vec<[i32; 2]> {
+        let mut rng = StdRng::seed_from_u64(self.seed as u64);
+        let turn = (self.direction_bias as f32 + rng.gen_range(-20.0..=20.0)).to_radians();
+        let (sin, cos) = turn.sin_cos();
+        let scale = self.amplitude.max(0) as f32 / 100.0;
+        let jitter = self.jitter.max(0) as f32;
+
+        path.iter()
+            .map(|point| {
+                let dx = (point[0] - anchor[0]) as f32 * scale;
+                let dy = (point[1] - anchor[1]) as f32 * scale;
+                let (nudge_x, nudge_y) = if jitter > 0.0 {
+                    (
+                        rng.gen_range(-jitter..=jitter),
+                        rng.gen_range(-jitter..=jitter),
+                    )
+                } else {
+                    (0.0, 0.0)
+                };
+
+                [
+                    anchor[0] + (dx * cos - dy * sin + nudge_x).round() as i32,
+                    anchor[1] + (dx * sin + dy * cos + nudge_y).round() as i32,
+                ]
+            })
+            .collect()
+    }
+}
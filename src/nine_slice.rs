@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+use crate::vertex::Vertex;
+
+/// Borders in source image pixels that keep their size when the image is resized, as
+/// with screenshot frames or button artwork. Only the middle stretches.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub struct NineSliceInsets {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+
+/// Builds the unit quad (-0.5 to 0.5) used by images as a 3x3 grid, with the borders sized
+/// so they show at their source size once scaled to `size`. Borders wider than the image
+/// shrink proportionally to fit.
+pub fn build_nine_slice_mesh(
+    insets: &NineSliceInsets,
+    source_dimensions: (u32, u32),
+    size: (f32, f32),
+    color: [f32; 4],
+) -> (Vec<Vertex>, Vec<u32>) {
+    let (position_xs, tex_xs) = slice_stops(insets.left, insets.right, source_dimensions.0, size.0);
+    let (position_ys, tex_ys) = slice_stops(insets.top, insets.bottom, source_dimensions.1, size.1);
+
+    let mut vertices = Vec::with_capacity(16);
+    for y in 0..4 {
+        for x in 0..4 {
+            vertices.push(Vertex {
+                position: [-0.5 + position_xs[x], -0.5 + position_ys[y], 0.0],
+                tex_coords: [tex_xs[x], tex_ys[y]],
+                color,
+            });
+        }
+    }
+
+    let mut indices = Vec::with_capacity(54);
+    for y in 0..3 {
+        for x in 0..3 {
+            let top_left = y * 4 + x;
+            let top_right = top_left + 1;
+            let bottom_left = (y + 1) * 4 + x;
+            let bottom_right = bottom_left + 1;
+
+            indices.push(top_left);
+            indices.push(top_right);
+            indices.push(bottom_right);
+
+            indices.push(top_left);
+            indices.push(bottom_right);
+            indices.push(bottom_left);
+        }
+    }
+
+    (vertices, indices)
+}
+
+/// Grid lines along one axis, as fractions of the drawn size and of the texture
+fn slice_stops(start: u32, end: u32, source_size: u32, size: f32) -> ([f32; 4], [f32; 4]) {
+    let source_size = source_size.max(1) as f32;
+    let size = size.abs().max(1.0);
+
+    let start = start as f32;
+    let end = end as f32;
+    let fit = (size / (start + end).max(1.0)).min(1.0);
+
+    let positions = [0.0, start * fit / size, 1.0 - end * fit / size, 1.0];
+    let tex_coords = [
+        0.0,
+        (start / source_size).min(1.0),
+        (1.0 - end / source_size).max(0.0),
+        1.0,
+    ];
+
+    (positions, tex_coords)
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use crate::animations::{AnimationData, KeyframeValue};
+
+/// Ghosts of the selected object at its keyframes around the playhead, shown in the editor only
+#[derive(Clone, Copy, Debug)]
+pub struct OnionSkinSettings {
+    pub enabled: bool,
+    pub previous: usize, // keyframes shown before the playhead
+    pub next: usize,     // and after it
+    pub opacity: f32,    // of the ghosts nearest the playhead, from 0.0 to 1.0
+    pub falloff: f32,    // opacity multiplier for each keyframe further away
+}
+
+impl Default for OnionSkinSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            previous: 2,
+            next: 2,
+            opacity: 0.4,
+            falloff: 0.5,
+        }
+    }
+}
+
+/// Where and how faintly to draw one ghost
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OnionSkinGhost {
+    pub time: Duration,     // of the keyframe, relative to the start of the animation
+    pub position: [i32; 2], // including the animation's group position
+    pub opacity: f32,
+    pub is_previous: bool, // before the playhead, tinted differently from those after
+}
+
+/// Ghosts at the position keyframes either side of a time relative to the start of the
+/// animation. A keyframe at the time itself is where the object already is, so it's skipped.
+pub fn onion_skin_ghosts(
+    animation: &AnimationData,
+    time: Duration,
+    settings: &OnionSkinSettings,
+) -> Vec<OnionSkinGhost> {
+    let Some(property) = animation
+        .properties
+        .iter()
+        .find(|p| p.property_path == "position")
+    else {
+        return Vec::new();
+    };
+
+    let mut keyframes: Vec<(Duration, [i32; 2])> = property
+        .keyframes
+        .iter()
+        .filter_map(|k| match k.value {
+            KeyframeValue::Position(position) => Some((k.time, position)),
+            _ => None,
+        })
+        .collect();
+    keyframes.sort_by_key(|(time, _)| *time);
+
+    // index counts keyframes away from the playhead, 0 being the nearest
+    let ghost = |index: usize, (keyframe_time, position): &(Duration, [i32; 2]), is_previous| {
+        OnionSkinGhost {
+            time: *keyframe_time,
+            position: [
+                position[0] + animation.position[0],
+                position[1] + animation.position[1],
+            ],
+            opacity: settings.opacity * settings.falloff.powi(index as i32),
+            is_previous,
+        }
+    };
+
+    let previous = keyframes
+        .iter()
+        .rev()
+        .filter(|(keyframe_time, _)| *keyframe_time < time)
+        .take(settings.previous)
+        .enumerate()
+        .map(|(index, keyframe)| ghost(index, keyframe, true));
+    let next = keyframes
+        .iter()
+        .filter(|(keyframe_time, _)| *keyframe_time > time)
+        .take(settings.next)
+        .enumerate()
+        .map(|(index, keyframe)| ghost(index, keyframe, false));
+
+    previous.chain(next).collect()
+}
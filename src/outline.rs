@@ -0,0 +1,269 @@
+use fontdue::{
+    layout::{CoordinateSystem, GlyphPosition, Layout, LayoutSettings, TextStyle},
+    Font,
+};
+use serde::{Deserialize, Serialize};
+use ttf_parser::{Face, GlyphId, OutlineBuilder};
+
+use crate::animations::{AnimationData, ObjectType};
+use crate::clipboard::clone_animation_data;
+use crate::editor::Point;
+use crate::polygon::SavedPoint;
+use crate::text_due::SavedTextRendererConfig;
+
+/// Saved outline points are normalized, so they are stored in thousandths to keep them as i32
+pub const OUTLINE_PRECISION: f32 = 1000.0;
+
+/// Line segments used to flatten each quadratic or cubic curve of a glyph
+const CURVE_SEGMENTS: usize = 8;
+
+/// Marks a polygon as a glyph outlined from a text item
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct OutlineSource {
+    pub text_id: String,
+    pub glyph_index: i32, // position of the glyph within the text
+    pub character: String,
+}
+
+/// Non-rectangular polygon shape, saved as normalized points in thousandths
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct SavedOutline {
+    pub points: Vec<SavedPoint>,
+    pub contours: Vec<Vec<SavedPoint>>,
+    pub source: Option<OutlineSource>,
+}
+
+/// The original text item retained after outlining, so it can be restored or re-converted
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct OutlinedText {
+    pub text: SavedTextRendererConfig,
+    pub animation: Option<AnimationData>,
+}
+
+/// A single laid out glyph, ready to become a polygon
+#[derive(Clone, Debug)]
+pub struct GlyphOutline {
+    pub character: char,
+    pub glyph_index: usize,
+    /// Largest contour, used for hit testing
+    pub points: Vec<Point>,
+    /// Remaining contours, such as counters and dots
+    pub contours: Vec<Vec<Point>>,
+    pub dimensions: (f32, f32),
+    /// Center of the glyph relative to the center of the text
+    pub offset: Point,
+}
+
+struct ContourCollector {
+    contours: Vec<Vec<(f32, f32)>>,
+    current: Vec<(f32, f32)>,
+}
+
+impl ContourCollector {
+    fn last(&self) -> (f32, f32) {
+        *self.current.last().unwrap_or(&(0.0, 0.0))
+    }
+}
+
+impl OutlineBuilder for ContourCollector {
+    fn move_to(&mut self, x: f32, y: f32) {
+        if !self.current.is_empty() {
+            self.contours.push(std::mem::take(&mut self.current));
+        }
+        self.current.push((x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.current.push((x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (x0, y0) = self.last();
+        for step in 1..=CURVE_SEGMENTS {
+            let t = step as f32 / CURVE_SEGMENTS as f32;
+            let mt = 1.0 - t;
+            self.current.push((
+                mt * mt * x0 + 2.0 * mt * t * x1 + t * t * x,
+                mt * mt * y0 + 2.0 * mt * t * y1 + t * t * y,
+            ));
+        }
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (x0, y0) = self.last();
+        for step in 1..=CURVE_SEGMENTS {
+            let t = step as f32 / CURVE_SEGMENTS as f32;
+            let mt = 1.0 - t;
+            self.current.push((
+                mt * mt * mt * x0 + 3.0 * mt * mt * t * x1 + 3.0 * mt * t * t * x2 + t * t * t * x,
+                mt * mt * mt * y0 + 3.0 * mt * mt * t * y1 + 3.0 * mt * t * t * y2 + t * t * t * y,
+            ));
+        }
+    }
+
+    fn close(&mut self) {
+        // the closing point duplicates the first, the polygon path closes itself
+        if self.current.len() > 1 && self.current.first() == self.current.last() {
+            self.current.pop();
+        }
+        if !self.current.is_empty() {
+            self.contours.push(std::mem::take(&mut self.current));
+        }
+    }
+}
+
+fn contour_area(contour: &[Point]) -> f32 {
+    let n = contour.len();
+    let mut area = 0.0;
+    for i in 0..n {
+        let a = contour[i];
+        let b = contour[(i + 1) % n];
+        area += a.x * b.y - b.x * a.y;
+    }
+    (area / 2.0).abs()
+}
+
+/// Lays out the text as the text renderer does and returns each visible glyph's outline,
+/// flattened and normalized to its own bounding box
+pub fn text_to_glyph_outlines(
+    font_data: &[u8],
+    text: &str,
+    font_size: f32,
+    max_width: f32,
+) -> Result<Vec<GlyphOutline>, String> {
+    let font = Font::from_bytes(font_data, fontdue::FontSettings::default())
+        .map_err(|e| format!("Couldn't load font: {}", e))?;
+    let face = Face::parse(font_data, 0).map_err(|e| format!("Couldn't parse font: {}", e))?;
+
+    let scale = font_size / face.units_per_em() as f32;
+
+    let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+    layout.reset(&LayoutSettings {
+        max_width: Some(max_width),
+        ..LayoutSettings::default()
+    });
+    layout.append(
+        &[&font],
+        &TextStyle {
+            text,
+            font_index: 0,
+            px: font_size,
+            user_data: (),
+        },
+    );
+
+    let glyphs = layout.glyphs();
+
+    // same centering as TextRenderer::render_text
+    let total_width = glyphs
+        .iter()
+        .fold(0.0, |max_width: f32, glyph: &GlyphPosition| {
+            max_width.max(glyph.x + glyph.width as f32)
+        });
+    let total_height = layout.height();
+    let start_x = -total_width / 2.0;
+    let start_y = -total_height / 2.0;
+
+    let mut outlines = Vec::new();
+
+    for (glyph_index, glyph) in glyphs.iter().enumerate() {
+        if glyph.width == 0 || glyph.height == 0 {
+            continue; // whitespace
+        }
+
+        let mut collector = ContourCollector {
+            contours: Vec::new(),
+            current: Vec::new(),
+        };
+
+        let bounds = match face.outline_glyph(GlyphId(glyph.key.glyph_index), &mut collector) {
+            Some(bounds) => bounds,
+            None => continue,
+        };
+        collector.close();
+
+        let bounds_width = (bounds.x_max - bounds.x_min) as f32;
+        let bounds_height = (bounds.y_max - bounds.y_min) as f32;
+
+        if bounds_width <= 0.0 || bounds_height <= 0.0 {
+            continue;
+        }
+
+        // font units are y-up, the canvas is y-down
+        let mut contours: Vec<Vec<Point>> = collector
+            .contours
+            .iter()
+            .filter(|c| c.len() > 2)
+            .map(|c| {
+                c.iter()
+                    .map(|(x, y)| Point {
+                        x: (x - bounds.x_min as f32) / bounds_width,
+                        y: (bounds.y_max as f32 - y) / bounds_height,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        if contours.is_empty() {
+            continue;
+        }
+
+        let outer_index = contours
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| contour_area(a).total_cmp(&contour_area(b)))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        let points = contours.remove(outer_index);
+
+        outlines.push(GlyphOutline {
+            character: glyph.parent,
+            glyph_index,
+            points,
+            contours,
+            dimensions: (bounds_width * scale, bounds_height * scale),
+            offset: Point {
+                x: start_x + glyph.x + glyph.width as f32 / 2.0,
+                y: start_y + glyph.y + glyph.height as f32 / 2.0,
+            },
+        });
+    }
+
+    Ok(outlines)
+}
+
+pub fn points_to_saved(points: &[Point]) -> Vec<SavedPoint> {
+    points
+        .iter()
+        .map(|p| SavedPoint {
+            x: (p.x * OUTLINE_PRECISION) as i32,
+            y: (p.y * OUTLINE_PRECISION) as i32,
+        })
+        .collect()
+}
+
+pub fn saved_to_points(points: &[SavedPoint]) -> Vec<Point> {
+    points
+        .iter()
+        .map(|p| Point {
+            x: p.x as f32 / OUTLINE_PRECISION,
+            y: p.y as f32 / OUTLINE_PRECISION,
+        })
+        .collect()
+}
+
+/// Copies the text's animation onto a glyph, shifting positions so the glyph keeps its place in the text
+pub fn glyph_animation(
+    text_animation: &AnimationData,
+    polygon_id: String,
+    offset: Point,
+) -> AnimationData {
+    let mut animation = clone_animation_data(
+        text_animation,
+        polygon_id,
+        [offset.x as i32, offset.y as i32],
+    );
+    animation.object_type = ObjectType::Polygon;
+
+    animation
+}
@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::animations::ObjectType;
+
+/// Recently used colors kept in the palette, oldest dropped first
+pub const MAX_RECENT_COLORS: usize = 12;
+
+/// A named project color
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct Swatch {
+    pub id: String,
+    pub name: String,
+    pub color: [i32; 4],
+}
+
+/// Which of an object's colors a swatch or color is applied to
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub enum ColorTarget {
+    Fill,   // polygon fill, or a text item's background
+    Stroke, // polygons only
+    Text,   // text items only
+}
+
+/// An object color that follows a swatch, updated when the swatch changes
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct SwatchLink {
+    pub swatch_id: String,
+    pub object_id: String,
+    pub object_type: ObjectType,
+    pub target: ColorTarget,
+}
+
+/// The project's swatches, recently used colors and linked styles
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub struct Palette {
+    pub swatches: Vec<Swatch>,
+    pub recent_colors: Vec<[i32; 4]>, // most recent first
+    pub links: Vec<SwatchLink>,
+}
+
+impl Palette {
+    /// Adds a swatch, returning its id
+    pub fn add_swatch(&mut self, name: String, color: [i32; 4]) -> String {
+        let id = Uuid::new_v4().to_string();
+        self.swatches.push(Swatch {
+            id: id.clone(),
+            name,
+            color,
+        });
+
+        id
+    }
+
+    pub fn swatch(&self, swatch_id: &str) -> Option<&Swatch> {
+        self.swatches.iter().find(|s| s.id == swatch_id)
+    }
+
+    pub fn swatch_mut(&mut self, swatch_id: &str) -> Result<&mut Swatch, String> {
+        self.swatches
+            .iter_mut()
+            .find(|s| s.id == swatch_id)
+            .ok_or_else(|| format!("No swatch found with id {}", swatch_id))
+    }
+
+    /// Removes a swatch, leaving the objects that used it their current color
+    pub fn remove_swatch(&mut self, swatch_id: &str) -> Result<(), String> {
+        let count = self.swatches.len();
+        self.swatches.retain(|s| s.id != swatch_id);
+        if self.swatches.len() == count {
+            return Err(format!("No swatch found with id {}", swatch_id));
+        }
+
+        self.links.retain(|l| l.swatch_id != swatch_id);
+
+        Ok(())
+    }
+
+    /// Moves a color to the front of the recent colors
+    pub fn use_color(&mut self, color: [i32; 4]) {
+        self.recent_colors.retain(|c| *c != color);
+        self.recent_colors.insert(0, color);
+        self.recent_colors.truncate(MAX_RECENT_COLORS);
+    }
+
+    /// Links an object color to a swatch, replacing any link it had
+    pub fn link(&mut self, link: SwatchLink) {
+        self.unlink(&link.object_id, link.target);
+        self.links.push(link);
+    }
+
+    pub fn unlink(&mut self, object_id: &str, target: ColorTarget) {
+        self.links
+            .retain(|l| !(l.object_id == object_id && l.target == target));
+    }
+
+    /// Drops links to objects that no longer exist, such as after deleting them
+    pub fn unlink_object(&mut self, object_id: &str) {
+        self.links.retain(|l| l.object_id != object_id);
+    }
+
+    pub fn links_to(&self, swatch_id: &str) -> Vec<SwatchLink> {
+        self.links
+            .iter()
+            .filter(|l| l.swatch_id == swatch_id)
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,527 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use cgmath::{SquareMatrix, Vector2};
+use image::GenericImageView;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use wgpu::util::DeviceExt;
+
+use crate::editor::{Point, WindowSize, CANVAS_HORIZ_OFFSET, CANVAS_VERT_OFFSET};
+use crate::polygon::SavedPoint;
+use crate::tags::ColorTag;
+use crate::transform::{create_empty_group_transform, matrix4_to_raw_array, Transform};
+use crate::vertex::Vertex;
+
+/// Upper bound on live particles per emitter, which keeps the per-frame CPU cost fixed
+pub const MAX_PARTICLES: usize = 2000;
+
+/// Smallest size used for selecting a point emitter on the canvas
+const MIN_HIT_SIZE: f32 = 40.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub enum ParticlePreset {
+    Confetti,
+    Sparks,
+    Snow,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct SavedParticleEmitterConfig {
+    pub id: String,
+    pub name: String,
+    /// Center of the emitter
+    pub position: SavedPoint,
+    /// Area particles spawn within, (0, 0) for a point emitter
+    pub dimensions: (i32, i32),
+    pub layer: i32,
+    pub rate: i32, // particles per second, keyframed with EmissionRate
+    pub lifetime_ms: i32,
+    pub direction: i32,      // degrees, 0 is right and 90 is down
+    pub spread: i32,         // degrees either side of the direction
+    pub speed: i32,          // pixels per second
+    pub speed_variance: i32, // out of 100
+    pub gravity: i32,        // pixels per second squared, positive is down
+    pub particle_size: i32,
+    /// Colors from birth to death, 0-255 like other saved colors
+    pub color_ramp: Vec<[i32; 4]>,
+    /// Image drawn for each particle, a plain square when None
+    pub sprite_path: Option<String>,
+    /// Same seed, same particles, so exports are reproducible
+    pub seed: i32,
+    #[serde(default)]
+    pub tags: Vec<ColorTag>,
+    #[serde(default)]
+    pub locked: bool,
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+impl SavedParticleEmitterConfig {
+    pub fn from_preset(
+        preset: ParticlePreset,
+        id: String,
+        name: String,
+        position: SavedPoint,
+    ) -> Self {
+        let base = SavedParticleEmitterConfig {
+            id,
+            name,
+            position,
+            dimensions: (0, 0),
+            layer: 0,
+            rate: 60,
+            lifetime_ms: 2000,
+            direction: 270,
+            spread: 30,
+            speed: 300,
+            speed_variance: 30,
+            gravity: 400,
+            particle_size: 10,
+            color_ramp: vec![[255, 255, 255, 255], [255, 255, 255, 0]],
+            sprite_path: None,
+            seed: 1,
+            tags: Vec::new(),
+            locked: false,
+            hidden: false,
+        };
+
+        match preset {
+            ParticlePreset::Confetti => SavedParticleEmitterConfig {
+                rate: 120,
+                lifetime_ms: 3000,
+                spread: 40,
+                speed: 600,
+                speed_variance: 40,
+                gravity: 500,
+                particle_size: 12,
+                color_ramp: vec![
+                    [255, 82, 82, 255],
+                    [255, 215, 64, 255],
+                    [64, 196, 255, 255],
+                    [105, 240, 174, 0],
+                ],
+                ..base
+            },
+            ParticlePreset::Sparks => SavedParticleEmitterConfig {
+                rate: 200,
+                lifetime_ms: 700,
+                spread: 180,
+                speed: 350,
+                speed_variance: 60,
+                gravity: 300,
+                particle_size: 4,
+                color_ramp: vec![[255, 255, 220, 255], [255, 170, 40, 255], [200, 40, 0, 0]],
+                ..base
+            },
+            ParticlePreset::Snow => SavedParticleEmitterConfig {
+                dimensions: (800, 0),
+                rate: 40,
+                lifetime_ms: 8000,
+                direction: 90,
+                spread: 15,
+                speed: 60,
+                speed_variance: 50,
+                gravity: 5,
+                particle_size: 6,
+                color_ramp: vec![[255, 255, 255, 0], [255, 255, 255, 230], [255, 255, 255, 0]],
+                ..base
+            },
+        }
+    }
+}
+
+/// Emits particles from a point or area. Particles aren't simulated step by step, each is
+/// derived from its index, spawn time and the seed, so frames reproduce from the playhead time.
+/// All live particles are batched into one vertex buffer and drawn in a single call.
+pub struct ParticleEmitter {
+    pub id: Uuid,
+    pub current_sequence_id: Uuid,
+    pub name: String,
+    pub config: SavedParticleEmitterConfig,
+    pub rate: f32, // current emission rate, animated by EmissionRate keyframes
+    pub transform: Transform,
+    pub bind_group: wgpu::BindGroup,
+    pub group_bind_group: wgpu::BindGroup,
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub vertices: Vec<Vertex>,
+    pub index_count: u32,
+    pub hidden: bool,
+    pub user_hidden: bool, // hidden from the layer panel, separate from sequence visibility
+    pub locked: bool,
+    pub layer: i32,
+    pub opacity: f32,
+    spawns: VecDeque<(u64, f32)>, // particle index and spawn time in seconds
+    emitted: f64,
+    last_time_s: f32,
+}
+
+impl ParticleEmitter {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        model_bind_group_layout: &wgpu::BindGroupLayout,
+        group_bind_group_layout: &Arc<wgpu::BindGroupLayout>,
+        window_size: &WindowSize,
+        config: &SavedParticleEmitterConfig,
+        current_sequence_id: Uuid,
+    ) -> Self {
+        let texture_view = create_particle_texture(device, queue, config.sprite_path.as_deref());
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let raw_matrix = matrix4_to_raw_array(&cgmath::Matrix4::<f32>::identity());
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Particle Uniform Buffer"),
+            contents: bytemuck::cast_slice(&raw_matrix),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: model_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("Particle Bind Group"),
+        });
+
+        let mut transform = Transform::new(
+            Vector2::new(
+                CANVAS_HORIZ_OFFSET + config.position.x as f32,
+                CANVAS_VERT_OFFSET + config.position.y as f32,
+            ),
+            0.0,
+            Vector2::new(1.0, 1.0),
+            uniform_buffer,
+            window_size,
+        );
+        transform.layer = config.layer as f32;
+        transform.update_uniform_buffer(queue, window_size);
+
+        // sized for the most particles that can be alive, only the live ones are drawn
+        let vertices = vec![
+            Vertex {
+                position: [0.0, 0.0, 0.0],
+                tex_coords: [0.0, 0.0],
+                color: [0.0, 0.0, 0.0, 0.0],
+            };
+            MAX_PARTICLES * 4
+        ];
+
+        let mut indices: Vec<u32> = Vec::with_capacity(MAX_PARTICLES * 6);
+        for particle in 0..MAX_PARTICLES as u32 {
+            let base = particle * 4;
+            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Particle Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Particle Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let (tmp_group_bind_group, tmp_group_transform) =
+            create_empty_group_transform(device, group_bind_group_layout, window_size);
+
+        ParticleEmitter {
+            id: Uuid::parse_str(&config.id).expect("Couldn't convert string to uuid"),
+            current_sequence_id,
+            name: config.name.clone(),
+            config: config.clone(),
+            rate: config.rate as f32,
+            transform,
+            bind_group,
+            group_bind_group: tmp_group_bind_group,
+            vertex_buffer,
+            index_buffer,
+            vertices,
+            index_count: 0,
+            hidden: false,
+            user_hidden: false,
+            locked: false,
+            layer: config.layer,
+            opacity: 1.0,
+            spawns: VecDeque::new(),
+            emitted: 0.0,
+            last_time_s: 0.0,
+        }
+    }
+
+    /// Clears all particles, as when playback restarts or the playhead moves backwards
+    pub fn reset(&mut self) {
+        self.spawns.clear();
+        self.emitted = 0.0;
+        self.last_time_s = 0.0;
+        self.index_count = 0;
+    }
+
+    /// Emits and ages particles up to the given sequence time and writes the live ones to the
+    /// vertex buffer. With a constant rate the result doesn't depend on how time was stepped.
+    pub fn update(&mut self, queue: &wgpu::Queue, time_s: f32) {
+        if time_s < self.last_time_s {
+            self.reset();
+        }
+
+        let dt = time_s - self.last_time_s;
+        let previous = self.emitted;
+        self.emitted += (self.rate.max(0.0) * dt) as f64;
+
+        let first = previous.floor() as u64;
+        let last = self.emitted.floor() as u64;
+        // particles beyond the cap would be dropped anyway, skip straight past them
+        let first = first.max(last.saturating_sub(MAX_PARTICLES as u64));
+
+        for index in first..last {
+            let fraction = ((index + 1) as f64 - previous) / (self.emitted - previous);
+            let spawn_time = self.last_time_s + dt * fraction as f32;
+            self.spawns.push_back((index, spawn_time));
+        }
+
+        let lifetime = self.config.lifetime_ms.max(1) as f32 / 1000.0;
+        while let Some((_, spawn_time)) = self.spawns.front() {
+            if time_s - spawn_time < lifetime && self.spawns.len() <= MAX_PARTICLES {
+                break;
+            }
+            self.spawns.pop_front();
+        }
+
+        self.last_time_s = time_s;
+
+        let mut particle_count = 0;
+        for (index, spawn_time) in self.spawns.iter() {
+            let age = time_s - spawn_time;
+            let quad = particle_quad(&self.config, *index, age, lifetime, self.opacity);
+
+            let start = particle_count * 4;
+            self.vertices[start..start + 4].copy_from_slice(&quad);
+            particle_count += 1;
+        }
+
+        self.index_count = (particle_count * 6) as u32;
+
+        if particle_count > 0 {
+            queue.write_buffer(
+                &self.vertex_buffer,
+                0,
+                bytemuck::cast_slice(&self.vertices[..particle_count * 4]),
+            );
+        }
+    }
+
+    pub fn update_opacity(&mut self, opacity: f32) {
+        // applied as particles are written on the next update
+        self.opacity = opacity;
+    }
+
+    pub fn update_layer(&mut self, layer_index: i32) {
+        self.layer = layer_index;
+        self.transform.layer = layer_index as f32;
+    }
+
+    /// Size used for selection and snapping, point emitters get a small square
+    pub fn get_dimensions(&self) -> (f32, f32) {
+        (
+            (self.config.dimensions.0 as f32).max(MIN_HIT_SIZE),
+            (self.config.dimensions.1 as f32).max(MIN_HIT_SIZE),
+        )
+    }
+
+    pub fn contains_point(&self, point: &Point) -> bool {
+        let (width, height) = self.get_dimensions();
+
+        (point.x - self.transform.position.x).abs() <= width / 2.0
+            && (point.y - self.transform.position.y).abs() <= height / 2.0
+    }
+
+    pub fn to_saved_config(&self) -> SavedParticleEmitterConfig {
+        let mut config = self.config.clone();
+
+        config.position = SavedPoint {
+            x: (self.transform.position.x - CANVAS_HORIZ_OFFSET) as i32,
+            y: (self.transform.position.y - CANVAS_VERT_OFFSET) as i32,
+        };
+        config.layer = self.layer;
+        config.locked = self.locked;
+        config.hidden = self.user_hidden;
+
+        config
+    }
+}
+
+/// Deterministic value in 0..1 for a particle, each salt giving an independent value
+fn random_unit(seed: i32, index: u64, salt: u64) -> f32 {
+    // splitmix64
+    let mut z = (seed as u64)
+        .wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        .wrapping_add(index.wrapping_mul(0xBF58_476D_1CE4_E5B9))
+        .wrapping_add(salt.wrapping_mul(0x94D0_49BB_1331_11EB));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+
+    (z >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Samples the color ramp at t (0 at birth, 1 at death), returning wgpu colors
+fn sample_color_ramp(ramp: &[[i32; 4]], t: f32) -> [f32; 4] {
+    let to_wgpu = |c: &[i32; 4]| {
+        [
+            c[0] as f32 / 255.0,
+            c[1] as f32 / 255.0,
+            c[2] as f32 / 255.0,
+            c[3] as f32 / 255.0,
+        ]
+    };
+
+    match ramp.len() {
+        0 => [1.0, 1.0, 1.0, 1.0],
+        1 => to_wgpu(&ramp[0]),
+        _ => {
+            let scaled = t.clamp(0.0, 1.0) * (ramp.len() - 1) as f32;
+            let i = (scaled.floor() as usize).min(ramp.len() - 2);
+            let local = scaled - i as f32;
+            let a = to_wgpu(&ramp[i]);
+            let b = to_wgpu(&ramp[i + 1]);
+
+            [
+                a[0] + (b[0] - a[0]) * local,
+                a[1] + (b[1] - a[1]) * local,
+                a[2] + (b[2] - a[2]) * local,
+                a[3] + (b[3] - a[3]) * local,
+            ]
+        }
+    }
+}
+
+/// The four corners of a particle at the given age, relative to the emitter position
+fn particle_quad(
+    config: &SavedParticleEmitterConfig,
+    index: u64,
+    age: f32,
+    lifetime: f32,
+    opacity: f32,
+) -> [Vertex; 4] {
+    let random = |salt: u64| random_unit(config.seed, index, salt);
+
+    let spawn_x = (random(0) - 0.5) * config.dimensions.0 as f32;
+    let spawn_y = (random(1) - 0.5) * config.dimensions.1 as f32;
+
+    let angle =
+        (config.direction as f32 + (random(2) * 2.0 - 1.0) * config.spread as f32).to_radians();
+    let speed = config.speed as f32
+        * (1.0 + (random(3) * 2.0 - 1.0) * config.speed_variance as f32 / 100.0);
+
+    let x = spawn_x + angle.cos() * speed * age;
+    let y = spawn_y + angle.sin() * speed * age + 0.5 * config.gravity as f32 * age * age;
+
+    // vary size and spin a little so particles don't look stamped
+    let half_size = config.particle_size as f32 * (0.75 + random(4) * 0.5) / 2.0;
+    let spin =
+        random(5) * std::f32::consts::TAU + age * (random(6) * 2.0 - 1.0) * std::f32::consts::TAU;
+    let (sin, cos) = spin.sin_cos();
+
+    let mut color = sample_color_ramp(&config.color_ramp, age / lifetime);
+    color[3] *= opacity;
+
+    let corner = |cx: f32, cy: f32, u: f32, v: f32| Vertex {
+        position: [
+            x + cx * half_size * cos - cy * half_size * sin,
+            y + cx * half_size * sin + cy * half_size * cos,
+            0.0,
+        ],
+        tex_coords: [u, v],
+        color,
+    };
+
+    [
+        corner(-1.0, -1.0, 0.0, 0.0),
+        corner(1.0, -1.0, 1.0, 0.0),
+        corner(1.0, 1.0, 1.0, 1.0),
+        corner(-1.0, 1.0, 0.0, 1.0),
+    ]
+}
+
+/// Loads the sprite, falling back to a 1x1 white texture so particles draw as squares
+fn create_particle_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    sprite_path: Option<&str>,
+) -> wgpu::TextureView {
+    let sprite = sprite_path.and_then(|path| match image::open(path) {
+        Ok(img) => Some(img),
+        Err(e) => {
+            println!("Couldn't open particle sprite {}: {:?}", path, e);
+            None
+        }
+    });
+
+    let (rgba, width, height) = match sprite {
+        Some(img) => {
+            let (width, height) = img.dimensions();
+            (img.to_rgba8().into_raw(), width, height)
+        }
+        None => (vec![255, 255, 255, 255], 1, 1),
+    };
+
+    let texture_size = wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Particle Texture"),
+        size: texture_size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[wgpu::TextureFormat::Rgba8UnormSrgb],
+    });
+
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        &rgba,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * width),
+            rows_per_image: Some(height),
+        },
+        texture_size,
+    );
+
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
@@ -0,0 +1,387 @@
+use std::sync::mpsc;
+
+use crate::editor::{Editor, InteractionTarget, Point, WindowSize};
+use crate::masking::{depth_stencil_state, MASK_DEPTH_FORMAT};
+use crate::vertex::Vertex;
+
+const ID_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Uint;
+
+/// One row of a texture copy, the smallest readback wgpu allows
+const READBACK_SIZE: u64 = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64;
+
+/// Buffers for drawing one object, or one part of it like a text background
+struct PickDraw<'a> {
+    bind_group: &'a wgpu::BindGroup,
+    group_bind_group: &'a wgpu::BindGroup,
+    vertex_buffer: &'a wgpu::Buffer,
+    index_buffer: &'a wgpu::Buffer,
+    index_count: u32,
+}
+
+/// Finds the object under the cursor by drawing every pickable object's id into an offscreen
+/// texture and reading back the pixel, so rotated text and concave polygons are hit exactly
+/// where they're drawn. Each pick waits on the gpu, so only pick on clicks.
+pub struct GpuPicker {
+    pipeline: wgpu::RenderPipeline,
+    id_texture: wgpu::Texture,
+    id_view: wgpu::TextureView,
+    depth_view: wgpu::TextureView,
+    readback_buffer: wgpu::Buffer,
+}
+
+impl GpuPicker {
+    /// `layout` has the camera, model, window size and group bind groups, as for the main pipeline
+    pub fn new(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        window_size: &WindowSize,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Picking Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("export/shaders/picking.wgsl").into()),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Picking Pipeline"),
+            layout: Some(layout),
+            multiview: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: ID_FORMAT,
+                    blend: None, // ids can't be blended
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                conservative: false,
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+            },
+            depth_stencil: Some(depth_stencil_state()),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+        });
+
+        let (id_texture, id_view, depth_view) = create_targets(device, window_size);
+
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Picking Readback Buffer"),
+            size: READBACK_SIZE,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        GpuPicker {
+            pipeline,
+            id_texture,
+            id_view,
+            depth_view,
+            readback_buffer,
+        }
+    }
+
+    /// The topmost visible, unlocked object drawn at a point in window pixels
+    pub fn pick(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        editor: &Editor,
+        screen_point: Point,
+    ) -> Option<InteractionTarget> {
+        let camera = editor.scene.camera.as_ref()?;
+        let camera_binding = editor.scene.camera_binding.as_ref()?;
+        let window_size_bind_group = editor.window_size_bind_group.as_ref()?;
+
+        let window_size = camera.window_size;
+        if self.id_texture.width() != window_size.width
+            || self.id_texture.height() != window_size.height
+        {
+            (self.id_texture, self.id_view, self.depth_view) = create_targets(device, &window_size);
+        }
+
+        if screen_point.x < 0.0
+            || screen_point.y < 0.0
+            || screen_point.x >= window_size.width as f32
+            || screen_point.y >= window_size.height as f32
+        {
+            return None;
+        }
+        let (x, y) = (screen_point.x as u32, screen_point.y as u32);
+
+        let (targets, draws) = pickable_draws(editor);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Picking Encoder"),
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Picking Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.id_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), // 0 is nothing
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            // only the pixel under the cursor is read
+            render_pass.set_scissor_rect(x, y, 1, 1);
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(0, &camera_binding.bind_group, &[]);
+            render_pass.set_bind_group(2, window_size_bind_group, &[]);
+
+            for (target_index, draw) in draws.iter() {
+                let id = *target_index as u32 + 1;
+
+                render_pass.set_bind_group(1, draw.bind_group, &[]);
+                render_pass.set_bind_group(3, draw.group_bind_group, &[]);
+                render_pass.set_vertex_buffer(0, draw.vertex_buffer.slice(..));
+                render_pass
+                    .set_index_buffer(draw.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                // the shader reads the id from the instance index
+                render_pass.draw_indexed(0..draw.index_count, 0, id..id + 1);
+            }
+        }
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &self.id_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &self.readback_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(READBACK_SIZE as u32),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let id = self.read_id(device)?;
+
+        targets.into_iter().nth((id as usize).checked_sub(1)?)
+    }
+
+    fn read_id(&self, device: &wgpu::Device) -> Option<u32> {
+        let buffer_slice = self.readback_buffer.slice(..4);
+
+        let (tx, rx) = mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            tx.send(result).ok();
+        });
+        device.poll(wgpu::Maintain::Wait);
+
+        if let Err(e) = rx.recv().ok()? {
+            println!("Couldn't read picking buffer: {:?}", e);
+            return None;
+        }
+
+        let id = {
+            let data = buffer_slice.get_mapped_range();
+            u32::from_ne_bytes([data[0], data[1], data[2], data[3]])
+        };
+        self.readback_buffer.unmap();
+
+        Some(id)
+    }
+}
+
+fn create_targets(
+    device: &wgpu::Device,
+    window_size: &WindowSize,
+) -> (wgpu::Texture, wgpu::TextureView, wgpu::TextureView) {
+    let size = wgpu::Extent3d {
+        width: window_size.width.max(1),
+        height: window_size.height.max(1),
+        depth_or_array_layers: 1,
+    };
+
+    let id_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Picking Id Texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: ID_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let id_view = id_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Picking Depth Texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: MASK_DEPTH_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    (id_texture, id_view, depth_view)
+}
+
+/// The objects that can be clicked, as the editor's hit tests see them, with the draws
+/// for each. Depth keeps the topmost one, as in the preview.
+fn pickable_draws(editor: &Editor) -> (Vec<InteractionTarget>, Vec<(usize, PickDraw<'_>)>) {
+    let mut targets = Vec::new();
+    let mut draws = Vec::new();
+
+    for (index, polygon) in editor.scene.polygons.iter().enumerate() {
+        if polygon.hidden
+            || polygon.locked
+            || polygon.user_hidden
+            || polygon.mask_target_id.is_some()
+        {
+            continue;
+        }
+
+        draws.push((
+            targets.len(),
+            PickDraw {
+                bind_group: &polygon.bind_group,
+                group_bind_group: &polygon.group_bind_group,
+                vertex_buffer: &polygon.vertex_buffer,
+                index_buffer: &polygon.index_buffer,
+                index_count: polygon.indices.len() as u32,
+            },
+        ));
+        targets.push(InteractionTarget::Polygon(index));
+    }
+
+    for (index, text_item) in editor.scene.text_items.iter().enumerate() {
+        if text_item.hidden
+            || text_item.locked
+            || text_item.user_hidden
+            || text_item.mask_target_id.is_some()
+        {
+            continue;
+        }
+
+        let background = &text_item.background_polygon;
+        if !background.hidden {
+            draws.push((
+                targets.len(),
+                PickDraw {
+                    bind_group: &background.bind_group,
+                    group_bind_group: &background.group_bind_group,
+                    vertex_buffer: &background.vertex_buffer,
+                    index_buffer: &background.index_buffer,
+                    index_count: background.indices.len() as u32,
+                },
+            ));
+        }
+        draws.push((
+            targets.len(),
+            PickDraw {
+                bind_group: &text_item.bind_group,
+                group_bind_group: &text_item.group_bind_group,
+                vertex_buffer: &text_item.vertex_buffer,
+                index_buffer: &text_item.index_buffer,
+                index_count: text_item.indices.len() as u32,
+            },
+        ));
+        targets.push(InteractionTarget::Text(index));
+    }
+
+    for (index, image_item) in editor.scene.image_items.iter().enumerate() {
+        if image_item.hidden
+            || image_item.locked
+            || image_item.user_hidden
+            || image_item.mask_target_id.is_some()
+        {
+            continue;
+        }
+
+        draws.push((
+            targets.len(),
+            PickDraw {
+                bind_group: &image_item.bind_group,
+                group_bind_group: &image_item.group_bind_group,
+                vertex_buffer: &image_item.vertex_buffer,
+                index_buffer: &image_item.index_buffer,
+                index_count: image_item.indices.len() as u32,
+            },
+        ));
+        targets.push(InteractionTarget::Image(index));
+    }
+
+    for (index, video_item) in editor.scene.video_items.iter().enumerate() {
+        if video_item.hidden
+            || video_item.locked
+            || video_item.user_hidden
+            || video_item.mask_target_id.is_some()
+        {
+            continue;
+        }
+
+        draws.push((
+            targets.len(),
+            PickDraw {
+                bind_group: &video_item.bind_group,
+                group_bind_group: &video_item.group_bind_group,
+                vertex_buffer: &video_item.vertex_buffer,
+                index_buffer: &video_item.index_buffer,
+                index_count: video_item.indices.len() as u32,
+            },
+        ));
+        targets.push(InteractionTarget::Video(index));
+    }
+
+    for (index, emitter) in editor.scene.particle_emitters.iter().enumerate() {
+        if emitter.hidden || emitter.locked || emitter.user_hidden {
+            continue;
+        }
+
+        draws.push((
+            targets.len(),
+            PickDraw {
+                bind_group: &emitter.bind_group,
+                group_bind_group: &emitter.group_bind_group,
+                vertex_buffer: &emitter.vertex_buffer,
+                index_buffer: &emitter.index_buffer,
+                index_count: emitter.index_count,
+            },
+        ));
+        targets.push(InteractionTarget::ParticleEmitter(index));
+    }
+
+    (targets, draws)
+}
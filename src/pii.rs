@@ -0,0 +1,253 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+#[cfg(not(target_arch = "wasm32"))]
+use windows::Win32::Media::MediaFoundation::{MFShutdown, MFStartup, MFSTARTUP_FULL, MF_VERSION};
+#[cfg(not(target_arch = "wasm32"))]
+use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_MULTITHREADED};
+
+use crate::redaction::{RedactionKeyframe, RedactionRegion, RedactionStyle};
+use crate::st_video::StVideo;
+
+/// A piece of text found in a frame, with its bounds in frame pixels
+#[derive(Clone, PartialEq, Debug)]
+pub struct RecognizedText {
+    pub text: String,
+    pub rect: [i32; 4], // x, y, width, height
+}
+
+/// Reads text from frames. Implement this to plug in an OCR engine for PII scans.
+pub trait TextRecognizer: Send {
+    /// Text in a BGRA frame, ideally one entry per line or word
+    fn recognize(
+        &mut self,
+        frame: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<RecognizedText>, String>;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub enum PiiKind {
+    Email,
+    /// Card, account and phone numbers and the like
+    DigitSequence,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct PiiScanSettings {
+    pub sample_interval_ms: i32, // OCR is slow, so only some frames are read
+    pub min_digits: i32,         // digits in a run before it's flagged
+    pub padding: i32,            // pixels added around flagged text
+}
+
+impl Default for PiiScanSettings {
+    fn default() -> Self {
+        PiiScanSettings {
+            sample_interval_ms: 500,
+            min_digits: 8,
+            padding: 6,
+        }
+    }
+}
+
+/// Sensitive looking text, tracked over the time it stays on screen.
+/// The text itself isn't kept, so findings can be stored without leaking it.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct PiiFinding {
+    pub kind: PiiKind,
+    pub start_ms: i32,
+    pub end_ms: i32,
+    pub keyframes: Vec<RedactionKeyframe>, // where the text was each time it was seen
+}
+
+impl PiiFinding {
+    pub fn to_redaction(&self, style: RedactionStyle) -> RedactionRegion {
+        RedactionRegion {
+            keyframes: self.keyframes.clone(),
+            ..RedactionRegion::new([0, 0, 0, 0], self.start_ms, self.end_ms, style)
+        }
+    }
+
+    fn last_rect(&self) -> [i32; 4] {
+        self.keyframes.last().map(|k| k.rect).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PiiScanProgress {
+    Progress(f32), // percent of the video scanned
+    Complete(Vec<PiiFinding>),
+    Error(String),
+}
+
+/// Which kind of PII the text looks like, if any
+pub fn classify_text(text: &str, min_digits: usize) -> Option<PiiKind> {
+    if text.split_whitespace().any(looks_like_email) {
+        return Some(PiiKind::Email);
+    }
+
+    // digits separated by spaces, dashes or dots still count as one run, like 4111 1111 1111
+    let mut run = 0;
+    let mut longest_run = 0;
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            run += 1;
+            longest_run = longest_run.max(run);
+        } else if !matches!(c, ' ' | '-' | '.' | '(' | ')') {
+            run = 0;
+        }
+    }
+
+    if longest_run >= min_digits {
+        return Some(PiiKind::DigitSequence);
+    }
+
+    None
+}
+
+fn looks_like_email(word: &str) -> bool {
+    let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+    let Some((local, domain)) = word.split_once('@') else {
+        return false;
+    };
+    let Some((name, tld)) = domain.rsplit_once('.') else {
+        return false;
+    };
+
+    !local.is_empty()
+        && !name.is_empty()
+        && tld.len() >= 2
+        && tld.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+fn rects_overlap(a: [i32; 4], b: [i32; 4]) -> bool {
+    a[0] < b[0] + b[2] && b[0] < a[0] + a[2] && a[1] < b[1] + b[3] && b[1] < a[1] + a[3]
+}
+
+/// Reads the video's frames on this thread, sending the findings when done.
+/// Decodes the whole video, so run it off the main thread.
+pub fn scan_video(
+    path: String,
+    source_dimensions: (u32, u32),
+    source_duration_ms: i64,
+    source_frame_rate: f64,
+    mut recognizer: Box<dyn TextRecognizer>,
+    settings: PiiScanSettings,
+    progress_tx: UnboundedSender<PiiScanProgress>,
+) {
+    #[cfg(not(target_arch = "wasm32"))]
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        if let Err(e) = MFStartup(MF_VERSION, MFSTARTUP_FULL) {
+            progress_tx.send(PiiScanProgress::Error(e.to_string())).ok();
+            return;
+        }
+    }
+
+    let result = scan_frames(
+        &path,
+        source_dimensions,
+        source_duration_ms,
+        source_frame_rate,
+        recognizer.as_mut(),
+        &settings,
+        &progress_tx,
+    );
+
+    #[cfg(not(target_arch = "wasm32"))]
+    unsafe {
+        MFShutdown().ok();
+        CoUninitialize();
+    }
+
+    match result {
+        Ok(findings) => {
+            progress_tx.send(PiiScanProgress::Complete(findings)).ok();
+        }
+        Err(e) => {
+            progress_tx.send(PiiScanProgress::Error(e)).ok();
+        }
+    }
+}
+
+fn scan_frames(
+    path: &str,
+    source_dimensions: (u32, u32),
+    source_duration_ms: i64,
+    source_frame_rate: f64,
+    recognizer: &mut dyn TextRecognizer,
+    settings: &PiiScanSettings,
+    progress_tx: &UnboundedSender<PiiScanProgress>,
+) -> Result<Vec<PiiFinding>, String> {
+    let source_reader =
+        StVideo::create_source_reader(Path::new(path).to_str().ok_or("Couldn't get path string")?)
+            .map_err(|e| e.to_string())?;
+
+    let sample_interval_ms = settings.sample_interval_ms.max(1);
+    let mut findings: Vec<PiiFinding> = Vec::new();
+    let mut open_findings: Vec<usize> = Vec::new(); // seen in the last sample
+    let mut next_sample_ms = 0;
+    let mut frame_index = 0;
+
+    while let Some(frame_data) =
+        StVideo::read_next_frame(&source_reader).map_err(|e| e.to_string())?
+    {
+        let time_ms = (frame_index as f64 / source_frame_rate * 1000.0) as i32;
+        frame_index += 1;
+
+        if time_ms < next_sample_ms {
+            continue;
+        }
+        next_sample_ms = time_ms + sample_interval_ms;
+
+        let texts = recognizer.recognize(&frame_data, source_dimensions.0, source_dimensions.1)?;
+
+        let mut still_open = Vec::new();
+        for text in texts {
+            let Some(kind) = classify_text(&text.text, settings.min_digits.max(1) as usize) else {
+                continue;
+            };
+
+            let rect = [
+                text.rect[0] - settings.padding,
+                text.rect[1] - settings.padding,
+                text.rect[2] + settings.padding * 2,
+                text.rect[3] + settings.padding * 2,
+            ];
+
+            // continue a finding from the last sample if this is the same text, maybe scrolled a little
+            let continued = open_findings.iter().copied().find(|i| {
+                !still_open.contains(i)
+                    && findings[*i].kind == kind
+                    && rects_overlap(findings[*i].last_rect(), rect)
+            });
+
+            match continued {
+                Some(i) => {
+                    findings[i].end_ms = time_ms + sample_interval_ms;
+                    findings[i]
+                        .keyframes
+                        .push(RedactionKeyframe { time_ms, rect });
+                    still_open.push(i);
+                }
+                None => {
+                    findings.push(PiiFinding {
+                        kind,
+                        start_ms: time_ms,
+                        end_ms: time_ms + sample_interval_ms,
+                        keyframes: vec![RedactionKeyframe { time_ms, rect }],
+                    });
+                    still_open.push(findings.len() - 1);
+                }
+            }
+        }
+        open_findings = still_open;
+
+        let progress = (time_ms as f32 / source_duration_ms.max(1) as f32 * 100.0).min(100.0);
+        progress_tx.send(PiiScanProgress::Progress(progress)).ok();
+    }
+
+    Ok(findings)
+}
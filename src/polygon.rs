@@ -7,8 +7,12 @@ use uuid::Uuid;
 use wgpu::util::DeviceExt;
 
 use crate::{
+    blend::BlendMode,
     camera::{Camera3D as Camera},
     editor::{BoundingBox, Point, Shape, WindowSize},
+    masking::MaskMode,
+    outline::SavedOutline,
+    tags::ColorTag,
     transform::{
         create_empty_group_transform, matrix4_to_raw_array, Transform as SnTransform,
     },
@@ -126,6 +130,7 @@ pub fn get_polygon_data(
     bind_group_layout: &wgpu::BindGroupLayout,
     camera: &Camera,
     points: Vec<Point>,
+    contours: &[Vec<Point>],
     dimensions: (f32, f32),
     position: Point,
     rotation: f32,
@@ -144,48 +149,7 @@ pub fn get_polygon_data(
 ) {
     // println!("Get polygon data: {:?}", fill);
 
-    let mut geometry: VertexBuffers<Vertex, u32> = VertexBuffers::new();
-    let mut fill_tessellator = FillTessellator::new();
-    let mut stroke_tessellator = StrokeTessellator::new();
-
-    let path = create_rounded_polygon_path(points, dimensions, border_radius);
-
-    // Fill the polygon
-    fill_tessellator
-        .tessellate_path(
-            &path,
-            &FillOptions::default(),
-            &mut BuffersBuilder::new(&mut geometry, |vertex: FillVertex| {
-                // let x = ((vertex.position().x) / window_size.width as f32) * 2.0 - 1.0;
-                // let y = 1.0 - ((vertex.position().y) / window_size.height as f32) * 2.0;
-                let x = vertex.position().x;
-                let y = vertex.position().y;
-
-                // Vertex::new(x, y, get_z_layer(base_layer + 2.0), fill)
-                Vertex::new(x, y, 0.0, fill)
-            }),
-        )
-        .unwrap();
-
-    // Stroke the polygon (optional, for a border effect)
-    if stroke.thickness > 0.0 {
-        stroke_tessellator
-            .tessellate_path(
-                &path,
-                &StrokeOptions::default().with_line_width(stroke.thickness),
-                &mut BuffersBuilder::new(&mut geometry, |vertex: StrokeVertex| {
-                    // let x = ((vertex.position().x) / window_size.width as f32) * 2.0 - 1.0;
-                    // let y = 1.0 - ((vertex.position().y) / window_size.height as f32) * 2.0;
-                    let x = vertex.position().x;
-                    let y = vertex.position().y;
-
-                    // Vertex::new(x, y, get_z_layer(base_layer + 3.0), stroke.fill)
-                    Vertex::new(x, y, 0.0 + 0.001, stroke.fill)
-                    // Black border
-                }),
-            )
-            .unwrap();
-    }
+    let geometry = tessellate_polygon(points, contours, dimensions, border_radius, fill, stroke);
 
     let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some("Vertex Buffer"),
@@ -229,6 +193,93 @@ pub fn get_polygon_data(
     //     label: None,
     // });
 
+    let bind_group = create_default_bind_group(device, queue, bind_group_layout, &uniform_buffer);
+
+    let mut transform = SnTransform::new(
+        Vector2::new(position.x, position.y),
+        rotation,
+        Vector2::new(1.0, 1.0),
+        uniform_buffer,
+        window_size,
+    );
+
+    // -10.0 to provide 10 spots for internal items on top of objects
+    // transform.layer = transform_layer as f32 - 0 as f32; // important?
+    transform.layer = transform_layer as f32;
+    transform.update_uniform_buffer(&queue, &camera.window_size);
+
+    (
+        geometry.vertices,
+        geometry.indices,
+        vertex_buffer,
+        index_buffer,
+        bind_group,
+        transform,
+    )
+}
+
+/// Fill vertices first, then stroke vertices just above them
+pub fn tessellate_polygon(
+    points: Vec<Point>,
+    contours: &[Vec<Point>],
+    dimensions: (f32, f32),
+    border_radius: f32,
+    fill: [f32; 4],
+    stroke: Stroke,
+) -> VertexBuffers<Vertex, u32> {
+    let mut geometry: VertexBuffers<Vertex, u32> = VertexBuffers::new();
+    let mut fill_tessellator = FillTessellator::new();
+    let mut stroke_tessellator = StrokeTessellator::new();
+
+    let path = create_rounded_polygon_path(points, contours, dimensions, border_radius);
+
+    // Fill the polygon
+    fill_tessellator
+        .tessellate_path(
+            &path,
+            &FillOptions::default(),
+            &mut BuffersBuilder::new(&mut geometry, |vertex: FillVertex| {
+                // let x = ((vertex.position().x) / window_size.width as f32) * 2.0 - 1.0;
+                // let y = 1.0 - ((vertex.position().y) / window_size.height as f32) * 2.0;
+                let x = vertex.position().x;
+                let y = vertex.position().y;
+
+                // Vertex::new(x, y, get_z_layer(base_layer + 2.0), fill)
+                Vertex::new(x, y, 0.0, fill)
+            }),
+        )
+        .unwrap();
+
+    // Stroke the polygon (optional, for a border effect)
+    if stroke.thickness > 0.0 {
+        stroke_tessellator
+            .tessellate_path(
+                &path,
+                &StrokeOptions::default().with_line_width(stroke.thickness),
+                &mut BuffersBuilder::new(&mut geometry, |vertex: StrokeVertex| {
+                    // let x = ((vertex.position().x) / window_size.width as f32) * 2.0 - 1.0;
+                    // let y = 1.0 - ((vertex.position().y) / window_size.height as f32) * 2.0;
+                    let x = vertex.position().x;
+                    let y = vertex.position().y;
+
+                    // Vertex::new(x, y, get_z_layer(base_layer + 3.0), stroke.fill)
+                    Vertex::new(x, y, 0.0 + 0.001, stroke.fill)
+                    // Black border
+                }),
+            )
+            .unwrap();
+    }
+
+    geometry
+}
+
+/// Model bind group for untextured objects, sampling a 1x1 white texture
+pub fn create_default_bind_group(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    bind_group_layout: &wgpu::BindGroupLayout,
+    uniform_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
     // Create a 1x1 white texture as a default
     let texture_size = wgpu::Extent3d {
         width: 1,
@@ -281,7 +332,7 @@ pub fn get_polygon_data(
     });
 
     // Now create your bind group with these defaults
-    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
         layout: &bind_group_layout,
         entries: &[
             wgpu::BindGroupEntry {
@@ -298,29 +349,7 @@ pub fn get_polygon_data(
             },
         ],
         label: None,
-    });
-
-    let mut transform = SnTransform::new(
-        Vector2::new(position.x, position.y),
-        rotation,
-        Vector2::new(1.0, 1.0),
-        uniform_buffer,
-        window_size,
-    );
-
-    // -10.0 to provide 10 spots for internal items on top of objects
-    // transform.layer = transform_layer as f32 - 0 as f32; // important?
-    transform.layer = transform_layer as f32;
-    transform.update_uniform_buffer(&queue, &camera.window_size);
-
-    (
-        geometry.vertices,
-        geometry.indices,
-        vertex_buffer,
-        index_buffer,
-        bind_group,
-        transform,
-    )
+    })
 }
 
 use lyon_tessellation::math::point;
@@ -328,10 +357,30 @@ use lyon_tessellation::math::Vector;
 
 fn create_rounded_polygon_path(
     normalized_points: Vec<Point>,
+    contours: &[Vec<Point>],
     dimensions: (f32, f32),
     border_radius: f32,
 ) -> LyonPath {
     let mut builder = LyonPath::builder();
+
+    add_rounded_subpath(&mut builder, &normalized_points, dimensions, border_radius);
+
+    // extra contours, such as glyph counters, are filled even-odd so inner ones become holes
+    for contour in contours {
+        if contour.len() > 2 {
+            add_rounded_subpath(&mut builder, contour, dimensions, border_radius);
+        }
+    }
+
+    builder.build()
+}
+
+fn add_rounded_subpath(
+    builder: &mut lyon_tessellation::path::path::Builder,
+    normalized_points: &[Point],
+    dimensions: (f32, f32),
+    border_radius: f32,
+) {
     let n = normalized_points.len();
 
     // Scale border radius to match dimensions
@@ -392,7 +441,6 @@ fn create_rounded_polygon_path(
     }
 
     builder.close();
-    builder.build()
 }
 
 use cgmath::SquareMatrix;
@@ -440,6 +488,7 @@ impl Polygon {
                 bind_group_layout,
                 camera,
                 points.clone(),
+                &[],
                 dimensions,
                 position,
                 rotation,
@@ -466,6 +515,7 @@ impl Polygon {
             source_path_id: None,
             name,
             points,
+            contours: Vec::new(),
             old_points: None,
             dimensions,
             transform,
@@ -478,6 +528,13 @@ impl Polygon {
             index_buffer,
             bind_group,
             hidden: false,
+            user_hidden: false,
+            locked: false,
+            base_opacity: 1.0,
+            animated_opacity: 1.0,
+            blend_mode: BlendMode::Normal,
+            mask_target_id: None,
+            mask_mode: MaskMode::Clip,
             layer: transform_layer,
             group_bind_group: tmp_group_bind_group,
             active_group_position: [0, 0],
@@ -485,7 +542,14 @@ impl Polygon {
     }
 
     pub fn update_opacity(&mut self, queue: &wgpu::Queue, opacity: f32) {
-        let new_color = [self.fill[0], self.fill[1], self.fill[2], opacity];
+        self.animated_opacity = opacity;
+
+        let new_color = [
+            self.fill[0],
+            self.fill[1],
+            self.fill[2],
+            opacity * self.base_opacity,
+        ];
 
         self.vertices.iter_mut().for_each(|v| {
             v.color = new_color;
@@ -494,6 +558,46 @@ impl Polygon {
         queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&self.vertices));
     }
 
+    /// Sets the static opacity, keeping the current animated opacity
+    pub fn update_base_opacity(&mut self, queue: &wgpu::Queue, base_opacity: f32) {
+        self.base_opacity = base_opacity;
+        self.update_opacity(queue, self.animated_opacity);
+    }
+
+    /// Recolors the fill and stroke for color keyframes, writing into the existing vertex
+    /// buffer as the shape doesn't change. Keeps the current opacity.
+    pub fn update_colors(&mut self, queue: &wgpu::Queue, fill: [f32; 4], stroke_fill: [f32; 4]) {
+        let stroke = Stroke {
+            fill: stroke_fill,
+            ..self.stroke
+        };
+        let mut geometry = tessellate_polygon(
+            self.points.clone(),
+            &self.contours,
+            self.dimensions,
+            self.border_radius,
+            fill,
+            stroke,
+        );
+        if geometry.vertices.len() != self.vertices.len() {
+            return;
+        }
+
+        let opacity = self.animated_opacity * self.base_opacity;
+        for vertex in geometry.vertices.iter_mut() {
+            vertex.color[3] *= opacity;
+        }
+        queue.write_buffer(
+            &self.vertex_buffer,
+            0,
+            bytemuck::cast_slice(&geometry.vertices),
+        );
+
+        self.fill = fill;
+        self.stroke = stroke;
+        self.vertices = geometry.vertices;
+    }
+
     pub fn update_layer(&mut self, layer_index: i32) {
         // -10.0 to provide 10 spots for internal items on top of objects
         // let layer_index = layer_index - 0;
@@ -562,6 +666,7 @@ impl Polygon {
                 bind_group_layout,
                 camera,
                 self.points.clone(),
+                &self.contours,
                 dimensions,
                 Point {
                     x: self.transform.position.x,
@@ -585,6 +690,40 @@ impl Polygon {
         self.transform = transform;
     }
 
+    /// Like `update_data_from_dimensions`, but writes into the existing vertex buffer.
+    /// Returns false, changing nothing, if the new geometry has a different layout.
+    pub fn update_dimensions_in_place(
+        &mut self,
+        queue: &wgpu::Queue,
+        dimensions: (f32, f32),
+    ) -> bool {
+        let mut geometry = tessellate_polygon(
+            self.points.clone(),
+            &self.contours,
+            dimensions,
+            self.border_radius,
+            self.fill,
+            self.stroke,
+        );
+        if geometry.vertices.len() != self.vertices.len() || geometry.indices != self.indices {
+            return false;
+        }
+
+        // keep the current colors, which may include opacity
+        for (vertex, old) in geometry.vertices.iter_mut().zip(self.vertices.iter()) {
+            vertex.color = old.color;
+        }
+        queue.write_buffer(
+            &self.vertex_buffer,
+            0,
+            bytemuck::cast_slice(&geometry.vertices),
+        );
+
+        self.dimensions = dimensions;
+        self.vertices = geometry.vertices;
+        true
+    }
+
     pub fn update_data_from_position(
         &mut self,
         window_size: &WindowSize,
@@ -614,6 +753,7 @@ impl Polygon {
                 bind_group_layout,
                 camera,
                 self.points.clone(),
+                &self.contours,
                 self.dimensions,
                 Point {
                     x: self.transform.position.x,
@@ -656,6 +796,7 @@ impl Polygon {
                 bind_group_layout,
                 camera,
                 self.points.clone(),
+                &self.contours,
                 self.dimensions,
                 Point {
                     x: self.transform.position.x,
@@ -679,6 +820,47 @@ impl Polygon {
         self.transform = transform;
     }
 
+    pub fn update_data_from_contours(
+        &mut self,
+        window_size: &WindowSize,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        points: Vec<Point>,
+        contours: Vec<Vec<Point>>,
+        camera: &Camera,
+    ) {
+        let (vertices, indices, vertex_buffer, index_buffer, bind_group, transform) =
+            get_polygon_data(
+                window_size,
+                device,
+                queue,
+                bind_group_layout,
+                camera,
+                points.clone(),
+                &contours,
+                self.dimensions,
+                Point {
+                    x: self.transform.position.x,
+                    y: self.transform.position.y,
+                },
+                self.transform.rotation,
+                self.border_radius,
+                self.fill,
+                self.stroke,
+                self.layer,
+            );
+
+        self.points = points;
+        self.contours = contours;
+        self.vertices = vertices;
+        self.indices = indices;
+        self.vertex_buffer = vertex_buffer;
+        self.index_buffer = index_buffer;
+        self.bind_group = bind_group;
+        self.transform = transform;
+    }
+
     pub fn update_data_from_fill(
         &mut self,
         window_size: &WindowSize,
@@ -698,6 +880,7 @@ impl Polygon {
                 bind_group_layout,
                 camera,
                 self.points.clone(),
+                &self.contours,
                 self.dimensions,
                 Point {
                     x: self.transform.position.x,
@@ -827,6 +1010,8 @@ pub struct Polygon {
     pub source_path_id: Option<Uuid>,
     pub name: String,
     pub points: Vec<Point>,
+    /// Additional normalized subpaths, such as the holes and dots of outlined glyphs
+    pub contours: Vec<Vec<Point>>,
     pub old_points: Option<Vec<Point>>,
     pub dimensions: (f32, f32), // (width, height) in pixels
     pub fill: [f32; 4],
@@ -839,6 +1024,13 @@ pub struct Polygon {
     pub index_buffer: wgpu::Buffer,
     pub bind_group: wgpu::BindGroup,
     pub hidden: bool,
+    pub user_hidden: bool, // hidden from the layer panel, separate from sequence visibility
+    pub locked: bool,
+    pub base_opacity: f32,     // static opacity, multiplied with animated opacity
+    pub animated_opacity: f32, // last opacity from keyframes
+    pub blend_mode: BlendMode,
+    pub mask_target_id: Option<String>,
+    pub mask_mode: MaskMode,
     pub layer: i32,
     pub group_bind_group: wgpu::BindGroup,
     pub active_group_position: [i32; 2],
@@ -871,6 +1063,11 @@ pub struct SavedPoint {
     pub y: i32,
 }
 
+/// Full opacity for objects saved before opacity was a static property
+pub fn default_opacity() -> i32 {
+    100
+}
+
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
 pub struct SavedStroke {
     pub thickness: i32,
@@ -888,4 +1085,20 @@ pub struct SavedPolygonConfig {
     pub border_radius: i32,
     pub stroke: SavedStroke,
     pub layer: i32,
+    #[serde(default)]
+    pub tags: Vec<ColorTag>,
+    #[serde(default)]
+    pub outline: Option<SavedOutline>,
+    #[serde(default)]
+    pub locked: bool,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default = "default_opacity")]
+    pub opacity: i32, // out of 100, like opacity keyframes
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+    #[serde(default)]
+    pub mask_target_id: Option<String>, // makes this a mask for the object with this id
+    #[serde(default)]
+    pub mask_mode: MaskMode,
 }
@@ -0,0 +1,427 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::animations::{BackgroundFill, Sequence};
+use crate::blend::{pipeline_for_mode, BlendMode};
+use crate::camera::Camera3D as Camera;
+use crate::editor::{rgb_to_wgpu, Viewport};
+use crate::error::{parse_id, EditorError};
+use crate::gpu_resources::GpuResources;
+use crate::masking::{begin_masked_draw, MaskPipelines};
+use crate::polygon::Polygon;
+use crate::render_order::{draw_order, DrawTarget};
+use crate::saved_state::SavedState;
+use crate::scene::Scene;
+use crate::timelines::{set_transition_scissor, TrackType};
+
+/// Read-only player for embedding a project preview in other apps.
+/// Only play, pause, seek and render are exposed. It plays a Scene without an Editor, so
+/// there's no capture, selection, resize handles, motion paths or guides, and nothing is
+/// written back to disk.
+pub struct PreviewPlayer {
+    scene: Scene,
+    viewport: Arc<Mutex<Viewport>>,
+    camera: Camera, // the host's, the scene's own camera follows camera keyframes
+    pub total_duration_s: f64,
+    pub current_time_s: f64,
+    pub is_playing: bool,
+    play_started_at: Option<Instant>,
+    play_started_from_s: f64,
+}
+
+impl PreviewPlayer {
+    pub fn new(
+        viewport: Arc<Mutex<Viewport>>,
+        gpu_resources: Arc<GpuResources>,
+        model_bind_group_layout: Arc<wgpu::BindGroupLayout>,
+        group_bind_group_layout: Arc<wgpu::BindGroupLayout>,
+        camera: Camera,
+        saved_state: SavedState,
+    ) -> Result<Self, EditorError> {
+        let mut scene = Scene::new();
+
+        scene.gpu_resources = Some(gpu_resources);
+        scene.model_bind_group_layout = Some(model_bind_group_layout);
+        scene.group_bind_group_layout = Some(group_bind_group_layout);
+        scene.camera = Some(camera);
+
+        // play sequences in timeline order, as export does
+        let timeline = saved_state.timeline_state.clone();
+        let video_sequences: Vec<Sequence> = timeline
+            .timeline_sequences
+            .iter()
+            .filter(|ts| ts.track_type == TrackType::Video)
+            .filter_map(|ts| {
+                saved_state
+                    .sequences
+                    .iter()
+                    .find(|s| s.id == ts.sequence_id)
+                    .cloned()
+            })
+            .collect();
+
+        let total_duration_ms = timeline
+            .timeline_sequences
+            .iter()
+            .filter(|ts| ts.track_type == TrackType::Video)
+            .map(|ts| {
+                let duration_ms = video_sequences
+                    .iter()
+                    .find(|s| s.id == ts.sequence_id)
+                    .map(|s| s.duration_ms)
+                    .unwrap_or(0);
+                ts.start_time_ms + duration_ms
+            })
+            .max()
+            .unwrap_or(0);
+
+        // restore objects, only the first sequence starts visible
+        for (i, s) in video_sequences.iter().enumerate() {
+            scene.restore_sequence_objects(&s, if i == 0 { false } else { true })?;
+        }
+
+        if let Some(first_sequence) = video_sequences.first() {
+            if let Some(BackgroundFill::Color(fill)) = &first_sequence.background_fill {
+                scene.replace_background(
+                    parse_id(&first_sequence.id)?,
+                    rgb_to_wgpu(fill[0] as u8, fill[1] as u8, fill[2] as u8, fill[3] as f32),
+                )?;
+            }
+            scene.current_sequence_data = Some(first_sequence.clone());
+        }
+
+        let now = Instant::now();
+        scene.video_start_playing_time = Some(now);
+        scene.start_playing_time = Some(now);
+        scene.video_current_sequence_timeline = Some(timeline);
+        scene.video_current_sequences_data = Some(video_sequences);
+
+        // time is always provided by the player, these just enable stepping
+        scene.video_is_playing = true;
+        scene.is_playing = true;
+
+        Ok(PreviewPlayer {
+            scene,
+            viewport,
+            camera,
+            total_duration_s: total_duration_ms as f64 / 1000.0,
+            current_time_s: 0.0,
+            is_playing: false,
+            play_started_at: None,
+            play_started_from_s: 0.0,
+        })
+    }
+
+    pub fn play(&mut self) -> Result<(), EditorError> {
+        if self.is_playing {
+            return Ok(());
+        }
+
+        if self.current_time_s >= self.total_duration_s {
+            self.seek(0.0)?;
+        }
+
+        self.play_started_at = Some(Instant::now());
+        self.play_started_from_s = self.current_time_s;
+        self.is_playing = true;
+
+        Ok(())
+    }
+
+    pub fn pause(&mut self) {
+        self.advance_time();
+        self.is_playing = false;
+        self.play_started_at = None;
+    }
+
+    pub fn seek(&mut self, time_s: f64) -> Result<(), EditorError> {
+        let time_s = time_s.clamp(0.0, self.total_duration_s);
+
+        // video decoders only read forward, so rewind them and let catch-up redraw
+        if time_s < self.current_time_s {
+            for video in self.scene.video_items.iter_mut() {
+                video
+                    .reset_playback()
+                    .map_err(|e| EditorError::Media {
+                        path: video.path.clone(),
+                        reason: e.to_string(),
+                    })?;
+                video.num_frames_drawn = 0;
+            }
+        }
+
+        self.current_time_s = time_s;
+
+        if self.is_playing {
+            self.play_started_at = Some(Instant::now());
+            self.play_started_from_s = time_s;
+        }
+
+        Ok(())
+    }
+
+    fn advance_time(&mut self) {
+        if !self.is_playing {
+            return;
+        }
+
+        if let Some(play_started_at) = self.play_started_at {
+            let elapsed = play_started_at.elapsed().as_secs_f64();
+            self.current_time_s = self.play_started_from_s + elapsed;
+        }
+
+        // stop at the end rather than looping
+        if self.current_time_s >= self.total_duration_s {
+            self.current_time_s = self.total_duration_s;
+            self.is_playing = false;
+            self.play_started_at = None;
+        }
+    }
+
+    /// Advances the playhead and animates objects, call once per frame before draw
+    pub fn step(&mut self) -> Result<(), EditorError> {
+        self.advance_time();
+
+        let camera = self.camera;
+        self.scene
+            .step_video_animations(&camera, Some(self.current_time_s))?;
+        self.scene
+            .step_motion_path_animations(&camera, Some(self.current_time_s))?;
+        self.scene.step_sequence_transition();
+
+        let queue = &self
+            .scene
+            .gpu_resources
+            .as_ref()
+            .ok_or(EditorError::NotInitialized("gpu resources"))?
+            .queue;
+
+        for polygon in self.scene.static_polygons.iter() {
+            polygon
+                .transform
+                .update_uniform_buffer(queue, &camera.window_size);
+        }
+        for polygon in self.scene.polygons.iter().filter(|p| !p.hidden && !p.user_hidden) {
+            polygon
+                .transform
+                .update_uniform_buffer(queue, &camera.window_size);
+        }
+        for text_item in self.scene.text_items.iter().filter(|t| !t.hidden && !t.user_hidden) {
+            text_item
+                .background_polygon
+                .transform
+                .update_uniform_buffer(queue, &camera.window_size);
+            text_item
+                .transform
+                .update_uniform_buffer(queue, &camera.window_size);
+        }
+        for st_image in self.scene.image_items.iter().filter(|i| !i.hidden && !i.user_hidden) {
+            st_image
+                .transform
+                .update_uniform_buffer(queue, &camera.window_size);
+        }
+        for st_video in self.scene.video_items.iter().filter(|v| !v.hidden && !v.user_hidden) {
+            st_video
+                .transform
+                .update_uniform_buffer(queue, &camera.window_size);
+        }
+        for emitter in self.scene.particle_emitters.iter().filter(|e| !e.hidden && !e.user_hidden) {
+            emitter
+                .transform
+                .update_uniform_buffer(queue, &camera.window_size);
+        }
+
+        Ok(())
+    }
+
+    /// The camera to bind for drawing, moved by the sequence's camera keyframes during playback
+    pub fn current_camera(&self) -> Camera {
+        self.scene.camera.unwrap_or(self.camera)
+    }
+
+    /// Draws the current frame. The caller sets the pipeline along with
+    /// the camera (0) and window size (2) bind groups, as for the editor.
+    /// Every object is drawn with normal blending.
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        self.draw_objects(render_pass, None, None);
+    }
+
+    /// Draws the current frame, switching to each object's blend mode pipeline,
+    /// as created by `create_blend_pipelines`
+    pub fn draw_with_blend_modes<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        render_pipeline: &'a wgpu::RenderPipeline,
+        blend_pipelines: &'a HashMap<BlendMode, wgpu::RenderPipeline>,
+    ) {
+        self.draw_objects(render_pass, Some((render_pipeline, blend_pipelines)), None);
+    }
+
+    /// Draws the current frame with blend modes and masks, as created by `create_mask_pipelines`.
+    /// The depth attachment must be `MASK_DEPTH_FORMAT`, with the stencil cleared to 0.
+    pub fn draw_with_masks<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        render_pipeline: &'a wgpu::RenderPipeline,
+        blend_pipelines: &'a HashMap<BlendMode, wgpu::RenderPipeline>,
+        mask_pipelines: &'a MaskPipelines,
+    ) {
+        self.draw_objects(
+            render_pass,
+            Some((render_pipeline, blend_pipelines)),
+            Some(mask_pipelines),
+        );
+    }
+
+    fn draw_objects<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        pipelines: Option<(&'a wgpu::RenderPipeline, &'a HashMap<BlendMode, wgpu::RenderPipeline>)>,
+        mask_pipelines: Option<&'a MaskPipelines>,
+    ) {
+        let mut stencil_reference = 0;
+        // wipes clip each sequence's objects to their side of the viewport
+        let (target_width, target_height) = {
+            let viewport = self.viewport.lock().unwrap();
+            (viewport.width as u32, viewport.height as u32)
+        };
+        let transition = self.scene.active_transition.as_ref();
+        let mut prepare_object =
+            |render_pass: &mut wgpu::RenderPass<'a>,
+             mode: BlendMode,
+             object_id: String,
+             sequence_id: String| {
+                set_transition_scissor(
+                    render_pass,
+                    transition,
+                    &sequence_id,
+                    target_width,
+                    target_height,
+                );
+                if let Some((render_pipeline, blend_pipelines)) = pipelines {
+                    render_pass.set_pipeline(pipeline_for_mode(render_pipeline, blend_pipelines, mode));
+                }
+                if let Some(mask_pipelines) = mask_pipelines {
+                    begin_masked_draw(
+                        render_pass,
+                        &self.scene,
+                        &object_id,
+                        mask_pipelines,
+                        mode,
+                        &mut stencil_reference,
+                    );
+                }
+            };
+
+        for polygon in self.scene.static_polygons.iter() {
+            draw_polygon(render_pass, polygon);
+        }
+
+        // masks are only drawn into the stencil, along with their target, and transparent
+        // objects are drawn back to front
+        for target in draw_order(&self.scene, &self.current_camera()) {
+            match target {
+                DrawTarget::Polygon(index) => {
+                    let polygon = &self.scene.polygons[index];
+                    prepare_object(
+                        render_pass,
+                        polygon.blend_mode,
+                        polygon.id.to_string(),
+                        polygon.current_sequence_id.to_string(),
+                    );
+                    draw_polygon(render_pass, polygon);
+                }
+                DrawTarget::TextBackground(index) => {
+                    let text_item = &self.scene.text_items[index];
+                    prepare_object(
+                        render_pass,
+                        text_item.blend_mode,
+                        text_item.id.to_string(),
+                        text_item.current_sequence_id.to_string(),
+                    );
+                    draw_polygon(render_pass, &text_item.background_polygon);
+                }
+                DrawTarget::Text(index) => {
+                    let text_item = &self.scene.text_items[index];
+                    prepare_object(
+                        render_pass,
+                        text_item.blend_mode,
+                        text_item.id.to_string(),
+                        text_item.current_sequence_id.to_string(),
+                    );
+                    render_pass.set_bind_group(1, &text_item.bind_group, &[]);
+                    render_pass.set_bind_group(3, &text_item.group_bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, text_item.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(
+                        text_item.index_buffer.slice(..),
+                        wgpu::IndexFormat::Uint32,
+                    );
+                    render_pass.draw_indexed(0..text_item.indices.len() as u32, 0, 0..1);
+                }
+                DrawTarget::Image(index) => {
+                    let st_image = &self.scene.image_items[index];
+                    prepare_object(
+                        render_pass,
+                        st_image.blend_mode,
+                        st_image.id.clone(),
+                        st_image.current_sequence_id.to_string(),
+                    );
+                    render_pass.set_bind_group(1, &st_image.bind_group, &[]);
+                    render_pass.set_bind_group(3, &st_image.group_bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, st_image.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(
+                        st_image.index_buffer.slice(..),
+                        wgpu::IndexFormat::Uint32,
+                    );
+                    render_pass.draw_indexed(0..st_image.indices.len() as u32, 0, 0..1);
+                }
+                DrawTarget::Video(index) => {
+                    let st_video = &self.scene.video_items[index];
+                    prepare_object(
+                        render_pass,
+                        st_video.blend_mode,
+                        st_video.id.clone(),
+                        st_video.current_sequence_id.to_string(),
+                    );
+                    render_pass.set_bind_group(1, &st_video.bind_group, &[]);
+                    render_pass.set_bind_group(3, &st_video.group_bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, st_video.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(
+                        st_video.index_buffer.slice(..),
+                        wgpu::IndexFormat::Uint32,
+                    );
+                    render_pass.draw_indexed(0..st_video.indices.len() as u32, 0, 0..1);
+                }
+                DrawTarget::ParticleEmitter(index) => {
+                    let emitter = &self.scene.particle_emitters[index];
+                    if let Some((render_pipeline, _)) = pipelines {
+                        render_pass.set_pipeline(render_pipeline);
+                    }
+                    set_transition_scissor(
+                        render_pass,
+                        transition,
+                        &emitter.current_sequence_id.to_string(),
+                        target_width,
+                        target_height,
+                    );
+
+                    render_pass.set_bind_group(1, &emitter.bind_group, &[]);
+                    render_pass.set_bind_group(3, &emitter.group_bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, emitter.vertex_buffer.slice(..));
+                    render_pass
+                        .set_index_buffer(emitter.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                    render_pass.draw_indexed(0..emitter.index_count, 0, 0..1);
+                }
+            }
+        }
+    }
+}
+
+fn draw_polygon<'a>(render_pass: &mut wgpu::RenderPass<'a>, polygon: &'a Polygon) {
+    render_pass.set_bind_group(1, &polygon.bind_group, &[]);
+    render_pass.set_bind_group(3, &polygon.group_bind_group, &[]);
+    render_pass.set_vertex_buffer(0, polygon.vertex_buffer.slice(..));
+    render_pass.set_index_buffer(polygon.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+    render_pass.draw_indexed(0..polygon.indices.len() as u32, 0, 0..1);
+}
@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum RedactionStyle {
+    #[default]
+    Pixelate,
+    Blur,
+}
+
+/// Where a redacted region is at a point in the video, in source video pixels
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct RedactionKeyframe {
+    pub time_ms: i32, // from the start of the video, so regions follow the footage if it's moved
+    pub rect: [i32; 4], // x, y, width, height
+}
+
+/// A rectangle hidden on a video item for privacy, such as a face or an email address.
+/// Redactions are applied to decoded frames, so they're in every preview and export.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct RedactionRegion {
+    pub id: String,
+    pub style: RedactionStyle,
+    pub strength: i32, // pixel block size or blur radius
+    pub start_ms: i32,
+    pub end_ms: i32,
+    /// Moves the region to track what it hides, linearly between keyframes
+    pub keyframes: Vec<RedactionKeyframe>,
+}
+
+impl RedactionRegion {
+    pub fn new(rect: [i32; 4], start_ms: i32, end_ms: i32, style: RedactionStyle) -> Self {
+        RedactionRegion {
+            id: Uuid::new_v4().to_string(),
+            style,
+            strength: 16,
+            start_ms,
+            end_ms,
+            keyframes: vec![RedactionKeyframe {
+                time_ms: start_ms,
+                rect,
+            }],
+        }
+    }
+
+    /// The region's rectangle at a time, or None outside of its time range
+    pub fn rect_at(&self, time_ms: i32) -> Option<[i32; 4]> {
+        if time_ms < self.start_ms || time_ms > self.end_ms {
+            return None;
+        }
+
+        let mut keyframes: Vec<&RedactionKeyframe> = self.keyframes.iter().collect();
+        keyframes.sort_by_key(|k| k.time_ms);
+
+        let next_index = keyframes.iter().position(|k| k.time_ms > time_ms);
+        let (start, end) = match next_index {
+            Some(0) => (keyframes[0], keyframes[0]),
+            Some(index) => (keyframes[index - 1], keyframes[index]),
+            None => {
+                let last = keyframes.last()?;
+                (*last, *last)
+            }
+        };
+
+        if start.time_ms == end.time_ms {
+            return Some(start.rect);
+        }
+
+        let progress = (time_ms - start.time_ms) as f32 / (end.time_ms - start.time_ms) as f32;
+        let mut rect = [0; 4];
+        for i in 0..4 {
+            rect[i] = (start.rect[i] as f32 + (end.rect[i] - start.rect[i]) as f32 * progress)
+                .round() as i32;
+        }
+
+        Some(rect)
+    }
+
+    /// The same region for a frame of a different size, such as a proxy's
+    pub fn scaled(&self, scale_x: f32, scale_y: f32) -> RedactionRegion {
+        let scale = |value: i32, scale: f32| (value as f32 * scale).round() as i32;
+
+        RedactionRegion {
+            strength: scale(self.strength, scale_x.min(scale_y)).max(2),
+            keyframes: self
+                .keyframes
+                .iter()
+                .map(|keyframe| RedactionKeyframe {
+                    time_ms: keyframe.time_ms,
+                    rect: [
+                        scale(keyframe.rect[0], scale_x),
+                        scale(keyframe.rect[1], scale_y),
+                        scale(keyframe.rect[2], scale_x),
+                        scale(keyframe.rect[3], scale_y),
+                    ],
+                })
+                .collect(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Hides every region active at the given time in a decoded BGRA frame
+pub fn apply_redactions(
+    frame: &mut [u8],
+    width: u32,
+    height: u32,
+    regions: &[RedactionRegion],
+    time_ms: i32,
+) {
+    // decoded frames with row padding aren't supported
+    if frame.len() < width as usize * height as usize * 4 {
+        return;
+    }
+
+    for region in regions {
+        let Some(rect) = region.rect_at(time_ms) else {
+            continue;
+        };
+
+        // clamp to the frame
+        let x0 = rect[0].clamp(0, width as i32) as usize;
+        let y0 = rect[1].clamp(0, height as i32) as usize;
+        let x1 = (rect[0] + rect[2]).clamp(0, width as i32) as usize;
+        let y1 = (rect[1] + rect[3]).clamp(0, height as i32) as usize;
+        if x1 <= x0 || y1 <= y0 {
+            continue;
+        }
+
+        let strength = region.strength.max(2) as usize;
+        match region.style {
+            RedactionStyle::Pixelate => pixelate(frame, width as usize, (x0, y0, x1, y1), strength),
+            RedactionStyle::Blur => blur(frame, width as usize, (x0, y0, x1, y1), strength),
+        }
+    }
+}
+
+/// Fills blocks of the area with their average color
+fn pixelate(frame: &mut [u8], width: usize, area: (usize, usize, usize, usize), block_size: usize) {
+    let (x0, y0, x1, y1) = area;
+
+    for block_y in (y0..y1).step_by(block_size) {
+        for block_x in (x0..x1).step_by(block_size) {
+            let block_x1 = (block_x + block_size).min(x1);
+            let block_y1 = (block_y + block_size).min(y1);
+
+            let mut sum = [0u32; 3];
+            let mut count = 0;
+            for y in block_y..block_y1 {
+                for x in block_x..block_x1 {
+                    let i = (y * width + x) * 4;
+                    for c in 0..3 {
+                        sum[c] += frame[i + c] as u32;
+                    }
+                    count += 1;
+                }
+            }
+
+            for y in block_y..block_y1 {
+                for x in block_x..block_x1 {
+                    let i = (y * width + x) * 4;
+                    for c in 0..3 {
+                        frame[i + c] = (sum[c] / count) as u8;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Three box blur passes in each direction, close to a gaussian blur.
+/// Only pixels inside the area are sampled, so nothing outside it is smeared in or out.
+fn blur(frame: &mut [u8], width: usize, area: (usize, usize, usize, usize), radius: usize) {
+    let (x0, y0, x1, y1) = area;
+    let area_width = x1 - x0;
+    let area_height = y1 - y0;
+
+    let mut pixels: Vec<[f32; 3]> = Vec::with_capacity(area_width * area_height);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let i = (y * width + x) * 4;
+            pixels.push([frame[i] as f32, frame[i + 1] as f32, frame[i + 2] as f32]);
+        }
+    }
+
+    let box_blur = |pixels: &[[f32; 3]],
+                    length: usize,
+                    lines: usize,
+                    index: &dyn Fn(usize, usize) -> usize| {
+        let mut result = pixels.to_vec();
+        for line in 0..lines {
+            for position in 0..length {
+                let start = position.saturating_sub(radius);
+                let end = (position + radius + 1).min(length);
+                let mut sum = [0.0; 3];
+                for p in start..end {
+                    let pixel = pixels[index(line, p)];
+                    for c in 0..3 {
+                        sum[c] += pixel[c];
+                    }
+                }
+                let count = (end - start) as f32;
+                result[index(line, position)] = [sum[0] / count, sum[1] / count, sum[2] / count];
+            }
+        }
+        result
+    };
+
+    for _ in 0..3 {
+        pixels = box_blur(&pixels, area_width, area_height, &|y, x| y * area_width + x);
+        pixels = box_blur(&pixels, area_height, area_width, &|x, y| y * area_width + x);
+    }
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let i = (y * width + x) * 4;
+            let pixel = pixels[(y - y0) * area_width + (x - x0)];
+            for c in 0..3 {
+                frame[i + c] = pixel[c].round() as u8;
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::cmp::Ordering;
+
+use cgmath::{Matrix4, Vector4};
+
+use crate::blend::BlendMode;
+use crate::camera::Camera3D as Camera;
+use crate::scene::Scene;
+use crate::vertex::{get_z_layer, Vertex};
+
+/// One draw call, by index into the scene's object lists
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DrawTarget {
+    Polygon(usize),
+    TextBackground(usize), // always drawn just before its text
+    Text(usize),
+    Image(usize),
+    Video(usize),
+    ParticleEmitter(usize),
+}
+
+struct DrawItem {
+    target: DrawTarget,
+    depth: f32,
+    transparent: bool,
+}
+
+/// The order to draw visible, unmasked objects in. Opaque objects go first, front to back
+/// so the depth test can skip what they cover, then transparent ones back to front so each
+/// blends over everything behind it rather than being cut out by the depth buffer.
+pub fn draw_order(scene: &Scene, camera: &Camera) -> Vec<DrawTarget> {
+    let view_proj = camera.get_view_projection_matrix();
+    let mut items = Vec::new();
+
+    for (index, polygon) in scene.polygons.iter().enumerate() {
+        if polygon.hidden || polygon.user_hidden || polygon.mask_target_id.is_some() {
+            continue;
+        }
+
+        items.push(DrawItem {
+            target: DrawTarget::Polygon(index),
+            depth: view_depth(&view_proj, polygon.transform.layer),
+            transparent: is_transparent(&polygon.vertices, polygon.blend_mode),
+        });
+    }
+
+    for (index, text_item) in scene.text_items.iter().enumerate() {
+        if text_item.hidden || text_item.user_hidden || text_item.mask_target_id.is_some() {
+            continue;
+        }
+
+        // glyph edges are always blended, and the background sorts along with its text
+        let depth = view_depth(&view_proj, text_item.transform.layer);
+        if !text_item.background_polygon.hidden {
+            items.push(DrawItem {
+                target: DrawTarget::TextBackground(index),
+                depth,
+                transparent: true,
+            });
+        }
+        items.push(DrawItem {
+            target: DrawTarget::Text(index),
+            depth,
+            transparent: true,
+        });
+    }
+
+    for (index, st_image) in scene.image_items.iter().enumerate() {
+        if st_image.hidden || st_image.user_hidden || st_image.mask_target_id.is_some() {
+            continue;
+        }
+
+        // images can have transparent pixels anywhere
+        items.push(DrawItem {
+            target: DrawTarget::Image(index),
+            depth: view_depth(&view_proj, st_image.transform.layer),
+            transparent: true,
+        });
+    }
+
+    for (index, st_video) in scene.video_items.iter().enumerate() {
+        if st_video.hidden || st_video.user_hidden || st_video.mask_target_id.is_some() {
+            continue;
+        }
+
+        items.push(DrawItem {
+            target: DrawTarget::Video(index),
+            depth: view_depth(&view_proj, st_video.transform.layer),
+            transparent: is_transparent(&st_video.vertices, st_video.blend_mode),
+        });
+    }
+
+    for (index, emitter) in scene.particle_emitters.iter().enumerate() {
+        if emitter.hidden || emitter.user_hidden || emitter.index_count == 0 {
+            continue;
+        }
+
+        items.push(DrawItem {
+            target: DrawTarget::ParticleEmitter(index),
+            depth: view_depth(&view_proj, emitter.transform.layer),
+            transparent: true,
+        });
+    }
+
+    // stable, so a text background stays ahead of its text
+    items.sort_by(|a, b| match (a.transparent, b.transparent) {
+        (false, true) => Ordering::Less,
+        (true, false) => Ordering::Greater,
+        (false, false) => a.depth.total_cmp(&b.depth),
+        (true, true) => b.depth.total_cmp(&a.depth),
+    });
+
+    items.into_iter().map(|item| item.target).collect()
+}
+
+/// Depth after projection, larger is further away
+fn view_depth(view_proj: &Matrix4<f32>, layer: f32) -> f32 {
+    let clip = view_proj * Vector4::new(0.0, 0.0, get_z_layer(layer), 1.0);
+
+    clip.z / clip.w
+}
+
+/// Vertex colors include the animated opacity
+fn is_transparent(vertices: &[Vertex], blend_mode: BlendMode) -> bool {
+    blend_mode != BlendMode::Normal || vertices.iter().any(|vertex| vertex.color[3] < 1.0)
+}
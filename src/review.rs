@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::animations::ObjectType;
+
+/// Ties a comment to a specific object within a sequence
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct CommentAnchor {
+    pub object_id: String,
+    pub object_type: ObjectType,
+}
+
+/// A review comment left on a draft, optionally anchored to an object and a playhead time
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct ReviewComment {
+    pub id: String,
+    pub author: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    pub sequence_id: Option<String>,
+    pub anchor: Option<CommentAnchor>,
+    pub time_ms: Option<i32>, // playhead time within the sequence
+    pub resolved: bool,
+}
+
+impl ReviewComment {
+    pub fn new(
+        author: String,
+        body: String,
+        sequence_id: Option<String>,
+        anchor: Option<CommentAnchor>,
+        time_ms: Option<i32>,
+    ) -> Self {
+        ReviewComment {
+            id: Uuid::new_v4().to_string(),
+            author,
+            body,
+            created_at: Utc::now(),
+            sequence_id,
+            anchor,
+            time_ms,
+            resolved: false,
+        }
+    }
+}
+
+pub fn set_comment_resolved(
+    comments: &mut Vec<ReviewComment>,
+    comment_id: &str,
+    resolved: bool,
+) -> Result<(), String> {
+    let comment = comments
+        .iter_mut()
+        .find(|c| c.id == comment_id)
+        .ok_or_else(|| format!("No comment found with id {}", comment_id))?;
+
+    comment.resolved = resolved;
+
+    Ok(())
+}
+
+/// Returns comments ordered by playhead time, with unanchored comments (no time) last.
+/// Comments sharing a time are ordered by when they were written.
+pub fn comments_in_time_order(
+    comments: &[ReviewComment],
+    sequence_id: Option<&str>,
+    include_resolved: bool,
+) -> Vec<ReviewComment> {
+    let mut listed: Vec<ReviewComment> = comments
+        .iter()
+        .filter(|c| include_resolved || !c.resolved)
+        .filter(|c| match sequence_id {
+            Some(sequence_id) => c.sequence_id.as_deref() == Some(sequence_id),
+            None => true,
+        })
+        .cloned()
+        .collect();
+
+    listed.sort_by(|a, b| match (a.time_ms, b.time_ms) {
+        (Some(a_time), Some(b_time)) => a_time.cmp(&b_time).then(a.created_at.cmp(&b.created_at)),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.created_at.cmp(&b.created_at),
+    });
+
+    listed
+}
+
+pub fn comments_for_object(comments: &[ReviewComment], object_id: &str) -> Vec<ReviewComment> {
+    comments
+        .iter()
+        .filter(|c| {
+            c.anchor
+                .as_ref()
+                .map(|a| a.object_id == object_id)
+                .unwrap_or(false)
+        })
+        .cloned()
+        .collect()
+}
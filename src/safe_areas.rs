@@ -0,0 +1,115 @@
+use crate::editor::{BoundingBox, Point};
+use crate::guides::GuideLine;
+
+/// Share of the canvas inside the action-safe area
+pub const ACTION_SAFE: f32 = 0.9;
+/// Share of the canvas inside the title-safe area
+pub const TITLE_SAFE: f32 = 0.8;
+
+/// A short-form video app whose buttons and captions cover parts of the frame
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlatformOverlay {
+    TikTok,
+    InstagramReels,
+    YouTubeShorts,
+}
+
+impl PlatformOverlay {
+    /// Covered regions as (left, top, right, bottom) fractions of a portrait canvas
+    pub fn zone_fractions(&self) -> &'static [(f32, f32, f32, f32)] {
+        match self {
+            PlatformOverlay::TikTok => &[
+                (0.0, 0.0, 1.0, 0.11),  // following and for you tabs
+                (0.0, 0.8, 1.0, 1.0),   // caption, sound and tab bar
+                (0.86, 0.38, 1.0, 0.8), // like, comment and share buttons
+            ],
+            PlatformOverlay::InstagramReels => &[
+                (0.0, 0.0, 1.0, 0.09),
+                (0.0, 0.78, 1.0, 1.0),
+                (0.86, 0.5, 1.0, 0.78),
+            ],
+            PlatformOverlay::YouTubeShorts => &[
+                (0.0, 0.0, 1.0, 0.08),
+                (0.0, 0.8, 1.0, 1.0),
+                (0.85, 0.45, 1.0, 0.8),
+            ],
+        }
+    }
+}
+
+/// Guides drawn over the canvas in the editor only, never exported
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SafeAreaSettings {
+    pub action_safe: bool,
+    pub title_safe: bool,
+    pub platform: Option<PlatformOverlay>,
+}
+
+impl SafeAreaSettings {
+    pub fn any_visible(&self) -> bool {
+        self.action_safe || self.title_safe || self.platform.is_some()
+    }
+}
+
+/// The centered part of the canvas covering this share of its width and height
+pub fn safe_area(canvas: &BoundingBox, share: f32) -> BoundingBox {
+    let inset_x = (canvas.max.x - canvas.min.x) * (1.0 - share) / 2.0;
+    let inset_y = (canvas.max.y - canvas.min.y) * (1.0 - share) / 2.0;
+
+    BoundingBox {
+        min: Point {
+            x: canvas.min.x + inset_x,
+            y: canvas.min.y + inset_y,
+        },
+        max: Point {
+            x: canvas.max.x - inset_x,
+            y: canvas.max.y - inset_y,
+        },
+    }
+}
+
+/// The four edges of a box, for drawing it as an outline
+pub fn outline_lines(area: &BoundingBox) -> [GuideLine; 4] {
+    let corner = |x, y| Point { x, y };
+    let (min, max) = (area.min, area.max);
+
+    [
+        GuideLine {
+            start: corner(min.x, min.y),
+            end: corner(max.x, min.y),
+        },
+        GuideLine {
+            start: corner(max.x, min.y),
+            end: corner(max.x, max.y),
+        },
+        GuideLine {
+            start: corner(min.x, max.y),
+            end: corner(max.x, max.y),
+        },
+        GuideLine {
+            start: corner(min.x, min.y),
+            end: corner(min.x, max.y),
+        },
+    ]
+}
+
+/// Regions of the canvas a platform's interface covers
+pub fn obstruction_zones(canvas: &BoundingBox, platform: PlatformOverlay) -> Vec<BoundingBox> {
+    let width = canvas.max.x - canvas.min.x;
+    let height = canvas.max.y - canvas.min.y;
+
+    platform
+        .zone_fractions()
+        .iter()
+        .map(|(left, top, right, bottom)| BoundingBox {
+            min: Point {
+                x: canvas.min.x + width * left,
+                y: canvas.min.y + height * top,
+            },
+            max: Point {
+                x: canvas.min.x + width * right,
+                y: canvas.min.y + height * bottom,
+            },
+        })
+        .collect()
+}
@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 use directories::UserDirs;
-use std::{fs, path::PathBuf, sync::MutexGuard};
+use std::collections::HashSet;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::MutexGuard,
+};
 use uuid::Uuid;
 
 use crate::animations::AnimationData;
@@ -11,15 +16,24 @@ use crate::animations::KeyType;
 use crate::animations::KeyframeValue;
 use crate::animations::ObjectType;
 use crate::animations::UIKeyframe;
+use crate::blend::BlendMode;
+use crate::brand_kit::BrandKit;
+use crate::camera::CameraBookmark;
+use crate::masking::MaskMode;
+use crate::migrations::{migrate, CURRENT_SCHEMA_VERSION};
+use crate::palette::Palette;
 use crate::editor::wgpu_to_human;
 use crate::editor::PathType;
 use crate::polygon::SavedPoint;
 use crate::polygon::SavedStroke;
+use crate::review::ReviewComment;
+use crate::st_video::{source_time_at, VideoTrim};
+use crate::style_presets::StylePreset;
 use crate::editor::WindowSize;
 use crate::{
     animations::Sequence, 
     polygon::SavedPolygonConfig, 
-    timelines::SavedTimelineStateConfig,
+    timelines::{SavedTimelineStateConfig, TimelineSequence, TrackType},
 };
 
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
@@ -28,6 +42,434 @@ pub struct SavedState {
     // pub name: String,
     pub sequences: Vec<Sequence>,
     pub timeline_state: SavedTimelineStateConfig,
+    #[serde(default)]
+    pub comments: Vec<ReviewComment>,
+    #[serde(default)]
+    pub camera_bookmarks: Vec<CameraBookmark>,
+    #[serde(default)]
+    pub canvas_size: CanvasSize,
+    #[serde(default)]
+    pub schema_version: u32, // see migrations.rs
+    #[serde(default)]
+    pub file_format: ProjectFormat,
+    #[serde(default)]
+    pub palette: Palette,
+    #[serde(default)]
+    pub style_presets: Vec<StylePreset>,
+    #[serde(default)]
+    pub brand_kit: Option<BrandKit>,
+}
+
+/// How project files are written. Any of them is read back, whatever this is set to.
+/// The file keeps its project_data.json name either way, so paths to it stay valid.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum ProjectFormat {
+    #[default]
+    Json, // readable and diffable, for interop with other tools
+    MessagePack,
+    MessagePackZstd, // smallest, for projects with many keyframes
+}
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+#[cfg(not(target_arch = "wasm32"))]
+const ZSTD_LEVEL: i32 = 3;
+#[cfg(target_arch = "wasm32")]
+const ZSTD_UNAVAILABLE: &str = "Compressed projects aren't supported in the browser";
+
+/// Serializes a project in its file_format
+pub fn encode_saved_state(saved_state: &SavedState) -> Result<Vec<u8>, String> {
+    match saved_state.file_format {
+        ProjectFormat::Json => serde_json::to_vec_pretty(saved_state).map_err(|e| e.to_string()),
+        ProjectFormat::MessagePack => {
+            // named fields, so fields added later with serde(default) still read
+            rmp_serde::to_vec_named(saved_state).map_err(|e| e.to_string())
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        ProjectFormat::MessagePackZstd => {
+            let packed = rmp_serde::to_vec_named(saved_state).map_err(|e| e.to_string())?;
+            zstd::encode_all(packed.as_slice(), ZSTD_LEVEL).map_err(|e| e.to_string())
+        }
+        #[cfg(target_arch = "wasm32")]
+        ProjectFormat::MessagePackZstd => Err(ZSTD_UNAVAILABLE.to_string()),
+    }
+}
+
+impl ProjectFormat {
+    /// The format of a project file's contents, whatever its name says
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(&ZSTD_MAGIC) {
+            return ProjectFormat::MessagePackZstd;
+        }
+
+        let is_json = bytes
+            .iter()
+            .find(|b| !b.is_ascii_whitespace())
+            .is_some_and(|b| *b == b'{');
+        if is_json {
+            ProjectFormat::Json
+        } else {
+            ProjectFormat::MessagePack
+        }
+    }
+
+    /// For copies of a file kept outside its usual name, such as backups
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ProjectFormat::Json => "json",
+            ProjectFormat::MessagePack => "msgpack",
+            ProjectFormat::MessagePackZstd => "msgpack.zst",
+        }
+    }
+}
+
+/// Reads a project file in any format into JSON, ready for migrating
+pub fn decode_project(bytes: &[u8]) -> anyhow::Result<serde_json::Value> {
+    match ProjectFormat::detect(bytes) {
+        ProjectFormat::Json => Ok(serde_json::from_slice(bytes)?),
+        ProjectFormat::MessagePack => Ok(rmp_serde::from_slice(bytes)?),
+        #[cfg(not(target_arch = "wasm32"))]
+        ProjectFormat::MessagePackZstd => {
+            let packed = zstd::decode_all(bytes)?;
+            Ok(rmp_serde::from_slice(&packed)?)
+        }
+        #[cfg(target_arch = "wasm32")]
+        ProjectFormat::MessagePackZstd => Err(anyhow::anyhow!(ZSTD_UNAVAILABLE)),
+    }
+}
+
+impl SavedState {
+    /// Reads one of this machine's project files, upgrading it from older formats first.
+    /// The file as it was is kept beside it, named for its format such as project_data.v0.json
+    /// or project_data.v0.msgpack, and the upgraded project is written in its place so it's
+    /// only upgraded once. Both writes are best-effort, a project in a read-only folder
+    /// still opens.
+    pub fn load_with_migrations(path: &Path) -> anyhow::Result<SavedState> {
+        let content = fs::read(path)?;
+        let (saved_state, from_version) = SavedState::decode_with_migrations(&content)?;
+        if from_version >= CURRENT_SCHEMA_VERSION {
+            return Ok(saved_state);
+        }
+
+        println!(
+            "Upgraded project {} from format {}",
+            path.display(),
+            from_version
+        );
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("project");
+        let backup_path = path.with_file_name(format!(
+            "{}.v{}.{}",
+            stem,
+            from_version,
+            ProjectFormat::detect(&content).extension()
+        ));
+
+        // never replace the original without a copy of it
+        if !backup_path.exists() {
+            if let Err(e) = fs::write(&backup_path, &content) {
+                println!("Couldn't back up {}: {}", path.display(), e);
+                return Ok(saved_state);
+            }
+        }
+        if let Err(e) = encode_saved_state(&saved_state)
+            .and_then(|upgraded| write_atomic(path, &upgraded).map_err(|e| e.to_string()))
+        {
+            println!("Couldn't save upgraded project {}: {}", path.display(), e);
+        }
+
+        Ok(saved_state)
+    }
+
+    /// Reads a project from a file's contents, upgrading older formats in memory.
+    /// Also returns the format version it was saved with.
+    pub fn decode_with_migrations(content: &[u8]) -> anyhow::Result<(SavedState, u32)> {
+        let mut project = decode_project(content)?;
+        let from_version = migrate(&mut project).map_err(anyhow::Error::msg)?;
+
+        Ok((serde_json::from_value(project)?, from_version))
+    }
+
+    /// Adds a sequence to the project and places it at the end of the video track
+    pub fn append_sequence(&mut self, sequence: Sequence) {
+        let start_time_ms = self
+            .timeline_state
+            .track_end_ms(&TrackType::Video, &self.sequences);
+        self.timeline_state
+            .timeline_sequences
+            .push(TimelineSequence {
+                id: Uuid::new_v4().to_string(),
+                sequence_id: sequence.id.clone(),
+                track_type: TrackType::Video,
+                start_time_ms,
+                transition_in: None,
+            });
+        self.sequences.push(sequence);
+    }
+
+    /// Copies a sequence with new ids for it and everything in it, placing the copy
+    /// at the end of the video track. Returns the copy's id.
+    pub fn duplicate_sequence(&mut self, sequence_id: &str) -> Result<String, String> {
+        let sequence = self
+            .sequences
+            .iter()
+            .find(|s| s.id == sequence_id)
+            .ok_or_else(|| format!("No sequence found with id {}", sequence_id))?;
+
+        let mut copy = duplicate_sequence(sequence)?;
+        copy.name = format!("{} copy", sequence.name);
+        let copy_id = copy.id.clone();
+        self.append_sequence(copy);
+
+        Ok(copy_id)
+    }
+
+    /// Adds a fresh copy of a template's sequence, fitted to this project's canvas.
+    /// Media paths go through remap_asset_path, from the template's folder. Returns the new id.
+    pub fn add_template(
+        &mut self,
+        template: &SequenceTemplate,
+        template_dir: &Path,
+    ) -> Result<String, String> {
+        let mut sequence = duplicate_sequence(&template.sequence)?;
+        for image in sequence.active_image_items.iter_mut() {
+            image.path = remap_asset_path(&image.path, template_dir)
+                .ok_or_else(|| format!("Couldn't find image {}", image.path))?;
+        }
+        for video in sequence.active_video_items.iter_mut() {
+            video.path = remap_asset_path(&video.path, template_dir)
+                .ok_or_else(|| format!("Couldn't find video {}", video.path))?;
+            video.mouse_path = video
+                .mouse_path
+                .as_ref()
+                .and_then(|mouse_path| remap_asset_path(mouse_path, template_dir));
+        }
+        if template.canvas_size != self.canvas_size {
+            rescale_sequence(&mut sequence, template.canvas_size, self.canvas_size);
+        }
+
+        let sequence_id = sequence.id.clone();
+        self.append_sequence(sequence);
+
+        Ok(sequence_id)
+    }
+}
+
+/// A sequence saved on its own, so intros, outros and the like can be reused across projects
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct SequenceTemplate {
+    pub name: String,
+    pub canvas_size: CanvasSize, // the sequence is rescaled when used on other canvases
+    pub sequence: Sequence,
+}
+
+/// The project's canvas in canvas pixels, which sets its aspect ratio
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct CanvasSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for CanvasSize {
+    fn default() -> Self {
+        CanvasSize {
+            width: 1000,
+            height: 600,
+        }
+    }
+}
+
+/// Canvas shapes for new projects
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum CanvasPreset {
+    #[default]
+    Landscape,
+    /// 9:16, for phone-first video
+    Portrait,
+    /// 1:1
+    Square,
+}
+
+impl CanvasPreset {
+    pub fn canvas_size(&self) -> CanvasSize {
+        match self {
+            CanvasPreset::Landscape => CanvasSize::default(),
+            CanvasPreset::Portrait => CanvasSize {
+                width: 450,
+                height: 800,
+            },
+            CanvasPreset::Square => CanvasSize {
+                width: 600,
+                height: 600,
+            },
+        }
+    }
+}
+
+/// Canvas the motion model was trained on, predictions are percentages of it
+const INFERENCE_CANVAS: (f32, f32) = (800.0, 450.0);
+
+impl CanvasSize {
+    pub fn aspect_ratio(&self) -> f32 {
+        self.width as f32 / self.height.max(1) as f32
+    }
+
+    pub fn is_portrait(&self) -> bool {
+        self.height > self.width
+    }
+
+    /// Export frame size with this aspect ratio and the given shorter side, e.g. 1080 for
+    /// 1920x1080 landscape or 1080x1920 portrait. Rounded to even sizes as H.264 requires.
+    pub fn export_size(&self, short_side: u32) -> (u32, u32) {
+        let long_side = if self.is_portrait() {
+            (short_side as f32 / self.aspect_ratio()).round() as u32
+        } else {
+            (short_side as f32 * self.aspect_ratio()).round() as u32
+        };
+        let (width, height) = if self.is_portrait() {
+            (short_side, long_side)
+        } else {
+            (long_side, short_side)
+        };
+        (width / 2 * 2, height / 2 * 2)
+    }
+
+    /// Scale and offset fitting this canvas inside the inference canvas without stretching,
+    /// so motion on portrait and square canvases keeps its proportions
+    fn inference_fit(&self) -> (f32, f32, f32) {
+        let scale = (INFERENCE_CANVAS.0 / self.width.max(1) as f32)
+            .min(INFERENCE_CANVAS.1 / self.height.max(1) as f32);
+        let offset_x = (INFERENCE_CANVAS.0 - self.width as f32 * scale) / 2.0;
+        let offset_y = (INFERENCE_CANVAS.1 - self.height as f32 * scale) / 2.0;
+        (scale, offset_x, offset_y)
+    }
+
+    /// Canvas position as the percentages the motion model takes
+    pub fn to_inference(&self, x: f32, y: f32) -> (f32, f32) {
+        let (scale, offset_x, offset_y) = self.inference_fit();
+        (
+            (x * scale + offset_x) / INFERENCE_CANVAS.0 * 100.0,
+            (y * scale + offset_y) / INFERENCE_CANVAS.1 * 100.0,
+        )
+    }
+
+    /// Canvas position from the percentages the motion model predicts
+    pub fn from_inference(&self, x: f32, y: f32) -> (f32, f32) {
+        let (scale, offset_x, offset_y) = self.inference_fit();
+        (
+            (x * 0.01 * INFERENCE_CANVAS.0 - offset_x) / scale,
+            (y * 0.01 * INFERENCE_CANVAS.1 - offset_y) / scale,
+        )
+    }
+}
+
+/// Moves a sequence's objects, motion paths and camera pans from one canvas size to another,
+/// keeping their place relative to the canvas. Sizes scale by the smaller factor so objects
+/// keep their shape.
+pub fn rescale_sequence(sequence: &mut Sequence, from: CanvasSize, to: CanvasSize) {
+    let sx = to.width as f32 / from.width.max(1) as f32;
+    let sy = to.height as f32 / from.height.max(1) as f32;
+    let s = sx.min(sy);
+
+    let point = |p: &mut SavedPoint| {
+        p.x = (p.x as f32 * sx).round() as i32;
+        p.y = (p.y as f32 * sy).round() as i32;
+    };
+    let size_i32 = |d: &mut (i32, i32)| {
+        *d = (
+            (d.0 as f32 * s).round() as i32,
+            (d.1 as f32 * s).round() as i32,
+        );
+    };
+    let size_u32 = |d: &mut (u32, u32)| {
+        *d = (
+            (d.0 as f32 * s).round() as u32,
+            (d.1 as f32 * s).round() as u32,
+        );
+    };
+    let length = |v: i32| (v as f32 * s).round() as i32;
+    let position_keyframes = |properties: &mut [AnimationProperty]| {
+        for keyframe in properties.iter_mut().flat_map(|p| p.keyframes.iter_mut()) {
+            if let KeyframeValue::Position(position) = &mut keyframe.value {
+                *position = [
+                    (position[0] as f32 * sx).round() as i32,
+                    (position[1] as f32 * sy).round() as i32,
+                ];
+            }
+        }
+    };
+
+    for polygon in &mut sequence.active_polygons {
+        point(&mut polygon.position);
+        size_i32(&mut polygon.dimensions);
+    }
+    for text in &mut sequence.active_text_items {
+        point(&mut text.position);
+        size_i32(&mut text.dimensions);
+        text.font_size = (text.font_size as f32 * s).round() as i32;
+    }
+    for image in &mut sequence.active_image_items {
+        point(&mut image.position);
+        size_u32(&mut image.dimensions);
+    }
+    for video in &mut sequence.active_video_items {
+        point(&mut video.position);
+        size_u32(&mut video.dimensions);
+    }
+
+    for group in &mut sequence.active_groups {
+        point(&mut group.pivot);
+        point(&mut group.position);
+    }
+    for emitter in &mut sequence.active_particle_emitters {
+        point(&mut emitter.position);
+        size_i32(&mut emitter.dimensions);
+        emitter.particle_size = length(emitter.particle_size);
+        emitter.speed = length(emitter.speed);
+        emitter.gravity = length(emitter.gravity);
+    }
+    for callout in &mut sequence.active_callouts {
+        point(&mut callout.anchor);
+        callout.thickness = length(callout.thickness);
+        callout.head_size = length(callout.head_size);
+    }
+    for spotlight in &mut sequence.active_spotlights {
+        point(&mut spotlight.center);
+        size_i32(&mut spotlight.size);
+        spotlight.feather = length(spotlight.feather);
+    }
+
+    for animation in &mut sequence.polygon_motion_paths {
+        animation.position = [
+            (animation.position[0] as f32 * sx).round() as i32,
+            (animation.position[1] as f32 * sy).round() as i32,
+        ];
+
+        position_keyframes(&mut animation.properties);
+
+        // spotlight regions are keyframed as [x, y, width, height, feather]
+        for keyframe in animation
+            .properties
+            .iter_mut()
+            .filter(|p| p.property_path == "spotlight")
+            .flat_map(|p| p.keyframes.iter_mut())
+        {
+            if let KeyframeValue::Custom(values) = &mut keyframe.value {
+                if let [x, y, width, height, feather] = values.as_mut_slice() {
+                    *x = (*x as f32 * sx).round() as i32;
+                    *y = (*y as f32 * sy).round() as i32;
+                    *width = length(*width);
+                    *height = length(*height);
+                    *feather = length(*feather);
+                }
+            }
+        }
+    }
+    // pans are in canvas pixels too
+    if let Some(camera_animation) = &mut sequence.camera_animation {
+        position_keyframes(&mut camera_animation.properties);
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
@@ -54,24 +496,39 @@ pub fn get_ground_truth_dir() -> Option<PathBuf> {
     })
 }
 
-pub fn save_saved_state(saved_state: MutexGuard<SavedState>) {
+pub fn save_saved_state(saved_state: MutexGuard<SavedState>) -> Result<(), String> {
     let owned = saved_state.to_owned();
-    save_saved_state_raw(owned);
+    save_saved_state_raw(owned)
 }
 
-pub fn save_saved_state_raw(saved_state: SavedState) {
-    let json = serde_json::to_string_pretty(&saved_state).expect("Couldn't serialize saved state");
+pub fn get_project_dir(project_id: &str) -> PathBuf {
     let sync_dir = get_ground_truth_dir().expect("Couldn't get Stunts directory");
-    let project_dir = sync_dir.join("projects").join(saved_state.id.clone());
+    sync_dir.join("projects").join(project_id)
+}
+
+/// Writes to a temporary file then renames it over the target,
+/// so a crash mid-write never leaves a half-written file behind
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, path)
+}
+
+pub fn save_saved_state_raw(saved_state: SavedState) -> Result<(), String> {
+    let content = encode_saved_state(&saved_state)?;
+    let project_dir = get_project_dir(&saved_state.id);
     let save_path = project_dir.join("project_data.json");
 
     println!("Saving saved state... {}", save_path.display());
 
-    fs::write(&save_path, json).expect("Couldn't write saved state");
+    write_atomic(&save_path, &content)
+        .map_err(|e| format!("Couldn't write {}: {}", save_path.display(), e))?;
 
     drop(saved_state);
 
     println!("Saved!");
+
+    Ok(())
 }
 
 #[cfg(feature = "production")]
@@ -131,6 +588,69 @@ pub fn get_captures_dir() -> PathBuf {
     captures_dir
 }
 
+pub fn get_templates_dir() -> PathBuf {
+    let main_dir = get_ground_truth_dir().expect("Couldn't check or create Stunts directory");
+    let templates_dir = main_dir.join("templates");
+
+    fs::create_dir_all(&templates_dir)
+        .ok()
+        .expect("Couldn't check or create Stunts templates directory");
+
+    templates_dir
+}
+
+/// Saves a template into the templates folder, returning its path
+pub fn save_sequence_template(template: &SequenceTemplate) -> anyhow::Result<PathBuf> {
+    let json = serde_json::to_string_pretty(template)?;
+    let save_path = get_templates_dir().join(format!("{}.json", Uuid::new_v4()));
+    fs::write(&save_path, json)?;
+
+    Ok(save_path)
+}
+
+pub fn load_sequence_template(path: &Path) -> anyhow::Result<SequenceTemplate> {
+    let json_content = fs::read_to_string(path)?;
+    let template: SequenceTemplate = serde_json::from_str(&json_content)?;
+
+    Ok(template)
+}
+
+/// Every template in the templates folder, with its path
+pub fn list_sequence_templates() -> Vec<(PathBuf, SequenceTemplate)> {
+    let Ok(entries) = fs::read_dir(get_templates_dir()) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+        .filter_map(|path| {
+            let template = load_sequence_template(&path).ok()?;
+            Some((path, template))
+        })
+        .collect()
+}
+
+/// Starts a new project from a template, on the template's canvas
+pub fn create_project_from_template(
+    name: String,
+    template_path: &Path,
+) -> anyhow::Result<SavedState> {
+    let template = load_sequence_template(template_path)?;
+    let template_dir = template_path.parent().unwrap_or(Path::new("."));
+
+    let mut saved_state = create_project_state(name)?;
+    saved_state.canvas_size = template.canvas_size;
+    saved_state
+        .add_template(&template, template_dir)
+        .map_err(anyhow::Error::msg)?;
+
+    save_saved_state_raw(saved_state.clone()).map_err(anyhow::Error::msg)?;
+
+    Ok(saved_state)
+}
+
 pub fn load_projects_datafile() -> anyhow::Result<ProjectsDataFile> {
     let sync_dir = get_ground_truth_dir().expect("Couldn't get Stunts directory");
     let json_path = sync_dir.join("projects.json");
@@ -183,7 +703,16 @@ pub fn load_project_state(project_id: String) -> anyhow::Result<SavedState> {
             sequences: Vec::new(),
             timeline_state: SavedTimelineStateConfig {
                 timeline_sequences: Vec::new(),
+                markers: Vec::new(),
             },
+            comments: Vec::new(),
+            camera_bookmarks: Vec::new(),
+            canvas_size: CanvasSize::default(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            file_format: ProjectFormat::default(),
+            palette: Palette::default(),
+            style_presets: Vec::new(),
+            brand_kit: None,
         };
 
         let json = serde_json::to_string_pretty(&json).expect("Couldn't serialize saved state");
@@ -191,15 +720,18 @@ pub fn load_project_state(project_id: String) -> anyhow::Result<SavedState> {
         fs::write(&json_path, json).expect("Couldn't write saved state");
     }
 
-    // Read and parse the JSON file
-    let json_content = fs::read_to_string(json_path)?;
-    let state: SavedState = serde_json::from_str(&json_content)?;
-
-    Ok(state)
+    SavedState::load_with_migrations(&json_path)
 }
 
 // Add this function to handle project creation
 pub fn create_project_state(name: String) -> anyhow::Result<SavedState> {
+    create_project_state_with_preset(name, CanvasPreset::Landscape)
+}
+
+pub fn create_project_state_with_preset(
+    name: String,
+    preset: CanvasPreset,
+) -> anyhow::Result<SavedState> {
     let project_id = Uuid::new_v4().to_string();
 
     // Create project directory and save initial state
@@ -214,7 +746,16 @@ pub fn create_project_state(name: String) -> anyhow::Result<SavedState> {
         sequences: Vec::new(),
         timeline_state: SavedTimelineStateConfig {
             timeline_sequences: Vec::new(),
+            markers: Vec::new(),
         },
+        comments: Vec::new(),
+        camera_bookmarks: Vec::new(),
+        canvas_size: preset.canvas_size(),
+        schema_version: CURRENT_SCHEMA_VERSION,
+        file_format: ProjectFormat::default(),
+        palette: Palette::default(),
+        style_presets: Vec::new(),
+        brand_kit: None,
     };
 
     let json = serde_json::to_string_pretty(&initial_state)?;
@@ -234,6 +775,236 @@ pub fn create_project_state(name: String) -> anyhow::Result<SavedState> {
     Ok(initial_state)
 }
 
+/// Reads a project file from any path, such as a project_data.json from another machine.
+/// Older formats are upgraded in memory only, the file and its folder aren't written to.
+pub fn load_saved_state_file(path: &Path) -> anyhow::Result<SavedState> {
+    let content = fs::read(path)?;
+    let (saved_state, _) = SavedState::decode_with_migrations(&content)?;
+
+    Ok(saved_state)
+}
+
+/// Finds an imported project's media on this machine. Paths are kept when they exist,
+/// otherwise the file is looked for by name next to the imported project file and in
+/// the images, videos and captures folders.
+pub fn remap_asset_path(path: &str, project_dir: &Path) -> Option<String> {
+    let original = Path::new(path);
+    if original.exists() {
+        return Some(path.to_string());
+    }
+
+    let file_name = original.file_name()?;
+    [
+        project_dir.to_path_buf(),
+        get_images_dir(),
+        get_videos_dir(),
+        get_captures_dir(),
+    ]
+    .iter()
+    .map(|dir| dir.join(file_name))
+    .find(|candidate| candidate.exists())
+    .map(|candidate| candidate.to_string_lossy().to_string())
+}
+
+/// Copies sequences out of another project so they can be appended to this one.
+/// Any id already used in this project, whether a sequence, object, animation or keyframe,
+/// is replaced with a fresh one everywhere it appears, including references such as group
+/// children and mask targets. Media paths go through remap_asset_path.
+pub fn prepare_sequence_import(
+    source: &SavedState,
+    sequence_ids: &[String],
+    existing: &SavedState,
+    project_dir: &Path,
+) -> Result<Vec<Sequence>, String> {
+    let existing_json = serde_json::to_value(existing).map_err(|e| e.to_string())?;
+    let mut taken_ids = HashSet::new();
+    collect_uuids(&existing_json, &mut taken_ids);
+
+    let mut imported = Vec::new();
+    for sequence_id in sequence_ids {
+        let sequence = source
+            .sequences
+            .iter()
+            .find(|s| &s.id == sequence_id)
+            .ok_or_else(|| format!("No sequence found with id {}", sequence_id))?;
+
+        let mut sequence_json = serde_json::to_value(sequence).map_err(|e| e.to_string())?;
+        let mut sequence_ids_found = HashSet::new();
+        collect_uuids(&sequence_json, &mut sequence_ids_found);
+
+        let new_ids: HashMap<String, String> = sequence_ids_found
+            .into_iter()
+            .filter(|id| taken_ids.contains(id))
+            .map(|id| (id, Uuid::new_v4().to_string()))
+            .collect();
+        replace_ids(&mut sequence_json, &new_ids);
+
+        let mut sequence: Sequence =
+            serde_json::from_value(sequence_json).map_err(|e| e.to_string())?;
+
+        for image in sequence.active_image_items.iter_mut() {
+            image.path = remap_asset_path(&image.path, project_dir)
+                .ok_or_else(|| format!("Couldn't find image {}", image.path))?;
+        }
+        for video in sequence.active_video_items.iter_mut() {
+            video.path = remap_asset_path(&video.path, project_dir)
+                .ok_or_else(|| format!("Couldn't find video {}", video.path))?;
+            // recorded mouse movement is optional, drop it rather than failing
+            video.mouse_path = video
+                .mouse_path
+                .as_ref()
+                .and_then(|mouse_path| remap_asset_path(mouse_path, project_dir));
+        }
+
+        // ids are unique across everything imported too, in case a sequence is picked twice
+        let imported_json = serde_json::to_value(&sequence).map_err(|e| e.to_string())?;
+        collect_uuids(&imported_json, &mut taken_ids);
+
+        imported.push(sequence);
+    }
+
+    Ok(imported)
+}
+
+/// Copy of a sequence where every id is new, so it can sit beside the original
+pub fn duplicate_sequence(sequence: &Sequence) -> Result<Sequence, String> {
+    let mut sequence_json = serde_json::to_value(sequence).map_err(|e| e.to_string())?;
+    let mut ids = HashSet::new();
+    collect_uuids(&sequence_json, &mut ids);
+
+    let new_ids: HashMap<String, String> = ids
+        .into_iter()
+        .map(|id| (id, Uuid::new_v4().to_string()))
+        .collect();
+    replace_ids(&mut sequence_json, &new_ids);
+
+    serde_json::from_value(sequence_json).map_err(|e| e.to_string())
+}
+
+/// Cuts a sequence in two at at_ms. The first half keeps the id and everything before the cut,
+/// the second gets new ids and carries on from there. Animations running across the cut
+/// start the second half from their last keyframe before it.
+pub fn split_sequence(sequence: &Sequence, at_ms: i32) -> Result<(Sequence, Sequence), String> {
+    if at_ms <= 0 || at_ms >= sequence.duration_ms {
+        return Err(format!(
+            "Can't split a {}ms sequence at {}ms",
+            sequence.duration_ms, at_ms
+        ));
+    }
+    let cut = Duration::from_millis(at_ms as u64);
+
+    let mut first = sequence.clone();
+    first.duration_ms = at_ms;
+    first
+        .polygon_motion_paths
+        .retain(|animation| animation.start_time_ms < at_ms);
+
+    let mut second = duplicate_sequence(sequence)?;
+    second.name = format!("{} (2)", sequence.name);
+    second.duration_ms = sequence.duration_ms - at_ms;
+    second.polygon_motion_paths.retain(|animation| {
+        Duration::from_millis(animation.start_time_ms as u64) + animation.duration > cut
+    });
+
+    for animation in second.polygon_motion_paths.iter_mut() {
+        let start = Duration::from_millis(animation.start_time_ms as u64);
+        if start >= cut {
+            animation.start_time_ms -= at_ms;
+            continue;
+        }
+        let offset = cut - start;
+
+        // videos carry on from the source frame the first half stopped at
+        if animation.object_type == ObjectType::VideoItem {
+            if let Some(video) = second
+                .active_video_items
+                .iter_mut()
+                .find(|v| v.id == animation.polygon_id)
+            {
+                let speed_keyframes = animation
+                    .properties
+                    .iter()
+                    .find(|p| p.property_path == "speed")
+                    .map(|p| p.keyframes.as_slice())
+                    .unwrap_or(&[]);
+                let speed = video.speed as f32 / 100.0;
+                let played_ms = source_time_at(speed_keyframes, speed, offset).as_millis() as i32;
+
+                video.trim = Some(match video.trim {
+                    Some(trim) => VideoTrim {
+                        start_ms: trim.start_ms + played_ms,
+                        end_ms: trim.end_ms,
+                    },
+                    None => VideoTrim {
+                        start_ms: played_ms,
+                        end_ms: source_time_at(speed_keyframes, speed, animation.duration)
+                            .as_millis() as i32,
+                    },
+                });
+            }
+        }
+
+        animation.start_time_ms = 0;
+        animation.duration -= offset;
+        for property in animation.properties.iter_mut() {
+            shift_keyframes(property, offset);
+        }
+    }
+
+    if let Some(camera_animation) = second.camera_animation.as_mut() {
+        for property in camera_animation.properties.iter_mut() {
+            shift_keyframes(property, cut);
+        }
+    }
+
+    Ok((first, second))
+}
+
+/// Moves keyframes earlier by offset. Of those before it, only the last is kept, at zero.
+fn shift_keyframes(property: &mut AnimationProperty, offset: Duration) {
+    property.keyframes.sort_by_key(|k| k.time);
+    if let Some(held) = property.keyframes.iter().rposition(|k| k.time <= offset) {
+        property.keyframes.drain(..held);
+    }
+
+    for keyframe in property.keyframes.iter_mut() {
+        keyframe.time = keyframe.time.saturating_sub(offset);
+        if let KeyType::Range(range_data) = &mut keyframe.key_type {
+            range_data.end_time = range_data.end_time.saturating_sub(offset);
+        }
+    }
+
+    for child in property.children.iter_mut() {
+        shift_keyframes(child, offset);
+    }
+}
+
+fn collect_uuids(value: &serde_json::Value, ids: &mut HashSet<String>) {
+    match value {
+        serde_json::Value::String(s) => {
+            if Uuid::parse_str(s).is_ok() {
+                ids.insert(s.clone());
+            }
+        }
+        serde_json::Value::Array(values) => values.iter().for_each(|v| collect_uuids(v, ids)),
+        serde_json::Value::Object(map) => map.values().for_each(|v| collect_uuids(v, ids)),
+        _ => {}
+    }
+}
+
+fn replace_ids(value: &mut serde_json::Value, new_ids: &HashMap<String, String>) {
+    match value {
+        serde_json::Value::String(s) => {
+            if let Some(new_id) = new_ids.get(s.as_str()) {
+                *s = new_id.clone();
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(|v| replace_ids(v, new_ids)),
+        serde_json::Value::Object(map) => map.values_mut().for_each(|v| replace_ids(v, new_ids)),
+        _ => {}
+    }
+}
+
 
 
 // // Function to check subscription status
@@ -335,6 +1106,15 @@ pub fn parse_animation_data(content: &str) -> Result<Vec<Sequence>, Box<dyn std:
             active_text_items: Vec::new(),
             active_image_items: Vec::new(),
             active_video_items: Vec::new(),
+            camera_animation: None,
+            tags: Vec::new(),
+            outlined_text_items: Vec::new(),
+            active_groups: Vec::new(),
+            active_particle_emitters: Vec::new(),
+            active_callouts: Vec::new(),
+            active_spotlights: Vec::new(),
+            caption_tracks: Vec::new(),
+            constraints: Vec::new(),
         };
 
         result.push(sequence);
@@ -379,6 +1159,14 @@ fn parse_active_polygons(
                 fill: [0, 0, 0, 255], // Default black
             },
             layer: -2,
+            tags: Vec::new(),
+            outline: None,
+            locked: false,
+            hidden: false,
+            opacity: 100,
+            blend_mode: BlendMode::Normal,
+            mask_target_id: None,
+            mask_mode: MaskMode::Clip,
         };
 
         polygons.push(polygon);
@@ -454,6 +1242,7 @@ fn parse_motion_paths(
             duration: max_time,
             start_time_ms: 0,
             position: [0, 0],
+            motion_blur: 0,
             properties: vec![position_property],
         };
 
@@ -472,4 +1261,189 @@ pub fn get_random_coords(window_size: WindowSize) -> (u32, u32) {
     let random_y = rng.gen_range(150..=(window_size.height - 150));
 
     (random_x, random_y)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animations::CameraAnimation;
+    use crate::callout::SavedCalloutConfig;
+    use crate::input_overlays::keyframe_property;
+    use crate::particles::{ParticlePreset, SavedParticleEmitterConfig};
+    use crate::spotlight::{build_spotlight, SpotlightSettings};
+    use serde_json::json;
+
+    // 1000x600 to 500x600, so x halves, y stays and sizes halve
+    const FROM: CanvasSize = CanvasSize {
+        width: 1000,
+        height: 600,
+    };
+    const TO: CanvasSize = CanvasSize {
+        width: 500,
+        height: 600,
+    };
+
+    fn sequence() -> Sequence {
+        let object = |id: &str| {
+            json!({
+                "id": id,
+                "name": id,
+                "position": { "x": 400, "y": 300 },
+                "dimensions": [200, 100],
+                "layer": 0,
+            })
+        };
+        let mut polygon = object("polygon");
+        polygon["fill"] = json!([255, 255, 255, 255]);
+        polygon["border_radius"] = json!(0);
+        polygon["stroke"] = json!({ "thickness": 0, "fill": [0, 0, 0, 255] });
+        let mut text = object("text");
+        text["text"] = json!("Text");
+        text["font_family"] = json!("Aleo");
+        text["font_size"] = json!(40);
+        text["color"] = json!([0, 0, 0, 255]);
+        text["background_fill"] = json!(null);
+        let mut image = object("image");
+        image["path"] = json!("image.png");
+        let mut video = object("video");
+        video["path"] = json!("video.mp4");
+        video["mouse_path"] = json!(null);
+
+        let mut sequence: Sequence = serde_json::from_value(json!({
+            "id": "sequence",
+            "name": "Sequence",
+            "background_fill": null,
+            "duration_ms": 1000,
+            "active_polygons": [polygon],
+            "polygon_motion_paths": [],
+            "active_text_items": [text],
+            "active_image_items": [image],
+            "active_video_items": [video],
+            "active_groups": [{
+                "id": "group",
+                "name": "Group",
+                "children": [],
+                "pivot": { "x": 400, "y": 300 },
+                "position": { "x": 600, "y": 300 },
+                "rotation": 0,
+                "scale": 100,
+            }],
+        }))
+        .expect("Couldn't build sequence");
+
+        let position = |x, y| {
+            keyframe_property(
+                "Position",
+                "position",
+                &[(0, KeyframeValue::Position([x, y]))],
+            )
+        };
+        sequence.polygon_motion_paths.push(AnimationData {
+            polygon_id: "polygon".to_string(),
+            properties: vec![position(400, 300)],
+            position: [100, 100],
+            ..Default::default()
+        });
+        sequence.camera_animation = Some(CameraAnimation {
+            properties: vec![position(200, 60)],
+        });
+
+        let mut emitter = SavedParticleEmitterConfig::from_preset(
+            ParticlePreset::Sparks,
+            "emitter".to_string(),
+            "Emitter".to_string(),
+            SavedPoint { x: 400, y: 300 },
+        );
+        emitter.dimensions = (200, 100);
+        emitter.particle_size = 8;
+        emitter.speed = 200;
+        emitter.gravity = 100;
+        sequence.active_particle_emitters.push(emitter);
+
+        sequence.active_callouts.push(SavedCalloutConfig {
+            id: "callout".to_string(),
+            box_id: "polygon".to_string(),
+            text_id: "text".to_string(),
+            arrow_id: "arrow".to_string(),
+            anchor: SavedPoint { x: 400, y: 300 },
+            thickness: 4,
+            head_size: 20,
+        });
+
+        sequence
+    }
+
+    fn keyframe_position(properties: &[AnimationProperty]) -> [i32; 2] {
+        match properties[0].keyframes[0].value {
+            KeyframeValue::Position(position) => position,
+            _ => panic!("Not a position keyframe"),
+        }
+    }
+
+    #[test]
+    fn rescaling_moves_every_kind_of_object() {
+        let mut sequence = sequence();
+        rescale_sequence(&mut sequence, FROM, TO);
+
+        let moved = SavedPoint { x: 200, y: 300 };
+        assert_eq!(sequence.active_polygons[0].position, moved);
+        assert_eq!(sequence.active_polygons[0].dimensions, (100, 50));
+        assert_eq!(sequence.active_text_items[0].position, moved);
+        assert_eq!(sequence.active_text_items[0].dimensions, (100, 50));
+        assert_eq!(sequence.active_text_items[0].font_size, 20);
+        assert_eq!(sequence.active_image_items[0].position, moved);
+        assert_eq!(sequence.active_image_items[0].dimensions, (100, 50));
+        assert_eq!(sequence.active_video_items[0].position, moved);
+        assert_eq!(sequence.active_video_items[0].dimensions, (100, 50));
+
+        let group = &sequence.active_groups[0];
+        assert_eq!(group.pivot, moved);
+        assert_eq!(group.position, SavedPoint { x: 300, y: 300 });
+
+        let emitter = &sequence.active_particle_emitters[0];
+        assert_eq!(emitter.position, moved);
+        assert_eq!(emitter.dimensions, (100, 50));
+        assert_eq!(
+            (emitter.particle_size, emitter.speed, emitter.gravity),
+            (4, 100, 50)
+        );
+
+        let callout = &sequence.active_callouts[0];
+        assert_eq!(callout.anchor, moved);
+        assert_eq!((callout.thickness, callout.head_size), (2, 10));
+
+        let animation = &sequence.polygon_motion_paths[0];
+        assert_eq!(animation.position, [50, 100]);
+        assert_eq!(keyframe_position(&animation.properties), [200, 300]);
+
+        let camera_animation = sequence.camera_animation.as_ref().unwrap();
+        assert_eq!(keyframe_position(&camera_animation.properties), [100, 60]);
+    }
+    #[test]
+    fn rescaling_moves_spotlight_regions() {
+        let settings = SpotlightSettings {
+            center: SavedPoint { x: 400, y: 300 },
+            size: (200, 100),
+            feather: 20,
+            ..Default::default()
+        };
+        let mut sequence = build_spotlight(&settings, "sequence".to_string(), 1000, FROM);
+        rescale_sequence(&mut sequence, FROM, TO);
+
+        let spotlight = &sequence.active_spotlights[0];
+        assert_eq!(spotlight.center, SavedPoint { x: 200, y: 300 });
+        assert_eq!((spotlight.size, spotlight.feather), ((100, 50), 10));
+
+        let region = sequence.polygon_motion_paths[0]
+            .properties
+            .iter()
+            .find(|p| p.property_path == "spotlight")
+            .unwrap();
+        for keyframe in &region.keyframes {
+            assert_eq!(
+                keyframe.value,
+                KeyframeValue::Custom(vec![200, 300, 100, 50, 10])
+            );
+        }
+    }
+}
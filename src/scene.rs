@@ -0,0 +1,2565 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use cgmath::Vector2;
+use uuid::Uuid;
+
+use crate::animated_image::AnimatedImageMode;
+use crate::animations::{
+    interpolate_color, sequence_time, AnimationPlan, BackgroundFill, KeyframeValue, ObjectType,
+    Sequence,
+};
+use crate::callout::{arrow_shape, leader_start};
+use crate::camera::{Camera3D as Camera, CameraBinding};
+use crate::capture::{MousePosition, SourceData};
+use crate::constraints::{evaluation_order, PinConstraint};
+use crate::editor::{
+    recorded_clicks, rgb_to_wgpu, BoundingBox, LoopRegion, Point, WindowSize, CANVAS_HORIZ_OFFSET,
+    CANVAS_TOP, CANVAS_VERT_OFFSET,
+};
+use crate::error::{parse_id, EditorError};
+use crate::fonts::FontManager;
+use crate::gpu_resources::GpuResources;
+use crate::group::{GroupChild, ObjectGroup};
+use crate::image_assets::ImageAssetCache;
+use crate::matting::{Matte, SegmentationModelFactory};
+use crate::outline::saved_to_points;
+use crate::particles::ParticleEmitter;
+use crate::polygon::{Polygon, Stroke};
+use crate::saved_state::CanvasSize;
+use crate::spotlight::{canvas_bounds, draw_spotlight, SavedSpotlightConfig, SpotlightRegion};
+use crate::st_image::{StImage, StImageConfig};
+use crate::st_video::{FrameSkipPolicy, StVideo, StVideoConfig, VideoPlaybackMode};
+use crate::text_due::{TextRenderer, TextRendererConfig};
+use crate::timelines::{ActiveTransition, SavedTimelineStateConfig, TrackType, TransitionKind};
+use crate::transform::create_empty_group_transform;
+use crate::video_prefetch::PrefetchSettings;
+use crate::widgets::update_widget_parts;
+
+/// What objects are made with, cloned out of the scene so objects can be added while it's held
+pub(crate) struct ObjectResources {
+    pub(crate) gpu_resources: Arc<GpuResources>,
+    pub(crate) camera: Camera,
+    pub(crate) model_bind_group_layout: Arc<wgpu::BindGroupLayout>,
+    pub(crate) group_bind_group_layout: Arc<wgpu::BindGroupLayout>,
+}
+
+/// What's drawn and played back: the objects, the GPU resources they're made with and the
+/// sequence playback state. The editor wraps one with its selection, handles and tools,
+/// and PreviewPlayer plays one on its own.
+pub struct Scene {
+    // objects
+    pub polygons: Vec<Polygon>,
+    pub static_polygons: Vec<Polygon>,
+    pub text_items: Vec<TextRenderer>,
+    pub image_items: Vec<StImage>,
+    pub image_assets: ImageAssetCache, // textures shared by image items, by path
+    pub font_manager: FontManager,
+    pub video_items: Vec<StVideo>,
+    pub groups: Vec<ObjectGroup>,
+    pub callout_endpoints: HashMap<String, [f32; 4]>, // arrows as last drawn, by callout id
+    pub spotlight_regions: HashMap<String, SpotlightRegion>, // regions as last drawn, by polygon id
+    pub particle_emitters: Vec<ParticleEmitter>,
+    pub segmentation_models: HashMap<String, SegmentationModelFactory>,
+
+    // rendering
+    pub gpu_resources: Option<Arc<GpuResources>>,
+    pub camera: Option<Camera>,
+    pub camera_binding: Option<CameraBinding>,
+    pub model_bind_group_layout: Option<Arc<wgpu::BindGroupLayout>>,
+    pub group_bind_group_layout: Option<Arc<wgpu::BindGroupLayout>>,
+    pub canvas_size: CanvasSize, // set from saved_state.canvas_size when opening a project
+
+    // playback
+    pub is_playing: bool,
+    pub current_sequence_data: Option<Sequence>,
+    pub animation_plan: Option<AnimationPlan>, // compiled from current_sequence_data
+    pub active_transition: Option<ActiveTransition>, // between video sequences during playback
+    pub last_frame_time: Option<Instant>,
+    pub start_playing_time: Option<Instant>,
+    pub video_is_playing: bool,
+    pub interpolate_video_frames: bool, // only while exporting, as it costs a frame of latency
+    pub frame_skip_policy: FrameSkipPolicy, // for videos without their own
+    pub video_prefetch: Option<PrefetchSettings>, // None decodes video frames on demand
+    pub use_video_proxies: bool,        // off for exports, which decode the originals
+    pub motion_blur_shift: Duration, // how far back motion blurred animations are sampled, set by exports
+    pub video_start_playing_time: Option<Instant>,
+    pub playback_rate: f32, // 1.0 is realtime
+    pub loop_region: Option<LoopRegion>,
+    pub last_playhead_s: f32, // to notice when a loop jumps back
+    pub video_current_sequence_timeline: Option<SavedTimelineStateConfig>,
+    pub video_current_sequences_data: Option<Vec<Sequence>>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Scene {
+            // objects
+            polygons: Vec::new(),
+            static_polygons: Vec::new(),
+            text_items: Vec::new(),
+            image_items: Vec::new(),
+            image_assets: ImageAssetCache::default(),
+            font_manager: FontManager::new(),
+            video_items: Vec::new(),
+            groups: Vec::new(),
+            callout_endpoints: HashMap::new(),
+            spotlight_regions: HashMap::new(),
+            particle_emitters: Vec::new(),
+            segmentation_models: HashMap::new(),
+
+            // rendering
+            gpu_resources: None,
+            camera: None,
+            camera_binding: None,
+            model_bind_group_layout: None,
+            group_bind_group_layout: None,
+            canvas_size: CanvasSize::default(),
+
+            // playback
+            is_playing: false,
+            current_sequence_data: None,
+            animation_plan: None,
+            active_transition: None,
+            last_frame_time: None,
+            start_playing_time: None,
+            video_is_playing: false,
+            interpolate_video_frames: false,
+            frame_skip_policy: FrameSkipPolicy::default(),
+            video_prefetch: None,
+            use_video_proxies: true,
+            motion_blur_shift: Duration::ZERO,
+            video_start_playing_time: None,
+            playback_rate: 1.0,
+            loop_region: None,
+            last_playhead_s: 0.0,
+            video_current_sequence_timeline: None,
+            video_current_sequences_data: None,
+        }
+    }
+
+    /// Creates the sequence's objects. Stops at the first one that can't be made, like an image
+    /// that's gone missing, while missing videos get a placeholder instead.
+    pub fn restore_sequence_objects(
+        &mut self,
+        saved_sequence: &Sequence,
+        // window_size: WindowSize,
+        // camera: &Camera,
+        hidden: bool,
+        // device: &wgpu::Device,
+        // queue: &wgpu::Queue,
+    ) -> Result<(), EditorError> {
+        let resources = self.object_resources()?;
+        let device = &resources.gpu_resources.device;
+        let queue = &resources.gpu_resources.queue;
+        let camera = &resources.camera;
+        let window_size = camera.window_size;
+        let sequence_id = parse_id(&saved_sequence.id)?;
+
+        for p in &saved_sequence.active_polygons {
+            // let gpu_resources = self
+            //     .gpu_resources
+            //     .as_ref()
+            //     .expect("Couldn't get GPU Resources");
+
+            // Generate a random number between 0 and 800
+            // let random_number_800 = rng.gen_range(0..=800);
+
+            // Generate a random number between 0 and 450
+            // let random_number_450 = rng.gen_range(0..=450);
+
+            let mut restored_polygon = Polygon::new(
+                &window_size,
+                &device,
+                &queue,
+                &resources.model_bind_group_layout,
+                &resources.group_bind_group_layout,
+                &camera,
+                // TODO: restoring triangles or non rectangles?
+                vec![
+                    Point { x: 0.0, y: 0.0 },
+                    Point { x: 1.0, y: 0.0 },
+                    Point { x: 1.0, y: 1.0 },
+                    Point { x: 0.0, y: 1.0 },
+                ],
+                (p.dimensions.0 as f32, p.dimensions.1 as f32),
+                Point {
+                    // x: random_number_800 as f32,
+                    // y: random_number_450 as f32,
+                    x: p.position.x as f32,
+                    y: p.position.y as f32,
+                },
+                // TODO: restore rotation?
+                0.0,
+                p.border_radius as f32,
+                [
+                    p.fill[0] as f32,
+                    p.fill[1] as f32,
+                    p.fill[2] as f32,
+                    p.fill[3] as f32,
+                ],
+                Stroke {
+                    thickness: p.stroke.thickness as f32,
+                    fill: [
+                        p.stroke.fill[0] as f32,
+                        p.stroke.fill[1] as f32,
+                        p.stroke.fill[2] as f32,
+                        p.stroke.fill[3] as f32,
+                    ],
+                },
+                // -2.0,
+                p.layer.clone(),
+                p.name.clone(),
+                parse_id(&p.id)?,
+                sequence_id,
+            );
+
+            // outlined glyphs and other non-rectangular shapes
+            if let Some(outline) = &p.outline {
+                restored_polygon.update_data_from_contours(
+                    &window_size,
+                    &device,
+                    &queue,
+                    &resources.model_bind_group_layout,
+                    saved_to_points(&outline.points),
+                    outline
+                        .contours
+                        .iter()
+                        .map(|c| saved_to_points(c))
+                        .collect(),
+                    &camera,
+                );
+            }
+
+            restored_polygon.hidden = hidden;
+            if p.opacity != 100 {
+                restored_polygon.update_base_opacity(&queue, p.opacity as f32 / 100.0);
+            }
+            restored_polygon.user_hidden = p.hidden;
+            restored_polygon.locked = p.locked;
+            restored_polygon.blend_mode = p.blend_mode;
+            restored_polygon.mask_target_id = p.mask_target_id.clone();
+            restored_polygon.mask_mode = p.mask_mode;
+
+            // editor.add_polygon(restored_polygon);
+            self.polygons.push(restored_polygon);
+
+            println!("Polygon restored...");
+        }
+
+        for t in &saved_sequence.active_text_items {
+            // let gpu_resources = self
+            //     .gpu_resources
+            //     .as_ref()
+            //     .expect("Couldn't get GPU Resources");
+
+            // TODO: save and restore chosen font
+
+            let position = Point {
+                x: CANVAS_HORIZ_OFFSET + t.position.x as f32,
+                y: CANVAS_VERT_OFFSET + t.position.y as f32,
+            };
+
+            let text_id = parse_id(&t.id)?;
+            let mut restored_text = TextRenderer::new(
+                &device,
+                &queue,
+                &resources.model_bind_group_layout,
+                &resources.group_bind_group_layout,
+                self.font_manager
+                    .get_font_by_name(&t.font_family)
+                    .ok_or_else(|| EditorError::FontNotFound(t.font_family.clone()))?,
+                &window_size,
+                t.text.clone(),
+                TextRendererConfig {
+                    id: text_id,
+                    name: t.name.clone(),
+                    text: t.text.clone(),
+                    font_family: t.font_family.clone(),
+                    dimensions: (t.dimensions.0 as f32, t.dimensions.1 as f32),
+                    position,
+                    layer: t.layer.clone(),
+                    color: t.color.clone(),
+                    font_size: t.font_size.clone(),
+                    background_fill: t.background_fill.unwrap_or([200, 200, 200, 255]),
+                },
+                text_id,
+                sequence_id,
+                camera,
+            );
+
+            restored_text.hidden = hidden;
+            restored_text.user_hidden = t.hidden;
+            restored_text.locked = t.locked;
+            restored_text.blend_mode = t.blend_mode;
+            restored_text.mask_target_id = t.mask_target_id.clone();
+            restored_text.mask_mode = t.mask_mode;
+            restored_text.warp = t.warp.clone();
+
+            restored_text.render_text(&device, &queue);
+
+            if t.opacity != 100 {
+                restored_text.update_base_opacity(&queue, t.opacity as f32 / 100.0);
+            }
+
+            // editor.add_polygon(restored_polygon);
+            self.text_items.push(restored_text);
+
+            println!("Text restored...");
+        }
+
+        // textures of images no longer shown
+        self.image_assets.release_unused();
+
+        for i in &saved_sequence.active_image_items {
+            // let gpu_resources = self
+            //     .gpu_resources
+            //     .as_ref()
+            //     .expect("Couldn't get GPU Resources");
+
+            let position = Point {
+                x: CANVAS_HORIZ_OFFSET + i.position.x as f32,
+                y: CANVAS_VERT_OFFSET + i.position.y as f32,
+            };
+
+            let image_config = StImageConfig {
+                id: i.id.clone(),
+                name: i.name.clone(),
+                dimensions: i.dimensions.clone(),
+                path: i.path.clone(),
+                position,
+                layer: i.layer.clone(),
+                nine_slice: i.nine_slice,
+            };
+
+            let mut restored_image = StImage::new(
+                &device,
+                &queue,
+                // string to Path
+                Path::new(&i.path),
+                image_config,
+                &window_size,
+                &resources.model_bind_group_layout,
+                &resources.group_bind_group_layout,
+                &mut self.image_assets,
+                -2.0,
+                i.id.clone(),
+                sequence_id,
+            )
+            .map_err(|reason| EditorError::Media {
+                path: i.path.clone(),
+                reason,
+            })?;
+
+            if i.warp.is_some() {
+                restored_image.update_warp(&device, &queue, i.warp.clone());
+            }
+
+            if i.max_resident_size.is_some() {
+                if let Err(e) = restored_image.update_max_resident_size(
+                    &device,
+                    &queue,
+                    &resources.model_bind_group_layout,
+                    &mut self.image_assets,
+                    i.max_resident_size,
+                ) {
+                    println!("Couldn't limit image residency: {}", e);
+                }
+            }
+
+            if i.animation_mode != AnimatedImageMode::Animate {
+                if let Err(e) = restored_image.update_animation_mode(
+                    &device,
+                    &queue,
+                    &resources.model_bind_group_layout,
+                    &mut self.image_assets,
+                    i.animation_mode,
+                ) {
+                    println!("Couldn't set image animation mode: {}", e);
+                }
+            }
+
+            restored_image.hidden = hidden;
+            if i.opacity != 100 {
+                restored_image.update_base_opacity(&queue, i.opacity as f32 / 100.0);
+            }
+            restored_image.user_hidden = i.hidden;
+            restored_image.locked = i.locked;
+            restored_image.blend_mode = i.blend_mode;
+            restored_image.mask_target_id = i.mask_target_id.clone();
+            restored_image.mask_mode = i.mask_mode;
+
+            // editor.add_polygon(restored_polygon);
+            self.image_items.push(restored_image);
+
+            println!("Image restored...");
+        }
+
+        for i in &saved_sequence.active_video_items {
+            // let mut saved_mouse_path = None;
+            let mut source_data_path = None;
+            let mut stored_mouse_positions = None;
+            if let Some(mouse_path) = &i.mouse_path {
+                let mut mouse_pathbuf = Path::new(&mouse_path).to_path_buf();
+                mouse_pathbuf.pop();
+                source_data_path = Some(mouse_pathbuf.join("sourceData.json"));
+
+                if let Ok(positions) = fs::read_to_string(mouse_path) {
+                    if let Ok(mouse_positions) =
+                        serde_json::from_str::<Vec<MousePosition>>(&positions)
+                    {
+                        // saved_mouse_path = Some(mouse_path);
+                        stored_mouse_positions = Some(mouse_positions);
+                    }
+                }
+            }
+
+            let mut stored_source_data = None;
+            if let Some(source_path) = &source_data_path {
+                if let Ok(source_data) = fs::read_to_string(source_path) {
+                    if let Ok(data) = serde_json::from_str::<SourceData>(&source_data) {
+                        stored_source_data = Some(data);
+                    }
+                }
+            }
+
+            println!(
+                "Restoring video source data... {:?} {:?}",
+                source_data_path, stored_source_data
+            );
+
+            let position = Point {
+                x: CANVAS_HORIZ_OFFSET + i.position.x as f32,
+                y: CANVAS_VERT_OFFSET + i.position.y as f32,
+            };
+
+            let video_config = StVideoConfig {
+                id: i.id.clone(),
+                name: i.name.clone(),
+                dimensions: i.dimensions.clone(),
+                path: i.path.clone(),
+                position,
+                layer: i.layer.clone(),
+                mouse_path: i.mouse_path.clone(),
+            };
+
+            let model_bind_group_layout = &resources.model_bind_group_layout;
+            let group_bind_group_layout = &resources.group_bind_group_layout;
+            let placeholder_font = &self.font_manager.font_data[0].1;
+
+            // preview from the proxy while it's there, otherwise the original
+            let proxy = i
+                .proxy
+                .clone()
+                .filter(|proxy| self.use_video_proxies && Path::new(&proxy.path).exists());
+            let decode_path = proxy.as_ref().map_or(&i.path, |proxy| &proxy.path);
+
+            // a missing or corrupt video shouldn't stop the rest of the project from loading
+            let mut restored_video = match StVideo::new(
+                &device,
+                &queue,
+                // string to Path
+                Path::new(decode_path),
+                video_config.clone(),
+                &window_size,
+                model_bind_group_layout,
+                group_bind_group_layout,
+                -2.0,
+                i.id.clone(),
+                sequence_id,
+            ) {
+                Ok(mut video) => {
+                    if let Some(proxy) = proxy {
+                        video.set_proxy(i.path.clone(), proxy);
+                    }
+                    video
+                }
+                Err(e) => StVideo::new_placeholder(
+                    &device,
+                    &queue,
+                    Path::new(&i.path),
+                    video_config,
+                    &window_size,
+                    model_bind_group_layout,
+                    group_bind_group_layout,
+                    i.id.clone(),
+                    sequence_id,
+                    e.to_string(),
+                    placeholder_font,
+                ),
+            };
+
+            restored_video.hidden = hidden;
+            if i.opacity != 100 {
+                restored_video.update_base_opacity(&queue, i.opacity as f32 / 100.0);
+            }
+            restored_video.update_frame_interpolation(i.frame_interpolation.clone());
+            restored_video.user_hidden = i.hidden;
+            restored_video.locked = i.locked;
+            restored_video.blend_mode = i.blend_mode;
+            restored_video.mask_target_id = i.mask_target_id.clone();
+            restored_video.mask_mode = i.mask_mode;
+            if i.stabilization.is_some() {
+                restored_video.update_stabilization(&queue, i.stabilization.clone());
+            }
+            restored_video.matte = i
+                .matte
+                .clone()
+                .map(|config| Matte::from_config(config, &self.segmentation_models));
+            restored_video.redactions = i.redactions.clone();
+            restored_video.trim = i.trim;
+            restored_video.speed = i.speed as f32 / 100.0;
+            restored_video.frame_skip_policy = i.frame_skip_policy;
+            restored_video.playback_mode = i.playback_mode;
+            if i.trim.is_some() {
+                if let Err(e) = restored_video.reset_playback() {
+                    restored_video.show_error(&queue, e.to_string(), placeholder_font);
+                }
+            }
+
+            // set window data from capture
+            restored_video.source_data = stored_source_data;
+
+            // set mouse positions
+            restored_video.mouse_positions = stored_mouse_positions;
+
+            if i.cursor.is_some() {
+                let clicks = recorded_clicks(i.mouse_path.as_deref());
+                if let Err(e) = restored_video.set_cursor(i.cursor.clone(), clicks) {
+                    println!("Couldn't restore cursor for video {}: {}", i.id, e);
+                }
+            }
+
+            // render 1 frame to provide preview image
+            if let Err(e) = restored_video.draw_video_frame(device, queue) {
+                restored_video.show_error(queue, e.to_string(), placeholder_font);
+            }
+
+            if let Some(settings) = self.video_prefetch {
+                restored_video.start_prefetch(settings);
+            }
+
+            // editor.add_polygon(restored_polygon);
+            self.video_items.push(restored_video);
+
+            println!("Video restored...");
+        }
+
+        for e in &saved_sequence.active_particle_emitters {
+            let mut restored_emitter = ParticleEmitter::new(
+                &device,
+                &queue,
+                &resources.model_bind_group_layout,
+                &resources.group_bind_group_layout,
+                &window_size,
+                e,
+                sequence_id,
+            );
+
+            restored_emitter.hidden = hidden;
+            restored_emitter.user_hidden = e.hidden;
+            restored_emitter.locked = e.locked;
+
+            self.particle_emitters.push(restored_emitter);
+
+            println!("Particle emitter restored...");
+        }
+
+        // groups come last so their children exist to be bound
+        let restored_groups: Vec<ObjectGroup> = saved_sequence
+            .active_groups
+            .iter()
+            .map(|g| {
+                let mut restored_group = ObjectGroup::new(
+                    &device,
+                    &queue,
+                    &resources.group_bind_group_layout,
+                    &window_size,
+                    g,
+                    sequence_id,
+                );
+
+                restored_group.hidden = hidden;
+
+                restored_group
+            })
+            .collect();
+
+        for restored_group in restored_groups {
+            self.bind_group_children(&restored_group.children, Some(&restored_group));
+            self.groups.push(restored_group);
+
+            println!("Group restored...");
+        }
+
+        self.draw_spotlights(&saved_sequence.active_spotlights);
+
+        Ok(())
+    }
+
+    /// Draws spotlight overlays at their last animated region, or their saved one
+    pub(crate) fn draw_spotlights(&mut self, spotlights: &[SavedSpotlightConfig]) {
+        let Some(gpu_resources) = self.gpu_resources.as_ref() else {
+            return;
+        };
+
+        for spotlight in spotlights {
+            let Some(polygon) = self
+                .polygons
+                .iter_mut()
+                .find(|p| p.id.to_string() == spotlight.id)
+            else {
+                continue;
+            };
+
+            let region = self
+                .spotlight_regions
+                .get(&spotlight.id)
+                .copied()
+                .unwrap_or_else(|| spotlight.region());
+            draw_spotlight(
+                polygon,
+                spotlight,
+                &region,
+                canvas_bounds(self.canvas_size),
+                &gpu_resources.device,
+                &gpu_resources.queue,
+            );
+        }
+    }
+
+    pub fn step_video_animations(
+        &mut self,
+        camera: &Camera,
+        provided_current_time_s: Option<f64>,
+    ) -> Result<(), EditorError> {
+        if !self.video_is_playing || self.video_current_sequence_timeline.is_none() {
+            return Ok(());
+        }
+
+        let now = std::time::Instant::now();
+        // let dt = if let Some(last_time) = self.last_frame_time {
+        //     (now - last_time).as_secs_f32()
+        // } else {
+        //     0.0
+        // };
+        // let dt = if let Some(provided_dt) = provided_dt {
+        //     provided_dt
+        // } else {
+        //     dt
+        // };
+        let total_dt = self.playhead_s(self.video_start_playing_time);
+        // self.last_frame_time = Some(now);
+
+        // undo last frame's slide, so the offsets don't build up
+        let previous_transition = self.active_transition.take();
+        if let Some(previous) = previous_transition.as_ref() {
+            let (outgoing_offset, incoming_offset) = previous.applied_offsets;
+            self.offset_sequence_objects(&previous.outgoing_sequence_id, -outgoing_offset);
+            self.offset_sequence_objects(&previous.incoming_sequence_id, -incoming_offset);
+        }
+
+        let sequence_timeline = self
+            .video_current_sequence_timeline
+            .as_ref()
+            .expect("Couldn't get current sequence timeline");
+
+        // Convert total_dt from seconds to milliseconds for comparison with timeline
+        let current_time_ms = if let Some(provided_current_time_s) = provided_current_time_s {
+            (provided_current_time_s * 1000.0) as i32
+        } else {
+            (total_dt * 1000.0) as i32
+        };
+
+        // Get the sequences data
+        let video_current_sequences_data = match self.video_current_sequences_data.as_ref() {
+            Some(data) => data,
+            None => return Ok(()),
+        };
+
+        // let mut elapsed = 0;
+        // let mut current_found = false;
+
+        let mut update_background = false;
+
+        if total_dt <= 1.0 / 60.0 {
+            println!("Update initial background...");
+            update_background = true;
+        }
+
+        // Iterate through timeline sequences in order
+        let mut previous_video_sequence_id: Option<&String> = None;
+        for ts in &sequence_timeline.timeline_sequences {
+            // Skip audio tracks as we're only handling video
+            if ts.track_type != TrackType::Video {
+                continue;
+            }
+
+            // slow?
+            let duration_ms = video_current_sequences_data
+                .iter()
+                .find(|s| s.id == ts.sequence_id)
+                .map(|s| s.duration_ms)
+                .unwrap_or(0);
+
+            // dynamic start times
+            // if let Some(current_sequence) = &self.current_sequence_data {
+            //     if !current_found {
+            //         elapsed = elapsed + ts.duration_ms;
+            //     }
+
+            //     if current_sequence.id == ts.sequence_id {
+            //         current_found = true;
+            //     }
+            // } else {
+            //     current_found = true;
+            // }
+
+            // if current_found {}
+            // Check if this sequence should be playing at the current time
+            if current_time_ms >= ts.start_time_ms
+                && current_time_ms < (ts.start_time_ms + duration_ms)
+            {
+                // Find the corresponding sequence data
+                if let Some(sequence) = video_current_sequences_data
+                    .iter()
+                    .find(|s| s.id == ts.sequence_id)
+                {
+                    // Calculate local time within this sequence
+                    let sequence_local_time = (current_time_ms - ts.start_time_ms) as f32 / 1000.0;
+
+                    if let (Some(transition), Some(outgoing_id)) =
+                        (&ts.transition_in, previous_video_sequence_id)
+                    {
+                        let elapsed_ms = current_time_ms - ts.start_time_ms;
+                        if elapsed_ms < transition.duration_ms {
+                            self.active_transition = Some(ActiveTransition {
+                                kind: transition.kind,
+                                outgoing_sequence_id: outgoing_id.clone(),
+                                incoming_sequence_id: ts.sequence_id.clone(),
+                                progress: elapsed_ms as f32 / transition.duration_ms as f32,
+                                applied_offsets: (0.0, 0.0),
+                            });
+                        }
+                    }
+
+                    if let Some(current_sequence) = &self.current_sequence_data {
+                        // need to somehow efficiently restore polygons for the sequence
+                        // Check id to avoid unnecessary cloning
+                        // plan is to preload with a hidden attribute or similar
+                        if sequence.id != current_sequence.id {
+                            self.current_sequence_data = Some(sequence.clone());
+                            // set hidden attribute on relevant objects
+                            let current_sequence_id = sequence.id.clone();
+
+                            for polygon in self.polygons.iter_mut() {
+                                if polygon.current_sequence_id.to_string() == current_sequence_id {
+                                    polygon.hidden = false;
+                                } else {
+                                    polygon.hidden = true;
+                                }
+                            }
+                            for text in self.text_items.iter_mut() {
+                                if text.current_sequence_id.to_string() == current_sequence_id {
+                                    text.hidden = false;
+                                } else {
+                                    text.hidden = true;
+                                }
+                            }
+                            for image in self.image_items.iter_mut() {
+                                if image.current_sequence_id.to_string() == current_sequence_id {
+                                    image.hidden = false;
+                                } else {
+                                    image.hidden = true;
+                                }
+                            }
+                            for video in self.video_items.iter_mut() {
+                                if video.current_sequence_id.to_string() == current_sequence_id {
+                                    video.hidden = false;
+                                } else {
+                                    video.hidden = true;
+                                }
+                            }
+                            for emitter in self.particle_emitters.iter_mut() {
+                                if emitter.current_sequence_id.to_string() == current_sequence_id {
+                                    emitter.hidden = false;
+                                    emitter.reset();
+                                } else {
+                                    emitter.hidden = true;
+                                }
+                            }
+
+                            update_background = true;
+                        }
+                    } else {
+                        self.current_sequence_data = Some(sequence.clone());
+                    }
+                }
+            }
+
+            previous_video_sequence_id = Some(&ts.sequence_id);
+        }
+
+        self.update_transition_visibility(previous_transition);
+
+        {
+            if update_background {
+                if let Some(current_sequence) = &self.current_sequence_data {
+                    match current_sequence.background_fill.as_ref() {
+                        Some(BackgroundFill::Color(fill)) => {
+                            self.replace_background(
+                                parse_id(&current_sequence.id)?,
+                                rgb_to_wgpu(
+                                    fill[0] as u8,
+                                    fill[1] as u8,
+                                    fill[2] as u8,
+                                    fill[3] as f32,
+                                ),
+                            )?;
+                        }
+                        _ => {
+                            println!("Not supported yet...");
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies the current transition to both sequences' objects,
+    /// call after step_motion_path_animations so keyframed opacity and position are blended too
+    pub fn step_sequence_transition(&mut self) {
+        let Some(transition) = self.active_transition.clone() else {
+            return;
+        };
+
+        if transition.kind == TransitionKind::Crossfade {
+            let (outgoing_opacity, incoming_opacity) = transition.opacities();
+            self.set_sequence_opacity(&transition.outgoing_sequence_id, outgoing_opacity);
+            self.set_sequence_opacity(&transition.incoming_sequence_id, incoming_opacity);
+        }
+
+        let offsets = transition.slide_offsets(self.canvas_size.width as f32);
+        self.offset_sequence_objects(&transition.outgoing_sequence_id, offsets.0);
+        self.offset_sequence_objects(&transition.incoming_sequence_id, offsets.1);
+        if let Some(active) = self.active_transition.as_mut() {
+            active.applied_offsets = offsets;
+        }
+    }
+
+    /// Keeps the outgoing sequence's objects shown while a transition runs,
+    /// then hides them and restores opacity once it's over
+    fn update_transition_visibility(&mut self, previous_transition: Option<ActiveTransition>) {
+        if let Some(transition) = self.active_transition.as_ref() {
+            let outgoing_id = transition.outgoing_sequence_id.clone();
+            self.set_sequence_hidden(&outgoing_id, false);
+            return;
+        }
+
+        let Some(previous) = previous_transition else {
+            return;
+        };
+
+        let still_current = self
+            .current_sequence_data
+            .as_ref()
+            .is_some_and(|s| s.id == previous.outgoing_sequence_id);
+        if !still_current {
+            self.set_sequence_hidden(&previous.outgoing_sequence_id, true);
+        }
+        if previous.kind == TransitionKind::Crossfade {
+            self.set_sequence_opacity(&previous.outgoing_sequence_id, 1.0);
+            self.set_sequence_opacity(&previous.incoming_sequence_id, 1.0);
+        }
+    }
+
+    fn set_sequence_hidden(&mut self, sequence_id: &str, hidden: bool) {
+        for polygon in self.polygons.iter_mut() {
+            if polygon.current_sequence_id.to_string() == sequence_id {
+                polygon.hidden = hidden;
+            }
+        }
+        for text in self.text_items.iter_mut() {
+            if text.current_sequence_id.to_string() == sequence_id {
+                text.hidden = hidden;
+            }
+        }
+        for image in self.image_items.iter_mut() {
+            if image.current_sequence_id.to_string() == sequence_id {
+                image.hidden = hidden;
+            }
+        }
+        for video in self.video_items.iter_mut() {
+            if video.current_sequence_id.to_string() == sequence_id {
+                video.hidden = hidden;
+            }
+        }
+        for emitter in self.particle_emitters.iter_mut() {
+            if emitter.current_sequence_id.to_string() == sequence_id {
+                emitter.hidden = hidden;
+            }
+        }
+    }
+
+    /// Multiplies the saved opacity of a sequence's objects by factor
+    fn set_sequence_opacity(&mut self, sequence_id: &str, factor: f32) {
+        let Some(sequence) = self
+            .video_current_sequences_data
+            .as_ref()
+            .and_then(|sequences| sequences.iter().find(|s| s.id == sequence_id))
+        else {
+            return;
+        };
+        let queue = &self
+            .gpu_resources
+            .as_ref()
+            .expect("Couldn't get gpu resources")
+            .queue;
+
+        for saved in sequence.active_polygons.iter() {
+            if let Some(polygon) = self
+                .polygons
+                .iter_mut()
+                .find(|p| p.id.to_string() == saved.id)
+            {
+                polygon.update_base_opacity(queue, saved.opacity as f32 / 100.0 * factor);
+            }
+        }
+        for saved in sequence.active_text_items.iter() {
+            if let Some(text) = self
+                .text_items
+                .iter_mut()
+                .find(|t| t.id.to_string() == saved.id)
+            {
+                text.update_base_opacity(queue, saved.opacity as f32 / 100.0 * factor);
+            }
+        }
+        for saved in sequence.active_image_items.iter() {
+            if let Some(image) = self.image_items.iter_mut().find(|i| i.id == saved.id) {
+                image.update_base_opacity(queue, saved.opacity as f32 / 100.0 * factor);
+            }
+        }
+        for saved in sequence.active_video_items.iter() {
+            if let Some(video) = self.video_items.iter_mut().find(|v| v.id == saved.id) {
+                video.update_base_opacity(queue, saved.opacity as f32 / 100.0 * factor);
+            }
+        }
+    }
+
+    fn offset_sequence_objects(&mut self, sequence_id: &str, dx: f32) {
+        if dx == 0.0 {
+            return;
+        }
+        let translation = Vector2::new(dx, 0.0);
+
+        for polygon in self.polygons.iter_mut() {
+            if polygon.current_sequence_id.to_string() == sequence_id {
+                polygon.transform.translate(translation);
+            }
+        }
+        for text in self.text_items.iter_mut() {
+            if text.current_sequence_id.to_string() == sequence_id {
+                text.transform.translate(translation);
+                text.background_polygon.transform.translate(translation);
+            }
+        }
+        for image in self.image_items.iter_mut() {
+            if image.current_sequence_id.to_string() == sequence_id {
+                image.transform.translate(translation);
+            }
+        }
+        for video in self.video_items.iter_mut() {
+            if video.current_sequence_id.to_string() == sequence_id {
+                video.transform.translate(translation);
+            }
+        }
+        for emitter in self.particle_emitters.iter_mut() {
+            if emitter.current_sequence_id.to_string() == sequence_id {
+                emitter.transform.translate(translation);
+            }
+        }
+    }
+
+    pub fn step_motion_path_animations(
+        &mut self,
+        camera: &Camera,
+        provided_current_time_s: Option<f64>,
+    ) -> Result<(), EditorError> {
+        if !self.is_playing || self.current_sequence_data.is_none() {
+            return Ok(());
+        }
+
+        // TODO: disable time based dt determination for export only
+        let now = std::time::Instant::now();
+        // let dt = if let Some(last_time) = self.last_frame_time {
+        //     (now - last_time).as_secs_f32()
+        // } else {
+        //     0.0
+        // };
+        let total_dt = self.playhead_s(self.start_playing_time);
+        let total_dt = if let Some(provided_current_time_s) = provided_current_time_s {
+            provided_current_time_s
+        } else {
+            total_dt as f64
+        };
+        self.last_frame_time = Some(now);
+
+        // video decoders only read forward, so rewind them when the loop jumps back
+        if (total_dt as f32) < self.last_playhead_s {
+            if let Err(e) = self.seek_sequence_videos(total_dt as f32) {
+                println!("Couldn't rewind videos for loop: {}", e);
+            }
+        }
+        self.last_playhead_s = total_dt as f32;
+
+        self.step_animate_sequence(total_dt as f32, camera)?;
+        self.step_particle_emitters(total_dt as f32);
+        self.step_animated_images(total_dt as f32);
+        self.step_camera_animation(total_dt as f32);
+
+        Ok(())
+    }
+
+    /// Seeks the current sequence's videos to the frames shown at a sequence time
+    pub(crate) fn seek_sequence_videos(&mut self, time_s: f32) -> Result<(), String> {
+        let gpu_resources = self
+            .gpu_resources
+            .clone()
+            .ok_or("Couldn't get gpu resources")?;
+        let Some(sequence) = self.current_sequence_data.as_ref() else {
+            return Ok(());
+        };
+
+        // video frames follow sequence time at the video's speed, as in step_animate_sequence
+        for animation in sequence
+            .polygon_motion_paths
+            .iter()
+            .filter(|a| a.object_type == ObjectType::VideoItem)
+        {
+            let Some(video) = self
+                .video_items
+                .iter_mut()
+                .find(|v| v.id == animation.polygon_id)
+            else {
+                continue;
+            };
+
+            let source_time = video.source_time(animation, Duration::from_secs_f32(time_s));
+            let frame_index = (source_time.as_secs_f64() * video.source_frame_rate).floor() as u32;
+            video
+                .seek_to_frame(&gpu_resources.queue, frame_index)
+                .map_err(|e| e.to_string())?;
+        }
+
+        Ok(())
+    }
+
+    /// Playback time since the start instant, at the playback rate and wrapped into the loop region
+    pub(crate) fn playhead_s(&self, start_playing_time: Option<Instant>) -> f32 {
+        let elapsed = start_playing_time.map_or(0.0, |start| start.elapsed().as_secs_f32());
+        let time_s = elapsed * self.playback_rate;
+
+        match self.loop_region {
+            Some(loop_region) => loop_region.wrap(time_s),
+            None => time_s,
+        }
+    }
+
+    /// Moves the camera along the sequence's camera keyframes, if it has any
+    pub fn step_camera_animation(&mut self, total_dt: f32) {
+        let Some(sequence) = self.current_sequence_data.as_ref() else {
+            return;
+        };
+        let Some(camera_animation) = sequence.camera_animation.as_ref() else {
+            return;
+        };
+        let current_time = sequence_time(total_dt, sequence.duration_ms);
+
+        let (pan, zoom) = camera_animation.framing_at(current_time);
+        let Some(camera) = self.camera.as_mut() else {
+            return;
+        };
+        camera.frame_canvas(pan, zoom);
+
+        self.update_camera_binding();
+    }
+
+    /// Emits and ages particles for the current playhead time, after keyframes set emission rates
+    pub fn step_particle_emitters(&mut self, total_dt: f32) {
+        let Some(sequence) = self.current_sequence_data.as_ref() else {
+            return;
+        };
+        let current_time_s = sequence_time(total_dt, sequence.duration_ms).as_secs_f32();
+
+        let queue = &self
+            .gpu_resources
+            .as_ref()
+            .expect("Couldn't get gpu resources")
+            .queue;
+
+        for emitter in self.particle_emitters.iter_mut().filter(|e| !e.hidden) {
+            emitter.update(queue, current_time_s);
+        }
+    }
+
+    /// Shows the frame of each animated GIF or WebP at the sequence time
+    pub fn step_animated_images(&mut self, total_dt: f32) {
+        let Some(sequence) = self.current_sequence_data.as_ref() else {
+            return;
+        };
+        let current_time_ms = sequence_time(total_dt, sequence.duration_ms).as_millis() as u32;
+
+        let queue = &self
+            .gpu_resources
+            .as_ref()
+            .expect("Couldn't get gpu resources")
+            .queue;
+
+        for st_image in self.image_items.iter_mut().filter(|i| !i.hidden) {
+            st_image.update_animation_frame(queue, current_time_ms);
+        }
+    }
+
+    /// Steps the currently selected sequence unless one is provided
+    /// TODO: make more efficient
+    /// Index of an object in its list, as animations refer to objects by id
+    fn find_object_index(&self, object_type: &ObjectType, id: &str) -> Option<usize> {
+        match object_type {
+            ObjectType::Polygon => self.polygons.iter().position(|p| p.id.to_string() == id),
+            ObjectType::TextItem => self.text_items.iter().position(|t| t.id.to_string() == id),
+            ObjectType::ImageItem => self.image_items.iter().position(|i| i.id.to_string() == id),
+            ObjectType::VideoItem => self.video_items.iter().position(|i| i.id.to_string() == id),
+            ObjectType::Group => self.groups.iter().position(|g| g.id.to_string() == id),
+            ObjectType::ParticleEmitter => self
+                .particle_emitters
+                .iter()
+                .position(|e| e.id.to_string() == id),
+        }
+    }
+
+    fn object_has_id(&self, object_type: &ObjectType, index: usize, id: &str) -> bool {
+        match object_type {
+            ObjectType::Polygon => self
+                .polygons
+                .get(index)
+                .is_some_and(|p| p.id.to_string() == id),
+            ObjectType::TextItem => self
+                .text_items
+                .get(index)
+                .is_some_and(|t| t.id.to_string() == id),
+            ObjectType::ImageItem => self
+                .image_items
+                .get(index)
+                .is_some_and(|i| i.id.to_string() == id),
+            ObjectType::VideoItem => self
+                .video_items
+                .get(index)
+                .is_some_and(|i| i.id.to_string() == id),
+            ObjectType::Group => self
+                .groups
+                .get(index)
+                .is_some_and(|g| g.id.to_string() == id),
+            ObjectType::ParticleEmitter => self
+                .particle_emitters
+                .get(index)
+                .is_some_and(|e| e.id.to_string() == id),
+        }
+    }
+
+    /// Compiles the current sequence's animation plan again if the sequence has been edited
+    /// since, which is checked by hashing its motion paths
+    pub fn refresh_animation_plan(&mut self) {
+        let Some(sequence) = self.current_sequence_data.as_ref() else {
+            self.animation_plan = None;
+            return;
+        };
+
+        if self
+            .animation_plan
+            .as_ref()
+            .is_some_and(|plan| plan.is_current(sequence))
+        {
+            return;
+        }
+
+        let plan = AnimationPlan::compile(sequence, |object_type, id| {
+            self.find_object_index(object_type, id)
+        });
+        self.animation_plan = Some(plan);
+    }
+
+    pub fn step_animate_sequence(
+        &mut self,
+        total_dt: f32,
+        camera: &Camera,
+    ) -> Result<(), EditorError> {
+        self.refresh_animation_plan();
+
+        let gpu_resources = self
+            .gpu_resources
+            .as_ref()
+            .ok_or(EditorError::NotInitialized("gpu resources"))?;
+        let sequence = self
+            .current_sequence_data
+            .as_ref()
+            .ok_or(EditorError::NoCurrentSequence)?;
+        // compiled from the current sequence
+        let plan = self
+            .animation_plan
+            .as_ref()
+            .ok_or(EditorError::NoCurrentSequence)?;
+
+        // animation index, object index, time into the animation, and video source time
+        let mut active: Vec<(usize, usize, Duration, Duration)> = Vec::new();
+
+        // Update each animation path
+        for (animation_index, (animation, compiled)) in sequence
+            .polygon_motion_paths
+            .iter()
+            .zip(&plan.animations)
+            .enumerate()
+        {
+            // Get current time within animation duration
+            let current_time = sequence_time(total_dt, sequence.duration_ms);
+            let start_time = Duration::from_millis(animation.start_time_ms as u64);
+
+            // Check if the current time is within the animation's active period
+            if current_time < start_time || current_time > start_time + animation.duration {
+                continue;
+            }
+
+            // Find the object to update, searching only when objects moved since compiling
+            let object_idx = compiled
+                .object_index
+                .filter(|idx| {
+                    self.object_has_id(&animation.object_type, *idx, &animation.polygon_id)
+                })
+                .or_else(|| self.find_object_index(&animation.object_type, &animation.polygon_id));
+
+            let Some(object_idx) = object_idx else {
+                continue;
+            };
+
+            // Determine whether to draw the video frame based on the frame rate and current time
+            // step rate is throttled to 60FPS
+            // if video frame rate is 60FPS, then call draw on each frame
+            // if video frame rate is 30FPS, then call draw on every other frame
+            let mut animate_properties = false;
+            // where a video is in its source, which runs ahead or behind at speeds other than 1x
+            let mut source_time = current_time;
+
+            if animation.object_type == ObjectType::VideoItem {
+                let frame_rate = self.video_items[object_idx].source_frame_rate;
+                let source_duration_ms = self.video_items[object_idx].playable_duration_ms();
+                let frame_interval = Duration::from_secs_f64(1.0 / frame_rate as f64);
+                source_time = self.video_items[object_idx].source_time(animation, current_time);
+                let playback_mode = self.video_items[object_idx].playback_mode;
+
+                // loops and ping-pong turn back, which decoders only reach by seeking
+                let target_frame = (source_time.as_secs_f64() * frame_rate).floor() as u32;
+                if playback_mode != VideoPlaybackMode::Once
+                    && target_frame + 1 < self.video_items[object_idx].num_frames_drawn
+                {
+                    if let Err(e) = self.video_items[object_idx]
+                        .seek_to_frame(&gpu_resources.queue, target_frame)
+                    {
+                        self.video_items[object_idx].show_error(
+                            &gpu_resources.queue,
+                            e.to_string(),
+                            &self.font_manager.font_data[0].1,
+                        );
+                    }
+                }
+
+                // Calculate the number of frames that should have been displayed by now
+                let elapsed_time: Duration = current_time - start_time;
+                let current_frame_time = self.video_items[object_idx].num_frames_drawn as f64
+                    * frame_interval.as_secs_f64();
+                // let last_frame_time = self.last_frame_time.expect("Couldn't get last frame time");
+
+                // println!(
+                //     "current times {:?} frame: {:?}",
+                //     current_time.as_secs_f64(),
+                //     current_frame_time
+                // );
+
+                // frames due by now, one when the current time is within the next frame's interval
+                let difference = source_time.as_secs_f64() - current_frame_time;
+                let frames_due = if difference >= 0.0 {
+                    (difference / frame_interval.as_secs_f64()).floor() as u32 + 1
+                } else {
+                    0
+                };
+
+                // skipping frames that are already decoded is cheap, so prefetched videos
+                // always catch up fully
+                let default_policy = if self.video_items[object_idx].prefetcher.is_some() {
+                    FrameSkipPolicy::SkipToLatest
+                } else {
+                    self.frame_skip_policy
+                };
+                let policy = self.video_items[object_idx]
+                    .frame_skip_policy
+                    .unwrap_or(default_policy);
+                let frames_to_decode = policy.frames_to_decode(frames_due);
+
+                // Only draw within the video duration, which repeating videos never leave
+                let within_video = playback_mode != VideoPlaybackMode::Once
+                    || source_time.as_millis() + 1000 < source_duration_ms as u128;
+                if frames_to_decode > 0 && within_video {
+                    for i in 0..frames_to_decode {
+                        // frames shown in the same step are never seen, so skip all but the last
+                        let skip = policy != FrameSkipPolicy::Never && i + 1 < frames_to_decode;
+                        let result = if skip {
+                            self.video_items[object_idx].skip_video_frame()
+                        } else {
+                            self.video_items[object_idx]
+                                .draw_video_frame(&gpu_resources.device, &gpu_resources.queue)
+                        };
+                        if let Err(e) = result {
+                            self.video_items[object_idx].show_error(
+                                &gpu_resources.queue,
+                                e.to_string(),
+                                &self.font_manager.font_data[0].1,
+                            );
+                            break;
+                        }
+
+                        self.video_items[object_idx].num_frames_drawn += 1;
+                        self.video_items[object_idx]
+                            .playback_stats
+                            .record_frame(skip);
+                    }
+
+                    animate_properties = true;
+                }
+
+                // how far the shown frame is behind playback, a frame or less is on time
+                let video_item = &mut self.video_items[object_idx];
+                let shown_frame_time = video_item.num_frames_drawn.saturating_sub(1) as f64
+                    * frame_interval.as_secs_f64();
+                let drift_ms = ((source_time.as_secs_f64() - shown_frame_time) * 1000.0
+                    - frame_interval.as_secs_f64() * 1000.0)
+                    .max(0.0) as f32;
+                video_item.playback_stats.record_drift(drift_ms);
+
+                // fill in the steps between source frames, running a frame behind the decoder
+                if self.interpolate_video_frames
+                    && self.video_items[object_idx].interpolator.is_some()
+                    && self.video_items[object_idx].num_frames_drawn > 0
+                {
+                    let latest_frame_time = (self.video_items[object_idx].num_frames_drawn - 1)
+                        as f64
+                        * frame_interval.as_secs_f64();
+                    let t = (source_time.as_secs_f64() - latest_frame_time)
+                        / frame_interval.as_secs_f64();
+
+                    self.video_items[object_idx]
+                        .draw_interpolated_frame(&gpu_resources.queue, t as f32);
+                }
+            } else {
+                animate_properties = true;
+            }
+
+            // let mut animate_properties = false;
+
+            // Modified video drawing code
+            // if animation.object_type == ObjectType::VideoItem {
+            //     let frame_rate = self.video_items[object_idx].source_frame_rate;
+            //     let source_duration_ms = self.video_items[object_idx].source_duration_ms;
+
+            //     // Initialize frame timer if not exists
+            //     if self.video_items[object_idx].frame_timer.is_none() {
+            //         self.video_items[object_idx].frame_timer = Some(FrameTimer::new());
+            //     }
+
+            //     // Get number of frames to draw this step
+            //     let frames_to_draw = self.video_items[object_idx]
+            //         .frame_timer
+            //         .as_mut()
+            //         .expect("Couldn't get frame timer")
+            //         .update_and_get_frames_to_draw(current_time, frame_rate as f32);
+
+            //     // Draw the required number of frames
+            //     if frames_to_draw > 0
+            //         && current_time.as_millis() + 1000 < source_duration_ms as u128
+            //     {
+            //         println!("frames_to_draw {:?}", frames_to_draw);
+            //         // Draw each frame
+            //         for _ in 0..frames_to_draw {
+            //             self.video_items[object_idx]
+            //                 .draw_video_frame(&gpu_resources.device, &gpu_resources.queue)
+            //                 .expect("Couldn't draw video frame");
+            //         }
+
+            //         animate_properties = true;
+            //     }
+            // }
+
+            if !animate_properties {
+                // a video waiting on its next frame holds back the animations after it
+                break;
+            }
+
+            // exports sample blurred animations a little in the past and average the frames
+            let blur_shift = self
+                .motion_blur_shift
+                .mul_f32(animation.motion_blur.clamp(0, 100) as f32 / 100.0);
+
+            active.push((
+                animation_index,
+                object_idx,
+                (current_time - start_time).saturating_sub(blur_shift),
+                source_time,
+            ));
+        }
+
+        // Interpolate the active objects' properties, in parallel for large scenes
+        let times: Vec<(usize, Duration)> = active
+            .iter()
+            .map(|(animation_index, _, time, _)| (*animation_index, *time))
+            .collect();
+        let samples = plan.sample_animations(&times);
+
+        // then apply them in one pass
+        for (&(animation_index, object_idx, _, source_time), tracks) in active.iter().zip(samples) {
+            let animation = &sequence.polygon_motion_paths[animation_index];
+
+            // Group transform position
+            let path_group_position = animation.position;
+
+            // Go through each property, tracks being in the same order
+            let sampled = animation.properties.iter().zip(tracks);
+            for (property, sample) in sampled.filter_map(|(p, s)| Some((p, s?))) {
+                let (start_frame, end_frame, progress) =
+                    (sample.start, sample.end, sample.progress);
+
+                // do not update a property when start and end are the same
+                // TODO: make this a setting for zooms so the center_point can continue its interpolation?
+                // if start_frame.value == end_frame.value {
+                //     continue;
+                // }
+
+                // Apply the interpolated value to the object's property
+                match (&start_frame.value, &end_frame.value) {
+                    (KeyframeValue::Position(start), KeyframeValue::Position(end)) => {
+                        let x = self.lerp(start[0], end[0], progress);
+                        let y = self.lerp(start[1], end[1], progress);
+
+                        let position = Point {
+                            x: CANVAS_HORIZ_OFFSET + x + path_group_position[0] as f32,
+                            y: CANVAS_VERT_OFFSET + y + path_group_position[1] as f32,
+                        };
+
+                        match animation.object_type {
+                            ObjectType::Polygon => {
+                                self.polygons[object_idx]
+                                    .transform
+                                    .update_position([position.x, position.y], &camera.window_size);
+                            }
+                            ObjectType::TextItem => {
+                                self.text_items[object_idx]
+                                    .transform
+                                    .update_position([position.x, position.y], &camera.window_size);
+                                self.text_items[object_idx]
+                                    .background_polygon
+                                    .transform
+                                    .update_position([position.x, position.y], &camera.window_size);
+                            }
+                            ObjectType::ImageItem => {
+                                self.image_items[object_idx]
+                                    .transform
+                                    .update_position([position.x, position.y], &camera.window_size);
+                            }
+                            ObjectType::VideoItem => {
+                                self.video_items[object_idx]
+                                    .transform
+                                    .update_position([position.x, position.y], &camera.window_size);
+                            }
+                            ObjectType::Group => {
+                                self.groups[object_idx]
+                                    .transform
+                                    .update_position([position.x, position.y], &camera.window_size);
+                                self.groups[object_idx].update_uniform_buffer(&gpu_resources.queue);
+                            }
+                            ObjectType::ParticleEmitter => {
+                                self.particle_emitters[object_idx]
+                                    .transform
+                                    .update_position([position.x, position.y], &camera.window_size);
+                            }
+                        }
+                    }
+                    (KeyframeValue::Rotation(start), KeyframeValue::Rotation(end)) => {
+                        // rotation is stored as degrees
+                        let new_rotation = self.lerp(*start, *end, progress);
+
+                        let new_rotation_rad = new_rotation.to_radians();
+
+                        match animation.object_type {
+                            ObjectType::Polygon => {
+                                self.polygons[object_idx]
+                                    .transform
+                                    .update_rotation(new_rotation_rad);
+                            }
+                            ObjectType::TextItem => {
+                                self.text_items[object_idx]
+                                    .transform
+                                    .update_rotation(new_rotation_rad);
+                                self.text_items[object_idx]
+                                    .background_polygon
+                                    .transform
+                                    .update_rotation(new_rotation_rad);
+                            }
+                            ObjectType::ImageItem => {
+                                self.image_items[object_idx]
+                                    .transform
+                                    .update_rotation(new_rotation_rad);
+                            }
+                            ObjectType::VideoItem => {
+                                self.video_items[object_idx]
+                                    .transform
+                                    .update_rotation(new_rotation_rad);
+                            }
+                            ObjectType::Group => {
+                                self.groups[object_idx]
+                                    .transform
+                                    .update_rotation(new_rotation_rad);
+                                self.groups[object_idx].update_uniform_buffer(&gpu_resources.queue);
+                            }
+                            ObjectType::ParticleEmitter => {
+                                self.particle_emitters[object_idx]
+                                    .transform
+                                    .update_rotation(new_rotation_rad);
+                            }
+                        }
+                    }
+                    (KeyframeValue::PerspectiveX(start), KeyframeValue::PerspectiveX(end)) => {
+                        // tilt is stored as degrees
+                        let tilt = self.lerp(*start, *end, progress).to_radians();
+
+                        match animation.object_type {
+                            ObjectType::Polygon => {
+                                self.polygons[object_idx].transform.update_rotation_x(tilt);
+                            }
+                            ObjectType::TextItem => {
+                                self.text_items[object_idx]
+                                    .transform
+                                    .update_rotation_x(tilt);
+                                self.text_items[object_idx]
+                                    .background_polygon
+                                    .transform
+                                    .update_rotation_x(tilt);
+                            }
+                            ObjectType::ImageItem => {
+                                self.image_items[object_idx]
+                                    .transform
+                                    .update_rotation_x(tilt);
+                            }
+                            ObjectType::VideoItem => {
+                                self.video_items[object_idx]
+                                    .transform
+                                    .update_rotation_x(tilt);
+                            }
+                            ObjectType::Group => {
+                                self.groups[object_idx].transform.update_rotation_x(tilt);
+                                self.groups[object_idx].update_uniform_buffer(&gpu_resources.queue);
+                            }
+                            ObjectType::ParticleEmitter => {
+                                self.particle_emitters[object_idx]
+                                    .transform
+                                    .update_rotation_x(tilt);
+                            }
+                        }
+                    }
+                    (KeyframeValue::PerspectiveY(start), KeyframeValue::PerspectiveY(end)) => {
+                        let tilt = self.lerp(*start, *end, progress).to_radians();
+
+                        match animation.object_type {
+                            ObjectType::Polygon => {
+                                self.polygons[object_idx].transform.update_rotation_y(tilt);
+                            }
+                            ObjectType::TextItem => {
+                                self.text_items[object_idx]
+                                    .transform
+                                    .update_rotation_y(tilt);
+                                self.text_items[object_idx]
+                                    .background_polygon
+                                    .transform
+                                    .update_rotation_y(tilt);
+                            }
+                            ObjectType::ImageItem => {
+                                self.image_items[object_idx]
+                                    .transform
+                                    .update_rotation_y(tilt);
+                            }
+                            ObjectType::VideoItem => {
+                                self.video_items[object_idx]
+                                    .transform
+                                    .update_rotation_y(tilt);
+                            }
+                            ObjectType::Group => {
+                                self.groups[object_idx].transform.update_rotation_y(tilt);
+                                self.groups[object_idx].update_uniform_buffer(&gpu_resources.queue);
+                            }
+                            ObjectType::ParticleEmitter => {
+                                self.particle_emitters[object_idx]
+                                    .transform
+                                    .update_rotation_y(tilt);
+                            }
+                        }
+                    }
+                    (
+                        KeyframeValue::Scale(_) | KeyframeValue::ScaleXY(_),
+                        KeyframeValue::Scale(_) | KeyframeValue::ScaleXY(_),
+                    ) => {
+                        // scale is stored out 100 (100 being standard size, ie. 100%), uniform
+                        // and per-axis keyframes blending into each other
+                        let [start, end] = [&start_frame.value, &end_frame.value]
+                            .map(|v| v.axis_scales().unwrap_or([100, 100]));
+                        let new_scale = [
+                            self.lerp(start[0], end[0], progress) / 100.0,
+                            self.lerp(start[1], end[1], progress) / 100.0,
+                        ];
+
+                        // TODO: verify scale on all objects as some treat it differently as-is
+
+                        match animation.object_type {
+                            ObjectType::Polygon => {
+                                self.polygons[object_idx].transform.update_scale(new_scale);
+                            }
+                            ObjectType::TextItem => {
+                                self.text_items[object_idx]
+                                    .transform
+                                    .update_scale(new_scale);
+                                self.text_items[object_idx]
+                                    .background_polygon
+                                    .transform
+                                    .update_scale(new_scale);
+                            }
+                            ObjectType::ImageItem => {
+                                let original_scale = self.image_items[object_idx].dimensions;
+                                self.image_items[object_idx].transform.update_scale([
+                                    original_scale.0 as f32 * new_scale[0],
+                                    original_scale.1 as f32 * new_scale[1],
+                                ]);
+                                self.image_items[object_idx]
+                                    .update_nine_slice_mesh(&gpu_resources.queue);
+                            }
+                            ObjectType::VideoItem => {
+                                let original_scale = self.video_items[object_idx].dimensions;
+                                self.video_items[object_idx].transform.update_scale([
+                                    original_scale.0 as f32 * new_scale[0],
+                                    original_scale.1 as f32 * new_scale[1],
+                                ]);
+                            }
+                            ObjectType::Group => {
+                                self.groups[object_idx].transform.update_scale(new_scale);
+                                self.groups[object_idx].update_uniform_buffer(&gpu_resources.queue);
+                            }
+                            ObjectType::ParticleEmitter => {
+                                self.particle_emitters[object_idx]
+                                    .transform
+                                    .update_scale(new_scale);
+                            }
+                        }
+                    }
+                    (KeyframeValue::Opacity(start), KeyframeValue::Opacity(end)) => {
+                        // opacity is out 100 (100%)
+                        let opacity = self.lerp(*start, *end, progress) / 100.0;
+
+                        let gpu_resources = self
+                            .gpu_resources
+                            .as_ref()
+                            .expect("Couldn't get gpu resources");
+
+                        match animation.object_type {
+                            ObjectType::Polygon => {
+                                let spotlight = sequence
+                                    .active_spotlights
+                                    .iter()
+                                    .find(|s| s.id == animation.polygon_id);
+                                if let Some(spotlight) = spotlight {
+                                    // flat colors would fill the cutout
+                                    let polygon = &mut self.polygons[object_idx];
+                                    polygon.animated_opacity = opacity;
+                                    let region = self
+                                        .spotlight_regions
+                                        .get(&spotlight.id)
+                                        .copied()
+                                        .unwrap_or_else(|| spotlight.region());
+                                    draw_spotlight(
+                                        polygon,
+                                        spotlight,
+                                        &region,
+                                        canvas_bounds(self.canvas_size),
+                                        &gpu_resources.device,
+                                        &gpu_resources.queue,
+                                    );
+                                } else {
+                                    self.polygons[object_idx]
+                                        .update_opacity(&gpu_resources.queue, opacity);
+                                }
+                            }
+                            ObjectType::TextItem => {
+                                self.text_items[object_idx]
+                                    .update_opacity(&gpu_resources.queue, opacity);
+                                self.text_items[object_idx]
+                                    .background_polygon
+                                    .update_opacity(&gpu_resources.queue, opacity);
+                            }
+                            ObjectType::ImageItem => {
+                                self.image_items[object_idx]
+                                    .update_opacity(&gpu_resources.queue, opacity);
+                            }
+                            ObjectType::VideoItem => {
+                                self.video_items[object_idx]
+                                    .update_opacity(&gpu_resources.queue, opacity);
+                            }
+                            ObjectType::Group => {
+                                // groups have no fill of their own, fade the children instead
+                                for child in &self.groups[object_idx].children {
+                                    match child.object_type {
+                                        ObjectType::Polygon => {
+                                            if let Some(polygon) = self
+                                                .polygons
+                                                .iter_mut()
+                                                .find(|p| p.id.to_string() == child.object_id)
+                                            {
+                                                polygon
+                                                    .update_opacity(&gpu_resources.queue, opacity);
+                                            }
+                                        }
+                                        ObjectType::TextItem => {
+                                            if let Some(text) = self
+                                                .text_items
+                                                .iter_mut()
+                                                .find(|t| t.id.to_string() == child.object_id)
+                                            {
+                                                text.update_opacity(&gpu_resources.queue, opacity);
+                                                text.background_polygon
+                                                    .update_opacity(&gpu_resources.queue, opacity);
+                                            }
+                                        }
+                                        ObjectType::ImageItem => {
+                                            if let Some(image) = self
+                                                .image_items
+                                                .iter_mut()
+                                                .find(|i| i.id == child.object_id)
+                                            {
+                                                image.update_opacity(&gpu_resources.queue, opacity);
+                                            }
+                                        }
+                                        ObjectType::VideoItem => {
+                                            if let Some(video) = self
+                                                .video_items
+                                                .iter_mut()
+                                                .find(|v| v.id == child.object_id)
+                                            {
+                                                video.update_opacity(&gpu_resources.queue, opacity);
+                                            }
+                                        }
+                                        ObjectType::ParticleEmitter => {
+                                            if let Some(emitter) = self
+                                                .particle_emitters
+                                                .iter_mut()
+                                                .find(|e| e.id.to_string() == child.object_id)
+                                            {
+                                                emitter.update_opacity(opacity);
+                                            }
+                                        }
+                                        ObjectType::Group => {}
+                                    }
+                                }
+                            }
+                            ObjectType::ParticleEmitter => {
+                                self.particle_emitters[object_idx].update_opacity(opacity);
+                            }
+                        }
+                    }
+                    (KeyframeValue::Zoom(start), KeyframeValue::Zoom(end)) => {
+                        let zoom = self.lerp(*start, *end, progress) / 100.0;
+
+                        let gpu_resources = self
+                            .gpu_resources
+                            .as_ref()
+                            .expect("Couldn't get gpu resources");
+
+                        match animation.object_type {
+                            ObjectType::VideoItem => {
+                                let video_item = &mut self.video_items[object_idx];
+                                // mouse positions are timed against the whole recording
+                                let elapsed_ms = source_time.as_millis() as u128
+                                    + video_item.trim_start_ms() as u128;
+
+                                let autofollow_delay = 150;
+
+                                if let (Some(mouse_positions), Some(source_data)) = (
+                                    video_item.mouse_positions.as_ref(),
+                                    video_item.source_data.as_ref(),
+                                ) {
+                                    // Check if we need to update the shift points
+                                    let should_update_shift = match video_item.last_shift_time {
+                                        Some(last_shift_time) => {
+                                            elapsed_ms - last_shift_time > autofollow_delay
+                                        }
+                                        None => {
+                                            video_item.last_shift_time = Some(elapsed_ms);
+
+                                            if let Some((start_point, end_point)) = mouse_positions
+                                                .iter()
+                                                .filter(|p| p.timestamp >= elapsed_ms)
+                                                .zip(mouse_positions.iter().filter(|p| {
+                                                    p.timestamp >= elapsed_ms + autofollow_delay
+                                                }))
+                                                .next()
+                                                .map(|(start, end)| {
+                                                    ((*start).clone(), (*end).clone())
+                                                })
+                                            {
+                                                video_item.last_start_point = Some(start_point);
+                                                video_item.last_end_point = Some(end_point);
+                                            }
+
+                                            false
+                                        }
+                                    };
+
+                                    let delay_offset = 500; // Potential time offset for a consistent lag
+                                    let min_distance = 100.0; // Distance to incur a shift
+                                    let base_alpha = 0.01; // Your current default value
+                                    let max_alpha = 0.1; // Maximum blending speed
+                                    let scaling_factor = 0.01; // Controls how quickly alpha increases with distance
+
+                                    // Update shift points if needed
+                                    if should_update_shift {
+                                        if let Some((start_point, end_point)) = mouse_positions
+                                            .iter()
+                                            .filter(|p| {
+                                                p.timestamp
+                                                    >= (elapsed_ms - autofollow_delay)
+                                                        + delay_offset
+                                                    && p.timestamp
+                                                        < video_item.source_duration_ms as u128
+                                            })
+                                            .zip(mouse_positions.iter().filter(|p| {
+                                                p.timestamp >= elapsed_ms + delay_offset
+                                                    && p.timestamp
+                                                        < video_item.source_duration_ms as u128
+                                            }))
+                                            .next()
+                                            .map(|(start, end)| ((*start).clone(), (*end).clone()))
+                                        {
+                                            if let Some(last_start_point) =
+                                                video_item.last_start_point
+                                            {
+                                                if let Some(last_end_point) =
+                                                    video_item.last_end_point
+                                                {
+                                                    let dx = start_point.x - last_start_point.x;
+                                                    let dy = start_point.y - last_start_point.y;
+                                                    let distance = (dx * dx + dy * dy).sqrt(); // Euclidean distance
+
+                                                    let dx2 = end_point.x - last_end_point.x;
+                                                    let dy2 = end_point.y - last_end_point.y;
+                                                    let distance2 = (dx2 * dx2 + dy2 * dy2).sqrt(); // Euclidean distance
+
+                                                    if distance >= min_distance
+                                                        || distance2 >= min_distance
+                                                    {
+                                                        video_item.last_shift_time =
+                                                            Some(elapsed_ms);
+
+                                                        video_item.last_start_point =
+                                                            Some(start_point);
+                                                        video_item.last_end_point = Some(end_point);
+
+                                                        // Use the larger of the two distances
+                                                        let max_distance = distance.max(distance2);
+
+                                                        // Exponential smoothing that plateaus
+                                                        let dynamic_alpha = base_alpha
+                                                            + (max_alpha - base_alpha)
+                                                                * (1.0
+                                                                    - (-scaling_factor
+                                                                        * max_distance)
+                                                                        .exp());
+
+                                                        video_item.dynamic_alpha = dynamic_alpha;
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+
+                                    // Always interpolate between the current shift points
+                                    if let (Some(start), Some(end)) =
+                                        (&video_item.last_start_point, &video_item.last_end_point)
+                                    {
+                                        let clamped_elapsed_ms =
+                                            elapsed_ms.clamp(start.timestamp, end.timestamp);
+
+                                        let time_progress = (clamped_elapsed_ms - start.timestamp)
+                                            as f32
+                                            / (end.timestamp - start.timestamp) as f32;
+
+                                        let interpolated_x =
+                                            start.x + (end.x - start.x) * time_progress;
+                                        let interpolated_y =
+                                            start.y + (end.y - start.y) * time_progress;
+
+                                        let dimensions = video_item.dimensions;
+                                        let source_dimensions = video_item.source_dimensions;
+
+                                        let new_center_point = Point {
+                                            x: ((interpolated_x - source_data.x as f32)
+                                                / source_dimensions.0 as f32)
+                                                * dimensions.0 as f32,
+                                            y: ((interpolated_y - source_data.y as f32)
+                                                / source_dimensions.1 as f32)
+                                                * dimensions.1 as f32,
+                                        };
+
+                                        // Smooth transition with existing center point
+                                        let blended_center_point = if let Some(last_center_point) =
+                                            video_item.last_center_point
+                                        {
+                                            // need to calculate a dynamic alpha based on distance between start and and end point
+                                            // let alpha = 0.01; // this was a close value, but not quite right depending on distance
+                                            let alpha = video_item.dynamic_alpha;
+
+                                            Point {
+                                                x: last_center_point.x * (1.0 - alpha)
+                                                    + new_center_point.x * alpha,
+                                                y: last_center_point.y * (1.0 - alpha)
+                                                    + new_center_point.y * alpha,
+                                            }
+                                        } else {
+                                            new_center_point
+                                        };
+
+                                        video_item.update_zoom(
+                                            &gpu_resources.queue,
+                                            zoom,
+                                            blended_center_point,
+                                        );
+                                        video_item.last_center_point = Some(blended_center_point);
+
+                                        // video_item.update_popout(
+                                        //     &gpu_resources.queue,
+                                        //     blended_center_point,
+                                        //     1.5,
+                                        //     (200.0, 200.0),
+                                        // );
+                                    }
+                                }
+                            }
+                            _ => {
+                                // println!("Zoom not supported here");
+                            }
+                        }
+                    }
+                    (KeyframeValue::Warp(start), KeyframeValue::Warp(end)) => {
+                        let warp = start.lerp(end, progress);
+
+                        let gpu_resources = self
+                            .gpu_resources
+                            .as_ref()
+                            .expect("Couldn't get gpu resources");
+
+                        match animation.object_type {
+                            ObjectType::TextItem => {
+                                self.text_items[object_idx].update_warp(
+                                    &gpu_resources.device,
+                                    &gpu_resources.queue,
+                                    Some(warp),
+                                );
+                            }
+                            ObjectType::ImageItem => {
+                                self.image_items[object_idx].update_warp(
+                                    &gpu_resources.device,
+                                    &gpu_resources.queue,
+                                    Some(warp),
+                                );
+                            }
+                            _ => {
+                                // println!("Warp not supported here");
+                            }
+                        }
+                    }
+                    (KeyframeValue::EmissionRate(start), KeyframeValue::EmissionRate(end)) => {
+                        let rate = self.lerp(*start, *end, progress);
+
+                        if animation.object_type == ObjectType::ParticleEmitter {
+                            self.particle_emitters[object_idx].rate = rate;
+                        }
+                    }
+                    (KeyframeValue::Color(start, interpolation), KeyframeValue::Color(end, _)) => {
+                        let color = interpolate_color(*start, *end, progress, *interpolation);
+                        let wgpu_color =
+                            rgb_to_wgpu(color[0] as u8, color[1] as u8, color[2] as u8, color[3]);
+
+                        match (&animation.object_type, property.property_path.as_str()) {
+                            (ObjectType::Polygon, "fill") => {
+                                let polygon = &mut self.polygons[object_idx];
+                                let stroke_fill = polygon.stroke.fill;
+                                polygon.update_colors(
+                                    &gpu_resources.queue,
+                                    wgpu_color,
+                                    stroke_fill,
+                                );
+                            }
+                            (ObjectType::Polygon, "stroke") => {
+                                let polygon = &mut self.polygons[object_idx];
+                                let fill = polygon.fill;
+                                polygon.update_colors(&gpu_resources.queue, fill, wgpu_color);
+                            }
+                            (ObjectType::TextItem, "color") => {
+                                self.text_items[object_idx].update_color(
+                                    &gpu_resources.queue,
+                                    color.map(|c| c.round() as i32),
+                                );
+                            }
+                            (ObjectType::TextItem, "background_fill") => {
+                                let background =
+                                    &mut self.text_items[object_idx].background_polygon;
+                                let stroke_fill = background.stroke.fill;
+                                background.update_colors(
+                                    &gpu_resources.queue,
+                                    wgpu_color,
+                                    stroke_fill,
+                                );
+                            }
+                            _ => {
+                                // println!("Color not supported here");
+                            }
+                        }
+                    }
+                    (KeyframeValue::Percent(start), KeyframeValue::Percent(end)) => {
+                        let percent = self.lerp(*start, *end, progress);
+
+                        if animation.object_type == ObjectType::Group {
+                            let group = &mut self.groups[object_idx];
+                            // reshaping rebuilds buffers, so only when the value moves
+                            if group.widget_percent != Some(percent) {
+                                if let Some(widget) = &group.widget {
+                                    update_widget_parts(
+                                        widget,
+                                        percent,
+                                        &mut self.polygons,
+                                        &mut self.text_items,
+                                        &gpu_resources.device,
+                                        &gpu_resources.queue,
+                                        self.model_bind_group_layout
+                                            .as_ref()
+                                            .expect("Couldn't get model bind group layout"),
+                                        camera,
+                                    );
+                                    group.widget_percent = Some(percent);
+                                }
+                            }
+                        }
+                    }
+                    (KeyframeValue::Custom(start), KeyframeValue::Custom(end))
+                        if start.len() == end.len() =>
+                    {
+                        let values: Vec<f32> = start
+                            .iter()
+                            .zip(end.iter())
+                            .map(|(start, end)| self.lerp(*start, *end, progress))
+                            .collect();
+
+                        let spotlight = sequence
+                            .active_spotlights
+                            .iter()
+                            .filter(|_| animation.object_type == ObjectType::Polygon)
+                            .find(|s| s.id == animation.polygon_id);
+                        if let (Some(spotlight), Some(region)) =
+                            (spotlight, SpotlightRegion::from_custom(&values))
+                        {
+                            // rewriting the mesh every frame is wasted on a still region
+                            if self.spotlight_regions.get(&spotlight.id) != Some(&region) {
+                                draw_spotlight(
+                                    &mut self.polygons[object_idx],
+                                    spotlight,
+                                    &region,
+                                    canvas_bounds(self.canvas_size),
+                                    &gpu_resources.device,
+                                    &gpu_resources.queue,
+                                );
+                                self.spotlight_regions.insert(spotlight.id.clone(), region);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        self.apply_pin_constraints();
+        self.sync_callouts();
+
+        Ok(())
+    }
+
+    pub fn lerp(&self, start: i32, end: i32, progress: f32) -> f32 {
+        start as f32 + ((end - start) as f32 * progress)
+    }
+
+    pub fn update_camera_binding(&mut self) {
+        if self.camera_binding.is_some() {
+            let gpu_resources = self
+                .gpu_resources
+                .as_ref()
+                .expect("Couldn't get gpu resources");
+
+            self.camera_binding
+                .as_mut()
+                .expect("Couldn't get camera binding")
+                .update_3d(
+                    &gpu_resources.queue,
+                    &self.camera.as_ref().expect("Couldn't get camera"),
+                );
+        }
+    }
+
+    pub(crate) fn object_resources(&self) -> Result<ObjectResources, EditorError> {
+        Ok(ObjectResources {
+            gpu_resources: self
+                .gpu_resources
+                .clone()
+                .ok_or(EditorError::NotInitialized("gpu resources"))?,
+            camera: self.camera.ok_or(EditorError::NotInitialized("camera"))?,
+            model_bind_group_layout: self
+                .model_bind_group_layout
+                .clone()
+                .ok_or(EditorError::NotInitialized("model bind group layout"))?,
+            group_bind_group_layout: self
+                .group_bind_group_layout
+                .clone()
+                .ok_or(EditorError::NotInitialized("group bind group layout"))?,
+        })
+    }
+
+    pub fn replace_background(
+        &mut self,
+        sequence_id: Uuid,
+        fill: [f32; 4],
+    ) -> Result<(), EditorError> {
+        println!("replace background {:?} {:?}", sequence_id, fill);
+
+        let resources = self.object_resources()?;
+        let camera = &resources.camera;
+        let window_size = camera.window_size;
+        let model_bind_group_layout = &resources.model_bind_group_layout;
+        let group_bind_group_layout = &resources.group_bind_group_layout;
+
+        // Remove existing background
+        self.static_polygons
+            .retain(|p| p.name != "canvas_background");
+
+        let gpu_resources = &resources.gpu_resources;
+
+        let canvas_polygon = Polygon::new(
+            &window_size,
+            &gpu_resources.device,
+            &gpu_resources.queue,
+            &model_bind_group_layout,
+            &group_bind_group_layout,
+            &camera,
+            vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 1.0, y: 0.0 },
+                Point { x: 1.0, y: 1.0 },
+                Point { x: 0.0, y: 1.0 },
+            ],
+            // (800.0 as f32, 450.0 as f32),
+            // Point { x: 400.0, y: 225.0 },
+            (
+                self.canvas_size.width as f32,
+                self.canvas_size.height as f32,
+            ),
+            Point {
+                x: self.canvas_size.width as f32 / 2.0,
+                y: CANVAS_TOP + self.canvas_size.height as f32 / 2.0,
+            },
+            0.0,
+            0.0,
+            fill,
+            Stroke {
+                thickness: 0.0,
+                fill: rgb_to_wgpu(0, 0, 0, 255.0),
+            },
+            // 0.0,
+            1, // camera far is -100
+            "canvas_background".to_string(),
+            sequence_id,
+            Uuid::nil(),
+        );
+
+        self.static_polygons.push(canvas_polygon);
+
+        Ok(())
+    }
+
+    /// Bounds of the canvas, taken from the background polygon
+    pub fn get_canvas_bounding_box(&self) -> Option<BoundingBox> {
+        self.static_polygons
+            .iter()
+            .find(|p| p.name == "canvas_background")
+            .map(|p| {
+                let pos = p.transform.position; // This is center position
+                let half_width = p.dimensions.0 / 2.0;
+                let half_height = p.dimensions.1 / 2.0;
+                BoundingBox {
+                    min: Point {
+                        x: pos.x - half_width,
+                        y: pos.y - half_height,
+                    },
+                    max: Point {
+                        x: pos.x + half_width,
+                        y: pos.y + half_height,
+                    },
+                }
+            })
+    }
+
+    /// Moves pinned objects to their targets, targets that are pinned themselves first
+    pub(crate) fn apply_pin_constraints(&mut self) {
+        let Some(sequence) = self.current_sequence_data.as_ref() else {
+            return;
+        };
+        if sequence.constraints.is_empty() {
+            return;
+        }
+        let Some(window_size) = self.camera.as_ref().map(|c| c.window_size) else {
+            return;
+        };
+
+        let constraints: Vec<PinConstraint> = evaluation_order(&sequence.constraints)
+            .into_iter()
+            .cloned()
+            .collect();
+        for constraint in constraints {
+            let (Ok(object_id), Ok(target_id)) = (
+                Uuid::from_str(&constraint.object_id),
+                Uuid::from_str(&constraint.target_id),
+            ) else {
+                continue;
+            };
+            // targets that were deleted leave their pins idle
+            let Some(target) = self.object_position(target_id, &constraint.target_type) else {
+                continue;
+            };
+
+            let position = Point {
+                x: target.x + constraint.offset[0] as f32,
+                y: target.y + constraint.offset[1] as f32,
+            };
+            self.place_object(object_id, &constraint.object_type, position, &window_size);
+        }
+    }
+
+    /// Where an object's transform puts it on the canvas
+    pub(crate) fn object_position(
+        &self,
+        object_id: Uuid,
+        object_type: &ObjectType,
+    ) -> Option<Point> {
+        let position = match object_type {
+            ObjectType::Polygon => {
+                self.polygons
+                    .iter()
+                    .find(|p| p.id == object_id)?
+                    .transform
+                    .position
+            }
+            ObjectType::TextItem => {
+                self.text_items
+                    .iter()
+                    .find(|t| t.id == object_id)?
+                    .transform
+                    .position
+            }
+            ObjectType::ImageItem => {
+                self.image_items
+                    .iter()
+                    .find(|i| i.id == object_id.to_string())?
+                    .transform
+                    .position
+            }
+            ObjectType::VideoItem => {
+                self.video_items
+                    .iter()
+                    .find(|v| v.id == object_id.to_string())?
+                    .transform
+                    .position
+            }
+            ObjectType::Group => {
+                self.groups
+                    .iter()
+                    .find(|g| g.id == object_id)?
+                    .transform
+                    .position
+            }
+            ObjectType::ParticleEmitter => {
+                self.particle_emitters
+                    .iter()
+                    .find(|e| e.id == object_id)?
+                    .transform
+                    .position
+            }
+        };
+
+        Some(Point {
+            x: position.x,
+            y: position.y,
+        })
+    }
+
+    /// Moves just the object to a canvas position, unlike translate_object leaving its
+    /// motion path and handles, as playback does
+    fn place_object(
+        &mut self,
+        object_id: Uuid,
+        object_type: &ObjectType,
+        position: Point,
+        window_size: &WindowSize,
+    ) {
+        let position = [position.x, position.y];
+
+        match object_type {
+            ObjectType::Polygon => {
+                if let Some(polygon) = self.polygons.iter_mut().find(|p| p.id == object_id) {
+                    polygon.transform.update_position(position, window_size);
+                }
+            }
+            ObjectType::TextItem => {
+                if let Some(text_item) = self.text_items.iter_mut().find(|t| t.id == object_id) {
+                    text_item.transform.update_position(position, window_size);
+                    text_item
+                        .background_polygon
+                        .transform
+                        .update_position(position, window_size);
+                }
+            }
+            ObjectType::ImageItem => {
+                if let Some(image_item) = self
+                    .image_items
+                    .iter_mut()
+                    .find(|i| i.id == object_id.to_string())
+                {
+                    image_item.transform.update_position(position, window_size);
+                }
+            }
+            ObjectType::VideoItem => {
+                if let Some(video_item) = self
+                    .video_items
+                    .iter_mut()
+                    .find(|v| v.id == object_id.to_string())
+                {
+                    video_item.transform.update_position(position, window_size);
+                }
+            }
+            ObjectType::Group => {
+                if let Some(group) = self.groups.iter_mut().find(|g| g.id == object_id) {
+                    group.transform.update_position(position, window_size);
+                    if let Some(gpu_resources) = self.gpu_resources.as_ref() {
+                        group.update_uniform_buffer(&gpu_resources.queue);
+                    }
+                }
+            }
+            ObjectType::ParticleEmitter => {
+                if let Some(emitter) = self
+                    .particle_emitters
+                    .iter_mut()
+                    .find(|e| e.id == object_id)
+                {
+                    emitter.transform.update_position(position, window_size);
+                }
+            }
+        }
+    }
+
+    /// Redraws the arrows of callouts whose label or anchor moved, from the edge of the
+    /// label box as drawn, including its group's animation, to the anchor
+    pub fn sync_callouts(&mut self) {
+        let callouts = match &self.current_sequence_data {
+            Some(sequence) if !sequence.active_callouts.is_empty() => {
+                sequence.active_callouts.clone()
+            }
+            _ => return,
+        };
+
+        // nothing to redraw until the host has set up rendering
+        let Ok(resources) = self.object_resources() else {
+            return;
+        };
+        let gpu_resources = &resources.gpu_resources;
+        let camera = &resources.camera;
+        let model_bind_group_layout = &resources.model_bind_group_layout;
+
+        for callout in &callouts {
+            let Some(label_box) = self
+                .polygons
+                .iter()
+                .find(|p| p.id.to_string() == callout.box_id)
+            else {
+                continue;
+            };
+            let mut center = Point {
+                x: label_box.transform.position.x,
+                y: label_box.transform.position.y,
+            };
+            let mut half = (
+                label_box.dimensions.0 * label_box.transform.scale.x / 2.0,
+                label_box.dimensions.1 * label_box.transform.scale.y / 2.0,
+            );
+            if let Some(group) = self
+                .groups
+                .iter()
+                .find(|g| g.contains_child(&callout.box_id))
+            {
+                center = group.to_group_space(center);
+                half = (
+                    half.0 * group.transform.scale.x,
+                    half.1 * group.transform.scale.y,
+                );
+            }
+
+            let anchor = Point {
+                x: CANVAS_HORIZ_OFFSET + callout.anchor.x as f32,
+                y: CANVAS_VERT_OFFSET + callout.anchor.y as f32,
+            };
+            let from = leader_start(center, half, anchor);
+            let endpoints = [from.x, from.y, anchor.x, anchor.y];
+            let (arrow_center, dimensions, points) = arrow_shape(
+                from,
+                anchor,
+                callout.thickness as f32,
+                callout.head_size as f32,
+            );
+
+            let Some(arrow) = self
+                .polygons
+                .iter_mut()
+                .find(|p| p.id.to_string() == callout.arrow_id)
+            else {
+                continue;
+            };
+            // the arrow's own keyframes hold it where it was saved
+            if arrow.transform.position.x != arrow_center.x
+                || arrow.transform.position.y != arrow_center.y
+            {
+                arrow
+                    .transform
+                    .update_position([arrow_center.x, arrow_center.y], &camera.window_size);
+            }
+            if self.callout_endpoints.get(&callout.id) == Some(&endpoints) {
+                continue;
+            }
+
+            arrow.dimensions = dimensions;
+            arrow.update_data_from_contours(
+                &camera.window_size,
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                model_bind_group_layout,
+                points,
+                Vec::new(),
+                camera,
+            );
+            // rebuilt vertices take the plain fill color
+            arrow.update_opacity(&gpu_resources.queue, arrow.animated_opacity);
+
+            self.callout_endpoints.insert(callout.id.clone(), endpoints);
+        }
+    }
+
+    /// Points each child's group bind group at the group's transform, or back at an identity
+    /// transform when no group is given
+    pub(crate) fn bind_group_children(
+        &mut self,
+        children: &[GroupChild],
+        group: Option<&ObjectGroup>,
+    ) {
+        let gpu_resources = self
+            .gpu_resources
+            .clone()
+            .expect("Couldn't get gpu resources");
+        let device = &gpu_resources.device;
+        let group_bind_group_layout = self
+            .group_bind_group_layout
+            .clone()
+            .expect("Couldn't get group bind group layout");
+        let window_size = self
+            .camera
+            .as_ref()
+            .expect("Couldn't get camera")
+            .window_size;
+
+        let create_bind_group = || match group {
+            Some(group) => group.create_child_bind_group(device, &group_bind_group_layout),
+            None => create_empty_group_transform(device, &group_bind_group_layout, &window_size).0,
+        };
+
+        for child in children {
+            match child.object_type {
+                ObjectType::Polygon => {
+                    if let Some(polygon) = self
+                        .polygons
+                        .iter_mut()
+                        .find(|p| p.id.to_string() == child.object_id)
+                    {
+                        polygon.group_bind_group = create_bind_group();
+                    }
+                }
+                ObjectType::TextItem => {
+                    if let Some(text) = self
+                        .text_items
+                        .iter_mut()
+                        .find(|t| t.id.to_string() == child.object_id)
+                    {
+                        text.group_bind_group = create_bind_group();
+                        text.background_polygon.group_bind_group = create_bind_group();
+                    }
+                }
+                ObjectType::ImageItem => {
+                    if let Some(image) = self
+                        .image_items
+                        .iter_mut()
+                        .find(|i| i.id == child.object_id)
+                    {
+                        image.group_bind_group = create_bind_group();
+                    }
+                }
+                ObjectType::VideoItem => {
+                    if let Some(video) = self
+                        .video_items
+                        .iter_mut()
+                        .find(|v| v.id == child.object_id)
+                    {
+                        video.group_bind_group = create_bind_group();
+                    }
+                }
+                ObjectType::ParticleEmitter => {
+                    if let Some(emitter) = self
+                        .particle_emitters
+                        .iter_mut()
+                        .find(|e| e.id.to_string() == child.object_id)
+                    {
+                        emitter.group_bind_group = create_bind_group();
+                    }
+                }
+                ObjectType::Group => {
+                    // nested groups are not supported
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::animations::{AnimationData, KeyframeValue, ObjectType, Sequence};
+use crate::blend::BlendMode;
+use crate::choreography::delay_animation;
+use crate::input_overlays::keyframe_property;
+use crate::masking::MaskMode;
+use crate::motion_presets::{preset_animation, MotionPreset, PresetSettings, SlideEdge};
+use crate::polygon::{SavedPoint, SavedPolygonConfig, SavedStroke};
+use crate::saved_state::CanvasSize;
+use crate::text_due::SavedTextRendererConfig;
+
+/// Writes a scene's layout as JSON from a description, following the instructions from
+/// layout_instructions. Backends run wherever they like, such as a local model or a
+/// hosted service.
+pub trait LayoutGenerator {
+    fn generate_layout(&self, instructions: &str, prompt: &str) -> Result<String, String>;
+}
+
+/// Runs a command line language model, such as llama.cpp's llama-cli, writing the
+/// instructions and prompt to its input and reading the layout from its output
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct CommandLayoutGenerator {
+    pub executable: PathBuf,
+    pub args: Vec<String>, // such as the model to load
+}
+
+impl LayoutGenerator for CommandLayoutGenerator {
+    fn generate_layout(&self, instructions: &str, prompt: &str) -> Result<String, String> {
+        let mut child = Command::new(&self.executable)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Couldn't run {}: {}", self.executable.display(), e))?;
+
+        child
+            .stdin
+            .take()
+            .expect("Couldn't get stdin")
+            .write_all(format!("{}\n\n{}\n", instructions, prompt).as_bytes())
+            .map_err(|e| format!("Couldn't send the prompt: {}", e))?;
+
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("Couldn't run {}: {}", self.executable.display(), e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Layout generation failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutKind {
+    Text,
+    Box,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutMotionKind {
+    SlideInLeft,
+    SlideInRight,
+    SlideInTop,
+    SlideInBottom,
+    Bounce,
+    Shake,
+    Pop,
+}
+
+impl LayoutMotionKind {
+    pub fn preset(&self) -> MotionPreset {
+        match self {
+            LayoutMotionKind::SlideInLeft => MotionPreset::SlideIn(SlideEdge::Left),
+            LayoutMotionKind::SlideInRight => MotionPreset::SlideIn(SlideEdge::Right),
+            LayoutMotionKind::SlideInTop => MotionPreset::SlideIn(SlideEdge::Top),
+            LayoutMotionKind::SlideInBottom => MotionPreset::SlideIn(SlideEdge::Bottom),
+            LayoutMotionKind::Bounce => MotionPreset::Bounce,
+            LayoutMotionKind::Shake => MotionPreset::Shake,
+            LayoutMotionKind::Pop => MotionPreset::Pop,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct LayoutMotion {
+    pub kind: LayoutMotionKind,
+    #[serde(default)]
+    pub delay_ms: i32, // from the start of the sequence
+    #[serde(default)]
+    pub duration_ms: Option<i32>,
+}
+
+/// An object of a generated layout. Positions are centers, in canvas pixels.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct LayoutObject {
+    pub kind: LayoutKind,
+    #[serde(default)]
+    pub text: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    #[serde(default)]
+    pub font_size: Option<i32>,
+    pub color: [i32; 4], // text color, or a box's fill
+    #[serde(default)]
+    pub border_radius: i32,
+    #[serde(default)]
+    pub motion: Option<LayoutMotion>,
+}
+
+/// Objects of a generated scene, listed back to front
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct SceneLayout {
+    pub objects: Vec<LayoutObject>,
+}
+
+/// Tells a language model the JSON to answer with
+pub fn layout_instructions(canvas_size: &CanvasSize) -> String {
+    format!(
+        "Lay out an animated scene on a {width}x{height} pixel canvas as described below. \
+        Answer with JSON only, in this form:\n\
+        {{\"objects\": [{{\"kind\": \"box\" or \"text\", \"text\": \"for text only\", \
+        \"x\": center x, \"y\": center y, \"width\": pixels, \"height\": pixels, \
+        \"font_size\": for text only, \"color\": [r, g, b, a], \"border_radius\": pixels, \
+        \"motion\": {{\"kind\": \"slide_in_left\", \"slide_in_right\", \"slide_in_top\", \
+        \"slide_in_bottom\", \"bounce\", \"shake\" or \"pop\", \"delay_ms\": 0, \
+        \"duration_ms\": 800}} or null}}]}}\n\
+        List objects back to front, so boxes behind text come first. Colors are 0 to 255. \
+        Keep everything inside the canvas.",
+        width = canvas_size.width,
+        height = canvas_size.height
+    )
+}
+
+/// Reads a layout from a model's answer, skipping any text around the JSON
+pub fn parse_layout(response: &str) -> Result<SceneLayout, String> {
+    let (Some(start), Some(end)) = (response.find('{'), response.rfind('}')) else {
+        return Err("No layout found in the response".to_string());
+    };
+    if end < start {
+        return Err("No layout found in the response".to_string());
+    }
+
+    let layout: SceneLayout = serde_json::from_str(&response[start..=end])
+        .map_err(|e| format!("Couldn't parse layout: {}", e))?;
+    if layout.objects.is_empty() {
+        return Err("The layout has no objects".to_string());
+    }
+
+    Ok(layout)
+}
+
+/// Builds a layout's objects, each with its motion or keeping still. Returns a sequence
+/// with only the new objects so they can be restored and merged.
+pub fn build_scene(
+    layout: &SceneLayout,
+    sequence_id: String,
+    sequence_duration_ms: i32,
+    canvas_size: &CanvasSize,
+    font_family: &str,
+    layer: i32, // of the frontmost object
+) -> Sequence {
+    let mut scene = Sequence {
+        id: sequence_id,
+        name: String::new(),
+        background_fill: None,
+        duration_ms: 0,
+        active_polygons: Vec::new(),
+        polygon_motion_paths: Vec::new(),
+        active_text_items: Vec::new(),
+        active_image_items: Vec::new(),
+        active_video_items: Vec::new(),
+        tags: Vec::new(),
+        outlined_text_items: Vec::new(),
+        active_groups: Vec::new(),
+        active_particle_emitters: Vec::new(),
+        camera_animation: None,
+        active_callouts: Vec::new(),
+        active_spotlights: Vec::new(),
+        caption_tracks: Vec::new(),
+        constraints: Vec::new(),
+    };
+
+    let count = layout.objects.len() as i32;
+    for (index, object) in layout.objects.iter().enumerate() {
+        let id = Uuid::new_v4().to_string();
+        let position = SavedPoint {
+            x: object.x.clamp(0, canvas_size.width as i32),
+            y: object.y.clamp(0, canvas_size.height as i32),
+        };
+        let dimensions = (object.width.max(1), object.height.max(1));
+        let color = object.color.map(|c| c.clamp(0, 255));
+        // lower layers are drawn in front, and later objects go in front
+        let object_layer = layer + count - 1 - index as i32;
+
+        let object_type = match object.kind {
+            LayoutKind::Box => {
+                scene.active_polygons.push(SavedPolygonConfig {
+                    id: id.clone(),
+                    name: format!("Box {}", index + 1),
+                    fill: color,
+                    dimensions,
+                    position: position.clone(),
+                    border_radius: object.border_radius.max(0),
+                    stroke: SavedStroke {
+                        thickness: 0,
+                        fill: [0, 0, 0, 0],
+                    },
+                    layer: object_layer,
+                    tags: Vec::new(),
+                    outline: None,
+                    locked: false,
+                    hidden: false,
+                    opacity: 100,
+                    blend_mode: BlendMode::default(),
+                    mask_target_id: None,
+                    mask_mode: MaskMode::default(),
+                });
+                ObjectType::Polygon
+            }
+            LayoutKind::Text => {
+                scene.active_text_items.push(SavedTextRendererConfig {
+                    id: id.clone(),
+                    name: format!("Text {}", index + 1),
+                    text: object.text.clone(),
+                    font_family: font_family.to_string(),
+                    font_size: object.font_size.unwrap_or(dimensions.1 * 3 / 5).max(1),
+                    dimensions,
+                    position: position.clone(),
+                    layer: object_layer,
+                    color,
+                    background_fill: None,
+                    tags: Vec::new(),
+                    warp: None,
+                    locked: false,
+                    hidden: false,
+                    opacity: 100,
+                    blend_mode: BlendMode::default(),
+                    mask_target_id: None,
+                    mask_mode: MaskMode::default(),
+                });
+                ObjectType::TextItem
+            }
+        };
+
+        let animation = match &object.motion {
+            Some(motion) => {
+                let settings = PresetSettings {
+                    duration_ms: motion.duration_ms.unwrap_or(800).max(1),
+                    ..PresetSettings::new(motion.kind.preset())
+                };
+                let mut animation = preset_animation(
+                    &settings,
+                    id,
+                    object_type,
+                    [position.x, position.y],
+                    (dimensions.0 as f32, dimensions.1 as f32),
+                    canvas_size,
+                );
+                delay_animation(&mut animation, motion.delay_ms);
+                animation
+            }
+            None => {
+                let still = KeyframeValue::Position([position.x, position.y]);
+                AnimationData {
+                    id: Uuid::new_v4().to_string(),
+                    object_type,
+                    polygon_id: id,
+                    duration: Duration::from_millis(sequence_duration_ms.max(0) as u64),
+                    start_time_ms: 0,
+                    position: [0, 0],
+                    motion_blur: 0,
+                    properties: vec![keyframe_property(
+                        "Position",
+                        "position",
+                        &[(0, still.clone()), (sequence_duration_ms, still)],
+                    )],
+                }
+            }
+        };
+        scene.polygon_motion_paths.push(animation);
+    }
+
+    scene
+}
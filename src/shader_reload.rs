@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Watches a folder of WGSL files during development, so shader edits show without
+/// restarting. Set `STUNTS_SHADER_DIR` to the shaders folder, e.g. src/export/shaders,
+/// to turn it on. Otherwise the shaders built into the crate are used.
+pub struct ShaderWatcher {
+    pub dir: PathBuf,
+    modified: HashMap<PathBuf, SystemTime>,
+}
+
+impl ShaderWatcher {
+    pub fn new(dir: PathBuf) -> Self {
+        let mut watcher = ShaderWatcher {
+            dir,
+            modified: HashMap::new(),
+        };
+        // only changes from now on count
+        watcher.poll();
+        watcher
+    }
+
+    /// A watcher for `STUNTS_SHADER_DIR`, or None outside of dev mode
+    pub fn from_env() -> Option<Self> {
+        let dir = std::env::var("STUNTS_SHADER_DIR").ok()?;
+        if dir.is_empty() {
+            return None;
+        }
+        Some(Self::new(PathBuf::from(dir)))
+    }
+
+    /// File names of the shaders written since the last poll. Cheap enough to call every frame.
+    pub fn poll(&mut self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut changed = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("wgsl") {
+                continue;
+            }
+            let Ok(modified) = entry.metadata().and_then(|m| m.modified()) else {
+                continue;
+            };
+
+            if self.modified.insert(path.clone(), modified) != Some(modified) {
+                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                    changed.push(name.to_string());
+                }
+            }
+        }
+
+        changed
+    }
+
+    /// Reads and compiles a shader from the watched folder
+    pub fn load(
+        &self,
+        device: &wgpu::Device,
+        file_name: &str,
+    ) -> Result<wgpu::ShaderModule, String> {
+        load_wgsl(device, &self.dir.join(file_name))
+    }
+}
+
+/// Runs wgpu calls, returning validation errors instead of panicking on them,
+/// so a broken shader leaves the previous pipeline in place
+#[cfg(not(target_arch = "wasm32"))]
+pub fn with_validation<T>(device: &wgpu::Device, create: impl FnOnce() -> T) -> Result<T, String> {
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let created = create();
+    match pollster::block_on(device.pop_error_scope()) {
+        Some(error) => Err(error.to_string()),
+        None => Ok(created),
+    }
+}
+
+/// The browser can't block on the error scope, so WebGPU logs validation errors to the console instead
+#[cfg(target_arch = "wasm32")]
+pub fn with_validation<T>(device: &wgpu::Device, create: impl FnOnce() -> T) -> Result<T, String> {
+    Ok(create())
+}
+
+pub fn load_wgsl(device: &wgpu::Device, path: &Path) -> Result<wgpu::ShaderModule, String> {
+    let source = fs::read_to_string(path)
+        .map_err(|e| format!("Couldn't read shader {}: {}", path.display(), e))?;
+
+    with_validation(device, || {
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: path.file_name().and_then(|n| n.to_str()),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        })
+    })
+    .map_err(|e| format!("Couldn't compile shader {}: {}", path.display(), e))
+}
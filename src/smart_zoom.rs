@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::animations::{AnimationProperty, EasingType, KeyType, KeyframeValue, UIKeyframe};
+use crate::capture::MousePosition;
+use crate::editor::PathType;
+
+/// How mouse activity turns into zoom segments
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SmartZoomSettings {
+    pub zoom: i32,            // out of 100 like Zoom keyframes, 200 is 2x
+    pub min_movement: f32,    // source pixels between samples that count as activity
+    pub idle_gap_ms: u128,    // stillness this long ends a segment
+    pub min_segment_ms: u128, // shorter bursts, like nudging the mouse aside, are ignored
+    pub transition_ms: u128,  // time spent zooming in and out
+}
+
+impl Default for SmartZoomSettings {
+    fn default() -> Self {
+        SmartZoomSettings {
+            zoom: 200,
+            min_movement: 4.0,
+            idle_gap_ms: 1500,
+            min_segment_ms: 1000,
+            transition_ms: 500,
+        }
+    }
+}
+
+/// A stretch of mouse activity, in recording time like mouse positions
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ZoomSegment {
+    pub start_ms: u128,
+    pub end_ms: u128,
+}
+
+/// Groups moving samples into segments, merging ones separated by less than the idle gap
+pub fn find_active_segments(
+    positions: &[MousePosition],
+    settings: &SmartZoomSettings,
+) -> Vec<ZoomSegment> {
+    let mut segments: Vec<ZoomSegment> = Vec::new();
+
+    for pair in positions.windows(2) {
+        let (previous, position) = (pair[0], pair[1]);
+        let distance =
+            ((position.x - previous.x).powi(2) + (position.y - previous.y).powi(2)).sqrt();
+        if distance < settings.min_movement {
+            continue;
+        }
+
+        match segments.last_mut() {
+            Some(segment) if previous.timestamp <= segment.end_ms + settings.idle_gap_ms => {
+                segment.end_ms = position.timestamp;
+            }
+            _ => segments.push(ZoomSegment {
+                start_ms: previous.timestamp,
+                end_ms: position.timestamp,
+            }),
+        }
+    }
+
+    segments.retain(|segment| segment.end_ms - segment.start_ms >= settings.min_segment_ms);
+
+    segments
+}
+
+/// A Zoom property zooming in for each segment and back out between them. to_animation_ms
+/// maps recording time to time in the video's animation, such as for trims and speed.
+pub fn smart_zoom_property(
+    segments: &[ZoomSegment],
+    to_animation_ms: impl Fn(u128) -> i64,
+    animation_duration_ms: i64,
+    settings: &SmartZoomSettings,
+) -> AnimationProperty {
+    let transition_ms = settings.transition_ms as i64;
+
+    let mut values = vec![(0, 100)];
+    for segment in segments {
+        let start_ms = to_animation_ms(segment.start_ms);
+        let end_ms = to_animation_ms(segment.end_ms);
+        if end_ms <= 0 || start_ms >= animation_duration_ms {
+            continue;
+        }
+
+        values.push((start_ms - transition_ms, 100));
+        values.push((start_ms, settings.zoom));
+        values.push((end_ms, settings.zoom));
+        values.push((end_ms + transition_ms, 100));
+    }
+    values.push((animation_duration_ms, 100));
+
+    let mut keyframes: Vec<UIKeyframe> = Vec::with_capacity(values.len());
+    for (time_ms, zoom) in values {
+        let time_ms = time_ms.clamp(0, animation_duration_ms.max(0));
+        let time = Duration::from_millis(time_ms as u64);
+        // overlapping transitions keep the earlier keyframe
+        if keyframes.last().map(|k| k.time >= time).unwrap_or(false) {
+            continue;
+        }
+
+        keyframes.push(UIKeyframe {
+            id: Uuid::new_v4().to_string(),
+            time,
+            value: KeyframeValue::Zoom(zoom),
+            easing: EasingType::EaseInOut,
+            path_type: PathType::Linear,
+            key_type: KeyType::Frame,
+        });
+    }
+
+    AnimationProperty {
+        name: "Zoom / Popout".to_string(),
+        property_path: "zoom".to_string(),
+        children: Vec::new(),
+        keyframes,
+        depth: 0,
+    }
+}
@@ -0,0 +1,370 @@
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use wgpu::util::DeviceExt;
+
+use crate::animations::{AnimationData, KeyframeValue, ObjectType, Sequence};
+use crate::blend::BlendMode;
+use crate::editor::CANVAS_TOP;
+use crate::input_overlays::keyframe_property;
+use crate::masking::MaskMode;
+use crate::polygon::{Polygon, SavedPoint, SavedPolygonConfig, SavedStroke};
+use crate::saved_state::CanvasSize;
+use crate::vertex::Vertex;
+
+/// Directions sampled around the region, on top of the corners of the dimmed area
+const SPOTLIGHT_SEGMENTS: usize = 64;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum SpotlightShape {
+    #[default]
+    Rectangle,
+    Ellipse,
+}
+
+/// The part left undimmed, in the same space as object positions
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SpotlightRegion {
+    pub center: [f32; 2],
+    pub size: [f32; 2],
+    pub feather: f32, // width of the soft edge, centered on the region's outline
+}
+
+impl SpotlightRegion {
+    /// Regions are keyframed as Custom values of [x, y, width, height, feather]
+    pub fn from_custom(values: &[f32]) -> Option<Self> {
+        match values {
+            [x, y, width, height, feather] => Some(SpotlightRegion {
+                center: [*x, *y],
+                size: [width.max(0.0), height.max(0.0)],
+                feather: feather.max(0.0),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn to_keyframe_value(&self) -> KeyframeValue {
+        KeyframeValue::Custom(vec![
+            self.center[0] as i32,
+            self.center[1] as i32,
+            self.size[0] as i32,
+            self.size[1] as i32,
+            self.feather as i32,
+        ])
+    }
+}
+
+/// Dims the canvas outside a region. The overlay is a polygon covering the canvas whose
+/// mesh is replaced with a cutout, animated by a Custom "spotlight" property.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct SavedSpotlightConfig {
+    pub id: String, // the polygon drawn as the overlay
+    pub shape: SpotlightShape,
+    pub color: [i32; 4], // alpha sets how dark the dimming is
+    pub center: SavedPoint,
+    pub size: (i32, i32),
+    pub feather: i32,
+}
+
+impl SavedSpotlightConfig {
+    pub fn region(&self) -> SpotlightRegion {
+        SpotlightRegion {
+            center: [self.center.x as f32, self.center.y as f32],
+            size: [self.size.0 as f32, self.size.1 as f32],
+            feather: self.feather as f32,
+        }
+    }
+}
+
+/// Look and starting region of a new spotlight
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct SpotlightSettings {
+    pub name: String,
+    pub shape: SpotlightShape,
+    pub color: [i32; 4],
+    pub center: SavedPoint,
+    pub size: (i32, i32),
+    pub feather: i32,
+    pub layer: i32, // low enough to cover the objects it dims
+}
+
+impl Default for SpotlightSettings {
+    fn default() -> Self {
+        SpotlightSettings {
+            name: "Spotlight".to_string(),
+            shape: SpotlightShape::Rectangle,
+            color: [0, 0, 0, 160],
+            center: SavedPoint { x: 400, y: 300 },
+            size: (320, 200),
+            feather: 24,
+            layer: -10,
+        }
+    }
+}
+
+/// Builds the overlay polygon covering the canvas with its animation, returning a sequence
+/// holding only the new objects so they can be restored and merged. The region holds still
+/// until keyframed.
+pub fn build_spotlight(
+    settings: &SpotlightSettings,
+    sequence_id: String,
+    sequence_duration_ms: i32,
+    canvas_size: CanvasSize,
+) -> Sequence {
+    let mut spotlight = Sequence {
+        id: sequence_id,
+        name: String::new(),
+        background_fill: None,
+        duration_ms: 0,
+        active_polygons: Vec::new(),
+        polygon_motion_paths: Vec::new(),
+        active_text_items: Vec::new(),
+        active_image_items: Vec::new(),
+        active_video_items: Vec::new(),
+        tags: Vec::new(),
+        outlined_text_items: Vec::new(),
+        active_groups: Vec::new(),
+        active_particle_emitters: Vec::new(),
+        camera_animation: None,
+        active_callouts: Vec::new(),
+        active_spotlights: Vec::new(),
+        caption_tracks: Vec::new(),
+        constraints: Vec::new(),
+    };
+
+    // over the canvas background
+    let position = SavedPoint {
+        x: canvas_size.width as i32 / 2,
+        y: CANVAS_TOP as i32 + canvas_size.height as i32 / 2,
+    };
+    let overlay = SavedPolygonConfig {
+        id: Uuid::new_v4().to_string(),
+        name: settings.name.clone(),
+        fill: settings.color,
+        dimensions: (canvas_size.width as i32, canvas_size.height as i32),
+        position: position.clone(),
+        border_radius: 0,
+        stroke: SavedStroke {
+            thickness: 0,
+            fill: [0, 0, 0, 0],
+        },
+        layer: settings.layer,
+        tags: Vec::new(),
+        outline: None,
+        locked: true, // covers the canvas, so it would catch every click
+        hidden: false,
+        opacity: 100,
+        blend_mode: BlendMode::default(),
+        mask_target_id: None,
+        mask_mode: MaskMode::default(),
+    };
+
+    let config = SavedSpotlightConfig {
+        id: overlay.id.clone(),
+        shape: settings.shape,
+        color: settings.color,
+        center: settings.center.clone(),
+        size: settings.size,
+        feather: settings.feather,
+    };
+
+    let still = |value: KeyframeValue| [(0, value.clone()), (sequence_duration_ms, value)];
+    spotlight.polygon_motion_paths.push(AnimationData {
+        id: Uuid::new_v4().to_string(),
+        object_type: ObjectType::Polygon,
+        polygon_id: overlay.id.clone(),
+        duration: Duration::from_millis(sequence_duration_ms.max(0) as u64),
+        start_time_ms: 0,
+        properties: vec![
+            keyframe_property(
+                "Position",
+                "position",
+                &still(KeyframeValue::Position([position.x, position.y])),
+            ),
+            keyframe_property("Opacity", "opacity", &still(KeyframeValue::Opacity(100))),
+            keyframe_property(
+                "Spotlight",
+                "spotlight",
+                &still(config.region().to_keyframe_value()),
+            ),
+        ],
+        position: [0, 0],
+        motion_blur: 0,
+    });
+
+    spotlight.active_polygons.push(overlay);
+    spotlight.active_spotlights.push(config);
+
+    spotlight
+}
+
+/// Top left and size of the area an overlay dims, matching the canvas background
+pub fn canvas_bounds(canvas_size: CanvasSize) -> ([f32; 2], [f32; 2]) {
+    (
+        [0.0, CANVAS_TOP],
+        [canvas_size.width as f32, canvas_size.height as f32],
+    )
+}
+
+/// Replaces a polygon's mesh with the overlay for a region. canvas is from canvas_bounds,
+/// and the overlay is expected to be centered on it. The dimming fades
+/// with the polygon's opacity.
+pub fn draw_spotlight(
+    polygon: &mut Polygon,
+    config: &SavedSpotlightConfig,
+    region: &SpotlightRegion,
+    canvas: ([f32; 2], [f32; 2]),
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+) {
+    let opacity = polygon.animated_opacity * polygon.base_opacity;
+    let color = [
+        config.color[0] as f32 / 255.0,
+        config.color[1] as f32 / 255.0,
+        config.color[2] as f32 / 255.0,
+        config.color[3] as f32 / 255.0 * opacity,
+    ];
+    let (vertices, indices) = spotlight_mesh(config.shape, region, canvas, color);
+
+    // the first draw replaces the rectangle the polygon was restored with
+    if vertices.len() == polygon.vertices.len() && indices == polygon.indices {
+        queue.write_buffer(&polygon.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+    } else {
+        polygon.vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+        polygon.index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        polygon.indices = indices;
+    }
+
+    polygon.vertices = vertices;
+}
+
+/// Rings around the region, clear inside the feather and fully dimmed beyond it out to
+/// the edge of the canvas, relative to the canvas center. Regions reaching past the canvas
+/// stretch the dimmed area to cover them.
+pub fn spotlight_mesh(
+    shape: SpotlightShape,
+    region: &SpotlightRegion,
+    canvas: ([f32; 2], [f32; 2]),
+    color: [f32; 4],
+) -> (Vec<Vertex>, Vec<u32>) {
+    let [cx, cy] = region.center;
+    let half_feather = region.feather / 2.0;
+    let inner = [
+        (region.size[0] / 2.0 - half_feather).max(0.0),
+        (region.size[1] / 2.0 - half_feather).max(0.0),
+    ];
+    let outer = [
+        region.size[0] / 2.0 + half_feather,
+        region.size[1] / 2.0 + half_feather,
+    ];
+
+    let (canvas_min, canvas_size) = canvas;
+    let far_min = [
+        canvas_min[0].min(cx - outer[0]),
+        canvas_min[1].min(cy - outer[1]),
+    ];
+    let far_max = [
+        (canvas_min[0] + canvas_size[0]).max(cx + outer[0]),
+        (canvas_min[1] + canvas_size[1]).max(cy + outer[1]),
+    ];
+
+    // the dimmed area's corners are sampled so its edges stay straight
+    let mut angles: Vec<f32> = (0..SPOTLIGHT_SEGMENTS)
+        .map(|i| i as f32 / SPOTLIGHT_SEGMENTS as f32 * 2.0 * PI)
+        .collect();
+    for corner in [
+        [far_min[0], far_min[1]],
+        [far_max[0], far_min[1]],
+        [far_max[0], far_max[1]],
+        [far_min[0], far_max[1]],
+    ] {
+        angles.push((corner[1] - cy).atan2(corner[0] - cx).rem_euclid(2.0 * PI));
+    }
+    angles.sort_by(|a, b| a.total_cmp(b));
+
+    let canvas_center = [
+        canvas_min[0] + canvas_size[0] / 2.0,
+        canvas_min[1] + canvas_size[1] / 2.0,
+    ];
+    let clear = [color[0], color[1], color[2], 0.0];
+    let vertex = |distance: f32, angle: f32, color: [f32; 4]| {
+        Vertex::new(
+            cx + distance * angle.cos() - canvas_center[0],
+            cy + distance * angle.sin() - canvas_center[1],
+            0.0,
+            color,
+        )
+    };
+
+    let mut vertices = Vec::with_capacity(angles.len() * 3);
+    for angle in &angles {
+        let (cos, sin) = (angle.cos(), angle.sin());
+        let inner_distance = shape_distance(shape, inner, cos, sin);
+        let outer_distance = shape_distance(shape, outer, cos, sin);
+        let far_distance = box_distance(far_min, far_max, [cx, cy], cos, sin);
+
+        vertices.push(vertex(inner_distance, *angle, clear));
+        vertices.push(vertex(outer_distance, *angle, color));
+        vertices.push(vertex(far_distance, *angle, color));
+    }
+
+    // quads between each ring and the next, around the region
+    let count = angles.len() as u32;
+    let mut indices = Vec::with_capacity(angles.len() * 12);
+    for i in 0..count {
+        let next = (i + 1) % count;
+        for ring in 0..2 {
+            let (a, b) = (i * 3 + ring, next * 3 + ring);
+            indices.extend_from_slice(&[a, b, b + 1, a, b + 1, a + 1]);
+        }
+    }
+
+    (vertices, indices)
+}
+
+/// Distance from the center to a shape's outline in a direction, half being its half size
+fn shape_distance(shape: SpotlightShape, half: [f32; 2], cos: f32, sin: f32) -> f32 {
+    match shape {
+        SpotlightShape::Rectangle => {
+            let to_side = |half: f32, direction: f32| {
+                if direction.abs() > f32::EPSILON {
+                    half / direction.abs()
+                } else {
+                    f32::MAX
+                }
+            };
+            to_side(half[0], cos).min(to_side(half[1], sin))
+        }
+        SpotlightShape::Ellipse => {
+            if half[0] <= 0.0 || half[1] <= 0.0 {
+                return 0.0;
+            }
+            half[0] * half[1] / ((half[1] * cos).powi(2) + (half[0] * sin).powi(2)).sqrt()
+        }
+    }
+}
+
+/// Distance from a point inside a box to its edge in a direction
+fn box_distance(min: [f32; 2], max: [f32; 2], from: [f32; 2], cos: f32, sin: f32) -> f32 {
+    let to_side = |from: f32, min: f32, max: f32, direction: f32| {
+        if direction > f32::EPSILON {
+            (max - from) / direction
+        } else if direction < -f32::EPSILON {
+            (min - from) / direction
+        } else {
+            f32::MAX
+        }
+    };
+
+    to_side(from[0], min[0], max[0], cos).min(to_side(from[1], min[1], max[1], sin))
+}
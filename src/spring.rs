@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+/// A damped spring pulling a property from one keyframe's value to the next, used as the
+/// earlier keyframe's easing. It's evaluated in closed form from the time since that
+/// keyframe, so any frame can be rendered on its own and exports match the preview.
+/// The spring doesn't stretch to fit the keyframes, so leave room for it to settle.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct SpringConfig {
+    pub stiffness: i32, // pull toward the target
+    pub damping: i32,   // friction, 2 * sqrt(stiffness * mass) stops without overshooting
+    pub mass: i32,      // out of 100, so 100 is a mass of 1
+    pub velocity: i32,  // initial impulse, in hundredths of the distance per second
+}
+
+impl Default for SpringConfig {
+    fn default() -> Self {
+        Self {
+            stiffness: 170,
+            damping: 26,
+            mass: 100,
+            velocity: 0,
+        }
+    }
+}
+
+impl SpringConfig {
+    pub fn gentle() -> Self {
+        Self {
+            stiffness: 120,
+            damping: 14,
+            ..Self::default()
+        }
+    }
+
+    pub fn wobbly() -> Self {
+        Self {
+            stiffness: 180,
+            damping: 12,
+            ..Self::default()
+        }
+    }
+
+    pub fn stiff() -> Self {
+        Self {
+            stiffness: 210,
+            damping: 20,
+            ..Self::default()
+        }
+    }
+
+    /// How far the spring has carried the value toward its target after some seconds,
+    /// from 0 at the start to 1 at rest, passing 1 when it overshoots
+    pub fn progress(&self, elapsed_s: f32) -> f32 {
+        let stiffness = self.stiffness.max(1) as f32;
+        let mass = self.mass.max(1) as f32 / 100.0;
+        let damping = self.damping.max(0) as f32;
+        let t = elapsed_s.max(0.0);
+
+        // displacement from the target, starting the whole distance short of it
+        let x0 = -1.0;
+        let v0 = self.velocity as f32 / 100.0;
+        let omega = (stiffness / mass).sqrt();
+        let zeta = damping / (2.0 * (stiffness * mass).sqrt());
+
+        let displacement = if (zeta - 1.0).abs() < 1e-4 {
+            // critically damped
+            (-omega * t).exp() * (x0 + (v0 + omega * x0) * t)
+        } else if zeta < 1.0 {
+            // underdamped, oscillating around the target
+            let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+            (-zeta * omega * t).exp()
+                * (x0 * (omega_d * t).cos()
+                    + (v0 + zeta * omega * x0) / omega_d * (omega_d * t).sin())
+        } else {
+            // overdamped, creeping toward the target
+            let root = (zeta * zeta - 1.0).sqrt();
+            let (r1, r2) = (-omega * (zeta - root), -omega * (zeta + root));
+            let c2 = (v0 - r1 * x0) / (r2 - r1);
+            let c1 = x0 - c2;
+            c1 * (r1 * t).exp() + c2 * (r2 * t).exp()
+        };
+
+        1.0 + displacement
+    }
+}
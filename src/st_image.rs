@@ -9,10 +9,17 @@ use uuid::Uuid;
 use wgpu::util::DeviceExt;
 use wgpu::{Device, Queue, TextureView};
 
+use crate::animated_image::{decode_animation, AnimatedFrames, AnimatedImageMode};
+use crate::blend::BlendMode;
+use crate::image_assets::ImageAssetCache;
 use crate::camera::Camera3D as Camera;
 use crate::editor::Point;
-use crate::polygon::SavedPoint;
+use crate::masking::MaskMode;
+use crate::nine_slice::{build_nine_slice_mesh, NineSliceInsets};
+use crate::polygon::{default_opacity, SavedPoint};
+use crate::tags::ColorTag;
 use crate::transform::{create_empty_group_transform, matrix4_to_raw_array};
+use crate::warp::{build_warped_mesh, MeshWarp};
 use crate::{
     editor::WindowSize,
     transform::Transform,
@@ -30,6 +37,7 @@ pub struct StImageConfig {
     pub position: Point,
     pub path: String,
     pub layer: i32,
+    pub nine_slice: Option<NineSliceInsets>,
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
@@ -40,6 +48,55 @@ pub struct SavedStImageConfig {
     pub path: String,
     pub position: SavedPoint,
     pub layer: i32,
+    #[serde(default)]
+    pub tags: Vec<ColorTag>,
+    #[serde(default)]
+    pub warp: Option<MeshWarp>,
+    #[serde(default)]
+    pub locked: bool,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default = "default_opacity")]
+    pub opacity: i32, // out of 100, like opacity keyframes
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+    #[serde(default)]
+    pub mask_target_id: Option<String>, // makes this a mask for the object with this id
+    #[serde(default)]
+    pub mask_mode: MaskMode,
+    #[serde(default)]
+    pub max_resident_size: Option<u32>, // longest side kept on the GPU, None for the device limit
+    #[serde(default)]
+    pub animation_mode: AnimatedImageMode,
+    #[serde(default)]
+    pub nine_slice: Option<NineSliceInsets>,
+}
+
+/// How much of an image is on the GPU compared to the file on disk
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ImageResidency {
+    pub source_dimensions: (u32, u32),
+    pub texture_dimensions: (u32, u32), // effective resolution after downscaling
+    pub mip_level_count: u32,
+    pub max_resident_size: Option<u32>,
+}
+
+impl ImageResidency {
+    pub fn is_downscaled(&self) -> bool {
+        self.texture_dimensions != self.source_dimensions
+    }
+
+    /// Approximate GPU memory used by the texture and its mips
+    pub fn gpu_bytes(&self) -> u64 {
+        let (mut width, mut height) = self.texture_dimensions;
+        let mut bytes = 0;
+        for _ in 0..self.mip_level_count {
+            bytes += width as u64 * height as u64 * 4;
+            width = (width / 2).max(1);
+            height = (height / 2).max(1);
+        }
+        bytes
+    }
 }
 
 pub struct StImage {
@@ -47,19 +104,32 @@ pub struct StImage {
     pub current_sequence_id: Uuid,
     pub name: String,
     pub path: String,
-    pub texture: wgpu::Texture,
+    pub texture: Arc<wgpu::Texture>, // shared with other items showing the file unless animated
     pub texture_view: TextureView,
     pub transform: Transform,
     pub vertex_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
     pub dimensions: (u32, u32),
     pub bind_group: wgpu::BindGroup,
-    pub vertices: [Vertex; 4],
-    pub indices: [u32; 6],
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
     pub hidden: bool,
+    pub user_hidden: bool, // hidden from the layer panel, separate from sequence visibility
+    pub locked: bool,
+    pub base_opacity: f32,     // static opacity, multiplied with animated opacity
+    pub animated_opacity: f32, // last opacity from keyframes
+    pub blend_mode: BlendMode,
+    pub mask_target_id: Option<String>,
+    pub mask_mode: MaskMode,
     pub layer: i32,
     pub group_bind_group: wgpu::BindGroup,
     pub original_dimensions: (u32, u32),
+    pub warp: Option<MeshWarp>,
+    pub nine_slice: Option<NineSliceInsets>, // ignored while warped
+    pub residency: ImageResidency,
+    pub animation: Option<AnimatedFrames>, // None for still images and static mode
+    pub animation_mode: AnimatedImageMode,
+    pub current_frame: usize,
 }
 
 impl StImage {
@@ -71,97 +141,34 @@ impl StImage {
         window_size: &WindowSize,
         bind_group_layout: &wgpu::BindGroupLayout,
         group_bind_group_layout: &Arc<wgpu::BindGroupLayout>,
+        image_assets: &mut ImageAssetCache,
         z_index: f32,
         new_id: String,
         current_sequence_id: Uuid,
-    ) -> StImage {
+    ) -> Result<StImage, String> {
         // specify resizing strategy
         let feature = "low_quality_resize"; // faster
                                             // let feature = "high_quality_resize"; // slow
 
-        // Load the image
-        let img = image::open(path).expect("Couldn't open image");
-        let original_dimensions = img.dimensions();
         let dimensions = image_config.dimensions;
 
         // store dimensions as orginal_dimensions?
         // self.orginal_dimensions = dimensions;
 
-        // Option 1: Resize image data before creating texture
-        let img = if feature == "high_quality_resize" {
-            img.resize_exact(
-                dimensions.0,
-                dimensions.1,
-                image::imageops::FilterType::Lanczos3,
-            )
-        } else {
-            img
-        };
-
-        // Create texture with original or resized dimensions
-        let texture_size = wgpu::Extent3d {
-            width: if feature == "high_quality_resize" {
-                dimensions.0
-            } else {
-                original_dimensions.0
-            },
-            height: if feature == "high_quality_resize" {
-                dimensions.1
-            } else {
-                original_dimensions.1
-            },
-            depth_or_array_layers: 1,
-        };
-
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("Image Texture"),
-            size: texture_size,
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            // format: wgpu::TextureFormat::Rgba8Unorm,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[wgpu::TextureFormat::Rgba8UnormSrgb],
+        // the texture holds the first frame
+        let animation = decode_animation(path).unwrap_or_else(|e| {
+            println!("Couldn't decode animation, showing first frame: {}", e);
+            None
         });
 
-        // Convert image to RGBA
-        let rgba = img.to_rgba8().into_raw();
-
-        // Write texture data
-        queue.write_texture(
-            wgpu::ImageCopyTexture {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            &rgba,
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(4 * texture_size.width),
-                rows_per_image: Some(texture_size.height),
-            },
-            texture_size,
-        );
-
+        // Huge images are downscaled for the editor, the original stays on disk
+        let (texture, residency) = if animation.is_some() {
+            image_assets.load_unshared(device, queue, path, None)?
+        } else {
+            image_assets.load(device, queue, path, None)?
+        };
         let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        // Create sampler with appropriate filtering
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: if feature == "high_quality_resize" {
-                wgpu::FilterMode::Linear
-            } else {
-                wgpu::FilterMode::Linear // You might want to use Nearest for pixel art
-            },
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::FilterMode::Linear,
-            ..Default::default()
-        });
-
         let empty_buffer = Matrix4::<f32>::identity();
         let raw_matrix = matrix4_to_raw_array(&empty_buffer);
 
@@ -171,25 +178,8 @@ impl StImage {
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
-        // Rest of the bind group creation...
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: uniform_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::TextureView(&texture_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
-                },
-            ],
-            label: Some("Image Bind Group"),
-        });
+        let bind_group =
+            create_image_bind_group(device, bind_group_layout, &uniform_buffer, &texture_view);
 
         let scale_x = dimensions.0 as f32;
         let scale_y = dimensions.1 as f32;
@@ -219,29 +209,16 @@ impl StImage {
         transform.layer = image_config.layer as f32 - 0 as f32;
         transform.update_uniform_buffer(&queue, &window_size);
 
-        // Rest of the implementation remains the same...
-        let vertices = [
-            Vertex {
-                position: [-0.5, -0.5, 0.0],
-                tex_coords: [0.0, 0.0],
-                color: [1.0, 1.0, 1.0, 1.0],
-            },
-            Vertex {
-                position: [0.5, -0.5, 0.0],
-                tex_coords: [1.0, 0.0],
-                color: [1.0, 1.0, 1.0, 1.0],
-            },
-            Vertex {
-                position: [0.5, 0.5, 0.0],
-                tex_coords: [1.0, 1.0],
-                color: [1.0, 1.0, 1.0, 1.0],
-            },
-            Vertex {
-                position: [-0.5, 0.5, 0.0],
-                tex_coords: [0.0, 1.0],
-                color: [1.0, 1.0, 1.0, 1.0],
-            },
-        ];
+        // a plain quad until a warp subdivides it
+        let (vertices, indices) = match &image_config.nine_slice {
+            Some(insets) => build_nine_slice_mesh(
+                insets,
+                residency.source_dimensions,
+                (scale_x, scale_y),
+                [1.0, 1.0, 1.0, 1.0],
+            ),
+            None => build_warped_mesh(None, [1.0, 1.0, 1.0, 1.0]),
+        };
 
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Vertex Buffer"),
@@ -249,7 +226,6 @@ impl StImage {
             usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
         });
 
-        let indices: [u32; 6] = [0, 1, 2, 0, 2, 3];
         let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Index Buffer"),
             contents: bytemuck::cast_slice(&indices),
@@ -259,7 +235,7 @@ impl StImage {
         let (tmp_group_bind_group, tmp_group_transform) =
             create_empty_group_transform(device, group_bind_group_layout, window_size);
 
-        Self {
+        Ok(Self {
             id: new_id,
             current_sequence_id,
             name: image_config.name,
@@ -277,14 +253,124 @@ impl StImage {
             vertices,
             indices: indices.clone(),
             hidden: false,
+            user_hidden: false,
+            locked: false,
+            base_opacity: 1.0,
+            animated_opacity: 1.0,
+            blend_mode: BlendMode::Normal,
+            mask_target_id: None,
+            mask_mode: MaskMode::Clip,
             layer: image_config.layer - 0,
             group_bind_group: tmp_group_bind_group,
-            original_dimensions: dimensions
+            original_dimensions: dimensions,
+            warp: None,
+            nine_slice: image_config.nine_slice,
+            residency,
+            animation,
+            animation_mode: AnimatedImageMode::Animate,
+            current_frame: 0,
+        })
+    }
+
+    /// Shows the frame of an animated image at a sequence time, uploading it only when
+    /// the frame changes
+    pub fn update_animation_frame(&mut self, queue: &Queue, time_ms: u32) {
+        let Some(animation) = &self.animation else {
+            return;
+        };
+
+        let frame_index = animation.frame_at(time_ms);
+        if frame_index == self.current_frame {
+            return;
         }
+
+        write_animation_frame(
+            queue,
+            &self.texture,
+            &self.residency,
+            &animation.frames[frame_index],
+        );
+        self.current_frame = frame_index;
+    }
+
+    /// Switches between playing an animated image and only showing its first frame.
+    /// The static mode frees the decoded frames.
+    pub fn update_animation_mode(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        image_assets: &mut ImageAssetCache,
+        animation_mode: AnimatedImageMode,
+    ) -> Result<(), String> {
+        self.animation_mode = animation_mode;
+
+        match animation_mode {
+            AnimatedImageMode::Animate => {
+                if self.animation.is_none() {
+                    self.animation = decode_animation(Path::new(&self.path))?;
+
+                    // frames can't be written into a texture other items share
+                    if self.animation.is_some() {
+                        let max_resident_size = self.residency.max_resident_size;
+                        self.update_max_resident_size(
+                            device,
+                            queue,
+                            bind_group_layout,
+                            image_assets,
+                            max_resident_size,
+                        )?;
+                    }
+                }
+            }
+            AnimatedImageMode::StaticFirstFrame => {
+                if self.current_frame != 0 {
+                    self.update_animation_frame(queue, 0);
+                }
+                self.animation = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reloads the texture with its longest side limited to max_resident_size, on top of
+    /// the cache's limit. Lower limits save GPU memory on huge images.
+    pub fn update_max_resident_size(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        image_assets: &mut ImageAssetCache,
+        max_resident_size: Option<u32>,
+    ) -> Result<(), String> {
+        let path = Path::new(&self.path);
+        let (texture, residency) = if self.animation.is_some() {
+            image_assets.load_unshared(device, queue, path, max_resident_size)?
+        } else {
+            image_assets.load(device, queue, path, max_resident_size)?
+        };
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        self.bind_group = create_image_bind_group(
+            device,
+            bind_group_layout,
+            &self.transform.uniform_buffer,
+            &texture_view,
+        );
+        self.texture = texture;
+        self.texture_view = texture_view;
+        self.residency = residency;
+        // the new texture holds the first frame
+        self.current_frame = 0;
+
+        Ok(())
     }
 
     pub fn update_opacity(&mut self, queue: &wgpu::Queue, opacity: f32) {
-        let new_color = [1.0, 1.0, 1.0, opacity];
+        self.animated_opacity = opacity;
+
+        let new_color = [1.0, 1.0, 1.0, opacity * self.base_opacity];
 
         self.vertices.iter_mut().for_each(|v| {
             v.color = new_color;
@@ -293,6 +379,85 @@ impl StImage {
         queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&self.vertices));
     }
 
+    /// Sets the static opacity, keeping the current animated opacity
+    pub fn update_base_opacity(&mut self, queue: &wgpu::Queue, base_opacity: f32) {
+        self.base_opacity = base_opacity;
+        self.update_opacity(queue, self.animated_opacity);
+    }
+
+    /// Rebuilds the mesh for the warp, or back to a plain quad when None
+    pub fn update_warp(&mut self, device: &Device, queue: &Queue, warp: Option<MeshWarp>) {
+        self.warp = warp;
+        self.rebuild_mesh(device, queue);
+    }
+
+    /// Keeps the borders of the image at their source size when it's resized, or
+    /// stretches the whole image again with None
+    pub fn update_nine_slice(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        nine_slice: Option<NineSliceInsets>,
+    ) {
+        self.nine_slice = nine_slice;
+        self.rebuild_mesh(device, queue);
+    }
+
+    /// Fits nine-slice borders to the current scale, after resizing or scale keyframes
+    pub fn update_nine_slice_mesh(&mut self, queue: &Queue) {
+        if self.nine_slice.is_none() || self.warp.is_some() {
+            return;
+        }
+
+        // the grid is always the same size, so only the vertices change
+        let (vertices, _) = self.build_mesh();
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+        self.vertices = vertices;
+    }
+
+    /// A warp takes precedence over nine-slice borders
+    fn build_mesh(&self) -> (Vec<Vertex>, Vec<u32>) {
+        // keep any opacity already applied
+        let color = self
+            .vertices
+            .first()
+            .map(|v| v.color)
+            .unwrap_or([1.0, 1.0, 1.0, 1.0]);
+
+        match (&self.warp, &self.nine_slice) {
+            (None, Some(insets)) => build_nine_slice_mesh(
+                insets,
+                self.residency.source_dimensions,
+                (self.transform.scale.x, self.transform.scale.y),
+                color,
+            ),
+            (warp, _) => build_warped_mesh(warp.as_ref(), color),
+        }
+    }
+
+    fn rebuild_mesh(&mut self, device: &Device, queue: &Queue) {
+        let (vertices, indices) = self.build_mesh();
+
+        if vertices.len() == self.vertices.len() {
+            // same grid size, as when stepping warp keyframes
+            queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+        } else {
+            self.vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Vertex Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            });
+            self.index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Index Buffer"),
+                contents: bytemuck::cast_slice(&indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+        }
+
+        self.vertices = vertices;
+        self.indices = indices;
+    }
+
     pub fn update_data_from_dimensions(
         &mut self,
         window_size: &WindowSize,
@@ -306,6 +471,7 @@ impl StImage {
         self.dimensions = (dimensions.0 as u32, dimensions.1 as u32);
         self.transform.update_scale([dimensions.0, dimensions.1]);
         self.transform.update_uniform_buffer(&queue, &window_size);
+        self.update_nine_slice_mesh(queue);
     }
 
     pub fn update_data_from_scale(
@@ -320,6 +486,7 @@ impl StImage {
         // for "low" quality resize
         self.transform.update_scale([self.dimensions.0 as f32 * (scale_factor / 100.0), self.dimensions.1 as f32 * (scale_factor / 100.0)]);
         self.transform.update_uniform_buffer(&queue, &window_size);
+        self.update_nine_slice_mesh(queue);
     }
 
     pub fn update_layer(&mut self, layer_index: i32) {
@@ -445,6 +612,7 @@ impl StImage {
                 y: self.transform.position.y - CANVAS_VERT_OFFSET,
             },
             layer: self.layer,
+            nine_slice: self.nine_slice,
         }
     }
 
@@ -455,9 +623,10 @@ impl StImage {
         queue: &wgpu::Queue,
         model_bind_group_layout: &Arc<wgpu::BindGroupLayout>,
         group_bind_group_layout: &Arc<wgpu::BindGroupLayout>,
+        image_assets: &mut ImageAssetCache,
         camera: &Camera,
         selected_sequence_id: String,
-    ) -> StImage {
+    ) -> Result<StImage, String> {
         StImage::new(
             &device,
             &queue,
@@ -467,9 +636,166 @@ impl StImage {
             &window_size,
             model_bind_group_layout,
             group_bind_group_layout,
+            image_assets,
             -2.0,
             config.id.clone(),
             Uuid::from_str(&selected_sequence_id).expect("Couldn't convert string to uuid"),
         )
     }
 }
+
+/// Uploads an image with a full mip chain, downscaling it first if it's larger than the
+/// device allows or than the optional limit
+pub fn create_image_texture(
+    device: &Device,
+    queue: &Queue,
+    img: &image::DynamicImage,
+    max_resident_size: Option<u32>,
+) -> (wgpu::Texture, TextureView, ImageResidency) {
+    let source_dimensions = img.dimensions();
+    let device_limit = device.limits().max_texture_dimension_2d;
+    let limit = max_resident_size.map_or(device_limit, |size| size.clamp(1, device_limit));
+
+    let longest_side = source_dimensions.0.max(source_dimensions.1);
+    let rgba = if longest_side > limit {
+        let ratio = limit as f32 / longest_side as f32;
+        let width = ((source_dimensions.0 as f32 * ratio).round() as u32).max(1);
+        let height = ((source_dimensions.1 as f32 * ratio).round() as u32).max(1);
+        println!(
+            "Downscaling image from {:?} to {:?} to fit texture limits",
+            source_dimensions,
+            (width, height)
+        );
+        image::imageops::resize(img, width, height, image::imageops::FilterType::Triangle)
+    } else {
+        img.to_rgba8()
+    };
+
+    let texture_dimensions = rgba.dimensions();
+    // mips keep downscaled drawing smooth instead of shimmering
+    let mip_level_count = 32 - texture_dimensions.0.max(texture_dimensions.1).leading_zeros();
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Image Texture"),
+        size: wgpu::Extent3d {
+            width: texture_dimensions.0,
+            height: texture_dimensions.1,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[wgpu::TextureFormat::Rgba8UnormSrgb],
+    });
+
+    write_image_mips(queue, &texture, rgba, mip_level_count);
+
+    let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let residency = ImageResidency {
+        source_dimensions,
+        texture_dimensions,
+        mip_level_count,
+        max_resident_size,
+    };
+
+    (texture, texture_view, residency)
+}
+
+/// Replaces the contents of an image texture with a frame the size of the source image
+fn write_animation_frame(
+    queue: &Queue,
+    texture: &wgpu::Texture,
+    residency: &ImageResidency,
+    frame: &image::RgbaImage,
+) {
+    let (width, height) = residency.texture_dimensions;
+    let frame = if frame.dimensions() != (width, height) {
+        image::imageops::resize(frame, width, height, image::imageops::FilterType::Triangle)
+    } else {
+        frame.clone()
+    };
+
+    write_image_mips(queue, texture, frame, residency.mip_level_count);
+}
+
+/// Writes an image to the first mip level and its halvings to the rest
+fn write_image_mips(
+    queue: &Queue,
+    texture: &wgpu::Texture,
+    rgba: image::RgbaImage,
+    mip_level_count: u32,
+) {
+    let mut level_image = rgba;
+    for mip_level in 0..mip_level_count {
+        if mip_level > 0 {
+            let width = (level_image.width() / 2).max(1);
+            let height = (level_image.height() / 2).max(1);
+            level_image = image::imageops::resize(
+                &level_image,
+                width,
+                height,
+                image::imageops::FilterType::Triangle,
+            );
+        }
+
+        let (width, height) = level_image.dimensions();
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            level_image.as_raw(),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
+
+pub fn create_image_bind_group(
+    device: &Device,
+    bind_group_layout: &wgpu::BindGroupLayout,
+    uniform_buffer: &wgpu::Buffer,
+    texture_view: &TextureView,
+) -> wgpu::BindGroup {
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Linear,
+        ..Default::default()
+    });
+
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: bind_group_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(texture_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::Sampler(&sampler),
+            },
+        ],
+        label: Some("Image Bind Group"),
+    })
+}
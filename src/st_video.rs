@@ -8,21 +8,54 @@ use std::sync::Arc;
 use uuid::Uuid;
 use wgpu::util::DeviceExt;
 use wgpu::{Device, Queue};
+#[cfg(not(target_arch = "wasm32"))]
+use windows::Win32::Foundation::E_FAIL;
+#[cfg(not(target_arch = "wasm32"))]
 use windows::Win32::Media::KernelStreaming::GUID_NULL;
+#[cfg(not(target_arch = "wasm32"))]
 use windows::Win32::Media::MediaFoundation::*;
+#[cfg(not(target_arch = "wasm32"))]
 use windows::Win32::System::Com::StructuredStorage::PropVariantToInt64;
+#[cfg(not(target_arch = "wasm32"))]
 use windows_core::{PCWSTR, PROPVARIANT};
 
+use crate::animations::{AnimationData, KeyType, KeyframeValue, UIKeyframe};
+use crate::blend::BlendMode;
 use crate::camera::Camera3D as Camera;
 use crate::capture::{MousePosition, SourceData};
+use crate::cursor::{CursorOverlay, SavedCursorOverlay};
 use crate::editor::{Point, WindowSize};
-use crate::polygon::SavedPoint;
+use crate::frame_interpolation::{FrameInterpolationSettings, FrameInterpolator};
+use crate::masking::MaskMode;
+use crate::matting::{Matte, SavedMatteConfig};
+use crate::polygon::{default_opacity, SavedPoint};
+use crate::redaction::{apply_redactions, RedactionRegion};
+use crate::stabilization::SavedStabilization;
+use crate::tags::ColorTag;
+use crate::thumbnails::{extract_thumbnails, Thumbnail, ThumbnailRequest};
+use crate::transcode::proxy::VideoProxy;
 use crate::transform::{create_empty_group_transform, matrix4_to_raw_array, Transform};
 use crate::vertex::Vertex;
+use crate::video_prefetch::{PrefetchSettings, VideoPrefetcher};
 use crate::{
     editor::{CANVAS_HORIZ_OFFSET, CANVAS_VERT_OFFSET},
 };
 
+/// Decodes a video file's frames, with Media Foundation natively
+#[cfg(not(target_arch = "wasm32"))]
+pub type SourceReader = IMFSourceReader;
+#[cfg(not(target_arch = "wasm32"))]
+pub type DecodeError = windows::core::Error;
+
+/// There's no decoder in the browser yet, so none can be created and videos show placeholders
+#[cfg(target_arch = "wasm32")]
+pub enum SourceReader {}
+#[cfg(target_arch = "wasm32")]
+pub type DecodeError = String;
+
+#[cfg(target_arch = "wasm32")]
+const NO_BROWSER_DECODER: &str = "Videos can't be decoded in the browser yet";
+
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
 pub struct SavedStVideoConfig {
     pub id: String,
@@ -32,6 +65,120 @@ pub struct SavedStVideoConfig {
     pub position: SavedPoint,
     pub layer: i32,
     pub mouse_path: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<ColorTag>,
+    #[serde(default)]
+    pub frame_interpolation: Option<FrameInterpolationSettings>,
+    #[serde(default)]
+    pub locked: bool,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default = "default_opacity")]
+    pub opacity: i32, // out of 100, like opacity keyframes
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+    #[serde(default)]
+    pub mask_target_id: Option<String>, // makes this a mask for the object with this id
+    #[serde(default)]
+    pub mask_mode: MaskMode,
+    #[serde(default)]
+    pub stabilization: Option<SavedStabilization>,
+    #[serde(default)]
+    pub matte: Option<SavedMatteConfig>,
+    #[serde(default)]
+    pub redactions: Vec<RedactionRegion>,
+    #[serde(default)]
+    pub trim: Option<VideoTrim>,
+    #[serde(default = "default_speed")]
+    pub speed: i32, // out of 100, like speed keyframes
+    #[serde(default)]
+    pub frame_skip_policy: Option<FrameSkipPolicy>, // overrides the editor's policy
+    #[serde(default)]
+    pub proxy: Option<VideoProxy>, // previewed instead of the original when it exists
+    #[serde(default)]
+    pub playback_mode: VideoPlaybackMode,
+    #[serde(default)]
+    pub cursor: Option<SavedCursorOverlay>,
+}
+
+/// What playback does when it falls behind a video, such as when steps take longer than a frame
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub enum FrameSkipPolicy {
+    /// Shows every frame, at most one per step, so the video lags rather than dropping any
+    Never,
+    /// Jumps to the frame that's due however far behind, decoding the ones between unseen
+    SkipToLatest,
+    /// Decodes up to this many frames per step, showing the last, and leaves the rest for
+    /// later steps so a slow step doesn't stall on decoding
+    BoundedCatchUp(u32),
+}
+
+impl Default for FrameSkipPolicy {
+    fn default() -> Self {
+        FrameSkipPolicy::BoundedCatchUp(5)
+    }
+}
+
+impl FrameSkipPolicy {
+    /// Frames to decode this step when this many are due
+    pub fn frames_to_decode(&self, frames_due: u32) -> u32 {
+        match self {
+            FrameSkipPolicy::Never => frames_due.min(1),
+            FrameSkipPolicy::SkipToLatest => frames_due,
+            FrameSkipPolicy::BoundedCatchUp(max_frames) => frames_due.min((*max_frames).max(1)),
+        }
+    }
+}
+
+/// What a video does once it reaches its out point
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub enum VideoPlaybackMode {
+    /// Holds the last frame
+    #[default]
+    Once,
+    /// Starts again from the in point
+    Loop,
+    /// Plays back to the in point, then forwards again
+    PingPong,
+}
+
+/// How well a video has kept up since playback last started
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct VideoPlaybackStats {
+    pub frames_shown: u64,
+    pub frames_skipped: u64, // decoded but never shown
+    pub drift_ms: f32,       // how far the shown frame is behind playback, 0 when on time
+    pub max_drift_ms: f32,
+}
+
+impl VideoPlaybackStats {
+    pub fn record_frame(&mut self, skipped: bool) {
+        if skipped {
+            self.frames_skipped += 1;
+        } else {
+            self.frames_shown += 1;
+        }
+    }
+
+    pub fn record_drift(&mut self, drift_ms: f32) {
+        self.drift_ms = drift_ms;
+        self.max_drift_ms = self.max_drift_ms.max(drift_ms);
+    }
+}
+
+pub fn default_speed() -> i32 {
+    100
+}
+
+/// Speeds a video can be set to, out of 100
+pub const MIN_VIDEO_SPEED: i32 = 10;
+pub const MAX_VIDEO_SPEED: i32 = 1000;
+
+/// Source in and out points, so only part of a recording plays. Snapped to whole frames.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct VideoTrim {
+    pub start_ms: i32,
+    pub end_ms: i32,
 }
 
 #[derive(Clone)]
@@ -53,6 +200,7 @@ pub struct StVideo {
     pub source_duration: i64,
     pub source_duration_ms: i64,
     pub source_dimensions: (u32, u32),
+    pub frame_dimensions: (u32, u32), // as decoded, smaller than source_dimensions for proxies
     pub source_frame_rate: f64,
     pub texture: wgpu::Texture,
     pub texture_view: wgpu::TextureView,
@@ -66,6 +214,13 @@ pub struct StVideo {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
     pub hidden: bool,
+    pub user_hidden: bool, // hidden from the layer panel, separate from sequence visibility
+    pub locked: bool,
+    pub base_opacity: f32,     // static opacity, multiplied with animated opacity
+    pub animated_opacity: f32, // last opacity from keyframes
+    pub blend_mode: BlendMode,
+    pub mask_target_id: Option<String>,
+    pub mask_mode: MaskMode,
     pub layer: i32,
     pub group_bind_group: wgpu::BindGroup,
     pub current_zoom: f32,
@@ -81,10 +236,21 @@ pub struct StVideo {
     pub dynamic_alpha: f32,
     pub num_frames_drawn: u32,
     pub original_dimensions: (u32, u32),
-    #[cfg(target_os = "windows")]
-    pub source_reader: IMFSourceReader,
-    // #[cfg(target_arch = "wasm32")]
-    // pub source_reader: WebCodecs
+    pub interpolator: Option<FrameInterpolator>, // smooths export when the video has fewer frames
+    pub stabilization: Option<SavedStabilization>,
+    pub zoom_window: [f32; 4], // min x, min y, max x, max y of the texture shown at the current zoom
+    pub matte: Option<Matte>,  // background removal, for webcam overlays
+    pub redactions: Vec<RedactionRegion>,
+    pub trim: Option<VideoTrim>,
+    pub speed: f32, // 2.0 plays the source twice as fast, unless speed keyframes ramp it
+    pub frame_skip_policy: Option<FrameSkipPolicy>, // None follows the editor's policy
+    pub playback_mode: VideoPlaybackMode,
+    pub playback_stats: VideoPlaybackStats,
+    pub load_error: Option<String>, // why the video shows a placeholder, without a source reader
+    pub prefetcher: Option<VideoPrefetcher>, // decodes ahead on a worker, see start_prefetch
+    pub proxy: Option<VideoProxy>,  // decoded in place of the file at path
+    pub cursor: Option<CursorOverlay>, // drawn along the mouse path, see set_cursor
+    pub source_reader: Option<SourceReader>,
 }
 
 impl StVideo {
@@ -99,10 +265,84 @@ impl StVideo {
         z_index: f32,
         new_id: String,
         current_sequence_id: Uuid,
-    ) -> Result<Self, windows::core::Error> {
+    ) -> Result<Self, DecodeError> {
         let (source_reader, duration, duration_ms, source_width, source_height, source_frame_rate) =
             Self::initialize_media_source(path)?;
 
+        Ok(Self::from_source(
+            device,
+            queue,
+            path,
+            video_config,
+            window_size,
+            bind_group_layout,
+            group_bind_group_layout,
+            new_id,
+            current_sequence_id,
+            Some(source_reader),
+            (duration, duration_ms),
+            (source_width, source_height),
+            source_frame_rate,
+        ))
+    }
+
+    /// Stands in for a video that couldn't be opened, showing a checkerboard with the error
+    /// so the rest of the project can still be edited and exported. It keeps the video's
+    /// id, path and placement, so the saved video is untouched.
+    pub fn new_placeholder(
+        device: &Device,
+        queue: &Queue,
+        path: &Path,
+        video_config: StVideoConfig,
+        window_size: &WindowSize,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        group_bind_group_layout: &Arc<wgpu::BindGroupLayout>,
+        new_id: String,
+        current_sequence_id: Uuid,
+        error: String,
+        font_data: &[u8],
+    ) -> Self {
+        // enough pixels for the message to be readable at the placed size
+        let placeholder_dimensions = (
+            video_config.dimensions.0.clamp(64, 1920),
+            video_config.dimensions.1.clamp(64, 1080),
+        );
+
+        let mut placeholder = Self::from_source(
+            device,
+            queue,
+            path,
+            video_config,
+            window_size,
+            bind_group_layout,
+            group_bind_group_layout,
+            new_id,
+            current_sequence_id,
+            None,
+            (0, 0),
+            placeholder_dimensions,
+            30.0,
+        );
+        placeholder.show_error(queue, error, font_data);
+
+        placeholder
+    }
+
+    fn from_source(
+        device: &Device,
+        queue: &Queue,
+        path: &Path,
+        video_config: StVideoConfig,
+        window_size: &WindowSize,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        group_bind_group_layout: &Arc<wgpu::BindGroupLayout>,
+        new_id: String,
+        current_sequence_id: Uuid,
+        source_reader: Option<SourceReader>,
+        (duration, duration_ms): (i64, i64),
+        (source_width, source_height): (u32, u32),
+        source_frame_rate: f64,
+    ) -> Self {
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Video Texture"),
             size: wgpu::Extent3d {
@@ -274,7 +514,7 @@ impl StVideo {
             new_id, duration_ms, source_frame_rate
         );
 
-        Ok(Self {
+        Self {
             id: new_id,
             current_sequence_id,
             name: video_config.name,
@@ -285,6 +525,7 @@ impl StVideo {
             source_duration: duration,
             source_duration_ms: duration_ms,
             source_dimensions: (source_width, source_height),
+            frame_dimensions: (source_width, source_height),
             source_frame_rate,
             texture,
             texture_view,
@@ -296,6 +537,13 @@ impl StVideo {
             vertices,
             indices,
             hidden: false,
+            user_hidden: false,
+            locked: false,
+            base_opacity: 1.0,
+            animated_opacity: 1.0,
+            blend_mode: BlendMode::Normal,
+            mask_target_id: None,
+            mask_mode: MaskMode::Clip,
             layer: video_config.layer - 0,
             source_reader,
             group_bind_group: tmp_group_bind_group,
@@ -311,14 +559,76 @@ impl StVideo {
             frame_timer: None,
             dynamic_alpha: 0.01,
             num_frames_drawn: 0,
-            original_dimensions: video_config.dimensions
-        })
+            original_dimensions: video_config.dimensions,
+            interpolator: None,
+            stabilization: None,
+            zoom_window: [0.0, 0.0, 1.0, 1.0],
+            matte: None,
+            redactions: Vec::new(),
+            trim: None,
+            speed: 1.0,
+            frame_skip_policy: None,
+            playback_mode: VideoPlaybackMode::Once,
+            playback_stats: VideoPlaybackStats::default(),
+            load_error: None,
+            prefetcher: None,
+            proxy: None,
+            cursor: None,
+        }
     }
 
-    #[cfg(target_os = "windows")]
+    /// Draws a cursor along the recorded mouse path, or stops with None. clicks are the
+    /// recording's click timestamps, for the click animation.
+    pub fn set_cursor(
+        &mut self,
+        config: Option<SavedCursorOverlay>,
+        clicks: Vec<u128>,
+    ) -> Result<(), String> {
+        let Some(config) = config else {
+            self.cursor = None;
+            return Ok(());
+        };
+
+        let mouse_positions = self
+            .mouse_positions
+            .as_ref()
+            .ok_or("This video wasn't captured with mouse tracking")?;
+        self.cursor = Some(CursorOverlay::new(config, mouse_positions, clicks)?);
+
+        Ok(())
+    }
+
+    /// Marks a video opened from a proxy as standing in for the original at `original_path`,
+    /// which saving and analysis refer to, as do positions in source pixels
+    pub fn set_proxy(&mut self, original_path: String, proxy: VideoProxy) {
+        self.path = original_path;
+        self.source_dimensions = proxy.source_dimensions;
+        self.proxy = Some(proxy);
+    }
+
+    /// The file frames are decoded from
+    pub fn decode_path(&self) -> &str {
+        self.proxy.as_ref().map_or(&self.path, |proxy| &proxy.path)
+    }
+
+    /// Swaps the picture for a checkerboard with the error written over it, and stops decoding
+    pub fn show_error(&mut self, queue: &Queue, error: String, font_data: &[u8]) {
+        println!("Video {} couldn't be played: {}", self.id, error);
+
+        let (width, height) = self.frame_dimensions;
+        let frame_data = placeholder_pixels(width, height, &error, font_data);
+        self.write_frame_data(queue, &frame_data);
+
+        self.source_reader = None;
+        self.source_duration = 0;
+        self.source_duration_ms = 0;
+        self.load_error = Some(error);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
     fn initialize_media_source(
         path: &Path,
-    ) -> Result<(IMFSourceReader, i64, i64, u32, u32, f64), windows::core::Error> {
+    ) -> Result<(SourceReader, i64, i64, u32, u32, f64), DecodeError> {
         // Intialize Media Foundation
         unsafe {
             MFStartup(MF_VERSION, MFSTARTUP_FULL)?;
@@ -380,13 +690,23 @@ impl StVideo {
         ))
     }
 
-    // #[cfg(target_arch = "wasm32")]
-    // fn initialize_media_source() {}
+    #[cfg(target_arch = "wasm32")]
+    fn initialize_media_source(
+        path: &Path,
+    ) -> Result<(SourceReader, i64, i64, u32, u32, f64), DecodeError> {
+        Err(NO_BROWSER_DECODER.to_string())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn create_source_reader(file_path: &str) -> Result<SourceReader, DecodeError> {
+        Err(NO_BROWSER_DECODER.to_string())
+    }
 
-    fn create_source_reader(
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn create_source_reader(
         // &self,
         file_path: &str,
-    ) -> Result<IMFSourceReader, windows::core::Error> {
+    ) -> Result<SourceReader, DecodeError> {
         unsafe {
             let wide_path: Vec<u16> = file_path.encode_utf16().chain(Some(0)).collect();
 
@@ -420,79 +740,481 @@ impl StVideo {
         }
     }
 
-    pub fn draw_video_frame(&self, device: &Device, queue: &Queue) -> windows::core::Result<()> {
-        unsafe {
-            // println!("Drawing video frame");
-            let mut flags: u32 = 0;
-            let mut timestamp: i64 = 0; // store timestamp for later use?
-            let mut sample: Option<IMFSample> = None;
-            let actual_stream_index: &mut u32 = &mut 0;
-
-            // println!("Reading sample");
-            self.source_reader.ReadSample(
-                MF_SOURCE_READER_FIRST_VIDEO_STREAM.0 as u32,
-                0,
-                Some(actual_stream_index),
-                Some(&mut flags),
-                Some(&mut timestamp),
-                Some(&mut sample),
-            )?;
+    pub fn draw_video_frame(&mut self, device: &Device, queue: &Queue) -> Result<(), DecodeError> {
+        // the end of the stream keeps the last frame
+        if let Some(frame_data) = self.next_frame()? {
+            self.show_frame(queue, frame_data);
+        }
 
-            // println!("Convert to buffer");
-            let sample = sample.as_ref().expect("Couldn't get sample container");
-            let buffer = sample.ConvertToContiguousBuffer()?;
-
-            // println!("Lock buffer");
-            let mut data_ptr: *mut u8 = std::ptr::null_mut();
-            let mut data_len: u32 = 0;
-            let mut max_length = 0;
-            buffer.Lock(&mut data_ptr, Some(&mut max_length), Some(&mut data_len))?;
-
-            // println!("Copy data");
-            let mut frame_data = Vec::with_capacity(data_len as usize);
-            std::ptr::copy_nonoverlapping(data_ptr, frame_data.as_mut_ptr(), data_len as usize);
-            frame_data.set_len(data_len as usize);
-
-            // println!("Unlock buffer");
-            buffer.Unlock()?;
-
-            // println!("Write texture {:?}", frame_data.len());
-            // Write texture data
-            // need to write nv12 / YUV data to texture with proper bytes per row
-            queue.write_texture(
-                wgpu::ImageCopyTexture {
-                    texture: &self.texture,
-                    mip_level: 0,
-                    origin: wgpu::Origin3d::ZERO,
-                    aspect: wgpu::TextureAspect::All,
-                },
-                &frame_data,
-                wgpu::ImageDataLayout {
-                    offset: 0,
-                    bytes_per_row: Some(4 * self.source_dimensions.0),
-                    rows_per_image: Some(self.source_dimensions.1),
-                },
-                wgpu::Extent3d {
-                    width: self.source_dimensions.0,
-                    height: self.source_dimensions.1,
-                    depth_or_array_layers: 1,
-                },
+        Ok(())
+    }
+
+    /// Decodes frames ahead of playback on a worker thread, from the next frame to be drawn.
+    /// Stepping through frames then only waits on decoding when the worker falls behind.
+    pub fn start_prefetch(&mut self, settings: PrefetchSettings) {
+        if self.source_reader.is_none() {
+            return;
+        }
+
+        let frame_bytes = 4 * self.frame_dimensions.0 as usize * self.frame_dimensions.1 as usize;
+        self.prefetcher = Some(VideoPrefetcher::start(
+            self.decode_path(),
+            self.source_frame_rate,
+            frame_bytes,
+            self.trim_start_frame() + self.num_frames_drawn,
+            settings,
+        ));
+    }
+
+    /// Goes back to decoding on demand, from where the worker had got to
+    pub fn stop_prefetch(&mut self) -> Result<(), DecodeError> {
+        if self.prefetcher.take().is_none() {
+            return Ok(());
+        }
+
+        let next_frame = self.trim_start_frame() + self.num_frames_drawn;
+        if next_frame == 0 {
+            let Some(source_reader) = self.source_reader.as_ref() else {
+                return Ok(());
+            };
+            Self::set_reader_position(source_reader, 0)?;
+        } else {
+            self.seek_source(next_frame)?;
+        }
+
+        Ok(())
+    }
+
+    /// The next decoded frame, from the prefetcher when there is one
+    fn next_frame(&mut self) -> Result<Option<Vec<u8>>, DecodeError> {
+        if let Some(prefetcher) = self.prefetcher.as_mut() {
+            #[cfg(not(target_arch = "wasm32"))]
+            return prefetcher
+                .next_frame()
+                .map_err(|e| windows::core::Error::new::<&str>(E_FAIL, &e));
+            #[cfg(target_arch = "wasm32")]
+            return prefetcher.next_frame();
+        }
+
+        let Some(source_reader) = self.source_reader.as_ref() else {
+            return Ok(None);
+        };
+
+        Self::read_next_frame(source_reader)
+    }
+
+    /// Runs a decoded frame through the matte and redactions and uploads it
+    fn show_frame(&mut self, queue: &Queue, mut frame_data: Vec<u8>) {
+        if let Some(matte) = self.matte.as_mut() {
+            matte.apply(
+                &mut frame_data,
+                self.frame_dimensions.0,
+                self.frame_dimensions.1,
             );
+        }
 
-            Ok(())
+        if !self.redactions.is_empty() {
+            // redactions are timed against the whole recording
+            let source_frame = self.trim_start_frame() + self.num_frames_drawn;
+            let time_ms = (source_frame as f64 / self.source_frame_rate * 1000.0) as i32;
+            if self.frame_dimensions == self.source_dimensions {
+                apply_redactions(
+                    &mut frame_data,
+                    self.frame_dimensions.0,
+                    self.frame_dimensions.1,
+                    &self.redactions,
+                    time_ms,
+                );
+            } else {
+                // regions are in the original's pixels
+                let scale_x = self.frame_dimensions.0 as f32 / self.source_dimensions.0 as f32;
+                let scale_y = self.frame_dimensions.1 as f32 / self.source_dimensions.1 as f32;
+                let redactions: Vec<RedactionRegion> = self
+                    .redactions
+                    .iter()
+                    .map(|region| region.scaled(scale_x, scale_y))
+                    .collect();
+                apply_redactions(
+                    &mut frame_data,
+                    self.frame_dimensions.0,
+                    self.frame_dimensions.1,
+                    &redactions,
+                    time_ms,
+                );
+            }
+        }
+
+        if let (Some(cursor), Some(source_data)) = (self.cursor.as_ref(), self.source_data.as_ref())
+        {
+            // mouse positions are timed against the whole recording too
+            let source_frame = self.trim_start_frame() + self.num_frames_drawn;
+            let time_ms = (source_frame as f64 / self.source_frame_rate * 1000.0) as u128;
+            cursor.draw(
+                &mut frame_data,
+                self.frame_dimensions,
+                self.source_dimensions,
+                source_data,
+                time_ms,
+            );
+        }
+
+        self.write_frame_data(queue, &frame_data);
+
+        if let Some(interpolator) = self.interpolator.as_mut() {
+            interpolator.push_frame(frame_data);
+        }
+
+        if self.stabilization.is_some() {
+            self.update_tex_coords(queue, self.num_frames_drawn as usize);
         }
     }
 
-    pub fn reset_playback(&mut self) -> Result<(), windows::core::Error> {
-        let time = PROPVARIANT::from(0i64);
+    /// Decodes the next frame from a reader of its own, returning None at the end of the stream
+    pub(crate) fn read_next_frame(
+        source_reader: &SourceReader,
+    ) -> Result<Option<Vec<u8>>, DecodeError> {
+        Ok(Self::read_next_sample(source_reader)?.map(|(_, frame_data)| frame_data))
+    }
 
+    /// The next frame along with its timestamp in 100 nanosecond units
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read_next_sample(
+        source_reader: &SourceReader,
+    ) -> Result<Option<(i64, Vec<u8>)>, DecodeError> {
         unsafe {
-            self.source_reader.SetCurrentPosition(&GUID_NULL, &time)?;
+            loop {
+                let mut flags: u32 = 0;
+                let mut timestamp: i64 = 0;
+                let mut sample: Option<IMFSample> = None;
+                let actual_stream_index: &mut u32 = &mut 0;
+
+                source_reader.ReadSample(
+                    MF_SOURCE_READER_FIRST_VIDEO_STREAM.0 as u32,
+                    0,
+                    Some(actual_stream_index),
+                    Some(&mut flags),
+                    Some(&mut timestamp),
+                    Some(&mut sample),
+                )?;
+
+                if flags & MF_SOURCE_READERF_ENDOFSTREAM.0 as u32 != 0 {
+                    return Ok(None);
+                }
+
+                // gaps in the stream don't carry a sample, read on to the next one
+                let Some(sample) = sample else {
+                    continue;
+                };
+                let buffer = sample.ConvertToContiguousBuffer()?;
+
+                let mut data_ptr: *mut u8 = std::ptr::null_mut();
+                let mut data_len: u32 = 0;
+                let mut max_length = 0;
+                buffer.Lock(&mut data_ptr, Some(&mut max_length), Some(&mut data_len))?;
+
+                let mut frame_data = Vec::with_capacity(data_len as usize);
+                std::ptr::copy_nonoverlapping(data_ptr, frame_data.as_mut_ptr(), data_len as usize);
+                frame_data.set_len(data_len as usize);
+
+                buffer.Unlock()?;
+
+                return Ok(Some((timestamp, frame_data)));
+            }
         }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn read_next_sample(source_reader: &SourceReader) -> Result<Option<(i64, Vec<u8>)>, DecodeError> {
+        match *source_reader {}
+    }
+
+    /// Seeks the reader to a time in 100 nanosecond units. It lands on the keyframe before.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn set_reader_position(source_reader: &SourceReader, time: i64) -> Result<(), DecodeError> {
+        unsafe { source_reader.SetCurrentPosition(&GUID_NULL, &PROPVARIANT::from(time)) }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn set_reader_position(source_reader: &SourceReader, time: i64) -> Result<(), DecodeError> {
+        match *source_reader {}
+    }
+
+    /// Shows the frame `t` of the way between the last two decoded frames. The interpolated
+    /// picture runs one source frame behind, so there's a decoded frame on either side of it.
+    pub fn draw_interpolated_frame(&mut self, queue: &Queue, t: f32) {
+        let Some(interpolator) = self.interpolator.as_mut() else {
+            return;
+        };
+
+        if let Some(frame_data) = interpolator.interpolate(t) {
+            let frame_data = frame_data.to_vec();
+            self.write_frame_data(queue, &frame_data);
+
+            // the shown frame starts from the second to last decoded frame
+            if self.stabilization.is_some() {
+                self.update_tex_coords(queue, self.num_frames_drawn.saturating_sub(2) as usize);
+            }
+        }
+    }
+
+    pub fn update_stabilization(&mut self, queue: &Queue, stabilization: Option<SavedStabilization>) {
+        self.stabilization = stabilization;
+        self.update_tex_coords(queue, self.num_frames_drawn.saturating_sub(1) as usize);
+    }
+
+    pub fn update_frame_interpolation(&mut self, settings: Option<FrameInterpolationSettings>) {
+        self.interpolator = settings.map(|settings| {
+            FrameInterpolator::new(settings, self.frame_dimensions.0, self.frame_dimensions.1)
+        });
+    }
+
+    fn write_frame_data(&self, queue: &Queue, frame_data: &[u8]) {
+        // Write texture data
+        // need to write nv12 / YUV data to texture with proper bytes per row
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            frame_data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * self.frame_dimensions.0),
+                rows_per_image: Some(self.frame_dimensions.1),
+            },
+            wgpu::Extent3d {
+                width: self.frame_dimensions.0,
+                height: self.frame_dimensions.1,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Decodes the next frame without showing it, for skipping frames when playing fast
+    pub fn skip_video_frame(&mut self) -> Result<(), DecodeError> {
+        if let Some(frame_data) = self.next_frame()? {
+            // the interpolator still needs the frame to blend from
+            if let Some(interpolator) = self.interpolator.as_mut() {
+                interpolator.push_frame(frame_data);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rewinds to the in point, so the next frame drawn is the first of the trimmed video
+    pub fn reset_playback(&mut self) -> Result<(), DecodeError> {
+        self.playback_stats = VideoPlaybackStats::default();
+
+        let start_frame = self.trim_start_frame();
+        if let Some(prefetcher) = self.prefetcher.as_mut() {
+            prefetcher.seek(start_frame);
+        } else if start_frame == 0 {
+            let Some(source_reader) = self.source_reader.as_ref() else {
+                return Ok(());
+            };
+
+            Self::set_reader_position(source_reader, 0)?;
+        } else {
+            self.seek_source(start_frame)?;
+        }
+
+        if let Some(interpolator) = self.interpolator.as_mut() {
+            interpolator.reset();
+        }
+
+        Ok(())
+    }
+
+    /// First source frame shown
+    pub fn trim_start_frame(&self) -> u32 {
+        match self.trim {
+            Some(trim) => (trim.start_ms as f64 * self.source_frame_rate / 1000.0).round() as u32,
+            None => 0,
+        }
+    }
+
+    /// Source frame after the last one shown
+    pub fn trim_end_frame(&self) -> u32 {
+        let end_ms = match self.trim {
+            Some(trim) => trim.end_ms as f64,
+            None => self.source_duration_ms as f64,
+        };
+
+        (end_ms * self.source_frame_rate / 1000.0).round() as u32
+    }
+
+    /// Time of the in point in the recording, for lining up mouse positions and the like
+    pub fn trim_start_ms(&self) -> i64 {
+        (self.trim_start_frame() as f64 / self.source_frame_rate * 1000.0) as i64
+    }
+
+    /// How long the video plays for once trimmed, use this over source_duration_ms for timing
+    pub fn playable_duration_ms(&self) -> i64 {
+        let frames = self
+            .trim_end_frame()
+            .saturating_sub(self.trim_start_frame());
+
+        (frames as f64 / self.source_frame_rate * 1000.0) as i64
+    }
+
+    /// How long the trimmed video lasts on the timeline at its speed
+    pub fn timeline_duration_ms(&self) -> i64 {
+        (self.playable_duration_ms() as f32 / self.speed.max(0.01)) as i64
+    }
+
+    /// Evenly spaced filmstrip frames across the trimmed video, for extract_thumbnails
+    pub fn thumbnail_request(&self, count: u32, max_height: u32) -> ThumbnailRequest {
+        ThumbnailRequest {
+            count,
+            max_height,
+            start_frame: self.trim_start_frame(),
+            end_frame: self.trim_end_frame(),
+        }
+    }
+
+    /// Decodes a filmstrip on the calling thread, without touching playback. Hosts can
+    /// instead pass thumbnail_request to extract_thumbnails on a thread of their own.
+    pub fn thumbnails(&self, count: u32, max_height: u32) -> Result<Vec<Thumbnail>, String> {
+        extract_thumbnails(
+            self.decode_path(),
+            self.frame_dimensions,
+            self.source_frame_rate,
+            self.thumbnail_request(count, max_height),
+        )
+    }
+
+    /// Time into the trimmed video to show at a sequence time, following the animation's
+    /// Speed keyframes when it has them and the video's speed otherwise, and repeating
+    /// past the out point for looping playback modes
+    pub fn source_time(&self, animation: &AnimationData, sequence_time: Duration) -> Duration {
+        let time =
+            sequence_time.saturating_sub(Duration::from_millis(animation.start_time_ms as u64));
+        let speed_keyframes = animation
+            .properties
+            .iter()
+            .find(|p| p.property_path == "speed")
+            .map(|p| p.keyframes.as_slice())
+            .unwrap_or(&[]);
+
+        self.repeat_source_time(source_time_at(speed_keyframes, self.speed, time))
+    }
+
+    fn repeat_source_time(&self, time: Duration) -> Duration {
+        let duration_s = self.playable_duration_ms() as f64 / 1000.0;
+        if self.playback_mode == VideoPlaybackMode::Once || duration_s <= 0.0 {
+            return time;
+        }
+
+        let time_s = time.as_secs_f64();
+        let repeated_s = match self.playback_mode {
+            VideoPlaybackMode::PingPong => {
+                let cycle_s = time_s % (duration_s * 2.0);
+                if cycle_s < duration_s {
+                    cycle_s
+                } else {
+                    duration_s * 2.0 - cycle_s
+                }
+            }
+            _ => time_s % duration_s,
+        };
+
+        // stay on the last frame rather than just past it
+        let last_frame_s = (duration_s - 1.0 / self.source_frame_rate).max(0.0);
+        Duration::from_secs_f64(repeated_s.min(last_frame_s))
+    }
+
+    /// Sets the in and out points, or plays the whole recording with None.
+    /// Playback goes back to the in point, which is shown as a preview.
+    pub fn update_trim(&mut self, queue: &Queue, trim: Option<VideoTrim>) -> Result<(), String> {
+        if let Some(trim) = trim {
+            if trim.start_ms < 0
+                || trim.end_ms <= trim.start_ms
+                || trim.end_ms as i64 > self.source_duration_ms
+            {
+                return Err(format!(
+                    "Trim must be within the video's {}ms and end after it starts",
+                    self.source_duration_ms
+                ));
+            }
+        }
+
+        self.trim = trim;
+        self.num_frames_drawn = 0;
+        self.reset_playback().map_err(|e| e.to_string())?;
+        self.seek_to_frame(queue, 1).map_err(|e| e.to_string())?;
+        self.reset_playback().map_err(|e| e.to_string())?;
+        self.num_frames_drawn = 0;
+
+        Ok(())
+    }
+
+    /// Positions the decoder so the next frame drawn is the given frame, counted from the in
+    /// point. Readers only seek to keyframes, so the frames from the keyframe up to the one
+    /// before it are decoded and dropped, then the frame before is shown so there's a picture
+    /// until the next draw.
+    pub fn seek_to_frame(&mut self, queue: &Queue, frame_index: u32) -> Result<(), DecodeError> {
+        if let Some(interpolator) = self.interpolator.as_mut() {
+            interpolator.reset();
+        }
+
+        if frame_index == 0 {
+            self.reset_playback()?;
+            self.num_frames_drawn = 0;
+            return Ok(());
+        }
+
+        let previous_frame = self.seek_source(self.trim_start_frame() + frame_index)?;
+
+        self.num_frames_drawn = frame_index - 1;
+        if let Some(frame_data) = previous_frame {
+            self.show_frame(queue, frame_data);
+        }
+        self.num_frames_drawn = frame_index;
 
         Ok(())
     }
 
+    /// Positions the decoder so the next sample read is the given source frame, returning the
+    /// decoded frame before it
+    fn seek_source(&mut self, source_frame: u32) -> Result<Option<Vec<u8>>, DecodeError> {
+        // the worker decodes on from the frame before, which is taken here
+        if let Some(prefetcher) = self.prefetcher.as_mut() {
+            prefetcher.seek(source_frame.saturating_sub(1));
+            return self.next_frame();
+        }
+
+        let Some(source_reader) = self.source_reader.as_ref() else {
+            return Ok(None);
+        };
+
+        Self::seek_reader(source_reader, self.source_frame_rate, source_frame)
+    }
+
+    /// seek_source for any reader, such as a prefetcher's
+    pub(crate) fn seek_reader(
+        source_reader: &SourceReader,
+        source_frame_rate: f64,
+        source_frame: u32,
+    ) -> Result<Option<Vec<u8>>, DecodeError> {
+        let frame_duration = 10_000_000.0 / source_frame_rate;
+        let previous_frame_time = (source_frame.saturating_sub(1) as f64 * frame_duration) as i64;
+
+        Self::set_reader_position(source_reader, previous_frame_time)?;
+
+        // timestamps can be off by a little, so anything within half a frame is the frame
+        let half_frame = (frame_duration / 2.0) as i64;
+        while let Some((timestamp, frame_data)) = Self::read_next_sample(source_reader)? {
+            if timestamp + half_frame >= previous_frame_time {
+                return Ok(Some(frame_data));
+            }
+        }
+
+        Ok(None)
+    }
+
     pub fn update_data_from_dimensions(
         &mut self,
         window_size: &WindowSize,
@@ -554,6 +1276,15 @@ impl StVideo {
         //         _ => {}
         //     });
 
+        self.zoom_window = [uv_min_x, uv_min_y, uv_max_x, uv_max_y];
+        self.update_tex_coords(queue, self.num_frames_drawn.saturating_sub(1) as usize);
+    }
+
+    /// Maps the grid onto the zoomed part of the texture, moved by the stabilization
+    /// correction for the given frame, counted from the in point
+    fn update_tex_coords(&mut self, queue: &Queue, frame_index: usize) {
+        let frame_index = self.trim_start_frame() as usize + frame_index;
+        let [uv_min_x, uv_min_y, uv_max_x, uv_max_y] = self.zoom_window;
         let (rows, cols) = self.grid_resolution;
 
         // Update UV coordinates for each vertex in place
@@ -566,7 +1297,10 @@ impl StVideo {
                 let uv_x = uv_min_x + (uv_max_x - uv_min_x) * u_ratio;
 
                 let vertex = &mut self.vertices[y as usize * (cols as usize + 1) + x as usize];
-                vertex.tex_coords = [uv_x, uv_y];
+                vertex.tex_coords = match &self.stabilization {
+                    Some(stabilization) => stabilization.correct_tex_coords(frame_index, [uv_x, uv_y]),
+                    None => [uv_x, uv_y],
+                };
             }
         }
 
@@ -662,7 +1396,9 @@ impl StVideo {
     }
 
     pub fn update_opacity(&mut self, queue: &wgpu::Queue, opacity: f32) {
-        let new_color = [1.0, 1.0, 1.0, opacity];
+        self.animated_opacity = opacity;
+
+        let new_color = [1.0, 1.0, 1.0, opacity * self.base_opacity];
 
         self.vertices.iter_mut().for_each(|v| {
             v.color = new_color;
@@ -671,6 +1407,12 @@ impl StVideo {
         queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&self.vertices));
     }
 
+    /// Sets the static opacity, keeping the current animated opacity
+    pub fn update_base_opacity(&mut self, queue: &wgpu::Queue, base_opacity: f32) {
+        self.base_opacity = base_opacity;
+        self.update_opacity(queue, self.animated_opacity);
+    }
+
     pub fn contains_point(&self, point: &Point, camera: &Camera) -> bool {
         let untranslated = Point {
             x: point.x - (self.transform.position.x),
@@ -734,14 +1476,152 @@ impl StVideo {
     }
 }
 
+/// Grey checkerboard in BGRA, like an image editor's transparency, with the message
+/// written in white across the middle, wrapping at the edges
+fn placeholder_pixels(width: u32, height: u32, message: &str, font_data: &[u8]) -> Vec<u8> {
+    let (width, height) = (width as usize, height as usize);
+    let square = (width.min(height) / 12).max(4);
+
+    let mut pixels = vec![0u8; width * height * 4];
+    for y in 0..height {
+        for x in 0..width {
+            let shade = if (x / square + y / square) % 2 == 0 { 90 } else { 60 };
+            let i = (y * width + x) * 4;
+            pixels[i..i + 4].copy_from_slice(&[shade, shade, shade, 255]);
+        }
+    }
+
+    let Ok(font) = fontdue::Font::from_bytes(font_data, fontdue::FontSettings::default()) else {
+        return pixels;
+    };
+
+    let font_size = (height as f32 / 14.0).clamp(10.0, 48.0);
+    let margin = font_size as usize;
+    let line_height = (font_size * 1.4) as usize;
+    let text = format!("Video unavailable: {}", message);
+
+    // break into lines that fit, by character as messages can have long paths in them
+    let mut lines: Vec<Vec<char>> = vec![Vec::new()];
+    let mut line_width = 0.0;
+    for c in text.chars() {
+        let advance = font.metrics(c, font_size).advance_width;
+        if line_width + advance > (width - margin * 2) as f32 && line_width > 0.0 {
+            lines.push(Vec::new());
+            line_width = 0.0;
+        }
+        lines.last_mut().expect("Couldn't get line").push(c);
+        line_width += advance;
+    }
+
+    let text_height = lines.len() * line_height;
+    let mut baseline = height.saturating_sub(text_height) / 2 + font_size as usize;
+    for line in lines {
+        let mut pen_x = margin as f32;
+        for c in line {
+            let (metrics, bitmap) = font.rasterize(c, font_size);
+            let left = pen_x as i32 + metrics.xmin;
+            let top = baseline as i32 - metrics.height as i32 - metrics.ymin;
+
+            for gy in 0..metrics.height {
+                for gx in 0..metrics.width {
+                    let (px, py) = (left + gx as i32, top + gy as i32);
+                    if px < 0 || py < 0 || px as usize >= width || py as usize >= height {
+                        continue;
+                    }
+
+                    let coverage = bitmap[gy * metrics.width + gx] as u32;
+                    let i = (py as usize * width + px as usize) * 4;
+                    for channel in 0..3 {
+                        let background = pixels[i + channel] as u32;
+                        pixels[i + channel] =
+                            ((255 * coverage + background * (255 - coverage)) / 255) as u8;
+                    }
+                }
+            }
+
+            pen_x += metrics.advance_width;
+        }
+        baseline += line_height;
+    }
+
+    pixels
+}
+
+/// Time into a source after playing it for `time` at a constant speed, or along a ramp of
+/// Speed keyframes when there are any. Speeds ease in and out between keyframes like other
+/// properties, Range keys hold their speed until they end, and the first and last speeds
+/// hold before and after the keyframes.
+pub fn source_time_at(speed_keyframes: &[UIKeyframe], base_speed: f32, time: Duration) -> Duration {
+    let mut keyframes: Vec<&UIKeyframe> = speed_keyframes
+        .iter()
+        .filter(|k| matches!(k.value, KeyframeValue::Speed(_)))
+        .collect();
+    keyframes.sort_by_key(|k| k.time);
+
+    let t = time.as_secs_f32();
+    if keyframes.is_empty() {
+        return Duration::from_secs_f32(t * base_speed.max(0.0));
+    }
+
+    // speeds the ramp passes through, a range adds a point where its hold ends
+    let mut points: Vec<(f32, f32)> = Vec::new();
+    for (i, keyframe) in keyframes.iter().enumerate() {
+        let KeyframeValue::Speed(speed) = keyframe.value else {
+            continue;
+        };
+        let speed = speed.max(0) as f32 / 100.0;
+        points.push((keyframe.time.as_secs_f32(), speed));
+
+        if let KeyType::Range(range_data) = &keyframe.key_type {
+            let hold_end = match keyframes.get(i + 1) {
+                Some(next) => range_data.end_time.min(next.time),
+                None => range_data.end_time,
+            };
+            points.push((hold_end.as_secs_f32(), speed));
+        }
+    }
+
+    let (first_time, first_speed) = points[0];
+    let mut source_s = first_speed * t.min(first_time);
+
+    for pair in points.windows(2) {
+        let ((t0, a), (t1, b)) = (pair[0], pair[1]);
+        let duration = t1 - t0;
+        if t <= t0 || duration <= 0.0 {
+            continue;
+        }
+
+        let u = ((t - t0) / duration).min(1.0);
+        source_s += duration * (a * u + (b - a) * eased_area(u));
+    }
+
+    let (last_time, last_speed) = points[points.len() - 1];
+    if t > last_time {
+        source_s += last_speed * (t - last_time);
+    }
+
+    Duration::from_secs_f32(source_s.max(0.0))
+}
+
+/// Area under the ease in out curve from 0 to u
+fn eased_area(u: f32) -> f32 {
+    if u < 0.5 {
+        2.0 * u * u * u / 3.0
+    } else {
+        u + 2.0 * (1.0 - u).powi(3) / 3.0 - 0.5
+    }
+}
+
 // TODO: add to Drop trait?
-fn shutdown_media_foundation() -> Result<(), windows::core::Error> {
+#[cfg(not(target_arch = "wasm32"))]
+fn shutdown_media_foundation() -> Result<(), DecodeError> {
     unsafe {
         MFShutdown()?;
     }
     Ok(())
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl Drop for StVideo {
     fn drop(&mut self) {
         unsafe {
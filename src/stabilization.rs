@@ -0,0 +1,400 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+#[cfg(not(target_arch = "wasm32"))]
+use windows::Win32::Media::MediaFoundation::{MFShutdown, MFStartup, MFSTARTUP_FULL, MF_VERSION};
+#[cfg(not(target_arch = "wasm32"))]
+use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_MULTITHREADED};
+
+use crate::st_video::StVideo;
+
+/// Corrections are fractions of the frame size, stored in thousandths to keep them as i32
+pub const STABILIZATION_PRECISION: f32 = 1000.0;
+
+/// Frames are tracked at this width, which is plenty for finding camera shake
+const ANALYSIS_WIDTH: usize = 320;
+
+/// Features are picked one per cell of this grid, so they're spread over the frame
+const FEATURE_GRID: (usize, usize) = (8, 6);
+
+/// Half size of the patch matched around each feature
+const PATCH_RADIUS: i32 = 4;
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct StabilizationSettings {
+    pub smoothing_radius: i32, // frames either side averaged into the smoothed camera path
+    pub search_radius: i32,    // pixels at analysis size a feature may move between frames
+    pub max_crop: i32,         // percent the frame may be zoomed in to hide moving borders
+}
+
+impl Default for StabilizationSettings {
+    fn default() -> Self {
+        StabilizationSettings {
+            smoothing_radius: 15,
+            search_radius: 12,
+            max_crop: 10,
+        }
+    }
+}
+
+/// Shift and rotation which moves a frame from the shaky path onto the smoothed one
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub struct FrameCorrection {
+    pub x: i32,        // thousandths of the frame width
+    pub y: i32,        // thousandths of the frame height
+    pub rotation: i32, // thousandths of a radian
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct SavedStabilization {
+    pub settings: StabilizationSettings,
+    pub corrections: Vec<FrameCorrection>, // one per source frame
+    pub crop: i32,                         // thousandths of zoom applied to hide borders
+}
+
+impl SavedStabilization {
+    pub fn correction_at(&self, frame_index: usize) -> FrameCorrection {
+        self.corrections
+            .get(frame_index)
+            .or(self.corrections.last())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Where a texture coordinate of the displayed frame samples the source frame.
+    /// The frame is zoomed by the crop so the corrections never reveal its edges.
+    pub fn correct_tex_coords(&self, frame_index: usize, tex_coords: [f32; 2]) -> [f32; 2] {
+        let correction = self.correction_at(frame_index);
+        let zoom = 1.0 + self.crop as f32 / STABILIZATION_PRECISION;
+        let rotation = correction.rotation as f32 / STABILIZATION_PRECISION;
+
+        let u = (tex_coords[0] - 0.5) / zoom;
+        let v = (tex_coords[1] - 0.5) / zoom;
+        let (sin, cos) = (-rotation).sin_cos();
+
+        [
+            0.5 + u * cos - v * sin - correction.x as f32 / STABILIZATION_PRECISION,
+            0.5 + u * sin + v * cos - correction.y as f32 / STABILIZATION_PRECISION,
+        ]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum StabilizationProgress {
+    Progress(f32), // percent of frames analyzed
+    Complete(SavedStabilization),
+    Error(String),
+}
+
+/// Tracks the video's camera motion and smooths it, sending the per-frame corrections when done.
+/// Decodes the whole video, so run it off the main thread.
+pub fn analyze_video(
+    path: String,
+    source_dimensions: (u32, u32),
+    source_duration_ms: i64,
+    source_frame_rate: f64,
+    settings: StabilizationSettings,
+    progress_tx: UnboundedSender<StabilizationProgress>,
+) {
+    #[cfg(not(target_arch = "wasm32"))]
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        if let Err(e) = MFStartup(MF_VERSION, MFSTARTUP_FULL) {
+            progress_tx
+                .send(StabilizationProgress::Error(e.to_string()))
+                .ok();
+            return;
+        }
+    }
+
+    let result = track_motion(
+        &path,
+        source_dimensions,
+        source_duration_ms,
+        source_frame_rate,
+        &settings,
+        &progress_tx,
+    );
+
+    #[cfg(not(target_arch = "wasm32"))]
+    unsafe {
+        MFShutdown().ok();
+        CoUninitialize();
+    }
+
+    match result {
+        Ok(motion) => {
+            let stabilization = smooth_motion(&motion, &settings, source_dimensions);
+            progress_tx
+                .send(StabilizationProgress::Complete(stabilization))
+                .ok();
+        }
+        Err(e) => {
+            progress_tx.send(StabilizationProgress::Error(e)).ok();
+        }
+    }
+}
+
+/// Camera motion between each frame and the one before, in analysis pixels and radians
+fn track_motion(
+    path: &str,
+    source_dimensions: (u32, u32),
+    source_duration_ms: i64,
+    source_frame_rate: f64,
+    settings: &StabilizationSettings,
+    progress_tx: &UnboundedSender<StabilizationProgress>,
+) -> Result<Vec<(f32, f32, f32)>, String> {
+    let source_reader =
+        StVideo::create_source_reader(Path::new(path).to_str().ok_or("Couldn't get path string")?)
+            .map_err(|e| e.to_string())?;
+
+    let expected_frames =
+        ((source_duration_ms as f64 / 1000.0) * source_frame_rate).max(1.0) as usize;
+
+    let mut motion = vec![(0.0, 0.0, 0.0)];
+    let mut previous: Option<LumaFrame> = None;
+
+    while let Some(frame_data) =
+        StVideo::read_next_frame(&source_reader).map_err(|e| e.to_string())?
+    {
+        let current = LumaFrame::from_bgra(&frame_data, source_dimensions);
+
+        if let Some(previous) = &previous {
+            motion.push(estimate_motion(previous, &current, settings.search_radius));
+        }
+        previous = Some(current);
+
+        if motion.len() % 30 == 0 {
+            let progress = (motion.len() as f32 / expected_frames as f32 * 100.0).min(100.0);
+            progress_tx
+                .send(StabilizationProgress::Progress(progress))
+                .ok();
+        }
+    }
+
+    if previous.is_none() {
+        return Err("No frames could be decoded".to_string());
+    }
+
+    Ok(motion)
+}
+
+struct LumaFrame {
+    width: usize,
+    height: usize,
+    pixels: Vec<f32>,
+}
+
+impl LumaFrame {
+    fn from_bgra(frame_data: &[u8], source_dimensions: (u32, u32)) -> Self {
+        let (source_width, source_height) =
+            (source_dimensions.0 as usize, source_dimensions.1 as usize);
+        let width = ANALYSIS_WIDTH.min(source_width.max(1));
+        let height = (source_height * width / source_width.max(1)).max(1);
+
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let sx = x * source_width / width;
+                let sy = y * source_height / height;
+                let i = (sy * source_width + sx) * 4;
+                let luma = frame_data
+                    .get(i..i + 3)
+                    .map(|p| p[2] as f32 * 0.299 + p[1] as f32 * 0.587 + p[0] as f32 * 0.114)
+                    .unwrap_or(0.0);
+                pixels.push(luma);
+            }
+        }
+
+        LumaFrame {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    fn at(&self, x: i32, y: i32) -> f32 {
+        let x = x.clamp(0, self.width as i32 - 1) as usize;
+        let y = y.clamp(0, self.height as i32 - 1) as usize;
+        self.pixels[y * self.width + x]
+    }
+
+    /// Corner strength, high where there's texture in both directions so a patch can be tracked
+    fn corner_score(&self, x: i32, y: i32) -> f32 {
+        let mut score = 0.0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let gx = self.at(x + dx + 1, y + dy) - self.at(x + dx - 1, y + dy);
+                let gy = self.at(x + dx, y + dy + 1) - self.at(x + dx, y + dy - 1);
+                score += gx.abs().min(gy.abs());
+            }
+        }
+        score
+    }
+}
+
+/// Picks features in the previous frame, finds them in the current one and fits a
+/// translation and rotation to how they moved
+fn estimate_motion(
+    previous: &LumaFrame,
+    current: &LumaFrame,
+    search_radius: i32,
+) -> (f32, f32, f32) {
+    let margin = PATCH_RADIUS + search_radius + 1;
+    let (columns, rows) = FEATURE_GRID;
+    let cell_width = previous.width as i32 / columns as i32;
+    let cell_height = previous.height as i32 / rows as i32;
+
+    let mut matches: Vec<((f32, f32), (f32, f32))> = Vec::new();
+
+    for row in 0..rows as i32 {
+        for column in 0..columns as i32 {
+            // strongest corner in the cell
+            let mut best: Option<(i32, i32, f32)> = None;
+            for y in (row * cell_height).max(margin)
+                ..((row + 1) * cell_height).min(previous.height as i32 - margin)
+            {
+                for x in (column * cell_width).max(margin)
+                    ..((column + 1) * cell_width).min(previous.width as i32 - margin)
+                {
+                    let score = previous.corner_score(x, y);
+                    if best.map_or(true, |(_, _, best_score)| score > best_score) {
+                        best = Some((x, y, score));
+                    }
+                }
+            }
+
+            let Some((fx, fy, score)) = best else {
+                continue;
+            };
+            // flat areas like sky or walls can't be tracked
+            if score < 40.0 {
+                continue;
+            }
+
+            let mut best_offset = (0, 0);
+            let mut best_cost = f32::MAX;
+            for oy in -search_radius..=search_radius {
+                for ox in -search_radius..=search_radius {
+                    let mut cost = 0.0;
+                    for py in -PATCH_RADIUS..=PATCH_RADIUS {
+                        for px in -PATCH_RADIUS..=PATCH_RADIUS {
+                            cost += (previous.at(fx + px, fy + py)
+                                - current.at(fx + ox + px, fy + oy + py))
+                            .abs();
+                        }
+                    }
+                    if cost < best_cost {
+                        best_cost = cost;
+                        best_offset = (ox, oy);
+                    }
+                }
+            }
+
+            matches.push((
+                (fx as f32, fy as f32),
+                ((fx + best_offset.0) as f32, (fy + best_offset.1) as f32),
+            ));
+        }
+    }
+
+    if matches.is_empty() {
+        return (0.0, 0.0, 0.0);
+    }
+
+    // moving subjects disagree with the camera motion, keep matches near the median shift
+    let median = |mut values: Vec<f32>| {
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        values[values.len() / 2]
+    };
+    let median_x = median(matches.iter().map(|(a, b)| b.0 - a.0).collect());
+    let median_y = median(matches.iter().map(|(a, b)| b.1 - a.1).collect());
+    let inliers: Vec<_> = matches
+        .into_iter()
+        .filter(|(a, b)| {
+            ((b.0 - a.0) - median_x).abs() <= 2.0 && ((b.1 - a.1) - median_y).abs() <= 2.0
+        })
+        .collect();
+
+    if inliers.len() < 2 {
+        return (median_x, median_y, 0.0);
+    }
+
+    // least squares rigid fit about the centroids
+    let count = inliers.len() as f32;
+    let (mut ax, mut ay, mut bx, mut by) = (0.0, 0.0, 0.0, 0.0);
+    for (a, b) in &inliers {
+        ax += a.0;
+        ay += a.1;
+        bx += b.0;
+        by += b.1;
+    }
+    let (ax, ay, bx, by) = (ax / count, ay / count, bx / count, by / count);
+
+    let (mut cross, mut dot) = (0.0, 0.0);
+    for (a, b) in &inliers {
+        let (px, py) = (a.0 - ax, a.1 - ay);
+        let (qx, qy) = (b.0 - bx, b.1 - by);
+        cross += px * qy - py * qx;
+        dot += px * qx + py * qy;
+    }
+
+    (bx - ax, by - ay, cross.atan2(dot))
+}
+
+/// Accumulates the frame to frame motion into a camera path, smooths it with a moving
+/// average and returns how far each frame is from the smooth path
+fn smooth_motion(
+    motion: &[(f32, f32, f32)],
+    settings: &StabilizationSettings,
+    source_dimensions: (u32, u32),
+) -> SavedStabilization {
+    let analysis_width = ANALYSIS_WIDTH.min(source_dimensions.0.max(1) as usize) as f32;
+    let analysis_height =
+        (source_dimensions.1 as f32 * analysis_width / source_dimensions.0.max(1) as f32).max(1.0);
+
+    let mut path = Vec::with_capacity(motion.len());
+    let mut position = (0.0, 0.0, 0.0);
+    for (dx, dy, dr) in motion {
+        position = (position.0 + dx, position.1 + dy, position.2 + dr);
+        path.push(position);
+    }
+
+    let radius = settings.smoothing_radius.max(1) as usize;
+    let max_shift = settings.max_crop.max(0) as f32 / 100.0 / 2.0;
+
+    let mut corrections = Vec::with_capacity(path.len());
+    let mut largest_shift: f32 = 0.0;
+    for i in 0..path.len() {
+        let start = i.saturating_sub(radius);
+        let end = (i + radius + 1).min(path.len());
+        let window = &path[start..end];
+        let count = window.len() as f32;
+        let smooth = window.iter().fold((0.0, 0.0, 0.0), |sum, p| {
+            (
+                sum.0 + p.0 / count,
+                sum.1 + p.1 / count,
+                sum.2 + p.2 / count,
+            )
+        });
+
+        let x = ((smooth.0 - path[i].0) / analysis_width).clamp(-max_shift, max_shift);
+        let y = ((smooth.1 - path[i].1) / analysis_height).clamp(-max_shift, max_shift);
+        let rotation = smooth.2 - path[i].2;
+
+        largest_shift = largest_shift.max(x.abs()).max(y.abs());
+        corrections.push(FrameCorrection {
+            x: (x * STABILIZATION_PRECISION) as i32,
+            y: (y * STABILIZATION_PRECISION) as i32,
+            rotation: (rotation * STABILIZATION_PRECISION) as i32,
+        });
+    }
+
+    SavedStabilization {
+        settings: settings.clone(),
+        corrections,
+        // zoom just enough to cover the largest shift on both sides
+        crop: (largest_shift * 2.0 * STABILIZATION_PRECISION).ceil() as i32,
+    }
+}
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::animations::{BackgroundFill, ObjectType, Sequence};
+use crate::polygon::SavedStroke;
+
+/// A saved look applied to every text item and polygon in a sequence at once. Fields left
+/// as None keep whatever each object already has.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct StylePreset {
+    pub id: String,
+    pub name: String,
+    pub font_family: Option<String>,
+    pub font_size: Option<i32>,
+    pub text_color: Option<[i32; 4]>,
+    pub text_background: Option<[i32; 4]>,
+    pub polygon_fill: Option<[i32; 4]>,
+    pub polygon_stroke: Option<SavedStroke>,
+    pub border_radius: Option<i32>,
+    pub background_fill: Option<BackgroundFill>, // the sequence's canvas background
+}
+
+/// An object a preset changed, so its live copy and palette links can follow
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct StyledObject {
+    pub object_id: String,
+    pub object_type: ObjectType,
+}
+
+impl StylePreset {
+    /// Takes a preset from a sequence's background and an example text item and polygon,
+    /// leaving out whatever isn't given
+    pub fn capture(
+        name: String,
+        sequence: &Sequence,
+        text_id: Option<&str>,
+        polygon_id: Option<&str>,
+    ) -> Self {
+        let text_item =
+            text_id.and_then(|id| sequence.active_text_items.iter().find(|t| t.id == id));
+        let polygon =
+            polygon_id.and_then(|id| sequence.active_polygons.iter().find(|p| p.id == id));
+
+        StylePreset {
+            id: Uuid::new_v4().to_string(),
+            name,
+            font_family: text_item.map(|t| t.font_family.clone()),
+            font_size: text_item.map(|t| t.font_size),
+            text_color: text_item.map(|t| t.color),
+            text_background: text_item.and_then(|t| t.background_fill),
+            polygon_fill: polygon.map(|p| p.fill),
+            polygon_stroke: polygon.map(|p| p.stroke.clone()),
+            border_radius: polygon.map(|p| p.border_radius),
+            background_fill: sequence.background_fill.clone(),
+        }
+    }
+
+    pub fn styles_text(&self) -> bool {
+        self.font_family.is_some()
+            || self.font_size.is_some()
+            || self.text_color.is_some()
+            || self.text_background.is_some()
+    }
+
+    pub fn styles_polygons(&self) -> bool {
+        self.polygon_fill.is_some() || self.polygon_stroke.is_some() || self.border_radius.is_some()
+    }
+
+    /// Restyles the sequence's text items and polygons, returning the objects changed.
+    /// Masks and spotlight overlays keep their own colors, as they aren't seen as drawn.
+    pub fn apply_to_sequence(&self, sequence: &mut Sequence) -> Vec<StyledObject> {
+        let mut styled = Vec::new();
+
+        if let Some(background_fill) = &self.background_fill {
+            sequence.background_fill = Some(background_fill.clone());
+        }
+
+        if self.styles_text() {
+            for text_item in sequence
+                .active_text_items
+                .iter_mut()
+                .filter(|t| t.mask_target_id.is_none())
+            {
+                if let Some(font_family) = &self.font_family {
+                    text_item.font_family = font_family.clone();
+                }
+                if let Some(font_size) = self.font_size {
+                    text_item.font_size = font_size;
+                }
+                if let Some(color) = self.text_color {
+                    text_item.color = color;
+                }
+                if let Some(background) = self.text_background {
+                    text_item.background_fill = Some(background);
+                }
+
+                styled.push(StyledObject {
+                    object_id: text_item.id.clone(),
+                    object_type: ObjectType::TextItem,
+                });
+            }
+        }
+
+        if self.styles_polygons() {
+            let spotlight_ids: Vec<String> = sequence
+                .active_spotlights
+                .iter()
+                .map(|s| s.id.clone())
+                .collect();
+
+            for polygon in sequence
+                .active_polygons
+                .iter_mut()
+                .filter(|p| p.mask_target_id.is_none() && !spotlight_ids.contains(&p.id))
+            {
+                if let Some(fill) = self.polygon_fill {
+                    polygon.fill = fill;
+                }
+                if let Some(stroke) = &self.polygon_stroke {
+                    polygon.stroke = stroke.clone();
+                }
+                if let Some(border_radius) = self.border_radius {
+                    polygon.border_radius = border_radius;
+                }
+
+                styled.push(StyledObject {
+                    object_id: polygon.id.clone(),
+                    object_type: ObjectType::Polygon,
+                });
+            }
+        }
+
+        styled
+    }
+}
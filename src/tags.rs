@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+
+use crate::animations::{ObjectType, Sequence};
+use crate::saved_state::SavedState;
+
+/// A named, colored label that can be attached to sequences and objects
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct ColorTag {
+    pub name: String,
+    pub color: [i32; 4], // 0-255 like other saved colors
+}
+
+/// An object found by a tag query, with enough info for a layer or timeline panel to locate it
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct TaggedObject {
+    pub sequence_id: String,
+    pub object_id: String,
+    pub object_type: ObjectType,
+    pub name: String,
+}
+
+pub fn has_tag(tags: &[ColorTag], tag_name: &str) -> bool {
+    tags.iter().any(|t| t.name == tag_name)
+}
+
+/// Adds the tag, or updates its color if a tag with the same name is already present
+pub fn upsert_tag(tags: &mut Vec<ColorTag>, tag: ColorTag) {
+    if let Some(existing) = tags.iter_mut().find(|t| t.name == tag.name) {
+        existing.color = tag.color;
+    } else {
+        tags.push(tag);
+    }
+}
+
+pub fn remove_tag(tags: &mut Vec<ColorTag>, tag_name: &str) {
+    tags.retain(|t| t.name != tag_name);
+}
+
+pub fn sequence_objects_with_tag(sequence: &Sequence, tag_name: &str) -> Vec<TaggedObject> {
+    let mut found = Vec::new();
+
+    sequence
+        .active_polygons
+        .iter()
+        .filter(|p| has_tag(&p.tags, tag_name))
+        .for_each(|p| {
+            found.push(TaggedObject {
+                sequence_id: sequence.id.clone(),
+                object_id: p.id.clone(),
+                object_type: ObjectType::Polygon,
+                name: p.name.clone(),
+            })
+        });
+
+    sequence
+        .active_text_items
+        .iter()
+        .filter(|t| has_tag(&t.tags, tag_name))
+        .for_each(|t| {
+            found.push(TaggedObject {
+                sequence_id: sequence.id.clone(),
+                object_id: t.id.clone(),
+                object_type: ObjectType::TextItem,
+                name: t.name.clone(),
+            })
+        });
+
+    sequence
+        .active_image_items
+        .iter()
+        .filter(|i| has_tag(&i.tags, tag_name))
+        .for_each(|i| {
+            found.push(TaggedObject {
+                sequence_id: sequence.id.clone(),
+                object_id: i.id.clone(),
+                object_type: ObjectType::ImageItem,
+                name: i.name.clone(),
+            })
+        });
+
+    sequence
+        .active_video_items
+        .iter()
+        .filter(|v| has_tag(&v.tags, tag_name))
+        .for_each(|v| {
+            found.push(TaggedObject {
+                sequence_id: sequence.id.clone(),
+                object_id: v.id.clone(),
+                object_type: ObjectType::VideoItem,
+                name: v.name.clone(),
+            })
+        });
+
+    sequence
+        .active_groups
+        .iter()
+        .filter(|g| has_tag(&g.tags, tag_name))
+        .for_each(|g| {
+            found.push(TaggedObject {
+                sequence_id: sequence.id.clone(),
+                object_id: g.id.clone(),
+                object_type: ObjectType::Group,
+                name: g.name.clone(),
+            })
+        });
+
+    sequence
+        .active_particle_emitters
+        .iter()
+        .filter(|e| has_tag(&e.tags, tag_name))
+        .for_each(|e| {
+            found.push(TaggedObject {
+                sequence_id: sequence.id.clone(),
+                object_id: e.id.clone(),
+                object_type: ObjectType::ParticleEmitter,
+                name: e.name.clone(),
+            })
+        });
+
+    found
+}
+
+/// Lists every object in the project carrying the tag, e.g. all objects tagged "needs-review"
+pub fn objects_with_tag(saved_state: &SavedState, tag_name: &str) -> Vec<TaggedObject> {
+    saved_state
+        .sequences
+        .iter()
+        .flat_map(|s| sequence_objects_with_tag(s, tag_name))
+        .collect()
+}
+
+pub fn sequences_with_tag<'a>(saved_state: &'a SavedState, tag_name: &str) -> Vec<&'a Sequence> {
+    saved_state
+        .sequences
+        .iter()
+        .filter(|s| has_tag(&s.tags, tag_name))
+        .collect()
+}
+
+/// Collects every distinct tag used in the project, for rendering a tag legend or filter chips
+pub fn all_tags(saved_state: &SavedState) -> Vec<ColorTag> {
+    let mut tags: Vec<ColorTag> = Vec::new();
+
+    for sequence in &saved_state.sequences {
+        let object_tags = sequence
+            .tags
+            .iter()
+            .chain(sequence.active_polygons.iter().flat_map(|p| p.tags.iter()))
+            .chain(sequence.active_text_items.iter().flat_map(|t| t.tags.iter()))
+            .chain(sequence.active_image_items.iter().flat_map(|i| i.tags.iter()))
+            .chain(sequence.active_video_items.iter().flat_map(|v| v.tags.iter()))
+            .chain(sequence.active_groups.iter().flat_map(|g| g.tags.iter()))
+            .chain(sequence.active_particle_emitters.iter().flat_map(|e| e.tags.iter()));
+
+        for tag in object_tags {
+            if !has_tag(&tags, &tag.name) {
+                tags.push(tag.clone());
+            }
+        }
+    }
+
+    tags
+}
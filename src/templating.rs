@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::animations::Sequence;
+
+/// Values for a template's variables, one row of a dataset
+pub type TemplateRow = HashMap<String, String>;
+
+/// Names of the {{variables}} in a text, in order of first use
+pub fn template_variables(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + end].trim();
+        if !name.is_empty() && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        rest = &rest[start + 2 + end + 2..];
+    }
+
+    names
+}
+
+/// Replaces each {{variable}} with its value from the row. Spaces inside the braces are
+/// ignored, and a variable missing from the row is an error rather than left blank.
+pub fn fill_template(text: &str, row: &TemplateRow) -> Result<String, String> {
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + end].trim();
+        let value = row
+            .get(name)
+            .ok_or_else(|| format!("No value for {{{{{}}}}}", name))?;
+
+        filled.push_str(&rest[..start]);
+        filled.push_str(value);
+        rest = &rest[start + 2 + end + 2..];
+    }
+    filled.push_str(rest);
+
+    Ok(filled)
+}
+
+/// Variables used by the text items of some sequences
+pub fn sequence_variables(sequences: &[Sequence]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for text_item in sequences.iter().flat_map(|s| &s.active_text_items) {
+        for name in template_variables(&text_item.text) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+
+    names
+}
+
+/// Copies of the sequences with their text items filled in from a row
+pub fn fill_sequences(sequences: &[Sequence], row: &TemplateRow) -> Result<Vec<Sequence>, String> {
+    let mut filled = sequences.to_vec();
+    for sequence in filled.iter_mut() {
+        for text_item in sequence.active_text_items.iter_mut() {
+            text_item.text = fill_template(&text_item.text, row)
+                .map_err(|e| format!("{} in {}", e, text_item.name))?;
+        }
+    }
+
+    Ok(filled)
+}
+
+/// Reads rows from a .csv file with a header row, or a .json file holding an array of
+/// objects
+pub fn load_dataset(path: &Path) -> Result<Vec<TemplateRow>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .as_deref()
+    {
+        Some("csv") => parse_csv(&text),
+        Some("json") => parse_json_rows(&text),
+        _ => Err(format!("{} isn't a .csv or .json file", path.display())),
+    }
+}
+
+/// Rows of a CSV file, keyed by its header row. Quoted fields may hold commas, line
+/// breaks and doubled quotes.
+pub fn parse_csv(text: &str) -> Result<Vec<TemplateRow>, String> {
+    let mut records: Vec<Vec<String>> = Vec::new();
+    let mut record: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("A quoted field isn't closed".to_string());
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    // blank lines aren't rows
+    records.retain(|r| r.iter().any(|f| !f.trim().is_empty()));
+    let mut records = records.into_iter();
+    let header: Vec<String> = records
+        .next()
+        .ok_or("The CSV file is empty")?
+        .into_iter()
+        .map(|name| name.trim().to_string())
+        .collect();
+
+    records
+        .enumerate()
+        .map(|(index, record)| {
+            if record.len() != header.len() {
+                return Err(format!(
+                    "Row {} has {} fields, the header has {}",
+                    index + 1,
+                    record.len(),
+                    header.len()
+                ));
+            }
+            Ok(header.iter().cloned().zip(record).collect())
+        })
+        .collect()
+}
+
+/// Rows of a JSON array of objects. Numbers and booleans are written as they appear.
+pub fn parse_json_rows(text: &str) -> Result<Vec<TemplateRow>, String> {
+    let rows: Vec<serde_json::Map<String, serde_json::Value>> =
+        serde_json::from_str(text).map_err(|e| format!("Couldn't parse rows: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|(name, value)| {
+                    let value = match value {
+                        serde_json::Value::String(value) => value,
+                        serde_json::Value::Null => String::new(),
+                        other => other.to_string(),
+                    };
+                    (name, value)
+                })
+                .collect()
+        })
+        .collect())
+}
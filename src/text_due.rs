@@ -19,8 +19,10 @@ use std::sync::Arc;
 use wgpu::util::DeviceExt;
 
 use crate::{
+    blend::BlendMode,
     camera::Camera3D as Camera,
     editor::{Point, WindowSize},
+    masking::MaskMode,
     transform::{matrix4_to_raw_array, Transform},
     vertex::Vertex,
 };
@@ -28,7 +30,10 @@ use crate::{editor::rgb_to_wgpu, transform::create_empty_group_transform};
 use crate::editor::wgpu_to_human;
 use crate::{
     editor::{CANVAS_HORIZ_OFFSET, CANVAS_VERT_OFFSET},
-    polygon::{Polygon, SavedPoint, Stroke},
+    polygon::{default_opacity, Polygon, SavedPoint, Stroke},
+    tags::ColorTag,
+    text_editing::CaretStop,
+    warp::{warp_text_vertices, MeshWarp},
 };
 
 pub struct AtlasGlyph {
@@ -63,6 +68,22 @@ pub struct SavedTextRendererConfig {
     pub layer: i32,
     pub color: [i32; 4],
     pub background_fill: Option<[i32; 4]>,
+    #[serde(default)]
+    pub tags: Vec<ColorTag>,
+    #[serde(default)]
+    pub warp: Option<MeshWarp>,
+    #[serde(default)]
+    pub locked: bool,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default = "default_opacity")]
+    pub opacity: i32, // out of 100, like opacity keyframes
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+    #[serde(default)]
+    pub mask_target_id: Option<String>, // makes this a mask for the object with this id
+    #[serde(default)]
+    pub mask_mode: MaskMode,
 }
 
 pub struct TextRenderer {
@@ -87,11 +108,19 @@ pub struct TextRenderer {
     // pub glyph_cache: HashMap<String, AtlasGlyph>,
     pub glyph_cache: HashMap<GlyphRasterConfig, AtlasGlyph>,
     pub hidden: bool,
+    pub user_hidden: bool, // hidden from the layer panel, separate from sequence visibility
+    pub locked: bool,
+    pub base_opacity: f32,     // static opacity, multiplied with animated opacity
+    pub animated_opacity: f32, // last opacity from keyframes
+    pub blend_mode: BlendMode,
+    pub mask_target_id: Option<String>,
+    pub mask_mode: MaskMode,
     pub layer: i32,
     pub color: [i32; 4],
     pub font_size: i32,
     pub group_bind_group: BindGroup,
     pub background_polygon: Polygon,
+    pub warp: Option<MeshWarp>,
 }
 
 impl TextRenderer {
@@ -272,11 +301,19 @@ impl TextRenderer {
             current_row_height: 0,
             glyph_cache: HashMap::new(),
             hidden: false,
+            user_hidden: false,
+            locked: false,
+            base_opacity: 1.0,
+            animated_opacity: 1.0,
+            blend_mode: BlendMode::Normal,
+            mask_target_id: None,
+            mask_mode: MaskMode::Clip,
             layer: text_config.layer - 0,
             color: text_config.color,
             font_size: text_config.font_size,
             group_bind_group: tmp_group_bind_group,
             background_polygon,
+            warp: None,
         }
     }
 
@@ -393,43 +430,12 @@ impl TextRenderer {
         let mut vertices = Vec::new();
         let mut indices: Vec<u32> = Vec::new();
 
-        let text = self.text.clone();
-
-        // Create a layout instance
-        let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
-
-        // Configure layout settings
-        let layout_settings = LayoutSettings {
-            max_width: Some(self.dimensions.0), // Set a maximum width for text wrapping
-            ..LayoutSettings::default()
-        };
-        layout.reset(&layout_settings);
-
-        // Append text to the layout
-        let font = &self.font; // Assuming `self.font` is your `fontdue::Font` instance
-        let style = TextStyle {
-            text: &text,
-            font_index: 0, // Use the first font in the list
-            px: self.font_size as f32,
-            user_data: (),
-        };
-        layout.append(&[font], &style);
+        let layout = self.text_layout();
 
         // Get the laid out glyphs
         let glyphs = layout.glyphs();
 
-        // Calculate the total width and height of the text
-        // TODO: more accurate to just use dimensions instead?
-        let total_width = glyphs
-            .iter()
-            .fold(0.0, |max_width: f32, glyph: &GlyphPosition| {
-                max_width.max(glyph.x + glyph.width as f32)
-            });
-        let total_height = layout.height();
-
-        // Calculate the starting x and y positions to center the text
-        let start_x = -total_width / 2.0;
-        let start_y = -total_height / 2.0;
+        let (start_x, start_y) = layout_origin(&layout);
 
         for glyph in glyphs {
             let key: GlyphRasterConfig = glyph.key; // hashable key
@@ -499,6 +505,10 @@ impl TextRenderer {
             ]);
         }
 
+        if let Some(warp) = &self.warp {
+            warp_text_vertices(&mut vertices, warp, self.dimensions);
+        }
+
         // Update buffers and draw
         queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
         queue.write_buffer(&self.index_buffer, 0, bytemuck::cast_slice(&indices));
@@ -507,6 +517,99 @@ impl TextRenderer {
         self.indices = indices;
     }
 
+    /// Lays out the text wrapped to the item's width, as it's rendered
+    fn text_layout(&self) -> Layout {
+        // Create a layout instance
+        let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+
+        // Configure layout settings
+        let layout_settings = LayoutSettings {
+            max_width: Some(self.dimensions.0), // Set a maximum width for text wrapping
+            ..LayoutSettings::default()
+        };
+        layout.reset(&layout_settings);
+
+        // Append text to the layout
+        let font = &self.font; // Assuming `self.font` is your `fontdue::Font` instance
+        let style = TextStyle {
+            text: &self.text,
+            font_index: 0, // Use the first font in the list
+            px: self.font_size as f32,
+            user_data: (),
+        };
+        layout.append(&[font], &style);
+
+        layout
+    }
+
+    /// Every place a caret can sit in the rendered text, in order, relative to the item's
+    /// position. The last stop is after the final character.
+    pub fn caret_stops(&self) -> Vec<CaretStop> {
+        let layout = self.text_layout();
+        let glyphs = layout.glyphs();
+        let (start_x, start_y) = layout_origin(&layout);
+        let px = self.font_size as f32;
+
+        let line_height = self
+            .font
+            .horizontal_line_metrics(px)
+            .map(|m| m.new_line_size)
+            .unwrap_or(px);
+        let mut stops = Vec::new();
+        let mut last_line = (0, start_y, start_y + line_height);
+
+        for (line_index, line) in layout.lines().into_iter().flatten().enumerate() {
+            let top = start_y + line.baseline_y - line.max_ascent;
+            let bottom = start_y + line.baseline_y - line.min_descent;
+            last_line = (line_index, top, bottom);
+
+            let line_glyphs = glyphs.get(line.glyph_start..=line.glyph_end).unwrap_or(&[]);
+            for glyph in line_glyphs {
+                // glyph positions include their bearing, the caret sits at the pen position
+                let metrics = self.font.metrics(glyph.parent, px);
+                stops.push(CaretStop {
+                    byte_offset: glyph.byte_offset,
+                    line: line_index,
+                    x: start_x + glyph.x - metrics.xmin as f32,
+                    top,
+                    bottom,
+                });
+            }
+        }
+
+        // after the last character, or at the start of a new line after a trailing newline
+        let (line, top, bottom) = last_line;
+        let end = match glyphs.last() {
+            Some(glyph) if glyph.parent == '\n' => CaretStop {
+                byte_offset: self.text.len(),
+                line: line + 1,
+                x: start_x,
+                top: bottom,
+                bottom: bottom + line_height,
+            },
+            Some(glyph) => {
+                let metrics = self.font.metrics(glyph.parent, px);
+                CaretStop {
+                    byte_offset: self.text.len(),
+                    line,
+                    x: start_x + glyph.x - metrics.xmin as f32 + metrics.advance_width,
+                    top,
+                    bottom,
+                }
+            }
+            None => CaretStop {
+                byte_offset: 0,
+                line: 0,
+                x: 0.0,
+                top: -line_height / 2.0,
+                bottom: line_height / 2.0,
+            },
+        };
+        stops.push(end);
+
+        stops
+    }
+
     // pub fn update_color(&mut self, color: [u8; 3]) {
     //     let active_color = rgb_to_wgpu(
     //         self.color[0] as u8,
@@ -516,14 +619,26 @@ impl TextRenderer {
     //     );
     // }
 
+    /// Sets the text's color for color keyframes, keeping its opacity
+    pub fn update_color(&mut self, queue: &wgpu::Queue, color: [i32; 4]) {
+        self.color = color;
+        self.update_opacity(queue, self.animated_opacity);
+    }
+
+    pub fn update_warp(&mut self, device: &Device, queue: &Queue, warp: Option<MeshWarp>) {
+        self.warp = warp;
+        self.render_text(device, queue);
+    }
+
     pub fn update_opacity(&mut self, queue: &wgpu::Queue, opacity: f32) {
+        self.animated_opacity = opacity;
         self.background_polygon.update_opacity(queue, opacity);
 
         let new_color = rgb_to_wgpu(
             self.color[0] as u8,
             self.color[1] as u8,
             self.color[2] as u8,
-            opacity * 255.0,
+            opacity * self.base_opacity * 255.0,
         );
 
         self.vertices.iter_mut().for_each(|v| {
@@ -533,6 +648,13 @@ impl TextRenderer {
         queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&self.vertices));
     }
 
+    /// Sets the static opacity, keeping the current animated opacity
+    pub fn update_base_opacity(&mut self, queue: &wgpu::Queue, base_opacity: f32) {
+        self.base_opacity = base_opacity;
+        self.background_polygon.base_opacity = base_opacity;
+        self.update_opacity(queue, self.animated_opacity);
+    }
+
     pub fn update_data_from_dimensions(
         &mut self,
         window_size: &WindowSize,
@@ -661,3 +783,18 @@ impl TextRenderer {
         )
     }
 }
+
+/// Top left of laid out text, so that it's centered on the item's position
+fn layout_origin(layout: &Layout) -> (f32, f32) {
+    // Calculate the total width and height of the text
+    // TODO: more accurate to just use dimensions instead?
+    let total_width = layout
+        .glyphs()
+        .iter()
+        .fold(0.0, |max_width: f32, glyph: &GlyphPosition| {
+            max_width.max(glyph.x + glyph.width as f32)
+        });
+    let total_height = layout.height();
+
+    (-total_width / 2.0, -total_height / 2.0)
+}
@@ -0,0 +1,335 @@
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+use crate::editor::{BoundingBox, Point};
+
+/// How long the caret is shown, then hidden, while editing
+pub const CARET_BLINK_INTERVAL: Duration = Duration::from_millis(530);
+/// Clicks closer together than this on a text item start editing it
+pub const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+/// How far apart in pixels the clicks of a double click can be
+pub const DOUBLE_CLICK_DISTANCE: f32 = 4.0;
+
+/// Input while editing text on the canvas, translated by the host from its key and IME events
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TextInput {
+    Insert(String), // typed or pasted text, replacing the selection
+    Newline,
+    Backspace,
+    Delete,
+    Left { extend: bool }, // extend grows the selection, usually while Shift is held
+    Right { extend: bool },
+    Up { extend: bool },
+    Down { extend: bool },
+    Home { extend: bool }, // start of the line
+    End { extend: bool },
+    SelectAll,
+    Compose(String), // IME preedit, shown at the caret until committed
+    CommitComposition(String),
+    CancelComposition,
+    Commit, // stop editing and keep the changes, like clicking away
+    Cancel, // stop editing and restore the text
+}
+
+/// A place the caret can sit, relative to the text item's position
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CaretStop {
+    pub byte_offset: usize,
+    pub line: usize,
+    pub x: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+/// A text item being edited on the canvas. Offsets are in bytes and always on char boundaries.
+#[derive(Clone, Debug)]
+pub struct TextEditState {
+    pub text_id: Uuid,
+    pub text: String,          // without the composition
+    pub original_text: String, // restored on cancel
+    pub caret: usize,
+    pub anchor: usize, // other end of the selection, the caret when nothing is selected
+    pub composition: Option<String>,
+    pub stops: Vec<CaretStop>, // of the displayed text, set by the editor after each change
+    pub selecting: bool,       // mouse held down to drag out a selection
+    pub blink_start: Instant,
+    pub caret_visible: bool,
+}
+
+impl TextEditState {
+    pub fn new(text_id: Uuid, text: String) -> Self {
+        let end = text.len();
+
+        TextEditState {
+            text_id,
+            original_text: text.clone(),
+            text,
+            caret: end,
+            anchor: end,
+            composition: None,
+            stops: Vec::new(),
+            selecting: false,
+            blink_start: Instant::now(),
+            caret_visible: true,
+        }
+    }
+
+    /// Start and end of the selection, equal when nothing is selected
+    pub fn selection(&self) -> (usize, usize) {
+        (self.caret.min(self.anchor), self.caret.max(self.anchor))
+    }
+
+    pub fn has_selection(&self) -> bool {
+        self.caret != self.anchor
+    }
+
+    pub fn selected_text(&self) -> &str {
+        let (start, end) = self.selection();
+        &self.text[start..end]
+    }
+
+    /// The text as shown, with any composition at the caret
+    pub fn display_text(&self) -> String {
+        match &self.composition {
+            Some(composition) => {
+                let mut text = self.text.clone();
+                text.insert_str(self.caret, composition);
+                text
+            }
+            None => self.text.clone(),
+        }
+    }
+
+    /// Where the caret is shown, after any composition
+    pub fn display_caret(&self) -> usize {
+        self.caret + self.composition.as_ref().map_or(0, |c| c.len())
+    }
+
+    pub fn set_caret(&mut self, offset: usize, extend: bool) {
+        self.caret = offset.min(self.text.len());
+        if !extend {
+            self.anchor = self.caret;
+        }
+        self.restart_blink();
+    }
+
+    /// Shows the caret again, so it doesn't vanish while typing
+    pub fn restart_blink(&mut self) {
+        self.blink_start = Instant::now();
+        self.caret_visible = true;
+    }
+
+    /// Whether the caret should be shown now
+    pub fn caret_blink_visible(&self) -> bool {
+        let phase = self.blink_start.elapsed().as_millis() / CARET_BLINK_INTERVAL.as_millis();
+        phase % 2 == 0
+    }
+
+    /// Applies input other than commit and cancel, which the editor handles. Returns false
+    /// for input that's ignored, like arrow keys while composing.
+    pub fn apply(&mut self, input: &TextInput) -> bool {
+        let composing = self.composition.is_some();
+
+        match input {
+            TextInput::Insert(text) => {
+                self.composition = None;
+                self.replace_selection(text);
+            }
+            TextInput::Newline => {
+                self.composition = None;
+                self.replace_selection("\n");
+            }
+            TextInput::Backspace if !composing => {
+                if !self.has_selection() {
+                    self.anchor = previous_boundary(&self.text, self.caret);
+                }
+                self.replace_selection("");
+            }
+            TextInput::Delete if !composing => {
+                if !self.has_selection() {
+                    self.anchor = next_boundary(&self.text, self.caret);
+                }
+                self.replace_selection("");
+            }
+            TextInput::Left { extend } if !composing => {
+                let offset = match (self.has_selection(), extend) {
+                    (true, false) => self.selection().0,
+                    _ => previous_boundary(&self.text, self.caret),
+                };
+                self.set_caret(offset, *extend);
+            }
+            TextInput::Right { extend } if !composing => {
+                let offset = match (self.has_selection(), extend) {
+                    (true, false) => self.selection().1,
+                    _ => next_boundary(&self.text, self.caret),
+                };
+                self.set_caret(offset, *extend);
+            }
+            TextInput::Up { extend } if !composing => {
+                let offset = self.offset_on_adjacent_line(-1).unwrap_or(0);
+                self.set_caret(offset, *extend);
+            }
+            TextInput::Down { extend } if !composing => {
+                let offset = self.offset_on_adjacent_line(1).unwrap_or(self.text.len());
+                self.set_caret(offset, *extend);
+            }
+            TextInput::Home { extend } if !composing => {
+                let offset = self.line_stops().first().map_or(0, |s| s.byte_offset);
+                self.set_caret(offset, *extend);
+            }
+            TextInput::End { extend } if !composing => {
+                let offset = self.line_end();
+                self.set_caret(offset, *extend);
+            }
+            TextInput::SelectAll if !composing => {
+                self.anchor = 0;
+                self.caret = self.text.len();
+            }
+            TextInput::Compose(composition) => {
+                // the composition replaces the selection once it starts
+                if self.has_selection() {
+                    self.replace_selection("");
+                }
+                self.composition = Some(composition.clone()).filter(|c| !c.is_empty());
+                self.restart_blink();
+                return true;
+            }
+            TextInput::CommitComposition(text) => {
+                self.composition = None;
+                self.replace_selection(text);
+            }
+            TextInput::CancelComposition => {
+                self.composition = None;
+                return composing;
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// The nearest caret offset to a point relative to the text item's position
+    pub fn offset_at(&self, point: Point) -> usize {
+        let line = match self
+            .stops
+            .iter()
+            .min_by(|a, b| line_distance(a, point.y).total_cmp(&line_distance(b, point.y)))
+        {
+            Some(stop) => stop.line,
+            None => return self.text.len(),
+        };
+
+        self.stops
+            .iter()
+            .filter(|s| s.line == line)
+            .min_by(|a, b| (a.x - point.x).abs().total_cmp(&(b.x - point.x).abs()))
+            .map_or(self.text.len(), |s| s.byte_offset.min(self.text.len()))
+    }
+
+    fn replace_selection(&mut self, text: &str) {
+        let (start, end) = self.selection();
+        self.text.replace_range(start..end, text);
+        self.set_caret(start + text.len(), false);
+    }
+
+    fn caret_stop(&self) -> Option<&CaretStop> {
+        self.stops.iter().find(|s| s.byte_offset == self.caret)
+    }
+
+    fn line_stops(&self) -> Vec<&CaretStop> {
+        match self.caret_stop() {
+            Some(caret) => self.stops.iter().filter(|s| s.line == caret.line).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Before the line's newline, otherwise after its last character
+    fn line_end(&self) -> usize {
+        let last = match self.line_stops().last() {
+            Some(last) => last.byte_offset,
+            None => return self.text.len(),
+        };
+
+        if last >= self.text.len() || self.text[last..].starts_with('\n') {
+            last
+        } else {
+            next_boundary(&self.text, last)
+        }
+    }
+
+    /// The offset nearest the caret's x on the line above or below
+    fn offset_on_adjacent_line(&self, direction: i32) -> Option<usize> {
+        let caret = self.caret_stop()?;
+        let line = caret.line as i32 + direction;
+        if line < 0 {
+            return None;
+        }
+
+        self.stops
+            .iter()
+            .filter(|s| s.line == line as usize)
+            .min_by(|a, b| (a.x - caret.x).abs().total_cmp(&(b.x - caret.x).abs()))
+            .map(|s| s.byte_offset)
+    }
+}
+
+/// Boxes covering the text between two offsets, one per line, relative to the item's position
+pub fn span_boxes(stops: &[CaretStop], start: usize, end: usize) -> Vec<BoundingBox> {
+    let mut boxes: Vec<(usize, BoundingBox)> = Vec::new();
+
+    for stop in stops
+        .iter()
+        .filter(|s| s.byte_offset >= start && s.byte_offset <= end)
+    {
+        match boxes.iter_mut().find(|(line, _)| *line == stop.line) {
+            Some((_, line_box)) => {
+                line_box.min.x = line_box.min.x.min(stop.x);
+                line_box.max.x = line_box.max.x.max(stop.x);
+            }
+            None => boxes.push((
+                stop.line,
+                BoundingBox {
+                    min: Point {
+                        x: stop.x,
+                        y: stop.top,
+                    },
+                    max: Point {
+                        x: stop.x,
+                        y: stop.bottom,
+                    },
+                },
+            )),
+        }
+    }
+
+    boxes
+        .into_iter()
+        .map(|(_, line_box)| line_box)
+        .filter(|line_box| line_box.max.x > line_box.min.x)
+        .collect()
+}
+
+fn line_distance(stop: &CaretStop, y: f32) -> f32 {
+    if y < stop.top {
+        stop.top - y
+    } else if y > stop.bottom {
+        y - stop.bottom
+    } else {
+        0.0
+    }
+}
+
+fn previous_boundary(text: &str, offset: usize) -> usize {
+    text[..offset]
+        .char_indices()
+        .next_back()
+        .map_or(0, |(index, _)| index)
+}
+
+fn next_boundary(text: &str, offset: usize) -> usize {
+    text[offset..]
+        .chars()
+        .next()
+        .map_or(offset, |c| offset + c.len_utf8())
+}
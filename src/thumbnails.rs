@@ -0,0 +1,161 @@
+use std::io::Cursor;
+
+use image::{ImageFormat, RgbaImage};
+#[cfg(not(target_arch = "wasm32"))]
+use windows::Win32::Media::MediaFoundation::{MFShutdown, MFStartup, MFSTARTUP_FULL, MF_VERSION};
+#[cfg(not(target_arch = "wasm32"))]
+use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_MULTITHREADED};
+
+use crate::st_video::StVideo;
+
+/// Where and how large to take filmstrip frames from a video
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ThumbnailRequest {
+    pub count: u32,
+    pub max_height: u32, // thumbnails keep the video's aspect ratio
+    pub start_frame: u32,
+    pub end_frame: u32, // exclusive
+}
+
+/// A small decoded frame for a timeline filmstrip
+#[derive(Clone, Debug)]
+pub struct Thumbnail {
+    pub source_time_ms: i64,
+    pub image: RgbaImage,
+}
+
+impl Thumbnail {
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        let mut png = Cursor::new(Vec::new());
+        self.image
+            .write_to(&mut png, ImageFormat::Png)
+            .map_err(|e| format!("Couldn't encode thumbnail: {}", e))?;
+
+        Ok(png.into_inner())
+    }
+
+    /// Uploads the thumbnail for drawing in a wgpu based UI
+    pub fn create_texture(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::Texture {
+        let (width, height) = self.image.dimensions();
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Thumbnail Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            // matches how video frames are shown
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            self.image.as_raw(),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+
+        texture
+    }
+}
+
+/// Decodes evenly spaced frames with a source reader of its own, so it can run on any
+/// thread while the video plays. Each frame is taken from the middle of its stretch.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn extract_thumbnails(
+    path: &str,
+    source_dimensions: (u32, u32),
+    source_frame_rate: f64,
+    request: ThumbnailRequest,
+) -> Result<Vec<Thumbnail>, String> {
+    // both are reference counted, so this is fine on a thread that already started them
+    let com_initialized = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED).is_ok() };
+    let result = unsafe { MFStartup(MF_VERSION, MFSTARTUP_FULL) }
+        .map_err(|e| e.to_string())
+        .and_then(|_| {
+            let result = decode_thumbnails(path, source_dimensions, source_frame_rate, request);
+            unsafe { MFShutdown().ok() };
+            result
+        });
+
+    if com_initialized {
+        unsafe { CoUninitialize() };
+    }
+
+    result
+}
+
+/// Nothing to start in the browser, where the reader can't be created yet either
+#[cfg(target_arch = "wasm32")]
+pub fn extract_thumbnails(
+    path: &str,
+    source_dimensions: (u32, u32),
+    source_frame_rate: f64,
+    request: ThumbnailRequest,
+) -> Result<Vec<Thumbnail>, String> {
+    decode_thumbnails(path, source_dimensions, source_frame_rate, request)
+}
+
+fn decode_thumbnails(
+    path: &str,
+    source_dimensions: (u32, u32),
+    source_frame_rate: f64,
+    request: ThumbnailRequest,
+) -> Result<Vec<Thumbnail>, String> {
+    let source_reader = StVideo::create_source_reader(path).map_err(|e| e.to_string())?;
+
+    let (source_width, source_height) = source_dimensions;
+    let height = request.max_height.clamp(1, source_height.max(1));
+    let width = ((source_width as u64 * height as u64) / source_height.max(1) as u64).max(1) as u32;
+
+    let span = request.end_frame.saturating_sub(request.start_frame) as f64;
+    let mut thumbnails = Vec::with_capacity(request.count as usize);
+
+    for index in 0..request.count {
+        let offset = (index as f64 + 0.5) * span / request.count as f64;
+        let source_frame = request.start_frame + offset as u32;
+
+        let Some(mut frame_data) =
+            StVideo::seek_reader(&source_reader, source_frame_rate, source_frame)
+                .map_err(|e| e.to_string())?
+        else {
+            break;
+        };
+
+        // frames decode as BGRA
+        for pixel in frame_data.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+            pixel[3] = 255;
+        }
+        let frame = RgbaImage::from_raw(source_width, source_height, frame_data)
+            .ok_or("Decoded frame doesn't match the video size")?;
+
+        thumbnails.push(Thumbnail {
+            source_time_ms: (source_frame as f64 / source_frame_rate * 1000.0) as i64,
+            image: image::imageops::resize(
+                &frame,
+                width,
+                height,
+                image::imageops::FilterType::Triangle,
+            ),
+        });
+    }
+
+    Ok(thumbnails)
+}
@@ -1,12 +1,78 @@
 use serde::{Deserialize, Serialize};
 
+use crate::animations::Sequence;
+
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
 pub struct TimelineSequence {
     pub id: String,
     pub sequence_id: String,
     pub track_type: TrackType,
     pub start_time_ms: i32, // in milliseconds
-                            // pub duration_ms: i32,   // in milliseconds
+    // pub duration_ms: i32,   // in milliseconds
+    #[serde(default)]
+    pub transition_in: Option<SequenceTransition>, // from the previous video sequence
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub enum TransitionKind {
+    Crossfade,
+    Slide, // the incoming sequence pushes the outgoing one off to the left
+    Wipe,  // the incoming sequence is revealed from left to right
+}
+
+/// Blends into a sequence over its first duration_ms, instead of hard-cutting
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct SequenceTransition {
+    pub kind: TransitionKind,
+    pub duration_ms: i32,
+}
+
+/// A transition in progress, both sequences' objects are shown until it finishes
+#[derive(Clone, Debug)]
+pub struct ActiveTransition {
+    pub kind: TransitionKind,
+    pub outgoing_sequence_id: String,
+    pub incoming_sequence_id: String,
+    pub progress: f32,               // 0.0 to 1.0
+    pub applied_offsets: (f32, f32), // slide offsets currently on the outgoing and incoming objects
+}
+
+impl ActiveTransition {
+    /// Opacity multipliers for the outgoing and incoming objects
+    pub fn opacities(&self) -> (f32, f32) {
+        match self.kind {
+            TransitionKind::Crossfade => (1.0 - self.progress, self.progress),
+            _ => (1.0, 1.0),
+        }
+    }
+
+    /// Horizontal offsets for the outgoing and incoming objects
+    pub fn slide_offsets(&self, canvas_width: f32) -> (f32, f32) {
+        match self.kind {
+            TransitionKind::Slide => (
+                -self.progress * canvas_width,
+                (1.0 - self.progress) * canvas_width,
+            ),
+            _ => (0.0, 0.0),
+        }
+    }
+
+    /// Scissor rect (x, y, width, height) for drawing a sequence's objects into a target,
+    /// or None when they aren't clipped
+    pub fn scissor_rect(&self, sequence_id: &str, width: u32, height: u32) -> Option<[u32; 4]> {
+        if self.kind != TransitionKind::Wipe {
+            return None;
+        }
+
+        let split = ((self.progress * width as f32).round() as u32).min(width);
+        if sequence_id == self.incoming_sequence_id {
+            Some([0, 0, split, height])
+        } else if sequence_id == self.outgoing_sequence_id {
+            Some([split, 0, width - split, height])
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
@@ -18,4 +84,217 @@ pub enum TrackType {
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
 pub struct SavedTimelineStateConfig {
     pub timeline_sequences: Vec<TimelineSequence>,
+    #[serde(default)]
+    pub markers: Vec<TimelineMarker>, // kept in time order
+}
+
+/// A labelled point on the timeline, exported as a chapter
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct TimelineMarker {
+    pub id: String,
+    pub time_ms: i32,
+    pub label: String,
+    pub color: [i32; 4], // 0-255 like other saved colors
+}
+
+fn sequence_duration_ms(sequences: &[Sequence], sequence_id: &str) -> i32 {
+    sequences
+        .iter()
+        .find(|s| s.id == sequence_id)
+        .map(|s| s.duration_ms)
+        .unwrap_or(0)
+}
+
+/// Edits leave the timeline as it was when they fail validation
+impl SavedTimelineStateConfig {
+    fn position(&self, timeline_sequence_id: &str) -> Result<usize, String> {
+        self.timeline_sequences
+            .iter()
+            .position(|ts| ts.id == timeline_sequence_id)
+            .ok_or_else(|| format!("No timeline sequence with id {}", timeline_sequence_id))
+    }
+
+    /// Entries on a track, by start time
+    fn track_order(&self, track_type: &TrackType) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..self.timeline_sequences.len())
+            .filter(|&i| &self.timeline_sequences[i].track_type == track_type)
+            .collect();
+        indices.sort_by_key(|&i| self.timeline_sequences[i].start_time_ms);
+        indices
+    }
+
+    /// Where the last entry on a track ends, or zero for an empty track
+    pub fn track_end_ms(&self, track_type: &TrackType, sequences: &[Sequence]) -> i32 {
+        self.timeline_sequences
+            .iter()
+            .filter(|ts| &ts.track_type == track_type)
+            .map(|ts| ts.start_time_ms + sequence_duration_ms(sequences, &ts.sequence_id))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Checks that nothing starts before zero and no two entries on a track overlap
+    pub fn validate(&self, sequences: &[Sequence]) -> Result<(), String> {
+        for track_type in [TrackType::Video, TrackType::Audio] {
+            let mut previous: Option<(&TimelineSequence, i32)> = None;
+            for i in self.track_order(&track_type) {
+                let ts = &self.timeline_sequences[i];
+                if ts.start_time_ms < 0 {
+                    return Err(format!("{} starts before the timeline", ts.id));
+                }
+                if let Some((previous, previous_end_ms)) = previous {
+                    if ts.start_time_ms < previous_end_ms {
+                        return Err(format!("{} overlaps {}", ts.id, previous.id));
+                    }
+                }
+                let end_ms = ts.start_time_ms + sequence_duration_ms(sequences, &ts.sequence_id);
+                previous = Some((ts, end_ms));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(
+        &mut self,
+        sequences: &[Sequence],
+        edit: impl FnOnce(&mut Self) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let mut edited = self.clone();
+        edit(&mut edited)?;
+        edited.validate(sequences)?;
+        *self = edited;
+        Ok(())
+    }
+
+    pub fn insert(
+        &mut self,
+        timeline_sequence: TimelineSequence,
+        sequences: &[Sequence],
+    ) -> Result<(), String> {
+        self.apply(sequences, |timeline| {
+            timeline.timeline_sequences.push(timeline_sequence);
+            Ok(())
+        })
+    }
+
+    /// Removes an entry. With ripple, later entries on its track move back to close the gap.
+    pub fn remove(
+        &mut self,
+        timeline_sequence_id: &str,
+        ripple: bool,
+        sequences: &[Sequence],
+    ) -> Result<TimelineSequence, String> {
+        let index = self.position(timeline_sequence_id)?;
+        let removed = self.timeline_sequences[index].clone();
+
+        self.apply(sequences, |timeline| {
+            timeline.timeline_sequences.remove(index);
+            if ripple {
+                let duration_ms = sequence_duration_ms(sequences, &removed.sequence_id);
+                timeline.shift(&removed.track_type, removed.start_time_ms, -duration_ms);
+            }
+            Ok(())
+        })?;
+
+        Ok(removed)
+    }
+
+    /// Moves an entry to another place on its track. The track's entries are then laid
+    /// back to back from where the first one started.
+    pub fn reorder(
+        &mut self,
+        timeline_sequence_id: &str,
+        new_track_index: usize,
+        sequences: &[Sequence],
+    ) -> Result<(), String> {
+        let index = self.position(timeline_sequence_id)?;
+
+        self.apply(sequences, |timeline| {
+            let track_type = timeline.timeline_sequences[index].track_type.clone();
+            let mut order = timeline.track_order(&track_type);
+            let Some(first) = order.first() else {
+                return Ok(());
+            };
+            let mut start_ms = timeline.timeline_sequences[*first].start_time_ms;
+
+            order.retain(|&i| i != index);
+            order.insert(new_track_index.min(order.len()), index);
+
+            for i in order {
+                let ts = &mut timeline.timeline_sequences[i];
+                ts.start_time_ms = start_ms;
+                start_ms += sequence_duration_ms(sequences, &ts.sequence_id);
+            }
+            Ok(())
+        })
+    }
+
+    /// Moves every entry on a track starting at or after from_ms by delta_ms
+    pub fn ripple_shift(
+        &mut self,
+        track_type: &TrackType,
+        from_ms: i32,
+        delta_ms: i32,
+        sequences: &[Sequence],
+    ) -> Result<(), String> {
+        self.apply(sequences, |timeline| {
+            timeline.shift(track_type, from_ms, delta_ms);
+            Ok(())
+        })
+    }
+
+    pub fn add_marker(&mut self, marker: TimelineMarker) -> Result<(), String> {
+        if marker.time_ms < 0 {
+            return Err("Markers can't be placed before the timeline".to_string());
+        }
+        self.markers.push(marker);
+        self.markers.sort_by_key(|m| m.time_ms);
+        Ok(())
+    }
+
+    pub fn remove_marker(&mut self, marker_id: &str) -> Result<TimelineMarker, String> {
+        let index = self
+            .markers
+            .iter()
+            .position(|m| m.id == marker_id)
+            .ok_or_else(|| format!("No marker with id {}", marker_id))?;
+        Ok(self.markers.remove(index))
+    }
+
+    pub fn move_marker(&mut self, marker_id: &str, time_ms: i32) -> Result<(), String> {
+        if time_ms < 0 {
+            return Err("Markers can't be placed before the timeline".to_string());
+        }
+        let marker = self
+            .markers
+            .iter_mut()
+            .find(|m| m.id == marker_id)
+            .ok_or_else(|| format!("No marker with id {}", marker_id))?;
+        marker.time_ms = time_ms;
+        self.markers.sort_by_key(|m| m.time_ms);
+        Ok(())
+    }
+
+    fn shift(&mut self, track_type: &TrackType, from_ms: i32, delta_ms: i32) {
+        for ts in self.timeline_sequences.iter_mut() {
+            if &ts.track_type == track_type && ts.start_time_ms >= from_ms {
+                ts.start_time_ms += delta_ms;
+            }
+        }
+    }
+}
+
+/// Clips an object to its side of a wipe, or to the whole target otherwise.
+/// Call before drawing each object, with the size of the render target.
+pub fn set_transition_scissor(
+    render_pass: &mut wgpu::RenderPass,
+    transition: Option<&ActiveTransition>,
+    sequence_id: &str,
+    width: u32,
+    height: u32,
+) {
+    let [x, y, w, h] = transition
+        .and_then(|transition| transition.scissor_rect(sequence_id, width, height))
+        .unwrap_or([0, 0, width, height]);
+    render_pass.set_scissor_rect(x, y, w, h);
 }
@@ -11,6 +11,7 @@ use windows::Win32::System::Memory::HeapSetInformation;
 pub struct EncoderConfig {
     pub audio_profile: usize,
     pub video_profile: usize,
+    pub custom_video: Option<H264ProfileInfo>, // used over video_profile, as for proxies
 }
 
 impl Default for EncoderConfig {
@@ -18,6 +19,7 @@ impl Default for EncoderConfig {
         Self {
             audio_profile: 0,
             video_profile: 0,
+            custom_video: None,
         }
     }
 }
@@ -204,7 +206,10 @@ fn create_transcode_profile(config: &EncoderConfig) -> Result<IMFTranscodeProfil
         profile.SetAudioAttributes(&audio_attrs)?;
 
         // Create and set video attributes
-        let video_attrs = create_h264_profile(config.video_profile)?;
+        let video_attrs = match &config.custom_video {
+            Some(profile) => create_h264_attributes(profile)?,
+            None => create_h264_profile(config.video_profile)?,
+        };
         profile.SetVideoAttributes(&video_attrs)?;
 
         // Create and set container attributes
@@ -225,7 +230,7 @@ fn create_transcode_profile(config: &EncoderConfig) -> Result<IMFTranscodeProfil
 // use windows::core::{Result, GUID};
 // use windows::Win32::Media::MediaFoundation::*;
 
-use super::profiles::{AAC_PROFILES, H264_PROFILES};
+use super::profiles::{H264ProfileInfo, AAC_PROFILES, H264_PROFILES};
 use super::session::Session;
 
 fn create_h264_profile(profile_index: usize) -> Result<IMFAttributes> {
@@ -237,8 +242,10 @@ fn create_h264_profile(profile_index: usize) -> Result<IMFAttributes> {
         ));
     }
 
-    let profile = &H264_PROFILES[profile_index];
+    create_h264_attributes(&H264_PROFILES[profile_index])
+}
 
+fn create_h264_attributes(profile: &H264ProfileInfo) -> Result<IMFAttributes> {
     unsafe {
         // Create attributes store
         let attributes: IMFAttributes = {
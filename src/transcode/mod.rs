@@ -1,3 +1,7 @@
+#[cfg(not(target_arch = "wasm32"))]
 pub mod encode;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod profiles;
+pub mod proxy;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod session;
@@ -0,0 +1,125 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use windows::core::Result;
+#[cfg(not(target_arch = "wasm32"))]
+use windows::Win32::Media::MediaFoundation::*;
+#[cfg(not(target_arch = "wasm32"))]
+use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_APARTMENTTHREADED};
+
+#[cfg(not(target_arch = "wasm32"))]
+use super::encode::{encode_media_file, EncoderConfig};
+#[cfg(not(target_arch = "wasm32"))]
+use super::profiles::H264ProfileInfo;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::st_video::StVideo;
+
+/// How small proxies are encoded
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct ProxySettings {
+    pub max_height: u32, // proxies keep the original's aspect ratio and frame rate
+    pub bitrate: u32,
+}
+
+impl Default for ProxySettings {
+    fn default() -> Self {
+        ProxySettings {
+            max_height: 540,
+            bitrate: 2_000_000,
+        }
+    }
+}
+
+/// A low resolution copy of a video for smooth editing. Exports always decode the original.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct VideoProxy {
+    pub path: String,
+    pub source_dimensions: (u32, u32), // of the original, which positions and redactions use
+}
+
+/// Next to the original, as `name.proxy.mp4`
+pub fn proxy_path_for(original_path: &Path) -> PathBuf {
+    let stem = original_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    original_path.with_file_name(format!("{}.proxy.mp4", stem))
+}
+
+/// Fits the height, keeping both sides even as H.264 requires
+pub fn proxy_frame_size(source_dimensions: (u32, u32), max_height: u32) -> (u32, u32) {
+    let (source_width, source_height) = source_dimensions;
+    let height = max_height.min(source_height).max(2);
+    let width = (source_width as u64 * height as u64 / source_height.max(1) as u64) as u32;
+
+    ((width / 2 * 2).max(2), height / 2 * 2)
+}
+
+/// Transcodes a proxy of a video, blocking until it's written. Run this on a background
+/// thread after importing or capturing, then pass the proxy to Editor::set_video_proxy.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn generate_proxy(original_path: &Path, settings: ProxySettings) -> Result<VideoProxy> {
+    let (source_dimensions, frame_rate) = probe_video(original_path)?;
+    let frame_size = proxy_frame_size(source_dimensions, settings.max_height);
+    let proxy_path = proxy_path_for(original_path);
+
+    let config = EncoderConfig {
+        custom_video: Some(H264ProfileInfo {
+            profile: eAVEncH264VProfile_Main.0,
+            fps: frame_rate,
+            frame_size: MFRatio {
+                Numerator: frame_size.0,
+                Denominator: frame_size.1,
+            },
+            bitrate: settings.bitrate,
+        }),
+        ..Default::default()
+    };
+
+    encode_media_file(original_path, proxy_path.as_path(), config)?;
+
+    Ok(VideoProxy {
+        path: proxy_path.to_string_lossy().to_string(),
+        source_dimensions,
+    })
+}
+
+/// The video's size and frame rate
+#[cfg(not(target_arch = "wasm32"))]
+fn probe_video(path: &Path) -> Result<((u32, u32), MFRatio)> {
+    unsafe {
+        // matches encode_media_file, which initializes COM the same way after this
+        CoInitializeEx(None, COINIT_APARTMENTTHREADED).ok()?;
+        let result = MFStartup(MF_VERSION, MFSTARTUP_FULL).and_then(|_| {
+            let result = read_video_format(path);
+            MFShutdown().ok();
+            result
+        });
+        CoUninitialize();
+
+        result
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_video_format(path: &Path) -> Result<((u32, u32), MFRatio)> {
+    let source_reader = StVideo::create_source_reader(&path.to_string_lossy())?;
+
+    unsafe {
+        let media_type =
+            source_reader.GetNativeMediaType(MF_SOURCE_READER_FIRST_VIDEO_STREAM.0 as u32, 0)?;
+
+        let size_attr = media_type.GetUINT64(&MF_MT_FRAME_SIZE)?;
+        let frame_rate_attr = media_type.GetUINT64(&MF_MT_FRAME_RATE)?;
+
+        Ok((
+            ((size_attr >> 32) as u32, (size_attr & 0xFFFFFFFF) as u32),
+            MFRatio {
+                Numerator: (frame_rate_attr >> 32) as u32,
+                Denominator: (frame_rate_attr & 0xFFFFFFFF) as u32,
+            },
+        ))
+    }
+}
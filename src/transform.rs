@@ -7,9 +7,14 @@ use crate::vertex::get_z_layer;
 
 use crate::editor::{Point, WindowSize};
 
+/// How far in pixels the viewer is from a tilted object, lower is a stronger perspective
+pub const TILT_PERSPECTIVE_DISTANCE: f32 = 1200.0;
+
 pub struct Transform {
     pub position: Vector2<f32>,
     pub rotation: f32, // Rotation angle in radians
+    pub rotation_x: f32, // 3D tilt around the horizontal axis, in radians
+    pub rotation_y: f32, // 3D tilt around the vertical axis, in radians
     pub scale: Vector2<f32>,
     pub uniform_buffer: wgpu::Buffer,
     pub layer: f32,
@@ -32,6 +37,8 @@ impl Transform {
             // position: Vector2::new(x, y),
             position,
             rotation,
+            rotation_x: 0.0,
+            rotation_y: 0.0,
             scale,
             uniform_buffer,
             layer: 0.0,
@@ -54,12 +61,13 @@ impl Transform {
 
         // Create individual transformation matrices
         let translation = Matrix4::from_translation(Vector3::new(x, y, get_z_layer(self.layer)));
+        let tilt = tilt_matrix(self.rotation_x, self.rotation_y);
         let rotation = Matrix4::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), Rad(self.rotation));
         // let scale = Matrix4::from_scale(self.scale.x);
         let scale = Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, 1.0); // Use both x and y scale
 
-        // Combine transformations: translation * rotation * scale
-        translation * rotation * scale
+        // Combine transformations: translation * tilt * rotation * scale
+        translation * tilt * rotation * scale
     }
 
     pub fn update_uniform_buffer(&self, queue: &wgpu::Queue, window_size: &WindowSize) {
@@ -95,6 +103,14 @@ impl Transform {
         self.rotation = degrees * (PI / 180.0);
     }
 
+    pub fn update_rotation_x(&mut self, angle: f32) {
+        self.rotation_x = angle;
+    }
+
+    pub fn update_rotation_y(&mut self, angle: f32) {
+        self.rotation_y = angle;
+    }
+
     pub fn update_scale(&mut self, scale: [f32; 2]) {
         self.scale = Vector2::new(scale[0], scale[1]);
     }
@@ -127,6 +143,26 @@ pub fn matrix4_to_raw_array(matrix: &Matrix4<f32>) -> [[f32; 4]; 4] {
     array
 }
 
+/// Rotates a flat object around its x and y axes with perspective, like a card tilted
+/// toward the viewer. The perspective goes in w, which the vertex shader divides by,
+/// while z is left alone so layers keep their order.
+pub fn tilt_matrix(rotation_x: f32, rotation_y: f32) -> Matrix4<f32> {
+    if rotation_x == 0.0 && rotation_y == 0.0 {
+        return Matrix4::identity();
+    }
+
+    let rotation = Matrix3::from_angle_x(Rad(rotation_x)) * Matrix3::from_angle_y(Rad(rotation_y));
+    let d = TILT_PERSPECTIVE_DISTANCE;
+
+    // columns: x and y are rotated, the depth they're rotated to only feeds the perspective
+    Matrix4::new(
+        rotation.x.x, rotation.x.y, 0.0, -rotation.x.z / d, // 1
+        rotation.y.x, rotation.y.y, 0.0, -rotation.y.z / d, // 2
+        0.0, 0.0, 1.0, 0.0, // 3
+        0.0, 0.0, 0.0, 1.0, // 4
+    )
+}
+
 pub fn angle_between_points(p1: Point, p2: Point) -> f32 {
     let dx = p2.x - p1.x;
     let dy = p2.y - p1.y;
@@ -146,8 +182,7 @@ pub fn degrees_between_points(p1: Point, p2: Point) -> f32 {
     angle_deg
 }
 
-/// For creating temporary group bind groups
-/// Later, when real groups are introduced, this will be replaced
+/// For creating identity group bind groups, used by objects outside of an ObjectGroup
 pub fn create_empty_group_transform(
     device: &wgpu::Device,
     group_bind_group_layout: &wgpu::BindGroupLayout,
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+/// Sounds played as characters are typed out, picked from per character so
+/// repeated keys don't sound identical
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct SoundSet {
+    pub name: String,
+    pub key_paths: Vec<String>,
+    /// Played for spaces and line breaks, which are silent when None
+    pub space_path: Option<String>,
+    /// Played once the last character is revealed
+    pub end_path: Option<String>,
+}
+
+/// When a single character of a typewriter reveal appears
+#[derive(Clone, PartialEq, Debug)]
+pub struct CharacterReveal {
+    pub index: usize, // position of the character within the text
+    pub character: char,
+    pub time_ms: i32,
+}
+
+/// A sound to be mixed in at a point on the sequence timeline
+#[derive(Clone, PartialEq, Debug)]
+pub struct SoundCue {
+    pub path: String,
+    pub time_ms: i32,
+}
+
+/// Times at which each character is revealed, one every `1000 / chars_per_second` ms from `start_ms`
+pub fn reveal_timings(text: &str, start_ms: i32, chars_per_second: f32) -> Vec<CharacterReveal> {
+    let interval_ms = 1000.0 / chars_per_second.max(0.1);
+
+    text.chars()
+        .enumerate()
+        .map(|(index, character)| CharacterReveal {
+            index,
+            character,
+            time_ms: start_ms + (index as f32 * interval_ms).round() as i32,
+        })
+        .collect()
+}
+
+/// Number of characters visible at the given time, for driving the reveal itself
+pub fn visible_characters(reveals: &[CharacterReveal], time_ms: i32) -> usize {
+    reveals.iter().filter(|r| r.time_ms <= time_ms).count()
+}
+
+/// Sound cues for a reveal. Key sounds are picked by character index and seed, so the same
+/// text always produces the same mix.
+pub fn sound_cues(reveals: &[CharacterReveal], sound_set: &SoundSet, seed: u32) -> Vec<SoundCue> {
+    let mut cues: Vec<SoundCue> = reveals
+        .iter()
+        .filter_map(|reveal| {
+            let path = if reveal.character.is_whitespace() {
+                sound_set.space_path.clone()
+            } else if sound_set.key_paths.is_empty() {
+                None
+            } else {
+                let pick = (reveal.index as u32)
+                    .wrapping_mul(2654435761)
+                    .wrapping_add(seed)
+                    .rotate_left(13) as usize;
+                Some(sound_set.key_paths[pick % sound_set.key_paths.len()].clone())
+            };
+
+            path.map(|path| SoundCue {
+                path,
+                time_ms: reveal.time_ms,
+            })
+        })
+        .collect();
+
+    if let (Some(end_path), Some(last)) = (&sound_set.end_path, reveals.last()) {
+        cues.push(SoundCue {
+            path: end_path.clone(),
+            time_ms: last.time_ms,
+        });
+    }
+
+    cues
+}
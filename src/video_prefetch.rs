@@ -0,0 +1,242 @@
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TryRecvError};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use windows::Win32::Media::MediaFoundation::{MFShutdown, MFStartup, MFSTARTUP_FULL, MF_VERSION};
+#[cfg(not(target_arch = "wasm32"))]
+use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_MULTITHREADED};
+
+use crate::st_video::{DecodeError, SourceReader, StVideo};
+
+/// How far ahead of the playhead videos are decoded
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct PrefetchSettings {
+    pub frames_ahead: u32,
+    pub max_memory_mb: u32, // per video, buffering fewer frames when they're large
+}
+
+impl Default for PrefetchSettings {
+    fn default() -> Self {
+        PrefetchSettings {
+            frames_ahead: 8,
+            max_memory_mb: 256,
+        }
+    }
+}
+
+impl PrefetchSettings {
+    /// Frames to keep decoded when each takes this many bytes, at least one
+    pub fn capacity(&self, frame_bytes: usize) -> usize {
+        let memory_frames = self.max_memory_mb as usize * 1024 * 1024 / frame_bytes.max(1);
+
+        (self.frames_ahead as usize).min(memory_frames).max(1)
+    }
+}
+
+enum Prefetched {
+    Frame(Vec<u8>),
+    End,
+    Error(String),
+}
+
+struct Seek {
+    generation: u64,
+    source_frame: u32,
+}
+
+/// Decodes a video on a worker thread with a source reader of its own, staying a bounded
+/// number of frames ahead of playback. Frames come out in order, one per `next_frame`.
+pub struct VideoPrefetcher {
+    frames: Receiver<(u64, Prefetched)>,
+    seeks: Sender<Seek>,
+    generation: u64, // frames decoded before the last seek have an older generation
+    ended: bool,
+    pub capacity: usize,
+}
+
+impl VideoPrefetcher {
+    /// Starts decoding at a source frame, frames being `frame_bytes` long once decoded
+    pub fn start(
+        path: &str,
+        source_frame_rate: f64,
+        frame_bytes: usize,
+        source_frame: u32,
+        settings: PrefetchSettings,
+    ) -> Self {
+        let capacity = settings.capacity(frame_bytes);
+        // the worker blocks once the buffer is full, which keeps it just ahead
+        let (frame_sender, frames) = mpsc::sync_channel(capacity);
+        let (seeks, seek_receiver) = mpsc::channel();
+
+        let path = path.to_string();
+        thread::spawn(move || {
+            decode_ahead(
+                &path,
+                source_frame_rate,
+                source_frame,
+                frame_sender,
+                seek_receiver,
+            );
+        });
+
+        VideoPrefetcher {
+            frames,
+            seeks,
+            generation: 0,
+            ended: false,
+            capacity,
+        }
+    }
+
+    /// Restarts decoding at a source frame, dropping the frames buffered so far
+    pub fn seek(&mut self, source_frame: u32) {
+        self.generation += 1;
+        self.ended = false;
+        self.seeks
+            .send(Seek {
+                generation: self.generation,
+                source_frame,
+            })
+            .ok();
+
+        // frees the worker if it's waiting on a full buffer
+        while self.frames.try_recv().is_ok() {}
+    }
+
+    /// The next frame, waiting for the worker if it has fallen behind. None at the end of
+    /// the video, until the next seek.
+    pub fn next_frame(&mut self) -> Result<Option<Vec<u8>>, String> {
+        if self.ended {
+            return Ok(None);
+        }
+
+        loop {
+            let (generation, prefetched) = self
+                .frames
+                .recv()
+                .map_err(|_| "Video decoder stopped".to_string())?;
+            if generation != self.generation {
+                continue;
+            }
+
+            return match prefetched {
+                Prefetched::Frame(frame_data) => Ok(Some(frame_data)),
+                Prefetched::End => {
+                    self.ended = true;
+                    Ok(None)
+                }
+                Prefetched::Error(e) => {
+                    self.ended = true;
+                    Err(e)
+                }
+            };
+        }
+    }
+}
+
+/// Runs until the prefetcher is dropped
+fn decode_ahead(
+    path: &str,
+    source_frame_rate: f64,
+    source_frame: u32,
+    frames: SyncSender<(u64, Prefetched)>,
+    seeks: Receiver<Seek>,
+) {
+    #[cfg(not(target_arch = "wasm32"))]
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        if let Err(e) = MFStartup(MF_VERSION, MFSTARTUP_FULL) {
+            frames.send((0, Prefetched::Error(e.to_string()))).ok();
+            return;
+        }
+    }
+
+    match StVideo::create_source_reader(path) {
+        Ok(source_reader) => decode_frames(
+            &source_reader,
+            source_frame_rate,
+            Seek {
+                generation: 0,
+                source_frame,
+            },
+            &frames,
+            &seeks,
+        ),
+        Err(e) => {
+            frames.send((0, Prefetched::Error(e.to_string()))).ok();
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    unsafe {
+        MFShutdown().ok();
+        CoUninitialize();
+    }
+}
+
+fn decode_frames(
+    source_reader: &SourceReader,
+    source_frame_rate: f64,
+    first_seek: Seek,
+    frames: &SyncSender<(u64, Prefetched)>,
+    seeks: &Receiver<Seek>,
+) {
+    let mut generation = 0;
+    let mut pending_seek = Some(first_seek);
+
+    loop {
+        // only the latest seek matters
+        loop {
+            match seeks.try_recv() {
+                Ok(seek) => pending_seek = Some(seek),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return,
+            }
+        }
+
+        let prefetched = match pending_seek.take() {
+            Some(seek) => {
+                generation = seek.generation;
+                position_reader(source_reader, source_frame_rate, seek.source_frame)
+                    .err()
+                    .map(|e| Prefetched::Error(e.to_string()))
+            }
+            None => None,
+        };
+        let prefetched =
+            prefetched.unwrap_or_else(|| match StVideo::read_next_frame(source_reader) {
+                Ok(Some(frame_data)) => Prefetched::Frame(frame_data),
+                Ok(None) => Prefetched::End,
+                Err(e) => Prefetched::Error(e.to_string()),
+            });
+
+        let stopped = !matches!(prefetched, Prefetched::Frame(_));
+        if frames.send((generation, prefetched)).is_err() {
+            return;
+        }
+
+        // nothing more to decode until playback seeks
+        if stopped {
+            match seeks.recv() {
+                Ok(seek) => pending_seek = Some(seek),
+                Err(_) => return,
+            }
+        }
+    }
+}
+
+/// Positions the reader so the next frame read is the given source frame
+fn position_reader(
+    source_reader: &SourceReader,
+    source_frame_rate: f64,
+    source_frame: u32,
+) -> Result<(), DecodeError> {
+    if source_frame == 0 {
+        return StVideo::set_reader_position(source_reader, 0);
+    }
+
+    StVideo::seek_reader(source_reader, source_frame_rate, source_frame)?;
+
+    Ok(())
+}
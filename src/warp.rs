@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+
+use crate::vertex::Vertex;
+
+/// Control point offsets are fractions of the object's size, stored in thousandths to keep them as i32
+pub const WARP_PRECISION: f32 = 1000.0;
+
+/// Mesh quads per control grid cell, so the warp bends smoothly between control points
+pub const WARP_SUBDIVISIONS: u32 = 8;
+
+/// An N×M control grid laid over an image or text. Each control point can be offset,
+/// and the mesh in between is interpolated, for wave, flag or bulge distortions.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct MeshWarp {
+    pub columns: u32,
+    pub rows: u32,
+    /// (columns + 1) * (rows + 1) offsets, row by row from the top left
+    pub offsets: Vec<[i32; 2]>,
+}
+
+impl MeshWarp {
+    /// A flat grid with no distortion
+    pub fn new(columns: u32, rows: u32) -> Self {
+        let columns = columns.max(1);
+        let rows = rows.max(1);
+
+        MeshWarp {
+            columns,
+            rows,
+            offsets: vec![[0, 0]; ((columns + 1) * (rows + 1)) as usize],
+        }
+    }
+
+    pub fn control_index(&self, column: u32, row: u32) -> usize {
+        (row * (self.columns + 1) + column) as usize
+    }
+
+    pub fn set_offset(&mut self, column: u32, row: u32, offset: [i32; 2]) -> Result<(), String> {
+        if column > self.columns || row > self.rows {
+            return Err(format!(
+                "Control point ({}, {}) is outside the {}x{} warp grid",
+                column, row, self.columns, self.rows
+            ));
+        }
+
+        // deserialized warps aren't sized by new, so their offsets can come up short
+        let index = self.control_index(column, row);
+        let Some(control_point) = self.offsets.get_mut(index) else {
+            return Err(format!(
+                "Control point ({}, {}) has no offset in the warp grid",
+                column, row
+            ));
+        };
+        *control_point = offset;
+
+        Ok(())
+    }
+
+    fn offset_at(&self, column: u32, row: u32) -> (f32, f32) {
+        let offset = self
+            .offsets
+            .get(self.control_index(column, row))
+            .copied()
+            .unwrap_or([0, 0]);
+
+        (
+            offset[0] as f32 / WARP_PRECISION,
+            offset[1] as f32 / WARP_PRECISION,
+        )
+    }
+
+    /// Bilinearly interpolated displacement at normalized (u, v), as a fraction of the object's size
+    pub fn displacement(&self, u: f32, v: f32) -> (f32, f32) {
+        let grid_x = u.clamp(0.0, 1.0) * self.columns as f32;
+        let grid_y = v.clamp(0.0, 1.0) * self.rows as f32;
+
+        // a 0x0 grid from a saved project displaces nothing, rather than underflowing
+        let column = (grid_x.floor() as u32).min(self.columns.saturating_sub(1));
+        let row = (grid_y.floor() as u32).min(self.rows.saturating_sub(1));
+
+        let tx = grid_x - column as f32;
+        let ty = grid_y - row as f32;
+
+        let top_left = self.offset_at(column, row);
+        let top_right = self.offset_at(column + 1, row);
+        let bottom_left = self.offset_at(column, row + 1);
+        let bottom_right = self.offset_at(column + 1, row + 1);
+
+        let top = (
+            top_left.0 + (top_right.0 - top_left.0) * tx,
+            top_left.1 + (top_right.1 - top_left.1) * tx,
+        );
+        let bottom = (
+            bottom_left.0 + (bottom_right.0 - bottom_left.0) * tx,
+            bottom_left.1 + (bottom_right.1 - bottom_left.1) * tx,
+        );
+
+        (
+            top.0 + (bottom.0 - top.0) * ty,
+            top.1 + (bottom.1 - top.1) * ty,
+        )
+    }
+
+    /// Interpolates between two warps of the same grid size, used when keyframing.
+    /// Grids of different sizes snap to the end warp.
+    pub fn lerp(&self, end: &MeshWarp, progress: f32) -> MeshWarp {
+        if self.columns != end.columns || self.rows != end.rows {
+            return end.clone();
+        }
+
+        MeshWarp {
+            columns: self.columns,
+            rows: self.rows,
+            offsets: self
+                .offsets
+                .iter()
+                .zip(end.offsets.iter())
+                .map(|(a, b)| {
+                    [
+                        a[0] + ((b[0] - a[0]) as f32 * progress) as i32,
+                        a[1] + ((b[1] - a[1]) as f32 * progress) as i32,
+                    ]
+                })
+                .collect(),
+        }
+    }
+
+    /// Vertical sine wave travelling across the object, shift the phase over keyframes to animate it
+    pub fn wave(columns: u32, rows: u32, amplitude: f32, phase: f32) -> Self {
+        let mut warp = MeshWarp::new(columns, rows);
+
+        for row in 0..=warp.rows {
+            for column in 0..=warp.columns {
+                let u = column as f32 / warp.columns as f32;
+                let dy = amplitude * (u * std::f32::consts::TAU + phase).sin();
+                let index = warp.control_index(column, row);
+                warp.offsets[index] = [0, (dy * WARP_PRECISION) as i32];
+            }
+        }
+
+        warp
+    }
+
+    /// Like a wave, but pinned along the left edge as if attached to a flagpole
+    pub fn flag(columns: u32, rows: u32, amplitude: f32, phase: f32) -> Self {
+        let mut warp = MeshWarp::wave(columns, rows, amplitude, phase);
+
+        for row in 0..=warp.rows {
+            for column in 0..=warp.columns {
+                let u = column as f32 / warp.columns as f32;
+                let index = warp.control_index(column, row);
+                warp.offsets[index][1] = (warp.offsets[index][1] as f32 * u) as i32;
+            }
+        }
+
+        warp
+    }
+
+    /// Pushes points away from the center, negative strength pinches inward instead
+    pub fn bulge(columns: u32, rows: u32, strength: f32) -> Self {
+        let mut warp = MeshWarp::new(columns, rows);
+
+        for row in 0..=warp.rows {
+            for column in 0..=warp.columns {
+                let dx = column as f32 / warp.columns as f32 - 0.5;
+                let dy = row as f32 / warp.rows as f32 - 0.5;
+                let distance = (dx * dx + dy * dy).sqrt();
+                let falloff = (1.0 - distance / 0.7071).max(0.0);
+
+                let index = warp.control_index(column, row);
+                warp.offsets[index] = [
+                    (dx * strength * falloff * WARP_PRECISION) as i32,
+                    (dy * strength * falloff * WARP_PRECISION) as i32,
+                ];
+            }
+        }
+
+        warp
+    }
+}
+
+/// Builds the unit quad (-0.5 to 0.5) used by images, subdivided and displaced by the warp.
+/// Without a warp this is the plain 4 vertex quad.
+pub fn build_warped_mesh(warp: Option<&MeshWarp>, color: [f32; 4]) -> (Vec<Vertex>, Vec<u32>) {
+    let (cols, rows) = match warp {
+        Some(warp) => (
+            warp.columns.max(1) * WARP_SUBDIVISIONS,
+            warp.rows.max(1) * WARP_SUBDIVISIONS,
+        ),
+        None => (1, 1),
+    };
+
+    let mut vertices = Vec::with_capacity(((rows + 1) * (cols + 1)) as usize);
+
+    for y in 0..=rows {
+        for x in 0..=cols {
+            let tex_x = x as f32 / cols as f32;
+            let tex_y = y as f32 / rows as f32;
+
+            let (dx, dy) = match warp {
+                Some(warp) => warp.displacement(tex_x, tex_y),
+                None => (0.0, 0.0),
+            };
+
+            vertices.push(Vertex {
+                position: [-0.5 + tex_x + dx, -0.5 + tex_y + dy, 0.0],
+                tex_coords: [tex_x, tex_y],
+                color,
+            });
+        }
+    }
+
+    let mut indices = Vec::with_capacity((rows * cols * 6) as usize);
+    for y in 0..rows {
+        for x in 0..cols {
+            let top_left = y * (cols + 1) + x;
+            let top_right = top_left + 1;
+            let bottom_left = (y + 1) * (cols + 1) + x;
+            let bottom_right = bottom_left + 1;
+
+            indices.push(top_left);
+            indices.push(top_right);
+            indices.push(bottom_right);
+
+            indices.push(top_left);
+            indices.push(bottom_right);
+            indices.push(bottom_left);
+        }
+    }
+
+    (vertices, indices)
+}
+
+/// Displaces already laid out text vertices, which are in pixels around the center of the text box
+pub fn warp_text_vertices(vertices: &mut [Vertex], warp: &MeshWarp, dimensions: (f32, f32)) {
+    if dimensions.0 <= 0.0 || dimensions.1 <= 0.0 {
+        return;
+    }
+
+    for vertex in vertices.iter_mut() {
+        let u = vertex.position[0] / dimensions.0 + 0.5;
+        let v = vertex.position[1] / dimensions.1 + 0.5;
+
+        let (dx, dy) = warp.displacement(u, v);
+
+        vertex.position[0] += dx * dimensions.0;
+        vertex.position[1] += dy * dimensions.1;
+    }
+}
@@ -0,0 +1,148 @@
+// Browser entry points, built for wasm32 only. Files are fetched over HTTP
+// instead of read from disk, and canvases are drawn to through WebGPU.
+
+use std::sync::Arc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{HtmlCanvasElement, Response};
+
+use crate::export::lottie::sequence_to_lottie;
+use crate::gpu_resources::{request_adapter, required_limits, AdapterPreference, GpuResources};
+use crate::saved_state::SavedState;
+
+/// Runs when the module loads, so panics show in the browser console
+#[wasm_bindgen(start)]
+pub fn start() {
+    console_error_panic_hook::set_once();
+}
+
+fn js_error(value: JsValue) -> String {
+    value.as_string().unwrap_or_else(|| format!("{:?}", value))
+}
+
+pub async fn fetch_bytes(url: &str) -> Result<Vec<u8>, String> {
+    let window = web_sys::window().ok_or("Couldn't get browser window")?;
+    let response: Response = JsFuture::from(window.fetch_with_str(url))
+        .await
+        .and_then(|response| response.dyn_into())
+        .map_err(|e| format!("Couldn't fetch {}: {}", url, js_error(e)))?;
+    if !response.ok() {
+        return Err(format!(
+            "Couldn't fetch {}: status {}",
+            url,
+            response.status()
+        ));
+    }
+
+    let buffer = JsFuture::from(response.array_buffer().map_err(js_error)?)
+        .await
+        .map_err(|e| format!("Couldn't read {}: {}", url, js_error(e)))?;
+
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
+
+/// Fetches a project file in any of its formats, upgrading older ones
+pub async fn fetch_project(url: &str) -> Result<SavedState, String> {
+    let content = fetch_bytes(url).await?;
+    let (saved_state, _) =
+        SavedState::decode_with_migrations(&content).map_err(|e| e.to_string())?;
+
+    Ok(saved_state)
+}
+
+/// Fetches and decodes an image without blocking, for projects whose asset paths are URLs
+pub async fn fetch_image(url: &str) -> Result<image::DynamicImage, String> {
+    let content = fetch_bytes(url).await?;
+    image::load_from_memory(&content).map_err(|e| format!("Couldn't decode image {}: {}", url, e))
+}
+
+/// Sets up WebGPU drawing into a canvas, with its surface configured to the canvas's size
+pub async fn canvas_gpu_resources(canvas: HtmlCanvasElement) -> Result<GpuResources, String> {
+    let (width, height) = (canvas.width(), canvas.height());
+
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::BROWSER_WEBGPU,
+        ..Default::default()
+    });
+    let surface = instance
+        .create_surface(wgpu::SurfaceTarget::Canvas(canvas))
+        .map_err(|e| format!("Couldn't create canvas surface: {}", e))?;
+
+    let adapter = request_adapter(&instance, &AdapterPreference::Default, Some(&surface))
+        .await
+        .map_err(|e| format!("{}, the browser may not support WebGPU", e))?;
+    let (device, queue) = adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                label: None,
+                required_limits: required_limits(&adapter),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let config = surface
+        .get_default_config(&adapter, width, height)
+        .ok_or("Canvas surface isn't supported by the gpu adapter")?;
+    surface.configure(&device, &config);
+
+    Ok(GpuResources::with_surface(
+        adapter,
+        device,
+        queue,
+        Arc::new(surface),
+    ))
+}
+
+/// Project details for JavaScript hosts, without exposing engine types
+#[wasm_bindgen]
+pub struct WebProject {
+    saved_state: SavedState,
+}
+
+#[wasm_bindgen]
+impl WebProject {
+    /// Fetches a project, e.g. `await WebProject.load("/projects/demo/project_data.json")`
+    pub async fn load(url: String) -> Result<WebProject, JsValue> {
+        let saved_state = fetch_project(&url).await.map_err(JsValue::from)?;
+
+        Ok(WebProject { saved_state })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.saved_state.id.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn canvas_width(&self) -> u32 {
+        self.saved_state.canvas_size.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn canvas_height(&self) -> u32 {
+        self.saved_state.canvas_size.height
+    }
+
+    /// Sequences and timeline as JSON, for host UIs to list
+    pub fn to_json(&self) -> Result<String, JsValue> {
+        serde_json::to_string(&self.saved_state).map_err(|e| JsValue::from(e.to_string()))
+    }
+
+    /// A sequence's shapes and text as a Lottie animation, for playing without WebGPU
+    pub fn sequence_lottie(&self, sequence_id: &str) -> Result<String, JsValue> {
+        let sequence = self
+            .saved_state
+            .sequences
+            .iter()
+            .find(|s| s.id == sequence_id)
+            .ok_or_else(|| JsValue::from(format!("No sequence found with id {}", sequence_id)))?;
+        let lottie = sequence_to_lottie(sequence, self.saved_state.canvas_size);
+
+        Ok(lottie.to_string())
+    }
+}
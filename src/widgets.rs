@@ -0,0 +1,336 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::animations::{ObjectType, Sequence};
+use crate::blend::BlendMode;
+use crate::camera::Camera3D as Camera;
+use crate::editor::Point;
+use crate::group::{GroupChild, SavedGroupConfig};
+use crate::masking::MaskMode;
+use crate::outline::{points_to_saved, SavedOutline};
+use crate::polygon::{Polygon, SavedPoint, SavedPolygonConfig, SavedStroke};
+use crate::text_due::{SavedTextRendererConfig, TextRenderer};
+
+/// Smallest fill drawn, as an empty bar or gauge would have no area to tessellate
+const MIN_FILL_FRACTION: f32 = 0.001;
+/// Segments in a full gauge sweep, partial sweeps use fewer
+const GAUGE_SEGMENTS: usize = 64;
+/// Gauges leave a gap at the bottom, sweeping clockwise from the lower left
+const GAUGE_START_DEGREES: f32 = 135.0;
+const GAUGE_SWEEP_DEGREES: f32 = 270.0;
+/// Ring thickness as a fraction of the gauge's radius
+const GAUGE_THICKNESS: f32 = 0.2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub enum WidgetKind {
+    ProgressBar,
+    Gauge,
+    Counter, // only a label
+}
+
+/// Makes a group a parametric widget, whose parts are reshaped from the group's Percent
+/// keyframes during playback
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct SavedWidgetConfig {
+    pub kind: WidgetKind,
+    pub fill_id: Option<String>,  // polygon reshaped to the value
+    pub label_id: Option<String>, // text item showing the value
+    pub min: i32,                 // shown at 0 percent
+    pub max: i32,                 // shown at 100 percent
+    pub decimals: u32,
+    pub prefix: String,
+    pub suffix: String, // such as "%"
+}
+
+/// Look and starting value of a new widget
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct WidgetSettings {
+    pub kind: WidgetKind,
+    pub name: String,
+    pub position: SavedPoint, // center, like other objects
+    pub dimensions: (i32, i32),
+    pub layer: i32,
+    pub track_fill: [i32; 4],
+    pub fill: [i32; 4],
+    pub show_label: bool, // counters always have one
+    pub label_color: [i32; 4],
+    pub font_family: String,
+    pub font_size: i32,
+    pub min: i32,
+    pub max: i32,
+    pub decimals: u32,
+    pub prefix: String,
+    pub suffix: String,
+    pub value: i32, // percent
+}
+
+impl Default for WidgetSettings {
+    fn default() -> Self {
+        WidgetSettings {
+            kind: WidgetKind::ProgressBar,
+            name: "Progress".to_string(),
+            position: SavedPoint { x: 400, y: 300 },
+            dimensions: (400, 24),
+            layer: -1,
+            track_fill: [220, 220, 220, 255],
+            fill: [60, 130, 246, 255],
+            show_label: true,
+            label_color: [20, 20, 20, 255],
+            font_family: "Figtree".to_string(),
+            font_size: 28,
+            min: 0,
+            max: 100,
+            decimals: 0,
+            prefix: String::new(),
+            suffix: "%".to_string(),
+            value: 0,
+        }
+    }
+}
+
+/// Builds a widget's track, fill and label under a group, returning a sequence holding
+/// only the new objects so they can be restored and merged. The group has no animation.
+pub fn build_widget(settings: &WidgetSettings, sequence_id: String) -> Sequence {
+    let mut widget = Sequence {
+        id: sequence_id,
+        name: String::new(),
+        background_fill: None,
+        duration_ms: 0,
+        active_polygons: Vec::new(),
+        polygon_motion_paths: Vec::new(),
+        active_text_items: Vec::new(),
+        active_image_items: Vec::new(),
+        active_video_items: Vec::new(),
+        tags: Vec::new(),
+        outlined_text_items: Vec::new(),
+        active_groups: Vec::new(),
+        active_particle_emitters: Vec::new(),
+        camera_animation: None,
+        active_callouts: Vec::new(),
+        active_spotlights: Vec::new(),
+        caption_tracks: Vec::new(),
+        constraints: Vec::new(),
+    };
+
+    let mut config = SavedWidgetConfig {
+        kind: settings.kind,
+        fill_id: None,
+        label_id: None,
+        min: settings.min,
+        max: settings.max,
+        decimals: settings.decimals,
+        prefix: settings.prefix.clone(),
+        suffix: settings.suffix.clone(),
+    };
+    let percent = settings.value as f32;
+
+    let border_radius = match settings.kind {
+        WidgetKind::ProgressBar => settings.dimensions.1 / 2,
+        _ => 0,
+    };
+    let polygon = |name: &str, fill: [i32; 4], layer: i32, outline: Option<SavedOutline>| {
+        SavedPolygonConfig {
+            id: Uuid::new_v4().to_string(),
+            name: format!("{} {}", settings.name, name),
+            fill,
+            dimensions: settings.dimensions,
+            position: settings.position.clone(),
+            border_radius,
+            stroke: SavedStroke {
+                thickness: 0,
+                fill: [0, 0, 0, 0],
+            },
+            layer,
+            tags: Vec::new(),
+            outline,
+            locked: false,
+            hidden: false,
+            opacity: 100,
+            blend_mode: BlendMode::default(),
+            mask_target_id: None,
+            mask_mode: MaskMode::default(),
+        }
+    };
+
+    if settings.kind != WidgetKind::Counter {
+        let outline = |points: Vec<Point>| SavedOutline {
+            points: points_to_saved(&points),
+            contours: Vec::new(),
+            source: None,
+        };
+        // bars are already rectangles
+        let track_outline = match settings.kind {
+            WidgetKind::Gauge => widget_fill_points(settings.kind, 100.0).map(outline),
+            _ => None,
+        };
+        let fill_outline = widget_fill_points(settings.kind, percent).map(outline);
+
+        let track = polygon("Track", settings.track_fill, settings.layer, track_outline);
+        let fill = polygon("Fill", settings.fill, settings.layer - 1, fill_outline);
+        config.fill_id = Some(fill.id.clone());
+        widget.active_polygons.push(track);
+        widget.active_polygons.push(fill);
+    }
+
+    if settings.show_label || settings.kind == WidgetKind::Counter {
+        let font_size = settings.font_size;
+        // sized for the longest value, roughly as fonts vary
+        let longest = widget_label(&config, 0.0)
+            .chars()
+            .count()
+            .max(widget_label(&config, 100.0).chars().count()) as i32;
+        let label_position = match settings.kind {
+            WidgetKind::ProgressBar => SavedPoint {
+                x: settings.position.x,
+                y: settings.position.y - settings.dimensions.1 / 2 - font_size,
+            },
+            _ => settings.position.clone(),
+        };
+
+        let label = SavedTextRendererConfig {
+            id: Uuid::new_v4().to_string(),
+            name: format!("{} Label", settings.name),
+            text: widget_label(&config, percent),
+            font_family: settings.font_family.clone(),
+            font_size,
+            dimensions: ((longest * font_size * 3 / 5) + font_size, font_size * 8 / 5),
+            position: label_position,
+            layer: settings.layer - 2,
+            color: settings.label_color,
+            background_fill: None,
+            tags: Vec::new(),
+            warp: None,
+            locked: false,
+            hidden: false,
+            opacity: 100,
+            blend_mode: BlendMode::default(),
+            mask_target_id: None,
+            mask_mode: MaskMode::default(),
+        };
+        config.label_id = Some(label.id.clone());
+        widget.active_text_items.push(label);
+    }
+
+    let children = widget
+        .active_polygons
+        .iter()
+        .map(|p| GroupChild {
+            object_id: p.id.clone(),
+            object_type: ObjectType::Polygon,
+        })
+        .chain(widget.active_text_items.iter().map(|t| GroupChild {
+            object_id: t.id.clone(),
+            object_type: ObjectType::TextItem,
+        }))
+        .collect();
+
+    widget.active_groups.push(SavedGroupConfig {
+        id: Uuid::new_v4().to_string(),
+        name: settings.name.clone(),
+        children,
+        pivot: settings.position.clone(),
+        position: settings.position.clone(),
+        rotation: 0,
+        scale: 100,
+        tags: Vec::new(),
+        widget: Some(config),
+    });
+
+    widget
+}
+
+/// Reshapes the fill and rewrites the label for a percent. Parts are looked up by id,
+/// so it takes the editor's object lists rather than the editor.
+pub fn update_widget_parts(
+    widget: &SavedWidgetConfig,
+    percent: f32,
+    polygons: &mut [Polygon],
+    text_items: &mut [TextRenderer],
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    model_bind_group_layout: &wgpu::BindGroupLayout,
+    camera: &Camera,
+) {
+    let fill = widget
+        .fill_id
+        .as_ref()
+        .and_then(|id| polygons.iter_mut().find(|p| p.id.to_string() == *id));
+    if let (Some(fill), Some(points)) = (fill, widget_fill_points(widget.kind, percent)) {
+        fill.update_data_from_contours(
+            &camera.window_size,
+            device,
+            queue,
+            model_bind_group_layout,
+            points,
+            Vec::new(),
+            camera,
+        );
+        // rebuilt vertices take the plain fill color
+        fill.update_opacity(queue, fill.animated_opacity);
+    }
+
+    let label = widget
+        .label_id
+        .as_ref()
+        .and_then(|id| text_items.iter_mut().find(|t| t.id.to_string() == *id));
+    if let Some(label) = label {
+        let text = widget_label(widget, percent);
+        if label.text != text {
+            label.update_text(device, queue, text);
+        }
+    }
+}
+
+/// The label at a percent, with the value that far from min to max
+pub fn widget_label(config: &SavedWidgetConfig, percent: f32) -> String {
+    let value = config.min as f32 + (config.max - config.min) as f32 * percent / 100.0;
+
+    format!(
+        "{}{:.*}{}",
+        config.prefix, config.decimals as usize, value, config.suffix
+    )
+}
+
+/// Normalized points of the fill at a percent, None for widgets without one
+pub fn widget_fill_points(kind: WidgetKind, percent: f32) -> Option<Vec<Point>> {
+    let fraction = (percent / 100.0).clamp(MIN_FILL_FRACTION, 1.0);
+
+    match kind {
+        WidgetKind::ProgressBar => Some(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point {
+                x: fraction,
+                y: 0.0,
+            },
+            Point {
+                x: fraction,
+                y: 1.0,
+            },
+            Point { x: 0.0, y: 1.0 },
+        ]),
+        WidgetKind::Gauge => Some(gauge_points(fraction)),
+        WidgetKind::Counter => None,
+    }
+}
+
+/// A ring segment, outer edge clockwise then inner edge back
+fn gauge_points(fraction: f32) -> Vec<Point> {
+    let segments = ((GAUGE_SEGMENTS as f32 * fraction).ceil() as usize).max(2);
+    let start = GAUGE_START_DEGREES.to_radians();
+    let sweep = (GAUGE_SWEEP_DEGREES * fraction).to_radians();
+    let outer_radius = 0.5;
+    let inner_radius = outer_radius * (1.0 - GAUGE_THICKNESS);
+
+    let arc = |radius: f32, step: usize| {
+        let angle = start + sweep * step as f32 / segments as f32;
+        Point {
+            x: 0.5 + radius * angle.cos(),
+            y: 0.5 + radius * angle.sin(),
+        }
+    };
+
+    let mut points: Vec<Point> = (0..=segments).map(|step| arc(outer_radius, step)).collect();
+    points.extend((0..=segments).rev().map(|step| arc(inner_radius, step)));
+
+    points
+}